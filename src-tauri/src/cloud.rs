@@ -843,7 +843,7 @@ fn derive_encryption_key() -> Result<[u8; 32], CloudError> {
 }
 
 /// 加密密码
-fn encrypt_password(password: &str) -> Result<(String, String), CloudError> {
+pub(crate) fn encrypt_password(password: &str) -> Result<(String, String), CloudError> {
     let key = derive_encryption_key()?;
     let cipher = Aes256Gcm::new_from_slice(&key)
         .map_err(|e| CloudError::Encryption(format!("创建加密器失败: {}", e)))?;
@@ -869,7 +869,7 @@ fn encrypt_password(password: &str) -> Result<(String, String), CloudError> {
 }
 
 /// 解密密码
-fn decrypt_password(encrypted_base64: &str, nonce_base64: &str) -> Result<String, CloudError> {
+pub(crate) fn decrypt_password(encrypted_base64: &str, nonce_base64: &str) -> Result<String, CloudError> {
     let key = derive_encryption_key()?;
     let cipher = Aes256Gcm::new_from_slice(&key)
         .map_err(|e| CloudError::Encryption(format!("创建解密器失败：{}", e)))?;
@@ -0,0 +1,247 @@
+//! UDP 广播发现模块
+//!
+//! 网络屏蔽多播时的后备发现方式：定期向固定端口广播携带设备名称、端口和指纹的信标，
+//! 同时监听其他设备的信标，结果合并到 mDNS 使用的同一设备列表
+
+use crate::discovery::{upsert_peer, PeerStore};
+use crate::error::{DiscoveryError, DiscoveryResult};
+use crate::models::{DeviceType, DiscoverySource, PeerDiscoveryEvent, PeerInfo, PeerStatus};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::{OnceLock, RwLock};
+use std::time::Duration;
+use tokio::sync::{broadcast, Mutex};
+use tokio::task::JoinHandle;
+
+/// 信标端口
+pub const BROADCAST_DISCOVERY_PORT: u16 = 53318;
+
+/// 信标发送间隔
+const BEACON_INTERVAL: Duration = Duration::from_secs(3);
+
+/// 信标标识，用于过滤同端口上的其他流量
+const BEACON_MAGIC: &str = "puresend-beacon";
+
+/// 信标协议版本
+const BEACON_VERSION: u8 = 1;
+
+/// 接收缓冲区大小
+const BEACON_RECV_BUFFER_SIZE: usize = 2048;
+
+/// 是否启用广播发现
+static BROADCAST_DISCOVERY_ENABLED: OnceLock<RwLock<bool>> = OnceLock::new();
+
+fn get_broadcast_discovery_setting() -> &'static RwLock<bool> {
+    BROADCAST_DISCOVERY_ENABLED.get_or_init(|| RwLock::new(true))
+}
+
+/// 获取是否启用广播发现
+pub fn is_broadcast_discovery_enabled() -> bool {
+    get_broadcast_discovery_setting()
+        .read()
+        .map(|v| *v)
+        .unwrap_or(true)
+}
+
+/// 设置是否启用广播发现（仅修改设置，不启停信标）
+pub fn set_broadcast_discovery_enabled_internal(enabled: bool) {
+    if let Ok(mut value) = get_broadcast_discovery_setting().write() {
+        *value = enabled;
+    }
+}
+
+/// 信标消息
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct BeaconMessage {
+    /// 信标标识
+    magic: String,
+    /// 协议版本
+    version: u8,
+    /// 设备名称
+    device_name: String,
+    /// 监听端口
+    port: u16,
+    /// 设备类型
+    device_type: DeviceType,
+    /// 设备指纹
+    fingerprint: String,
+}
+
+impl BeaconMessage {
+    fn new(device_name: &str, port: u16) -> Self {
+        Self {
+            magic: BEACON_MAGIC.to_string(),
+            version: BEACON_VERSION,
+            device_name: device_name.to_string(),
+            port,
+            device_type: DeviceType::Desktop,
+            fingerprint: crate::transfer::current_device_identity().fingerprint(),
+        }
+    }
+
+    /// 解析信标，非本协议或来自本机的消息返回 None
+    fn parse(bytes: &[u8], own_fingerprint: &str) -> Option<Self> {
+        let message = serde_json::from_slice::<Self>(bytes).ok()?;
+        if message.magic != BEACON_MAGIC || message.fingerprint == own_fingerprint {
+            return None;
+        }
+        Some(message)
+    }
+
+    fn into_peer(self, ip: IpAddr) -> PeerInfo {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+
+        PeerInfo {
+            id: format!("{}-{}", self.device_name, ip),
+            name: self.device_name,
+            ip: ip.to_string(),
+            port: self.port,
+            device_type: self.device_type,
+            discovered_at: now,
+            last_seen: now,
+            status: PeerStatus::Available,
+            source: DiscoverySource::Broadcast,
+            fingerprint: Some(self.fingerprint),
+            pinned: false,
+        }
+    }
+}
+
+/// UDP 广播信标
+pub struct BroadcastBeacon {
+    /// 本机设备名称
+    device_name: String,
+    /// 本机监听端口
+    listen_port: u16,
+    /// 与 mDNS 共享的设备列表
+    peers: PeerStore,
+    /// 与 mDNS 共享的事件发送器
+    event_sender: broadcast::Sender<PeerDiscoveryEvent>,
+    /// 运行中的发送/监听任务
+    tasks: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl BroadcastBeacon {
+    /// 创建信标，设备列表和事件发送器与 mDNS 发现共享
+    pub fn new(
+        device_name: String,
+        listen_port: u16,
+        peers: PeerStore,
+        event_sender: broadcast::Sender<PeerDiscoveryEvent>,
+    ) -> Self {
+        Self {
+            device_name,
+            listen_port,
+            peers,
+            event_sender,
+            tasks: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// 启动信标发送和监听
+    ///
+    /// 信标端口被占用时只发送不监听，对端仍可发现本机
+    pub async fn start(&self) -> DiscoveryResult<()> {
+        let mut tasks = self.tasks.lock().await;
+        if !tasks.is_empty() {
+            return Ok(());
+        }
+
+        let send_socket = tokio::net::UdpSocket::bind("0.0.0.0:0")
+            .await
+            .map_err(|e| DiscoveryError::Internal(format!("广播套接字创建失败: {}", e)))?;
+        send_socket
+            .set_broadcast(true)
+            .map_err(|e| DiscoveryError::Internal(format!("无法启用广播: {}", e)))?;
+        tasks.push(self.spawn_send_task(send_socket));
+
+        match tokio::net::UdpSocket::bind(("0.0.0.0", BROADCAST_DISCOVERY_PORT)).await {
+            Ok(listen_socket) => tasks.push(self.spawn_listen_task(listen_socket)),
+            Err(e) => {
+                tracing::warn!(error = %e, port = BROADCAST_DISCOVERY_PORT, "广播发现端口不可用，仅发送信标")
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 停止信标
+    pub async fn stop(&self) {
+        for task in self.tasks.lock().await.drain(..) {
+            task.abort();
+        }
+    }
+
+    fn spawn_send_task(&self, socket: tokio::net::UdpSocket) -> JoinHandle<()> {
+        let device_name = self.device_name.clone();
+        let listen_port = self.listen_port;
+
+        tokio::spawn(async move {
+            let broadcast_addr =
+                SocketAddr::new(IpAddr::V4(Ipv4Addr::BROADCAST), BROADCAST_DISCOVERY_PORT);
+
+            loop {
+                // 每次重新生成，设备密钥轮换后信标随之更新
+                if let Ok(bytes) =
+                    serde_json::to_vec(&BeaconMessage::new(&device_name, listen_port))
+                {
+                    // 发送失败通常是网络暂不可用，下个周期重试
+                    let _ = socket.send_to(&bytes, broadcast_addr).await;
+                }
+                tokio::time::sleep(BEACON_INTERVAL).await;
+            }
+        })
+    }
+
+    fn spawn_listen_task(&self, socket: tokio::net::UdpSocket) -> JoinHandle<()> {
+        let peers = self.peers.clone();
+        let event_sender = self.event_sender.clone();
+
+        tokio::spawn(async move {
+            let mut buf = vec![0u8; BEACON_RECV_BUFFER_SIZE];
+
+            loop {
+                let (len, addr) = match socket.recv_from(&mut buf).await {
+                    Ok(received) => received,
+                    Err(_) => continue,
+                };
+                let own_fingerprint = crate::transfer::current_device_identity().fingerprint();
+                if let Some(message) = BeaconMessage::parse(&buf[..len], &own_fingerprint) {
+                    upsert_peer(&peers, &event_sender, message.into_peer(addr.ip())).await;
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_beacon() {
+        let message = BeaconMessage {
+            magic: BEACON_MAGIC.to_string(),
+            version: BEACON_VERSION,
+            device_name: "Laptop".to_string(),
+            port: 8080,
+            device_type: DeviceType::Desktop,
+            fingerprint: "AA:BB".to_string(),
+        };
+        let bytes = serde_json::to_vec(&message).unwrap();
+
+        let parsed = BeaconMessage::parse(&bytes, "CC:DD").unwrap();
+        let peer = parsed.into_peer(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 9)));
+        assert_eq!(peer.source, DiscoverySource::Broadcast);
+        assert_eq!(peer.fingerprint.as_deref(), Some("AA:BB"));
+        assert_eq!(peer.port, 8080);
+
+        // 本机信标被忽略
+        assert!(BeaconMessage::parse(&bytes, "AA:BB").is_none());
+        // 非本协议消息被忽略
+        let foreign = br#"{"device_name":"x","port":1,"device_type":"desktop"}"#;
+        assert!(BeaconMessage::parse(foreign, "CC:DD").is_none());
+    }
+}
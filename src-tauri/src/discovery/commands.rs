@@ -1,22 +1,38 @@
 //! 设备发现相关 Tauri 命令
 
 use crate::discovery::DiscoveryManager;
-use crate::models::PeerInfo;
+use crate::models::{PeerEventType, PeerInfo};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter};
+use tauri_plugin_store::StoreExt;
 use tokio::sync::Mutex;
 
+/// 收藏设备存储文件名
+const PINNED_PEERS_STORE_FILE: &str = "pinned-peers.json";
+/// 收藏设备存储键名
+const PINNED_PEERS_STORE_KEY: &str = "peers";
+
+/// 设备过期时间下限（秒），需大于通告间隔
+const MIN_PEER_TTL_SECS: u64 = 5;
+
+/// 设备过期时间上限（秒）
+const MAX_PEER_TTL_SECS: u64 = 3600;
+
 /// 设备发现状态（用于 Tauri 状态管理）
 ///
 /// 包装 DiscoveryManager，支持延迟初始化（init_discovery 时创建）。
 pub struct DiscoveryState {
     pub manager: Arc<Mutex<Option<Arc<DiscoveryManager>>>>,
+    /// 进行中的子网扫描的取消标志
+    scan_cancel: Arc<Mutex<Option<Arc<AtomicBool>>>>,
 }
 
 impl Default for DiscoveryState {
     fn default() -> Self {
         Self {
             manager: Arc::new(Mutex::new(None)),
+            scan_cancel: Arc::new(Mutex::new(None)),
         }
     }
 }
@@ -44,12 +60,21 @@ pub async fn init_discovery(
         _ => Arc::new(DiscoveryManager::default()),
     };
 
+    // 启动前载入收藏的设备，载入失败不影响发现服务
+    match load_pinned_peers(&app) {
+        Ok(peers) => manager.preload_pinned(peers).await,
+        Err(e) => tracing::warn!(error = %e, "载入收藏设备失败"),
+    }
+
     manager.start().await.map_err(|e| e.to_string())?;
 
-    // 订阅设备发现事件并发送到前端
+    // 订阅设备发现事件并发送到前端，过期移除的设备另外发送 peer-removed 事件
     let mut receiver = manager.subscribe();
     tauri::async_runtime::spawn(async move {
         while let Ok(event) = receiver.recv().await {
+            if event.event_type == PeerEventType::Offline {
+                let _ = app.emit("peer-removed", &event.peer);
+            }
             let _ = app.emit("peer-discovery", event);
         }
     });
@@ -142,4 +167,164 @@ pub async fn restart_discovery(
         manager.restart().await.map_err(|e| e.to_string())?;
     }
     Ok(())
-}
\ No newline at end of file
+}
+
+/// 扫描本机所在 /24 网段中的 PureSend 设备
+///
+/// mDNS 被网络屏蔽时的后备方式，对每个主机的传输端口尝试连接并握手，
+/// 响应的设备合并到设备列表（发现来源为 scan）。`port` 默认为本机通告的传输端口；
+/// 扫描可通过 `cancel_subnet_scan` 取消，取消后返回已发现的设备
+#[tauri::command]
+pub async fn scan_subnet_for_peers(
+    state: tauri::State<'_, DiscoveryState>,
+    port: Option<u16>,
+) -> Result<Vec<PeerInfo>, String> {
+    let manager = state
+        .manager
+        .lock()
+        .await
+        .clone()
+        .ok_or_else(|| "Discovery service not initialized".to_string())?;
+    let port = port.unwrap_or_else(|| manager.listen_port());
+    if port == 0 {
+        return Err("未指定扫描端口".to_string());
+    }
+
+    let cancelled = Arc::new(AtomicBool::new(false));
+    {
+        let mut scan_cancel = state.scan_cancel.lock().await;
+        if scan_cancel.is_some() {
+            return Err("子网扫描已在进行中".to_string());
+        }
+        *scan_cancel = Some(cancelled.clone());
+    }
+
+    let peers = crate::discovery::scan_subnet(port, &cancelled).await;
+    *state.scan_cancel.lock().await = None;
+    Ok(manager.merge_scanned(peers).await)
+}
+
+/// 取消进行中的子网扫描，返回是否有扫描在进行
+#[tauri::command]
+pub async fn cancel_subnet_scan(state: tauri::State<'_, DiscoveryState>) -> Result<bool, String> {
+    match state.scan_cancel.lock().await.as_ref() {
+        Some(cancelled) => {
+            cancelled.store(true, Ordering::Relaxed);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// 收藏设备
+///
+/// 收藏的设备在设备列表中排在前面，重启后保留，且不会因过期被移除
+#[tauri::command]
+pub async fn pin_peer(
+    app: AppHandle,
+    state: tauri::State<'_, DiscoveryState>,
+    peer_id: String,
+) -> Result<PeerInfo, String> {
+    set_peer_pinned(&app, &state, &peer_id, true).await
+}
+
+/// 取消收藏设备
+#[tauri::command]
+pub async fn unpin_peer(
+    app: AppHandle,
+    state: tauri::State<'_, DiscoveryState>,
+    peer_id: String,
+) -> Result<PeerInfo, String> {
+    set_peer_pinned(&app, &state, &peer_id, false).await
+}
+
+async fn set_peer_pinned(
+    app: &AppHandle,
+    state: &DiscoveryState,
+    peer_id: &str,
+    pinned: bool,
+) -> Result<PeerInfo, String> {
+    let manager_guard = state.manager.lock().await;
+    let manager = manager_guard
+        .as_ref()
+        .ok_or_else(|| "Discovery service not initialized".to_string())?;
+    let peer = manager
+        .set_pinned(peer_id, pinned)
+        .await
+        .ok_or_else(|| "Peer not found".to_string())?;
+    save_pinned_peers(app, &manager.pinned_peers().await)?;
+    Ok(peer)
+}
+
+/// 从 Tauri Store 加载收藏的设备
+fn load_pinned_peers(app: &AppHandle) -> Result<Vec<PeerInfo>, String> {
+    let store = app
+        .store(PINNED_PEERS_STORE_FILE)
+        .map_err(|e| format!("打开存储失败：{}", e))?;
+
+    match store.get(PINNED_PEERS_STORE_KEY) {
+        Some(value) => {
+            serde_json::from_value(value).map_err(|e| format!("解析存储数据失败：{}", e))
+        }
+        None => Ok(Vec::new()),
+    }
+}
+
+/// 保存收藏的设备到 Tauri Store
+fn save_pinned_peers(app: &AppHandle, peers: &[PeerInfo]) -> Result<(), String> {
+    let store = app
+        .store(PINNED_PEERS_STORE_FILE)
+        .map_err(|e| format!("打开存储失败：{}", e))?;
+
+    let value = serde_json::to_value(peers).map_err(|e| format!("序列化数据失败：{}", e))?;
+    store.set(PINNED_PEERS_STORE_KEY, value);
+    store.save().map_err(|e| format!("保存存储失败：{}", e))
+}
+
+/// 获取设备过期时间（秒）
+#[tauri::command]
+pub async fn get_peer_ttl() -> Result<u64, String> {
+    Ok(crate::discovery::peer_ttl().as_secs())
+}
+
+/// 设置设备过期时间（秒）
+///
+/// 超过该时间未再通告的设备从列表中移除，手动添加的设备不受影响
+#[tauri::command]
+pub async fn set_peer_ttl(seconds: u64) -> Result<(), String> {
+    validate_peer_ttl(seconds)?;
+    crate::discovery::set_peer_ttl_internal(seconds);
+    Ok(())
+}
+
+fn validate_peer_ttl(seconds: u64) -> Result<(), String> {
+    if !(MIN_PEER_TTL_SECS..=MAX_PEER_TTL_SECS).contains(&seconds) {
+        return Err(format!(
+            "无效的设备过期时间: {}，范围为 {}-{} 秒",
+            seconds, MIN_PEER_TTL_SECS, MAX_PEER_TTL_SECS
+        ));
+    }
+    Ok(())
+}
+
+/// 获取是否启用 UDP 广播发现
+#[tauri::command]
+pub async fn get_broadcast_discovery_enabled() -> Result<bool, String> {
+    Ok(crate::discovery::is_broadcast_discovery_enabled())
+}
+
+/// 设置是否启用 UDP 广播发现
+///
+/// 作为 mDNS 被屏蔽时的后备，发现服务运行中时立即启停信标
+#[tauri::command]
+pub async fn set_broadcast_discovery_enabled(
+    state: tauri::State<'_, DiscoveryState>,
+    enabled: bool,
+) -> Result<(), String> {
+    crate::discovery::set_broadcast_discovery_enabled_internal(enabled);
+    let manager_guard = state.manager.lock().await;
+    if let Some(manager) = manager_guard.as_ref() {
+        manager.set_broadcast_enabled(enabled).await;
+    }
+    Ok(())
+}
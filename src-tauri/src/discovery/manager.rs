@@ -2,7 +2,7 @@
 //!
 //! 统一管理设备发现和连接
 
-use crate::discovery::MdnsDiscovery;
+use crate::discovery::{is_broadcast_discovery_enabled, peer_ttl, BroadcastBeacon, MdnsDiscovery};
 use crate::error::DiscoveryResult;
 use crate::models::{PeerDiscoveryEvent, PeerInfo};
 use std::sync::Arc;
@@ -12,6 +12,8 @@ use tokio::sync::{broadcast, Mutex};
 pub struct DiscoveryManager {
     /// mDNS 发现服务
     mdns: Arc<MdnsDiscovery>,
+    /// UDP 广播信标（多播不可用时的后备）
+    beacon: Arc<BroadcastBeacon>,
     /// 是否已启动
     started: Arc<Mutex<bool>>,
 }
@@ -19,20 +21,57 @@ pub struct DiscoveryManager {
 impl DiscoveryManager {
     /// 创建新的发现管理器
     pub fn new(device_name: String, listen_port: u16) -> Self {
-        Self {
-            mdns: Arc::new(MdnsDiscovery::new(device_name, listen_port)),
-            started: Arc::new(Mutex::new(false)),
-        }
+        Self::with_mdns(
+            MdnsDiscovery::new(device_name.clone(), listen_port),
+            device_name,
+            listen_port,
+        )
     }
 
     /// 使用默认配置创建发现管理器
     pub fn default_manager() -> Self {
+        let mdns = MdnsDiscovery::default();
+        let device_name = mdns.device_name().to_string();
+        let listen_port = mdns.listen_port();
+        Self::with_mdns(mdns, device_name, listen_port)
+    }
+
+    fn with_mdns(mdns: MdnsDiscovery, device_name: String, listen_port: u16) -> Self {
+        let beacon = BroadcastBeacon::new(
+            device_name,
+            listen_port,
+            mdns.peer_store(),
+            mdns.event_sender(),
+        );
         Self {
-            mdns: Arc::new(MdnsDiscovery::default()),
+            mdns: Arc::new(mdns),
+            beacon: Arc::new(beacon),
             started: Arc::new(Mutex::new(false)),
         }
     }
 
+    /// 按设置启动广播信标，失败不影响 mDNS 发现
+    async fn start_beacon_if_enabled(&self) {
+        if !is_broadcast_discovery_enabled() {
+            return;
+        }
+        if let Err(e) = self.beacon.start().await {
+            tracing::warn!(error = %e, "广播发现启动失败");
+        }
+    }
+
+    /// 启用或停用广播发现，发现服务运行中时立即生效
+    pub async fn set_broadcast_enabled(&self, enabled: bool) {
+        if !*self.started.lock().await {
+            return;
+        }
+        if enabled {
+            self.start_beacon_if_enabled().await;
+        } else {
+            self.beacon.stop().await;
+        }
+    }
+
     /// 启动发现服务
     pub async fn start(&self) -> DiscoveryResult<()> {
         let started = self.started.lock().await;
@@ -42,6 +81,7 @@ impl DiscoveryManager {
         drop(started);
 
         self.mdns.start().await?;
+        self.start_beacon_if_enabled().await;
         *self.started.lock().await = true;
 
         Ok(())
@@ -55,6 +95,7 @@ impl DiscoveryManager {
         }
         drop(started);
 
+        self.beacon.stop().await;
         self.mdns.stop().await?;
         *self.started.lock().await = false;
 
@@ -70,6 +111,7 @@ impl DiscoveryManager {
 
         if *started {
             drop(started);
+            self.beacon.stop().await;
             self.mdns.stop().await?;
             started = self.started.lock().await;
             *started = false;
@@ -77,11 +119,22 @@ impl DiscoveryManager {
         }
 
         self.mdns.start().await?;
+        self.start_beacon_if_enabled().await;
         *self.started.lock().await = true;
 
         Ok(())
     }
 
+    /// 本机通告的传输端口
+    pub fn listen_port(&self) -> u16 {
+        self.mdns.listen_port()
+    }
+
+    /// 合并子网扫描发现的设备
+    pub async fn merge_scanned(&self, peers: Vec<PeerInfo>) -> Vec<PeerInfo> {
+        self.mdns.merge_scanned(peers).await
+    }
+
     /// 获取所有已发现的设备
     pub async fn get_peers(&self) -> Vec<PeerInfo> {
         self.mdns.get_peers().await
@@ -102,12 +155,27 @@ impl DiscoveryManager {
         self.mdns.add_peer_manual(ip, port).await
     }
 
-    /// 检查设备是否在线
+    /// 设置设备的收藏状态，设备不存在时返回 None
+    pub async fn set_pinned(&self, id: &str, pinned: bool) -> Option<PeerInfo> {
+        self.mdns.set_pinned(id, pinned).await
+    }
+
+    /// 获取收藏的设备
+    pub async fn pinned_peers(&self) -> Vec<PeerInfo> {
+        self.mdns.pinned_peers().await
+    }
+
+    /// 预加载已保存的收藏设备
+    pub async fn preload_pinned(&self, peers: Vec<PeerInfo>) {
+        self.mdns.preload_pinned(peers).await
+    }
+
+    /// 检查设备是否在线（按最后一次收到通告的时间判断）
     pub async fn is_peer_online(&self, id: &str) -> bool {
         self.mdns
             .get_peer(id)
             .await
-            .map(|p| p.is_online())
+            .map(|p| p.is_online(peer_ttl()))
             .unwrap_or(false)
     }
 
@@ -117,7 +185,7 @@ impl DiscoveryManager {
             .get_peers()
             .await
             .iter()
-            .filter(|p| p.is_online())
+            .filter(|p| p.is_online(peer_ttl()))
             .count()
     }
 }
@@ -144,4 +212,4 @@ mod tests {
         let peers = manager.get_peers().await;
         assert!(peers.is_empty());
     }
-}
\ No newline at end of file
+}
@@ -3,18 +3,20 @@
 //! 使用多播 DNS 在本地网络中发现 PureSend 设备
 
 use crate::error::DiscoveryResult;
-use crate::models::{DeviceType, PeerDiscoveryEvent, PeerEventType, PeerInfo, PeerStatus};
+use crate::models::{
+    DeviceType, DiscoverySource, PeerDiscoveryEvent, PeerEventType, PeerInfo, PeerStatus,
+};
 use std::collections::HashMap;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock, RwLock};
 use std::time::Duration;
 use tokio::sync::{broadcast, Mutex};
 
 /// mDNS 端口
 pub const MDNS_PORT: u16 = 5353;
 
-/// 设备过期时间（10秒无响应视为离线）
-pub const PEER_EXPIRE_TIMEOUT: Duration = Duration::from_secs(10);
+/// 默认设备过期时间（秒），超过该时间未收到通告的设备视为离线并移除
+pub const DEFAULT_PEER_TTL_SECS: u64 = 30;
 
 /// 广播间隔时间
 const BROADCAST_INTERVAL: Duration = Duration::from_secs(3);
@@ -25,6 +27,81 @@ const CLEANUP_INTERVAL: Duration = Duration::from_secs(5);
 /// UDP 接收缓冲区大小
 const UDP_RECV_BUFFER_SIZE: usize = 4096;
 
+/// 已发现设备列表（设备 ID -> 设备信息），mDNS 与广播信标共享
+pub type PeerStore = Arc<Mutex<HashMap<String, PeerInfo>>>;
+
+/// 设备过期时间（秒）
+static PEER_TTL_SECS: OnceLock<RwLock<u64>> = OnceLock::new();
+
+fn get_peer_ttl_lock() -> &'static RwLock<u64> {
+    PEER_TTL_SECS.get_or_init(|| RwLock::new(DEFAULT_PEER_TTL_SECS))
+}
+
+/// 获取设备过期时间
+pub fn peer_ttl() -> Duration {
+    let secs = get_peer_ttl_lock()
+        .read()
+        .map(|v| *v)
+        .unwrap_or(DEFAULT_PEER_TTL_SECS);
+    Duration::from_secs(secs)
+}
+
+/// 设置设备过期时间（秒），调用方需先校验范围
+pub fn set_peer_ttl_internal(secs: u64) {
+    if let Ok(mut value) = get_peer_ttl_lock().write() {
+        *value = secs;
+    }
+}
+
+/// 超过 `ttl` 未收到通告的设备 ID（手动添加的设备不会过期）
+fn expired_peer_ids(peers: &HashMap<String, PeerInfo>, now: u64, ttl: Duration) -> Vec<String> {
+    peers
+        .iter()
+        .filter(|(_, peer)| peer.is_expired(now, ttl))
+        .map(|(id, _)| id.clone())
+        .collect()
+}
+
+/// 是否为同一设备：双方都有指纹时比较指纹，否则比较地址和端口
+fn is_same_device(a: &PeerInfo, b: &PeerInfo) -> bool {
+    match (&a.fingerprint, &b.fingerprint) {
+        (Some(x), Some(y)) => x == y,
+        _ => a.ip == b.ip && a.port == b.port,
+    }
+}
+
+/// 合并设备到列表并发送事件
+///
+/// 与已有设备重复时沿用其 ID、首次发现时间、发现来源和收藏状态，其余信息以新数据为准
+pub(crate) async fn upsert_peer(
+    peers: &PeerStore,
+    event_sender: &broadcast::Sender<PeerDiscoveryEvent>,
+    mut peer: PeerInfo,
+) {
+    let mut peers_guard = peers.lock().await;
+    let existing_id = peers_guard
+        .values()
+        .find(|p| p.id == peer.id || is_same_device(p, &peer))
+        .map(|p| p.id.clone());
+
+    let event_type = match existing_id.and_then(|id| peers_guard.remove(&id)) {
+        Some(existing) => {
+            peer.id = existing.id;
+            peer.discovered_at = existing.discovered_at;
+            peer.source = existing.source;
+            peer.fingerprint = peer.fingerprint.or(existing.fingerprint);
+            peer.pinned = existing.pinned;
+            PeerEventType::Updated
+        }
+        None => PeerEventType::Discovered,
+    };
+
+    peers_guard.insert(peer.id.clone(), peer.clone());
+    drop(peers_guard);
+
+    let _ = event_sender.send(PeerDiscoveryEvent { event_type, peer });
+}
+
 /// mDNS 服务发现
 pub struct MdnsDiscovery {
     /// 本机设备名称
@@ -32,7 +109,7 @@ pub struct MdnsDiscovery {
     /// 本机监听端口
     listen_port: u16,
     /// 已发现的设备列表
-    peers: Arc<Mutex<HashMap<String, PeerInfo>>>,
+    peers: PeerStore,
     /// 事件广播发送器
     event_sender: broadcast::Sender<PeerDiscoveryEvent>,
     /// 是否正在运行
@@ -52,6 +129,26 @@ impl MdnsDiscovery {
         }
     }
 
+    /// 本机设备名称
+    pub fn device_name(&self) -> &str {
+        &self.device_name
+    }
+
+    /// 本机监听端口
+    pub fn listen_port(&self) -> u16 {
+        self.listen_port
+    }
+
+    /// 获取共享的设备列表
+    pub fn peer_store(&self) -> PeerStore {
+        self.peers.clone()
+    }
+
+    /// 获取事件发送器
+    pub fn event_sender(&self) -> broadcast::Sender<PeerDiscoveryEvent> {
+        self.event_sender.clone()
+    }
+
     /// 获取事件接收器
     pub fn subscribe(&self) -> broadcast::Receiver<PeerDiscoveryEvent> {
         self.event_sender.subscribe()
@@ -77,11 +174,11 @@ impl MdnsDiscovery {
         Ok(())
     }
 
-    /// 停止发现服务
+    /// 停止发现服务，清空设备列表（保留收藏的设备）
     pub async fn stop(&self) -> DiscoveryResult<()> {
         let mut running = self.running.lock().await;
         *running = false;
-        self.peers.lock().await.clear();
+        self.peers.lock().await.retain(|_, peer| peer.pinned);
         Ok(())
     }
 
@@ -172,19 +269,12 @@ impl MdnsDiscovery {
                                 discovered_at: now,
                                 last_seen: now,
                                 status: PeerStatus::Available,
+                                source: DiscoverySource::Mdns,
+                                fingerprint: None,
+                                pinned: false,
                             };
 
-                            let mut peers_guard = peers.lock().await;
-                            let event_type = if peers_guard.contains_key(&peer.id) {
-                                PeerEventType::Updated
-                            } else {
-                                PeerEventType::Discovered
-                            };
-
-                            peers_guard.insert(peer.id.clone(), peer.clone());
-                            drop(peers_guard);
-
-                            let _ = event_sender.send(PeerDiscoveryEvent { event_type, peer });
+                            upsert_peer(&peers, &event_sender, peer).await;
                         }
                     }
                     Err(_) => continue,
@@ -193,7 +283,7 @@ impl MdnsDiscovery {
         });
     }
 
-    /// 启动清理任务（清理过期设备）
+    /// 启动清理任务（移除超过过期时间未再通告的设备，并发送离线事件）
     async fn start_cleanup_task(&self) {
         let peers = self.peers.clone();
         let event_sender = self.event_sender.clone();
@@ -214,13 +304,7 @@ impl MdnsDiscovery {
                     .unwrap_or_default()
                     .as_millis() as u64;
 
-                let expired: Vec<String> = peers_guard
-                    .iter()
-                    .filter(|(_, peer)| {
-                        now.saturating_sub(peer.last_seen) > PEER_EXPIRE_TIMEOUT.as_millis() as u64
-                    })
-                    .map(|(id, _)| id.clone())
-                    .collect();
+                let expired = expired_peer_ids(&peers_guard, now, peer_ttl());
 
                 drop(peers_guard);
 
@@ -238,9 +322,58 @@ impl MdnsDiscovery {
         });
     }
 
-    /// 获取当前发现的设备列表
+    /// 获取当前发现的设备列表（收藏的设备在前，其余按发现时间排序）
     pub async fn get_peers(&self) -> Vec<PeerInfo> {
-        self.peers.lock().await.values().cloned().collect()
+        let mut peers: Vec<PeerInfo> = self.peers.lock().await.values().cloned().collect();
+        peers.sort_by_key(|peer| (!peer.pinned, peer.discovered_at));
+        peers
+    }
+
+    /// 获取收藏的设备
+    pub async fn pinned_peers(&self) -> Vec<PeerInfo> {
+        self.peers
+            .lock()
+            .await
+            .values()
+            .filter(|peer| peer.pinned)
+            .cloned()
+            .collect()
+    }
+
+    /// 设置设备的收藏状态，返回更新后的设备信息，设备不存在时返回 None
+    pub async fn set_pinned(&self, id: &str, pinned: bool) -> Option<PeerInfo> {
+        let mut peers = self.peers.lock().await;
+        let peer = peers.get_mut(id)?;
+        peer.pinned = pinned;
+        let peer = peer.clone();
+        drop(peers);
+
+        let _ = self.event_sender.send(PeerDiscoveryEvent {
+            event_type: PeerEventType::Updated,
+            peer: peer.clone(),
+        });
+        Some(peer)
+    }
+
+    /// 预加载已保存的收藏设备
+    ///
+    /// 已在列表中的同一设备只标记为收藏；通过通告发现的设备在重新收到通告前视为离线
+    pub async fn preload_pinned(&self, pinned: Vec<PeerInfo>) {
+        let mut peers = self.peers.lock().await;
+        for mut peer in pinned {
+            if let Some(existing) = peers
+                .values_mut()
+                .find(|p| p.id == peer.id || is_same_device(p, &peer))
+            {
+                existing.pinned = true;
+                continue;
+            }
+            peer.pinned = true;
+            if peer.is_announced() {
+                peer.status = PeerStatus::Offline;
+            }
+            peers.insert(peer.id.clone(), peer);
+        }
     }
 
     /// 获取指定设备信息
@@ -248,6 +381,35 @@ impl MdnsDiscovery {
         self.peers.lock().await.get(id).cloned()
     }
 
+    /// 合并子网扫描发现的设备，返回合并后的设备信息
+    ///
+    /// 扫描只能确认地址可用，与已有设备重复时仅刷新其活跃时间和状态，保留原有名称与发现来源
+    pub async fn merge_scanned(&self, scanned: Vec<PeerInfo>) -> Vec<PeerInfo> {
+        let mut merged = Vec::with_capacity(scanned.len());
+        for peer in scanned {
+            let mut peers = self.peers.lock().await;
+            let (event_type, peer) = match peers.values_mut().find(|p| is_same_device(p, &peer)) {
+                Some(existing) => {
+                    existing.last_seen = peer.last_seen;
+                    existing.status = PeerStatus::Available;
+                    (PeerEventType::Updated, existing.clone())
+                }
+                None => {
+                    peers.insert(peer.id.clone(), peer.clone());
+                    (PeerEventType::Discovered, peer)
+                }
+            };
+            drop(peers);
+
+            let _ = self.event_sender.send(PeerDiscoveryEvent {
+                event_type,
+                peer: peer.clone(),
+            });
+            merged.push(peer);
+        }
+        merged
+    }
+
     /// 手动添加设备（用于手动连接）
     pub async fn add_peer_manual(&self, ip: String, port: u16) -> PeerInfo {
         let now = std::time::SystemTime::now()
@@ -255,7 +417,7 @@ impl MdnsDiscovery {
             .unwrap_or_default()
             .as_millis() as u64;
 
-        let peer = PeerInfo {
+        let mut peer = PeerInfo {
             id: format!("manual-{}:{}", ip, port),
             name: format!("手动添加 ({}:{})", ip, port),
             ip,
@@ -264,9 +426,14 @@ impl MdnsDiscovery {
             discovered_at: now,
             last_seen: now,
             status: PeerStatus::Available,
+            source: DiscoverySource::Manual,
+            fingerprint: None,
+            pinned: false,
         };
 
         let mut peers = self.peers.lock().await;
+        // 重新添加已收藏的手动设备时保留收藏状态
+        peer.pinned = peers.get(&peer.id).is_some_and(|p| p.pinned);
         peers.insert(peer.id.clone(), peer.clone());
         drop(peers);
 
@@ -312,4 +479,134 @@ mod tests {
         assert_eq!(discovery.device_name, "TestDevice");
         assert_eq!(discovery.listen_port, 8080);
     }
+
+    #[tokio::test]
+    async fn test_upsert_peer_deduplicates() {
+        let discovery = MdnsDiscovery::new("TestDevice".to_string(), 8080);
+        let peers = discovery.peer_store();
+        let sender = discovery.event_sender();
+        let mut receiver = discovery.subscribe();
+
+        let mut mdns_peer = PeerInfo::new("Laptop".to_string(), "192.168.1.9".to_string(), 9000);
+        mdns_peer.source = DiscoverySource::Mdns;
+        upsert_peer(&peers, &sender, mdns_peer.clone()).await;
+        assert_eq!(
+            receiver.recv().await.unwrap().event_type,
+            PeerEventType::Discovered
+        );
+
+        // 同一地址的广播信标合并到已有设备，并补充指纹
+        let mut beacon_peer = PeerInfo::new("Laptop".to_string(), "192.168.1.9".to_string(), 9000);
+        beacon_peer.source = DiscoverySource::Broadcast;
+        beacon_peer.fingerprint = Some("AA:BB".to_string());
+        upsert_peer(&peers, &sender, beacon_peer).await;
+        let event = receiver.recv().await.unwrap();
+        assert_eq!(event.event_type, PeerEventType::Updated);
+        assert_eq!(event.peer.id, mdns_peer.id);
+        assert_eq!(event.peer.source, DiscoverySource::Mdns);
+        assert_eq!(event.peer.fingerprint.as_deref(), Some("AA:BB"));
+
+        // 指纹相同但地址变化仍视为同一设备
+        let mut moved_peer = PeerInfo::new("Laptop".to_string(), "10.0.0.4".to_string(), 9000);
+        moved_peer.fingerprint = Some("AA:BB".to_string());
+        upsert_peer(&peers, &sender, moved_peer).await;
+
+        let all = discovery.get_peers().await;
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].ip, "10.0.0.4");
+    }
+
+    #[test]
+    fn test_expired_peers_skip_manual() {
+        let ttl = Duration::from_secs(DEFAULT_PEER_TTL_SECS);
+        let mut fresh = PeerInfo::new("Fresh".to_string(), "192.168.1.2".to_string(), 9000);
+        fresh.source = DiscoverySource::Mdns;
+        let mut stale = PeerInfo::new("Stale".to_string(), "192.168.1.3".to_string(), 9000);
+        stale.source = DiscoverySource::Broadcast;
+        let mut manual = PeerInfo::new("Manual".to_string(), "192.168.1.4".to_string(), 9000);
+
+        let now = fresh.last_seen;
+        stale.last_seen = now - ttl.as_millis() as u64;
+        manual.last_seen = 0;
+        assert!(!stale.is_online(ttl));
+        assert!(manual.is_online(ttl));
+
+        // 扫描发现的设备同样会过期，但有效期不短于扫描设备的最短有效期
+        let mut scanned = PeerInfo::new("Scanned".to_string(), "192.168.1.6".to_string(), 9000);
+        scanned.source = DiscoverySource::Scan;
+        scanned.last_seen = now - ttl.as_millis() as u64;
+        assert!(scanned.is_online(ttl));
+        assert!(scanned.is_expired(now + 300_000, ttl));
+
+        // 收藏的设备过期后显示为离线，但不会被移除
+        let mut pinned = PeerInfo::new("Pinned".to_string(), "192.168.1.5".to_string(), 9000);
+        pinned.source = DiscoverySource::Mdns;
+        pinned.last_seen = 0;
+        pinned.pinned = true;
+        assert!(!pinned.is_online(ttl));
+
+        let stale_id = stale.id.clone();
+        let peers: HashMap<String, PeerInfo> = [fresh, stale, manual, pinned]
+            .into_iter()
+            .map(|peer| (peer.id.clone(), peer))
+            .collect();
+        assert_eq!(expired_peer_ids(&peers, now, ttl), vec![stale_id]);
+    }
+
+    #[tokio::test]
+    async fn test_merge_scanned_keeps_existing_peer() {
+        let discovery = MdnsDiscovery::new("Test".to_string(), 9000);
+        let mut mdns_peer = PeerInfo::new("Laptop".to_string(), "192.168.1.9".to_string(), 9000);
+        mdns_peer.source = DiscoverySource::Mdns;
+        mdns_peer.status = PeerStatus::Offline;
+        let sender = discovery.event_sender();
+        upsert_peer(&discovery.peer_store(), &sender, mdns_peer.clone()).await;
+
+        let mut known = PeerInfo::new("scan".to_string(), "192.168.1.9".to_string(), 9000);
+        known.source = DiscoverySource::Scan;
+        let mut new = PeerInfo::new("scan".to_string(), "192.168.1.10".to_string(), 9000);
+        new.source = DiscoverySource::Scan;
+        let merged = discovery.merge_scanned(vec![known, new.clone()]).await;
+
+        // 已有设备保留名称和来源，只刷新状态
+        assert_eq!(merged[0].id, mdns_peer.id);
+        assert_eq!(merged[0].name, "Laptop");
+        assert_eq!(merged[0].source, DiscoverySource::Mdns);
+        assert_eq!(merged[0].status, PeerStatus::Available);
+        assert_eq!(merged[1].id, new.id);
+        assert_eq!(discovery.get_peers().await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_preload_pinned_peers() {
+        let discovery = MdnsDiscovery::new("Test".to_string(), 9000);
+        let discovered = discovery
+            .add_peer_manual("192.168.1.10".to_string(), 9000)
+            .await;
+
+        let mut saved = PeerInfo::new("Saved".to_string(), "192.168.1.11".to_string(), 9000);
+        saved.source = DiscoverySource::Mdns;
+        let mut same = discovered.clone();
+        same.pinned = true;
+        discovery.preload_pinned(vec![saved.clone(), same]).await;
+
+        // 已存在的设备只标记收藏，新加载的设备在收到通告前离线
+        let peers = discovery.get_peers().await;
+        assert_eq!(peers.len(), 2);
+        assert!(peers.iter().all(|p| p.pinned));
+        let loaded = peers.iter().find(|p| p.id == saved.id).unwrap();
+        assert_eq!(loaded.status, PeerStatus::Offline);
+
+        // 取消收藏后排在收藏设备之后，停止发现时被清除
+        discovery.set_pinned(&discovered.id, false).await.unwrap();
+        assert!(discovery.set_pinned("missing", true).await.is_none());
+        let peers = discovery.get_peers().await;
+        assert_eq!(peers[0].id, saved.id);
+        assert_eq!(discovery.pinned_peers().await.len(), 1);
+
+        discovery.stop().await.unwrap();
+        let peers = discovery.get_peers().await;
+        assert_eq!(peers.len(), 1);
+        assert_eq!(peers[0].id, saved.id);
+    }
 }
\ No newline at end of file
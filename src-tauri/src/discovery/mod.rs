@@ -1,9 +1,13 @@
 //! 设备发现模块
 
+mod broadcast;
 mod commands;
 mod manager;
 mod mdns;
+mod scan;
 
+pub use broadcast::*;
 pub use commands::*;
 pub use manager::*;
 pub use mdns::*;
+pub use scan::*;
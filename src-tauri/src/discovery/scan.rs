@@ -0,0 +1,126 @@
+//! 子网扫描发现
+//!
+//! mDNS 多播被网络屏蔽时的后备方式：枚举本机所在 /24 网段的主机，
+//! 逐个尝试连接传输端口并完成一次握手，响应握手的主机视为 PureSend 设备
+
+use crate::models::{DeviceType, DiscoverySource, PeerInfo, PeerStatus};
+use crate::transfer::LocalTransport;
+use futures::StreamExt;
+use std::collections::HashSet;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// 同时探测的主机数上限，避免短时间内向网段发起过多连接
+const MAX_SCAN_CONCURRENCY: usize = 32;
+
+/// 单个主机的连接和握手超时（毫秒）
+const SCAN_PROBE_TIMEOUT_MS: u64 = 500;
+
+/// 枚举本机地址所在 /24 网段的主机地址（不含本机地址、网络地址和广播地址）
+fn subnet_hosts(local_ips: &[Ipv4Addr]) -> Vec<Ipv4Addr> {
+    let mut subnets = HashSet::new();
+    let mut hosts = Vec::new();
+    for ip in local_ips.iter().filter(|ip| !ip.is_loopback()) {
+        let [a, b, c, _] = ip.octets();
+        if !subnets.insert([a, b, c]) {
+            continue;
+        }
+        hosts.extend(
+            (1..=254)
+                .map(|d| Ipv4Addr::new(a, b, c, d))
+                .filter(|host| !local_ips.contains(host)),
+        );
+    }
+    hosts
+}
+
+/// 探测单个主机，完成握手时返回对应的设备信息
+async fn probe_host(ip: Ipv4Addr, port: u16) -> Option<PeerInfo> {
+    let addr = SocketAddr::new(IpAddr::V4(ip), port);
+    let timeout = Duration::from_millis(SCAN_PROBE_TIMEOUT_MS);
+    let result = LocalTransport::test_connection_with_timeout(addr, true, timeout).await;
+    if !result.is_puresend {
+        return None;
+    }
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    Some(PeerInfo {
+        id: format!("scan-{}:{}", ip, port),
+        name: format!("扫描发现 ({}:{})", ip, port),
+        ip: ip.to_string(),
+        port,
+        device_type: DeviceType::Unknown,
+        discovered_at: now,
+        last_seen: now,
+        status: PeerStatus::Available,
+        source: DiscoverySource::Scan,
+        fingerprint: None,
+        pinned: false,
+    })
+}
+
+/// 扫描本机所在 /24 网段中监听 `port` 的 PureSend 设备
+///
+/// 并发探测数受 `MAX_SCAN_CONCURRENCY` 限制；`cancelled` 置位后不再发起新的探测，
+/// 返回已发现的设备
+pub async fn scan_subnet(port: u16, cancelled: &AtomicBool) -> Vec<PeerInfo> {
+    let local_ips: Vec<Ipv4Addr> = crate::network::get_local_ips()
+        .iter()
+        .filter_map(|ip| ip.parse().ok())
+        .collect();
+    let hosts = subnet_hosts(&local_ips);
+    tracing::info!(hosts = hosts.len(), port, "开始子网扫描");
+
+    let peers: Vec<PeerInfo> = futures::stream::iter(hosts)
+        .map(|ip| async move {
+            if cancelled.load(Ordering::Relaxed) {
+                return None;
+            }
+            probe_host(ip, port).await
+        })
+        .buffer_unordered(MAX_SCAN_CONCURRENCY)
+        .filter_map(|peer| async move { peer })
+        .collect()
+        .await;
+
+    tracing::info!(
+        found = peers.len(),
+        cancelled = cancelled.load(Ordering::Relaxed),
+        "子网扫描结束"
+    );
+    peers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subnet_hosts_skips_local_addresses() {
+        let local_ips = [
+            Ipv4Addr::new(192, 168, 1, 20),
+            Ipv4Addr::new(192, 168, 1, 21),
+            Ipv4Addr::new(10, 0, 0, 5),
+            Ipv4Addr::LOCALHOST,
+        ];
+        let hosts = subnet_hosts(&local_ips);
+
+        // 两个 /24 网段各 254 个主机，去掉本机的 3 个地址，回环地址不扫描
+        assert_eq!(hosts.len(), 254 * 2 - 3);
+        assert!(hosts.contains(&Ipv4Addr::new(192, 168, 1, 1)));
+        assert!(hosts.contains(&Ipv4Addr::new(10, 0, 0, 254)));
+        assert!(!hosts.contains(&Ipv4Addr::new(192, 168, 1, 20)));
+        assert!(!hosts.contains(&Ipv4Addr::new(192, 168, 1, 255)));
+        assert!(hosts.iter().all(|ip| !ip.is_loopback()));
+    }
+
+    #[tokio::test]
+    async fn test_scan_cancelled_before_start() {
+        let cancelled = AtomicBool::new(true);
+        assert!(scan_subnet(9, &cancelled).await.is_empty());
+    }
+}
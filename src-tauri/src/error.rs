@@ -28,6 +28,10 @@ pub enum TransferError {
     #[error("Transfer cancelled")]
     Cancelled,
 
+    /// 用户暂停后未能恢复（暂停超时或连接断开），不自动重试
+    #[error("Transfer interrupted: {0}")]
+    Interrupted(String),
+
     #[error("Integrity check failed: {0}")]
     IntegrityCheckFailed(String),
 
@@ -40,6 +44,12 @@ pub enum TransferError {
     #[error("Insufficient storage")]
     InsufficientStorage,
 
+    #[error("Disk full: {0}")]
+    DiskFull(String),
+
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
+
     #[error("Unsupported operation: {0}")]
     UnsupportedOperation(String),
 
@@ -74,6 +84,16 @@ pub enum TransferError {
     ProtocolVersionMismatch(String),
 }
 
+impl TransferError {
+    /// 是否为可重试的临时错误（对端短暂不可达、网络抖动、超时）
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            TransferError::Network(_) | TransferError::Timeout | TransferError::PeerUnreachable(_)
+        )
+    }
+}
+
 impl From<io::Error> for TransferError {
     fn from(err: io::Error) -> Self {
         TransferError::Io(err.to_string())
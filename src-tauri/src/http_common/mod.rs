@@ -5,17 +5,20 @@
 use axum::{
     body::Body,
     extract::{connect_info::ConnectInfo, State as AxumState},
-    http::{header, HeaderName, StatusCode},
+    http::{header, HeaderMap, HeaderName, StatusCode},
     response::{Html, IntoResponse, Json, Response},
 };
-use serde::Serialize;
-use std::net::SocketAddr;
-use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use std::hash::{Hash, Hasher};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::{Arc, OnceLock, RwLock};
 use tokio::sync::Mutex;
+use tower_http::classify::{ServerErrorsAsFailures, SharedClassifier};
 use tower_http::cors::{Any, CorsLayer};
+use tower_http::trace::TraceLayer;
 
 use crate::transfer::compression::get_compression_config;
-use crate::transfer::crypto::is_encryption_enabled;
+use crate::transfer::crypto::{is_encryption_enabled, HandshakeVerification};
 use crate::transfer::http_crypto::{
     HandshakeRequest, HandshakeResponse, HttpCryptoSessionManager,
 };
@@ -46,11 +49,9 @@ impl ServerCapabilities {
         Self {
             encryption,
             compression: compression_config.enabled,
-            compression_algorithm: if compression_config.enabled {
-                Some("zstd".to_string())
-            } else {
-                None
-            },
+            compression_algorithm: compression_config
+                .enabled
+                .then(|| compression_config.algorithm.as_str().to_string()),
             chunk_size: HTTP_CHUNK_SIZE,
         }
     }
@@ -67,10 +68,65 @@ impl ServerCapabilities {
     }
 }
 
+/// Schema version of the `/info` response. Bump when fields are removed or change meaning.
+///
+/// v2: `capabilities` may be absent, with `restricted: true`, until access is granted.
+pub const SERVER_INFO_SCHEMA_VERSION: u32 = 2;
+
+/// Machine-readable server description returned by `/info`.
+///
+/// Unauthenticated by design: it only describes the server, never file contents.
+#[derive(Debug, Serialize)]
+pub struct ServerInfo {
+    pub schema_version: u32,
+    pub app_name: &'static str,
+    pub app_version: &'static str,
+    pub server_type: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capabilities: Option<ServerCapabilities>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub restricted: bool,
+    pub endpoints: Vec<&'static str>,
+}
+
+impl ServerInfo {
+    pub fn new(
+        server_type: &'static str,
+        capabilities: ServerCapabilities,
+        endpoints: &[&'static str],
+    ) -> Self {
+        Self {
+            schema_version: SERVER_INFO_SCHEMA_VERSION,
+            app_name: "PureSend",
+            app_version: env!("CARGO_PKG_VERSION"),
+            server_type,
+            capabilities: Some(capabilities),
+            restricted: false,
+            endpoints: endpoints.to_vec(),
+        }
+    }
+
+    /// Minimal description for clients that have not been granted access yet
+    pub fn restricted(server_type: &'static str, endpoints: &[&'static str]) -> Self {
+        Self {
+            schema_version: SERVER_INFO_SCHEMA_VERSION,
+            app_name: "PureSend",
+            app_version: env!("CARGO_PKG_VERSION"),
+            server_type,
+            capabilities: None,
+            restricted: true,
+            endpoints: endpoints.to_vec(),
+        }
+    }
+}
+
 // ─── Trait for crypto session access ────────────────────────────────────────
 
 pub trait HasCryptoSessions {
     fn crypto_sessions(&self) -> &Arc<Mutex<HttpCryptoSessionManager>>;
+
+    /// Called after each successful handshake, e.g. to show the SAS on the desktop side
+    fn on_handshake(&self, _verification: HandshakeVerification) {}
 }
 
 // ─── Shared Handlers ────────────────────────────────────────────────────────
@@ -91,8 +147,9 @@ pub async fn favicon_handler() -> impl IntoResponse {
 }
 
 pub async fn crypto_handshake_handler<S: HasCryptoSessions + Send + Sync + 'static>(
-    ConnectInfo(_client_addr): ConnectInfo<SocketAddr>,
+    ConnectInfo(client_addr): ConnectInfo<SocketAddr>,
     AxumState(state): AxumState<Arc<S>>,
+    headers: HeaderMap,
     Json(payload): Json<HandshakeRequest>,
 ) -> Json<HandshakeResponse> {
     if !is_encryption_enabled() {
@@ -100,30 +157,46 @@ pub async fn crypto_handshake_handler<S: HasCryptoSessions + Send + Sync + 'stat
             encryption: false,
             server_public_key: None,
             session_id: None,
+            cipher: None,
         });
     }
 
     let mut crypto_sessions = state.crypto_sessions().lock().await;
 
-    match crypto_sessions.handshake(&payload.client_public_key) {
-        Ok((session_id, server_pub_key)) => Json(HandshakeResponse {
-            encryption: true,
-            server_public_key: Some(server_pub_key),
-            session_id: Some(session_id),
-        }),
+    match crypto_sessions.handshake(&payload.client_public_key, &payload.supported_ciphers) {
+        Ok((session_id, server_pub_key, cipher, sas)) => {
+            // The SAS is never sent to the client: the browser derives its own and the user
+            // compares both, so a substituted key cannot go unnoticed
+            state.on_handshake(HandshakeVerification {
+                task_id: session_id.clone(),
+                peer: resolve_client_ip(&client_addr, &headers),
+                sas,
+            });
+            Json(HandshakeResponse {
+                encryption: true,
+                server_public_key: Some(server_pub_key),
+                session_id: Some(session_id),
+                cipher: Some(cipher),
+            })
+        }
         Err(e) => {
-            eprintln!("Crypto handshake failed: {}", e);
+            tracing::warn!(
+                client_ip = %resolve_client_ip(&client_addr, &headers),
+                error = %e,
+                "Crypto handshake failed"
+            );
             Json(HandshakeResponse {
                 encryption: false,
                 server_public_key: None,
                 session_id: None,
+                cipher: None,
             })
         }
     }
 }
 
 pub async fn fallback_handler(uri: axum::http::Uri) -> impl IntoResponse {
-    eprintln!("Unmatched route: {}", uri);
+    tracing::debug!(%uri, "Unmatched route");
     (
         StatusCode::NOT_FOUND,
         Html(format!(
@@ -135,7 +208,12 @@ pub async fn fallback_handler(uri: axum::http::Uri) -> impl IntoResponse {
 
 // ─── Session Cleanup ────────────────────────────────────────────────────────
 
-pub fn spawn_crypto_session_cleanup(crypto_sessions: Arc<Mutex<HttpCryptoSessionManager>>) {
+/// Periodically drop expired crypto sessions of one server.
+///
+/// Returns the task handle so the owning server can abort it on stop.
+pub fn spawn_crypto_session_cleanup(
+    crypto_sessions: Arc<Mutex<HttpCryptoSessionManager>>,
+) -> tokio::task::JoinHandle<()> {
     tokio::spawn(async move {
         let mut interval =
             tokio::time::interval(std::time::Duration::from_secs(SESSION_CLEANUP_INTERVAL_SECS));
@@ -143,7 +221,148 @@ pub fn spawn_crypto_session_cleanup(crypto_sessions: Arc<Mutex<HttpCryptoSession
             interval.tick().await;
             crypto_sessions.lock().await.cleanup_expired();
         }
-    });
+    })
+}
+
+// ─── Listener ───────────────────────────────────────────────────────────────
+
+/// Bind a listener that can take over a port still in TIME_WAIT from a previous run.
+///
+/// Lets a restarted server keep the URL it handed out. `SO_REUSEADDR` is skipped on
+/// Windows, where it would allow binding over a port another process is using.
+pub fn bind_reusable_listener(addr: SocketAddr) -> std::io::Result<tokio::net::TcpListener> {
+    let socket = if addr.is_ipv4() {
+        tokio::net::TcpSocket::new_v4()?
+    } else {
+        tokio::net::TcpSocket::new_v6()?
+    };
+    #[cfg(not(windows))]
+    socket.set_reuseaddr(true)?;
+    socket.bind(addr)?;
+    socket.listen(1024)
+}
+
+// ─── Bind Address ───────────────────────────────────────────────────────────
+
+/// Interface address the share and upload servers listen on; `None` listens on all.
+static HTTP_BIND_ADDRESS: OnceLock<RwLock<Option<IpAddr>>> = OnceLock::new();
+
+fn get_bind_address_setting() -> &'static RwLock<Option<IpAddr>> {
+    HTTP_BIND_ADDRESS.get_or_init(|| RwLock::new(None))
+}
+
+pub fn http_bind_address() -> Option<IpAddr> {
+    get_bind_address_setting().read().map(|v| *v).unwrap_or(None)
+}
+
+pub fn set_http_bind_address_internal(address: Option<IpAddr>) {
+    if let Ok(mut value) = get_bind_address_setting().write() {
+        *value = address;
+    }
+}
+
+/// Socket address a server on `port` listens on, honoring the bind address setting.
+pub fn http_listen_addr(port: u16) -> SocketAddr {
+    listen_addr(http_bind_address(), port)
+}
+
+/// Socket address for `port` on `bind`, or on all interfaces when `None`.
+pub fn listen_addr(bind: Option<IpAddr>, port: u16) -> SocketAddr {
+    SocketAddr::new(bind.unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED)), port)
+}
+
+/// Links clients can use to reach a server on `port`, honoring the bind address setting.
+pub fn server_links(port: u16) -> Vec<String> {
+    links_for(http_bind_address(), port)
+}
+
+/// Links clients can use to reach a server listening on `bind` and `port`.
+///
+/// When the server is restricted to one interface only that address is reachable,
+/// so it is the only link handed out.
+pub fn links_for(bind: Option<IpAddr>, port: u16) -> Vec<String> {
+    match bind {
+        Some(ip) => vec![format!("http://{}", SocketAddr::new(ip, port))],
+        None => crate::network::get_local_ips()
+            .iter()
+            .map(|ip| format!("http://{}:{}", ip, port))
+            .collect(),
+    }
+}
+
+// ─── Client IP Resolution ───────────────────────────────────────────────────
+
+/// Whether `X-Forwarded-For` / `X-Real-IP` are honored. Off by default: without a
+/// reverse proxy in front, any client could spoof these headers to bypass PIN
+/// lockout or impersonate an approved IP.
+static TRUST_PROXY_HEADERS: OnceLock<RwLock<bool>> = OnceLock::new();
+
+fn get_trust_proxy_setting() -> &'static RwLock<bool> {
+    TRUST_PROXY_HEADERS.get_or_init(|| RwLock::new(false))
+}
+
+pub fn is_trusted_proxy_enabled() -> bool {
+    get_trust_proxy_setting().read().map(|v| *v).unwrap_or(false)
+}
+
+pub fn set_trusted_proxy_enabled_internal(enabled: bool) {
+    if let Ok(mut value) = get_trust_proxy_setting().write() {
+        *value = enabled;
+    }
+}
+
+/// Resolve the client IP used for access control and request tracking.
+///
+/// When trusted proxy mode is enabled, the rightmost valid address of
+/// `X-Forwarded-For` (the one appended by the nearest proxy) wins, then
+/// `X-Real-IP`. Otherwise, or if neither header parses, the socket peer is used.
+/// The result is normalized with [`normalize_client_ip`], so one client keeps the
+/// same identity whichever address family it arrives on.
+pub fn resolve_client_ip(client_addr: &SocketAddr, headers: &HeaderMap) -> String {
+    if is_trusted_proxy_enabled() {
+        if let Some(ip) = forwarded_client_ip(headers) {
+            return normalize_client_ip(ip).to_string();
+        }
+    }
+    normalize_client_ip(client_addr.ip()).to_string()
+}
+
+/// Canonical form of a client address: IPv4-mapped IPv6 (`::ffff:a.b.c.d`)
+/// becomes plain IPv4, so lockouts and approvals carry over between families.
+pub fn normalize_client_ip(ip: IpAddr) -> IpAddr {
+    ip.to_canonical()
+}
+
+/// [`normalize_client_ip`] for stored address strings; unparsable values are kept as-is
+pub fn normalize_client_ip_str(ip: &str) -> String {
+    match ip.trim().parse::<IpAddr>() {
+        Ok(ip) => normalize_client_ip(ip).to_string(),
+        Err(_) => ip.to_string(),
+    }
+}
+
+fn forwarded_client_ip(headers: &HeaderMap) -> Option<IpAddr> {
+    let from_xff = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .filter_map(|part| part.trim().parse::<IpAddr>().ok())
+        .last();
+
+    from_xff.or_else(|| {
+        headers
+            .get("x-real-ip")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<IpAddr>().ok())
+    })
+}
+
+// ─── Request Tracing ────────────────────────────────────────────────────────
+
+/// Emit a tracing span per HTTP request (method, URI, status, latency).
+pub fn http_trace_layer() -> TraceLayer<SharedClassifier<ServerErrorsAsFailures>> {
+    TraceLayer::new_for_http()
 }
 
 // ─── CORS Configuration ─────────────────────────────────────────────────────
@@ -171,6 +390,7 @@ pub fn share_cors_layer() -> CorsLayer {
             header::ACCEPT,
             header::RANGE,
             HeaderName::from_static("x-encryption-session"),
+            HeaderName::from_static("x-accept-compression"),
         ],
         vec![
             header::CONTENT_RANGE,
@@ -180,6 +400,10 @@ pub fn share_cors_layer() -> CorsLayer {
             HeaderName::from_static("x-original-size"),
             HeaderName::from_static("x-compression"),
             HeaderName::from_static("x-encryption"),
+            HeaderName::from_static("x-chunk-count"),
+            HeaderName::from_static("x-chunk-sizes"),
+            HeaderName::from_static("x-original-sizes"),
+            HeaderName::from_static("x-compressed-chunks"),
         ],
     )
 }
@@ -199,8 +423,351 @@ pub fn web_upload_cors_layer() -> CorsLayer {
     )
 }
 
+// ─── Page Branding ──────────────────────────────────────────────────────────
+
+/// Maximum length (in characters) of the branded page title
+pub const MAX_BRANDING_TITLE_LEN: usize = 80;
+
+/// Maximum length (in characters) of the branded page subtitle
+pub const MAX_BRANDING_SUBTITLE_LEN: usize = 200;
+
+/// Operator branding for the browser-facing share and upload pages.
+///
+/// Every field is optional; unset fields keep the default PureSend look.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageBranding {
+    /// Replaces the page title and main heading
+    pub title: Option<String>,
+    /// Short line shown under the heading
+    pub subtitle: Option<String>,
+    /// Accent color used for links, buttons and progress bars
+    pub accent_color: Option<String>,
+}
+
+impl PageBranding {
+    /// Trim fields, drop empty ones and reject values that are too long or not a CSS color
+    pub fn normalized(self) -> Result<Self, String> {
+        fn non_empty(value: Option<String>) -> Option<String> {
+            value
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        }
+
+        let branding = Self {
+            title: non_empty(self.title),
+            subtitle: non_empty(self.subtitle),
+            accent_color: non_empty(self.accent_color),
+        };
+        if let Some(title) = &branding.title {
+            if title.chars().count() > MAX_BRANDING_TITLE_LEN {
+                return Err(format!(
+                    "无效的页面标题：不能超过 {} 个字符",
+                    MAX_BRANDING_TITLE_LEN
+                ));
+            }
+        }
+        if let Some(subtitle) = &branding.subtitle {
+            if subtitle.chars().count() > MAX_BRANDING_SUBTITLE_LEN {
+                return Err(format!(
+                    "无效的页面副标题：不能超过 {} 个字符",
+                    MAX_BRANDING_SUBTITLE_LEN
+                ));
+            }
+        }
+        if let Some(color) = &branding.accent_color {
+            if !is_valid_css_color(color) {
+                return Err(format!("无效的主题色: {}", color));
+            }
+        }
+        Ok(branding)
+    }
+
+    /// HTML-escaped title, or `default` when unset
+    pub fn title_html(&self, default: &str) -> String {
+        escape_html(self.title.as_deref().unwrap_or(default))
+    }
+
+    /// HTML-escaped subtitle, if set
+    pub fn subtitle_html(&self) -> Option<String> {
+        self.subtitle.as_deref().map(escape_html)
+    }
+
+    /// Accent color, only if it passes validation
+    ///
+    /// The value is interpolated into inline CSS, so it is re-checked here rather
+    /// than trusting whoever built the struct.
+    pub fn accent_color(&self) -> Option<&str> {
+        self.accent_color
+            .as_deref()
+            .filter(|color| is_valid_css_color(color))
+    }
+}
+
+static PAGE_BRANDING: OnceLock<RwLock<PageBranding>> = OnceLock::new();
+
+fn get_page_branding_setting() -> &'static RwLock<PageBranding> {
+    PAGE_BRANDING.get_or_init(|| RwLock::new(PageBranding::default()))
+}
+
+pub fn page_branding() -> PageBranding {
+    get_page_branding_setting()
+        .read()
+        .map(|v| v.clone())
+        .unwrap_or_default()
+}
+
+pub fn set_page_branding_internal(branding: PageBranding) {
+    if let Ok(mut value) = get_page_branding_setting().write() {
+        *value = branding;
+    }
+}
+
+/// Whether `value` is a plain CSS color: `#rgb`, `#rgba`, `#rrggbb`, `#rrggbbaa`,
+/// `rgb()`/`rgba()`/`hsl()`/`hsla()` with numeric arguments, or a named color.
+///
+/// Deliberately strict: anything that could close the declaration or the
+/// `<style>` element (`;`, `}`, `<`, quotes, `url(`...) is rejected.
+pub fn is_valid_css_color(value: &str) -> bool {
+    if let Some(hex) = value.strip_prefix('#') {
+        return matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit());
+    }
+
+    let lower = value.to_ascii_lowercase();
+    for function in ["rgba(", "rgb(", "hsla(", "hsl("] {
+        if let Some(args) = lower.strip_prefix(function) {
+            let Some(args) = args.strip_suffix(')') else {
+                return false;
+            };
+            return !args.trim().is_empty()
+                && args
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || matches!(c, ' ' | ',' | '.' | '%' | '/' | '-'));
+        }
+    }
+
+    (3..=20).contains(&value.len()) && value.chars().all(|c| c.is_ascii_alphabetic())
+}
+
+// ─── Page Shell Caching ─────────────────────────────────────────────────────
+
+/// `Last-Modified` of the page shells: the first time one was served by this process
+static PAGE_SHELL_LAST_MODIFIED: OnceLock<String> = OnceLock::new();
+
+/// Serve a static HTML page shell with `ETag` / `Last-Modified` validators.
+///
+/// The shells only depend on which page is shown, its language and the operator
+/// branding, so the ETag is derived from the app version plus those. Several shells
+/// share the `/` URL and the right one depends on access state, so the response
+/// uses `no-cache`: the browser still revalidates on every load, but an unchanged
+/// page costs a 304 instead of the full inline CSS/JS.
+pub fn page_shell_response(
+    request_headers: &HeaderMap,
+    page: &str,
+    is_english: bool,
+    render: impl FnOnce(bool) -> String,
+) -> Response {
+    let etag = page_shell_etag(page, is_english);
+    let last_modified = PAGE_SHELL_LAST_MODIFIED.get_or_init(|| {
+        chrono::Utc::now()
+            .format("%a, %d %b %Y %H:%M:%S GMT")
+            .to_string()
+    });
+
+    let not_modified = request_headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| etag_matches(v, &etag));
+
+    let mut response = if not_modified {
+        StatusCode::NOT_MODIFIED.into_response()
+    } else {
+        Html(render(is_english)).into_response()
+    };
+
+    let headers = response.headers_mut();
+    if let Ok(value) = axum::http::HeaderValue::from_str(&etag) {
+        headers.insert(header::ETAG, value);
+    }
+    if let Ok(value) = axum::http::HeaderValue::from_str(last_modified) {
+        headers.insert(header::LAST_MODIFIED, value);
+    }
+    headers.insert(
+        header::CACHE_CONTROL,
+        axum::http::HeaderValue::from_static("no-cache"),
+    );
+    headers.insert(
+        header::VARY,
+        axum::http::HeaderValue::from_static("Accept-Language"),
+    );
+    response
+}
+
+fn page_shell_etag(page: &str, is_english: bool) -> String {
+    let language = if is_english { "en" } else { "zh" };
+    let branding = page_branding();
+    if branding == PageBranding::default() {
+        return format!("\"{}-{}-{}\"", env!("CARGO_PKG_VERSION"), page, language);
+    }
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    branding.hash(&mut hasher);
+    format!(
+        "\"{}-{}-{}-{:x}\"",
+        env!("CARGO_PKG_VERSION"),
+        page,
+        language,
+        hasher.finish()
+    )
+}
+
+/// Whether an `If-None-Match` value matches `etag` (weak comparison)
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match
+        .split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
+
+// ─── Operator Admin Page ────────────────────────────────────────────────────
+
+/// A table rendered on the operator `/admin` page
+pub struct AdminTable {
+    pub title: &'static str,
+    pub columns: &'static [&'static str],
+    pub rows: Vec<Vec<String>>,
+}
+
+/// Whether a request may see the operator `/admin` page.
+///
+/// Only clients on this machine are allowed: the socket peer (and the forwarded
+/// client, in trusted proxy mode) must be loopback. The `Host` header must name
+/// a loopback host to defeat DNS rebinding, and a cross-origin `Origin` is
+/// rejected so other pages open in a local browser cannot read the log.
+pub fn is_admin_request_allowed(client_addr: &SocketAddr, headers: &HeaderMap) -> bool {
+    let client_is_loopback = normalize_client_ip(client_addr.ip()).is_loopback()
+        && resolve_client_ip(client_addr, headers)
+            .parse::<IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback());
+    if !client_is_loopback {
+        return false;
+    }
+
+    let Some(host) = headers.get(header::HOST).and_then(|v| v.to_str().ok()) else {
+        return false;
+    };
+    if !is_loopback_host(host) {
+        return false;
+    }
+
+    headers
+        .get(header::ORIGIN)
+        .map(|origin| origin.to_str().is_ok_and(|o| o == format!("http://{}", host)))
+        .unwrap_or(true)
+}
+
+/// Whether a `Host` header value (with optional port) names a loopback host
+fn is_loopback_host(host: &str) -> bool {
+    let hostname = match host.strip_prefix('[') {
+        Some(rest) => rest.split(']').next().unwrap_or_default(),
+        None => host.split(':').next().unwrap_or_default(),
+    };
+    hostname.eq_ignore_ascii_case("localhost")
+        || hostname
+            .parse::<IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
+/// 403 response for rejected `/admin` requests
+pub fn admin_forbidden_response(client_addr: &SocketAddr) -> Response {
+    tracing::warn!(client = %client_addr, "Rejected non-local admin page request");
+    (
+        StatusCode::FORBIDDEN,
+        Html("<html><body><h1>403 - Forbidden</h1></body></html>"),
+    )
+        .into_response()
+}
+
+/// Render the operator `/admin` page
+pub fn admin_page_response(title: &str, tables: &[AdminTable]) -> Response {
+    let mut html = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+body {{ font-family: system-ui, sans-serif; margin: 24px; color: #222; }}
+table {{ border-collapse: collapse; width: 100%; margin-bottom: 32px; font-size: 14px; }}
+th, td {{ border: 1px solid #ddd; padding: 6px 10px; text-align: left; }}
+th {{ background: #f5f5f5; }}
+.empty {{ color: #888; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+"#,
+        title = escape_html(title)
+    );
+    for table in tables {
+        html.push_str(&format!("<h2>{}</h2>\n", escape_html(table.title)));
+        if table.rows.is_empty() {
+            html.push_str("<p class=\"empty\">No records</p>\n");
+            continue;
+        }
+        html.push_str("<table>\n<tr>");
+        for column in table.columns {
+            html.push_str(&format!("<th>{}</th>", escape_html(column)));
+        }
+        html.push_str("</tr>\n");
+        for row in &table.rows {
+            html.push_str("<tr>");
+            for cell in row {
+                html.push_str(&format!("<td>{}</td>", escape_html(cell)));
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</table>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+
+    let mut response = Html(html).into_response();
+    response.headers_mut().insert(
+        header::CACHE_CONTROL,
+        axum::http::HeaderValue::from_static("no-store"),
+    );
+    response
+}
+
+/// Format a millisecond timestamp as local time for the admin page
+pub fn format_admin_timestamp(millis: u64) -> String {
+    chrono::DateTime::from_timestamp_millis(millis as i64)
+        .map(|t| {
+            t.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_default()
+}
+
 // ─── HTML Utilities ─────────────────────────────────────────────────────────
 
+/// Escape text for inclusion in HTML content or attribute values
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
 pub fn parse_user_agent(ua: &str) -> &'static str {
     let ua_lower = ua.to_lowercase();
 
@@ -260,4 +827,158 @@ pub fn parse_user_agent(ua: &str) -> &'static str {
         (_, "Linux") => "Browser(Linux)",
         (_, _) => "Browser",
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_shell_etag_matching() {
+        let etag = page_shell_etag("upload", true);
+        assert_ne!(etag, page_shell_etag("upload", false));
+        assert_ne!(etag, page_shell_etag("waiting", true));
+
+        assert!(etag_matches(&etag, &etag));
+        assert!(etag_matches(&format!("W/{}", etag), &etag));
+        assert!(etag_matches(&format!("\"stale\", {}", etag), &etag));
+        assert!(etag_matches("*", &etag));
+        assert!(!etag_matches("\"stale\"", &etag));
+    }
+
+    #[test]
+    fn test_admin_request_allowed() {
+        let local: SocketAddr = "127.0.0.1:50000".parse().unwrap();
+        let remote: SocketAddr = "192.168.1.20:50000".parse().unwrap();
+        let headers = |pairs: &[(&'static str, &'static str)]| {
+            let mut map = HeaderMap::new();
+            for (name, value) in pairs {
+                map.insert(*name, value.parse().unwrap());
+            }
+            map
+        };
+
+        let same_origin = headers(&[("host", "localhost:8080")]);
+        assert!(is_admin_request_allowed(&local, &same_origin));
+        assert!(is_admin_request_allowed(&local, &headers(&[("host", "[::1]:8080")])));
+        assert!(!is_admin_request_allowed(&remote, &same_origin));
+
+        // DNS rebinding: loopback socket but foreign Host
+        assert!(!is_admin_request_allowed(
+            &local,
+            &headers(&[("host", "evil.example:8080")])
+        ));
+        // Cross-origin page in a local browser
+        assert!(!is_admin_request_allowed(
+            &local,
+            &headers(&[("host", "localhost:8080"), ("origin", "http://evil.example")])
+        ));
+        assert!(is_admin_request_allowed(
+            &local,
+            &headers(&[("host", "localhost:8080"), ("origin", "http://localhost:8080")])
+        ));
+    }
+
+    #[test]
+    fn test_client_ip_normalization() {
+        let mapped: SocketAddr = "[::ffff:192.168.1.20]:50000".parse().unwrap();
+        let v4: SocketAddr = "192.168.1.20:50000".parse().unwrap();
+        let headers = HeaderMap::new();
+        assert_eq!(resolve_client_ip(&mapped, &headers), "192.168.1.20");
+        assert_eq!(
+            resolve_client_ip(&mapped, &headers),
+            resolve_client_ip(&v4, &headers)
+        );
+
+        // Native IPv6 addresses are left untouched
+        let v6: IpAddr = "fe80::1".parse().unwrap();
+        assert_eq!(normalize_client_ip(v6), v6);
+        assert_eq!(
+            normalize_client_ip("::ffff:127.0.0.1".parse().unwrap()),
+            IpAddr::from([127, 0, 0, 1])
+        );
+
+        let mapped_local: SocketAddr = "[::ffff:127.0.0.1]:50000".parse().unwrap();
+        let mut local_headers = HeaderMap::new();
+        local_headers.insert("host", "localhost:8080".parse().unwrap());
+        assert!(is_admin_request_allowed(&mapped_local, &local_headers));
+    }
+
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(
+            escape_html("<a href=\"x\">Tom & 'Jerry'</a>"),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; &#39;Jerry&#39;&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn test_bind_address_links() {
+        set_http_bind_address_internal(Some("fd00::42".parse().unwrap()));
+        assert_eq!(http_listen_addr(8080).to_string(), "[fd00::42]:8080");
+        assert_eq!(server_links(8080), vec!["http://[fd00::42]:8080".to_string()]);
+
+        set_http_bind_address_internal(None);
+        assert_eq!(http_listen_addr(8080).to_string(), "0.0.0.0:8080");
+
+        let lan: IpAddr = "192.168.1.5".parse().unwrap();
+        assert_eq!(listen_addr(Some(lan), 0).to_string(), "192.168.1.5:0");
+        assert_eq!(
+            links_for(Some(lan), 9000),
+            vec!["http://192.168.1.5:9000".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_css_color_validation() {
+        for color in ["#1976d2", "#fff", "#ffff", "#11223344", "rebeccapurple"] {
+            assert!(is_valid_css_color(color), "{}", color);
+        }
+        for color in ["rgb(25, 118, 210)", "RGBA(0,0,0,0.5)", "hsl(210 50% 40% / 80%)"] {
+            assert!(is_valid_css_color(color), "{}", color);
+        }
+        for color in [
+            "",
+            "#12",
+            "#ggg",
+            "red; } body { display: none",
+            "red</style><script>",
+            "url(http://x)",
+            "rgb(1,2,3",
+            "rgb(expression(alert(1)))",
+            "var(--x)",
+        ] {
+            assert!(!is_valid_css_color(color), "{}", color);
+        }
+    }
+
+    #[test]
+    fn test_page_branding_normalized() {
+        let branding = PageBranding {
+            title: Some("  <b>Team</b> Drop ".to_string()),
+            subtitle: Some("   ".to_string()),
+            accent_color: Some(" #e91e63 ".to_string()),
+        }
+        .normalized()
+        .unwrap();
+        assert_eq!(branding.title.as_deref(), Some("<b>Team</b> Drop"));
+        assert_eq!(branding.subtitle, None);
+        assert_eq!(branding.accent_color(), Some("#e91e63"));
+        assert_eq!(branding.title_html("PureSend"), "&lt;b&gt;Team&lt;/b&gt; Drop");
+        assert_eq!(PageBranding::default().title_html("PureSend"), "PureSend");
+
+        let invalid = PageBranding {
+            accent_color: Some("red;}".to_string()),
+            ..Default::default()
+        };
+        assert!(invalid.clone().normalized().is_err());
+        // A value that bypassed validation is still never emitted into CSS
+        assert_eq!(invalid.accent_color(), None);
+
+        let too_long = PageBranding {
+            title: Some("x".repeat(MAX_BRANDING_TITLE_LEN + 1)),
+            ..Default::default()
+        };
+        assert!(too_long.normalized().is_err());
+    }
+}
@@ -0,0 +1,96 @@
+//! 空闲自动停止
+//!
+//! 分享、网页上传和接收服务在设定时间内没有任何客户端交互时自动停止，
+//! 避免在公共网络中长时间暴露。超时为 0 表示不自动停止
+
+use serde::Serialize;
+use std::sync::{OnceLock, RwLock};
+use tauri::{AppHandle, Emitter};
+
+/// 空闲检查间隔（秒）
+pub const IDLE_CHECK_INTERVAL_SECS: u64 = 30;
+
+/// 空闲超时上限（分钟）
+pub const MAX_IDLE_TIMEOUT_MINUTES: u32 = 24 * 60;
+
+/// 空闲超时（分钟），0 表示关闭
+static IDLE_TIMEOUT_MINUTES: OnceLock<RwLock<u32>> = OnceLock::new();
+
+fn get_idle_timeout_setting() -> &'static RwLock<u32> {
+    IDLE_TIMEOUT_MINUTES.get_or_init(|| RwLock::new(0))
+}
+
+/// 获取空闲超时（分钟），0 表示关闭
+pub fn idle_timeout_minutes() -> u32 {
+    get_idle_timeout_setting().read().map(|v| *v).unwrap_or(0)
+}
+
+/// 设置空闲超时（分钟）
+pub fn set_idle_timeout_minutes_internal(minutes: u32) {
+    if let Ok(mut value) = get_idle_timeout_setting().write() {
+        *value = minutes;
+    }
+}
+
+/// 距上次活动是否已超过空闲超时，`minutes` 为 0 时始终为 false
+pub fn is_idle(last_activity: u64, now: u64, minutes: u32) -> bool {
+    minutes > 0 && now.saturating_sub(last_activity) >= u64::from(minutes) * 60_000
+}
+
+/// 被自动停止的服务
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum IdleService {
+    /// 文件分享
+    Share,
+    /// 网页上传
+    WebUpload,
+    /// 局域网接收
+    Receive,
+}
+
+/// `auto-stopped-idle` 事件载荷
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AutoStoppedIdleEvent {
+    /// 被停止的服务
+    service: IdleService,
+    /// 分享 ID（仅文件分享）
+    share_id: Option<String>,
+    /// 生效的空闲超时（分钟）
+    idle_minutes: u32,
+}
+
+/// 通知前端服务因空闲被自动停止
+pub fn emit_auto_stopped_idle(
+    app: &AppHandle,
+    service: IdleService,
+    share_id: Option<String>,
+    idle_minutes: u32,
+) {
+    tracing::info!(?service, ?share_id, idle_minutes, "服务空闲超时，已自动停止");
+    let _ = app.emit(
+        "auto-stopped-idle",
+        AutoStoppedIdleEvent {
+            service,
+            share_id,
+            idle_minutes,
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_idle() {
+        let last_activity = 1_000_000;
+        assert!(!is_idle(last_activity, last_activity + 4 * 60_000, 5));
+        assert!(is_idle(last_activity, last_activity + 5 * 60_000, 5));
+        // 0 表示关闭
+        assert!(!is_idle(last_activity, u64::MAX, 0));
+        // 时钟回拨不视为空闲
+        assert!(!is_idle(last_activity, last_activity - 1, 1));
+    }
+}
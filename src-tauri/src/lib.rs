@@ -7,10 +7,15 @@ mod cloud_providers;
 mod discovery;
 mod error;
 mod http_common;
+mod idle;
+mod logging;
 mod models;
 mod network;
 mod share;
+mod token_bucket;
 mod transfer;
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+mod tray;
 mod web_upload;
 
 use cloud::CloudState;
@@ -32,6 +37,8 @@ use tauri::menu::{MenuBuilder, MenuItemBuilder, PredefinedMenuItem, SubmenuBuild
 struct MenuTexts {
     // PureSend 菜单
     about: &'static str,
+    preferences: &'static str,
+    check_updates: &'static str,
     quit: &'static str,
     // 文件菜单
     file: &'static str,
@@ -54,6 +61,8 @@ struct MenuTexts {
 #[cfg(target_os = "macos")]
 const MENU_TEXTS_ZH: MenuTexts = MenuTexts {
     about: "关于 PureSend",
+    preferences: "偏好设置…",
+    check_updates: "检查更新…",
     quit: "退出",
     file: "文件",
     send_file: "发送文件",
@@ -71,6 +80,8 @@ const MENU_TEXTS_ZH: MenuTexts = MenuTexts {
 #[cfg(target_os = "macos")]
 const MENU_TEXTS_EN: MenuTexts = MenuTexts {
     about: "About PureSend",
+    preferences: "Preferences…",
+    check_updates: "Check for Updates…",
     quit: "Quit",
     file: "File",
     send_file: "Send File",
@@ -105,8 +116,16 @@ fn build_menu(
 
     // PureSend 菜单
     let about_item = MenuItemBuilder::with_id("about", texts.about).build(app)?;
+    let check_updates_item =
+        MenuItemBuilder::with_id("check_updates", texts.check_updates).build(app)?;
+    let preferences_item = MenuItemBuilder::with_id("preferences", texts.preferences)
+        .accelerator("CmdOrCtrl+,")
+        .build(app)?;
     let app_submenu = SubmenuBuilder::new(app, "PureSend")
         .item(&about_item)
+        .item(&check_updates_item)
+        .separator()
+        .item(&preferences_item)
         .separator()
         .item(&PredefinedMenuItem::quit(app, Some(texts.quit))?)
         .build()?;
@@ -171,8 +190,15 @@ fn update_menu_language(app: tauri::AppHandle, lang: String) -> Result<(), Strin
     Ok(())
 }
 
-/// 占位命令（非 macOS 平台）
-#[cfg(not(target_os = "macos"))]
+/// 更新系统托盘语言（Windows / Linux）
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+#[tauri::command]
+fn update_menu_language(app: tauri::AppHandle, lang: String) -> Result<(), String> {
+    tray::update_language(&app, &lang).map_err(|e| e.to_string())
+}
+
+/// 占位命令（移动端）
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
 #[tauri::command]
 fn update_menu_language(_lang: String) -> Result<(), String> {
     Ok(())
@@ -193,6 +219,28 @@ fn toggle_devtools(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
     }
 }
 
+/// 应用数据目录，启动时由 `init_app_storage` 设置
+static APP_DATA_DIR: std::sync::OnceLock<std::path::PathBuf> = std::sync::OnceLock::new();
+
+/// 应用数据目录（设备密钥、受信任 IP 等持久化文件所在目录）
+///
+/// 取不到系统的应用数据目录时使用临时目录下的 `puresend` 目录
+pub(crate) fn app_data_dir() -> std::path::PathBuf {
+    APP_DATA_DIR
+        .get_or_init(|| std::env::temp_dir().join("puresend"))
+        .clone()
+}
+
+/// 设置持久化文件的存储目录，并恢复已保存的后端设置
+///
+/// 需在首次读取任何持久化文件前调用
+fn init_app_storage(app: &tauri::App) {
+    if let Ok(dir) = app.path().app_data_dir() {
+        let _ = APP_DATA_DIR.set(dir);
+    }
+    transfer::restore_persisted_settings(app.handle());
+}
+
 /// 启动网络变化监视器
 ///
 /// 在应用启动时调用，监听网络状态变化并通知前端。
@@ -214,7 +262,7 @@ fn start_network_watcher(app: &tauri::App) {
                     let manager_guard = discovery_state.manager.lock().await;
                     if let Some(manager) = manager_guard.as_ref() {
                         if let Err(err) = manager.restart().await {
-                            eprintln!("网络变化后重启 mDNS 服务失败: {}", err);
+                            tracing::warn!(error = %err, "网络变化后重启 mDNS 服务失败");
                         }
                     }
                 });
@@ -250,39 +298,102 @@ pub fn run() {
             crate::discovery::get_peers,
             crate::discovery::get_peer,
             crate::discovery::add_peer_manual,
+            crate::discovery::scan_subnet_for_peers,
+            crate::discovery::cancel_subnet_scan,
+            crate::discovery::pin_peer,
+            crate::discovery::unpin_peer,
             crate::discovery::is_peer_online,
             crate::discovery::get_online_count,
+            crate::discovery::get_peer_ttl,
+            crate::discovery::set_peer_ttl,
             crate::discovery::restart_discovery,
+            crate::discovery::get_broadcast_discovery_enabled,
+            crate::discovery::set_broadcast_discovery_enabled,
             // Transfer commands
             crate::transfer::init_transfer,
             crate::transfer::get_transfer_port,
+            crate::transfer::get_cloud_config,
+            crate::transfer::set_cloud_config,
             crate::transfer::prepare_file_transfer,
+            crate::transfer::compute_file_hash,
+            crate::transfer::cancel_file_hash,
             crate::transfer::get_file_metadata,
             crate::transfer::get_files_in_folder,
+            crate::transfer::preview_folder_send,
+            crate::transfer::fetch_url_to_temp,
+            crate::transfer::validate_filename,
             crate::transfer::get_network_info,
             crate::transfer::start_receiving,
             crate::transfer::stop_receiving,
             crate::transfer::get_receive_directory,
             crate::transfer::set_receive_directory,
+            crate::transfer::list_received_files,
+            crate::transfer::delete_received_file,
+            crate::transfer::get_temp_directory,
+            crate::transfer::set_temp_directory,
+            crate::transfer::get_receive_capacity,
+            crate::transfer::test_peer_connection,
+            crate::transfer::verify_peer_session,
             crate::transfer::send_file,
             crate::transfer::send_file_async,
+            crate::transfer::send_files_async,
+            crate::transfer::send_file_to_peers,
+            crate::transfer::list_cloud_files,
+            crate::transfer::receive_cloud_file,
+            crate::transfer::get_peer_history,
+            crate::transfer::clear_peer_history,
             crate::transfer::cancel_transfer,
+            crate::transfer::confirm_handshake,
+            crate::transfer::pause_transfer,
+            crate::transfer::resume_transfer_active,
+            crate::transfer::cancel_all_transfers,
             crate::transfer::get_transfer_progress,
             crate::transfer::get_active_tasks,
             crate::transfer::verify_file_integrity,
+            crate::transfer::verify_partial_file,
             crate::transfer::cleanup_completed_tasks,
             // Receive settings commands
             crate::transfer::get_receive_settings,
             crate::transfer::set_auto_receive,
             crate::transfer::set_file_overwrite,
+            crate::transfer::set_max_auto_accept_size,
+            crate::transfer::set_verify_on_receive,
             crate::transfer::get_unique_file_path,
             // Transfer enhancement commands
             crate::transfer::get_encryption_enabled,
             crate::transfer::set_encryption_enabled,
+            crate::transfer::get_preferred_cipher,
+            crate::transfer::set_preferred_cipher,
+            crate::transfer::set_transfer_retry_policy,
+            crate::transfer::set_transfer_max_retries,
             crate::transfer::get_compression_enabled,
             crate::transfer::set_compression_enabled,
             crate::transfer::set_compression_mode,
             crate::transfer::set_compression_level,
+            crate::transfer::get_compression_algorithm,
+            crate::transfer::set_compression_algorithm,
+            crate::transfer::get_transfer_chunk_size,
+            crate::transfer::set_transfer_chunk_size,
+            crate::transfer::get_transfer_speed_limit,
+            crate::transfer::set_transfer_speed_limit,
+            crate::transfer::get_transfer_hash_algorithm,
+            crate::transfer::set_transfer_hash_algorithm,
+            crate::transfer::get_compression_stats,
+            crate::transfer::reset_compression_stats,
+            crate::transfer::get_trusted_proxy_enabled,
+            crate::transfer::set_trusted_proxy_enabled,
+            crate::transfer::get_preserve_timestamps,
+            crate::transfer::set_preserve_timestamps,
+            crate::transfer::get_http_bind_address,
+            crate::transfer::set_http_bind_address,
+            crate::transfer::get_page_branding,
+            crate::transfer::get_idle_timeout,
+            crate::transfer::set_idle_timeout,
+            crate::transfer::set_page_branding,
+            crate::transfer::get_all_settings,
+            crate::transfer::apply_settings,
+            crate::transfer::get_device_fingerprint,
+            crate::transfer::regenerate_device_keypair,
             crate::transfer::get_resumable_tasks,
             crate::transfer::resume_transfer,
             crate::transfer::cleanup_resume_info,
@@ -290,19 +401,36 @@ pub fn run() {
             crate::share::start_share,
             crate::share::stop_share,
             crate::share::get_share_info,
+            crate::share::list_shares,
+            crate::share::get_share_qr_code,
             crate::share::get_access_requests,
             crate::share::accept_access_request,
             crate::share::reject_access_request,
+            crate::share::accept_all_pending_access_requests,
+            crate::share::reject_all_pending_access_requests,
+            crate::share::list_trusted_ips,
+            crate::share::forget_trusted_ip,
             crate::share::remove_access_request,
             crate::share::clear_access_requests,
+            crate::share::clear_completed_records,
             crate::share::update_share_files,
             crate::share::update_share_settings,
+            crate::share::get_share_bandwidth_limit,
+            crate::share::set_share_bandwidth_limit,
+            crate::share::get_share_request_rate_limit,
+            crate::share::set_share_request_rate_limit,
             // Web upload commands
             crate::web_upload::start_web_upload,
             crate::web_upload::stop_web_upload,
             crate::web_upload::get_web_upload_requests,
+            crate::web_upload::clear_completed_upload_records,
             crate::web_upload::accept_web_upload,
             crate::web_upload::reject_web_upload,
+            crate::web_upload::cancel_web_upload,
+            crate::web_upload::get_receive_directory_free_space,
+            crate::web_upload::get_web_upload_filters,
+            crate::web_upload::set_web_upload_filters,
+            crate::web_upload::cleanup_web_upload_temp,
             // Cloud commands
             crate::cloud::list_cloud_accounts,
             crate::cloud::add_cloud_account,
@@ -315,6 +443,12 @@ pub fn run() {
             crate::cloud::create_cloud_directory,
             crate::cloud::upload_to_cloud,
             crate::cloud::download_from_cloud,
+            // Logging commands
+            crate::logging::set_log_level,
+            crate::logging::get_log_level,
+            crate::logging::get_log_file_path,
+            crate::logging::get_recent_logs,
+            crate::logging::open_logs,
             // Menu commands
             update_menu_language,
             toggle_devtools,
@@ -323,6 +457,9 @@ pub fn run() {
     // macOS: 构建自定义菜单栏并处理菜单事件
     #[cfg(target_os = "macos")]
     let builder = builder.setup(|app| {
+        logging::init(app);
+        init_app_storage(app);
+
         let handle = app.handle().clone();
         let menu = build_menu(&handle, "zh-CN")?;
         app.set_menu(menu)?;
@@ -334,6 +471,12 @@ pub fn run() {
                     // 发送事件到前端
                     let _ = app_handle.emit("menu-event", "about");
                 }
+                "preferences" => {
+                    let _ = app_handle.emit("menu-event", "preferences");
+                }
+                "check_updates" => {
+                    let _ = open::that("https://github.com/z-only/puresend/releases");
+                }
                 "send_file" => {
                     let _ = app_handle.emit("menu-event", "send_file");
                 }
@@ -359,9 +502,23 @@ pub fn run() {
         Ok(())
     });
 
-    // 非 macOS 平台：仅启动网络变化监视器
-    #[cfg(not(target_os = "macos"))]
+    // Windows / Linux：创建系统托盘并启动网络变化监视器
+    #[cfg(any(target_os = "windows", target_os = "linux"))]
+    let builder = builder.setup(|app| {
+        logging::init(app);
+        init_app_storage(app);
+        if let Err(err) = tray::init(app) {
+            tracing::warn!(error = %err, "创建系统托盘失败");
+        }
+        start_network_watcher(app);
+        Ok(())
+    });
+
+    // 移动端：仅启动网络变化监视器
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
     let builder = builder.setup(|app| {
+        logging::init(app);
+        init_app_storage(app);
         start_network_watcher(app);
         Ok(())
     });
@@ -0,0 +1,231 @@
+//! 日志模块
+//!
+//! 基于 tracing 输出结构化日志，同时写入 stderr 和应用日志目录下按天滚动的日志文件，
+//! 最近的日志另在内存中保留一份，供诊断面板读取
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use tauri::Manager;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, EnvFilter, Registry};
+
+/// 默认日志级别
+const DEFAULT_LOG_LEVEL: &str = "info";
+
+/// 日志文件名前缀
+const LOG_FILE_PREFIX: &str = "puresend";
+
+/// 保留的日志文件数量（按天滚动）
+const MAX_LOG_FILES: usize = 7;
+
+/// 内存中保留的最近日志条数
+const RECENT_LOG_CAPACITY: usize = 1000;
+
+/// 最近日志的环形缓冲区，超出容量时丢弃最早的日志
+#[derive(Clone, Default)]
+struct RecentLogs(Arc<Mutex<VecDeque<String>>>);
+
+impl RecentLogs {
+    fn push(&self, line: String) {
+        if let Ok(mut lines) = self.0.lock() {
+            if lines.len() >= RECENT_LOG_CAPACITY {
+                lines.pop_front();
+            }
+            lines.push_back(line);
+        }
+    }
+
+    /// 最近 `limit` 条日志，按时间从早到晚排列
+    fn latest(&self, limit: usize) -> Vec<String> {
+        self.0
+            .lock()
+            .map(|lines| {
+                let skip = lines.len().saturating_sub(limit);
+                lines.iter().skip(skip).cloned().collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// fmt 层每条日志格式化完成后整体写入一次，每次写入即为一条日志
+impl std::io::Write for RecentLogs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let line = String::from_utf8_lossy(buf).trim_end().to_string();
+        if !line.is_empty() {
+            self.push(line);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// 日志运行时状态
+struct LoggingState {
+    /// 日志目录
+    log_dir: PathBuf,
+    /// 动态调整日志级别的句柄
+    filter_handle: reload::Handle<EnvFilter, Registry>,
+    /// 最近的日志
+    recent: RecentLogs,
+    /// 后台写入线程守卫，丢弃后文件日志停止写入
+    _guard: WorkerGuard,
+}
+
+static LOGGING: OnceLock<LoggingState> = OnceLock::new();
+
+/// 初始化日志系统
+///
+/// 优先使用 `RUST_LOG` 环境变量作为初始级别，重复调用时直接返回
+pub fn init(app: &tauri::App) {
+    if LOGGING.get().is_some() {
+        return;
+    }
+
+    let log_dir = app
+        .path()
+        .app_log_dir()
+        .unwrap_or_else(|_| std::env::temp_dir().join("puresend").join("logs"));
+    if let Err(e) = init_with_dir(&log_dir) {
+        eprintln!("初始化日志失败: {}", e);
+    }
+}
+
+fn init_with_dir(log_dir: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(log_dir).map_err(|e| format!("无法创建日志目录: {}", e))?;
+
+    let file_appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix("log")
+        .max_log_files(MAX_LOG_FILES)
+        .build(log_dir)
+        .map_err(|e| format!("无法创建日志文件: {}", e))?;
+    let (file_writer, guard) = tracing_appender::non_blocking(file_appender);
+
+    let initial_filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new(DEFAULT_LOG_LEVEL));
+    let (filter_layer, filter_handle) = reload::Layer::new(initial_filter);
+    let recent = RecentLogs::default();
+    let recent_writer = recent.clone();
+
+    tracing_subscriber::registry()
+        .with(filter_layer)
+        .with(fmt::layer().with_writer(std::io::stderr))
+        .with(fmt::layer().with_ansi(false).with_writer(file_writer))
+        .with(
+            fmt::layer()
+                .with_ansi(false)
+                .with_writer(move || recent_writer.clone()),
+        )
+        .try_init()
+        .map_err(|e| e.to_string())?;
+
+    let _ = LOGGING.set(LoggingState {
+        log_dir: log_dir.to_path_buf(),
+        filter_handle,
+        recent,
+        _guard: guard,
+    });
+
+    Ok(())
+}
+
+fn logging_state() -> Result<&'static LoggingState, String> {
+    LOGGING.get().ok_or_else(|| "日志系统未初始化".to_string())
+}
+
+/// 设置日志级别
+///
+/// 支持 trace、debug、info、warn、error、off，也接受 `puresend_lib=debug` 形式的过滤规则
+#[tauri::command]
+pub async fn set_log_level(level: String) -> Result<(), String> {
+    let filter = EnvFilter::try_new(&level).map_err(|e| format!("无效的日志级别 '{}': {}", level, e))?;
+    logging_state()?
+        .filter_handle
+        .reload(filter)
+        .map_err(|e| e.to_string())?;
+    tracing::info!(level = %level, "日志级别已更新");
+    Ok(())
+}
+
+/// 获取当前日志级别
+#[tauri::command]
+pub async fn get_log_level() -> Result<String, String> {
+    logging_state()?
+        .filter_handle
+        .with_current(|filter| filter.to_string())
+        .map_err(|e| e.to_string())
+}
+
+/// 获取最新的日志文件路径
+///
+/// 尚未产生日志文件时返回日志目录
+#[tauri::command]
+pub async fn get_log_file_path() -> Result<String, String> {
+    let state = logging_state()?;
+    let latest = std::fs::read_dir(&state.log_dir)
+        .map_err(|e| e.to_string())?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .starts_with(LOG_FILE_PREFIX)
+        })
+        .filter_map(|entry| {
+            let modified = entry.metadata().and_then(|m| m.modified()).ok()?;
+            Some((modified, entry.path()))
+        })
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path)
+        .unwrap_or_else(|| state.log_dir.clone());
+
+    Ok(latest.to_string_lossy().to_string())
+}
+
+/// 获取最近的日志（按时间从早到晚），最多保留 1000 条
+#[tauri::command]
+pub async fn get_recent_logs(limit: usize) -> Result<Vec<String>, String> {
+    Ok(logging_state()?.recent.latest(limit))
+}
+
+/// 在系统文件管理器中打开日志目录
+#[tauri::command]
+pub async fn open_logs() -> Result<(), String> {
+    let state = logging_state()?;
+    open::that(&state.log_dir).map_err(|e| format!("无法打开日志目录: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_recent_logs_keeps_latest() {
+        let mut recent = RecentLogs::default();
+        for i in 0..RECENT_LOG_CAPACITY + 2 {
+            recent
+                .write_all(format!("line {}\n", i).as_bytes())
+                .unwrap();
+        }
+        // 空行不计入
+        recent.write_all(b"\n").unwrap();
+
+        assert_eq!(recent.latest(usize::MAX).len(), RECENT_LOG_CAPACITY);
+        assert_eq!(
+            recent.latest(2),
+            vec![
+                format!("line {}", RECENT_LOG_CAPACITY),
+                format!("line {}", RECENT_LOG_CAPACITY + 1)
+            ]
+        );
+    }
+}
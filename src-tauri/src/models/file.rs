@@ -1,8 +1,11 @@
 //! 文件元数据模型
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use uuid::Uuid;
 
+/// 附言最大字节数
+pub const MAX_NOTE_BYTES: usize = 4096;
+
 /// 文件元数据
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -17,10 +20,26 @@ pub struct FileMetadata {
     pub mime_type: String,
     /// 文件哈希（用于校验）
     pub hash: String,
+    /// 文件及分块哈希使用的算法，旧版本对端不携带时为 sha256
+    #[serde(default = "default_hash_algorithm")]
+    pub hash_algorithm: String,
     /// 分块信息
     pub chunks: Vec<ChunkInfo>,
     /// 文件路径（发送时为源路径，接收时为目标路径）
     pub path: Option<String>,
+    /// 随文件发送的附言，反序列化时即清理，对端发来的内容同样受长度限制
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_note"
+    )]
+    pub note: Option<String>,
+    /// 源文件修改时间（毫秒），接收端据此恢复保存文件的修改时间
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified_at: Option<u64>,
+    /// 文件在所选文件夹内的相对路径（含文件名），接收端据此重建子目录
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relative_path: Option<String>,
 }
 
 impl FileMetadata {
@@ -32,8 +51,12 @@ impl FileMetadata {
             size,
             mime_type,
             hash: String::new(),
+            hash_algorithm: default_hash_algorithm(),
             chunks: Vec::new(),
             path: None,
+            note: None,
+            modified_at: None,
+            relative_path: None,
         }
     }
 
@@ -126,6 +149,44 @@ impl ChunkInfo {
     }
 }
 
+/// 是否为可改变文字显示方向的 Unicode 控制字符
+fn is_bidi_control(c: char) -> bool {
+    matches!(c, '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}')
+}
+
+/// 清理附言用于显示
+///
+/// 去除换行、制表符以外的控制字符和文字方向控制字符，按字符边界截断到
+/// `MAX_NOTE_BYTES`，空白附言视为无附言
+pub fn sanitize_note(note: &str) -> Option<String> {
+    let mut cleaned = String::new();
+    for c in note
+        .trim()
+        .chars()
+        .filter(|&c| (!c.is_control() || c == '\n' || c == '\t') && !is_bidi_control(c))
+    {
+        if cleaned.len() + c.len_utf8() > MAX_NOTE_BYTES {
+            break;
+        }
+        cleaned.push(c);
+    }
+
+    let cleaned = cleaned.trim_end();
+    (!cleaned.is_empty()).then(|| cleaned.to_string())
+}
+
+fn default_hash_algorithm() -> String {
+    "sha256".to_string()
+}
+
+fn deserialize_note<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let note = Option::<String>::deserialize(deserializer)?;
+    Ok(note.as_deref().and_then(sanitize_note))
+}
+
 /// 默认分块大小：1MB
 pub const DEFAULT_CHUNK_SIZE: u64 = 1024 * 1024;
 
@@ -144,4 +205,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_sanitize_note() {
+        assert_eq!(
+            sanitize_note("  sign page 3\r\n\u{202E}thanks\u{0007} ").as_deref(),
+            Some("sign page 3\nthanks")
+        );
+        assert_eq!(sanitize_note(" \n\t "), None);
+
+        let long = "中".repeat(MAX_NOTE_BYTES);
+        let truncated = sanitize_note(&long).unwrap();
+        assert!(truncated.len() <= MAX_NOTE_BYTES);
+        assert!(truncated.chars().all(|c| c == '中'));
+
+        // 对端发来的附言在反序列化时被清理
+        let metadata: FileMetadata = serde_json::from_str(
+            r#"{"id":"1","name":"a.txt","size":1,"mimeType":"text/plain","hash":"","chunks":[],"path":null,"note":"  hi\u0000 "}"#,
+        )
+        .unwrap();
+        assert_eq!(metadata.note.as_deref(), Some("hi"));
+        assert_eq!(metadata.hash_algorithm, "sha256");
+    }
+
 }
\ No newline at end of file
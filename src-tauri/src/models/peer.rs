@@ -1,9 +1,14 @@
 //! 设备（Peer）模型
 
 use serde::{Deserialize, Serialize};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
 
+/// 子网扫描发现的设备的最短有效期
+///
+/// 扫描由用户手动发起、不会周期性刷新，有效期不短于该值，避免扫描结果很快消失
+const SCANNED_PEER_MIN_TTL: Duration = Duration::from_secs(300);
+
 /// 设备信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -24,6 +29,13 @@ pub struct PeerInfo {
     pub last_seen: u64,
     /// 设备状态
     pub status: PeerStatus,
+    /// 发现来源
+    pub source: DiscoverySource,
+    /// 设备指纹（广播信标携带，mDNS 与手动添加的设备为空）
+    pub fingerprint: Option<String>,
+    /// 是否已收藏（收藏的设备重启后保留，且不会因过期被移除）
+    #[serde(default)]
+    pub pinned: bool,
 }
 
 impl PeerInfo {
@@ -43,16 +55,42 @@ impl PeerInfo {
             discovered_at: now,
             last_seen: now,
             status: PeerStatus::Available,
+            source: DiscoverySource::Manual,
+            fingerprint: None,
+            pinned: false,
         }
     }
 
-    /// 检查设备是否在线（5秒内有活动）
-    pub fn is_online(&self) -> bool {
+    /// 是否通过周期性通告发现（mDNS 或广播信标）
+    pub fn is_announced(&self) -> bool {
+        matches!(self.source, DiscoverySource::Mdns | DiscoverySource::Broadcast)
+    }
+
+    /// 是否已超过有效期未收到通告或重新扫描到（`now` 为毫秒时间戳）
+    ///
+    /// 通告发现的设备有效期为 `ttl`，扫描发现的设备为 `ttl` 与 `SCANNED_PEER_MIN_TTL`
+    /// 中的较大者，手动添加的设备不会过期
+    fn is_stale(&self, now: u64, ttl: Duration) -> bool {
+        let ttl = match self.source {
+            DiscoverySource::Mdns | DiscoverySource::Broadcast => ttl,
+            DiscoverySource::Scan => ttl.max(SCANNED_PEER_MIN_TTL),
+            DiscoverySource::Manual => return false,
+        };
+        now.saturating_sub(self.last_seen) >= ttl.as_millis() as u64
+    }
+
+    /// 是否应从设备列表中移除（已过期且未收藏）
+    pub fn is_expired(&self, now: u64, ttl: Duration) -> bool {
+        !self.pinned && self.is_stale(now, ttl)
+    }
+
+    /// 检查设备是否在线（有效期内收到过通告或重新扫描到，手动添加的设备始终在线）
+    pub fn is_online(&self, ttl: Duration) -> bool {
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap_or_default()
             .as_millis() as u64;
-        now.saturating_sub(self.last_seen) < 5000
+        !self.is_stale(now, ttl)
     }
 }
 
@@ -76,6 +114,20 @@ impl Default for DeviceType {
     }
 }
 
+/// 设备发现来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiscoverySource {
+    /// mDNS 发现
+    Mdns,
+    /// UDP 广播信标发现
+    Broadcast,
+    /// 手动添加
+    Manual,
+    /// 子网扫描发现
+    Scan,
+}
+
 /// 设备状态
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
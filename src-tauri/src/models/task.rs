@@ -48,6 +48,15 @@ pub struct TransferTask {
     /// 压缩率（百分比，0 表示未压缩）
     #[serde(default)]
     pub compression_ratio: f64,
+    /// 本次传输的加密设置（为空时使用全局设置）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption_override: Option<bool>,
+    /// 本次传输的压缩设置（为空时使用全局设置）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression_override: Option<bool>,
+    /// 多设备发送的分组 ID（同一文件发往多个设备的任务共享）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_id: Option<String>,
 }
 
 impl TransferTask {
@@ -76,6 +85,9 @@ impl TransferTask {
             resumed: false,
             encrypted: false,
             compression_ratio: 0.0,
+            encryption_override: None,
+            compression_override: None,
+            group_id: None,
         }
     }
 
@@ -85,6 +97,27 @@ impl TransferTask {
         self
     }
 
+    /// 设置本次传输的加密/压缩覆盖项，优先于全局设置
+    pub fn with_overrides(mut self, encryption: Option<bool>, compression: Option<bool>) -> Self {
+        self.encryption_override = encryption;
+        self.compression_override = compression;
+        self
+    }
+
+    /// 设置多设备发送的分组 ID
+    pub fn with_group(mut self, group_id: String) -> Self {
+        self.group_id = Some(group_id);
+        self
+    }
+
+    /// 设置随文件发送的附言，附言经清理后写入文件元数据，随文件请求一并发送
+    pub fn with_note(mut self, note: Option<String>) -> Self {
+        if let Some(note) = note {
+            self.file.note = crate::models::sanitize_note(&note);
+        }
+        self
+    }
+
     /// 标记为传输中
     pub fn start(&mut self) {
         self.status = TaskStatus::Transferring;
@@ -155,6 +188,8 @@ pub enum TaskStatus {
     Cancelled,
     /// 已中断（可恢复）
     Interrupted,
+    /// 已暂停（连接保持，可继续发送）
+    Paused,
 }
 
 impl Default for TaskStatus {
@@ -193,6 +228,21 @@ pub struct TransferProgress {
     pub estimated_time_remaining: Option<u64>,
     /// 错误信息
     pub error: Option<String>,
+    /// 是否停滞（超过阈值未收到对方确认）
+    #[serde(default)]
+    pub is_stalled: bool,
+    /// 距上次进度更新的秒数
+    #[serde(default)]
+    pub seconds_since_progress: u64,
+    /// 接收完成后文件是否经过校验（接收端校验关闭时为 false）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verified: Option<bool>,
+    /// 多设备发送的分组 ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_id: Option<String>,
+    /// 云盘发送完成后的分享链接
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub share_url: Option<String>,
 }
 
 impl From<&TransferTask> for TransferProgress {
@@ -206,6 +256,11 @@ impl From<&TransferTask> for TransferProgress {
             speed: task.speed,
             estimated_time_remaining: task.estimated_time_remaining(),
             error: task.error.clone(),
+            is_stalled: false,
+            seconds_since_progress: 0,
+            verified: None,
+            group_id: task.group_id.clone(),
+            share_url: None,
         }
     }
 }
\ No newline at end of file
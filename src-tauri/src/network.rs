@@ -1,56 +1,150 @@
 //! 网络工具模块
 
-use std::net::Ipv4Addr;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter};
 use tokio::sync::Mutex;
 
-/// 获取本地所有有效的 IPv4 地址
-///
-/// 返回的地址列表按优先级排序：
-/// - 私有网段（192.168.x.x、10.x.x.x、172.16-31.x.x）优先
-/// - 公网 IP 次之
+/// 地址族
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AddressFamily {
+    V4,
+    V6,
+}
+
+/// 地址作用域
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AddressScope {
+    /// 回环地址
+    Loopback,
+    /// 链路本地地址（169.254.x.x / fe80::/10）
+    LinkLocal,
+    /// 私有网段（含 IPv6 ULA fc00::/7）
+    Private,
+    /// 公网地址
+    Public,
+}
+
+/// 局域网内其他设备能否通过该地址访问本机的推测
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reachability {
+    /// 物理网卡上的私有地址，最可能可用
+    Likely,
+    /// 虚拟网卡、VPN 或公网地址，视网络环境而定
+    Possible,
+    /// 回环或链路本地地址，通常不可用
+    Unlikely,
+}
+
+/// 本机网络地址及其所属网卡信息
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalAddress {
+    /// IP 地址
+    pub address: String,
+    /// 网卡名称
+    pub interface: String,
+    /// 地址族
+    pub family: AddressFamily,
+    /// 地址作用域
+    pub scope: AddressScope,
+    /// 是否为虚拟网卡（容器、虚拟机、VPN 隧道等）
+    pub is_virtual: bool,
+    /// 可达性推测
+    pub reachability: Reachability,
+}
+
+impl LocalAddress {
+    fn new(interface: String, ip: IpAddr) -> Self {
+        let (family, scope) = match ip {
+            IpAddr::V4(v4) => (AddressFamily::V4, ipv4_scope(v4)),
+            IpAddr::V6(v6) => (AddressFamily::V6, ipv6_scope(v6)),
+        };
+        let is_virtual = is_virtual_interface(&interface);
+        let reachability = match scope {
+            AddressScope::Loopback | AddressScope::LinkLocal => Reachability::Unlikely,
+            AddressScope::Private if !is_virtual => Reachability::Likely,
+            _ => Reachability::Possible,
+        };
+
+        Self {
+            address: ip.to_string(),
+            interface,
+            family,
+            scope,
+            is_virtual,
+            reachability,
+        }
+    }
+}
+
+/// 获取本机所有网卡地址（含 IPv6、回环与链路本地地址）
 ///
-/// 如果没有找到任何有效 IP，返回 localhost 地址作为回退
-pub fn get_local_ips() -> Vec<String> {
+/// 按可达性排序，同等可达性下 IPv4 优先，再按网段优先级排序
+pub fn get_local_addresses() -> Vec<LocalAddress> {
     use local_ip_address::list_afinet_netifas;
 
-    let mut ips: Vec<(String, u8)> = Vec::new();
-
-    // 枚举所有网络接口
     let network_interfaces = match list_afinet_netifas() {
         Ok(interfaces) => interfaces,
-        Err(_) => return vec!["127.0.0.1".to_string()],
+        Err(_) => return Vec::new(),
     };
 
-    for (_, ip_addr) in network_interfaces {
-        // 只处理 IPv4 地址
-        let ipv4 = match ip_addr {
-            std::net::IpAddr::V4(v4) => v4,
-            std::net::IpAddr::V6(_) => continue,
-        };
+    let mut addresses: Vec<LocalAddress> = network_interfaces
+        .into_iter()
+        .map(|(name, ip)| LocalAddress::new(name, ip))
+        .collect();
+    addresses.sort_by_key(address_sort_key);
+    addresses.dedup_by(|a, b| a.address == b.address && a.interface == b.interface);
+    addresses
+}
 
-        // 过滤掉回环地址（127.x.x.x）
-        if ipv4.is_loopback() {
-            continue;
-        }
+/// 地址是否属于本机某个网卡
+pub fn is_local_address(ip: IpAddr) -> bool {
+    get_local_addresses()
+        .iter()
+        .any(|local| local.address.parse::<IpAddr>().is_ok_and(|local| local == ip))
+}
 
-        // 过滤掉 link-local 地址（169.254.x.x）
-        if is_link_local(ipv4) {
-            continue;
-        }
+fn address_sort_key(address: &LocalAddress) -> (Reachability, u8, u8) {
+    let family_rank = match address.family {
+        AddressFamily::V4 => 0,
+        AddressFamily::V6 => 1,
+    };
+    let priority = match address.address.parse() {
+        Ok(IpAddr::V4(v4)) => get_ip_priority(v4),
+        _ => u8::MAX,
+    };
+    (address.reachability, family_rank, priority)
+}
 
-        // 根据优先级分配权重
-        let priority = get_ip_priority(ipv4);
-        ips.push((ipv4.to_string(), priority));
-    }
+/// 获取本地所有有效的 IPv4 地址
+///
+/// 返回的地址列表按优先级排序：
+/// - 私有网段（192.168.x.x、10.x.x.x、172.16-31.x.x）优先
+/// - 公网 IP 次之
+///
+/// 如果没有找到任何有效 IP，返回 localhost 地址作为回退
+pub fn get_local_ips() -> Vec<String> {
+    let mut ips: Vec<(Ipv4Addr, u8)> = get_local_addresses()
+        .into_iter()
+        .filter(|addr| {
+            // 过滤掉回环地址和 link-local 地址
+            matches!(addr.scope, AddressScope::Private | AddressScope::Public)
+        })
+        .filter_map(|addr| match addr.address.parse() {
+            Ok(IpAddr::V4(v4)) => Some((v4, get_ip_priority(v4))),
+            _ => None,
+        })
+        .collect();
 
     // 按优先级排序（权重越小优先级越高）
     ips.sort_by_key(|(_, priority)| *priority);
 
-    // 提取 IP 地址
-    let result: Vec<String> = ips.into_iter().map(|(ip, _)| ip).collect();
+    let result: Vec<String> = ips.into_iter().map(|(ip, _)| ip.to_string()).collect();
 
     // 如果没有找到任何有效 IP，返回 localhost 作为回退
     if result.is_empty() {
@@ -60,6 +154,44 @@ pub fn get_local_ips() -> Vec<String> {
     }
 }
 
+fn ipv4_scope(ip: Ipv4Addr) -> AddressScope {
+    if ip.is_loopback() {
+        AddressScope::Loopback
+    } else if is_link_local(ip) {
+        AddressScope::LinkLocal
+    } else if ip.is_private() {
+        AddressScope::Private
+    } else {
+        AddressScope::Public
+    }
+}
+
+fn ipv6_scope(ip: Ipv6Addr) -> AddressScope {
+    let first_segment = ip.segments()[0];
+    if ip.is_loopback() {
+        AddressScope::Loopback
+    } else if first_segment & 0xffc0 == 0xfe80 {
+        AddressScope::LinkLocal
+    } else if first_segment & 0xfe00 == 0xfc00 {
+        AddressScope::Private
+    } else {
+        AddressScope::Public
+    }
+}
+
+/// 常见虚拟网卡名称前缀（Docker、虚拟机、VPN 隧道等）
+const VIRTUAL_INTERFACE_PREFIXES: &[&str] = &[
+    "docker", "veth", "br-", "virbr", "vmnet", "vboxnet", "utun", "tun", "tap", "wg", "zt",
+    "tailscale", "llw", "awdl", "bridge", "vethernet", "virtualbox", "vmware", "hyper-v",
+];
+
+fn is_virtual_interface(name: &str) -> bool {
+    let name = name.to_lowercase();
+    VIRTUAL_INTERFACE_PREFIXES
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+}
+
 /// 判断是否为 link-local 地址（169.254.x.x）
 fn is_link_local(ip: Ipv4Addr) -> bool {
     let octets = ip.octets();
@@ -301,4 +433,31 @@ impl Default for NetworkWatcherState {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_address_classification() {
+        let wifi = LocalAddress::new("en0".to_string(), "192.168.1.42".parse().unwrap());
+        assert_eq!(wifi.family, AddressFamily::V4);
+        assert_eq!(wifi.scope, AddressScope::Private);
+        assert_eq!(wifi.reachability, Reachability::Likely);
+
+        let docker = LocalAddress::new("docker0".to_string(), "172.17.0.1".parse().unwrap());
+        assert!(docker.is_virtual);
+        assert_eq!(docker.reachability, Reachability::Possible);
+
+        let loopback = LocalAddress::new("lo".to_string(), "::1".parse().unwrap());
+        assert_eq!(loopback.scope, AddressScope::Loopback);
+        assert_eq!(loopback.reachability, Reachability::Unlikely);
+
+        let link_local = LocalAddress::new("en0".to_string(), "fe80::1".parse().unwrap());
+        assert_eq!(link_local.family, AddressFamily::V6);
+        assert_eq!(link_local.scope, AddressScope::LinkLocal);
+
+        let ula = LocalAddress::new("en0".to_string(), "fd12:3456::1".parse().unwrap());
+        assert_eq!(ula.scope, AddressScope::Private);
+    }
+}
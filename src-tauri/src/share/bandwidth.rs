@@ -0,0 +1,125 @@
+//! 分享下载限速
+//!
+//! 所有分享下载流共用一个令牌桶，整体吞吐不超过设定的每秒字节数。
+//! 下载流读取到数据块后先预留对应的令牌，令牌不足时延迟发出该数据块
+
+use crate::token_bucket::TokenBucket;
+use bytes::Bytes;
+use futures::{FutureExt, Stream, StreamExt};
+use std::pin::Pin;
+use std::sync::{Mutex, OnceLock};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+static BANDWIDTH_LIMITER: OnceLock<Mutex<TokenBucket>> = OnceLock::new();
+
+fn get_bandwidth_limiter() -> &'static Mutex<TokenBucket> {
+    BANDWIDTH_LIMITER.get_or_init(|| Mutex::new(TokenBucket::new(None, Instant::now())))
+}
+
+/// 获取分享下载限速（字节/秒），None 表示不限速
+pub fn bandwidth_limit() -> Option<u64> {
+    get_bandwidth_limiter()
+        .lock()
+        .ok()
+        .and_then(|bucket| bucket.rate())
+}
+
+/// 设置分享下载限速，None 或 0 表示不限速
+pub fn set_bandwidth_limit_internal(bytes_per_sec: Option<u64>) {
+    if let Ok(mut bucket) = get_bandwidth_limiter().lock() {
+        bucket.set_rate(bytes_per_sec, Instant::now());
+    }
+}
+
+/// 为即将发出的数据块预留带宽，返回需要等待的时长（不限速时为 0）
+fn reserve_bandwidth(bytes: u64) -> Duration {
+    get_bandwidth_limiter()
+        .lock()
+        .map(|mut bucket| bucket.reserve(bytes, Instant::now()))
+        .unwrap_or(Duration::ZERO)
+}
+
+/// 内存中的响应体按此大小分块限速，避免一次预留整块数据造成长时间停顿
+const THROTTLED_BODY_PIECE: usize = 64 * 1024;
+
+/// 按共享限速发出数据块的下载流
+///
+/// 数据块读出后先预留令牌，令牌不足时延迟到可以发送时再交给客户端
+pub(super) struct ThrottledStream<S> {
+    inner: S,
+    /// 等待令牌的数据块
+    throttled: Option<(Bytes, Pin<Box<tokio::time::Sleep>>)>,
+}
+
+impl<S> ThrottledStream<S> {
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            throttled: None,
+        }
+    }
+}
+
+/// 限速发出内存中的响应数据
+pub(super) fn throttled_bytes(
+    data: Vec<u8>,
+) -> ThrottledStream<impl Stream<Item = std::io::Result<Bytes>> + Unpin> {
+    let data = Bytes::from(data);
+    let pieces: Vec<std::io::Result<Bytes>> = (0..data.len())
+        .step_by(THROTTLED_BODY_PIECE)
+        .map(|start| Ok(data.slice(start..(start + THROTTLED_BODY_PIECE).min(data.len()))))
+        .collect();
+    ThrottledStream::new(futures::stream::iter(pieces))
+}
+
+impl<S: Stream<Item = std::io::Result<Bytes>> + Unpin> Stream for ThrottledStream<S> {
+    type Item = std::io::Result<Bytes>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        if let Some((_, delay)) = this.throttled.as_mut() {
+            if delay.poll_unpin(cx).is_pending() {
+                return Poll::Pending;
+            }
+            if let Some((chunk, _)) = this.throttled.take() {
+                return Poll::Ready(Some(Ok(chunk)));
+            }
+        }
+
+        match this.inner.poll_next_unpin(cx) {
+            Poll::Ready(Some(Ok(chunk))) => {
+                let wait = reserve_bandwidth(chunk.len() as u64);
+                if !wait.is_zero() {
+                    let mut delay = Box::pin(tokio::time::sleep(wait));
+                    if delay.poll_unpin(cx).is_pending() {
+                        this.throttled = Some((chunk, delay));
+                        return Poll::Pending;
+                    }
+                }
+                Poll::Ready(Some(Ok(chunk)))
+            }
+            other => other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_throttled_bytes_keeps_data() {
+        let data: Vec<u8> = (0..THROTTLED_BODY_PIECE * 2 + 10)
+            .map(|i| i as u8)
+            .collect();
+        let pieces: Vec<Bytes> = throttled_bytes(data.clone())
+            .map(|piece| piece.unwrap())
+            .collect()
+            .await;
+        assert_eq!(pieces.len(), 3);
+        assert_eq!(pieces.concat(), data);
+        assert_eq!(throttled_bytes(Vec::new()).count().await, 0);
+    }
+}
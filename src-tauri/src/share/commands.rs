@@ -1,27 +1,37 @@
 //! 分享相关 Tauri 命令
 
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter, State};
 use tokio::sync::Mutex;
 
+use super::models::current_timestamp_millis;
 use super::models::{AccessRequest, ShareLinkInfo, ShareSettings, ShareState};
 use super::server::ShareServer;
 use crate::models::FileMetadata;
 
-/// 分享管理器状态
-pub struct ShareManagerState {
+/// 单个分享实例
+pub struct ShareInstance {
     /// 分享状态
     pub share_state: Arc<Mutex<ShareState>>,
     /// HTTP 服务器
-    pub server: Arc<Mutex<Option<ShareServer>>>,
+    pub server: ShareServer,
+}
+
+/// 分享管理器状态
+///
+/// 支持同时运行多个分享（如一个 PIN 保护、一个公开），以分享 ID 区分，
+/// 每个分享拥有独立的 HTTP 服务器、端口和分享状态
+pub struct ShareManagerState {
+    /// 活跃分享（分享 ID -> 分享实例）
+    pub shares: Arc<Mutex<HashMap<String, ShareInstance>>>,
 }
 
 impl ShareManagerState {
     pub fn new() -> Self {
         Self {
-            share_state: Arc::new(Mutex::new(ShareState::new())),
-            server: Arc::new(Mutex::new(None)),
+            shares: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 }
@@ -32,7 +42,188 @@ impl Default for ShareManagerState {
     }
 }
 
+/// 按分享 ID 查找分享实例
+///
+/// 未指定 ID 时，仅在只有一个活跃分享时返回该分享（兼容单分享调用方式）
+fn resolve_share<'a>(
+    shares: &'a HashMap<String, ShareInstance>,
+    share_id: Option<&str>,
+) -> Result<&'a ShareInstance, String> {
+    match share_id {
+        Some(id) => shares
+            .get(id)
+            .ok_or_else(|| format!("分享不存在：{}", id)),
+        None => {
+            let mut iter = shares.values();
+            match (iter.next(), iter.next()) {
+                (Some(instance), None) => Ok(instance),
+                (None, _) => Err("当前没有活跃的分享".to_string()),
+                (Some(_), Some(_)) => Err("存在多个分享，请指定分享 ID".to_string()),
+            }
+        }
+    }
+}
+
+/// 获取分享信息快照，并填充实时的下载数
+async fn share_info_snapshot(instance: &ShareInstance) -> Option<ShareLinkInfo> {
+    let mut info = instance.share_state.lock().await.share_info.clone()?;
+    info.active_downloads = instance.server.active_downloads().await;
+    info.refresh_expiry(current_timestamp_millis());
+    Some(info)
+}
+
+/// 撤销任一分享中的 HTTP 加密会话，会话不存在时返回 false
+pub async fn revoke_crypto_session(state: &ShareManagerState, session_id: &str) -> bool {
+    let shares = state.shares.lock().await;
+    for instance in shares.values() {
+        let mut crypto_sessions = instance.server.state.crypto_sessions.lock().await;
+        if crypto_sessions.remove_session(session_id) {
+            return true;
+        }
+    }
+    false
+}
+
+/// 自动接受时间窗结束事件载荷
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AutoAcceptExpiredEvent {
+    share_id: String,
+}
+
+/// 分享到期事件载荷
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ShareExpiredEvent {
+    share_id: String,
+}
+
+/// 在自动接受截止时关闭自动接受并通知前端
+///
+/// 截止前设置被再次修改（截止时间变化）时不做任何处理，由新的定时任务负责
+fn spawn_auto_accept_expiry(
+    app: AppHandle,
+    share_state: Arc<Mutex<ShareState>>,
+    share_id: String,
+    deadline: u64,
+) {
+    tokio::spawn(async move {
+        let remaining = deadline.saturating_sub(current_timestamp_millis());
+        tokio::time::sleep(std::time::Duration::from_millis(remaining)).await;
+
+        let mut share_state = share_state.lock().await;
+        if share_state.share_info.is_none() || share_state.settings.auto_accept_until != Some(deadline) {
+            return;
+        }
+        share_state.settings.auto_accept = false;
+        share_state.settings.auto_accept_until = None;
+        if let Some(info) = share_state.share_info.as_mut() {
+            info.auto_accept = false;
+        }
+        drop(share_state);
+
+        let _ = app.emit("share-auto-accept-expired", AutoAcceptExpiredEvent { share_id });
+    });
+}
+
+/// 分享到期后停止服务器并通知前端
+///
+/// 分享已被停止或重新开始（到期时间变化）时不做任何处理
+fn spawn_share_expiry(
+    app: AppHandle,
+    shares: Arc<Mutex<HashMap<String, ShareInstance>>>,
+    share_id: String,
+    deadline: u64,
+) {
+    tokio::spawn(async move {
+        let remaining = deadline.saturating_sub(current_timestamp_millis());
+        tokio::time::sleep(std::time::Duration::from_millis(remaining)).await;
+
+        let mut shares_guard = shares.lock().await;
+        let Some(instance) = shares_guard.get(&share_id) else {
+            return;
+        };
+        let current_deadline = instance
+            .share_state
+            .lock()
+            .await
+            .share_info
+            .as_ref()
+            .and_then(|info| info.expires_at);
+        if current_deadline != Some(deadline) {
+            return;
+        }
+        let Some(mut instance) = shares_guard.remove(&share_id) else {
+            return;
+        };
+        drop(shares_guard);
+        instance.server.stop();
+        instance.share_state.lock().await.stop_share();
+        tracing::info!(share_id = %share_id, "分享已到期，自动停止");
+
+        let _ = app.emit("share-expired", ShareExpiredEvent { share_id });
+    });
+}
+
+/// 分享空闲超时后自动停止
+///
+/// 每隔一段时间检查一次，分享被停止（不在列表中）后结束。仍有下载进行时视为活跃，
+/// 避免长时间下载结束后立即被判定为空闲
+fn spawn_idle_watch(
+    app: AppHandle,
+    shares: Arc<Mutex<HashMap<String, ShareInstance>>>,
+    share_id: String,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+            crate::idle::IDLE_CHECK_INTERVAL_SECS,
+        ));
+        loop {
+            interval.tick().await;
+
+            let mut shares_guard = shares.lock().await;
+            let Some(instance) = shares_guard.get(&share_id) else {
+                return;
+            };
+            let minutes = crate::idle::idle_timeout_minutes();
+            if minutes == 0 {
+                continue;
+            }
+            let downloading = instance.server.active_downloads().await > 0;
+            {
+                let mut share_state = instance.share_state.lock().await;
+                if downloading {
+                    share_state.touch();
+                    continue;
+                }
+                let now = current_timestamp_millis();
+                if !crate::idle::is_idle(share_state.last_activity, now, minutes) {
+                    continue;
+                }
+            }
+
+            let Some(mut instance) = shares_guard.remove(&share_id) else {
+                return;
+            };
+            drop(shares_guard);
+            instance.server.stop();
+            instance.share_state.lock().await.stop_share();
+            crate::idle::emit_auto_stopped_idle(
+                &app,
+                crate::idle::IdleService::Share,
+                Some(share_id),
+                minutes,
+            );
+            return;
+        }
+    });
+}
+
 /// 开始分享
+///
+/// 每次调用创建一个新的分享（独立端口），已有分享不受影响。
+/// `bind_address` 为本机网卡地址时只在该网卡上监听，链接也只包含该地址；
+/// 未指定时沿用全局监听地址设置
 #[tauri::command]
 pub async fn start_share(
     app: AppHandle,
@@ -40,7 +231,12 @@ pub async fn start_share(
     files: Vec<FileMetadata>,
     settings: ShareSettings,
     preferred_port: Option<u16>,
+    expiry_seconds: Option<u64>,
+    bind_address: Option<String>,
 ) -> Result<ShareLinkInfo, String> {
+    let bind = crate::transfer::parse_http_bind_address(bind_address.as_deref())?
+        .or_else(crate::http_common::http_bind_address);
+
     // 验证文件存在性并收集路径
     let mut file_paths: Vec<(FileMetadata, PathBuf)> = Vec::new();
     let mut valid_files: Vec<FileMetadata> = Vec::new();
@@ -55,22 +251,23 @@ pub async fn start_share(
         valid_files.push(file.clone());
     }
 
+    let share_state = Arc::new(Mutex::new(ShareState::new()));
+
     // 创建并启动服务器（优先使用首选端口，失败则自动分配）
     let port = preferred_port.unwrap_or(0);
-    let mut server = ShareServer::new(state.share_state.clone(), app.clone(), port);
+    let mut server = ShareServer::new(share_state.clone(), app.clone(), bind, port);
 
     let actual_port = match server.start(file_paths.clone()).await {
         Ok(p) => p,
         Err(_) if port != 0 => {
-            server = ShareServer::new(state.share_state.clone(), app, 0);
+            server = ShareServer::new(share_state.clone(), app.clone(), bind, 0);
             server.start(file_paths).await?
         }
         Err(e) => return Err(e),
     };
 
-    // 获取本机 IP 地址
-    let local_ips = crate::network::get_local_ips();
-    let links: Vec<String> = local_ips.iter().map(|ip| format!("http://{}:{}", ip, actual_port)).collect();
+    // 生成访问链接（限定监听网卡时只包含该地址）
+    let links = crate::http_common::links_for(bind, actual_port);
 
     // 创建分享信息
     let mut share_info = ShareLinkInfo::new(links, actual_port, valid_files);
@@ -81,37 +278,77 @@ pub async fn start_share(
         }
     }
 
-    share_info = share_info.with_auto_accept(settings.auto_accept);
+    share_info = share_info
+        .with_auto_accept(settings.auto_accept)
+        .with_expiry(expiry_seconds);
+    let auto_accept_until = settings.auto_accept.then_some(settings.auto_accept_until).flatten();
 
     // 更新分享状态，同时传入设置信息
     {
-        let mut share_state = state.share_state.lock().await;
+        let mut share_state = share_state.lock().await;
         share_state.start_share(share_info.clone(), settings);
     }
 
-    // 保存服务器实例
+    if let Some(deadline) = auto_accept_until {
+        spawn_auto_accept_expiry(
+            app.clone(),
+            share_state.clone(),
+            share_info.share_id.clone(),
+            deadline,
+        );
+    }
+
+    // 保存分享实例
     {
-        let mut server_guard = state.server.lock().await;
-        *server_guard = Some(server);
+        let mut shares = state.shares.lock().await;
+        shares.insert(
+            share_info.share_id.clone(),
+            ShareInstance {
+                share_state,
+                server,
+            },
+        );
     }
 
+    if let Some(deadline) = share_info.expires_at {
+        spawn_share_expiry(
+            app.clone(),
+            state.shares.clone(),
+            share_info.share_id.clone(),
+            deadline,
+        );
+    }
+
+    spawn_idle_watch(app, state.shares.clone(), share_info.share_id.clone());
+
     Ok(share_info)
 }
 
 /// 停止分享
+///
+/// 指定分享 ID 时只停止该分享，否则停止所有分享
 #[tauri::command]
-pub async fn stop_share(state: State<'_, ShareManagerState>) -> Result<(), String> {
-    // 停止服务器
-    {
-        let mut server_guard = state.server.lock().await;
-        if let Some(mut server) = server_guard.take() {
-            server.stop();
+pub async fn stop_share(
+    state: State<'_, ShareManagerState>,
+    share_id: Option<String>,
+) -> Result<(), String> {
+    let stopped: Vec<ShareInstance> = {
+        let mut shares = state.shares.lock().await;
+        match share_id {
+            Some(id) => shares
+                .remove(&id)
+                .map(|instance| vec![instance])
+                .ok_or_else(|| format!("分享不存在：{}", id))?,
+            None => shares.drain().map(|(_, instance)| instance).collect(),
         }
-    }
+    };
 
-    // 清理分享状态
-    {
-        let mut share_state = state.share_state.lock().await;
+    for mut instance in stopped {
+        // 停止服务器
+        instance.server.stop();
+
+        // 清理分享状态
+        let mut share_state = instance.share_state.lock().await;
         share_state.stop_share();
     }
 
@@ -119,36 +356,223 @@ pub async fn stop_share(state: State<'_, ShareManagerState>) -> Result<(), Strin
 }
 
 /// 获取分享信息
+///
+/// 指定分享 ID 时返回该分享；未指定时返回最近创建的分享
 #[tauri::command]
 pub async fn get_share_info(
     state: State<'_, ShareManagerState>,
+    share_id: Option<String>,
 ) -> Result<Option<ShareLinkInfo>, String> {
-    let share_state = state.share_state.lock().await;
-    Ok(share_state.share_info.clone())
+    let shares = state.shares.lock().await;
+
+    if let Some(id) = share_id {
+        return match shares.get(&id) {
+            Some(instance) => Ok(share_info_snapshot(instance).await),
+            None => Ok(None),
+        };
+    }
+
+    let mut latest: Option<ShareLinkInfo> = None;
+    for instance in shares.values() {
+        if let Some(info) = share_info_snapshot(instance).await {
+            if latest.as_ref().is_none_or(|l| info.created_at > l.created_at) {
+                latest = Some(info);
+            }
+        }
+    }
+
+    Ok(latest)
+}
+
+/// 获取所有活跃分享（按创建时间排序）
+#[tauri::command]
+pub async fn list_shares(state: State<'_, ShareManagerState>) -> Result<Vec<ShareLinkInfo>, String> {
+    let shares = state.shares.lock().await;
+
+    let mut infos = Vec::with_capacity(shares.len());
+    for instance in shares.values() {
+        if let Some(info) = share_info_snapshot(instance).await {
+            infos.push(info);
+        }
+    }
+    infos.sort_by_key(|info| info.created_at);
+
+    Ok(infos)
+}
+
+/// 二维码图片的最小边长（像素）
+const MIN_QR_CODE_SIZE: u32 = 128;
+
+/// 二维码图片的最大边长（像素）
+const MAX_QR_CODE_SIZE: u32 = 1024;
+
+/// 将文本渲染为 PNG 二维码，返回 base64 data URI
+///
+/// 边长不超过 `size`，模块按整数像素绘制，保留标准空白边
+fn render_qr_code_data_uri(text: &str, size: u32) -> Result<String, String> {
+    use base64::Engine;
+
+    let code =
+        qrcode::QrCode::new(text.as_bytes()).map_err(|e| format!("生成二维码失败: {}", e))?;
+    let image = code
+        .render::<image::Luma<u8>>()
+        .max_dimensions(size, size)
+        .build();
+
+    let mut png = std::io::Cursor::new(Vec::new());
+    image::DynamicImage::ImageLuma8(image)
+        .write_to(&mut png, image::ImageFormat::Png)
+        .map_err(|e| format!("编码二维码图片失败: {}", e))?;
+
+    Ok(format!(
+        "data:image/png;base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(png.into_inner())
+    ))
+}
+
+/// 获取分享链接的二维码（PNG data URI）
+///
+/// `size` 为图片边长（像素），限制在 128–1024 之间；`link` 须为该分享的链接之一，
+/// 未指定时使用第一个链接。没有活跃分享时返回错误
+#[tauri::command]
+pub async fn get_share_qr_code(
+    state: State<'_, ShareManagerState>,
+    size: u32,
+    share_id: Option<String>,
+    link: Option<String>,
+) -> Result<String, String> {
+    let info = {
+        let shares = state.shares.lock().await;
+        let instance = resolve_share(&shares, share_id.as_deref())?;
+        let share_state = instance.share_state.lock().await;
+        share_state
+            .share_info
+            .clone()
+            .ok_or_else(|| "当前没有活跃的分享".to_string())?
+    };
+
+    let link = match link {
+        Some(link) if info.links.contains(&link) => link,
+        Some(link) => return Err(format!("不是该分享的链接：{}", link)),
+        None => info
+            .links
+            .first()
+            .cloned()
+            .ok_or_else(|| "分享没有可用的链接".to_string())?,
+    };
+
+    render_qr_code_data_uri(&link, size.clamp(MIN_QR_CODE_SIZE, MAX_QR_CODE_SIZE))
 }
 
 /// 获取访问请求列表
+///
+/// 指定分享 ID 时只返回该分享的请求，否则返回所有分享的请求；
+/// 结果按请求时间从新到旧排列，可通过 `offset` / `limit` 分页
 #[tauri::command]
 pub async fn get_access_requests(
     state: State<'_, ShareManagerState>,
+    share_id: Option<String>,
+    offset: Option<usize>,
+    limit: Option<usize>,
 ) -> Result<Vec<AccessRequest>, String> {
-    let share_state = state.share_state.lock().await;
-    Ok(share_state.access_requests.values().cloned().collect())
+    let shares = state.shares.lock().await;
+
+    let mut requests = Vec::new();
+    for (id, instance) in shares.iter() {
+        if share_id.as_ref().is_some_and(|target| target != id) {
+            continue;
+        }
+        let share_state = instance.share_state.lock().await;
+        requests.extend(share_state.access_requests.values().cloned());
+    }
+
+    requests.sort_by(|a, b| b.requested_at.cmp(&a.requested_at));
+    Ok(requests
+        .into_iter()
+        .skip(offset.unwrap_or(0))
+        .take(limit.unwrap_or(usize::MAX))
+        .collect())
 }
 
 /// 接受访问请求
+///
+/// `remember` 为 true 时记住该 IP，本次分享中后续请求自动接受；
+/// `persist` 为 true 时永久记住，之后的分享同样自动接受
 #[tauri::command]
 pub async fn accept_access_request(
     app: AppHandle,
     state: State<'_, ShareManagerState>,
     request_id: String,
+    remember: Option<bool>,
+    persist: Option<bool>,
 ) -> Result<(), String> {
-    let mut share_state = state.share_state.lock().await;
+    let shares = state.shares.lock().await;
+
+    // 请求 ID 全局唯一，依次在各分享中查找
+    for instance in shares.values() {
+        let mut share_state = instance.share_state.lock().await;
+        if let Some(request) = share_state.accept_request(&request_id) {
+            let request = request.clone();
+            if persist.unwrap_or(false) {
+                super::trusted::add_trusted_ip(&request.ip)?;
+            } else if remember.unwrap_or(false) {
+                share_state.trusted_ips.insert(request.ip.clone());
+            }
+            let _ = app.emit("access-request-accepted", request);
+            return Ok(());
+        }
+    }
+
+    Err("请求不存在".to_string())
+}
+
+/// 接受所有待处理的访问请求
+///
+/// 指定分享 ID 时只处理该分享，否则处理所有分享；返回接受的请求数
+#[tauri::command]
+pub async fn accept_all_pending_access_requests(
+    app: AppHandle,
+    state: State<'_, ShareManagerState>,
+    share_id: Option<String>,
+) -> Result<usize, String> {
+    let shares = state.shares.lock().await;
+
+    // 持有分享列表锁完成全部处理，期间不会有单个请求被并发处理
+    let mut accepted = Vec::new();
+    for (id, instance) in shares.iter() {
+        if share_id.as_ref().is_some_and(|target| target != id) {
+            continue;
+        }
+        accepted.extend(instance.share_state.lock().await.accept_all_pending());
+    }
 
-    if let Some(request) = share_state.accept_request(&request_id) {
-        let _ = app.emit("access-request-accepted", request.clone());
-    } else {
-        return Err("请求不存在".to_string());
+    let count = accepted.len();
+    for request in accepted {
+        let _ = app.emit("access-request-accepted", request);
+    }
+
+    Ok(count)
+}
+
+/// 获取永久记住的受信任 IP 列表
+#[tauri::command]
+pub async fn list_trusted_ips() -> Result<Vec<String>, String> {
+    Ok(super::trusted::trusted_ips())
+}
+
+/// 取消信任 IP
+///
+/// 同时从永久列表和所有分享的本次记住列表中移除，已接受的请求不受影响
+#[tauri::command]
+pub async fn forget_trusted_ip(
+    state: State<'_, ShareManagerState>,
+    ip: String,
+) -> Result<(), String> {
+    super::trusted::remove_trusted_ip(&ip)?;
+
+    let shares = state.shares.lock().await;
+    for instance in shares.values() {
+        instance.share_state.lock().await.trusted_ips.remove(&ip);
     }
 
     Ok(())
@@ -161,15 +585,76 @@ pub async fn reject_access_request(
     state: State<'_, ShareManagerState>,
     request_id: String,
 ) -> Result<(), String> {
-    let mut share_state = state.share_state.lock().await;
+    let shares = state.shares.lock().await;
+
+    for instance in shares.values() {
+        let mut share_state = instance.share_state.lock().await;
+        if let Some(request) = share_state.reject_request(&request_id).cloned() {
+            emit_if_rejection_limit_reached(&app, &share_state, &request.ip);
+            let _ = app.emit("access-request-rejected", request);
+            return Ok(());
+        }
+    }
 
-    if let Some(request) = share_state.reject_request(&request_id) {
-        let _ = app.emit("access-request-rejected", request.clone());
-    } else {
-        return Err("请求不存在".to_string());
+    Err("请求不存在".to_string())
+}
+
+/// 拒绝所有待处理的访问请求
+///
+/// 指定分享 ID 时只处理该分享，否则处理所有分享；返回拒绝的请求数
+#[tauri::command]
+pub async fn reject_all_pending_access_requests(
+    app: AppHandle,
+    state: State<'_, ShareManagerState>,
+    share_id: Option<String>,
+) -> Result<usize, String> {
+    let shares = state.shares.lock().await;
+
+    let mut rejected = Vec::new();
+    for (id, instance) in shares.iter() {
+        if share_id.as_ref().is_some_and(|target| target != id) {
+            continue;
+        }
+        let mut share_state = instance.share_state.lock().await;
+        let requests = share_state.reject_all_pending();
+        for request in &requests {
+            emit_if_rejection_limit_reached(&app, &share_state, &request.ip);
+        }
+        rejected.extend(requests);
     }
 
-    Ok(())
+    let count = rejected.len();
+    for request in rejected {
+        let _ = app.emit("access-request-rejected", request);
+    }
+
+    Ok(count)
+}
+
+/// `access-ip-auto-rejected` 事件载荷
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AccessIpAutoRejectedEvent {
+    /// 被自动拒绝的 IP
+    ip: String,
+    /// 累计被拒绝次数
+    rejections: u32,
+}
+
+/// IP 的被拒绝次数刚达到上限时通知前端，之后该 IP 的请求会被自动拒绝
+fn emit_if_rejection_limit_reached(app: &AppHandle, share_state: &ShareState, ip: &str) {
+    if !share_state.just_reached_rejection_limit(ip) {
+        return;
+    }
+    let rejections = share_state.rejection_counts.get(ip).copied().unwrap_or(0);
+    tracing::info!(ip, rejections, "访问请求被拒绝次数达到上限，后续自动拒绝");
+    let _ = app.emit(
+        "access-ip-auto-rejected",
+        AccessIpAutoRejectedEvent {
+            ip: ip.to_string(),
+            rejections,
+        },
+    );
 }
 
 /// 移除单个访问请求
@@ -179,29 +664,40 @@ pub async fn remove_access_request(
     state: State<'_, ShareManagerState>,
     request_id: String,
 ) -> Result<(), String> {
-    let mut share_state = state.share_state.lock().await;
-
-    if share_state.remove_request(&request_id).is_some() {
-        // 发送事件通知
-        let _ = app.emit("access-request-removed", request_id);
-    } else {
-        return Err("请求不存在".to_string());
+    let shares = state.shares.lock().await;
+
+    for instance in shares.values() {
+        let mut share_state = instance.share_state.lock().await;
+        if share_state.remove_request(&request_id).is_some() {
+            // 发送事件通知
+            let _ = app.emit("access-request-removed", request_id);
+            return Ok(());
+        }
     }
 
-    Ok(())
+    Err("请求不存在".to_string())
 }
 
 /// 移除所有访问请求
+///
+/// 指定分享 ID 时只清理该分享，否则清理所有分享
 #[tauri::command]
 pub async fn clear_access_requests(
     app: AppHandle,
     state: State<'_, ShareManagerState>,
+    share_id: Option<String>,
 ) -> Result<(), String> {
-    let mut share_state = state.share_state.lock().await;
+    let shares = state.shares.lock().await;
 
-    let removed_ids: Vec<String> = share_state.access_requests.keys().cloned().collect();
-
-    share_state.access_requests.clear();
+    let mut removed_ids: Vec<String> = Vec::new();
+    for (id, instance) in shares.iter() {
+        if share_id.as_ref().is_some_and(|target| target != id) {
+            continue;
+        }
+        let mut share_state = instance.share_state.lock().await;
+        removed_ids.extend(share_state.access_requests.keys().cloned());
+        share_state.access_requests.clear();
+    }
 
     // 发送事件通知
     for request_id in removed_ids {
@@ -211,11 +707,36 @@ pub async fn clear_access_requests(
     Ok(())
 }
 
+/// 清除已结束的上传记录
+///
+/// 指定分享 ID 时只清理该分享，否则清理所有分享；进行中的记录保留，返回清除的记录数
+#[tauri::command]
+pub async fn clear_completed_records(
+    state: State<'_, ShareManagerState>,
+    share_id: Option<String>,
+) -> Result<usize, String> {
+    let shares = state.shares.lock().await;
+
+    let mut cleared = 0;
+    for (id, instance) in shares.iter() {
+        if share_id.as_ref().is_some_and(|target| target != id) {
+            continue;
+        }
+        let mut share_state = instance.share_state.lock().await;
+        for request in share_state.access_requests.values_mut() {
+            cleared += request.clear_finished_records();
+        }
+    }
+
+    Ok(cleared)
+}
+
 /// 更新分享文件列表（动态同步已选文件到 HTTP 服务器）
 #[tauri::command]
 pub async fn update_share_files(
     state: State<'_, ShareManagerState>,
     files: Vec<FileMetadata>,
+    share_id: Option<String>,
 ) -> Result<(), String> {
     // 验证文件存在性并收集路径
     let mut new_file_paths: Vec<(FileMetadata, std::path::PathBuf)> = Vec::new();
@@ -231,38 +752,38 @@ pub async fn update_share_files(
         valid_files.push(file.clone());
     }
 
+    let shares = state.shares.lock().await;
+    let instance = resolve_share(&shares, share_id.as_deref())?;
+
     // 更新服务器的文件映射
     {
-        let server_guard = state.server.lock().await;
-        if let Some(server) = server_guard.as_ref() {
-            let mut file_paths = server.state.file_paths.lock().await;
-            let mut hash_to_filename = server.state.hash_to_filename.lock().await;
-
-            // 清空旧映射
-            file_paths.clear();
-            hash_to_filename.clear();
-
-            // 重建映射
-            for (metadata, path) in new_file_paths {
-                use sha2::{Digest, Sha256};
-                let hash = Sha256::digest(path.to_string_lossy().as_bytes());
-                let hash_id = hex::encode(hash);
-
-                let file_name = path
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or(&metadata.name)
-                    .to_string();
-
-                file_paths.insert(hash_id.clone(), path);
-                hash_to_filename.insert(hash_id, file_name);
-            }
+        let mut file_paths = instance.server.state.file_paths.lock().await;
+        let mut hash_to_filename = instance.server.state.hash_to_filename.lock().await;
+
+        // 清空旧映射
+        file_paths.clear();
+        hash_to_filename.clear();
+
+        // 重建映射
+        for (metadata, path) in new_file_paths {
+            use sha2::{Digest, Sha256};
+            let hash = Sha256::digest(path.to_string_lossy().as_bytes());
+            let hash_id = hex::encode(hash);
+
+            let file_name = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or(&metadata.name)
+                .to_string();
+
+            file_paths.insert(hash_id.clone(), path);
+            hash_to_filename.insert(hash_id, file_name);
         }
     }
 
     // 更新 share_state 中的文件列表
     {
-        let mut share_state = state.share_state.lock().await;
+        let mut share_state = instance.share_state.lock().await;
         if let Some(ref mut share_info) = share_state.share_info {
             share_info.files = valid_files;
         }
@@ -272,13 +793,63 @@ pub async fn update_share_files(
 }
 
 /// 更新分享设置
+///
+/// 指定 `auto_accept_duration_secs` 时开启限时自动接受，到期后新访客重新需要确认
 #[tauri::command]
 pub async fn update_share_settings(
+    app: AppHandle,
     state: State<'_, ShareManagerState>,
-    settings: ShareSettings,
+    mut settings: ShareSettings,
+    share_id: Option<String>,
+    auto_accept_duration_secs: Option<u64>,
 ) -> Result<(), String> {
-    let mut share_state = state.share_state.lock().await;
-    share_state.settings = settings;
+    let shares = state.shares.lock().await;
+    let instance = resolve_share(&shares, share_id.as_deref())?;
+
+    if let Some(secs) = auto_accept_duration_secs {
+        settings.auto_accept = true;
+        settings.auto_accept_until = Some(current_timestamp_millis() + secs * 1000);
+    }
+    let auto_accept_until = settings.auto_accept.then_some(settings.auto_accept_until).flatten();
+
+    let share_id = {
+        let mut share_state = instance.share_state.lock().await;
+        share_state.settings = settings;
+        share_state.share_info.as_ref().map(|info| info.share_id.clone())
+    };
+
+    if let (Some(deadline), Some(share_id)) = (auto_accept_until, share_id) {
+        spawn_auto_accept_expiry(app, instance.share_state.clone(), share_id, deadline);
+    }
     Ok(())
 }
 
+/// 获取分享下载限速（字节/秒），None 表示不限速
+#[tauri::command]
+pub async fn get_share_bandwidth_limit() -> Result<Option<u64>, String> {
+    Ok(super::bandwidth::bandwidth_limit())
+}
+
+/// 设置分享下载限速（字节/秒）
+///
+/// 限速由所有分享的全部下载连接共享，None 或 0 表示不限速，修改立即生效
+#[tauri::command]
+pub async fn set_share_bandwidth_limit(bytes_per_sec: Option<u64>) -> Result<(), String> {
+    super::bandwidth::set_bandwidth_limit_internal(bytes_per_sec);
+    Ok(())
+}
+
+/// 获取每分钟每个 IP 允许新建的访问请求数，0 表示不限制
+#[tauri::command]
+pub async fn get_share_request_rate_limit() -> Result<u32, String> {
+    Ok(super::request_limit::request_rate_limit())
+}
+
+/// 设置每分钟每个 IP 允许新建的访问请求数（默认 20）
+///
+/// 超过上限的访问返回 429，查询已有请求状态的轮询不计入，0 表示不限制
+#[tauri::command]
+pub async fn set_share_request_rate_limit(per_minute: u32) -> Result<(), String> {
+    super::request_limit::set_request_rate_limit_internal(per_minute);
+    Ok(())
+}
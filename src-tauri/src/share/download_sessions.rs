@@ -0,0 +1,102 @@
+//! 分块下载会话持久化
+//!
+//! 加密或压缩分享按分块下载，已下载的分块按文件和客户端 IP 保存到应用数据目录，
+//! 应用重启后重新分享同一文件时恢复会话，客户端继续下载时不会重新开始计数
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// 持久化文件名
+const DOWNLOAD_SESSIONS_FILENAME: &str = "share_download_sessions.json";
+
+/// 会话最长保留时间（毫秒），超过后加载时丢弃
+const MAX_SESSION_AGE_MS: u64 = 24 * 60 * 60 * 1000;
+
+fn download_sessions_path() -> PathBuf {
+    crate::app_data_dir().join(DOWNLOAD_SESSIONS_FILENAME)
+}
+
+/// 持久化的分块下载会话
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct PersistedDownloadSession {
+    /// 文件 ID
+    pub file_id: String,
+    /// 客户端 IP
+    pub client_ip: String,
+    /// 对应的上传记录 ID
+    pub upload_id: String,
+    /// 文件名
+    pub file_name: String,
+    /// 文件大小
+    pub file_size: u64,
+    /// 分块总数
+    pub chunk_count: usize,
+    /// 已下载的分块索引
+    pub downloaded_chunks: Vec<usize>,
+    /// 最近一次下载分块的时间（毫秒）
+    pub updated_at: u64,
+}
+
+fn load(path: &Path, now: u64) -> Vec<PersistedDownloadSession> {
+    std::fs::read(path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<Vec<PersistedDownloadSession>>(&bytes).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|session| now.saturating_sub(session.updated_at) < MAX_SESSION_AGE_MS)
+        .collect()
+}
+
+fn save(path: &Path, sessions: &[PersistedDownloadSession]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("无法创建目录: {}", e))?;
+    }
+    let json = serde_json::to_vec(sessions).map_err(|e| e.to_string())?;
+    std::fs::write(path, json).map_err(|e| format!("保存分块下载会话失败: {}", e))
+}
+
+/// 加载持久化的分块下载会话，丢弃超过 24 小时未活动的会话
+pub(super) fn load_download_sessions(now: u64) -> Vec<PersistedDownloadSession> {
+    load(&download_sessions_path(), now)
+}
+
+/// 保存当前的分块下载会话（覆盖原有内容）
+pub(super) fn save_download_sessions(sessions: &[PersistedDownloadSession]) -> Result<(), String> {
+    save(&download_sessions_path(), sessions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(file_id: &str, updated_at: u64) -> PersistedDownloadSession {
+        PersistedDownloadSession {
+            file_id: file_id.to_string(),
+            client_ip: "192.168.1.20".to_string(),
+            upload_id: "upload".to_string(),
+            file_name: "video.mp4".to_string(),
+            file_size: 10,
+            chunk_count: 3,
+            downloaded_chunks: vec![0, 2],
+            updated_at,
+        }
+    }
+
+    #[test]
+    fn test_save_and_load_prunes_stale() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(DOWNLOAD_SESSIONS_FILENAME);
+        let now = 10 * MAX_SESSION_AGE_MS;
+        assert!(load(&path, now).is_empty());
+
+        let fresh = session("fresh", now - 1000);
+        let stale = session("stale", now - MAX_SESSION_AGE_MS);
+        save(&path, &[fresh.clone(), stale]).unwrap();
+        assert_eq!(load(&path, now), vec![fresh]);
+
+        // 文件损坏时视为没有会话
+        std::fs::write(&path, b"not json").unwrap();
+        assert!(load(&path, now).is_empty());
+    }
+}
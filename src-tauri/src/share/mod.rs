@@ -2,8 +2,15 @@
 //!
 //! 提供 HTTP 服务器用于链接分享
 
+mod bandwidth;
 mod commands;
+mod download_sessions;
 mod models;
+mod request_limit;
+mod served_ranges;
 mod server;
+mod trusted;
+mod zip_archive;
 
 pub use commands::*;
+pub(crate) use models::current_timestamp_millis;
@@ -1,7 +1,7 @@
 //! 分享相关数据模型
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
 use crate::models::FileMetadata;
@@ -10,9 +10,11 @@ use crate::models::FileMetadata;
 const PIN_LOCK_DURATION_MS: u64 = 5 * 60 * 1000;
 /// PIN 验证最大失败次数
 const MAX_PIN_ATTEMPTS: u32 = 3;
+/// 每个访问请求保留的上传记录上限，超出部分合并为汇总
+pub const MAX_UPLOAD_RECORDS_PER_REQUEST: usize = 100;
 
 /// 获取当前时间戳（毫秒），如果系统时钟异常则返回 0
-fn current_timestamp_millis() -> u64 {
+pub(crate) fn current_timestamp_millis() -> u64 {
     std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap_or_default()
@@ -23,6 +25,8 @@ fn current_timestamp_millis() -> u64 {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ShareLinkInfo {
+    /// 分享 ID（同时运行多个分享时用于区分）
+    pub share_id: String,
     /// 分享链接列表
     pub links: Vec<String>,
     /// HTTP 服务器端口
@@ -40,6 +44,15 @@ pub struct ShareLinkInfo {
     pub auto_accept: bool,
     /// 分享状态
     pub status: ShareStatus,
+    /// 当前进行中的下载数（查询时实时填充）
+    #[serde(default)]
+    pub active_downloads: usize,
+    /// 自动到期时间戳（毫秒），为空表示不会自动到期
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+    /// 距离到期的剩余秒数（查询时实时填充）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remaining_seconds: Option<u64>,
 }
 
 impl ShareLinkInfo {
@@ -48,6 +61,7 @@ impl ShareLinkInfo {
         let now = current_timestamp_millis();
 
         Self {
+            share_id: Uuid::new_v4().to_string(),
             links,
             port,
             files,
@@ -56,6 +70,34 @@ impl ShareLinkInfo {
             pin: None,
             auto_accept: false,
             status: ShareStatus::Active,
+            active_downloads: 0,
+            expires_at: None,
+            remaining_seconds: None,
+        }
+    }
+
+    /// 设置自动到期时长（秒），为空或 0 表示不自动到期
+    pub fn with_expiry(mut self, expiry_seconds: Option<u64>) -> Self {
+        let expiry_seconds = expiry_seconds.filter(|&secs| secs > 0);
+        self.expires_at =
+            expiry_seconds.map(|secs| self.created_at.saturating_add(secs.saturating_mul(1000)));
+        self.remaining_seconds = expiry_seconds;
+        self
+    }
+
+    /// 是否已超过自动到期时间
+    pub fn is_expired_at(&self, now: u64) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+
+    /// 按当前时间刷新剩余秒数和到期状态
+    pub fn refresh_expiry(&mut self, now: u64) {
+        let Some(expires_at) = self.expires_at else {
+            return;
+        };
+        self.remaining_seconds = Some(expires_at.saturating_sub(now).div_ceil(1000));
+        if self.is_expired_at(now) {
+            self.status = ShareStatus::Expired;
         }
     }
 
@@ -158,6 +200,36 @@ impl ShareUploadRecord {
             completed_at: None,
         }
     }
+
+    /// 是否已结束（完成、取消或失败）
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self.status,
+            TransferStatus::Completed | TransferStatus::Cancelled | TransferStatus::Failed
+        )
+    }
+}
+
+/// 已移出列表的上传记录汇总
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchivedRecordSummary {
+    /// 记录数
+    pub count: u64,
+    /// 其中已完成的记录数
+    pub completed: u64,
+    /// 已传输字节数合计
+    pub transferred_bytes: u64,
+}
+
+impl ArchivedRecordSummary {
+    fn absorb(&mut self, record: &ShareUploadRecord) {
+        self.count += 1;
+        if record.status == TransferStatus::Completed {
+            self.completed += 1;
+        }
+        self.transferred_bytes += record.uploaded_bytes;
+    }
 }
 
 /// PIN 尝试状态（用于 PIN 验证前的锁定机制）
@@ -250,8 +322,11 @@ pub struct AccessRequest {
     /// 用户代理（浏览器/平台信息，如 "Chrome(Android)"）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub user_agent: Option<String>,
-    /// 上传记录列表
+    /// 上传记录列表（最新在前）
     pub upload_records: Vec<ShareUploadRecord>,
+    /// 超出保留上限后移出列表的记录汇总
+    #[serde(default)]
+    pub archived_records: ArchivedRecordSummary,
 }
 
 impl AccessRequest {
@@ -269,9 +344,34 @@ impl AccessRequest {
             locked_until: None,
             user_agent,
             upload_records: Vec::new(),
+            archived_records: ArchivedRecordSummary::default(),
         }
     }
 
+    /// 添加上传记录（插入到最前）
+    ///
+    /// 超出保留上限时从最早的已结束记录开始移出并计入汇总，进行中的记录不会被移出
+    pub fn push_upload_record(&mut self, record: ShareUploadRecord) {
+        self.upload_records.insert(0, record);
+
+        let mut index = self.upload_records.len();
+        while self.upload_records.len() > MAX_UPLOAD_RECORDS_PER_REQUEST && index > 0 {
+            index -= 1;
+            if self.upload_records[index].is_finished() {
+                let removed = self.upload_records.remove(index);
+                self.archived_records.absorb(&removed);
+            }
+        }
+    }
+
+    /// 清除已结束的上传记录及汇总，返回清除的记录数
+    pub fn clear_finished_records(&mut self) -> usize {
+        let before = self.upload_records.len();
+        self.upload_records.retain(|record| !record.is_finished());
+        self.archived_records = ArchivedRecordSummary::default();
+        before - self.upload_records.len()
+    }
+
     /// 接受请求
     pub fn accept(&mut self) {
         self.status = AccessRequestStatus::Accepted;
@@ -312,6 +412,59 @@ pub struct ShareSettings {
     pub pin: Option<String>,
     /// 是否自动接受所有访问请求
     pub auto_accept: bool,
+    /// 自动接受截止时间戳（毫秒），为空表示一直有效
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_accept_until: Option<u64>,
+    /// 同时进行的最大下载数，为空或 0 表示不限制
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_downloads: Option<u32>,
+    /// 允许在浏览器内预览的扩展名（不含点），为空时使用默认列表
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview_extensions: Option<Vec<String>>,
+    /// 访问获准（请求被接受或 PIN 验证通过）前隐藏服务能力等细节
+    #[serde(default)]
+    pub hide_details_before_access: bool,
+    /// 同一 IP 的请求被拒绝达到该次数后，本次分享内自动拒绝其后续请求；为空或 0 表示不限制
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_rejections: Option<u32>,
+    /// 每个文件允许完整下载的次数，用尽后拒绝该文件的下载；为空或 0 表示不限制
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_downloads_per_file: Option<u32>,
+}
+
+/// 默认允许内联预览的扩展名：常见图片、纯文本与 PDF
+///
+/// 不包含 HTML、SVG 等可在分享页源中执行脚本的类型
+pub const DEFAULT_PREVIEW_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "webp", "bmp", "avif", "txt", "log", "md", "csv", "pdf",
+];
+
+impl ShareSettings {
+    /// 当前是否处于自动接受状态（已开启且未超过截止时间）
+    pub fn is_auto_accept_active(&self) -> bool {
+        self.auto_accept
+            && self
+                .auto_accept_until
+                .is_none_or(|until| current_timestamp_millis() < until)
+    }
+
+    /// 文件是否允许在浏览器内联预览
+    pub fn is_preview_allowed(&self, file_name: &str) -> bool {
+        let Some(ext) = std::path::Path::new(file_name)
+            .extension()
+            .and_then(|e| e.to_str())
+        else {
+            return false;
+        };
+        match &self.preview_extensions {
+            Some(allowed) => allowed
+                .iter()
+                .any(|a| a.trim_start_matches('.').eq_ignore_ascii_case(ext)),
+            None => DEFAULT_PREVIEW_EXTENSIONS
+                .iter()
+                .any(|a| a.eq_ignore_ascii_case(ext)),
+        }
+    }
 }
 
 impl Default for ShareSettings {
@@ -320,6 +473,12 @@ impl Default for ShareSettings {
             pin_enabled: false,
             pin: None,
             auto_accept: false,
+            auto_accept_until: None,
+            max_concurrent_downloads: None,
+            preview_extensions: None,
+            hide_details_before_access: false,
+            max_rejections: None,
+            max_downloads_per_file: None,
         }
     }
 }
@@ -375,8 +534,16 @@ pub struct ShareState {
     pub verified_ips: Vec<String>,
     /// 被拒绝的 IP 地址
     pub rejected_ips: Vec<String>,
+    /// 本次分享期间记住的 IP，后续请求自动接受
+    pub trusted_ips: HashSet<String>,
     /// PIN 尝试状态（IP -> PinAttemptState）
     pub pin_attempts: HashMap<String, PinAttemptState>,
+    /// 各 IP 的请求被拒绝的次数
+    pub rejection_counts: HashMap<String, u32>,
+    /// 被拒绝次数达到上限、后续请求自动拒绝的 IP
+    pub auto_rejected_ips: HashSet<String>,
+    /// 最近一次客户端交互时间（毫秒），用于空闲自动停止
+    pub last_activity: u64,
 }
 
 impl ShareState {
@@ -388,7 +555,11 @@ impl ShareState {
             settings: ShareSettings::default(),
             verified_ips: Vec::new(),
             rejected_ips: Vec::new(),
+            trusted_ips: HashSet::new(),
             pin_attempts: HashMap::new(),
+            rejection_counts: HashMap::new(),
+            auto_rejected_ips: HashSet::new(),
+            last_activity: current_timestamp_millis(),
         }
     }
 
@@ -399,6 +570,23 @@ impl ShareState {
         self.access_requests.clear();
         self.verified_ips.clear();
         self.rejected_ips.clear();
+        self.trusted_ips.clear();
+        self.rejection_counts.clear();
+        self.auto_rejected_ips.clear();
+        self.touch();
+    }
+
+    /// 记录一次客户端交互
+    pub fn touch(&mut self) {
+        self.last_activity = current_timestamp_millis();
+    }
+
+    /// 分享已停止或已到期，不再提供访问
+    pub fn is_share_ended(&self) -> bool {
+        match &self.share_info {
+            Some(info) => info.is_expired_at(current_timestamp_millis()),
+            None => true,
+        }
     }
 
     /// 停止分享
@@ -410,7 +598,10 @@ impl ShareState {
         self.access_requests.clear();
         self.verified_ips.clear();
         self.rejected_ips.clear();
+        self.trusted_ips.clear();
         self.pin_attempts.clear();
+        self.rejection_counts.clear();
+        self.auto_rejected_ips.clear();
     }
 
     /// 接受访问请求
@@ -420,8 +611,10 @@ impl ShareState {
             if !self.verified_ips.contains(&request.ip) {
                 self.verified_ips.push(request.ip.clone());
             }
-            // 从拒绝列表中移除（如果存在）
+            // 从拒绝列表中移除（如果存在），手动接受也解除自动拒绝
             self.rejected_ips.retain(|ip| ip != &request.ip);
+            self.rejection_counts.remove(&request.ip);
+            self.auto_rejected_ips.remove(&request.ip);
             Some(request)
         } else {
             None
@@ -431,6 +624,18 @@ impl ShareState {
     /// 拒绝访问请求
     pub fn reject_request(&mut self, request_id: &str) -> Option<&AccessRequest> {
         if let Some(request) = self.access_requests.get_mut(request_id) {
+            // 重复拒绝同一请求不计入次数
+            if request.status != AccessRequestStatus::Rejected {
+                let count = self.rejection_counts.entry(request.ip.clone()).or_insert(0);
+                *count += 1;
+                if self
+                    .settings
+                    .max_rejections
+                    .is_some_and(|max| max > 0 && *count >= max)
+                {
+                    self.auto_rejected_ips.insert(request.ip.clone());
+                }
+            }
             request.reject();
             if !self.rejected_ips.contains(&request.ip) {
                 self.rejected_ips.push(request.ip.clone());
@@ -443,14 +648,62 @@ impl ShareState {
         }
     }
 
+    /// 待处理请求的 ID（按请求时间从早到晚）
+    fn pending_request_ids(&self) -> Vec<String> {
+        let mut pending: Vec<&AccessRequest> = self
+            .access_requests
+            .values()
+            .filter(|r| r.status == AccessRequestStatus::Pending)
+            .collect();
+        pending.sort_by_key(|r| r.requested_at);
+        pending.into_iter().map(|r| r.id.clone()).collect()
+    }
+
+    /// 接受所有待处理的访问请求，返回被接受的请求
+    pub fn accept_all_pending(&mut self) -> Vec<AccessRequest> {
+        self.pending_request_ids()
+            .iter()
+            .filter_map(|id| self.accept_request(id).cloned())
+            .collect()
+    }
+
+    /// 拒绝所有待处理的访问请求，返回被拒绝的请求
+    pub fn reject_all_pending(&mut self) -> Vec<AccessRequest> {
+        self.pending_request_ids()
+            .iter()
+            .filter_map(|id| self.reject_request(id).cloned())
+            .collect()
+    }
+
+    /// 检查 IP 是否受信任（本次分享记住或已永久记住）
+    pub fn is_ip_trusted(&self, ip: &str) -> bool {
+        self.trusted_ips.contains(ip) || super::trusted::is_persistently_trusted(ip)
+    }
+
+    /// 新请求是否应自动接受
+    pub fn should_auto_accept(&self, ip: &str) -> bool {
+        self.settings.is_auto_accept_active() || self.is_ip_trusted(ip)
+    }
+
     /// 检查 IP 是否已被验证
     pub fn is_ip_verified(&self, ip: &str) -> bool {
         self.verified_ips.contains(&ip.to_string())
     }
 
-    /// 检查 IP 是否已被拒绝
+    /// 检查 IP 是否已被拒绝（含自动拒绝）
     pub fn is_ip_rejected(&self, ip: &str) -> bool {
-        self.rejected_ips.contains(&ip.to_string())
+        self.rejected_ips.contains(&ip.to_string()) || self.is_ip_auto_rejected(ip)
+    }
+
+    /// 检查 IP 是否因被拒绝次数达到上限而自动拒绝
+    pub fn is_ip_auto_rejected(&self, ip: &str) -> bool {
+        self.auto_rejected_ips.contains(ip)
+    }
+
+    /// IP 是否恰好因最近一次拒绝达到上限，用于只通知一次
+    pub fn just_reached_rejection_limit(&self, ip: &str) -> bool {
+        self.is_ip_auto_rejected(ip)
+            && self.settings.max_rejections == self.rejection_counts.get(ip).copied()
     }
 
     /// 检查 IP 是否有访问权限（请求已被接受）
@@ -472,3 +725,139 @@ impl Default for ShareState {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview_allowlist() {
+        let mut settings = ShareSettings::default();
+        assert!(settings.is_preview_allowed("photo.JPG"));
+        assert!(settings.is_preview_allowed("notes.txt"));
+        assert!(settings.is_preview_allowed("manual.pdf"));
+        assert!(!settings.is_preview_allowed("index.html"));
+        assert!(!settings.is_preview_allowed("logo.svg"));
+        assert!(!settings.is_preview_allowed("README"));
+
+        settings.preview_extensions = Some(vec![".png".to_string()]);
+        assert!(settings.is_preview_allowed("a.png"));
+        assert!(!settings.is_preview_allowed("a.txt"));
+    }
+
+    #[test]
+    fn test_share_expiry() {
+        let info = ShareLinkInfo::new(Vec::new(), 8080, Vec::new());
+        assert!(info.clone().with_expiry(Some(0)).expires_at.is_none());
+        assert_eq!(
+            info.clone().with_expiry(Some(u64::MAX)).expires_at,
+            Some(u64::MAX)
+        );
+
+        let mut info = info.with_expiry(Some(60));
+        let created_at = info.created_at;
+        assert_eq!(info.expires_at, Some(created_at + 60_000));
+        assert!(!info.is_expired_at(created_at + 59_999));
+
+        info.refresh_expiry(created_at + 58_500);
+        assert_eq!(info.remaining_seconds, Some(2));
+        assert_eq!(info.status, ShareStatus::Active);
+
+        info.refresh_expiry(created_at + 60_000);
+        assert_eq!(info.remaining_seconds, Some(0));
+        assert_eq!(info.status, ShareStatus::Expired);
+
+        // 服务器停止前到达的请求同样视为分享已结束
+        let mut state = ShareState::new();
+        assert!(state.is_share_ended());
+        state.start_share(info.clone().with_expiry(Some(60)), ShareSettings::default());
+        assert!(!state.is_share_ended());
+        info.expires_at = Some(created_at);
+        state.start_share(info, ShareSettings::default());
+        assert!(state.is_share_ended());
+    }
+
+    #[test]
+    fn test_upload_records_capped() {
+        let mut request = AccessRequest::new("192.168.1.8".to_string(), None);
+        let mut in_progress = ShareUploadRecord::new("big.iso".to_string(), 1000);
+        in_progress.uploaded_bytes = 10;
+        let in_progress_id = in_progress.id.clone();
+        request.push_upload_record(in_progress);
+
+        for i in 0..MAX_UPLOAD_RECORDS_PER_REQUEST + 5 {
+            let mut record = ShareUploadRecord::new(format!("{}.txt", i), 10);
+            record.status = TransferStatus::Completed;
+            record.uploaded_bytes = 10;
+            request.push_upload_record(record);
+        }
+
+        assert_eq!(request.upload_records.len(), MAX_UPLOAD_RECORDS_PER_REQUEST);
+        assert_eq!(request.upload_records[0].file_name, "104.txt");
+        // 进行中的记录即使最早也会保留
+        assert!(request.upload_records.iter().any(|r| r.id == in_progress_id));
+        assert_eq!(request.archived_records.count, 6);
+        assert_eq!(request.archived_records.completed, 6);
+        assert_eq!(request.archived_records.transferred_bytes, 60);
+
+        assert_eq!(request.clear_finished_records(), MAX_UPLOAD_RECORDS_PER_REQUEST - 1);
+        assert_eq!(request.upload_records.len(), 1);
+        assert_eq!(request.archived_records.count, 0);
+    }
+
+    #[test]
+    fn test_resolve_all_pending() {
+        let mut state = ShareState::new();
+        for ip in ["10.0.0.1", "10.0.0.2", "10.0.0.3"] {
+            let request = AccessRequest::new(ip.to_string(), None);
+            state.access_requests.insert(request.id.clone(), request);
+        }
+        let rejected_id = state
+            .access_requests
+            .values()
+            .find(|r| r.ip == "10.0.0.3")
+            .map(|r| r.id.clone())
+            .unwrap();
+        state.reject_request(&rejected_id);
+
+        let accepted = state.accept_all_pending();
+        assert_eq!(accepted.len(), 2);
+        assert!(state.is_ip_allowed("10.0.0.1"));
+        assert!(state.is_ip_verified("10.0.0.2"));
+        // 已处理的请求不受影响
+        assert!(state.is_ip_rejected("10.0.0.3"));
+        assert!(state.reject_all_pending().is_empty());
+    }
+
+    #[test]
+    fn test_auto_reject_after_max_rejections() {
+        let mut state = ShareState::new();
+        state.settings.max_rejections = Some(2);
+        let ip = "10.0.0.9";
+
+        let reject_new_request = |state: &mut ShareState| {
+            let request = AccessRequest::new(ip.to_string(), None);
+            let id = request.id.clone();
+            state.access_requests.insert(id.clone(), request);
+            state.reject_request(&id);
+            // 重复拒绝同一请求不计入次数
+            state.reject_request(&id);
+            state.remove_request(&id);
+        };
+
+        reject_new_request(&mut state);
+        assert!(!state.is_ip_auto_rejected(ip));
+        reject_new_request(&mut state);
+        assert!(state.is_ip_auto_rejected(ip));
+        assert!(state.just_reached_rejection_limit(ip));
+        assert!(state.is_ip_rejected(ip));
+
+        // 手动接受后解除自动拒绝
+        let request = AccessRequest::new(ip.to_string(), None);
+        let id = request.id.clone();
+        state.access_requests.insert(id.clone(), request);
+        state.accept_request(&id);
+        assert!(!state.is_ip_rejected(ip));
+        assert_eq!(state.rejection_counts.get(ip), None);
+    }
+}
@@ -0,0 +1,89 @@
+//! 访问请求频率限制
+//!
+//! 未验证的访客每次访问都可能生成新的访问请求，同一 IP 在一分钟内新建的请求数超过上限时拒绝，
+//! 避免恶意客户端刷屏桌面端的请求列表。查询已有请求状态的轮询不受限制
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+/// 默认每分钟每个 IP 允许新建的访问请求数
+const DEFAULT_REQUEST_RATE_LIMIT: u32 = 20;
+
+/// 统计窗口
+const REQUEST_RATE_WINDOW: Duration = Duration::from_secs(60);
+
+static REQUEST_RATE_LIMIT: OnceLock<RwLock<u32>> = OnceLock::new();
+
+fn get_request_rate_limit_lock() -> &'static RwLock<u32> {
+    REQUEST_RATE_LIMIT.get_or_init(|| RwLock::new(DEFAULT_REQUEST_RATE_LIMIT))
+}
+
+/// 获取每分钟每个 IP 允许新建的访问请求数，0 表示不限制
+pub fn request_rate_limit() -> u32 {
+    get_request_rate_limit_lock()
+        .read()
+        .map(|v| *v)
+        .unwrap_or(DEFAULT_REQUEST_RATE_LIMIT)
+}
+
+/// 设置每分钟每个 IP 允许新建的访问请求数
+pub fn set_request_rate_limit_internal(per_minute: u32) {
+    if let Ok(mut value) = get_request_rate_limit_lock().write() {
+        *value = per_minute;
+    }
+}
+
+/// 各 IP 最近一分钟内新建访问请求的时间
+#[derive(Debug, Default)]
+pub(super) struct AccessRequestLimiter {
+    creations: HashMap<String, VecDeque<Instant>>,
+}
+
+impl AccessRequestLimiter {
+    /// 记录一次新建请求，超过 `limit` 时返回 false 且不记录
+    pub fn try_acquire(&mut self, ip: &str, limit: u32, now: Instant) -> bool {
+        // 顺带清理窗口外的记录，避免长时间运行后占用内存
+        self.creations.retain(|_, times| {
+            while times
+                .front()
+                .is_some_and(|t| now.saturating_duration_since(*t) >= REQUEST_RATE_WINDOW)
+            {
+                times.pop_front();
+            }
+            !times.is_empty()
+        });
+        if limit == 0 {
+            return true;
+        }
+
+        let times = self.creations.entry(ip.to_string()).or_default();
+        if times.len() >= limit as usize {
+            return false;
+        }
+        times.push_back(now);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits_creations_per_ip() {
+        let mut limiter = AccessRequestLimiter::default();
+        let start = Instant::now();
+        assert!(limiter.try_acquire("10.0.0.2", 2, start));
+        assert!(limiter.try_acquire("10.0.0.2", 2, start));
+        assert!(!limiter.try_acquire("10.0.0.2", 2, start));
+        // 其他 IP 不受影响
+        assert!(limiter.try_acquire("10.0.0.3", 2, start));
+
+        // 窗口过后重新计数
+        let later = start + REQUEST_RATE_WINDOW;
+        assert!(limiter.try_acquire("10.0.0.2", 2, later));
+        assert!(limiter.try_acquire("10.0.0.2", 0, later));
+        assert_eq!(limiter.creations.len(), 1);
+    }
+}
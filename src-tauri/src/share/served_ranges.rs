@@ -0,0 +1,99 @@
+//! 分段下载覆盖统计
+//!
+//! 断点续传和分享页的并行下载会把一次下载拆成多个 Range 请求。
+//! 同一客户端对同一文件已发送的字节区间合并记录，覆盖整个文件后才算完成一次下载
+
+/// 已发送给客户端的字节区间
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct ServedRanges {
+    /// 文件大小
+    size: u64,
+    /// 按起点排序、互不重叠也不相邻的闭区间
+    ranges: Vec<(u64, u64)>,
+}
+
+impl ServedRanges {
+    pub fn new(size: u64) -> Self {
+        Self {
+            size,
+            ranges: Vec::new(),
+        }
+    }
+
+    /// 记录已发送的闭区间 `start..=end`，超出文件末尾的部分忽略
+    pub fn insert(&mut self, start: u64, end: u64) {
+        if self.size == 0 || start >= self.size {
+            return;
+        }
+        let mut start = start;
+        let mut end = end.min(self.size - 1);
+        if start > end {
+            return;
+        }
+
+        // 合并与新区间重叠或相邻的已有区间
+        let mut merged = Vec::with_capacity(self.ranges.len() + 1);
+        for &(s, e) in &self.ranges {
+            if e.saturating_add(1) < start || end.saturating_add(1) < s {
+                merged.push((s, e));
+            } else {
+                start = start.min(s);
+                end = end.max(e);
+            }
+        }
+        let index = merged.partition_point(|&(s, _)| s < start);
+        merged.insert(index, (start, end));
+        self.ranges = merged;
+    }
+
+    /// 已覆盖的字节数
+    pub fn covered_bytes(&self) -> u64 {
+        self.ranges.iter().map(|&(s, e)| e - s + 1).sum()
+    }
+
+    /// 是否已覆盖整个文件（空文件视为已覆盖）
+    pub fn is_complete(&self) -> bool {
+        self.covered_bytes() >= self.size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_served_ranges_merge_overlapping_and_adjacent() {
+        let mut served = ServedRanges::new(100);
+        served.insert(50, 99);
+        assert!(!served.is_complete());
+        served.insert(0, 9);
+        served.insert(20, 29);
+        assert_eq!(served.covered_bytes(), 70);
+
+        // 相邻和重叠的区间合并为一个
+        served.insert(10, 19);
+        served.insert(25, 55);
+        assert_eq!(served.ranges, vec![(0, 99)]);
+        assert!(served.is_complete());
+    }
+
+    #[test]
+    fn test_served_ranges_repeated_bytes_count_once() {
+        let mut served = ServedRanges::new(10);
+        for _ in 0..5 {
+            served.insert(0, 4);
+        }
+        assert_eq!(served.covered_bytes(), 5);
+        served.insert(5, u64::MAX);
+        assert!(served.is_complete());
+    }
+
+    #[test]
+    fn test_served_ranges_ignore_out_of_bounds() {
+        let mut served = ServedRanges::new(10);
+        served.insert(10, 20);
+        served.insert(7, 3);
+        assert_eq!(served.covered_bytes(), 0);
+        assert!(ServedRanges::new(0).is_complete());
+    }
+}
@@ -4,20 +4,21 @@
 
 use axum::{
     body::Body,
-    extract::{connect_info::ConnectInfo, Path, State as AxumState},
+    extract::{connect_info::ConnectInfo, Path, Query, State as AxumState},
     http::{header, HeaderMap, HeaderName, StatusCode},
     response::{Html, IntoResponse, Json, Response},
     routing::{get, post},
     Router,
 };
 use bytes::Bytes;
-use futures::Stream;
+use futures::{Stream, StreamExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::HashSet;
 use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::task::{Context, Poll};
 use tauri::{AppHandle, Emitter};
@@ -25,19 +26,51 @@ use tokio::fs::File;
 use tokio::io::{AsyncReadExt, AsyncSeekExt};
 use tokio::sync::Mutex;
 use tokio_util::io::ReaderStream;
+use super::download_sessions::{
+    load_download_sessions, save_download_sessions, PersistedDownloadSession,
+};
+use super::bandwidth::{throttled_bytes, ThrottledStream};
+use super::models::current_timestamp_millis;
 use super::models::{ShareState, ShareUploadRecord};
+use super::request_limit::{request_rate_limit, AccessRequestLimiter};
+use super::served_ranges::ServedRanges;
+use super::zip_archive::{CrcCache, ZipLayout};
 use crate::http_common::{
-    self, HasCryptoSessions, ServerCapabilities, HTTP_CHUNK_SIZE,
+    self, page_shell_response, resolve_client_ip, HasCryptoSessions, ServerCapabilities,
+    ServerInfo, HTTP_CHUNK_SIZE,
 };
 use crate::models::FileMetadata;
 use crate::transfer::compression::{
-    create_compressor_from_config, get_compression_config, Compressor,
+    create_compressor_from_config, get_compression_config, CompressionAlgorithm, Compressor,
 };
-use crate::transfer::crypto::is_encryption_enabled;
+use crate::transfer::crypto::{is_encryption_enabled, CipherSuite, HandshakeVerification};
 use crate::transfer::http_crypto::HttpCryptoSessionManager;
 
+/// Endpoints advertised by `/info`
+const SHARE_ENDPOINTS: &[&str] = &[
+    "GET /",
+    "GET /info",
+    "GET /files",
+    "POST /verify-pin",
+    "GET /request-status",
+    "GET /capabilities",
+    "POST /crypto/handshake",
+    "GET /download/{file_id}/meta?resume_from={index}",
+    "GET /download/{file_id}/status",
+    "GET /download/{file_id}/chunk/{chunk_index}",
+    "GET /download/{file_id}/chunks?start={index}&count={n}",
+    "GET /download/{file_id}",
+    "GET /download-all",
+    "GET /preview/{file_id}",
+];
+
+/// Content-Security-Policy for preview responses: no scripts, only the file itself
+const PREVIEW_CSP: &str =
+    "default-src 'none'; img-src 'self' data:; media-src 'self'; style-src 'unsafe-inline'; object-src 'self'; frame-ancestors 'self'";
+
 #[derive(Debug)]
 struct ChunkDownloadSession {
+    file_id: String,
     upload_id: String,
     file_name: String,
     file_size: u64,
@@ -45,6 +78,311 @@ struct ChunkDownloadSession {
     downloaded_chunks: HashSet<usize>,
     client_ip: String,
     start_time: std::time::Instant,
+    last_activity: std::time::Instant,
+    /// Wall-clock time of the last fetched chunk (ms), persisted across restarts
+    updated_at: u64,
+    /// Whether `upload-start` was emitted and the record added in this run;
+    /// false for sessions restored from disk until the client comes back
+    announced: bool,
+    /// Bytes already downloaded when the session was restored, excluded from speed
+    resumed_bytes: u64,
+    /// Original (pre-compression) size of the distinct chunks delivered so far
+    delivered_bytes: u64,
+}
+
+/// Original size of a chunk: every chunk is full-sized except the last one
+fn chunk_original_size(file_size: u64, index: usize) -> u64 {
+    let offset = index as u64 * HTTP_CHUNK_SIZE as u64;
+    file_size.saturating_sub(offset).min(HTTP_CHUNK_SIZE as u64)
+}
+
+impl ChunkDownloadSession {
+    fn progress(&self) -> f64 {
+        if self.chunk_count == 0 || self.file_size == 0 {
+            return 100.0;
+        }
+        (self.delivered_bytes as f64 / self.file_size as f64 * 100.0).min(100.0)
+    }
+
+    /// Record a delivered chunk; chunks fetched again (e.g. when seeking) count once
+    fn record_chunk(&mut self, chunk_index: usize, original_size: u64) {
+        if chunk_index < self.chunk_count && self.downloaded_chunks.insert(chunk_index) {
+            self.delivered_bytes += original_size;
+        }
+    }
+
+    /// Count the leading chunks a resuming client already holds
+    ///
+    /// The last chunk is never counted, so the client always fetches it and the
+    /// download is completed through the normal chunk path.
+    fn mark_resumed(&mut self, resume_from: usize) {
+        for index in 0..resume_from.min(self.chunk_count.saturating_sub(1)) {
+            self.record_chunk(index, chunk_original_size(self.file_size, index));
+        }
+    }
+
+    fn to_persisted(&self) -> PersistedDownloadSession {
+        let mut downloaded_chunks: Vec<usize> = self.downloaded_chunks.iter().copied().collect();
+        downloaded_chunks.sort_unstable();
+        PersistedDownloadSession {
+            file_id: self.file_id.clone(),
+            client_ip: self.client_ip.clone(),
+            upload_id: self.upload_id.clone(),
+            file_name: self.file_name.clone(),
+            file_size: self.file_size,
+            chunk_count: self.chunk_count,
+            downloaded_chunks,
+            updated_at: self.updated_at,
+        }
+    }
+
+    fn from_persisted(persisted: PersistedDownloadSession) -> Self {
+        let now = std::time::Instant::now();
+        let mut session = Self {
+            file_id: persisted.file_id,
+            upload_id: persisted.upload_id,
+            file_name: persisted.file_name,
+            file_size: persisted.file_size,
+            chunk_count: persisted.chunk_count,
+            downloaded_chunks: persisted
+                .downloaded_chunks
+                .into_iter()
+                .filter(|&index| index < persisted.chunk_count)
+                .collect(),
+            client_ip: persisted.client_ip,
+            start_time: now,
+            last_activity: now,
+            updated_at: persisted.updated_at,
+            announced: false,
+            resumed_bytes: 0,
+            delivered_bytes: 0,
+        };
+        session.delivered_bytes = session
+            .downloaded_chunks
+            .iter()
+            .map(|&index| chunk_original_size(session.file_size, index))
+            .sum();
+        session.resumed_bytes = session.delivered_bytes;
+        session
+    }
+}
+
+/// Chunked download sessions idle longer than this no longer count as active
+const CHUNK_SESSION_IDLE_SECS: u64 = 60;
+
+/// Chunked download sessions idle longer than this are treated as abandoned
+const CHUNK_SESSION_STALE_SECS: u64 = 300;
+
+/// How often abandoned chunked download sessions are swept
+const CHUNK_SESSION_SWEEP_INTERVAL_SECS: u64 = 30;
+
+/// How often changed chunked download sessions are written to disk
+const CHUNK_SESSION_PERSIST_INTERVAL_SECS: u64 = 5;
+
+/// Restored sessions whose client never returns are dropped after this long (ms)
+const RESTORED_SESSION_MAX_AGE_MS: u64 = 24 * 60 * 60 * 1000;
+
+/// Seconds a client is asked to wait when the server is at its download limit
+const BUSY_RETRY_AFTER_SECS: u64 = 5;
+
+/// Completed downloads per file ID, counted against `max_downloads_per_file`
+type DownloadCounts = Arc<std::sync::Mutex<std::collections::HashMap<String, u32>>>;
+
+/// Counts one completed download of a file toward its download limit
+struct DownloadCount {
+    counts: DownloadCounts,
+    file_id: String,
+    file_name: String,
+    /// Limit in effect when the download started
+    limit: Option<u32>,
+}
+
+impl DownloadCount {
+    /// Increment the file's count, emitting `download-limit-reached` when it hits the limit
+    fn record(&self, app_handle: &AppHandle) {
+        let downloads = {
+            let mut counts = self.counts.lock().unwrap_or_else(|e| e.into_inner());
+            let count = counts.entry(self.file_id.clone()).or_insert(0);
+            *count += 1;
+            *count
+        };
+        if let Some(limit) = self.limit.filter(|&limit| downloads == limit) {
+            let _ = app_handle.emit(
+                "download-limit-reached",
+                DownloadLimitReachedPayload {
+                    file_id: self.file_id.clone(),
+                    file_name: self.file_name.clone(),
+                    downloads,
+                    limit,
+                },
+            );
+        }
+    }
+}
+
+/// Bytes of one file served to one client through Range requests
+///
+/// Parallel and resumed downloads split one download over many requests, so it is
+/// counted once every byte of the file has been served.
+struct RangedDownload {
+    served: ServedRanges,
+    /// Recorded when the last missing byte is served
+    download_counts: Vec<DownloadCount>,
+    last_activity: std::time::Instant,
+    /// Upload record shown on the desktop, for downloads of a single shared file
+    record: Option<RangedRecord>,
+}
+
+/// Upload record of a ranged download, advanced by the bytes served so far
+struct RangedRecord {
+    upload_id: String,
+    file_name: String,
+    client_ip: String,
+    file_size: u64,
+    start_time: std::time::Instant,
+    last_emit_time: std::time::Instant,
+}
+
+impl RangedRecord {
+    /// Progress to report once `covered` bytes were served, at most every 500 ms
+    fn progress(&mut self, covered: u64) -> Option<super::models::UploadProgress> {
+        if self.last_emit_time.elapsed() < std::time::Duration::from_millis(500) {
+            return None;
+        }
+        self.last_emit_time = std::time::Instant::now();
+        let elapsed = self.start_time.elapsed().as_secs_f64();
+        Some(super::models::UploadProgress {
+            upload_id: self.upload_id.clone(),
+            file_name: self.file_name.clone(),
+            progress: (covered as f64 / self.file_size.max(1) as f64 * 100.0).min(100.0),
+            uploaded_bytes: covered,
+            total_bytes: self.file_size,
+            speed: if elapsed > 0.0 {
+                (covered as f64 / elapsed) as u64
+            } else {
+                0
+            },
+            client_ip: self.client_ip.clone(),
+        })
+    }
+
+    fn complete(&self, app_handle: &AppHandle, record_updates: &RecordUpdateSender) {
+        let _ = app_handle.emit(
+            "upload-complete",
+            UploadCompletePayload {
+                upload_id: self.upload_id.clone(),
+                file_name: self.file_name.clone(),
+                file_size: self.file_size as i64,
+                client_ip: self.client_ip.clone(),
+            },
+        );
+        let update = RecordUpdate::Completed {
+            completed_at: current_timestamp_millis(),
+        };
+        send_final_record_update(record_updates, (self.upload_id.clone(), update));
+    }
+
+    /// The client stopped before every byte was served
+    fn cancel(&self, covered: u64, app_handle: &AppHandle, record_updates: &RecordUpdateSender) {
+        let status = super::models::TransferStatus::Cancelled;
+        let _ = app_handle.emit(
+            "upload-cancelled",
+            UploadCancelledPayload {
+                upload_id: self.upload_id.clone(),
+                file_name: self.file_name.clone(),
+                file_size: self.file_size as i64,
+                transferred_bytes: covered,
+                client_ip: self.client_ip.clone(),
+                status,
+            },
+        );
+        let update = RecordUpdate::Interrupted {
+            status,
+            completed_at: current_timestamp_millis(),
+        };
+        send_final_record_update(record_updates, (self.upload_id.clone(), update));
+    }
+}
+
+/// Ranged downloads in progress, keyed by client IP and ETag
+type RangedDownloads =
+    Arc<std::sync::Mutex<std::collections::HashMap<(String, String), RangedDownload>>>;
+
+/// Reports the bytes a ranged response served to its `RangedDownload`
+#[derive(Clone)]
+struct RangedDownloadHandle {
+    downloads: RangedDownloads,
+    key: (String, String),
+    app_handle: AppHandle,
+    record_updates: RecordUpdateSender,
+}
+
+impl RangedDownloadHandle {
+    /// Add `start..=end` to the served bytes, completing the download once every byte is out
+    fn record(&self, start: u64, end: u64) {
+        let (progress, completed) = {
+            let mut downloads = self.downloads.lock().unwrap_or_else(|e| e.into_inner());
+            let Some(download) = downloads.get_mut(&self.key) else {
+                return;
+            };
+            download.served.insert(start, end);
+            download.last_activity = std::time::Instant::now();
+            if download.served.is_complete() {
+                (None, downloads.remove(&self.key))
+            } else {
+                let covered = download.served.covered_bytes();
+                let progress = download.record.as_mut().and_then(|r| r.progress(covered));
+                (progress, None)
+            }
+        };
+
+        if let Some(progress) = progress {
+            // Never block the stream: a dropped tick is superseded by the next one
+            let _ = self.record_updates.try_send((
+                progress.upload_id.clone(),
+                RecordUpdate::Progress {
+                    uploaded_bytes: progress.uploaded_bytes,
+                    progress: progress.progress,
+                    speed: progress.speed,
+                },
+            ));
+            let _ = self.app_handle.emit("upload-progress", progress);
+        }
+        if let Some(download) = completed {
+            if let Some(record) = &download.record {
+                record.complete(&self.app_handle, &self.record_updates);
+            }
+            for download_count in &download.download_counts {
+                download_count.record(&self.app_handle);
+            }
+        }
+    }
+
+    /// Record processed chunks, given as index and original size, as served
+    fn record_chunks(&self, chunks: impl IntoIterator<Item = (usize, u64)>) {
+        for (chunk_index, original_size) in chunks {
+            if original_size > 0 {
+                let offset = chunk_index as u64 * HTTP_CHUNK_SIZE as u64;
+                self.record(offset, offset + original_size - 1);
+            }
+        }
+    }
+
+    fn on_served(self) -> OnServed {
+        Arc::new(move |start, end| self.record(start, end))
+    }
+}
+
+/// Called with each byte range (inclusive) a response delivers, as it is delivered
+type OnServed = Arc<dyn Fn(u64, u64) + Send + Sync>;
+
+/// Holds one slot of the concurrent-download limit until dropped
+struct DownloadPermit(Arc<AtomicUsize>);
+
+impl Drop for DownloadPermit {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
 }
 
 #[derive(Debug)]
@@ -55,35 +393,296 @@ pub struct ServerState {
     pub app_handle: AppHandle,
     pub crypto_sessions: Arc<Mutex<HttpCryptoSessionManager>>,
     chunk_download_sessions: Arc<Mutex<std::collections::HashMap<String, ChunkDownloadSession>>>,
+    /// Set when chunked download sessions changed since they were last persisted
+    chunk_sessions_dirty: AtomicBool,
+    zip_crc_cache: CrcCache,
+    /// Streaming (full or range) downloads currently in progress
+    active_streams: Arc<AtomicUsize>,
+    /// Feeds upload record progress to the record updater task
+    record_updates: RecordUpdateSender,
+    /// Completed downloads per file
+    download_counts: DownloadCounts,
+    /// Files being downloaded through Range requests
+    ranged_downloads: RangedDownloads,
+    /// Recent access request creations per IP
+    access_request_limiter: std::sync::Mutex<AccessRequestLimiter>,
+}
+
+impl ServerState {
+    async fn active_chunk_sessions(&self) -> usize {
+        let sessions = self.chunk_download_sessions.lock().await;
+        sessions
+            .values()
+            .filter(|s| {
+                s.announced && s.last_activity.elapsed().as_secs() < CHUNK_SESSION_IDLE_SECS
+            })
+            .count()
+    }
+
+    /// Count a new access request from `client_ip` against the per-minute limit
+    fn allow_access_request(&self, client_ip: &str) -> bool {
+        let limit = request_rate_limit();
+        self.access_request_limiter
+            .lock()
+            .map(|mut limiter| limiter.try_acquire(client_ip, limit, std::time::Instant::now()))
+            .unwrap_or(true)
+    }
+
+    /// Streaming downloads plus chunked download sessions that are still active
+    async fn active_downloads(&self) -> usize {
+        self.active_streams.load(Ordering::SeqCst) + self.active_chunk_sessions().await
+    }
+
+    async fn download_limit(&self) -> Option<usize> {
+        let share_state = self.share_state.lock().await;
+        share_state
+            .settings
+            .max_concurrent_downloads
+            .filter(|&limit| limit > 0)
+            .map(|limit| limit as usize)
+    }
+
+    /// Per-file download limit in effect, or a 403 page once `file_id` has used it up
+    async fn check_download_limit(&self, file_id: &str) -> Result<Option<u32>, Response> {
+        let limit = self
+            .share_state
+            .lock()
+            .await
+            .settings
+            .max_downloads_per_file
+            .filter(|&limit| limit > 0);
+        let downloads = self
+            .download_counts
+            .lock()
+            .map(|counts| counts.get(file_id).copied().unwrap_or(0))
+            .unwrap_or(0);
+        match limit {
+            Some(limit) if downloads >= limit => Err(download_limit_response()),
+            _ => Ok(limit),
+        }
+    }
+
+    fn download_count(&self, file_id: &str, file_name: &str, limit: Option<u32>) -> DownloadCount {
+        DownloadCount {
+            counts: self.download_counts.clone(),
+            file_id: file_id.to_string(),
+            file_name: file_name.to_string(),
+            limit,
+        }
+    }
+
+    /// Join the client's ranged download of `etag`, or start one counted by `download_counts`
+    ///
+    /// A download already in progress may finish after the file reached its limit;
+    /// starting a new one fails with the error in `download_counts`. When `record_as`
+    /// names the file, a new download gets one upload record for all of its requests.
+    async fn ranged_download(
+        &self,
+        client_ip: &str,
+        etag: &str,
+        size: u64,
+        record_as: Option<&str>,
+        download_counts: Result<Vec<DownloadCount>, Response>,
+    ) -> Result<RangedDownloadHandle, Response> {
+        let key = (client_ip.to_string(), etag.to_string());
+        let new_record = {
+            let mut downloads = self
+                .ranged_downloads
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            match downloads.entry(key.clone()) {
+                std::collections::hash_map::Entry::Occupied(mut entry) => {
+                    entry.get_mut().last_activity = std::time::Instant::now();
+                    None
+                }
+                std::collections::hash_map::Entry::Vacant(entry) => {
+                    let new_record = record_as
+                        .map(|file_name| ShareUploadRecord::new(file_name.to_string(), size));
+                    let now = std::time::Instant::now();
+                    entry.insert(RangedDownload {
+                        served: ServedRanges::new(size),
+                        download_counts: download_counts?,
+                        last_activity: now,
+                        record: new_record.as_ref().map(|record| RangedRecord {
+                            upload_id: record.id.clone(),
+                            file_name: record.file_name.clone(),
+                            client_ip: client_ip.to_string(),
+                            file_size: size,
+                            start_time: now,
+                            last_emit_time: now,
+                        }),
+                    });
+                    new_record
+                }
+            }
+        };
+
+        if let Some(record) = new_record {
+            let payload = UploadStartPayload {
+                upload_id: record.id.clone(),
+                file_name: record.file_name.clone(),
+                file_size: size as i64,
+                client_ip: client_ip.to_string(),
+            };
+            if let Some(request) = self
+                .share_state
+                .lock()
+                .await
+                .access_requests
+                .values_mut()
+                .find(|r| r.ip == client_ip)
+            {
+                request.push_upload_record(record);
+            }
+            let _ = self.app_handle.emit("upload-start", payload);
+        }
+
+        Ok(RangedDownloadHandle {
+            downloads: self.ranged_downloads.clone(),
+            key,
+            app_handle: self.app_handle.clone(),
+            record_updates: self.record_updates.clone(),
+        })
+    }
+
+    /// Track chunk requests made outside a chunked download session as a ranged download
+    ///
+    /// Returns `None` for clients with a session, which counts the download itself.
+    async fn sessionless_chunk_download(
+        &self,
+        file_id: &str,
+        file_name: &str,
+        client_ip: &str,
+        path: &std::path::Path,
+        file_size: u64,
+    ) -> Result<Option<RangedDownloadHandle>, Response> {
+        let session_key = format!("{}_{}", file_id, client_ip);
+        if self
+            .chunk_download_sessions
+            .lock()
+            .await
+            .contains_key(&session_key)
+        {
+            return Ok(None);
+        }
+        let download_count = self
+            .check_download_limit(file_id)
+            .await
+            .map(|limit| vec![self.download_count(file_id, file_name, limit)]);
+        let etag = generate_etag(path, file_size);
+        self.ranged_download(client_ip, &etag, file_size, None, download_count)
+            .await
+            .map(Some)
+    }
+
+    /// Reserve a slot for a streaming download, or return a 503 when the server is busy
+    async fn try_acquire_download(&self) -> Result<DownloadPermit, Response> {
+        let limit = self.download_limit().await;
+        let sessions = match limit {
+            Some(_) => self.active_chunk_sessions().await,
+            None => 0,
+        };
+        self.active_streams
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |streams| match limit {
+                Some(limit) if streams + sessions >= limit => None,
+                _ => Some(streams + 1),
+            })
+            .map(|_| DownloadPermit(self.active_streams.clone()))
+            .map_err(|_| busy_response())
+    }
+}
+
+/// 503 page asking the client to retry shortly
+fn busy_response() -> Response {
+    let mut response = (
+        StatusCode::SERVICE_UNAVAILABLE,
+        Html("<html><body><h1>服务器繁忙，请稍后重试</h1></body></html>"),
+    )
+        .into_response();
+    response.headers_mut().insert(
+        header::RETRY_AFTER,
+        BUSY_RETRY_AFTER_SECS.to_string().parse().unwrap(),
+    );
+    response
+}
+
+/// 429 page for a client sending access requests too quickly
+fn too_many_access_requests_response() -> Response {
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(header::RETRY_AFTER, "60")],
+        Html("<html><body><h1>请求过于频繁，请稍后再试</h1></body></html>"),
+    )
+        .into_response()
+}
+
+/// 403 page for a file whose download count is used up
+fn download_limit_response() -> Response {
+    (
+        StatusCode::FORBIDDEN,
+        Html("<html><body><h1>下载次数已用尽</h1></body></html>"),
+    )
+        .into_response()
+}
+
+/// Keep a download slot reserved until the response body is fully sent or dropped
+fn hold_download_permit(response: Response, permit: DownloadPermit) -> Response {
+    let (parts, body) = response.into_parts();
+    let stream = body.into_data_stream().map(move |chunk| {
+        let _held = &permit;
+        chunk
+    });
+    Response::from_parts(parts, Body::from_stream(stream))
 }
 
 impl HasCryptoSessions for ServerState {
     fn crypto_sessions(&self) -> &Arc<Mutex<HttpCryptoSessionManager>> {
         &self.crypto_sessions
     }
+
+    fn on_handshake(&self, verification: HandshakeVerification) {
+        let _ = self.app_handle.emit("handshake-verification", verification);
+    }
 }
 
 pub struct ShareServer {
     pub addr: SocketAddr,
     pub state: Arc<ServerState>,
     shutdown_tx: Option<tokio::sync::oneshot::Sender<()>>,
+    cleanup_task: Option<tokio::task::JoinHandle<()>>,
+    stale_session_task: Option<tokio::task::JoinHandle<()>>,
 }
 
 impl ShareServer {
-    pub fn new(share_state: Arc<Mutex<ShareState>>, app_handle: AppHandle, port: u16) -> Self {
-        let addr = SocketAddr::from(([0, 0, 0, 0], port));
+    /// Listen on `bind` only, or on all interfaces when `None`
+    pub fn new(
+        share_state: Arc<Mutex<ShareState>>,
+        app_handle: AppHandle,
+        bind: Option<std::net::IpAddr>,
+        port: u16,
+    ) -> Self {
+        let addr = http_common::listen_addr(bind, port);
 
         Self {
             addr,
             state: Arc::new(ServerState {
+                record_updates: spawn_record_updater(share_state.clone()),
                 share_state,
                 file_paths: Arc::new(Mutex::new(std::collections::HashMap::new())),
                 hash_to_filename: Arc::new(Mutex::new(std::collections::HashMap::new())),
                 app_handle,
                 crypto_sessions: Arc::new(Mutex::new(HttpCryptoSessionManager::new())),
                 chunk_download_sessions: Arc::new(Mutex::new(std::collections::HashMap::new())),
+                chunk_sessions_dirty: AtomicBool::new(false),
+                zip_crc_cache: CrcCache::default(),
+                active_streams: Arc::new(AtomicUsize::new(0)),
+                download_counts: Arc::default(),
+                ranged_downloads: Arc::default(),
+                access_request_limiter: std::sync::Mutex::default(),
             }),
             shutdown_tx: None,
+            cleanup_task: None,
+            stale_session_task: None,
         }
     }
 
@@ -105,6 +704,7 @@ impl ShareServer {
                 hash_to_filename.insert(hash_id, file_name);
             }
         }
+        restore_chunk_sessions(&self.state).await;
 
         let app = Router::new()
             .route("/", get(index_handler))
@@ -114,16 +714,24 @@ impl ShareServer {
             .route("/files", get(list_files_handler))
             .route("/verify-pin", post(verify_pin_handler))
             .route("/request-status", get(request_status_handler))
+            .route("/info", get(share_info_handler))
             .route("/capabilities", get(share_capabilities_handler))
             .route("/crypto/handshake", post(http_common::crypto_handshake_handler::<ServerState>))
             .route("/download/{file_id}/meta", get(download_meta_handler))
+            .route("/download/{file_id}/status", get(download_status_handler))
             .route(
                 "/download/{file_id}/chunk/{chunk_index}",
                 get(download_chunk_handler),
             )
+            .route("/download/{file_id}/chunks", get(download_chunk_range_handler))
             .route("/download/{file_id}", get(file_download_handler))
+            .route("/download-all", get(download_all_handler))
+            .route("/preview/{file_id}", get(preview_handler))
+            .route("/admin", get(admin_handler))
             .fallback(http_common::fallback_handler)
+            .layer(axum::middleware::from_fn_with_state(self.state.clone(), track_activity))
             .layer(http_common::share_cors_layer())
+            .layer(http_common::http_trace_layer())
             .with_state(self.state.clone());
 
         let listener = tokio::net::TcpListener::bind(self.addr)
@@ -138,7 +746,28 @@ impl ShareServer {
         let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
         self.shutdown_tx = Some(shutdown_tx);
 
-        http_common::spawn_crypto_session_cleanup(self.state.crypto_sessions.clone());
+        self.cleanup_task = Some(http_common::spawn_crypto_session_cleanup(
+            self.state.crypto_sessions.clone(),
+        ));
+
+        let sweep_state = self.state.clone();
+        self.stale_session_task = Some(tokio::spawn(async move {
+            let mut sweep = tokio::time::interval(std::time::Duration::from_secs(
+                CHUNK_SESSION_SWEEP_INTERVAL_SECS,
+            ));
+            let mut persist = tokio::time::interval(std::time::Duration::from_secs(
+                CHUNK_SESSION_PERSIST_INTERVAL_SECS,
+            ));
+            loop {
+                tokio::select! {
+                    _ = sweep.tick() => {
+                        sweep_stale_chunk_sessions(&sweep_state).await;
+                        sweep_stale_ranged_downloads(&sweep_state);
+                    }
+                    _ = persist.tick() => persist_chunk_sessions(&sweep_state).await,
+                }
+            }
+        }));
 
         tokio::spawn(async move {
             axum::serve(
@@ -159,36 +788,143 @@ impl ShareServer {
         if let Some(tx) = self.shutdown_tx.take() {
             let _ = tx.send(());
         }
+        if let Some(task) = self.cleanup_task.take() {
+            task.abort();
+        }
+        if let Some(task) = self.stale_session_task.take() {
+            task.abort();
+            // Write out progress made since the last periodic save
+            let state = self.state.clone();
+            tokio::spawn(async move { persist_chunk_sessions(&state).await });
+        }
+    }
+
+    /// Number of downloads currently being served
+    pub async fn active_downloads(&self) -> usize {
+        self.state.active_downloads().await
     }
 }
 
 // ─── Helper functions ───────────────────────────────────────────────────────
 
-fn parse_range(range_str: &str, file_size: u64) -> Option<(u64, u64)> {
-    let range_str = range_str.strip_prefix("bytes=")?;
-    let parts: Vec<&str> = range_str.splitn(2, '-').collect();
-    if parts.len() != 2 {
+/// Record client activity for the idle timeout; the operator `/admin` page does not count
+async fn track_activity(
+    AxumState(state): AxumState<Arc<ServerState>>,
+    request: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> Response {
+    if request.uri().path() != "/admin" {
+        state.share_state.lock().await.touch();
+    }
+    next.run(request).await
+}
+
+/// Most ranges accepted in one `Range` header; longer lists are ignored as malformed
+const MAX_RANGES: usize = 100;
+
+/// A `Range` header matched against a resource of known size
+#[derive(Debug, PartialEq, Eq)]
+enum RangeRequest {
+    /// Serve each `start..=end`; see `coalesce_ranges` for the order
+    Satisfiable(Vec<(u64, u64)>),
+    /// Well-formed, but one of the ranges lies outside the resource
+    Unsatisfiable,
+}
+
+/// Parse a `bytes=` range list.
+///
+/// Malformed headers yield `None` and are ignored as RFC 7233 allows, so the client
+/// gets the whole resource.
+fn parse_range(range_str: &str, file_size: u64) -> Option<RangeRequest> {
+    let specs: Vec<&str> = range_str.strip_prefix("bytes=")?.split(',').collect();
+    if specs.len() > MAX_RANGES {
         return None;
     }
 
-    let start = if parts[0].is_empty() {
-        let suffix_len: u64 = parts[1].parse().ok()?;
-        file_size.saturating_sub(suffix_len)
+    let mut ranges = Vec::with_capacity(specs.len());
+    let mut satisfiable = true;
+    for spec in specs {
+        match parse_range_spec(spec.trim(), file_size)? {
+            Some(range) => ranges.push(range),
+            None => satisfiable = false,
+        }
+    }
+    Some(if satisfiable {
+        RangeRequest::Satisfiable(coalesce_ranges(ranges))
     } else {
-        parts[0].parse().ok()?
-    };
+        RangeRequest::Unsatisfiable
+    })
+}
+
+/// Merge overlapping or adjacent ranges, as RFC 7233 §6.1 recommends
+///
+/// Disjoint ranges keep their request order. Otherwise the merged ranges are served in
+/// ascending order, so a request never makes the server send a byte twice.
+fn coalesce_ranges(ranges: Vec<(u64, u64)>) -> Vec<(u64, u64)> {
+    let mut sorted = ranges.clone();
+    sorted.sort_unstable();
+    if sorted
+        .windows(2)
+        .all(|pair| pair[0].1.saturating_add(1) < pair[1].0)
+    {
+        return ranges;
+    }
 
-    let end = if parts[1].is_empty() {
-        file_size - 1
+    let mut merged: Vec<(u64, u64)> = Vec::with_capacity(sorted.len());
+    for (start, end) in sorted {
+        match merged.last_mut() {
+            Some(last) if start <= last.1.saturating_add(1) => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// Parse one `first-last` spec: `None` when malformed, `Some(None)` when unsatisfiable
+fn parse_range_spec(spec: &str, file_size: u64) -> Option<Option<(u64, u64)>> {
+    let (first, last) = spec.split_once('-')?;
+
+    if first.is_empty() {
+        let suffix_len: u64 = last.parse().ok()?;
+        if suffix_len == 0 || file_size == 0 {
+            return Some(None);
+        }
+        return Some(Some((file_size.saturating_sub(suffix_len), file_size - 1)));
+    }
+
+    let start: u64 = first.parse().ok()?;
+    let end = if last.is_empty() {
+        None
     } else {
-        parts[1].parse::<u64>().ok()?.min(file_size - 1)
+        Some(last.parse::<u64>().ok()?)
     };
-
-    if start > end || start >= file_size {
+    if end.is_some_and(|end| end < start) {
         return None;
     }
+    if start >= file_size {
+        return Some(None);
+    }
+    let last_byte = file_size - 1;
+    Some(Some((
+        start,
+        end.map_or(last_byte, |end| end.min(last_byte)),
+    )))
+}
 
-    Some((start, end))
+/// Ranges to serve for an optional `Range` header.
+///
+/// `Ok(None)` serves the whole resource; a range starting past the end is answered
+/// with `416 Range Not Satisfiable` and `Content-Range: bytes */<size>`.
+fn requested_range(range: Option<&str>, size: u64) -> Result<Option<Vec<(u64, u64)>>, Response> {
+    match range.and_then(|r| parse_range(r, size)) {
+        None => Ok(None),
+        Some(RangeRequest::Satisfiable(ranges)) => Ok(Some(ranges)),
+        Some(RangeRequest::Unsatisfiable) => Err((
+            StatusCode::RANGE_NOT_SATISFIABLE,
+            [(header::CONTENT_RANGE, format!("bytes */{}", size))],
+        )
+            .into_response()),
+    }
 }
 
 fn generate_etag(file_path: &std::path::Path, file_size: u64) -> String {
@@ -209,7 +945,7 @@ async fn check_download_access(
 ) -> Result<(), Response> {
     let share_state = state.share_state.lock().await;
 
-    if share_state.share_info.is_none() {
+    if share_state.is_share_ended() {
         return Err(
             Html("<html><body><h1>分享已结束</h1></body></html>").into_response()
         );
@@ -246,17 +982,127 @@ async fn check_download_access(
 
 // ─── Handlers ───────────────────────────────────────────────────────────────
 
-async fn share_capabilities_handler() -> Json<ServerCapabilities> {
-    Json(ServerCapabilities::for_share())
+/// Whether server details may be shown to this client
+///
+/// With `hide_details_before_access` set, capabilities are withheld until the
+/// visitor's access request is accepted or their PIN is verified.
+async fn details_visible(state: &ServerState, client_ip: &str) -> bool {
+    let share_state = state.share_state.lock().await;
+    !share_state.settings.hide_details_before_access
+        || share_state.is_ip_allowed(client_ip)
+        || share_state.is_ip_verified(client_ip)
+}
+
+async fn share_info_handler(
+    ConnectInfo(client_addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    AxumState(state): AxumState<Arc<ServerState>>,
+) -> Json<ServerInfo> {
+    let client_ip = resolve_client_ip(&client_addr, &headers);
+    if !details_visible(&state, &client_ip).await {
+        return Json(ServerInfo::restricted("share", SHARE_ENDPOINTS));
+    }
+    Json(ServerInfo::new(
+        "share",
+        ServerCapabilities::for_share(),
+        SHARE_ENDPOINTS,
+    ))
+}
+
+async fn share_capabilities_handler(
+    ConnectInfo(client_addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    AxumState(state): AxumState<Arc<ServerState>>,
+) -> Response {
+    let client_ip = resolve_client_ip(&client_addr, &headers);
+    if !details_visible(&state, &client_ip).await {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(serde_json::json!({ "restricted": true })),
+        )
+            .into_response();
+    }
+    Json(ServerCapabilities::for_share()).into_response()
 }
 
 /// Download metadata (chunk info for encrypted/compressed mode)
+/// Operator view of access requests and download records, local clients only
+async fn admin_handler(
+    ConnectInfo(client_addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    AxumState(state): AxumState<Arc<ServerState>>,
+) -> Response {
+    if !http_common::is_admin_request_allowed(&client_addr, &headers) {
+        return http_common::admin_forbidden_response(&client_addr);
+    }
+
+    let mut requests: Vec<_> = {
+        let share_state = state.share_state.lock().await;
+        share_state.access_requests.values().cloned().collect()
+    };
+    requests.sort_by(|a, b| b.requested_at.cmp(&a.requested_at));
+
+    let access_rows = requests
+        .iter()
+        .map(|request| {
+            vec![
+                http_common::format_admin_timestamp(request.requested_at),
+                request.ip.clone(),
+                request.user_agent.clone().unwrap_or_default(),
+                format!("{:?}", request.status).to_lowercase(),
+                request.pin_attempts.to_string(),
+                (request.upload_records.len() as u64 + request.archived_records.count)
+                    .to_string(),
+            ]
+        })
+        .collect();
+    let record_rows = requests
+        .iter()
+        .flat_map(|request| {
+            request.upload_records.iter().map(move |record| {
+                vec![
+                    http_common::format_admin_timestamp(record.started_at),
+                    request.ip.clone(),
+                    record.file_name.clone(),
+                    format!("{:.1}%", record.progress),
+                    format!("{} / {}", record.uploaded_bytes, record.total_bytes),
+                    format!("{:?}", record.status).to_lowercase(),
+                ]
+            })
+        })
+        .collect();
+
+    http_common::admin_page_response(
+        "PureSend Share Activity",
+        &[
+            http_common::AdminTable {
+                title: "Access requests",
+                columns: &["Requested", "IP", "Client", "Status", "PIN attempts", "Downloads"],
+                rows: access_rows,
+            },
+            http_common::AdminTable {
+                title: "Downloads",
+                columns: &["Started", "IP", "File", "Progress", "Bytes", "Status"],
+                rows: record_rows,
+            },
+        ],
+    )
+}
+
+#[derive(Debug, Deserialize)]
+struct DownloadMetaQuery {
+    /// Number of leading chunks the client already holds from an earlier attempt
+    resume_from: Option<usize>,
+}
+
 async fn download_meta_handler(
     ConnectInfo(client_addr): ConnectInfo<SocketAddr>,
     AxumState(state): AxumState<Arc<ServerState>>,
+    headers: HeaderMap,
     Path(file_id): Path<String>,
+    Query(query): Query<DownloadMetaQuery>,
 ) -> Response {
-    let client_ip = client_addr.ip().to_string();
+    let client_ip = resolve_client_ip(&client_addr, &headers);
     if let Err(resp) = check_download_access(&state, &client_ip).await {
         return resp;
     }
@@ -285,17 +1131,60 @@ async fn download_meta_handler(
     let mime_type = FileMetadata::infer_mime_type(&file_name);
 
     let encryption = is_encryption_enabled();
-    let compression_config = get_compression_config();
-    let compression_active = compression_config.enabled
-        && !Compressor::should_skip_compression(&mime_type);
+    let compression = negotiated_compression(&headers)
+        .filter(|_| !Compressor::should_skip_compression(&mime_type));
+    let compression_active = compression.is_some();
 
     let chunk_count = ((file_size as f64) / (HTTP_CHUNK_SIZE as f64)).ceil() as usize;
 
+    if let Err(resp) = state.check_download_limit(&file_id).await {
+        return resp;
+    }
+
     // When encryption or compression is active, the client will download via chunks
     // (not through upload_handler), so we need to track and emit events here.
-    if encryption || compression_active {
-        let upload_record = ShareUploadRecord::new(file_name.clone(), file_size);
+    // A session restored after a restart, or one the client resumes, continues where
+    // it left off.
+    let chunked = encryption || compression_active;
+    let session_key = format!("{}_{}", file_id, client_ip);
+    if chunked && !resume_chunk_session(&state, &session_key, query.resume_from).await {
+        let is_retry = state
+            .chunk_download_sessions
+            .lock()
+            .await
+            .contains_key(&session_key);
+        if !is_retry {
+            if let Some(limit) = state.download_limit().await {
+                if state.active_downloads().await >= limit {
+                    return busy_response();
+                }
+            }
+        }
+
+        let mut upload_record = ShareUploadRecord::new(file_name.clone(), file_size);
         let upload_id = upload_record.id.clone();
+        let mut session = ChunkDownloadSession {
+            file_id: file_id.clone(),
+            upload_id: upload_id.clone(),
+            file_name: file_name.clone(),
+            file_size,
+            chunk_count,
+            downloaded_chunks: HashSet::new(),
+            client_ip: client_ip.clone(),
+            start_time: std::time::Instant::now(),
+            last_activity: std::time::Instant::now(),
+            updated_at: current_timestamp_millis(),
+            announced: true,
+            resumed_bytes: 0,
+            delivered_bytes: 0,
+        };
+        // The server no longer knows the earlier attempt; trust the chunks the client holds
+        if let Some(resume_from) = query.resume_from {
+            session.mark_resumed(resume_from);
+            session.resumed_bytes = session.delivered_bytes;
+            upload_record.uploaded_bytes = session.delivered_bytes;
+            upload_record.progress = session.progress();
+        }
 
         {
             let mut share_state = state.share_state.lock().await;
@@ -304,7 +1193,7 @@ async fn download_meta_handler(
                 .values_mut()
                 .find(|r| r.ip == client_ip)
             {
-                request.upload_records.insert(0, upload_record);
+                request.push_upload_record(upload_record);
             }
         }
 
@@ -318,20 +1207,13 @@ async fn download_meta_handler(
             },
         );
 
-        let session_key = format!("{}_{}", file_id, client_ip);
         let mut sessions = state.chunk_download_sessions.lock().await;
-        sessions.insert(
-            session_key,
-            ChunkDownloadSession {
-                upload_id,
-                file_name: file_name.clone(),
-                file_size,
-                chunk_count,
-                downloaded_chunks: HashSet::new(),
-                client_ip: client_ip.clone(),
-                start_time: std::time::Instant::now(),
-            },
-        );
+        let previous = sessions.insert(session_key, session);
+        state.chunk_sessions_dirty.store(true, Ordering::SeqCst);
+        // A restarted download supersedes the earlier attempt and its record
+        if let Some(previous) = previous {
+            cancel_chunk_session(&state, &previous);
+        }
     }
 
     Json(DownloadMeta {
@@ -341,11 +1223,7 @@ async fn download_meta_handler(
         chunk_size: HTTP_CHUNK_SIZE,
         chunk_count,
         encryption,
-        compression: if compression_active {
-            Some("zstd".to_string())
-        } else {
-            None
-        },
+        compression: compression.map(|algorithm| algorithm.as_str().to_string()),
         mime_type,
     })
     .into_response()
@@ -358,7 +1236,7 @@ async fn download_chunk_handler(
     Path((file_id, chunk_index)): Path<(String, usize)>,
     headers: HeaderMap,
 ) -> Response {
-    let client_ip = client_addr.ip().to_string();
+    let client_ip = resolve_client_ip(&client_addr, &headers);
     if let Err(resp) = check_download_access(&state, &client_ip).await {
         return resp;
     }
@@ -386,6 +1264,14 @@ async fn download_chunk_handler(
         .unwrap_or(0);
     let mime_type = FileMetadata::infer_mime_type(&file_name);
 
+    let ranged = match state
+        .sessionless_chunk_download(&file_id, &file_name, &client_ip, &path, file_size)
+        .await
+    {
+        Ok(ranged) => ranged,
+        Err(resp) => return resp,
+    };
+
     // Read the chunk
     let buffer = match read_file_chunk(&path, chunk_index, file_size).await {
         Ok(data) => data,
@@ -395,10 +1281,11 @@ async fn download_chunk_handler(
     let original_size = buffer.len();
 
     // Pipeline: compress (optional) → encrypt (optional)
-    let (data, compressed) = apply_compression_pipeline(buffer, &mime_type);
+    let algorithm = negotiated_compression(&headers);
+    let (data, compressed) = apply_compression_pipeline(buffer, &mime_type, algorithm);
     let (data, encrypted) = apply_encryption_pipeline(data, &headers, &state.crypto_sessions).await;
 
-    let mut response = Response::new(Body::from(data));
+    let mut response = Response::new(Body::from_stream(throttled_bytes(data)));
     *response.status_mut() = StatusCode::OK;
     let resp_headers = response.headers_mut();
     resp_headers.insert(
@@ -409,33 +1296,63 @@ async fn download_chunk_handler(
         HeaderName::from_static("x-original-size"),
         original_size.to_string().parse().unwrap(),
     );
-    if compressed {
+    if let Some(algorithm) = compressed {
         resp_headers.insert(
             HeaderName::from_static("x-compression"),
-            "zstd".parse().unwrap(),
+            algorithm.as_str().parse().unwrap(),
         );
     }
-    if encrypted {
+    if let Some(cipher) = encrypted {
         resp_headers.insert(
             HeaderName::from_static("x-encryption"),
-            "aes-256-gcm".parse().unwrap(),
+            cipher.as_str().parse().unwrap(),
         );
     }
 
     // Track chunk download progress and emit events
-    let session_key = format!("{}_{}", file_id, client_ip);
+    let delivered = [(chunk_index, original_size as u64)];
+    match ranged {
+        Some(download) => download.record_chunks(delivered),
+        None => {
+            let session_key = format!("{}_{}", file_id, client_ip);
+            record_chunk_progress(&state, &session_key, delivered).await;
+        }
+    }
+
+    response
+}
+
+/// Record downloaded chunks for a chunked download session and emit progress
+///
+/// Each chunk is given as its index and original (pre-compression) size. The session
+/// is only completed once every chunk has been fetched, so partial range fetches
+/// (e.g. media seeking) never mark the file as downloaded.
+async fn record_chunk_progress(
+    state: &ServerState,
+    session_key: &str,
+    chunks: impl IntoIterator<Item = (usize, u64)>,
+) {
     let mut sessions = state.chunk_download_sessions.lock().await;
-    if let Some(session) = sessions.get_mut(&session_key) {
-        session.downloaded_chunks.insert(chunk_index);
+    if let Some(session) = sessions.get_mut(session_key) {
+        // First chunk after a restart: the client resumed without asking for meta again
+        if !session.announced {
+            let mut share_state = state.share_state.lock().await;
+            announce_chunk_session(state, &mut share_state, session);
+        }
+        for (chunk_index, original_size) in chunks {
+            session.record_chunk(chunk_index, original_size);
+        }
+        session.last_activity = std::time::Instant::now();
+        session.updated_at = current_timestamp_millis();
+        state.chunk_sessions_dirty.store(true, Ordering::SeqCst);
 
         let downloaded = session.downloaded_chunks.len();
         let total = session.chunk_count;
-        let progress = (downloaded as f64 / total as f64) * 100.0;
+        let progress = session.progress();
         let elapsed_secs = session.start_time.elapsed().as_secs_f64();
-        let downloaded_bytes = (downloaded as u64).min(total as u64) * HTTP_CHUNK_SIZE as u64;
-        let downloaded_bytes = downloaded_bytes.min(session.file_size);
+        let downloaded_bytes = session.delivered_bytes;
         let speed = if elapsed_secs > 0.0 {
-            (downloaded_bytes as f64 / elapsed_secs) as u64
+            (downloaded_bytes.saturating_sub(session.resumed_bytes) as f64 / elapsed_secs) as u64
         } else {
             0
         };
@@ -490,6 +1407,16 @@ async fn download_chunk_handler(
         }
 
         if downloaded >= total {
+            let limit = state
+                .share_state
+                .lock()
+                .await
+                .settings
+                .max_downloads_per_file
+                .filter(|&limit| limit > 0);
+            state
+                .download_count(&session.file_id, &session.file_name, limit)
+                .record(&state.app_handle);
             let _ = state.app_handle.emit(
                 "upload-complete",
                 UploadCompletePayload {
@@ -499,7 +1426,384 @@ async fn download_chunk_handler(
                     client_ip: session.client_ip.clone(),
                 },
             );
-            sessions.remove(&session_key);
+            sessions.remove(session_key);
+        }
+    }
+
+}
+
+/// Finalize the record of a chunked download that will not finish
+///
+/// Marks the record cancelled and emits `upload-cancelled`; the caller has already
+/// removed the session. Restored sessions the client never resumed have no record.
+fn cancel_chunk_session(state: &ServerState, session: &ChunkDownloadSession) {
+    state.chunk_sessions_dirty.store(true, Ordering::SeqCst);
+    if !session.announced {
+        return;
+    }
+    let _ = state.app_handle.emit(
+        "upload-cancelled",
+        UploadCancelledPayload {
+            upload_id: session.upload_id.clone(),
+            file_name: session.file_name.clone(),
+            file_size: session.file_size as i64,
+            transferred_bytes: session.delivered_bytes,
+            client_ip: session.client_ip.clone(),
+            status: super::models::TransferStatus::Cancelled,
+        },
+    );
+    send_final_record_update(
+        &state.record_updates,
+        (
+            session.upload_id.clone(),
+            RecordUpdate::Interrupted {
+                status: super::models::TransferStatus::Cancelled,
+                completed_at: current_timestamp_millis(),
+            },
+        ),
+    );
+}
+
+/// Drop chunked download sessions that have not fetched a chunk for a while
+///
+/// Closing the page simply stops chunk requests, so this is the only sign that a
+/// chunked download was abandoned.
+async fn sweep_stale_chunk_sessions(state: &ServerState) {
+    let stale: Vec<ChunkDownloadSession> = {
+        let mut sessions = state.chunk_download_sessions.lock().await;
+        let now = current_timestamp_millis();
+        let stale_keys: Vec<String> = sessions
+            .iter()
+            .filter(|(_, s)| {
+                if s.announced {
+                    s.last_activity.elapsed().as_secs() >= CHUNK_SESSION_STALE_SECS
+                } else {
+                    now.saturating_sub(s.updated_at) >= RESTORED_SESSION_MAX_AGE_MS
+                }
+            })
+            .map(|(key, _)| key.clone())
+            .collect();
+        stale_keys
+            .iter()
+            .filter_map(|key| sessions.remove(key))
+            .collect()
+    };
+    for session in &stale {
+        cancel_chunk_session(state, session);
+    }
+}
+
+/// Forget ranged downloads whose client stopped requesting the missing bytes
+fn sweep_stale_ranged_downloads(state: &ServerState) {
+    let stale: Vec<RangedDownload> = {
+        let mut downloads = state
+            .ranged_downloads
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let keys: Vec<(String, String)> = downloads
+            .iter()
+            .filter(|(_, download)| {
+                download.last_activity.elapsed().as_secs() >= CHUNK_SESSION_STALE_SECS
+            })
+            .map(|(key, _)| key.clone())
+            .collect();
+        keys.iter()
+            .filter_map(|key| downloads.remove(key))
+            .collect()
+    };
+
+    // The client gave up before every byte was served
+    for download in stale {
+        if let Some(record) = &download.record {
+            record.cancel(
+                download.served.covered_bytes(),
+                &state.app_handle,
+                &state.record_updates,
+            );
+        }
+    }
+}
+
+/// Add the record of a restored chunked download and emit `upload-start` for it
+///
+/// Called once per restored session, when its client first comes back.
+fn announce_chunk_session(
+    state: &ServerState,
+    share_state: &mut ShareState,
+    session: &mut ChunkDownloadSession,
+) {
+    session.announced = true;
+    session.start_time = std::time::Instant::now();
+
+    let mut record = ShareUploadRecord::new(session.file_name.clone(), session.file_size);
+    record.id = session.upload_id.clone();
+    record.uploaded_bytes = session.delivered_bytes;
+    record.progress = session.progress();
+    if let Some(request) = share_state
+        .access_requests
+        .values_mut()
+        .find(|r| r.ip == session.client_ip)
+    {
+        request.push_upload_record(record);
+    }
+
+    let _ = state.app_handle.emit(
+        "upload-start",
+        UploadStartPayload {
+            upload_id: session.upload_id.clone(),
+            file_name: session.file_name.clone(),
+            file_size: session.file_size as i64,
+            client_ip: session.client_ip.clone(),
+        },
+    );
+}
+
+/// Continue an existing session when its client asks for the file meta again
+///
+/// Sessions restored from disk always continue. A session already active in this run
+/// continues only when the client passes `resume_from`, and keeps its record without
+/// emitting `upload-start` again. Returns false when the caller should start a new
+/// session instead.
+async fn resume_chunk_session(
+    state: &ServerState,
+    session_key: &str,
+    resume_from: Option<usize>,
+) -> bool {
+    let mut sessions = state.chunk_download_sessions.lock().await;
+    let Some(session) = sessions.get_mut(session_key) else {
+        return false;
+    };
+    if session.announced && resume_from.is_none() {
+        return false;
+    }
+    if let Some(resume_from) = resume_from {
+        session.mark_resumed(resume_from);
+        state.chunk_sessions_dirty.store(true, Ordering::SeqCst);
+    }
+    if !session.announced {
+        let mut share_state = state.share_state.lock().await;
+        announce_chunk_session(state, &mut share_state, session);
+    }
+    session.last_activity = std::time::Instant::now();
+    true
+}
+
+/// Chunks of a chunked download already delivered to the requesting client
+#[derive(Debug, Serialize)]
+struct DownloadStatus {
+    file_id: String,
+    chunk_count: usize,
+    downloaded_chunks: Vec<usize>,
+}
+
+/// Report the chunks of a file delivered to this client, so a reloaded page can resume
+async fn download_status_handler(
+    ConnectInfo(client_addr): ConnectInfo<SocketAddr>,
+    AxumState(state): AxumState<Arc<ServerState>>,
+    Path(file_id): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    let client_ip = resolve_client_ip(&client_addr, &headers);
+    if let Err(resp) = check_download_access(&state, &client_ip).await {
+        return resp;
+    }
+
+    let session_key = format!("{}_{}", file_id, client_ip);
+    let sessions = state.chunk_download_sessions.lock().await;
+    let Some(session) = sessions.get(&session_key) else {
+        return (StatusCode::NOT_FOUND, "Download session not found").into_response();
+    };
+    let mut downloaded_chunks: Vec<usize> = session.downloaded_chunks.iter().copied().collect();
+    downloaded_chunks.sort_unstable();
+    Json(DownloadStatus {
+        file_id,
+        chunk_count: session.chunk_count,
+        downloaded_chunks,
+    })
+    .into_response()
+}
+
+/// Load persisted chunked download sessions for the files being shared
+///
+/// Sessions are only restored when the file is still shared and has the same size.
+async fn restore_chunk_sessions(state: &ServerState) {
+    let persisted = load_download_sessions(current_timestamp_millis());
+    if persisted.is_empty() {
+        return;
+    }
+    let file_paths = state.file_paths.lock().await.clone();
+    let mut sessions = state.chunk_download_sessions.lock().await;
+    for session in persisted {
+        let unchanged = file_paths
+            .get(&session.file_id)
+            .and_then(|path| std::fs::metadata(path).ok())
+            .is_some_and(|meta| meta.is_file() && meta.len() == session.file_size);
+        if !unchanged {
+            continue;
+        }
+        let key = format!("{}_{}", session.file_id, session.client_ip);
+        sessions
+            .entry(key)
+            .or_insert_with(|| ChunkDownloadSession::from_persisted(session));
+    }
+    tracing::debug!(count = sessions.len(), "Restored chunked download sessions");
+}
+
+/// Write chunked download sessions to disk if they changed since the last save
+///
+/// Sessions for files that are no longer shared are kept until they expire, so
+/// stopping one share does not discard downloads of another.
+async fn persist_chunk_sessions(state: &ServerState) {
+    if !state.chunk_sessions_dirty.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    let mut persisted: Vec<PersistedDownloadSession> = {
+        let sessions = state.chunk_download_sessions.lock().await;
+        sessions
+            .values()
+            .map(ChunkDownloadSession::to_persisted)
+            .collect()
+    };
+    let now = current_timestamp_millis();
+    let keys: HashSet<(String, String)> = persisted
+        .iter()
+        .map(|s| (s.file_id.clone(), s.client_ip.clone()))
+        .collect();
+    let file_paths = state.file_paths.lock().await;
+    persisted.extend(load_download_sessions(now).into_iter().filter(|s| {
+        !file_paths.contains_key(&s.file_id)
+            && !keys.contains(&(s.file_id.clone(), s.client_ip.clone()))
+    }));
+    drop(file_paths);
+    if let Err(e) = save_download_sessions(&persisted) {
+        tracing::warn!(error = %e, "Failed to persist chunked download sessions");
+    }
+}
+
+/// Upper bound on chunks returned by one range request
+const MAX_RANGE_CHUNKS: usize = 16;
+
+#[derive(Debug, Deserialize)]
+struct ChunkRangeQuery {
+    start: usize,
+    count: Option<usize>,
+}
+
+/// Download a run of processed chunks (compressed + encrypted) in one response
+///
+/// Lets a player fetch only the region it needs, e.g. when seeking in a video.
+/// Chunks are concatenated in order; `x-chunk-index` is the first index and
+/// `x-chunk-sizes` / `x-original-sizes` / `x-compressed-chunks` describe each one.
+async fn download_chunk_range_handler(
+    ConnectInfo(client_addr): ConnectInfo<SocketAddr>,
+    AxumState(state): AxumState<Arc<ServerState>>,
+    Path(file_id): Path<String>,
+    Query(query): Query<ChunkRangeQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let client_ip = resolve_client_ip(&client_addr, &headers);
+    if let Err(resp) = check_download_access(&state, &client_ip).await {
+        return resp;
+    }
+
+    let file_path = {
+        let file_paths = state.file_paths.lock().await;
+        file_paths.get(&file_id).cloned()
+    };
+    let Some(path) = file_path.filter(|p| p.is_file()) else {
+        return (StatusCode::NOT_FOUND, "File not found").into_response();
+    };
+
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("download")
+        .to_string();
+    let file_size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+    let mime_type = FileMetadata::infer_mime_type(&file_name);
+
+    let chunk_count = file_size.div_ceil(HTTP_CHUNK_SIZE as u64) as usize;
+    if query.start >= chunk_count {
+        return (StatusCode::RANGE_NOT_SATISFIABLE, "Chunk index out of range").into_response();
+    }
+    let count = query
+        .count
+        .unwrap_or(1)
+        .clamp(1, MAX_RANGE_CHUNKS)
+        .min(chunk_count - query.start);
+
+    let ranged = match state
+        .sessionless_chunk_download(&file_id, &file_name, &client_ip, &path, file_size)
+        .await
+    {
+        Ok(ranged) => ranged,
+        Err(resp) => return resp,
+    };
+
+    let mut body = Vec::new();
+    let mut chunk_sizes = Vec::with_capacity(count);
+    let mut original_sizes = Vec::with_capacity(count);
+    let mut delivered = Vec::with_capacity(count);
+    let mut compressed_flags = Vec::with_capacity(count);
+    let mut cipher = None;
+    let algorithm = negotiated_compression(&headers);
+    for chunk_index in query.start..query.start + count {
+        let buffer = match read_file_chunk(&path, chunk_index, file_size).await {
+            Ok(data) => data,
+            Err(resp) => return resp,
+        };
+        original_sizes.push(buffer.len().to_string());
+        delivered.push((chunk_index, buffer.len() as u64));
+
+        let (data, compressed) = apply_compression_pipeline(buffer, &mime_type, algorithm);
+        let (data, encrypted) =
+            apply_encryption_pipeline(data, &headers, &state.crypto_sessions).await;
+        chunk_sizes.push(data.len().to_string());
+        compressed_flags.push(if compressed.is_some() { "1" } else { "0" });
+        cipher = cipher.or(encrypted);
+        body.extend_from_slice(&data);
+    }
+
+    let mut response = Response::new(Body::from_stream(throttled_bytes(body)));
+    let resp_headers = response.headers_mut();
+    resp_headers.insert(
+        HeaderName::from_static("x-chunk-index"),
+        query.start.to_string().parse().unwrap(),
+    );
+    resp_headers.insert(
+        HeaderName::from_static("x-chunk-count"),
+        count.to_string().parse().unwrap(),
+    );
+    resp_headers.insert(
+        HeaderName::from_static("x-chunk-sizes"),
+        chunk_sizes.join(",").parse().unwrap(),
+    );
+    resp_headers.insert(
+        HeaderName::from_static("x-original-sizes"),
+        original_sizes.join(",").parse().unwrap(),
+    );
+    resp_headers.insert(
+        HeaderName::from_static("x-compressed-chunks"),
+        compressed_flags.join(",").parse().unwrap(),
+    );
+    if let Some(algorithm) = algorithm.filter(|_| compressed_flags.contains(&"1")) {
+        resp_headers.insert(
+            HeaderName::from_static("x-compression"),
+            algorithm.as_str().parse().unwrap(),
+        );
+    }
+    if let Some(cipher) = cipher {
+        resp_headers.insert(
+            HeaderName::from_static("x-encryption"),
+            cipher.as_str().parse().unwrap(),
+        );
+    }
+
+    match ranged {
+        Some(download) => download.record_chunks(delivered),
+        None => {
+            let session_key = format!("{}_{}", file_id, client_ip);
+            record_chunk_progress(&state, &session_key, delivered).await;
         }
     }
 
@@ -507,23 +1811,30 @@ async fn download_chunk_handler(
 }
 
 /// Handle new visitor access request creation and auto-accept logic
-/// Returns whether the visitor has been granted access
+/// Returns whether the visitor has been granted access, or a 429 response
+/// when the IP creates access requests too quickly
 fn handle_new_visitor(
     share_state: &mut super::models::ShareState,
+    state: &ServerState,
     client_ip: &str,
     user_agent: &str,
-    app_handle: &AppHandle,
-) -> bool {
+) -> Result<bool, Response> {
+    let app_handle = &state.app_handle;
     let has_request = share_state
         .access_requests
         .values()
         .any(|r| r.ip == client_ip);
 
     if !has_request {
+        if !state.allow_access_request(client_ip) {
+            tracing::warn!(client_ip = %client_ip, "Access request rate limit exceeded");
+            return Err(too_many_access_requests_response());
+        }
+
         let mut new_request =
             super::models::AccessRequest::new(client_ip.to_string(), Some(user_agent.to_string()));
 
-        if share_state.settings.auto_accept {
+        if share_state.should_auto_accept(client_ip) {
             new_request.status = super::models::AccessRequestStatus::Accepted;
         }
 
@@ -539,11 +1850,11 @@ fn handle_new_visitor(
             }
 
             let _ = app_handle.emit("access-request-accepted", new_request);
-            return true;
+            return Ok(true);
         }
     }
 
-    false
+    Ok(false)
 }
 
 /// Index handler
@@ -552,7 +1863,7 @@ async fn index_handler(
     headers: HeaderMap,
     AxumState(state): AxumState<Arc<ServerState>>,
 ) -> Response {
-    let client_ip = client_addr.ip().to_string();
+    let client_ip = resolve_client_ip(&client_addr, &headers);
     let user_agent = headers
         .get(header::USER_AGENT)
         .and_then(|v| v.to_str().ok())
@@ -567,15 +1878,25 @@ async fn index_handler(
 
     {
         let share_state = state.share_state.lock().await;
-        if share_state.share_info.is_none() {
-            return Html(generate_share_ended_html(is_english)).into_response();
+        if share_state.is_share_ended() {
+            return page_shell_response(
+                &headers,
+                "share-ended",
+                is_english,
+                generate_share_ended_html,
+            );
         }
     }
 
     {
         let share_state = state.share_state.lock().await;
         if share_state.is_ip_rejected(&client_ip) {
-            return Html(generate_access_denied_html(is_english)).into_response();
+            return page_shell_response(
+                &headers,
+                "access-denied",
+                is_english,
+                generate_access_denied_html,
+            );
         }
     }
 
@@ -603,18 +1924,32 @@ async fn index_handler(
                 }
             }
 
-            return Html(generate_pin_input_html(is_english)).into_response();
+            return page_shell_response(&headers, "pin", is_english, generate_pin_input_html);
         }
 
         if !has_pin {
-            let granted_access = handle_new_visitor(&mut share_state, &client_ip, &user_agent, &state.app_handle);
+            let granted_access =
+                match handle_new_visitor(&mut share_state, &state, &client_ip, &user_agent) {
+                    Ok(granted) => granted,
+                    Err(response) => return response,
+                };
             if !granted_access && !share_state.is_ip_allowed(&client_ip) {
-                return Html(generate_waiting_response_html(is_english)).into_response();
+                return page_shell_response(
+                    &headers,
+                    "waiting",
+                    is_english,
+                    generate_waiting_response_html,
+                );
             }
         }
 
         if !share_state.is_ip_allowed(&client_ip) {
-            return Html(generate_waiting_response_html(is_english)).into_response();
+            return page_shell_response(
+                &headers,
+                "waiting",
+                is_english,
+                generate_waiting_response_html,
+            );
         }
     }
 
@@ -622,21 +1957,21 @@ async fn index_handler(
     let has_access = share_state.is_ip_allowed(&client_ip);
 
     if !has_access {
-        return Html(generate_waiting_response_html(is_english)).into_response();
+        return page_shell_response(&headers, "waiting", is_english, generate_waiting_response_html);
     }
 
-    let html = generate_file_list_html(is_english);
-    Html(html).into_response()
+    page_shell_response(&headers, "files", is_english, generate_file_list_html)
 }
 
 /// File list API
 async fn list_files_handler(
     ConnectInfo(client_addr): ConnectInfo<SocketAddr>,
     AxumState(state): AxumState<Arc<ServerState>>,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
     let share_state = state.share_state.lock().await;
 
-    if share_state.share_info.is_none() {
+    if share_state.is_share_ended() {
         return (
             StatusCode::NOT_FOUND,
             Json(FilesResponse {
@@ -646,7 +1981,7 @@ async fn list_files_handler(
         );
     }
 
-    let client_ip = client_addr.ip().to_string();
+    let client_ip = resolve_client_ip(&client_addr, &headers);
 
     if share_state.is_ip_rejected(&client_ip) {
         return (
@@ -746,13 +2081,26 @@ async fn verify_pin_handler(
     AxumState(state): AxumState<Arc<ServerState>>,
     Json(payload): Json<VerifyPinRequest>,
 ) -> impl IntoResponse {
-    let client_ip = client_addr.ip().to_string();
+    let client_ip = resolve_client_ip(&client_addr, &headers);
     let user_agent = headers
         .get(header::USER_AGENT)
         .and_then(|v| v.to_str().ok())
         .map(|s| http_common::parse_user_agent(s).to_string());
     let mut share_state = state.share_state.lock().await;
 
+    // Clients past the rejection limit are turned away without creating a new request
+    if share_state.is_ip_auto_rejected(&client_ip) {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(super::models::PinVerifyResult {
+                success: false,
+                remaining_attempts: None,
+                locked: false,
+                locked_until: None,
+            }),
+        );
+    }
+
     if let Some(attempt) = share_state.pin_attempts.get(&client_ip) {
         if attempt.is_still_locked() {
             return (
@@ -791,7 +2139,7 @@ async fn verify_pin_handler(
 
         let mut new_request = super::models::AccessRequest::new(client_ip.clone(), user_agent);
 
-        if share_state.settings.auto_accept {
+        if share_state.should_auto_accept(&client_ip) {
             new_request.status = super::models::AccessRequestStatus::Accepted;
         }
 
@@ -842,8 +2190,8 @@ async fn request_status_handler(
     ConnectInfo(client_addr): ConnectInfo<SocketAddr>,
     headers: HeaderMap,
     AxumState(state): AxumState<Arc<ServerState>>,
-) -> impl IntoResponse {
-    let client_ip = client_addr.ip().to_string();
+) -> Response {
+    let client_ip = resolve_client_ip(&client_addr, &headers);
     let user_agent = headers
         .get(header::USER_AGENT)
         .and_then(|v| v.to_str().ok())
@@ -871,7 +2219,7 @@ async fn request_status_handler(
             }
         }
         None => {
-            let auto_accept = share_state.settings.auto_accept;
+            let auto_accept = share_state.should_auto_accept(&client_ip);
             let has_pin = share_state.settings.pin.is_some()
                 && !share_state
                     .settings
@@ -880,7 +2228,17 @@ async fn request_status_handler(
                     .map_or(true, String::is_empty);
             let is_verified = share_state.is_ip_verified(&client_ip);
 
-            if auto_accept && !has_pin && !is_verified {
+            if share_state.is_ip_auto_rejected(&client_ip) {
+                RequestStatusResponse {
+                    has_request: true,
+                    status: Some("rejected".to_string()),
+                    waiting_response: false,
+                }
+            } else if auto_accept && !has_pin && !is_verified {
+                // Polling an existing request is free; only creating one counts
+                if !state.allow_access_request(&client_ip) {
+                    return too_many_access_requests_response();
+                }
                 let mut new_request = super::models::AccessRequest::new(
                     client_ip.clone(),
                     Some(user_agent.to_string()),
@@ -920,10 +2278,11 @@ async fn request_status_handler(
         }
     };
 
-    (StatusCode::OK, Json(response))
+    (StatusCode::OK, Json(response)).into_response()
 }
 
 /// Build a Range partial content response
+#[allow(clippy::too_many_arguments)]
 async fn build_range_response(
     path: &std::path::Path,
     file_name: &str,
@@ -932,6 +2291,7 @@ async fn build_range_response(
     end: u64,
     mime_type: &str,
     etag: &str,
+    on_served: OnServed,
 ) -> Response {
     let content_length = end - start + 1;
 
@@ -946,7 +2306,11 @@ async fn build_range_response(
             }
 
             let limited = file.take(content_length);
-            let stream = ReaderStream::new(limited);
+            let stream = ServedRangeStream::new(
+                ThrottledStream::new(ReaderStream::new(limited)),
+                start,
+                on_served,
+            );
             let body = Body::from_stream(stream);
 
             let mut response = Response::new(body);
@@ -988,7 +2352,82 @@ async fn build_range_response(
     }
 }
 
+/// Build a `multipart/byteranges` response for a request with several ranges
+///
+/// Each part is read from the file only when the body reaches it.
+async fn build_multipart_range_response(
+    path: &std::path::Path,
+    file_name: &str,
+    file_size: u64,
+    ranges: &[(u64, u64)],
+    mime_type: &str,
+    etag: &str,
+    on_served: OnServed,
+) -> Response {
+    if let Err(e) = File::open(path).await {
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Open file failed: {}", e),
+        )
+            .into_response();
+    }
+
+    let boundary = uuid::Uuid::new_v4().simple().to_string();
+    let closing = format!("\r\n--{}--\r\n", boundary);
+    let mut content_length = closing.len() as u64;
+    let mut parts: Vec<futures::stream::BoxStream<'static, std::io::Result<Bytes>>> =
+        Vec::with_capacity(ranges.len() * 2 + 1);
+    for &(start, end) in ranges {
+        let part_header = format!(
+            "\r\n--{}\r\nContent-Type: {}\r\nContent-Range: bytes {}-{}/{}\r\n\r\n",
+            boundary, mime_type, start, end, file_size
+        );
+        content_length += part_header.len() as u64 + (end - start + 1);
+        parts.push(futures::stream::once(async move { Ok(Bytes::from(part_header)) }).boxed());
+
+        let path = path.to_path_buf();
+        let part = futures::stream::once(async move {
+            let mut file = File::open(&path).await?;
+            file.seek(std::io::SeekFrom::Start(start)).await?;
+            Ok::<_, std::io::Error>(ThrottledStream::new(ReaderStream::new(
+                file.take(end - start + 1),
+            )))
+        })
+        .try_flatten()
+        .boxed();
+        parts.push(ServedRangeStream::new(part, start, on_served.clone()).boxed());
+    }
+    parts.push(futures::stream::once(async move { Ok(Bytes::from(closing)) }).boxed());
+
+    let mut response = Response::new(Body::from_stream(futures::stream::iter(parts).flatten()));
+    *response.status_mut() = StatusCode::PARTIAL_CONTENT;
+    let resp_headers = response.headers_mut();
+    resp_headers.insert(
+        header::CONTENT_TYPE,
+        format!("multipart/byteranges; boundary={}", boundary)
+            .parse()
+            .unwrap(),
+    );
+    resp_headers.insert(
+        header::CONTENT_LENGTH,
+        content_length.to_string().parse().unwrap(),
+    );
+    resp_headers.insert(header::ACCEPT_RANGES, "bytes".parse().unwrap());
+    resp_headers.insert(header::ETAG, etag.parse().unwrap());
+    resp_headers.insert(
+        header::CONTENT_DISPOSITION,
+        format!(
+            "attachment; filename*=UTF-8''{}",
+            urlencoding::encode(file_name)
+        )
+        .parse()
+        .unwrap(),
+    );
+    response
+}
+
 /// Build a full file download response with progress tracking stream
+#[allow(clippy::too_many_arguments)]
 async fn build_full_download_response(
     path: &std::path::Path,
     file_name: &str,
@@ -998,6 +2437,7 @@ async fn build_full_download_response(
     state: &Arc<ServerState>,
     upload_id: String,
     client_ip: String,
+    download_counts: Vec<DownloadCount>,
 ) -> Response {
     match File::open(path).await {
         Ok(file) => {
@@ -1005,12 +2445,13 @@ async fn build_full_download_response(
             let progress_stream = ProgressTrackingStream::new(
                 reader_stream,
                 state.app_handle.clone(),
-                state.share_state.clone(),
+                state.record_updates.clone(),
                 upload_id.clone(),
                 file_name.to_string(),
                 client_ip,
                 file_size,
-            );
+            )
+            .with_download_counts(download_counts);
             let body = Body::from_stream(progress_stream);
 
             let mut response = Response::new(body);
@@ -1069,7 +2510,7 @@ async fn file_download_handler(
     Path(file_id): Path<String>,
     headers: HeaderMap,
 ) -> Response {
-    let client_ip = client_addr.ip().to_string();
+    let client_ip = resolve_client_ip(&client_addr, &headers);
 
     if let Err(resp) = check_download_access(&state, &client_ip).await {
         return resp;
@@ -1103,56 +2544,443 @@ async fn file_download_handler(
                 }
             }
 
-            let upload_record = ShareUploadRecord::new(file_name.clone(), file_size);
-            let upload_id = upload_record.id.clone();
-            {
-                let mut share_state = state.share_state.lock().await;
-                if let Some(request) = share_state
-                    .access_requests
-                    .values_mut()
-                    .find(|r| r.ip == client_ip)
-                {
-                    request.upload_records.insert(0, upload_record);
-                }
-            }
+            // Check for Range request (plaintext mode)
+            let range_header = match requested_range(
+                headers.get(header::RANGE).and_then(|v| v.to_str().ok()),
+                file_size,
+            ) {
+                Ok(range) => range,
+                Err(resp) => return resp,
+            };
 
-            let _ = state.app_handle.emit(
-                "upload-start",
-                UploadStartPayload {
-                    upload_id: upload_id.clone(),
-                    file_name: file_name.clone(),
-                    file_size: file_size as i64,
-                    client_ip: client_ip.clone(),
-                },
-            );
+            let permit = match state.try_acquire_download().await {
+                Ok(permit) => permit,
+                Err(resp) => return resp,
+            };
 
-            // Check for Range request (plaintext mode)
-            let range_header = headers
-                .get(header::RANGE)
-                .and_then(|v| v.to_str().ok())
-                .and_then(|s| parse_range(s, file_size));
+            // Whole-file requests are counted when their body completes; ranged ones
+            // once the client has received every byte of the file
+            let download_count = state
+                .check_download_limit(&file_id)
+                .await
+                .map(|limit| state.download_count(&file_id, &file_name, limit));
+
+            let response = match range_header {
+                Some(ranges) => {
+                    // A parallel range download issues many requests for one file; they
+                    // share one record that completes once every byte has been served
+                    let download = match state
+                        .ranged_download(
+                            &client_ip,
+                            &etag,
+                            file_size,
+                            Some(&file_name),
+                            download_count.map(|count| vec![count]),
+                        )
+                        .await
+                    {
+                        Ok(download) => download,
+                        Err(resp) => return resp,
+                    };
+                    serve_ranges(
+                        &path, &file_name, file_size, &ranges, &mime_type, &etag, download,
+                    )
+                    .await
+                }
+                None => {
+                    let download_count = match download_count {
+                        Ok(count) => count,
+                        Err(resp) => return resp,
+                    };
+                    let upload_record = ShareUploadRecord::new(file_name.clone(), file_size);
+                    let upload_id = upload_record.id.clone();
+                    if let Some(request) = state
+                        .share_state
+                        .lock()
+                        .await
+                        .access_requests
+                        .values_mut()
+                        .find(|r| r.ip == client_ip)
+                    {
+                        request.push_upload_record(upload_record);
+                    }
 
-            if let Some((start, end)) = range_header {
-                return build_range_response(&path, &file_name, file_size, start, end, &mime_type, &etag).await;
-            }
+                    let _ = state.app_handle.emit(
+                        "upload-start",
+                        UploadStartPayload {
+                            upload_id: upload_id.clone(),
+                            file_name: file_name.clone(),
+                            file_size: file_size as i64,
+                            client_ip: client_ip.clone(),
+                        },
+                    );
+
+                    // Full file download with progress tracking
+                    build_full_download_response(
+                        &path,
+                        &file_name,
+                        file_size,
+                        &mime_type,
+                        &etag,
+                        &state,
+                        upload_id,
+                        client_ip,
+                        vec![download_count],
+                    )
+                    .await
+                }
+            };
+            hold_download_permit(response, permit)
+        }
+        None => {
+            Html("<html><body><h1>文件不存在</h1></body></html>").into_response()
+        }
+    }
+}
 
-            // Full file download with progress tracking
-            build_full_download_response(
-                &path,
-                &file_name,
+/// Serve one range, or several as `multipart/byteranges`, reporting the bytes to `download`
+async fn serve_ranges(
+    path: &std::path::Path,
+    file_name: &str,
+    file_size: u64,
+    ranges: &[(u64, u64)],
+    mime_type: &str,
+    etag: &str,
+    download: RangedDownloadHandle,
+) -> Response {
+    match *ranges {
+        [(start, end)] => {
+            build_range_response(
+                path,
+                file_name,
                 file_size,
-                &mime_type,
-                &etag,
-                &state,
-                upload_id,
-                client_ip,
+                start,
+                end,
+                mime_type,
+                etag,
+                download.on_served(),
             )
             .await
         }
-        None => {
-            Html("<html><body><h1>文件不存在</h1></body></html>").into_response()
+        _ => {
+            build_multipart_range_response(
+                path,
+                file_name,
+                file_size,
+                ranges,
+                mime_type,
+                etag,
+                download.on_served(),
+            )
+            .await
+        }
+    }
+}
+
+/// Serve a shared file for in-browser preview
+///
+/// Only extensions on the share's preview allowlist are sent `inline`; anything
+/// else (notably HTML/SVG) is forced to `attachment`. Responses always carry a
+/// restrictive CSP and `nosniff` so a crafted file cannot run in the share origin.
+async fn preview_handler(
+    ConnectInfo(client_addr): ConnectInfo<SocketAddr>,
+    AxumState(state): AxumState<Arc<ServerState>>,
+    Path(file_id): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    let client_ip = resolve_client_ip(&client_addr, &headers);
+
+    if let Err(resp) = check_download_access(&state, &client_ip).await {
+        return resp;
+    }
+
+    let file_path = {
+        let file_paths = state.file_paths.lock().await;
+        file_paths.get(&file_id).cloned()
+    };
+    let Some(path) = file_path.filter(|p| p.is_file()) else {
+        return Html("<html><body><h1>文件不存在</h1></body></html>").into_response();
+    };
+
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("download")
+        .to_string();
+    let file_size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+    let inline = state
+        .share_state
+        .lock()
+        .await
+        .settings
+        .is_preview_allowed(&file_name);
+
+    let mime_type = FileMetadata::infer_mime_type(&file_name);
+    let content_type = if !inline {
+        "application/octet-stream".to_string()
+    } else if mime_type.starts_with("text/") {
+        // Render text as plain text regardless of its declared subtype
+        "text/plain; charset=utf-8".to_string()
+    } else {
+        mime_type
+    };
+
+    // A completed preview delivers the whole file, so it counts like a ranged download
+    let download_count = state
+        .check_download_limit(&file_id)
+        .await
+        .map(|limit| vec![state.download_count(&file_id, &file_name, limit)]);
+    let etag = generate_etag(&path, file_size);
+    let download = match state
+        .ranged_download(&client_ip, &etag, file_size, None, download_count)
+        .await
+    {
+        Ok(download) => download,
+        Err(resp) => return resp,
+    };
+
+    let permit = match state.try_acquire_download().await {
+        Ok(permit) => permit,
+        Err(resp) => return resp,
+    };
+
+    let file = match File::open(&path).await {
+        Ok(file) => file,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Open file failed: {}", e),
+            )
+                .into_response();
+        }
+    };
+
+    let body = ServedRangeStream::new(
+        ThrottledStream::new(ReaderStream::new(file)),
+        0,
+        download.on_served(),
+    );
+    let mut response = Response::new(Body::from_stream(body));
+    let resp_headers = response.headers_mut();
+    resp_headers.insert(
+        header::CONTENT_TYPE,
+        content_type
+            .parse()
+            .unwrap_or_else(|_| "application/octet-stream".parse().unwrap()),
+    );
+    resp_headers.insert(
+        header::CONTENT_LENGTH,
+        file_size.to_string().parse().unwrap(),
+    );
+    let disposition = if inline { "inline" } else { "attachment" };
+    resp_headers.insert(
+        header::CONTENT_DISPOSITION,
+        format!(
+            "{}; filename*=UTF-8''{}",
+            disposition,
+            urlencoding::encode(&file_name)
+        )
+        .parse()
+        .unwrap(),
+    );
+    resp_headers.insert(
+        header::CONTENT_SECURITY_POLICY,
+        PREVIEW_CSP.parse().unwrap(),
+    );
+    resp_headers.insert(header::X_CONTENT_TYPE_OPTIONS, "nosniff".parse().unwrap());
+
+    hold_download_permit(response, permit)
+}
+
+/// File name offered for the all-files archive
+const ZIP_ARCHIVE_NAME: &str = "PureSend.zip";
+
+/// Download every shared file as one stored ZIP archive
+///
+/// The archive layout is computed up front, so the response carries an exact
+/// Content-Length and Range requests (guarded by If-Range) can resume it.
+async fn download_all_handler(
+    ConnectInfo(client_addr): ConnectInfo<SocketAddr>,
+    AxumState(state): AxumState<Arc<ServerState>>,
+    headers: HeaderMap,
+) -> Response {
+    let client_ip = resolve_client_ip(&client_addr, &headers);
+
+    if let Err(resp) = check_download_access(&state, &client_ip).await {
+        return resp;
+    }
+
+    let shared: Vec<(String, String, PathBuf)> = {
+        let file_paths = state.file_paths.lock().await;
+        let hash_to_filename = state.hash_to_filename.lock().await;
+        file_paths
+            .iter()
+            .filter(|(_, path)| path.is_file())
+            .map(|(id, path)| {
+                let name = hash_to_filename
+                    .get(id)
+                    .cloned()
+                    .unwrap_or_else(|| id.clone());
+                (name, id.clone(), path.clone())
+            })
+            .collect()
+    };
+    if shared.is_empty() {
+        return Html("<html><body><h1>文件不存在</h1></body></html>").into_response();
+    }
+
+    // Files that used up their download limit are left out; each one included is
+    // counted once the whole archive has been delivered
+    let mut files = Vec::with_capacity(shared.len());
+    let mut download_counts = Vec::with_capacity(shared.len());
+    for (name, file_id, path) in shared {
+        if let Ok(limit) = state.check_download_limit(&file_id).await {
+            download_counts.push(state.download_count(&file_id, &name, limit));
+            files.push((name, path));
+        }
+    }
+    if files.is_empty() {
+        return download_limit_response();
+    }
+    // Byte offsets must be identical across requests for Range to work
+    files.sort();
+
+    let layout = match ZipLayout::new(files) {
+        Ok(layout) => Arc::new(layout),
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to prepare archive: {}", e),
+            )
+                .into_response();
+        }
+    };
+    let total_size = layout.total_size();
+    let etag = layout.etag();
+
+    if let Some(if_none_match) = headers.get(header::IF_NONE_MATCH) {
+        if if_none_match.to_str().ok() == Some(&etag) {
+            return StatusCode::NOT_MODIFIED.into_response();
+        }
+    }
+
+    // Only resume when the client still holds the same archive
+    let if_range_matches = headers
+        .get(header::IF_RANGE)
+        .and_then(|v| v.to_str().ok())
+        .is_none_or(|v| v == etag);
+    let range = match requested_range(
+        headers
+            .get(header::RANGE)
+            .and_then(|v| v.to_str().ok())
+            .filter(|_| if_range_matches),
+        total_size,
+    ) {
+        Ok(Some(ranges)) if ranges.len() == 1 => Some(ranges[0]),
+        // The archive is generated on the fly, so several ranges get the whole archive
+        Ok(_) => None,
+        Err(resp) => return resp,
+    };
+    let (start, end) = range.unwrap_or((0, total_size - 1));
+
+    let (download_counts, ranged_download) = match range {
+        Some(_) => match state
+            .ranged_download(&client_ip, &etag, total_size, None, Ok(download_counts))
+            .await
+        {
+            Ok(download) => (Vec::new(), Some(download)),
+            Err(resp) => return resp,
+        },
+        None => (download_counts, None),
+    };
+
+    let permit = match state.try_acquire_download().await {
+        Ok(permit) => permit,
+        Err(resp) => return resp,
+    };
+
+    let (reader, mut writer) = tokio::io::duplex(HTTP_CHUNK_SIZE);
+    let crc_cache = state.zip_crc_cache.clone();
+    let writer_layout = layout.clone();
+    tokio::spawn(async move {
+        if let Err(e) = writer_layout
+            .write_range(start, end, &crc_cache, &mut writer)
+            .await
+        {
+            tracing::warn!(error = %e, "ZIP archive streaming failed");
+        }
+    });
+
+    let body = if let Some(download) = ranged_download {
+        Body::from_stream(ServedRangeStream::new(
+            ThrottledStream::new(ReaderStream::new(reader)),
+            start,
+            download.on_served(),
+        ))
+    } else {
+        let upload_record = ShareUploadRecord::new(ZIP_ARCHIVE_NAME.to_string(), total_size);
+        let upload_id = upload_record.id.clone();
+        {
+            let mut share_state = state.share_state.lock().await;
+            if let Some(request) = share_state
+                .access_requests
+                .values_mut()
+                .find(|r| r.ip == client_ip)
+            {
+                request.push_upload_record(upload_record);
+            }
         }
+
+        let _ = state.app_handle.emit(
+            "upload-start",
+            UploadStartPayload {
+                upload_id: upload_id.clone(),
+                file_name: ZIP_ARCHIVE_NAME.to_string(),
+                file_size: total_size as i64,
+                client_ip: client_ip.clone(),
+            },
+        );
+
+        Body::from_stream(
+            ProgressTrackingStream::new(
+                ReaderStream::new(reader),
+                state.app_handle.clone(),
+                state.record_updates.clone(),
+                upload_id,
+                ZIP_ARCHIVE_NAME.to_string(),
+                client_ip,
+                total_size,
+            )
+            .with_download_counts(download_counts),
+        )
+    };
+
+    let mut response = Response::new(body);
+    let resp_headers = response.headers_mut();
+    if range.is_some() {
+        *response.status_mut() = StatusCode::PARTIAL_CONTENT;
+        resp_headers.insert(
+            header::CONTENT_RANGE,
+            format!("bytes {}-{}/{}", start, end, total_size)
+                .parse()
+                .unwrap(),
+        );
     }
+    resp_headers.insert(header::CONTENT_TYPE, "application/zip".parse().unwrap());
+    resp_headers.insert(
+        header::CONTENT_LENGTH,
+        (end - start + 1).to_string().parse().unwrap(),
+    );
+    resp_headers.insert(header::ACCEPT_RANGES, "bytes".parse().unwrap());
+    resp_headers.insert(header::ETAG, etag.parse().unwrap());
+    resp_headers.insert(
+        header::CONTENT_DISPOSITION,
+        format!("attachment; filename=\"{}\"", ZIP_ARCHIVE_NAME)
+            .parse()
+            .unwrap(),
+    );
+
+    hold_download_permit(response, permit)
 }
 
 // ─── Helper functions for download_chunk_handler ─────────────────────────────
@@ -1208,19 +3036,44 @@ async fn read_file_chunk(
     Ok(buffer)
 }
 
-fn apply_compression_pipeline(data: Vec<u8>, mime_type: &str) -> (Vec<u8>, bool) {
-    let compression_config = get_compression_config();
-    let mut compressed = false;
+/// Compression algorithm to use for a download, if any.
+///
+/// Browsers list the algorithms they can decode in `X-Accept-Compression`
+/// (an empty value means none). Clients that omit the header get the
+/// configured algorithm.
+fn negotiated_compression(headers: &HeaderMap) -> Option<CompressionAlgorithm> {
+    let config = get_compression_config();
+    if !config.enabled {
+        return None;
+    }
+    match headers
+        .get("x-accept-compression")
+        .and_then(|v| v.to_str().ok())
+    {
+        Some(accepted) => accepted
+            .split(',')
+            .filter_map(|name| CompressionAlgorithm::from_name(name.trim()))
+            .any(|algorithm| algorithm == config.algorithm)
+            .then_some(config.algorithm),
+        None => Some(config.algorithm),
+    }
+}
+
+fn apply_compression_pipeline(
+    data: Vec<u8>,
+    mime_type: &str,
+    algorithm: Option<CompressionAlgorithm>,
+) -> (Vec<u8>, Option<CompressionAlgorithm>) {
+    let mut compressed = None;
     let mut result_data = data;
 
-    if compression_config.enabled {
-        if let Some(compressor) = create_compressor_from_config() {
-            if let Some(level) = compressor.get_level(mime_type) {
-                if let Ok(compressed_data) = Compressor::compress(&result_data, level) {
-                    if compressed_data.len() < result_data.len() {
-                        result_data = compressed_data;
-                        compressed = true;
-                    }
+    if let (Some(algorithm), Some(compressor)) = (algorithm, create_compressor_from_config()) {
+        let compressor = compressor.with_algorithm(algorithm);
+        if let Some(level) = compressor.get_level(mime_type) {
+            if let Ok(compressed_data) = compressor.compress(&result_data, level) {
+                if compressed_data.len() < result_data.len() {
+                    result_data = compressed_data;
+                    compressed = Some(algorithm);
                 }
             }
         }
@@ -1233,9 +3086,9 @@ async fn apply_encryption_pipeline(
     data: Vec<u8>,
     headers: &HeaderMap,
     crypto_sessions: &Arc<Mutex<HttpCryptoSessionManager>>,
-) -> (Vec<u8>, bool) {
+) -> (Vec<u8>, Option<CipherSuite>) {
     let encryption_enabled = is_encryption_enabled();
-    let mut encrypted = false;
+    let mut encrypted = None;
     let mut result_data = data;
 
     if encryption_enabled {
@@ -1250,10 +3103,14 @@ async fn apply_encryption_pipeline(
                 match session.encrypt(&result_data) {
                     Ok(encrypted_data) => {
                         result_data = encrypted_data;
-                        encrypted = true;
+                        encrypted = Some(session.cipher_suite());
                     }
                     Err(e) => {
-                        eprintln!("Chunk encryption failed: {}", e);
+                        tracing::error!(
+                            session_id = %session_id,
+                            error = %e,
+                            "Chunk encryption failed"
+                        );
                     }
                 }
             }
@@ -1277,11 +3134,33 @@ struct UploadStartPayload {
 struct UploadCompletePayload {
     upload_id: String,
     file_name: String,
-    file_size: i64,
-    client_ip: String,
+    file_size: i64,
+    client_ip: String,
+}
+
+/// Payload of `upload-cancelled`: a download that ended before the whole file was sent
+#[derive(Debug, Clone, Serialize)]
+struct UploadCancelledPayload {
+    upload_id: String,
+    file_name: String,
+    file_size: i64,
+    transferred_bytes: u64,
+    client_ip: String,
+    /// `cancelled` when the client went away, `failed` when reading the file failed
+    status: super::models::TransferStatus,
+}
+
+
+/// Payload of `download-limit-reached`
+#[derive(Debug, Clone, Serialize)]
+struct DownloadLimitReachedPayload {
+    file_id: String,
+    file_name: String,
+    /// Completed downloads of the file
+    downloads: u32,
+    limit: u32,
 }
 
-
 #[derive(Debug, Serialize)]
 struct DownloadMeta {
     file_id: String,
@@ -1319,10 +3198,128 @@ struct RequestStatusResponse {
 
 // ─── Progress tracking stream ───────────────────────────────────────────────
 
-struct ProgressTrackingStream {
-    inner: ReaderStream<File>,
+/// Change to a share upload record, applied by the record updater task
+#[derive(Debug, Clone)]
+enum RecordUpdate {
+    Progress {
+        uploaded_bytes: u64,
+        progress: f64,
+        speed: u64,
+    },
+    Completed {
+        completed_at: u64,
+    },
+    /// The download ended early (client disconnected or the read failed)
+    Interrupted {
+        status: super::models::TransferStatus,
+        completed_at: u64,
+    },
+}
+
+type RecordUpdateSender = tokio::sync::mpsc::Sender<(String, RecordUpdate)>;
+
+/// Capacity of the record update channel; progress ticks beyond this are dropped
+const RECORD_UPDATE_CHANNEL_SIZE: usize = 256;
+
+/// Queue a record update that must not be lost, waiting for room if the queue is full
+fn send_final_record_update(record_updates: &RecordUpdateSender, update: (String, RecordUpdate)) {
+    if let Err(tokio::sync::mpsc::error::TrySendError::Full(update)) =
+        record_updates.try_send(update)
+    {
+        // Streams can be dropped while the runtime shuts down
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            let record_updates = record_updates.clone();
+            runtime.spawn(async move {
+                let _ = record_updates.send(update).await;
+            });
+        }
+    }
+}
+
+/// Spawn the single task that applies upload record updates to the share state
+///
+/// Updates already queued are coalesced per upload ID, so the share state lock is
+/// taken once per batch instead of once per progress tick. The task exits when
+/// every sender has been dropped.
+fn spawn_record_updater(share_state: Arc<Mutex<ShareState>>) -> RecordUpdateSender {
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<(String, RecordUpdate)>(RECORD_UPDATE_CHANNEL_SIZE);
+
+    tokio::spawn(async move {
+        while let Some(first) = rx.recv().await {
+            let mut pending: std::collections::HashMap<String, RecordUpdate> =
+                std::collections::HashMap::new();
+            let mut next = Some(first);
+            while let Some((upload_id, update)) = next {
+                // A final update is never overwritten by a stale progress tick
+                let merged = match (pending.remove(&upload_id), update) {
+                    (
+                        Some(
+                            done @ (RecordUpdate::Completed { .. }
+                            | RecordUpdate::Interrupted { .. }),
+                        ),
+                        RecordUpdate::Progress { .. },
+                    ) => done,
+                    (_, update) => update,
+                };
+                pending.insert(upload_id, merged);
+                next = rx.try_recv().ok();
+            }
+
+            let mut state = share_state.lock().await;
+            for (upload_id, update) in pending {
+                let record = state
+                    .access_requests
+                    .values_mut()
+                    .find_map(|request| {
+                        request
+                            .upload_records
+                            .iter_mut()
+                            .find(|r| r.id == upload_id)
+                    });
+                let Some(record) = record else {
+                    continue;
+                };
+                match update {
+                    RecordUpdate::Progress {
+                        uploaded_bytes,
+                        progress,
+                        speed,
+                    } => {
+                        record.uploaded_bytes = uploaded_bytes;
+                        record.progress = progress;
+                        record.speed = speed;
+                    }
+                    RecordUpdate::Completed { completed_at } => {
+                        record.uploaded_bytes = record.total_bytes;
+                        record.progress = 100.0;
+                        record.status = super::models::TransferStatus::Completed;
+                        record.completed_at = Some(completed_at);
+                    }
+                    RecordUpdate::Interrupted {
+                        status,
+                        completed_at,
+                    } => {
+                        // The record may already have completed through another path
+                        if record.status != super::models::TransferStatus::Completed {
+                            record.status = status;
+                            record.speed = 0;
+                            record.completed_at = Some(completed_at);
+                        }
+                    }
+                }
+            }
+        }
+    });
+
+    tx
+}
+
+struct ProgressTrackingStream<R = File> {
+    /// Progress is tracked as chunks leave the throttle, so the reported speed reflects
+    /// the throttled rate rather than the disk read rate
+    inner: ThrottledStream<ReaderStream<R>>,
     app_handle: AppHandle,
-    share_state: Arc<Mutex<ShareState>>,
+    record_updates: RecordUpdateSender,
     upload_id: String,
     file_name: String,
     client_ip: String,
@@ -1331,22 +3328,28 @@ struct ProgressTrackingStream {
     last_emit_time: std::time::Instant,
     last_emit_progress: f64,
     start_time: std::time::Instant,
+    /// The whole body was produced
+    finished: bool,
+    /// Reading the source failed
+    failed: bool,
+    /// Counted toward the files' download limits once the body completes
+    download_counts: Vec<DownloadCount>,
 }
 
-impl ProgressTrackingStream {
+impl<R> ProgressTrackingStream<R> {
     fn new(
-        inner: ReaderStream<File>,
+        inner: ReaderStream<R>,
         app_handle: AppHandle,
-        share_state: Arc<Mutex<ShareState>>,
+        record_updates: RecordUpdateSender,
         upload_id: String,
         file_name: String,
         client_ip: String,
         total_bytes: u64,
     ) -> Self {
         Self {
-            inner,
+            inner: ThrottledStream::new(inner),
             app_handle,
-            share_state,
+            record_updates,
             upload_id,
             file_name,
             client_ip,
@@ -1355,9 +3358,17 @@ impl ProgressTrackingStream {
             last_emit_time: std::time::Instant::now(),
             last_emit_progress: 0.0,
             start_time: std::time::Instant::now(),
+            finished: false,
+            failed: false,
+            download_counts: Vec::new(),
         }
     }
 
+    fn with_download_counts(mut self, download_counts: Vec<DownloadCount>) -> Self {
+        self.download_counts = download_counts;
+        self
+    }
+
     fn calculate_speed(&self) -> u64 {
         let elapsed = self.start_time.elapsed().as_secs_f64();
         if elapsed > 0.0 {
@@ -1388,6 +3399,33 @@ impl ProgressTrackingStream {
         self.last_emit_progress = progress;
     }
 
+    /// Account for a chunk that is about to be handed to the client
+    fn track_chunk(&mut self, len: usize) {
+        self.transferred_bytes += len as u64;
+
+        let progress = if self.total_bytes > 0 {
+            (self.transferred_bytes as f64 / self.total_bytes as f64) * 100.0
+        } else {
+            0.0
+        };
+
+        let speed = self.calculate_speed();
+
+        if self.should_emit_progress(progress) {
+            self.emit_progress(progress, speed);
+
+            // Never block the stream: a dropped tick is superseded by the next one
+            let _ = self.record_updates.try_send((
+                self.upload_id.clone(),
+                RecordUpdate::Progress {
+                    uploaded_bytes: self.transferred_bytes,
+                    progress,
+                    speed,
+                },
+            ));
+        }
+    }
+
     fn emit_complete(&self) {
         let speed = self.calculate_speed();
         let payload = super::models::UploadProgress {
@@ -1413,79 +3451,36 @@ impl ProgressTrackingStream {
     }
 }
 
-impl Stream for ProgressTrackingStream {
+impl<R: tokio::io::AsyncRead + Unpin> Stream for ProgressTrackingStream<R> {
     type Item = Result<Bytes, std::io::Error>;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        let this = unsafe { self.get_unchecked_mut() };
-        let inner = unsafe { Pin::new_unchecked(&mut this.inner) };
+        let this = self.get_mut();
 
-        match inner.poll_next(cx) {
+        match this.inner.poll_next_unpin(cx) {
             Poll::Ready(Some(Ok(chunk))) => {
-                this.transferred_bytes += chunk.len() as u64;
-
-                let progress = if this.total_bytes > 0 {
-                    (this.transferred_bytes as f64 / this.total_bytes as f64) * 100.0
-                } else {
-                    0.0
-                };
-
-                let speed = this.calculate_speed();
-
-                if this.should_emit_progress(progress) {
-                    this.emit_progress(progress, speed);
-
-                    let share_state = this.share_state.clone();
-                    let upload_id = this.upload_id.clone();
-                    let transferred = this.transferred_bytes;
-                    let prog = progress;
-                    let spd = speed;
-                    tokio::spawn(async move {
-                        let mut state = share_state.lock().await;
-                        for request in state.access_requests.values_mut() {
-                            if let Some(record) = request
-                                .upload_records
-                                .iter_mut()
-                                .find(|r| r.id == upload_id)
-                            {
-                                record.uploaded_bytes = transferred;
-                                record.progress = prog;
-                                record.speed = spd;
-                                break;
-                            }
-                        }
-                    });
-                }
-
+                this.track_chunk(chunk.len());
                 Poll::Ready(Some(Ok(chunk)))
             }
-            Poll::Ready(Some(Err(err))) => Poll::Ready(Some(Err(err))),
+            Poll::Ready(Some(Err(err))) => {
+                this.failed = true;
+                Poll::Ready(Some(Err(err)))
+            }
             Poll::Ready(None) => {
+                this.finished = true;
                 this.transferred_bytes = this.total_bytes;
                 this.emit_complete();
+                for download_count in std::mem::take(&mut this.download_counts) {
+                    download_count.record(&this.app_handle);
+                }
 
-                let share_state = this.share_state.clone();
-                let upload_id = this.upload_id.clone();
-                tokio::spawn(async move {
-                    let mut state = share_state.lock().await;
-                    let now = std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap_or_default()
-                        .as_millis() as u64;
-                    for request in state.access_requests.values_mut() {
-                        if let Some(record) = request
-                            .upload_records
-                            .iter_mut()
-                            .find(|r| r.id == upload_id)
-                        {
-                            record.uploaded_bytes = record.total_bytes;
-                            record.progress = 100.0;
-                            record.status = super::models::TransferStatus::Completed;
-                            record.completed_at = Some(now);
-                            break;
-                        }
-                    }
-                });
+                let update = (
+                    this.upload_id.clone(),
+                    RecordUpdate::Completed {
+                        completed_at: current_timestamp_millis(),
+                    },
+                );
+                send_final_record_update(&this.record_updates, update);
 
                 Poll::Ready(None)
             }
@@ -1494,6 +3489,77 @@ impl Stream for ProgressTrackingStream {
     }
 }
 
+impl<R> Drop for ProgressTrackingStream<R> {
+    /// The body is dropped before its end when the client disconnects mid-download
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        let status = if self.failed {
+            super::models::TransferStatus::Failed
+        } else {
+            super::models::TransferStatus::Cancelled
+        };
+        let _ = self.app_handle.emit(
+            "upload-cancelled",
+            UploadCancelledPayload {
+                upload_id: self.upload_id.clone(),
+                file_name: self.file_name.clone(),
+                file_size: self.total_bytes as i64,
+                transferred_bytes: self.transferred_bytes,
+                client_ip: self.client_ip.clone(),
+                status,
+            },
+        );
+        send_final_record_update(
+            &self.record_updates,
+            (
+                self.upload_id.clone(),
+                RecordUpdate::Interrupted {
+                    status,
+                    completed_at: current_timestamp_millis(),
+                },
+            ),
+        );
+    }
+}
+
+/// Reports the file bytes of each chunk as it is handed to the client
+struct ServedRangeStream<S> {
+    inner: S,
+    /// Offset of the first byte of `inner` in the file
+    start: u64,
+    sent: u64,
+    on_served: OnServed,
+}
+
+impl<S> ServedRangeStream<S> {
+    fn new(inner: S, start: u64, on_served: OnServed) -> Self {
+        Self {
+            inner,
+            start,
+            sent: 0,
+            on_served,
+        }
+    }
+}
+
+impl<S: Stream<Item = std::io::Result<Bytes>> + Unpin> Stream for ServedRangeStream<S> {
+    type Item = std::io::Result<Bytes>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let polled = this.inner.poll_next_unpin(cx);
+        if let Poll::Ready(Some(Ok(chunk))) = &polled {
+            if !chunk.is_empty() {
+                let offset = this.start + this.sent;
+                this.sent += chunk.len() as u64;
+                (this.on_served)(offset, offset + chunk.len() as u64 - 1);
+            }
+        }
+        polled
+    }
+}
 
 /// Structure to hold internationalized labels for the file list page
 #[derive(Debug, Clone)]
@@ -1516,11 +3582,14 @@ struct FileListPageLabels {
 fn file_list_page_css() -> &'static str {
     r#"        body { font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif; max-width: 800px; margin: 0 auto; padding: 20px; }
         h1 { color: #333; }
+        .subtitle { color: #666; margin-top: -8px; }
         ul { list-style: none; padding: 0; }
         li { padding: 12px; border-bottom: 1px solid #eee; display: flex; align-items: center; justify-content: space-between; }
         a { color: #1976d2; text-decoration: none; cursor: pointer; }
         a:hover { text-decoration: underline; }
         .warning { background: #fff3cd; padding: 10px; border-radius: 4px; margin-bottom: 20px; }
+        .sas { background: #e8f5e9; padding: 10px; border-radius: 4px; margin-bottom: 20px; }
+        .sas strong { font-family: monospace; font-size: 18px; letter-spacing: 2px; }
         .empty { color: #999; text-align: center; padding: 40px 0; }
         .badges { display: flex; gap: 6px; margin-left: 10px; }
         .badge { font-size: 11px; padding: 2px 6px; border-radius: 4px; color: #fff; }
@@ -1530,7 +3599,13 @@ fn file_list_page_css() -> &'static str {
         .progress-fill { height: 100%; background: #1976d2; transition: width 0.3s; }
         .progress-text { font-size: 12px; color: #666; margin-top: 4px; }
         .file-info { flex: 1; }
-        .file-size { color: #888; font-size: 13px; margin-left: 8px; }"#
+        .file-size { color: #888; font-size: 13px; margin-left: 8px; }
+        .file-icon { margin-right: 6px; }
+        .file-type { color: #888; font-size: 12px; margin-left: 8px; }
+        .sort-bar { display: flex; align-items: center; gap: 6px; font-size: 13px; color: #666; }
+        .sort-bar button { border: 1px solid #ddd; background: #fff; border-radius: 4px; padding: 2px 8px; cursor: pointer; font-size: 13px; }
+        .sort-bar button.active { border-color: #1976d2; color: #1976d2; }
+        .sort-bar button.active::after { content: ' ' attr(data-dir); }"#
 }
 
 /// Returns the JavaScript code for the file list page with internationalized labels
@@ -1551,6 +3626,7 @@ fn file_list_page_javascript(labels: &FileListPageLabels) -> String {
         async function initEnhanced() {{
             try {{
                 var resp = await fetch('/capabilities');
+                if (!resp.ok) throw new Error('capabilities unavailable: ' + resp.status);
                 caps = await resp.json();
                 if (caps.encryption) {{
                     await performHandshake();
@@ -1604,12 +3680,35 @@ fn file_list_page_javascript(labels: &FileListPageLabels) -> String {
                     {{ name: 'AES-GCM', length: 256 }},
                     false, ['decrypt']
                 );
+                showSas(new Uint8Array(pubRaw), serverPubBytes).catch(function(e) {{
+                    console.warn('SAS unavailable:', e);
+                }});
             }} catch(e) {{
                 console.warn('Handshake failed:', e);
                 caps.encryption = false;
             }}
         }}
 
+        // Same derivation as the desktop app: HKDF-SHA256 over client key || server key.
+        // The user compares both codes to rule out a substituted key
+        async function showSas(clientPub, serverPub) {{
+            var ikm = new Uint8Array(clientPub.length + serverPub.length);
+            ikm.set(clientPub);
+            ikm.set(serverPub, clientPub.length);
+            var ikmKey = await crypto.subtle.importKey('raw', ikm, 'HKDF', false, ['deriveBits']);
+            var bits = await crypto.subtle.deriveBits(
+                {{
+                    name: 'HKDF', hash: 'SHA-256',
+                    salt: new Uint8Array(0),
+                    info: new TextEncoder().encode('puresend-sas')
+                }},
+                ikmKey, 32
+            );
+            var code = new DataView(bits).getUint32(0) % 1000000;
+            document.getElementById('sas-code').textContent = String(code).padStart(6, '0');
+            document.getElementById('sas').style.display = '';
+        }}
+
         async function decryptChunk(data) {{
             var nonce = data.slice(0, 12);
             var ciphertext = data.slice(12);
@@ -1662,6 +3761,207 @@ fn file_list_page_javascript(labels: &FileListPageLabels) -> String {
             }}
         }}
 
+        // Plaintext files at least this large are fetched as parallel ranges
+        var PARALLEL_MIN_SIZE = 8 * 1024 * 1024;
+        var RANGE_SIZE = 4 * 1024 * 1024;
+        var RANGE_WORKERS = 4;
+        var RANGE_MAX_ATTEMPTS = 5;
+
+        function saveBlob(parts, fileName) {{
+            var blob = new Blob(parts);
+            var url = URL.createObjectURL(blob);
+            var a = document.createElement('a');
+            a.href = url;
+            a.download = fileName;
+            document.body.appendChild(a);
+            a.click();
+            document.body.removeChild(a);
+            URL.revokeObjectURL(url);
+        }}
+
+        function sleep(ms) {{
+            return new Promise(function(resolve) {{ setTimeout(resolve, ms); }});
+        }}
+
+        function idbRequest(req) {{
+            return new Promise(function(resolve, reject) {{
+                req.onsuccess = function() {{ resolve(req.result); }};
+                req.onerror = function() {{ reject(req.error); }};
+            }});
+        }}
+
+        // Downloaded ranges live in IndexedDB (sessionStorage is far too small for file
+        // data); sessionStorage only records which ranges are done. Resolves to null when
+        // IndexedDB is unavailable, in which case ranges are kept in memory only.
+        async function openRangeStore() {{
+            try {{
+                if (!window.indexedDB) return null;
+                var req = indexedDB.open('puresend-downloads', 1);
+                req.onupgradeneeded = function() {{ req.result.createObjectStore('ranges'); }};
+                return await idbRequest(req);
+            }} catch(e) {{
+                console.warn('Range store unavailable:', e);
+                return null;
+            }}
+        }}
+
+        function rangeStore(db, mode) {{
+            return db.transaction('ranges', mode).objectStore('ranges');
+        }}
+
+        function loadRange(db, fileId, index) {{
+            var req = rangeStore(db, 'readonly').get(fileId + ':' + index);
+            return idbRequest(req).catch(function() {{ return null; }});
+        }}
+
+        function storeRange(db, fileId, index, data) {{
+            var req = rangeStore(db, 'readwrite').put(data, fileId + ':' + index);
+            return idbRequest(req).catch(function() {{}});
+        }}
+
+        function clearStoredRanges(db, fileId) {{
+            if (!db) return Promise.resolve();
+            var keys = IDBKeyRange.bound(fileId + ':', fileId + ':\uffff');
+            return idbRequest(rangeStore(db, 'readwrite').delete(keys)).catch(function() {{}});
+        }}
+
+        async function fetchRange(fileId, start, end) {{
+            for (var attempt = 1; ; attempt++) {{
+                try {{
+                    var resp = await fetch('/download/' + fileId, {{
+                        headers: {{ 'Range': 'bytes=' + start + '-' + end }}
+                    }});
+                    if (resp.status === 503 && attempt < RANGE_MAX_ATTEMPTS) {{
+                        var retryAfter = parseInt(resp.headers.get('Retry-After') || '1');
+                        await sleep(retryAfter * 1000);
+                        continue;
+                    }}
+                    if (resp.status !== 206) {{
+                        var error = new Error('HTTP ' + resp.status);
+                        error.rangeUnsupported = resp.ok;
+                        throw error;
+                    }}
+                    var data = new Uint8Array(await resp.arrayBuffer());
+                    if (data.length !== end - start + 1) throw new Error('Incomplete range');
+                    return {{ data: data, etag: resp.headers.get('ETag') }};
+                }} catch(e) {{
+                    if (e.rangeUnsupported || attempt >= RANGE_MAX_ATTEMPTS) throw e;
+                    await sleep(attempt * 1000);
+                }}
+            }}
+        }}
+
+        // Fetch a plaintext file as parallel Range requests, reassembled by offset.
+        // Finished ranges survive a reload of the page within the same tab session.
+        async function downloadParallel(fileId, fileName, fileSize) {{
+            if (fileSize < PARALLEL_MIN_SIZE) {{
+                await downloadDirect(fileId, fileName, fileSize);
+                return;
+            }}
+
+            var li = document.getElementById('dl-' + fileId);
+            var progressBar = li.querySelector('.progress-fill');
+            var progressText = li.querySelector('.progress-text');
+            if (progressBar) progressBar.style.width = '0%';
+            if (progressText) progressText.textContent = '{}';
+
+            var stateKey = 'puresend-dl-' + fileId;
+            var db = await openRangeStore();
+            var count = Math.ceil(fileSize / RANGE_SIZE);
+            var parts = new Array(count);
+            var saved = null;
+            try {{ saved = JSON.parse(sessionStorage.getItem(stateKey)); }} catch(e) {{}}
+            if (!db || !saved || saved.size !== fileSize || saved.rangeSize !== RANGE_SIZE) {{
+                saved = {{ size: fileSize, rangeSize: RANGE_SIZE, etag: null, done: [] }};
+                await clearStoredRanges(db, fileId);
+            }}
+
+            var received = 0;
+            var done = [];
+            for (var i = 0; i < saved.done.length; i++) {{
+                var stored = await loadRange(db, fileId, saved.done[i]);
+                if (stored) {{
+                    parts[saved.done[i]] = stored;
+                    done.push(saved.done[i]);
+                    received += stored.length;
+                }}
+            }}
+            saved.done = done;
+
+            function showProgress() {{
+                var pct = Math.min(100, Math.round(received / fileSize * 100));
+                if (progressBar) progressBar.style.width = pct + '%';
+                if (progressText) progressText.textContent = pct + '% (' + formatSize(received) + ' / ' + formatSize(fileSize) + ')';
+            }}
+            showProgress();
+
+            var pending = [];
+            for (var index = 0; index < count; index++) {{
+                if (!parts[index]) pending.push(index);
+            }}
+
+            async function worker() {{
+                while (pending.length > 0) {{
+                    var index = pending.shift();
+                    var start = index * RANGE_SIZE;
+                    var end = Math.min(fileSize, start + RANGE_SIZE) - 1;
+                    var result = await fetchRange(fileId, start, end);
+                    if (saved.etag && result.etag && result.etag !== saved.etag) {{
+                        throw new Error('File changed on the server');
+                    }}
+                    saved.etag = saved.etag || result.etag;
+                    parts[index] = result.data;
+                    received += result.data.length;
+                    if (db) {{
+                        await storeRange(db, fileId, index, result.data);
+                        saved.done.push(index);
+                        try {{ sessionStorage.setItem(stateKey, JSON.stringify(saved)); }} catch(e) {{}}
+                    }}
+                    showProgress();
+                }}
+            }}
+
+            try {{
+                var workers = [];
+                for (var w = 0; w < Math.min(RANGE_WORKERS, pending.length); w++) {{
+                    workers.push(worker());
+                }}
+                await Promise.all(workers);
+
+                saveBlob(parts, fileName);
+                sessionStorage.removeItem(stateKey);
+                await clearStoredRanges(db, fileId);
+
+                if (progressBar) {{ progressBar.style.width = '100%'; progressBar.style.background = '#4caf50'; }}
+                if (progressText) progressText.textContent = '{}';
+            }} catch(e) {{
+                pending.length = 0;
+                if (e.rangeUnsupported) {{
+                    await downloadDirect(fileId, fileName, fileSize);
+                    return;
+                }}
+                if (e.message === 'File changed on the server') {{
+                    sessionStorage.removeItem(stateKey);
+                    await clearStoredRanges(db, fileId);
+                }}
+                console.error('Download failed:', e);
+                if (progressText) {{ progressText.textContent = '{}: ' + e.message; progressText.style.color = '#d32f2f'; }}
+            }}
+        }}
+
+        // Browsers can only decode gzip natively
+        function acceptCompression() {{
+            return typeof DecompressionStream === 'function' ? 'gzip' : '';
+        }}
+
+        async function decompressChunk(data, algorithm) {{
+            if (algorithm !== 'gzip') throw new Error('Unsupported compression: ' + algorithm);
+            var stream = new Blob([data]).stream().pipeThrough(new DecompressionStream('gzip'));
+            return new Uint8Array(await new Response(stream).arrayBuffer());
+        }}
+
+        // Decoded chunks are kept in the range store under their own key prefix, so a
+        // reload of the page continues after the chunks already stored.
         async function downloadEnhanced(fileId, fileName, fileSize) {{
             var li = document.getElementById('dl-' + fileId);
             var progressBar = li.querySelector('.progress-fill');
@@ -1669,47 +3969,85 @@ fn file_list_page_javascript(labels: &FileListPageLabels) -> String {
             if (progressBar) progressBar.style.width = '0%';
             if (progressText) progressText.textContent = '{}';
 
+            var stateKey = 'puresend-dl-chunks-' + fileId;
+            var storeId = 'chunk-' + fileId;
+            var db = await openRangeStore();
+            var saved = null;
+            try {{ saved = JSON.parse(sessionStorage.getItem(stateKey)); }} catch(e) {{}}
+            if (!db || !saved || saved.size !== fileSize) {{
+                saved = {{ size: fileSize, chunkSize: 0, done: [] }};
+                await clearStoredRanges(db, storeId);
+            }}
+
             try {{
-                var metaResp = await fetch('/download/' + fileId + '/meta');
+                var chunks = [];
+                for (var d = 0; d < saved.done.length; d++) {{
+                    var stored = await loadRange(db, storeId, saved.done[d]);
+                    if (stored) chunks[saved.done[d]] = stored;
+                }}
+                var held = 0;
+                while (chunks[held]) held++;
+
+                var metaHeaders = {{ 'X-Accept-Compression': acceptCompression() }};
+                var metaUrl = '/download/' + fileId + '/meta';
+                if (held > 0) metaUrl += '?resume_from=' + held;
+                var metaResp = await fetch(metaUrl, {{ headers: metaHeaders }});
+                if (!metaResp.ok) throw new Error('HTTP ' + metaResp.status);
                 var meta = await metaResp.json();
 
                 if (!meta.encryption && !meta.compression) {{
-                    await downloadDirect(fileId, fileName, fileSize);
+                    await downloadParallel(fileId, fileName, fileSize);
                     return;
                 }}
 
-                var chunks = [];
+                if (saved.chunkSize !== meta.chunk_size) {{
+                    held = 0;
+                    saved.chunkSize = meta.chunk_size;
+                }}
+                // The last chunk is always fetched so the server sees the download finish
+                var resumeFrom = Math.max(0, Math.min(held, meta.chunk_count - 1));
+                chunks.length = resumeFrom;
+                saved.done = saved.done.filter(function(index) {{ return index < resumeFrom; }});
                 var downloaded = 0;
+                for (var r = 0; r < resumeFrom; r++) downloaded += chunks[r].length;
+
+                function showProgress() {{
+                    var pct = Math.min(100, Math.round(downloaded / meta.file_size * 100));
+                    if (progressBar) progressBar.style.width = pct + '%';
+                    if (progressText) progressText.textContent = pct + '% (' + formatSize(downloaded) + ' / ' + formatSize(meta.file_size) + ')';
+                }}
+                showProgress();
 
-                for (var i = 0; i < meta.chunk_count; i++) {{
-                    var headers = {{}};
+                for (var i = resumeFrom; i < meta.chunk_count; i++) {{
+                    var headers = {{ 'X-Accept-Compression': acceptCompression() }};
                     if (sessionId) headers['X-Encryption-Session'] = sessionId;
 
                     var resp = await fetch('/download/' + fileId + '/chunk/' + i, {{ headers: headers }});
+                    if (!resp.ok) throw new Error('HTTP ' + resp.status);
                     var data = new Uint8Array(await resp.arrayBuffer());
 
                     var isEncrypted = resp.headers.get('x-encryption') === 'aes-256-gcm';
                     if (isEncrypted && cryptoKey) {{
                         data = await decryptChunk(data);
                     }}
+                    var compression = resp.headers.get('x-compression');
+                    if (compression) {{
+                        data = await decompressChunk(data, compression);
+                    }}
 
-                    chunks.push(data);
+                    chunks[i] = data;
                     downloaded += data.length;
-
-                    var pct = Math.min(100, Math.round(downloaded / meta.file_size * 100));
-                    if (progressBar) progressBar.style.width = pct + '%';
-                    if (progressText) progressText.textContent = pct + '% (' + formatSize(downloaded) + ' / ' + formatSize(meta.file_size) + ')';
+                    if (db) {{
+                        await storeRange(db, storeId, i, data);
+                        saved.done.push(i);
+                        try {{ sessionStorage.setItem(stateKey, JSON.stringify(saved)); }} catch(e) {{}}
+                    }}
+                    showProgress();
                 }}
 
-                var blob = new Blob(chunks);
-                var url = URL.createObjectURL(blob);
-                var a = document.createElement('a');
-                a.href = url;
-                a.download = fileName;
-                document.body.appendChild(a);
-                a.click();
-                document.body.removeChild(a);
-                URL.revokeObjectURL(url);
+                saveBlob(chunks, fileName);
+                sessionStorage.removeItem(stateKey);
+                await clearStoredRanges(db, storeId);
 
                 if (progressBar) {{ progressBar.style.width = '100%'; progressBar.style.background = '#4caf50'; }}
                 if (progressText) progressText.textContent = '{}';
@@ -1726,10 +4064,87 @@ fn file_list_page_javascript(labels: &FileListPageLabels) -> String {
             if (caps && (caps.encryption || caps.compression)) {{
                 downloadEnhanced(fileId, fileName, fileSize);
             }} else {{
-                downloadDirect(fileId, fileName, fileSize);
+                downloadParallel(fileId, fileName, fileSize);
+            }}
+        }}
+
+        var currentFiles = [];
+        var sortKey = 'name';
+        var sortAsc = true;
+
+        function escapeHtml(text) {{
+            return String(text).replace(/[&<>"']/g, function(c) {{
+                return {{ '&': '&amp;', '<': '&lt;', '>': '&gt;', '"': '&quot;', "'": '&#39;' }}[c];
+            }});
+        }}
+
+        function fileTypeIcon(mime) {{
+            mime = mime || '';
+            if (mime.indexOf('image/') === 0) return '🖼️';
+            if (mime.indexOf('video/') === 0) return '🎬';
+            if (mime.indexOf('audio/') === 0) return '🎵';
+            if (mime === 'application/pdf') return '📕';
+            if (/zip|tar|rar|7z|gzip|compressed/.test(mime)) return '🗜️';
+            if (mime.indexOf('text/') === 0 || /json|xml|javascript/.test(mime)) return '📝';
+            return '📄';
+        }}
+
+        function compareFiles(a, b) {{
+            var result = 0;
+            if (sortKey === 'size') result = a.size - b.size;
+            else if (sortKey === 'type') result = (a.mime_type || '').localeCompare(b.mime_type || '');
+            if (result === 0) result = a.name.localeCompare(b.name, undefined, {{ numeric: true, sensitivity: 'base' }});
+            return sortAsc ? result : -result;
+        }}
+
+        function updateSortBar() {{
+            var bar = document.getElementById('sort-bar');
+            bar.style.display = currentFiles.length > 1 ? '' : 'none';
+            bar.querySelectorAll('button').forEach(function(btn) {{
+                var active = btn.getAttribute('data-sort') === sortKey;
+                btn.classList.toggle('active', active);
+                btn.setAttribute('data-dir', active ? (sortAsc ? '▲' : '▼') : '');
+            }});
+        }}
+
+        function renderFiles() {{
+            var ul = document.getElementById('file-list');
+            updateSortBar();
+            if (currentFiles.length === 0) {{
+                ul.innerHTML = '<li class="empty">{}</li>';
+                return;
             }}
+            ul.innerHTML = currentFiles.slice().sort(compareFiles).map(function(f) {{
+                var badges = '';
+                if (caps && caps.encryption) badges += '<span class="badge badge-enc">{}</span>';
+                if (caps && caps.compression) badges += '<span class="badge badge-comp">{}</span>';
+                return '<li id="dl-' + f.id + '">'
+                    + '<div class="file-info">'
+                    + '<span class="file-icon">' + fileTypeIcon(f.mime_type) + '</span>'
+                    + '<a onclick="downloadFile(\'' + f.id + '\',\'' + f.name.replace(/'/g, "\\'") + '\',' + f.size + ')">' + f.name + '</a>'
+                    + '<span class="file-size">(' + formatSize(f.size) + ')</span>'
+                    + '<span class="file-type">' + escapeHtml(f.mime_type || '') + '</span>'
+                    + (badges ? '<div class="badges">' + badges + '</div>' : '')
+                    + '<div class="progress-bar"><div class="progress-fill" style="width:0%"></div></div>'
+                    + '<div class="progress-text"></div>'
+                    + '</div>'
+                    + '</li>';
+            }}).join('');
         }}
 
+        document.querySelectorAll('#sort-bar button').forEach(function(btn) {{
+            btn.addEventListener('click', function() {{
+                var key = btn.getAttribute('data-sort');
+                if (key === sortKey) {{
+                    sortAsc = !sortAsc;
+                }} else {{
+                    sortKey = key;
+                    sortAsc = true;
+                }}
+                renderFiles();
+            }});
+        }});
+
         var lastJson = '';
         function refreshFiles() {{
             fetch('/files')
@@ -1738,25 +4153,8 @@ fn file_list_page_javascript(labels: &FileListPageLabels) -> String {
                     var json = JSON.stringify(data.files);
                     if (json === lastJson) return;
                     lastJson = json;
-                    var ul = document.getElementById('file-list');
-                    if (!data.files || data.files.length === 0) {{
-                        ul.innerHTML = '<li class="empty">{}</li>';
-                        return;
-                    }}
-                    ul.innerHTML = data.files.map(function(f) {{
-                        var badges = '';
-                        if (caps && caps.encryption) badges += '<span class="badge badge-enc">{}</span>';
-                        if (caps && caps.compression) badges += '<span class="badge badge-comp">{}</span>';
-                        return '<li id="dl-' + f.id + '">'
-                            + '<div class="file-info">'
-                            + '<a onclick="downloadFile(\'' + f.id + '\',\'' + f.name.replace(/'/g, "\\'") + '\',' + f.size + ')">' + f.name + '</a>'
-                            + '<span class="file-size">(' + formatSize(f.size) + ')</span>'
-                            + (badges ? '<div class="badges">' + badges + '</div>' : '')
-                            + '<div class="progress-bar"><div class="progress-fill" style="width:0%"></div></div>'
-                            + '<div class="progress-text"></div>'
-                            + '</div>'
-                            + '</li>';
-                    }}).join('');
+                    currentFiles = data.files || [];
+                    renderFiles();
                 }})
                 .catch(function() {{}});
         }}
@@ -1772,6 +4170,9 @@ fn file_list_page_javascript(labels: &FileListPageLabels) -> String {
         labels.downloading,
         labels.download_complete,
         labels.download_failed,
+        labels.downloading,
+        labels.download_complete,
+        labels.download_failed,
         labels.no_files,
         labels.encrypted_label,
         labels.compressed_label
@@ -2044,14 +4445,36 @@ fn generate_waiting_response_html(is_english: bool) -> String {
 
 /// Enhanced file list page with encryption, compression, and resume support
 fn generate_file_list_html(is_english: bool) -> String {
-    let title = if is_english { "PureSend - File Sharing" } else { "PureSend - 文件分享" };
-    let heading = if is_english { "PureSend File Sharing" } else { "PureSend 文件分享" };
+    let branding = http_common::page_branding();
+    let title = branding.title_html(if is_english {
+        "PureSend - File Sharing"
+    } else {
+        "PureSend - 文件分享"
+    });
+    let heading = branding.title_html(if is_english {
+        "PureSend File Sharing"
+    } else {
+        "PureSend 文件分享"
+    });
+    let subtitle = branding
+        .subtitle_html()
+        .map(|subtitle| format!("\n    <p class=\"subtitle\">{}</p>", subtitle))
+        .unwrap_or_default();
     let warning = if is_english {
         "⚠️ This link is for trusted networks only. Do not share on public platforms."
     } else {
         "⚠️ 此链接仅限可信网络内使用，请勿分享到公共平台"
     };
+    let verification = if is_english {
+        "Verification code (compare with the sharer)"
+    } else {
+        "验证码（请与分享者核对）"
+    };
     let files_heading = if is_english { "Available Files" } else { "可用文件" };
+    let sort_by = if is_english { "Sort by" } else { "排序" };
+    let sort_name = if is_english { "Name" } else { "名称" };
+    let sort_size = if is_english { "Size" } else { "大小" };
+    let sort_type = if is_english { "Type" } else { "类型" };
     let loading = if is_english { "Loading..." } else { "加载中..." };
     let lang = if is_english { "en" } else { "zh-CN" };
 
@@ -2064,7 +4487,12 @@ fn generate_file_list_html(is_english: bool) -> String {
         no_files: if is_english { "No files available".to_string() } else { "暂无可用文件".to_string() },
     };
 
-    let css = file_list_page_css().to_string();
+    let mut css = file_list_page_css().to_string();
+    if let Some(accent) = branding.accent_color() {
+        css.push_str(&format!(
+            "\n        a {{ color: {accent}; }}\n        .progress-fill {{ background: {accent}; }}"
+        ));
+    }
     let javascript = file_list_page_javascript(&labels);
 
     format!(
@@ -2080,9 +4508,15 @@ fn generate_file_list_html(is_english: bool) -> String {
     </style>
 </head>
 <body>
-    <h1>{heading}</h1>
+    <h1>{heading}</h1>{subtitle}
     <div class="warning">{warning}</div>
+    <div class="sas" id="sas" style="display: none">{verification}: <strong id="sas-code"></strong></div>
     <h2>{files_heading}</h2>
+    <div class="sort-bar" id="sort-bar" style="display: none">{sort_by}
+        <button type="button" data-sort="name">{sort_name}</button>
+        <button type="button" data-sort="size">{sort_size}</button>
+        <button type="button" data-sort="type">{sort_type}</button>
+    </div>
     <ul id="file-list">
         <li class="empty">{loading}</li>
     </ul>
@@ -2093,3 +4527,146 @@ fn generate_file_list_html(is_english: bool) -> String {
 </html>"##
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        use RangeRequest::*;
+        assert_eq!(
+            parse_range("bytes=0-99", 1000),
+            Some(Satisfiable(vec![(0, 99)]))
+        );
+        // Open-ended and end clamped to the last byte
+        assert_eq!(
+            parse_range("bytes=500-", 1000),
+            Some(Satisfiable(vec![(500, 999)]))
+        );
+        assert_eq!(
+            parse_range("bytes=900-5000", 1000),
+            Some(Satisfiable(vec![(900, 999)]))
+        );
+        // Suffix ranges, longer than the file means the whole file
+        assert_eq!(
+            parse_range("bytes=-100", 1000),
+            Some(Satisfiable(vec![(900, 999)]))
+        );
+        assert_eq!(
+            parse_range("bytes=-5000", 1000),
+            Some(Satisfiable(vec![(0, 999)]))
+        );
+
+        // Start past the end, empty suffix or empty file
+        assert_eq!(parse_range("bytes=1000-", 1000), Some(Unsatisfiable));
+        assert_eq!(parse_range("bytes=2000-3000", 1000), Some(Unsatisfiable));
+        assert_eq!(parse_range("bytes=-0", 1000), Some(Unsatisfiable));
+        assert_eq!(parse_range("bytes=0-", 0), Some(Unsatisfiable));
+
+        // Malformed headers are ignored
+        assert_eq!(parse_range("bytes=99-0", 1000), None);
+        assert_eq!(parse_range("bytes=abc", 1000), None);
+        assert_eq!(parse_range("items=0-1", 1000), None);
+    }
+
+    #[test]
+    fn test_parse_multiple_ranges() {
+        use RangeRequest::*;
+        assert_eq!(
+            parse_range("bytes=0-99, 200-299,-10", 1000),
+            Some(Satisfiable(vec![(0, 99), (200, 299), (990, 999)]))
+        );
+        // One unsatisfiable range rejects the request, one malformed range ignores it
+        assert_eq!(parse_range("bytes=0-99,2000-", 1000), Some(Unsatisfiable));
+        assert_eq!(parse_range("bytes=0-99,x", 1000), None);
+        assert_eq!(parse_range("bytes=0-99,", 1000), None);
+
+        let at_cap = vec!["0-0"; MAX_RANGES].join(",");
+        assert!(parse_range(&format!("bytes={}", at_cap), 1000).is_some());
+        let over_cap = vec!["0-0"; MAX_RANGES + 1].join(",");
+        assert_eq!(parse_range(&format!("bytes={}", over_cap), 1000), None);
+    }
+
+    #[test]
+    fn test_overlapping_ranges_are_coalesced() {
+        use RangeRequest::*;
+        // Repeating the whole file cannot multiply the response size
+        let repeated = vec!["0-"; MAX_RANGES].join(",");
+        assert_eq!(
+            parse_range(&format!("bytes={}", repeated), 1000),
+            Some(Satisfiable(vec![(0, 999)]))
+        );
+        assert_eq!(
+            parse_range("bytes=500-599,0-99,50-149,150-199", 1000),
+            Some(Satisfiable(vec![(0, 199), (500, 599)]))
+        );
+        assert_eq!(
+            parse_range("bytes=-100,0-", 1000),
+            Some(Satisfiable(vec![(0, 999)]))
+        );
+        // Disjoint ranges are served as requested
+        assert_eq!(
+            parse_range("bytes=200-299,0-99", 1000),
+            Some(Satisfiable(vec![(200, 299), (0, 99)]))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_multipart_range_response() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.txt");
+        std::fs::write(&path, b"0123456789abcdef").unwrap();
+
+        let response = build_multipart_range_response(
+            &path,
+            "data.txt",
+            16,
+            &[(0, 1), (10, 15)],
+            "text/plain",
+            "\"etag\"",
+            Arc::new(|_, _| {}),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        let content_type = response.headers()[header::CONTENT_TYPE].to_str().unwrap();
+        let boundary = content_type
+            .strip_prefix("multipart/byteranges; boundary=")
+            .unwrap()
+            .to_string();
+        let content_length: usize = response.headers()[header::CONTENT_LENGTH]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let expected = format!(
+            "\r\n--{b}\r\nContent-Type: text/plain\r\nContent-Range: bytes 0-1/16\r\n\r\n01\
+             \r\n--{b}\r\nContent-Type: text/plain\r\nContent-Range: bytes 10-15/16\r\n\r\nabcdef\
+             \r\n--{b}--\r\n",
+            b = boundary
+        );
+        assert_eq!(body, expected.as_bytes());
+        assert_eq!(content_length, body.len());
+    }
+
+    #[test]
+    fn test_requested_range_responses() {
+        assert_eq!(requested_range(None, 1000).ok(), Some(None));
+        assert_eq!(requested_range(Some("bytes=x-"), 1000).ok(), Some(None));
+        assert_eq!(
+            requested_range(Some("bytes=10-19"), 1000).ok(),
+            Some(Some(vec![(10, 19)]))
+        );
+
+        let response = requested_range(Some("bytes=1000-"), 1000).unwrap_err();
+        assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(
+            response.headers().get(header::CONTENT_RANGE).unwrap(),
+            "bytes */1000"
+        );
+    }
+}
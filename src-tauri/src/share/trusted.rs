@@ -0,0 +1,106 @@
+//! 受信任 IP 列表
+//!
+//! 以"接受并永久记住"方式接受的访问者 IP 会持久化保存，
+//! 之后任意分享中来自这些 IP 的访问请求都会被自动接受
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+
+/// 持久化文件名
+const TRUSTED_IPS_FILENAME: &str = "trusted_ips.json";
+
+/// 受信任的 IP
+static TRUSTED_IPS: OnceLock<RwLock<HashSet<String>>> = OnceLock::new();
+
+fn trusted_ips_path() -> PathBuf {
+    crate::app_data_dir().join(TRUSTED_IPS_FILENAME)
+}
+
+fn get_trusted_ips_lock() -> &'static RwLock<HashSet<String>> {
+    TRUSTED_IPS.get_or_init(|| RwLock::new(load(&trusted_ips_path())))
+}
+
+fn load(path: &Path) -> HashSet<String> {
+    std::fs::read(path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<Vec<String>>(&bytes).ok())
+        .map(|ips| {
+            ips.iter()
+                .map(|ip| crate::http_common::normalize_client_ip_str(ip))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn save(path: &Path, ips: &HashSet<String>) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("无法创建目录: {}", e))?;
+    }
+    let mut sorted: Vec<&String> = ips.iter().collect();
+    sorted.sort();
+    let json = serde_json::to_vec_pretty(&sorted).map_err(|e| e.to_string())?;
+    std::fs::write(path, json).map_err(|e| format!("保存受信任 IP 失败: {}", e))
+}
+
+/// IP 是否在持久化的受信任列表中
+pub fn is_persistently_trusted(ip: &str) -> bool {
+    get_trusted_ips_lock()
+        .read()
+        .map(|ips| ips.contains(ip))
+        .unwrap_or(false)
+}
+
+/// 添加受信任 IP 并保存
+pub fn add_trusted_ip(ip: &str) -> Result<(), String> {
+    let mut ips = get_trusted_ips_lock().write().map_err(|e| e.to_string())?;
+    if ips.insert(crate::http_common::normalize_client_ip_str(ip)) {
+        save(&trusted_ips_path(), &ips)?;
+    }
+    Ok(())
+}
+
+/// 移除受信任 IP 并保存，返回是否存在
+pub fn remove_trusted_ip(ip: &str) -> Result<bool, String> {
+    let mut ips = get_trusted_ips_lock().write().map_err(|e| e.to_string())?;
+    let removed = ips.remove(&crate::http_common::normalize_client_ip_str(ip));
+    if removed {
+        save(&trusted_ips_path(), &ips)?;
+    }
+    Ok(removed)
+}
+
+/// 获取持久化的受信任 IP 列表（已排序）
+pub fn trusted_ips() -> Vec<String> {
+    let mut ips: Vec<String> = get_trusted_ips_lock()
+        .read()
+        .map(|ips| ips.iter().cloned().collect())
+        .unwrap_or_default();
+    ips.sort();
+    ips
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(TRUSTED_IPS_FILENAME);
+        assert!(load(&path).is_empty());
+
+        let ips: HashSet<String> = ["192.168.1.20", "10.0.0.5"]
+            .iter()
+            .map(|ip| ip.to_string())
+            .collect();
+        save(&path, &ips).unwrap();
+        assert_eq!(load(&path), ips);
+
+        // IPv4-mapped IPv6 地址加载时统一为 IPv4 形式
+        std::fs::write(&path, r#"["::ffff:10.0.0.5", "fe80::1"]"#).unwrap();
+        let loaded = load(&path);
+        assert!(loaded.contains("10.0.0.5"));
+        assert!(loaded.contains("fe80::1"));
+    }
+}
@@ -0,0 +1,569 @@
+//! Stored (uncompressed) ZIP archive with a precomputed layout
+//!
+//! Entries use the "stored" method plus a trailing data descriptor, so the size and
+//! offset of every header, file body and trailer are known before any file is read.
+//! `/download-all` can therefore send an exact `Content-Length` and serve `Range`
+//! requests by mapping the requested bytes back onto archive parts. CRC-32 values are
+//! the only data-dependent fields; they are computed while streaming and cached.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::Mutex;
+
+const LOCAL_HEADER_SIG: u32 = 0x0403_4b50;
+const DATA_DESCRIPTOR_SIG: u32 = 0x0807_4b50;
+const CENTRAL_HEADER_SIG: u32 = 0x0201_4b50;
+const ZIP64_EOCD_SIG: u32 = 0x0606_4b50;
+const ZIP64_LOCATOR_SIG: u32 = 0x0706_4b50;
+const EOCD_SIG: u32 = 0x0605_4b50;
+
+/// General purpose flags: sizes/CRC in data descriptor (bit 3), UTF-8 names (bit 11)
+const ENTRY_FLAGS: u16 = 0x0008 | 0x0800;
+const ZIP64_EXTRA_ID: u16 = 0x0001;
+
+const LOCAL_HEADER_LEN: u64 = 30;
+const CENTRAL_HEADER_LEN: u64 = 46;
+const ZIP64_EOCD_LEN: u64 = 56;
+const ZIP64_LOCATOR_LEN: u64 = 20;
+const EOCD_LEN: u64 = 22;
+
+const COPY_BUFFER_SIZE: usize = 64 * 1024;
+
+/// CRC-32 of a file, valid while its size and mtime are unchanged
+#[derive(Debug, Clone, Copy)]
+pub struct CachedCrc {
+    size: u64,
+    modified: Option<SystemTime>,
+    crc: u32,
+}
+
+pub type CrcCache = Arc<Mutex<HashMap<PathBuf, CachedCrc>>>;
+
+#[derive(Debug, Clone)]
+struct ZipEntry {
+    name: String,
+    path: PathBuf,
+    size: u64,
+    modified: Option<SystemTime>,
+    dos_time: u16,
+    dos_date: u16,
+    local_offset: u64,
+}
+
+/// Byte layout of the whole archive
+#[derive(Debug, Clone)]
+pub struct ZipLayout {
+    entries: Vec<ZipEntry>,
+    zip64: bool,
+    central_offset: u64,
+    central_size: u64,
+    total_size: u64,
+}
+
+/// One contiguous region of the archive
+enum Part {
+    LocalHeader(usize),
+    FileData(usize),
+    Descriptor(usize),
+    CentralDirectory,
+    Trailer,
+}
+
+impl ZipLayout {
+    /// Plan an archive for `files` (archive name, path on disk)
+    ///
+    /// Duplicate names get a ` (n)` suffix. Order is kept as given, so callers must
+    /// pass files in a stable order for byte ranges to stay valid across requests.
+    pub fn new(files: Vec<(String, PathBuf)>) -> std::io::Result<Self> {
+        let mut used_names = std::collections::HashSet::new();
+        let mut entries = Vec::with_capacity(files.len());
+
+        for (name, path) in files {
+            let meta = std::fs::metadata(&path)?;
+            let modified = meta.modified().ok();
+            let (dos_time, dos_date) = dos_timestamp(modified);
+            entries.push(ZipEntry {
+                name: unique_entry_name(&name, &mut used_names),
+                path,
+                size: meta.len(),
+                modified,
+                dos_time,
+                dos_date,
+                local_offset: 0,
+            });
+        }
+
+        let plan = |zip64: bool, entries: &mut [ZipEntry]| -> (u64, u64, u64) {
+            let mut offset = 0u64;
+            for entry in entries.iter_mut() {
+                entry.local_offset = offset;
+                offset += local_header_len(entry, zip64) + entry.size + descriptor_len(zip64);
+            }
+            let central_size: u64 = entries
+                .iter()
+                .map(|e| central_header_len(e, zip64))
+                .sum();
+            let trailer = if zip64 {
+                ZIP64_EOCD_LEN + ZIP64_LOCATOR_LEN + EOCD_LEN
+            } else {
+                EOCD_LEN
+            };
+            (offset, central_size, offset + central_size + trailer)
+        };
+
+        let (mut central_offset, mut central_size, mut total_size) =
+            plan(false, &mut entries);
+        let zip64 = entries.len() >= 0xFFFF
+            || total_size >= u32::MAX as u64
+            || entries.iter().any(|e| e.size >= u32::MAX as u64);
+        if zip64 {
+            (central_offset, central_size, total_size) = plan(true, &mut entries);
+        }
+
+        Ok(Self {
+            entries,
+            zip64,
+            central_offset,
+            central_size,
+            total_size,
+        })
+    }
+
+    pub fn total_size(&self) -> u64 {
+        self.total_size
+    }
+
+    /// Validator that changes whenever any entry's name, size or mtime changes
+    pub fn etag(&self) -> String {
+        use sha2::{Digest, Sha256};
+        let mut hasher = Sha256::new();
+        for entry in &self.entries {
+            let mtime = entry
+                .modified
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_nanos())
+                .unwrap_or(0);
+            hasher.update(format!("{}\0{}\0{}\0", entry.name, entry.size, mtime).as_bytes());
+        }
+        format!("\"zip-{}-{}\"", &hex::encode(hasher.finalize())[..16], self.total_size)
+    }
+
+    fn parts(&self) -> Vec<(u64, u64, Part)> {
+        let mut parts = Vec::with_capacity(self.entries.len() * 3 + 2);
+        for (i, entry) in self.entries.iter().enumerate() {
+            let header_len = local_header_len(entry, self.zip64);
+            let data_offset = entry.local_offset + header_len;
+            parts.push((entry.local_offset, header_len, Part::LocalHeader(i)));
+            parts.push((data_offset, entry.size, Part::FileData(i)));
+            parts.push((
+                data_offset + entry.size,
+                descriptor_len(self.zip64),
+                Part::Descriptor(i),
+            ));
+        }
+        parts.push((self.central_offset, self.central_size, Part::CentralDirectory));
+        let trailer_offset = self.central_offset + self.central_size;
+        parts.push((
+            trailer_offset,
+            self.total_size - trailer_offset,
+            Part::Trailer,
+        ));
+        parts
+    }
+
+    /// Write archive bytes `start..=end` to `writer`
+    pub async fn write_range<W: AsyncWrite + Unpin>(
+        &self,
+        start: u64,
+        end: u64,
+        crc_cache: &CrcCache,
+        writer: &mut W,
+    ) -> std::io::Result<()> {
+        for (offset, len, part) in self.parts() {
+            if len == 0 || offset + len <= start || offset > end {
+                continue;
+            }
+            let from = start.saturating_sub(offset);
+            let to = (end - offset).min(len - 1);
+
+            let bytes = match part {
+                Part::FileData(i) => {
+                    self.write_file_data(i, from, to, len, crc_cache, writer)
+                        .await?;
+                    continue;
+                }
+                Part::LocalHeader(i) => self.local_header(&self.entries[i]),
+                Part::Descriptor(i) => {
+                    let crc = crc_for(&self.entries[i], crc_cache).await?;
+                    self.descriptor(&self.entries[i], crc)
+                }
+                Part::CentralDirectory => {
+                    let mut bytes = Vec::with_capacity(self.central_size as usize);
+                    for entry in &self.entries {
+                        let crc = crc_for(entry, crc_cache).await?;
+                        bytes.extend_from_slice(&self.central_header(entry, crc));
+                    }
+                    bytes
+                }
+                Part::Trailer => self.trailer(),
+            };
+            writer
+                .write_all(&bytes[from as usize..=to as usize])
+                .await?;
+        }
+        writer.flush().await
+    }
+
+    async fn write_file_data<W: AsyncWrite + Unpin>(
+        &self,
+        index: usize,
+        from: u64,
+        to: u64,
+        len: u64,
+        crc_cache: &CrcCache,
+        writer: &mut W,
+    ) -> std::io::Result<()> {
+        let entry = &self.entries[index];
+        let mut file = tokio::fs::File::open(&entry.path).await?;
+        if file.metadata().await?.len() != entry.size {
+            return Err(std::io::Error::other(format!(
+                "file changed while archiving: {}",
+                entry.path.display()
+            )));
+        }
+        file.seek(std::io::SeekFrom::Start(from)).await?;
+
+        // Only a pass over the whole file yields a usable CRC
+        let mut hasher = (from == 0 && to == len - 1).then(crc32fast::Hasher::new);
+        let mut remaining = to - from + 1;
+        let mut buffer = vec![0u8; COPY_BUFFER_SIZE];
+        while remaining > 0 {
+            let want = remaining.min(COPY_BUFFER_SIZE as u64) as usize;
+            let read = file.read(&mut buffer[..want]).await?;
+            if read == 0 {
+                return Err(std::io::ErrorKind::UnexpectedEof.into());
+            }
+            if let Some(hasher) = hasher.as_mut() {
+                hasher.update(&buffer[..read]);
+            }
+            writer.write_all(&buffer[..read]).await?;
+            remaining -= read as u64;
+        }
+
+        if let Some(hasher) = hasher {
+            crc_cache.lock().await.insert(
+                entry.path.clone(),
+                CachedCrc {
+                    size: entry.size,
+                    modified: entry.modified,
+                    crc: hasher.finalize(),
+                },
+            );
+        }
+        Ok(())
+    }
+
+    fn local_header(&self, entry: &ZipEntry) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(local_header_len(entry, self.zip64) as usize);
+        put_u32(&mut buf, LOCAL_HEADER_SIG);
+        put_u16(&mut buf, version_needed(self.zip64));
+        put_u16(&mut buf, ENTRY_FLAGS);
+        put_u16(&mut buf, 0); // stored
+        put_u16(&mut buf, entry.dos_time);
+        put_u16(&mut buf, entry.dos_date);
+        put_u32(&mut buf, 0); // CRC follows in the data descriptor
+        let size_marker = if self.zip64 { u32::MAX } else { 0 };
+        put_u32(&mut buf, size_marker);
+        put_u32(&mut buf, size_marker);
+        put_u16(&mut buf, entry.name.len() as u16);
+        put_u16(&mut buf, if self.zip64 { 20 } else { 0 });
+        buf.extend_from_slice(entry.name.as_bytes());
+        if self.zip64 {
+            put_u16(&mut buf, ZIP64_EXTRA_ID);
+            put_u16(&mut buf, 16);
+            put_u64(&mut buf, 0);
+            put_u64(&mut buf, 0);
+        }
+        buf
+    }
+
+    fn descriptor(&self, entry: &ZipEntry, crc: u32) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(descriptor_len(self.zip64) as usize);
+        put_u32(&mut buf, DATA_DESCRIPTOR_SIG);
+        put_u32(&mut buf, crc);
+        if self.zip64 {
+            put_u64(&mut buf, entry.size);
+            put_u64(&mut buf, entry.size);
+        } else {
+            put_u32(&mut buf, entry.size as u32);
+            put_u32(&mut buf, entry.size as u32);
+        }
+        buf
+    }
+
+    fn central_header(&self, entry: &ZipEntry, crc: u32) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(central_header_len(entry, self.zip64) as usize);
+        put_u32(&mut buf, CENTRAL_HEADER_SIG);
+        put_u16(&mut buf, version_needed(self.zip64));
+        put_u16(&mut buf, version_needed(self.zip64));
+        put_u16(&mut buf, ENTRY_FLAGS);
+        put_u16(&mut buf, 0);
+        put_u16(&mut buf, entry.dos_time);
+        put_u16(&mut buf, entry.dos_date);
+        put_u32(&mut buf, crc);
+        if self.zip64 {
+            put_u32(&mut buf, u32::MAX);
+            put_u32(&mut buf, u32::MAX);
+        } else {
+            put_u32(&mut buf, entry.size as u32);
+            put_u32(&mut buf, entry.size as u32);
+        }
+        put_u16(&mut buf, entry.name.len() as u16);
+        put_u16(&mut buf, if self.zip64 { 28 } else { 0 });
+        put_u16(&mut buf, 0); // comment
+        put_u16(&mut buf, 0); // disk number
+        put_u16(&mut buf, 0); // internal attributes
+        put_u32(&mut buf, 0); // external attributes
+        put_u32(
+            &mut buf,
+            if self.zip64 { u32::MAX } else { entry.local_offset as u32 },
+        );
+        buf.extend_from_slice(entry.name.as_bytes());
+        if self.zip64 {
+            put_u16(&mut buf, ZIP64_EXTRA_ID);
+            put_u16(&mut buf, 24);
+            put_u64(&mut buf, entry.size);
+            put_u64(&mut buf, entry.size);
+            put_u64(&mut buf, entry.local_offset);
+        }
+        buf
+    }
+
+    fn trailer(&self) -> Vec<u8> {
+        let count = self.entries.len() as u64;
+        let mut buf = Vec::new();
+        if self.zip64 {
+            let zip64_eocd_offset = self.central_offset + self.central_size;
+            put_u32(&mut buf, ZIP64_EOCD_SIG);
+            put_u64(&mut buf, ZIP64_EOCD_LEN - 12);
+            put_u16(&mut buf, 45);
+            put_u16(&mut buf, 45);
+            put_u32(&mut buf, 0);
+            put_u32(&mut buf, 0);
+            put_u64(&mut buf, count);
+            put_u64(&mut buf, count);
+            put_u64(&mut buf, self.central_size);
+            put_u64(&mut buf, self.central_offset);
+
+            put_u32(&mut buf, ZIP64_LOCATOR_SIG);
+            put_u32(&mut buf, 0);
+            put_u64(&mut buf, zip64_eocd_offset);
+            put_u32(&mut buf, 1);
+        }
+
+        put_u32(&mut buf, EOCD_SIG);
+        put_u16(&mut buf, 0);
+        put_u16(&mut buf, 0);
+        let short_count = if self.zip64 { 0xFFFF } else { count as u16 };
+        put_u16(&mut buf, short_count);
+        put_u16(&mut buf, short_count);
+        if self.zip64 {
+            put_u32(&mut buf, u32::MAX);
+            put_u32(&mut buf, u32::MAX);
+        } else {
+            put_u32(&mut buf, self.central_size as u32);
+            put_u32(&mut buf, self.central_offset as u32);
+        }
+        put_u16(&mut buf, 0);
+        buf
+    }
+}
+
+/// CRC-32 of an entry, from cache or by reading the file once
+async fn crc_for(entry: &ZipEntry, crc_cache: &CrcCache) -> std::io::Result<u32> {
+    if let Some(cached) = crc_cache.lock().await.get(&entry.path) {
+        if cached.size == entry.size && cached.modified == entry.modified {
+            return Ok(cached.crc);
+        }
+    }
+
+    let path = entry.path.clone();
+    let crc = tokio::task::spawn_blocking(move || -> std::io::Result<u32> {
+        use std::io::Read;
+        let mut file = std::fs::File::open(&path)?;
+        let mut hasher = crc32fast::Hasher::new();
+        let mut buffer = vec![0u8; COPY_BUFFER_SIZE];
+        loop {
+            let read = file.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..read]);
+        }
+        Ok(hasher.finalize())
+    })
+    .await
+    .map_err(std::io::Error::other)??;
+
+    crc_cache.lock().await.insert(
+        entry.path.clone(),
+        CachedCrc {
+            size: entry.size,
+            modified: entry.modified,
+            crc,
+        },
+    );
+    Ok(crc)
+}
+
+fn unique_entry_name(name: &str, used: &mut std::collections::HashSet<String>) -> String {
+    let mut candidate = name.to_string();
+    let (stem, extension) = crate::transfer::parse_filename(name);
+    let mut counter = 1;
+    while !used.insert(candidate.clone()) {
+        candidate = if extension.is_empty() {
+            format!("{} ({})", stem, counter)
+        } else {
+            format!("{} ({}).{}", stem, counter, extension)
+        };
+        counter += 1;
+    }
+    candidate
+}
+
+fn local_header_len(entry: &ZipEntry, zip64: bool) -> u64 {
+    LOCAL_HEADER_LEN + entry.name.len() as u64 + if zip64 { 20 } else { 0 }
+}
+
+fn central_header_len(entry: &ZipEntry, zip64: bool) -> u64 {
+    CENTRAL_HEADER_LEN + entry.name.len() as u64 + if zip64 { 28 } else { 0 }
+}
+
+fn descriptor_len(zip64: bool) -> u64 {
+    if zip64 {
+        24
+    } else {
+        16
+    }
+}
+
+fn version_needed(zip64: bool) -> u16 {
+    if zip64 {
+        45
+    } else {
+        20
+    }
+}
+
+/// MS-DOS time and date fields (local time, clamped to the 1980 epoch)
+fn dos_timestamp(modified: Option<SystemTime>) -> (u16, u16) {
+    use chrono::{Datelike, Timelike};
+    let Some(modified) = modified else {
+        return (0, (1 << 5) | 1);
+    };
+    let local: chrono::DateTime<chrono::Local> = modified.into();
+    if local.year() < 1980 {
+        return (0, (1 << 5) | 1);
+    }
+    let time = ((local.hour() as u16) << 11)
+        | ((local.minute() as u16) << 5)
+        | (local.second() as u16 / 2);
+    let date = (((local.year() - 1980) as u16) << 9)
+        | ((local.month() as u16) << 5)
+        | local.day() as u16;
+    (time, date)
+}
+
+fn put_u16(buf: &mut Vec<u8>, value: u16) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(buf: &mut Vec<u8>, value: u32) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u64(buf: &mut Vec<u8>, value: u64) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    async fn write_all(layout: &ZipLayout, crc_cache: &CrcCache) -> Vec<u8> {
+        let mut out = Vec::new();
+        layout
+            .write_range(0, layout.total_size() - 1, crc_cache, &mut out)
+            .await
+            .unwrap();
+        out
+    }
+
+    fn layout_for(dir: &Path, files: &[(&str, &[u8])]) -> ZipLayout {
+        let entries = files
+            .iter()
+            .map(|(name, content)| {
+                let path = dir.join(name);
+                std::fs::write(&path, content).unwrap();
+                (name.to_string(), path)
+            })
+            .collect();
+        ZipLayout::new(entries).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_content_length_matches_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let layout = layout_for(dir.path(), &[("a.txt", b"hello"), ("b.bin", &[7u8; 3000])]);
+        let cache = CrcCache::default();
+
+        let archive = write_all(&layout, &cache).await;
+        assert_eq!(archive.len() as u64, layout.total_size());
+        assert_eq!(&archive[..4], &LOCAL_HEADER_SIG.to_le_bytes());
+        assert_eq!(
+            &archive[archive.len() - EOCD_LEN as usize..][..4],
+            &EOCD_SIG.to_le_bytes()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ranges_concatenate_to_full_archive() {
+        let dir = tempfile::tempdir().unwrap();
+        let layout = layout_for(dir.path(), &[("a.txt", b"hello world"), ("c.txt", b"zip")]);
+        let full = write_all(&layout, &CrcCache::default()).await;
+
+        // A fresh cache forces CRCs of partially covered entries to be computed separately
+        let cache = CrcCache::default();
+        let mut pieces = Vec::new();
+        let mut start = 0;
+        while start < layout.total_size() {
+            let end = (start + 6).min(layout.total_size() - 1);
+            layout.write_range(start, end, &cache, &mut pieces).await.unwrap();
+            start = end + 1;
+        }
+        assert_eq!(pieces, full);
+    }
+
+    #[test]
+    fn test_duplicate_names_are_suffixed() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("one");
+        let second = dir.path().join("two");
+        std::fs::create_dir_all(&first).unwrap();
+        std::fs::create_dir_all(&second).unwrap();
+        std::fs::write(first.join("a.tar.gz"), b"1").unwrap();
+        std::fs::write(second.join("a.tar.gz"), b"2").unwrap();
+
+        let layout = ZipLayout::new(vec![
+            ("a.tar.gz".to_string(), first.join("a.tar.gz")),
+            ("a.tar.gz".to_string(), second.join("a.tar.gz")),
+        ])
+        .unwrap();
+        assert_eq!(layout.entries[0].name, "a.tar.gz");
+        assert_eq!(layout.entries[1].name, "a (1).tar.gz");
+    }
+}
@@ -0,0 +1,101 @@
+//! 令牌桶限速
+//!
+//! 分享下载和局域网发送的限速共用的令牌桶实现。同一个桶由多条连接共享时，
+//! 整体吞吐不超过设定的每秒字节数，各连接自然分摊额度
+
+use std::time::{Duration, Instant};
+
+/// 令牌桶容量对应的时长，容量越小限速越平滑
+const BURST_MILLIS: u64 = 100;
+
+/// 令牌桶最小容量，避免限速很低时单个数据块就超出容量
+const MIN_BURST_BYTES: f64 = 16.0 * 1024.0;
+
+/// 令牌桶
+#[derive(Debug)]
+pub(crate) struct TokenBucket {
+    /// 每秒字节数，None 表示不限速
+    rate: Option<u64>,
+    /// 当前可用令牌（字节），为负表示已预支
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(rate: Option<u64>, now: Instant) -> Self {
+        let mut bucket = Self {
+            rate: None,
+            tokens: 0.0,
+            last_refill: now,
+        };
+        bucket.set_rate(rate, now);
+        bucket
+    }
+
+    fn capacity(rate: u64) -> f64 {
+        (rate as f64 * BURST_MILLIS as f64 / 1000.0).max(MIN_BURST_BYTES)
+    }
+
+    /// 当前限速（字节/秒），None 表示不限速
+    pub fn rate(&self) -> Option<u64> {
+        self.rate
+    }
+
+    /// 修改限速后桶从满容量开始，已预支的令牌不再计算
+    pub fn set_rate(&mut self, rate: Option<u64>, now: Instant) {
+        self.rate = rate.filter(|&rate| rate > 0);
+        self.tokens = self.rate.map(Self::capacity).unwrap_or(0.0);
+        self.last_refill = now;
+    }
+
+    /// 预留 `bytes` 字节的令牌，返回发出这些字节前需要等待的时长
+    pub fn reserve(&mut self, bytes: u64, now: Instant) -> Duration {
+        let Some(rate) = self.rate else {
+            return Duration::ZERO;
+        };
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate as f64).min(Self::capacity(rate));
+        self.last_refill = now;
+
+        self.tokens -= bytes as f64;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / rate as f64)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket_limits_aggregate_rate() {
+        let start = Instant::now();
+        let mut unlimited = TokenBucket::new(None, start);
+        assert_eq!(unlimited.reserve(u64::MAX, start), Duration::ZERO);
+
+        // 1 MB/s，容量 100 毫秒
+        let rate = 1_000_000;
+        let mut bucket = TokenBucket::new(Some(rate), start);
+        let burst = TokenBucket::capacity(rate) as u64;
+        assert_eq!(bucket.reserve(burst, start), Duration::ZERO);
+
+        // 两个下载流同时各取 64 KiB，后者需要等待前者的份额
+        let first = bucket.reserve(64 * 1024, start);
+        let second = bucket.reserve(64 * 1024, start);
+        assert!(first.as_micros().abs_diff(65_536) <= 1);
+        assert!(second.as_micros().abs_diff(131_072) <= 1);
+
+        // 空闲再久也只能积攒到桶容量
+        let later = start + Duration::from_secs(10);
+        assert_eq!(bucket.reserve(burst, later), Duration::ZERO);
+        assert!(bucket.reserve(1, later) > Duration::ZERO);
+
+        bucket.set_rate(Some(0), later);
+        assert_eq!(bucket.reserve(u64::MAX, later), Duration::ZERO);
+    }
+}
@@ -0,0 +1,213 @@
+//! 批量发送进度汇总
+//!
+//! 多个文件作为一批发送时共享分组 ID，各文件的 `transfer-progress` 事件保持不变，
+//! 同时按分组汇总为 `batch-progress` 事件，供前端显示整批的进度。
+//! 单个文件失败或取消只结算该文件，不影响整批的进度统计
+
+use crate::models::{TaskStatus, TransferProgress, TransferTask};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use tauri::{AppHandle, Emitter};
+
+/// 批量发送的进度汇总（`batch-progress` 事件载荷）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchProgress {
+    /// 分组 ID
+    pub group_id: String,
+    /// 文件总数
+    pub total_files: usize,
+    /// 已完成的文件数
+    pub completed_files: usize,
+    /// 失败或取消的文件数
+    pub failed_files: usize,
+    /// 已传输字节数
+    pub transferred_bytes: u64,
+    /// 总字节数（失败或取消的文件只计已传输部分）
+    pub total_bytes: u64,
+    /// 进度百分比（0-100）
+    pub progress: f64,
+    /// 所有文件是否都已结束
+    pub finished: bool,
+}
+
+/// 批内单个文件的进度
+#[derive(Debug, Clone)]
+struct BatchEntry {
+    size: u64,
+    transferred: u64,
+    status: TaskStatus,
+}
+
+impl BatchEntry {
+    fn is_finished(&self) -> bool {
+        matches!(
+            self.status,
+            TaskStatus::Completed | TaskStatus::Failed | TaskStatus::Cancelled
+        )
+    }
+}
+
+/// 一批发送任务的进度
+#[derive(Debug, Clone, Default)]
+struct BatchGroup {
+    entries: HashMap<String, BatchEntry>,
+}
+
+impl BatchGroup {
+    fn new(tasks: &[TransferTask]) -> Self {
+        let entries = tasks
+            .iter()
+            .map(|task| {
+                let entry = BatchEntry {
+                    size: task.file.size,
+                    transferred: 0,
+                    status: TaskStatus::Pending,
+                };
+                (task.id.clone(), entry)
+            })
+            .collect();
+        Self { entries }
+    }
+
+    /// 记录单个文件的进度，已结束的文件忽略之后的更新
+    fn update(&mut self, progress: &TransferProgress) -> bool {
+        let Some(entry) = self.entries.get_mut(&progress.task_id) else {
+            return false;
+        };
+        if entry.is_finished() {
+            return false;
+        }
+        entry.status = progress.status;
+        entry.transferred = if progress.status == TaskStatus::Completed {
+            entry.size
+        } else {
+            progress.transferred_bytes.min(entry.size)
+        };
+        true
+    }
+
+    fn summary(&self, group_id: &str) -> BatchProgress {
+        let mut summary = BatchProgress {
+            group_id: group_id.to_string(),
+            total_files: self.entries.len(),
+            completed_files: 0,
+            failed_files: 0,
+            transferred_bytes: 0,
+            total_bytes: 0,
+            progress: 0.0,
+            finished: true,
+        };
+        for entry in self.entries.values() {
+            summary.transferred_bytes += entry.transferred;
+            match entry.status {
+                TaskStatus::Completed => {
+                    summary.completed_files += 1;
+                    summary.total_bytes += entry.size;
+                }
+                // 失败的文件不再有剩余字节，避免整批进度停在 100% 以下
+                TaskStatus::Failed | TaskStatus::Cancelled => {
+                    summary.failed_files += 1;
+                    summary.total_bytes += entry.transferred;
+                }
+                _ => {
+                    summary.finished = false;
+                    summary.total_bytes += entry.size;
+                }
+            }
+        }
+        summary.progress = if summary.total_bytes > 0 {
+            (summary.transferred_bytes as f64 / summary.total_bytes as f64 * 100.0).min(100.0)
+        } else if summary.finished {
+            100.0
+        } else {
+            0.0
+        };
+        summary
+    }
+}
+
+/// 进行中的批量发送（分组 ID → 进度）
+static BATCH_GROUPS: OnceLock<Mutex<HashMap<String, BatchGroup>>> = OnceLock::new();
+
+fn get_batch_groups() -> &'static Mutex<HashMap<String, BatchGroup>> {
+    BATCH_GROUPS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// 登记一批共享分组 ID 的发送任务
+pub fn register_batch(group_id: &str, tasks: &[TransferTask]) {
+    if let Ok(mut groups) = get_batch_groups().lock() {
+        groups.insert(group_id.to_string(), BatchGroup::new(tasks));
+    }
+}
+
+/// 按单个文件的进度更新所属批次，返回最新汇总
+///
+/// 不属于已登记批次的进度返回 None；整批结束后移除该批次
+fn update_batch(progress: &TransferProgress) -> Option<BatchProgress> {
+    let group_id = progress.group_id.as_deref()?;
+    let mut groups = get_batch_groups().lock().ok()?;
+    let group = groups.get_mut(group_id)?;
+    if !group.update(progress) {
+        return None;
+    }
+    let summary = group.summary(group_id);
+    if summary.finished {
+        groups.remove(group_id);
+    }
+    Some(summary)
+}
+
+/// 单个文件进度变化时通知前端所属批次的汇总进度
+pub fn emit_batch_progress(app: &AppHandle, progress: &TransferProgress) {
+    if let Some(summary) = update_batch(progress) {
+        let _ = app.emit("batch-progress", &summary);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{FileMetadata, TransferDirection, TransferMode};
+
+    fn task(size: u64) -> TransferTask {
+        let file = FileMetadata::new("a.bin".to_string(), size, String::new());
+        TransferTask::new(file, TransferMode::Local, TransferDirection::Send)
+            .with_group("group".to_string())
+    }
+
+    fn progress(
+        task: &TransferTask,
+        status: TaskStatus,
+        transferred_bytes: u64,
+    ) -> TransferProgress {
+        let mut progress = TransferProgress::from(task);
+        progress.status = status;
+        progress.transferred_bytes = transferred_bytes;
+        progress
+    }
+
+    #[test]
+    fn test_failed_file_settles_batch() {
+        let tasks = [task(100), task(300)];
+        let mut group = BatchGroup::new(&tasks);
+
+        assert!(group.update(&progress(&tasks[0], TaskStatus::Transferring, 50)));
+        let summary = group.summary("group");
+        assert_eq!((summary.transferred_bytes, summary.total_bytes), (50, 400));
+        assert!(!summary.finished);
+
+        // 失败的文件只计已传输部分，其余文件继续统计
+        assert!(group.update(&progress(&tasks[0], TaskStatus::Failed, 60)));
+        assert!(group.update(&progress(&tasks[1], TaskStatus::Completed, 300)));
+        let summary = group.summary("group");
+        assert_eq!((summary.completed_files, summary.failed_files), (1, 1));
+        assert_eq!((summary.transferred_bytes, summary.total_bytes), (360, 360));
+        assert_eq!(summary.progress, 100.0);
+        assert!(summary.finished);
+
+        // 已结束的文件忽略迟到的进度
+        assert!(!group.update(&progress(&tasks[1], TaskStatus::Transferring, 10)));
+    }
+}
@@ -2,14 +2,48 @@
 //!
 //! 负责将大文件分割成固定大小的块，便于传输和断点续传
 
-use crate::error::TransferResult;
+use crate::error::{TransferError, TransferResult};
 use crate::models::{ChunkInfo, FileMetadata, DEFAULT_CHUNK_SIZE};
+use crate::transfer::HashAlgorithm;
 use sha2::{Digest, Sha256};
 use std::fs::File;
-use std::io::{BufReader, Read, Seek, SeekFrom, Write};
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
 use std::path::Path;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{OnceLock, RwLock};
+
+/// 最小分块大小：64KB
+pub const MIN_CHUNK_SIZE: u64 = 64 * 1024;
+
+/// 最大分块大小：16MB
+pub const MAX_CHUNK_SIZE: u64 = 16 * 1024 * 1024;
+
+/// 并行计算分块哈希的最大线程数，同一时刻最多有这么多个分块在内存中
+const MAX_HASH_WORKERS: usize = 8;
+
+static CHUNK_SIZE_SETTING: OnceLock<RwLock<u64>> = OnceLock::new();
+
+fn get_chunk_size_lock() -> &'static RwLock<u64> {
+    CHUNK_SIZE_SETTING.get_or_init(|| RwLock::new(DEFAULT_CHUNK_SIZE))
+}
+
+/// 获取本地传输的分块大小（字节）
+pub fn transfer_chunk_size() -> u64 {
+    get_chunk_size_lock()
+        .read()
+        .map(|v| *v)
+        .unwrap_or(DEFAULT_CHUNK_SIZE)
+}
+
+/// 设置本地传输的分块大小，调用方需先校验范围
+pub fn set_transfer_chunk_size_internal(bytes: u64) {
+    if let Ok(mut value) = get_chunk_size_lock().write() {
+        *value = bytes;
+    }
+}
 
 /// 文件分块器
+#[derive(Debug, Clone)]
 pub struct FileChunker {
     /// 分块大小（字节）
     chunk_size: u64,
@@ -26,6 +60,24 @@ impl FileChunker {
         Self::new(DEFAULT_CHUNK_SIZE)
     }
 
+    /// 使用当前设置的分块大小创建分块器
+    pub fn from_config() -> Self {
+        Self::new(transfer_chunk_size())
+    }
+
+    /// 按元数据中的分块布局创建分块器，元数据未携带分块信息时返回 None
+    ///
+    /// 首个分块的大小即准备传输时使用的分块大小（仅一个分块时任何不小于它的分块大小
+    /// 都得到相同的布局），据此重新计算的分块与接收端预期一致
+    pub fn from_metadata(file: &FileMetadata) -> Option<Self> {
+        file.chunks.first().map(|chunk| Self::new(chunk.size))
+    }
+
+    /// 分块大小（字节）
+    pub fn chunk_size(&self) -> u64 {
+        self.chunk_size
+    }
+
     /// 计算文件的分块信息
     ///
     /// # Arguments
@@ -107,6 +159,19 @@ impl FileChunker {
         Ok(())
     }
 
+    /// 打开目标文件用于连续写入多个分块
+    ///
+    /// 接收大文件时应使用该写入器，避免每个分块都打开、定位、关闭一次文件
+    ///
+    /// # Arguments
+    /// * `file_path` - 目标文件路径
+    ///
+    /// # Returns
+    /// * `TransferResult<ChunkWriter>` - 分块写入器
+    pub fn open_writer(&self, file_path: &Path) -> TransferResult<ChunkWriter> {
+        ChunkWriter::open(file_path)
+    }
+
     /// 计算数据的 SHA256 哈希值
     ///
     /// # Arguments
@@ -148,6 +213,8 @@ impl FileChunker {
 
     /// 为文件元数据计算并设置所有分块的哈希值
     ///
+    /// 按 `metadata.hash_algorithm` 选择哈希算法，无法识别时返回错误
+    ///
     /// # Arguments
     /// * `metadata` - 文件元数据（会被修改）
     /// * `file_path` - 文件路径
@@ -155,24 +222,135 @@ impl FileChunker {
     /// # Returns
     /// * `TransferResult<FileMetadata>` - 包含哈希值的元数据
     pub fn compute_metadata_with_hashes(
+        &self,
+        metadata: FileMetadata,
+        file_path: &Path,
+    ) -> TransferResult<FileMetadata> {
+        self.compute_metadata_with_progress(metadata, file_path, &AtomicU64::new(0))
+    }
+
+    /// 为文件元数据计算哈希，同时累计已读取的字节数
+    ///
+    /// 文件总哈希在单独的线程中顺序计算，分块哈希由有限个工作线程各自读取分块并行计算，
+    /// 结果按分块顺序写回。文件会被完整读取两遍，`processed` 累计两遍读取的字节数，
+    /// 完成时为文件大小的两倍
+    pub fn compute_metadata_with_progress(
         &self,
         mut metadata: FileMetadata,
         file_path: &Path,
+        processed: &AtomicU64,
     ) -> TransferResult<FileMetadata> {
-        // 计算文件总哈希
-        metadata.hash = self.compute_file_hash(file_path)?;
-
-        // 计算每个分块的哈希
-        metadata.chunks = self.compute_chunks(file_path)?;
-        for chunk in &mut metadata.chunks {
-            let data = self.read_chunk(file_path, chunk)?;
-            chunk.hash = Self::compute_hash(&data);
-        }
+        let algorithm = HashAlgorithm::from_metadata(&metadata)?;
+        let mut chunks = self.compute_chunks(file_path)?;
+        let workers = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+            .clamp(1, MAX_HASH_WORKERS)
+            .min(chunks.len().max(1));
+        let next_chunk = AtomicUsize::new(0);
+
+        let (file_hash, chunk_hashes) = std::thread::scope(|scope| {
+            let file_hash = scope.spawn(|| {
+                let mut last = 0;
+                algorithm.hash_file_with_progress(file_path, |bytes| {
+                    processed.fetch_add(bytes - last, Ordering::Relaxed);
+                    last = bytes;
+                })
+            });
 
+            let chunks = &chunks;
+            let next_chunk = &next_chunk;
+            let handles: Vec<_> = (0..workers)
+                .map(|_| {
+                    scope.spawn(move || {
+                        let mut hashes = Vec::new();
+                        loop {
+                            let index = next_chunk.fetch_add(1, Ordering::Relaxed);
+                            let Some(chunk) = chunks.get(index) else {
+                                return Ok(hashes);
+                            };
+                            let data = match self.read_chunk(file_path, chunk) {
+                                Ok(data) => data,
+                                Err(e) => {
+                                    // 让其他工作线程尽快退出
+                                    next_chunk.store(chunks.len(), Ordering::Relaxed);
+                                    return Err(e);
+                                }
+                            };
+                            hashes.push((index, algorithm.hash_data(&data)));
+                            processed.fetch_add(chunk.size, Ordering::Relaxed);
+                        }
+                    })
+                })
+                .collect();
+
+            let join_failed = |_| TransferError::Internal("哈希计算线程异常退出".to_string());
+            let mut chunk_hashes = Vec::with_capacity(chunks.len());
+            for handle in handles {
+                chunk_hashes.extend(handle.join().map_err(join_failed)??);
+            }
+            let file_hash = file_hash.join().map_err(join_failed)??;
+            Ok::<_, TransferError>((file_hash, chunk_hashes))
+        })?;
+
+        for (index, hash) in chunk_hashes {
+            chunks[index].hash = hash;
+        }
+        metadata.hash = file_hash;
+        metadata.chunks = chunks;
         Ok(metadata)
     }
 }
 
+/// 分块写入器
+///
+/// 在整个接收过程中持有同一个带缓冲的文件句柄：按顺序到达的分块直接追加，
+/// 乱序分块才重新定位。写完后需调用 `finish` 刷新缓冲并落盘，再进行文件校验
+pub struct ChunkWriter {
+    /// 带缓冲的文件句柄
+    writer: BufWriter<File>,
+    /// 当前写入位置
+    position: u64,
+}
+
+impl ChunkWriter {
+    /// 打开（必要时创建）目标文件，保留已有内容以支持断点续传
+    pub fn open(file_path: &Path) -> TransferResult<Self> {
+        if let Some(parent) = file_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let file = File::options()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(file_path)?;
+
+        Ok(Self {
+            writer: BufWriter::with_capacity(DEFAULT_CHUNK_SIZE as usize, file),
+            position: 0,
+        })
+    }
+
+    /// 写入分块数据
+    pub fn write_chunk(&mut self, chunk: &ChunkInfo, data: &[u8]) -> TransferResult<()> {
+        if chunk.offset != self.position {
+            // 乱序分块：BufWriter 定位前会先刷新缓冲
+            self.writer.seek(SeekFrom::Start(chunk.offset))?;
+        }
+        self.writer.write_all(data)?;
+        self.position = chunk.offset + data.len() as u64;
+        Ok(())
+    }
+
+    /// 刷新缓冲并同步到磁盘
+    pub fn finish(mut self) -> TransferResult<()> {
+        self.writer.flush()?;
+        self.writer.get_ref().sync_data()?;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -201,4 +379,79 @@ mod tests {
         let hash = FileChunker::compute_hash(data);
         assert_eq!(hash.len(), 64); // SHA256 产生 64 个十六进制字符
     }
+
+    #[test]
+    fn test_chunk_writer_in_and_out_of_order() {
+        let chunker = FileChunker::new(4);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.bin");
+        let data = b"0123456789";
+
+        let chunks = vec![
+            ChunkInfo::new(0, 4, 0),
+            ChunkInfo::new(2, 2, 8),
+            ChunkInfo::new(1, 4, 4),
+        ];
+        let mut writer = chunker.open_writer(&path).unwrap();
+        for chunk in &chunks {
+            let start = chunk.offset as usize;
+            let end = start + chunk.size as usize;
+            writer.write_chunk(chunk, &data[start..end]).unwrap();
+        }
+        writer.finish().unwrap();
+
+        assert_eq!(std::fs::read(&path).unwrap(), data);
+    }
+
+    #[test]
+    fn test_chunker_from_metadata_matches_layout() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(&[1u8; 250]).unwrap();
+        temp_file.flush().unwrap();
+
+        let metadata = FileMetadata::new("a.bin".to_string(), 250, String::new());
+        assert!(FileChunker::from_metadata(&metadata).is_none());
+
+        // 按 100 字节准备的元数据，发送时即使当前设置不同也沿用原布局
+        let metadata = FileChunker::new(100)
+            .compute_metadata_with_hashes(metadata, temp_file.path())
+            .unwrap();
+        let chunker = FileChunker::from_metadata(&metadata).unwrap();
+        assert_eq!(chunker.chunk_size(), 100);
+        let layout = |chunks: &[ChunkInfo]| {
+            chunks
+                .iter()
+                .map(|c| (c.offset, c.size))
+                .collect::<Vec<_>>()
+        };
+        let chunks = chunker.compute_chunks(temp_file.path()).unwrap();
+        assert_eq!(layout(&chunks), layout(&metadata.chunks));
+    }
+
+    #[test]
+    fn test_parallel_hashes_match_serial() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        let content: Vec<u8> = (0..10_050u32).map(|i| (i % 251) as u8).collect();
+        temp_file.write_all(&content).unwrap();
+        temp_file.flush().unwrap();
+        let path = temp_file.path();
+
+        let chunker = FileChunker::new(100);
+        let metadata = FileMetadata::new("a.bin".to_string(), 10_050, String::new());
+        let processed = AtomicU64::new(0);
+        let metadata = chunker
+            .compute_metadata_with_progress(metadata, path, &processed)
+            .unwrap();
+        assert_eq!(processed.load(Ordering::Relaxed), 2 * 10_050);
+
+        // 与逐块顺序计算的结果一致
+        assert_eq!(metadata.hash, chunker.compute_file_hash(path).unwrap());
+        assert_eq!(metadata.chunks.len(), 101);
+        for (index, chunk) in metadata.chunks.iter().enumerate() {
+            assert_eq!(chunk.index as usize, index);
+            let start = chunk.offset as usize;
+            let end = start + chunk.size as usize;
+            assert_eq!(chunk.hash, FileChunker::compute_hash(&content[start..end]));
+        }
+    }
 }
@@ -1,18 +1,50 @@
-//! 云盘传输实现（接口预留）
+//! 云盘传输实现
 //!
-//! 提供云盘中转传输的抽象接口，具体实现在后续版本完成
+//! 通过 S3 兼容的对象存储中转文件：发送方按分块上传文件并写入清单，
+//! 接收方凭文件 ID（下载密钥）读取清单、逐块下载校验后落盘
 
 use crate::error::{TransferError, TransferResult};
-use crate::models::{TransferMode, TransferProgress, TransferTask};
-use crate::transfer::Transport;
+use crate::models::{
+    FileMetadata, TaskStatus, TransferDirection, TransferMode, TransferProgress, TransferTask,
+};
+use crate::share::current_timestamp_millis;
+use crate::transfer::{
+    FileChunker, FilenamePlatform, HashAlgorithm, IntegrityChecker, Transport, VerifyMode,
+};
 use async_trait::async_trait;
+use bytes::Bytes;
+use object_store::aws::AmazonS3Builder;
+use object_store::path::Path as ObjectPath;
+use object_store::signer::Signer;
+use object_store::{ObjectStore, PutPayload};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, RwLock};
+
+/// 对象键前缀
+const OBJECT_PREFIX: &str = "puresend";
+
+/// 清单对象名
+const MANIFEST_NAME: &str = "manifest.json";
+
+/// 清单格式版本
+const MANIFEST_VERSION: u32 = 1;
+
+/// 分享链接有效期（SigV4 预签名地址最长 7 天）
+const SHARE_URL_EXPIRY: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// 配置文件名
+const CLOUD_CONFIG_FILENAME: &str = "cloud_config.json";
 
 /// 云盘传输配置
-#[derive(Debug, Clone)]
-#[allow(dead_code)]
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct CloudTransportConfig {
     /// 云服务提供商
-    pub provider: CloudProvider,
+    pub provider: CloudProviderKind,
     /// 访问密钥
     pub access_key: String,
     /// 秘密密钥
@@ -21,24 +53,246 @@ pub struct CloudTransportConfig {
     pub bucket: String,
     /// 区域
     pub region: String,
+    /// 自定义服务地址（如自建 MinIO），为空时按提供商和区域推导
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
 }
 
 impl Default for CloudTransportConfig {
     fn default() -> Self {
         Self {
-            provider: CloudProvider::Unknown,
+            provider: CloudProviderKind::Unknown,
             access_key: String::new(),
             secret_key: String::new(),
             bucket: String::new(),
             region: String::new(),
+            endpoint: None,
+        }
+    }
+}
+
+// 秘密密钥不输出到日志
+impl std::fmt::Debug for CloudTransportConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CloudTransportConfig")
+            .field("provider", &self.provider)
+            .field("access_key", &self.access_key)
+            .field("secret_key", &"***")
+            .field("bucket", &self.bucket)
+            .field("region", &self.region)
+            .field("endpoint", &self.endpoint)
+            .finish()
+    }
+}
+
+impl CloudTransportConfig {
+    /// 检查必填项
+    pub fn validate(&self) -> TransferResult<()> {
+        if self.access_key.is_empty() || self.secret_key.is_empty() {
+            return Err(TransferError::InvalidMetadata(
+                "云盘访问凭据未配置".to_string(),
+            ));
+        }
+
+        if self.bucket.is_empty() {
+            return Err(TransferError::InvalidMetadata(
+                "存储桶名称未配置".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// 推导服务地址，返回地址及是否为虚拟主机风格（存储桶名在域名中）
+    ///
+    /// AWS S3 未指定地址时返回 None，使用 SDK 默认地址
+    fn resolve_endpoint(&self) -> TransferResult<Option<(String, bool)>> {
+        if let Some(endpoint) = self.endpoint.as_deref().filter(|e| !e.trim().is_empty()) {
+            return Ok(Some((
+                endpoint.trim().trim_end_matches('/').to_string(),
+                false,
+            )));
+        }
+
+        let (bucket, region) = (&self.bucket, &self.region);
+        let endpoint = match self.provider {
+            CloudProviderKind::AliyunOss => {
+                format!("https://{}.oss-{}.aliyuncs.com", bucket, region)
+            }
+            CloudProviderKind::TencentCos => {
+                format!("https://{}.cos.{}.myqcloud.com", bucket, region)
+            }
+            CloudProviderKind::Qiniu => format!("https://{}.s3.{}.qiniucs.com", bucket, region),
+            CloudProviderKind::AwsS3 => return Ok(None),
+            CloudProviderKind::Unknown => {
+                return Err(TransferError::InvalidMetadata(
+                    "未知的云服务提供商需要指定服务地址".to_string(),
+                ))
+            }
+        };
+        Ok(Some((endpoint, true)))
+    }
+
+    /// 根据配置创建 S3 兼容的存储提供方
+    fn build_provider(&self) -> TransferResult<Arc<dyn CloudProvider>> {
+        self.validate()?;
+
+        let mut builder = AmazonS3Builder::new()
+            .with_bucket_name(&self.bucket)
+            .with_access_key_id(&self.access_key)
+            .with_secret_access_key(&self.secret_key);
+        if !self.region.is_empty() {
+            builder = builder.with_region(&self.region);
+        }
+        if let Some((endpoint, virtual_hosted)) = self.resolve_endpoint()? {
+            builder = builder
+                .with_allow_http(endpoint.starts_with("http://"))
+                .with_virtual_hosted_style_request(virtual_hosted)
+                .with_endpoint(endpoint);
+        }
+
+        let store = builder.build().map_err(|e| {
+            TransferError::InvalidMetadata(format!(
+                "{} 配置无效: {}",
+                self.provider.display_name(),
+                e
+            ))
+        })?;
+        Ok(Arc::new(S3Provider::new(store)))
+    }
+}
+
+/// 云存储提供方，云盘传输通过它读写对象
+#[async_trait]
+pub trait CloudProvider: Send + Sync {
+    /// 上传对象，同名对象直接覆盖
+    async fn put_object(&self, path: &ObjectPath, data: Bytes) -> TransferResult<()>;
+
+    /// 下载对象的全部内容
+    async fn get_object(&self, path: &ObjectPath) -> TransferResult<Bytes>;
+
+    /// 列出前缀下一级的子目录名
+    async fn list(&self, prefix: &ObjectPath) -> TransferResult<Vec<String>>;
+
+    /// 生成对象的分享链接，不支持时返回 None
+    async fn share_url(
+        &self,
+        path: &ObjectPath,
+        expires_in: Duration,
+    ) -> TransferResult<Option<String>>;
+}
+
+/// S3 兼容的存储提供方（AWS S3、阿里云 OSS、腾讯云 COS、七牛云、MinIO 等）
+pub struct S3Provider {
+    store: Arc<dyn ObjectStore>,
+    /// 预签名器，用于生成分享链接
+    signer: Option<Arc<dyn Signer>>,
+}
+
+impl S3Provider {
+    /// 基于 S3 客户端创建，分享链接使用预签名地址
+    pub fn new(store: object_store::aws::AmazonS3) -> Self {
+        let store = Arc::new(store);
+        Self {
+            store: store.clone(),
+            signer: Some(store),
+        }
+    }
+
+    /// 基于任意对象存储创建，不支持分享链接
+    pub fn from_store(store: Arc<dyn ObjectStore>) -> Self {
+        Self {
+            store,
+            signer: None,
+        }
+    }
+}
+
+#[async_trait]
+impl CloudProvider for S3Provider {
+    async fn put_object(&self, path: &ObjectPath, data: Bytes) -> TransferResult<()> {
+        self.store
+            .put(path, PutPayload::from(data))
+            .await
+            .map_err(storage_error)?;
+        Ok(())
+    }
+
+    async fn get_object(&self, path: &ObjectPath) -> TransferResult<Bytes> {
+        self.store
+            .get(path)
+            .await
+            .map_err(storage_error)?
+            .bytes()
+            .await
+            .map_err(storage_error)
+    }
+
+    async fn list(&self, prefix: &ObjectPath) -> TransferResult<Vec<String>> {
+        let listing = self
+            .store
+            .list_with_delimiter(Some(prefix))
+            .await
+            .map_err(storage_error)?;
+        Ok(listing
+            .common_prefixes
+            .iter()
+            .filter_map(|p| p.filename().map(str::to_string))
+            .collect())
+    }
+
+    async fn share_url(
+        &self,
+        path: &ObjectPath,
+        expires_in: Duration,
+    ) -> TransferResult<Option<String>> {
+        let Some(signer) = self.signer.as_ref() else {
+            return Ok(None);
+        };
+        let url = signer
+            .signed_url(reqwest::Method::GET, path, expires_in)
+            .await
+            .map_err(storage_error)?;
+        Ok(Some(url.to_string()))
+    }
+}
+
+/// 云盘配置摘要，返回给前端时不含秘密密钥
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloudConfigSummary {
+    /// 云服务提供商
+    pub provider: CloudProviderKind,
+    /// 访问密钥
+    pub access_key: String,
+    /// 存储桶名称
+    pub bucket: String,
+    /// 区域
+    pub region: String,
+    /// 自定义服务地址
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+    /// 是否已保存秘密密钥
+    pub has_secret_key: bool,
+}
+
+impl From<&CloudTransportConfig> for CloudConfigSummary {
+    fn from(config: &CloudTransportConfig) -> Self {
+        Self {
+            provider: config.provider,
+            access_key: config.access_key.clone(),
+            bucket: config.bucket.clone(),
+            region: config.region.clone(),
+            endpoint: config.endpoint.clone(),
+            has_secret_key: !config.secret_key.is_empty(),
         }
     }
 }
 
 /// 云服务提供商
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-#[allow(dead_code)]
-pub enum CloudProvider {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CloudProviderKind {
     /// 阿里云 OSS
     AliyunOss,
     /// 腾讯云 COS
@@ -47,24 +301,300 @@ pub enum CloudProvider {
     Qiniu,
     /// AWS S3
     AwsS3,
-    /// 未知
+    /// 未知（需指定兼容 S3 的服务地址）
     Unknown,
 }
 
-/// 云盘传输实现
+impl CloudProviderKind {
+    /// 显示名称
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            CloudProviderKind::AliyunOss => "阿里云 OSS",
+            CloudProviderKind::TencentCos => "腾讯云 COS",
+            CloudProviderKind::Qiniu => "七牛云",
+            CloudProviderKind::AwsS3 => "AWS S3",
+            CloudProviderKind::Unknown => "S3 兼容存储",
+        }
+    }
+}
+
+fn cloud_config_path() -> PathBuf {
+    crate::app_data_dir().join(CLOUD_CONFIG_FILENAME)
+}
+
+/// 配置文件中的云盘配置，秘密密钥用设备派生的密钥加密保存
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredCloudConfig {
+    provider: CloudProviderKind,
+    access_key: String,
+    /// 加密后的秘密密钥（Base64）
+    #[serde(default)]
+    encrypted_secret_key: String,
+    /// 加密秘密密钥使用的 nonce（Base64）
+    #[serde(default)]
+    secret_key_nonce: String,
+    /// 旧版本以明文保存的秘密密钥，读取后改为加密保存
+    #[serde(default, skip_serializing)]
+    secret_key: Option<String>,
+    bucket: String,
+    region: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    endpoint: Option<String>,
+}
+
+/// 读取已保存的云盘配置，未配置、文件损坏或无法解密时返回 None
+pub fn load_cloud_config() -> Option<CloudTransportConfig> {
+    load_cloud_config_from(&cloud_config_path())
+}
+
+fn load_cloud_config_from(path: &Path) -> Option<CloudTransportConfig> {
+    let bytes = std::fs::read(path).ok()?;
+    let stored: StoredCloudConfig = match serde_json::from_slice(&bytes) {
+        Ok(stored) => stored,
+        Err(e) => {
+            tracing::warn!(error = %e, "云盘配置文件无法解析");
+            return None;
+        }
+    };
+
+    let legacy_secret = stored.secret_key.clone();
+    let secret_key = match legacy_secret.clone() {
+        Some(secret) => secret,
+        None => match crate::cloud::decrypt_password(
+            &stored.encrypted_secret_key,
+            &stored.secret_key_nonce,
+        ) {
+            Ok(secret) => secret,
+            Err(e) => {
+                tracing::warn!(error = %e, "云盘秘密密钥无法解密");
+                return None;
+            }
+        },
+    };
+    let config = CloudTransportConfig {
+        provider: stored.provider,
+        access_key: stored.access_key,
+        secret_key,
+        bucket: stored.bucket,
+        region: stored.region,
+        endpoint: stored.endpoint,
+    };
+
+    // 旧版本的明文配置改写为加密形式
+    if legacy_secret.is_some() {
+        if let Err(e) = save_cloud_config_to(path, &config) {
+            tracing::warn!(error = %e, "改写旧版云盘配置失败");
+        }
+    }
+    Some(config)
+}
+
+/// 保存云盘配置
+///
+/// 秘密密钥加密后写入；文件创建时即仅当前用户可读写，先写临时文件再重命名
+pub fn save_cloud_config(config: &CloudTransportConfig) -> TransferResult<()> {
+    save_cloud_config_to(&cloud_config_path(), config)
+}
+
+fn save_cloud_config_to(path: &Path, config: &CloudTransportConfig) -> TransferResult<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let (encrypted_secret_key, secret_key_nonce) =
+        crate::cloud::encrypt_password(&config.secret_key)
+            .map_err(|e| TransferError::Encryption(e.to_string()))?;
+    let stored = StoredCloudConfig {
+        provider: config.provider,
+        access_key: config.access_key.clone(),
+        encrypted_secret_key,
+        secret_key_nonce,
+        secret_key: None,
+        bucket: config.bucket.clone(),
+        region: config.region.clone(),
+        endpoint: config.endpoint.clone(),
+    };
+    let json =
+        serde_json::to_vec_pretty(&stored).map_err(|e| TransferError::Internal(e.to_string()))?;
+
+    let tmp_path = path.with_extension("json.tmp");
+    let _ = std::fs::remove_file(&tmp_path);
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&tmp_path)?;
+    std::io::Write::write_all(&mut file, &json)?;
+    file.sync_all()?;
+    drop(file);
+    std::fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// 云端文件清单，所有分块上传完成后写入，接收方据此下载
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CloudManifest {
+    /// 清单格式版本
+    version: u32,
+    /// 文件元数据（不含本地路径）
+    file: FileMetadata,
+    /// 上传完成时间（毫秒）
+    uploaded_at: u64,
+}
+
+impl CloudManifest {
+    /// 检查分块信息与文件大小一致，避免按损坏的清单写出越界数据
+    fn validate(&self) -> TransferResult<()> {
+        if self.version > MANIFEST_VERSION {
+            return Err(TransferError::InvalidMetadata(format!(
+                "不支持的云端清单版本: {}",
+                self.version
+            )));
+        }
+
+        let mut expected_offset = 0u64;
+        for (index, chunk) in self.file.chunks.iter().enumerate() {
+            if chunk.index as usize != index || chunk.offset != expected_offset {
+                return Err(TransferError::InvalidMetadata(
+                    "云端清单分块信息不连续".to_string(),
+                ));
+            }
+            expected_offset += chunk.size;
+        }
+        if expected_offset != self.file.size {
+            return Err(TransferError::InvalidMetadata(
+                "云端清单分块大小与文件大小不符".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// 云盘中已上传完成的文件
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloudFileSummary {
+    /// 下载密钥
+    pub key: String,
+    /// 文件名
+    pub file_name: String,
+    /// 文件大小
+    pub size: u64,
+    /// 上传完成时间（毫秒）
+    pub uploaded_at: u64,
+}
+
+/// 下载密钥对应的对象路径
+fn object_path(key: &str, name: &str) -> TransferResult<ObjectPath> {
+    let valid = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(TransferError::InvalidMetadata(format!(
+            "无效的下载密钥: {}",
+            key
+        )));
+    }
+    Ok(ObjectPath::from(format!(
+        "{}/{}/{}",
+        OBJECT_PREFIX, key, name
+    )))
+}
+
+fn chunk_object_path(key: &str, index: u32) -> TransferResult<ObjectPath> {
+    object_path(key, &format!("chunks/{:08}", index))
+}
+
+/// 从用户输入中取出下载密钥
 ///
-/// 当前仅提供接口定义，具体实现将在后续版本完成
-#[allow(dead_code)]
+/// 输入可以是下载密钥本身，也可以是发送完成后生成的分享链接
+pub fn download_key_from_input(input: &str) -> String {
+    let input = input.trim();
+    let path = input.split(['?', '#']).next().unwrap_or(input);
+    let mut segments = path.rsplit('/');
+    match (segments.next(), segments.next(), segments.next()) {
+        (Some(MANIFEST_NAME), Some(key), Some(OBJECT_PREFIX)) => key.to_string(),
+        _ => input.to_string(),
+    }
+}
+
+/// 读取并校验下载密钥对应的清单
+async fn read_manifest(provider: &dyn CloudProvider, key: &str) -> TransferResult<CloudManifest> {
+    let manifest_bytes = provider
+        .get_object(&object_path(key, MANIFEST_NAME)?)
+        .await?;
+    let manifest: CloudManifest = serde_json::from_slice(&manifest_bytes)
+        .map_err(|e| TransferError::InvalidMetadata(format!("云端清单无法解析: {}", e)))?;
+    manifest.validate()?;
+    Ok(manifest)
+}
+
+fn storage_error(e: object_store::Error) -> TransferError {
+    match e {
+        object_store::Error::NotFound { path, .. } => {
+            TransferError::FileNotFound(format!("云端对象不存在: {}", path))
+        }
+        e => TransferError::Network(format!("云存储请求失败: {}", e)),
+    }
+}
+
+/// 云盘任务状态
+#[derive(Debug, Clone)]
+struct CloudTaskState {
+    /// 进度
+    progress: TransferProgress,
+    /// 是否已取消
+    cancelled: bool,
+}
+
+/// 云盘传输实现
+#[derive(Clone)]
 pub struct CloudTransport {
     /// 配置
     config: CloudTransportConfig,
+    /// 存储提供方（配置无效时为 None）
+    provider: Option<Arc<dyn CloudProvider>>,
+    /// 分块器
+    chunker: FileChunker,
+    /// 进行中的任务
+    tasks: Arc<RwLock<HashMap<String, CloudTaskState>>>,
+    /// 进度事件发送器（由命令层转发给前端）
+    progress_sender: Arc<RwLock<Option<mpsc::UnboundedSender<TransferProgress>>>>,
 }
 
-#[allow(dead_code)]
 impl CloudTransport {
     /// 创建新的云盘传输实例
+    ///
+    /// 配置无效时实例仍可创建，`initialize` 会返回具体原因
     pub fn new(config: CloudTransportConfig) -> Self {
-        Self { config }
+        let provider = config.build_provider().ok();
+        Self::with_provider(config, provider)
+    }
+
+    /// 根据配置创建实例，配置无效时返回错误
+    pub fn connect(config: CloudTransportConfig) -> TransferResult<Self> {
+        let provider = config.build_provider()?;
+        Ok(Self::with_provider(config, Some(provider)))
+    }
+
+    /// 使用指定的存储提供方创建实例
+    pub fn with_provider(
+        config: CloudTransportConfig,
+        provider: Option<Arc<dyn CloudProvider>>,
+    ) -> Self {
+        Self {
+            config,
+            provider,
+            chunker: FileChunker::default_chunker(),
+            tasks: Arc::new(RwLock::new(HashMap::new())),
+            progress_sender: Arc::new(RwLock::new(None)),
+        }
     }
 
     /// 使用默认配置创建实例
@@ -77,87 +607,365 @@ impl CloudTransport {
         &self.config
     }
 
-    /// 更新配置
-    pub fn set_config(&mut self, config: CloudTransportConfig) {
-        self.config = config;
+    /// 设置进度事件发送器
+    pub async fn set_progress_sender(&self, sender: mpsc::UnboundedSender<TransferProgress>) {
+        *self.progress_sender.write().await = Some(sender);
     }
 
-    /// 上传文件到云盘（预留接口）
-    ///
-    /// # Arguments
-    /// * `_task` - 传输任务
-    ///
-    /// # Returns
-    /// * `TransferResult<TransferProgress>` - 传输进度
-    async fn upload_to_cloud(&self, _task: &TransferTask) -> TransferResult<TransferProgress> {
-        let provider_name = match self.config.provider {
-            CloudProvider::AliyunOss => "阿里云 OSS",
-            CloudProvider::TencentCos => "腾讯云 COS",
-            CloudProvider::Qiniu => "七牛云",
-            CloudProvider::AwsS3 => "AWS S3",
-            CloudProvider::Unknown => "未知的云服务提供商",
+    /// 取消所有进行中的任务，返回被取消的任务 ID
+    pub async fn cancel_all(&self) -> Vec<String> {
+        let mut tasks = self.tasks.write().await;
+        tasks
+            .iter_mut()
+            .filter(|(_, state)| !state.cancelled)
+            .map(|(task_id, state)| {
+                state.cancelled = true;
+                state.progress.status = TaskStatus::Cancelled;
+                task_id.clone()
+            })
+            .collect()
+    }
+
+    fn provider(&self) -> TransferResult<&Arc<dyn CloudProvider>> {
+        match self.provider.as_ref() {
+            Some(provider) => Ok(provider),
+            None => {
+                self.config.validate()?;
+                self.config.resolve_endpoint()?;
+                Err(TransferError::InvalidMetadata(format!(
+                    "{} 配置无效",
+                    self.config.provider.display_name()
+                )))
+            }
+        }
+    }
+
+    async fn register_task(&self, task: &TransferTask) {
+        let mut progress = TransferProgress::from(task);
+        progress.status = TaskStatus::Transferring;
+        self.tasks.write().await.insert(
+            task.id.clone(),
+            CloudTaskState {
+                progress,
+                cancelled: false,
+            },
+        );
+    }
+
+    /// 更新进度并推送事件，任务已取消时返回错误
+    async fn advance(
+        &self,
+        task_id: &str,
+        transferred: u64,
+        started: Instant,
+    ) -> TransferResult<()> {
+        let progress = {
+            let mut tasks = self.tasks.write().await;
+            let state = tasks
+                .get_mut(task_id)
+                .ok_or_else(|| TransferError::Internal(format!("任务不存在: {}", task_id)))?;
+            if state.cancelled {
+                return Err(TransferError::Cancelled);
+            }
+
+            let progress = &mut state.progress;
+            let elapsed = started.elapsed().as_secs_f64();
+            progress.transferred_bytes = transferred;
+            progress.progress = if progress.total_bytes == 0 {
+                100.0
+            } else {
+                transferred as f64 / progress.total_bytes as f64 * 100.0
+            };
+            progress.speed = if elapsed > 0.0 {
+                (transferred as f64 / elapsed) as u64
+            } else {
+                0
+            };
+            progress.estimated_time_remaining = (progress.speed > 0)
+                .then(|| progress.total_bytes.saturating_sub(transferred) / progress.speed);
+            progress.clone()
         };
-        Err(TransferError::UnsupportedOperation(
-            format!("{} 传输尚未实现", provider_name),
-        ))
+
+        if let Some(sender) = self.progress_sender.read().await.as_ref() {
+            let _ = sender.send(progress);
+        }
+        Ok(())
     }
 
-    /// 从云盘下载文件（预留接口）
-    ///
-    /// # Arguments
-    /// * `_task` - 传输任务
+    /// 结束任务并返回最终进度
+    async fn finish_task(
+        &self,
+        task: &TransferTask,
+        verified: Option<bool>,
+        share_url: Option<String>,
+    ) -> TransferProgress {
+        let mut progress = self
+            .tasks
+            .write()
+            .await
+            .remove(&task.id)
+            .map(|state| state.progress)
+            .unwrap_or_else(|| TransferProgress::from(task));
+        progress.status = TaskStatus::Completed;
+        progress.progress = 100.0;
+        progress.transferred_bytes = progress.total_bytes;
+        progress.estimated_time_remaining = Some(0);
+        progress.verified = verified;
+        progress.share_url = share_url;
+        progress
+    }
+
+    /// 上传文件到云盘
     ///
-    /// # Returns
-    /// * `TransferResult<TransferProgress>` - 传输进度
-    async fn download_from_cloud(&self, _task: &TransferTask) -> TransferResult<TransferProgress> {
-        let provider_name = match self.config.provider {
-            CloudProvider::AliyunOss => "阿里云 OSS",
-            CloudProvider::TencentCos => "腾讯云 COS",
-            CloudProvider::Qiniu => "七牛云",
-            CloudProvider::AwsS3 => "AWS S3",
-            CloudProvider::Unknown => "未知的云服务提供商",
+    /// 分块依次上传，全部完成后才写入清单，接收方不会读到不完整的文件。
+    /// 完成后附带清单的分享链接，接收方凭链接或下载密钥接收
+    async fn upload_to_cloud(&self, task: &TransferTask) -> TransferResult<TransferProgress> {
+        let provider = self.provider()?.clone();
+        let key = &task.file.id;
+        let file_path = task
+            .file
+            .path
+            .as_ref()
+            .map(PathBuf::from)
+            .ok_or_else(|| TransferError::InvalidMetadata("文件路径未设置".to_string()))?;
+        if !file_path.exists() {
+            return Err(TransferError::FileNotFound(file_path.display().to_string()));
+        }
+
+        let mut file = task.file.clone();
+        if file.chunks.is_empty() {
+            file.chunks = self.chunker.compute_chunks(&file_path)?;
+        }
+
+        let started = Instant::now();
+        let mut transferred = 0u64;
+        for chunk in &file.chunks {
+            let chunker = self.chunker.clone();
+            let path = file_path.clone();
+            let chunk_info = chunk.clone();
+            let data = tokio::task::spawn_blocking(move || chunker.read_chunk(&path, &chunk_info))
+                .await
+                .map_err(|e| TransferError::Internal(e.to_string()))??;
+
+            provider
+                .put_object(&chunk_object_path(key, chunk.index)?, Bytes::from(data))
+                .await?;
+
+            transferred += chunk.size;
+            self.advance(&task.id, transferred, started).await?;
+        }
+
+        file.path = None;
+        let manifest = CloudManifest {
+            version: MANIFEST_VERSION,
+            file,
+            uploaded_at: current_timestamp_millis(),
         };
-        Err(TransferError::UnsupportedOperation(
-            format!("{} 传输尚未实现", provider_name),
-        ))
+        let json =
+            serde_json::to_vec(&manifest).map_err(|e| TransferError::Internal(e.to_string()))?;
+        let manifest_path = object_path(key, MANIFEST_NAME)?;
+        provider
+            .put_object(&manifest_path, Bytes::from(json))
+            .await?;
+
+        // 分享链接生成失败不影响上传结果，接收方仍可凭下载密钥接收
+        let share_url = match provider.share_url(&manifest_path, SHARE_URL_EXPIRY).await {
+            Ok(url) => url,
+            Err(e) => {
+                tracing::warn!(task_id = %task.id, error = %e, "生成云盘分享链接失败");
+                None
+            }
+        };
+
+        tracing::info!(task_id = %task.id, key = %key, "云盘上传完成");
+        Ok(self.finish_task(task, None, share_url).await)
     }
 
-    /// 生成分享链接（预留接口）
+    /// 从云盘下载文件，返回最终进度和保存路径
     ///
-    /// # Arguments
-    /// * `_file_id` - 文件 ID
-    /// * `_expires_in` - 过期时间（秒）
+    /// `task.file.id` 为下载密钥，`task.file.path` 为保存目录，`verify_mode` 决定
+    /// 逐块校验和整体校验是否进行
+    pub async fn download_file(
+        &self,
+        task: &TransferTask,
+        file_overwrite: bool,
+        verify_mode: VerifyMode,
+    ) -> TransferResult<(TransferProgress, PathBuf)> {
+        self.register_task(task).await;
+        let result = self.download(task, file_overwrite, verify_mode).await;
+        if result.is_err() {
+            self.discard_task(&task.id).await;
+        }
+        result
+    }
+
+    /// 下载并落盘
     ///
-    /// # Returns
-    /// * `TransferResult<String>` - 分享链接
-    pub async fn generate_share_link(
+    /// 数据先写入临时文件，按校验模式逐块校验或整体校验后才重命名为目标文件，
+    /// 覆盖同名文件时传输失败也不会破坏原有文件
+    async fn download(
         &self,
-        _file_id: &str,
-        _expires_in: u64,
-    ) -> TransferResult<String> {
-        Err(TransferError::UnsupportedOperation(
-            "分享链接生成尚未实现".to_string(),
+        task: &TransferTask,
+        file_overwrite: bool,
+        verify_mode: VerifyMode,
+    ) -> TransferResult<(TransferProgress, PathBuf)> {
+        let provider = self.provider()?.clone();
+        let key = &task.file.id;
+        let directory = task
+            .file
+            .path
+            .as_ref()
+            .map(PathBuf::from)
+            .ok_or_else(|| TransferError::InvalidMetadata("保存目录未设置".to_string()))?;
+
+        let manifest = read_manifest(provider.as_ref(), key).await?;
+
+        let directory = crate::transfer::resolve_relative_dir(
+            &directory,
+            manifest.file.relative_path.as_deref(),
+            FilenamePlatform::current(),
+        )?;
+        std::fs::create_dir_all(&directory)?;
+        let available = fs2::available_space(&directory).unwrap_or(u64::MAX);
+        if available < manifest.file.size {
+            return Err(TransferError::DiskFull(format!(
+                "可用空间不足：需要 {} 字节，剩余 {} 字节",
+                manifest.file.size, available
+            )));
+        }
+
+        {
+            let mut tasks = self.tasks.write().await;
+            if let Some(state) = tasks.get_mut(&task.id) {
+                state.progress.total_bytes = manifest.file.size;
+            }
+        }
+
+        let file_name =
+            crate::transfer::sanitize_filename(&manifest.file.name, FilenamePlatform::current());
+        let part_path = directory.join(format!(".{}.{}.part", file_name, task.id));
+        let result = self
+            .download_chunks(provider.as_ref(), key, &manifest.file, &part_path, &task.id, verify_mode)
+            .await;
+        if let Err(e) = result {
+            let _ = std::fs::remove_file(&part_path);
+            return Err(e);
+        }
+
+        let file_verified = if manifest.file.hash.is_empty() {
+            None
+        } else {
+            let algorithm = HashAlgorithm::from_metadata(&manifest.file)?;
+            IntegrityChecker::new().verify_received_file(
+                &part_path,
+                &manifest.file.hash,
+                algorithm,
+                verify_mode,
+            )?
+        };
+        if file_verified == Some(false) {
+            let _ = std::fs::remove_file(&part_path);
+            return Err(TransferError::IntegrityCheckFailed(
+                "文件哈希与云端清单不符".to_string(),
+            ));
+        }
+        // 关闭校验时报告未校验，界面据此提示文件未经检查
+        let verified = file_verified.is_some()
+            || (verify_mode.verifies_chunks()
+                && manifest.file.chunks.iter().all(|c| !c.hash.is_empty()));
+
+        let final_path =
+            crate::transfer::get_receive_file_path(&directory, &file_name, file_overwrite)
+                .map_err(TransferError::Internal)?;
+        crate::transfer::commit_received_file(&part_path, &final_path)?;
+        crate::transfer::timestamps::restore_modified_time(&final_path, manifest.file.modified_at);
+
+        tracing::info!(
+            task_id = %task.id,
+            key = %key,
+            path = %final_path.display(),
+            "云盘下载完成"
+        );
+        Ok((
+            self.finish_task(task, Some(verified), None).await,
+            final_path,
         ))
     }
-}
 
-#[async_trait]
-impl Transport for CloudTransport {
-    async fn initialize(&self) -> TransferResult<()> {
-        // 验证配置
-        if self.config.access_key.is_empty() || self.config.secret_key.is_empty() {
-            return Err(TransferError::InvalidMetadata(
-                "云盘访问凭据未配置".to_string(),
-            ));
+    async fn download_chunks(
+        &self,
+        provider: &dyn CloudProvider,
+        key: &str,
+        file: &FileMetadata,
+        part_path: &Path,
+        task_id: &str,
+        verify_mode: VerifyMode,
+    ) -> TransferResult<()> {
+        let algorithm = HashAlgorithm::from_metadata(file)?;
+        let checker = IntegrityChecker::new();
+        let mut writer = self.chunker.open_writer(part_path)?;
+        let started = Instant::now();
+        let mut transferred = 0u64;
+
+        for chunk in &file.chunks {
+            let data = provider
+                .get_object(&chunk_object_path(key, chunk.index)?)
+                .await?;
+
+            if data.len() as u64 != chunk.size
+                || !checker.verify_received_chunk(&data, &chunk.hash, algorithm, verify_mode)
+            {
+                return Err(TransferError::IntegrityCheckFailed(format!(
+                    "分块 {} 校验失败",
+                    chunk.index
+                )));
+            }
+            writer.write_chunk(chunk, &data)?;
+
+            transferred += chunk.size;
+            self.advance(task_id, transferred, started).await?;
         }
 
-        if self.config.bucket.is_empty() {
-            return Err(TransferError::InvalidMetadata(
-                "存储桶名称未配置".to_string(),
-            ));
+        writer.finish()
+    }
+
+    /// 列出云盘中已上传完成的文件，按上传时间从新到旧排列
+    ///
+    /// 只列出已写入清单的文件，上传中或上传中断的文件不可见；清单损坏的文件跳过
+    pub async fn list_files(&self) -> TransferResult<Vec<CloudFileSummary>> {
+        let provider = self.provider()?;
+        let keys = provider.list(&ObjectPath::from(OBJECT_PREFIX)).await?;
+
+        let mut files = Vec::new();
+        for key in &keys {
+            match read_manifest(provider.as_ref(), key).await {
+                Ok(manifest) => files.push(CloudFileSummary {
+                    key: key.clone(),
+                    file_name: manifest.file.name,
+                    size: manifest.file.size,
+                    uploaded_at: manifest.uploaded_at,
+                }),
+                Err(TransferError::FileNotFound(_)) => {}
+                Err(TransferError::InvalidMetadata(e)) => {
+                    tracing::warn!(key = %key, error = %e, "跳过无效的云端清单");
+                }
+                Err(e) => return Err(e),
+            }
         }
+        files.sort_by(|a, b| b.uploaded_at.cmp(&a.uploaded_at));
+        Ok(files)
+    }
 
-        Ok(())
+    /// 发送/接收失败时移除任务状态
+    async fn discard_task(&self, task_id: &str) {
+        self.tasks.write().await.remove(task_id);
+    }
+}
+
+#[async_trait]
+impl Transport for CloudTransport {
+    async fn initialize(&self) -> TransferResult<()> {
+        self.provider().map(|_| ())
     }
 
     async fn send(&self, task: &TransferTask) -> TransferResult<TransferProgress> {
@@ -167,34 +975,48 @@ impl Transport for CloudTransport {
             ));
         }
 
-        self.upload_to_cloud(task).await
+        self.register_task(task).await;
+        let result = self.upload_to_cloud(task).await;
+        if result.is_err() {
+            self.discard_task(&task.id).await;
+        }
+        result
     }
 
     async fn receive(&self, task: &TransferTask) -> TransferResult<TransferProgress> {
-        if task.mode != TransferMode::Cloud {
+        if task.mode != TransferMode::Cloud || task.direction != TransferDirection::Receive {
             return Err(TransferError::UnsupportedOperation(
                 "仅支持云盘传输模式".to_string(),
             ));
         }
 
-        self.download_from_cloud(task).await
+        self.download_file(
+            task,
+            crate::transfer::file_overwrite_on_receive(),
+            crate::transfer::verify_on_receive_mode(),
+        )
+        .await
+            .map(|(progress, _)| progress)
     }
 
-    async fn cancel(&self, _task_id: &str) -> TransferResult<()> {
-        // TODO: 实现取消云盘传输
-        Err(TransferError::UnsupportedOperation(
-            "云盘传输取消尚未实现".to_string(),
-        ))
+    async fn cancel(&self, task_id: &str) -> TransferResult<()> {
+        if let Some(state) = self.tasks.write().await.get_mut(task_id) {
+            state.cancelled = true;
+            state.progress.status = TaskStatus::Cancelled;
+        }
+        Ok(())
     }
 
-    async fn progress(&self, _task_id: &str) -> TransferResult<TransferProgress> {
-        // TODO: 实现查询云盘传输进度
-        Err(TransferError::UnsupportedOperation(
-            "云盘传输进度查询尚未实现".to_string(),
-        ))
+    async fn progress(&self, task_id: &str) -> TransferResult<TransferProgress> {
+        let tasks = self.tasks.read().await;
+        tasks
+            .get(task_id)
+            .map(|state| state.progress.clone())
+            .ok_or_else(|| TransferError::Internal(format!("任务不存在: {}", task_id)))
     }
 
     async fn shutdown(&self) -> TransferResult<()> {
+        self.cancel_all().await;
         Ok(())
     }
 
@@ -216,7 +1038,42 @@ mod tests {
     #[test]
     fn test_cloud_provider() {
         let config = CloudTransportConfig::default();
-        assert_eq!(config.provider, CloudProvider::Unknown);
+        assert_eq!(config.provider, CloudProviderKind::Unknown);
+    }
+
+    #[test]
+    fn test_cloud_config_secret_is_encrypted() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CLOUD_CONFIG_FILENAME);
+        let config = CloudTransportConfig {
+            access_key: "AKID".to_string(),
+            secret_key: "top-secret-key".to_string(),
+            bucket: "bucket".to_string(),
+            ..Default::default()
+        };
+        save_cloud_config_to(&path, &config).unwrap();
+
+        let raw = std::fs::read_to_string(&path).unwrap();
+        assert!(!raw.contains("top-secret-key"));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        let loaded = load_cloud_config_from(&path).unwrap();
+        assert_eq!(loaded.secret_key, "top-secret-key");
+        assert_eq!(loaded.bucket, "bucket");
+
+        // 旧版明文配置读取后改写为加密形式
+        std::fs::write(
+            &path,
+            r#"{"provider":"aws_s3","accessKey":"AKID","secretKey":"legacy","bucket":"b","region":"r"}"#,
+        )
+        .unwrap();
+        assert_eq!(load_cloud_config_from(&path).unwrap().secret_key, "legacy");
+        assert!(!std::fs::read_to_string(&path).unwrap().contains("legacy"));
+        assert_eq!(load_cloud_config_from(&path).unwrap().secret_key, "legacy");
     }
 
     #[test]
@@ -224,4 +1081,145 @@ mod tests {
         let transport = CloudTransport::with_defaults();
         assert_eq!(transport.mode(), "cloud");
     }
+
+    #[test]
+    fn test_config_hides_secret() {
+        let config = CloudTransportConfig {
+            provider: CloudProviderKind::AliyunOss,
+            access_key: "AK".to_string(),
+            secret_key: "super-secret".to_string(),
+            bucket: "files".to_string(),
+            region: "cn-hangzhou".to_string(),
+            endpoint: None,
+        };
+        assert!(!format!("{:?}", config).contains("super-secret"));
+        assert_eq!(
+            config.resolve_endpoint().unwrap(),
+            Some((
+                "https://files.oss-cn-hangzhou.aliyuncs.com".to_string(),
+                true
+            ))
+        );
+    }
+
+    #[test]
+    fn test_object_path_rejects_traversal() {
+        assert!(object_path("../other", MANIFEST_NAME).is_err());
+        assert!(object_path("", MANIFEST_NAME).is_err());
+        assert_eq!(
+            chunk_object_path("abc-123", 7).unwrap().as_ref(),
+            "puresend/abc-123/chunks/00000007"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_s3_share_url_points_at_manifest() {
+        let store = AmazonS3Builder::new()
+            .with_bucket_name("files")
+            .with_region("us-east-1")
+            .with_access_key_id("AK")
+            .with_secret_access_key("SK")
+            .build()
+            .unwrap();
+        let provider = S3Provider::new(store);
+        let path = object_path("abc-123", MANIFEST_NAME).unwrap();
+        let url = provider
+            .share_url(&path, SHARE_URL_EXPIRY)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert!(url.contains("/puresend/abc-123/manifest.json?"));
+        assert!(url.contains("X-Amz-Signature="));
+        assert!(!url.contains("SK"));
+        assert_eq!(download_key_from_input(&url), "abc-123");
+        assert_eq!(download_key_from_input(" abc-123 "), "abc-123");
+    }
+
+    #[tokio::test]
+    async fn test_upload_and_download_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("report.bin");
+        let content: Vec<u8> = (0..300_000u32).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&source, &content).unwrap();
+
+        let store: Arc<dyn ObjectStore> = Arc::new(object_store::memory::InMemory::new());
+        let provider: Arc<dyn CloudProvider> = Arc::new(S3Provider::from_store(store.clone()));
+        let mut transport =
+            CloudTransport::with_provider(CloudTransportConfig::default(), Some(provider));
+        transport.chunker = FileChunker::new(64 * 1024);
+
+        let metadata = FileMetadata::new(
+            "report.bin".to_string(),
+            content.len() as u64,
+            String::new(),
+        );
+        let mut metadata = transport
+            .chunker
+            .compute_metadata_with_hashes(metadata, &source)
+            .unwrap();
+        metadata.path = Some(source.to_string_lossy().to_string());
+        let key = metadata.id.clone();
+        let send_task = TransferTask::new(metadata, TransferMode::Cloud, TransferDirection::Send);
+        let sent = transport.send(&send_task).await.unwrap();
+        assert_eq!(sent.status, TaskStatus::Completed);
+        assert_eq!(sent.share_url, None);
+
+        let target_dir = dir.path().join("received");
+        let mut request = FileMetadata::new(String::new(), 0, String::new());
+        request.id = key;
+        request.path = Some(target_dir.to_string_lossy().to_string());
+        let receive_task =
+            TransferTask::new(request, TransferMode::Cloud, TransferDirection::Receive);
+        let (progress, path) = transport
+            .download_file(&receive_task, false, VerifyMode::Both)
+            .await
+            .unwrap();
+
+        assert_eq!(progress.verified, Some(true));
+        assert_eq!(progress.total_bytes, content.len() as u64);
+
+        // 只列出写入了清单的文件
+        store
+            .put(
+                &chunk_object_path("partial", 0).unwrap(),
+                PutPayload::from(vec![1u8]),
+            )
+            .await
+            .unwrap();
+        let files = transport.list_files().await.unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].key, receive_task.file.id);
+        assert_eq!(files[0].file_name, "report.bin");
+        assert_eq!(files[0].size, content.len() as u64);
+        assert_eq!(path, target_dir.join("report.bin"));
+        assert_eq!(std::fs::read(&path).unwrap(), content);
+
+        // 覆盖模式：替换同名文件，且不残留临时文件
+        std::fs::write(&path, b"stale").unwrap();
+        let receive_task = TransferTask::new(
+            receive_task.file.clone(),
+            TransferMode::Cloud,
+            TransferDirection::Receive,
+        );
+        let (_, overwritten) = transport
+            .download_file(&receive_task, true, VerifyMode::default())
+            .await
+            .unwrap();
+        assert_eq!(overwritten, path);
+        assert_eq!(std::fs::read(&path).unwrap(), content);
+        assert_eq!(std::fs::read_dir(&target_dir).unwrap().count(), 1);
+
+        // 关闭校验时仍能接收，但报告未校验
+        let receive_task = TransferTask::new(
+            receive_task.file.clone(),
+            TransferMode::Cloud,
+            TransferDirection::Receive,
+        );
+        let (unchecked, _) = transport
+            .download_file(&receive_task, true, VerifyMode::Off)
+            .await
+            .unwrap();
+        assert_eq!(unchecked.verified, Some(false));
+    }
 }
@@ -3,25 +3,35 @@
 use crate::models::{
     FileMetadata, TransferDirection, TransferMode, TransferProgress, TransferTask,
 };
-use crate::transfer::{FileChunker, IntegrityChecker, LocalTransport, Transport};
+use crate::share::current_timestamp_millis;
+use crate::transfer::{
+    CloudConfigSummary, CloudTransport, CloudTransportConfig, FileChunker, HashAlgorithm,
+    IntegrityChecker, LocalTransport, Transport,
+};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter, State};
+use tauri_plugin_store::StoreExt;
 use tokio::sync::Mutex;
 
 /// 传输管理器状态
 pub struct TransferState {
     /// 本地传输实例
     local_transport: Arc<Mutex<Option<LocalTransport>>>,
+    /// 云盘传输实例（未配置时为 None）
+    cloud_transport: Arc<Mutex<Option<CloudTransport>>>,
     /// 活跃的传输任务
     active_tasks: Arc<Mutex<HashMap<String, TransferTask>>>,
-    /// 分块器
-    chunker: FileChunker,
     /// 校验器
     checker: IntegrityChecker,
     /// 接收状态
     receiving_state: Arc<Mutex<ReceivingState>>,
+    /// 接收监听器守护任务
+    receive_watchdog: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
+    /// 进行中的独立哈希计算（计算 ID -> 取消标记）
+    hash_jobs: Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>,
 }
 
 /// 接收状态
@@ -34,22 +44,28 @@ pub struct ReceivingState {
     pub port: u16,
     /// 网络地址列表
     pub network_addresses: Vec<String>,
+    /// 各网卡地址详情（含地址族、作用域与可达性推测）
+    pub network_interfaces: Vec<crate::network::LocalAddress>,
     /// 分享码
     pub share_code: String,
     /// 是否自动接收
     pub auto_receive: bool,
     /// 是否覆盖同名文件
     pub file_overwrite: bool,
+    /// 最近一次活动时间（毫秒），用于空闲自动停止
+    pub last_activity: u64,
 }
 
 impl TransferState {
     pub fn new() -> Self {
         Self {
             local_transport: Arc::new(Mutex::new(None)),
+            cloud_transport: Arc::new(Mutex::new(None)),
             active_tasks: Arc::new(Mutex::new(HashMap::new())),
-            chunker: FileChunker::default_chunker(),
             checker: IntegrityChecker::new(),
             receiving_state: Arc::new(Mutex::new(ReceivingState::default())),
+            receive_watchdog: Arc::new(Mutex::new(None)),
+            hash_jobs: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 }
@@ -60,15 +76,288 @@ impl Default for TransferState {
     }
 }
 
+impl TransferState {
+    fn transports(&self) -> TransportRegistry {
+        TransportRegistry {
+            local: self.local_transport.clone(),
+            cloud: self.cloud_transport.clone(),
+        }
+    }
+}
+
+/// 按传输模式选择传输实例
+#[derive(Clone)]
+struct TransportRegistry {
+    local: Arc<Mutex<Option<LocalTransport>>>,
+    cloud: Arc<Mutex<Option<CloudTransport>>>,
+}
+
+impl TransportRegistry {
+    /// 取出对应模式的传输实例，克隆后释放锁，传输期间仍可取消
+    async fn get(&self, mode: TransferMode) -> crate::error::TransferResult<Arc<dyn Transport>> {
+        match mode {
+            TransferMode::Local => self
+                .local
+                .lock()
+                .await
+                .clone()
+                .map(|transport| Arc::new(transport) as Arc<dyn Transport>)
+                .ok_or_else(|| {
+                    crate::error::TransferError::Internal("传输服务未初始化".to_string())
+                }),
+            TransferMode::Cloud => self
+                .cloud()
+                .await
+                .map(|transport| Arc::new(transport) as Arc<dyn Transport>),
+        }
+    }
+
+    async fn local(&self) -> crate::error::TransferResult<LocalTransport> {
+        self.local.lock().await.clone().ok_or_else(|| {
+            crate::error::TransferError::Internal("传输服务未初始化".to_string())
+        })
+    }
+
+    async fn cloud(&self) -> crate::error::TransferResult<CloudTransport> {
+        self.cloud.lock().await.clone().ok_or_else(|| {
+            crate::error::TransferError::InvalidMetadata("云盘传输未配置".to_string())
+        })
+    }
+}
+
+/// 创建进度转发通道，将传输实例推送的进度更新转发为前端 `transfer-progress` 事件，
+/// 属于批量发送的进度同时汇总为 `batch-progress` 事件
+fn progress_event_sender(app: AppHandle) -> tokio::sync::mpsc::UnboundedSender<TransferProgress> {
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel::<TransferProgress>();
+
+    // 传输实例销毁后发送端随之释放，转发任务自动结束
+    tokio::spawn(async move {
+        while let Some(progress) = receiver.recv().await {
+            let _ = app.emit("transfer-progress", &progress);
+            crate::transfer::batch::emit_batch_progress(&app, &progress);
+        }
+    });
+
+    sender
+}
+
+/// 创建短认证串转发通道，将握手派生的 SAS 转发为前端 `handshake-verification` 事件
+fn verification_event_sender(
+    app: AppHandle,
+) -> tokio::sync::mpsc::UnboundedSender<crate::transfer::crypto::HandshakeVerification> {
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        while let Some(verification) = receiver.recv().await {
+            let _ = app.emit("handshake-verification", &verification);
+        }
+    });
+
+    sender
+}
+
+/// 重试退避的最大等待时间（毫秒）
+const MAX_RETRY_DELAY_MS: u64 = 60_000;
+
+/// 重试等待期间检查任务是否已取消的间隔（毫秒）
+const RETRY_CANCEL_POLL_MS: u64 = 200;
+
+/// 整体发送重试事件载荷
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TransferRetryEvent {
+    /// 任务 ID
+    task_id: String,
+    /// 即将进行的重试次数（从 1 开始）
+    attempt: u32,
+    /// 最大重试次数
+    max_attempts: u32,
+    /// 本次重试前的等待时间（毫秒）
+    delay_ms: u64,
+    /// 上次失败的原因
+    error: String,
+}
+
+/// 端到端校验结果事件载荷
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TransferVerifyEvent {
+    /// 任务 ID
+    task_id: String,
+    /// 校验失败原因（仅 `transfer-verify-failed`）
+    error: Option<String>,
+}
+
+/// 根据发送结果通知前端端到端校验结果，未进行校验时不发送事件
+fn emit_send_verification(
+    app: &AppHandle,
+    task_id: &str,
+    result: &crate::error::TransferResult<TransferProgress>,
+) {
+    match result {
+        Ok(progress) if progress.verified == Some(true) => {
+            let _ = app.emit(
+                "transfer-verified",
+                TransferVerifyEvent {
+                    task_id: task_id.to_string(),
+                    error: None,
+                },
+            );
+        }
+        Err(crate::error::TransferError::IntegrityCheckFailed(reason)) => {
+            let _ = app.emit(
+                "transfer-verify-failed",
+                TransferVerifyEvent {
+                    task_id: task_id.to_string(),
+                    error: Some(reason.clone()),
+                },
+            );
+        }
+        _ => {}
+    }
+}
+
+/// 按重试策略发送文件
+///
+/// 遇到临时错误时按指数退避重新发送同一任务，传输实例按任务 ID 查找断点，
+/// 重试从上次的断点继续。任务在失败后或退避等待期间被取消时不再重试
+async fn send_with_retry(
+    app: &AppHandle,
+    transport: &dyn Transport,
+    task: &TransferTask,
+    active_tasks: &Mutex<HashMap<String, TransferTask>>,
+) -> crate::error::TransferResult<TransferProgress> {
+    let first = transport.send(task).await;
+    retry_send(app, transport, task, active_tasks, first).await
+}
+
+/// 以已完成的一次发送结果为首次尝试，按重试策略继续发送
+///
+/// 批量发送中经共享连接发送失败的文件由此单独重发
+async fn retry_send(
+    app: &AppHandle,
+    transport: &dyn Transport,
+    task: &TransferTask,
+    active_tasks: &Mutex<HashMap<String, TransferTask>>,
+    mut result: crate::error::TransferResult<TransferProgress>,
+) -> crate::error::TransferResult<TransferProgress> {
+    let policy = get_transfer_retry_policy();
+    let is_cancelled = || async move {
+        active_tasks
+            .lock()
+            .await
+            .get(&task.id)
+            .is_none_or(|t| t.status == crate::models::TaskStatus::Cancelled)
+    };
+    let mut attempt = 0;
+    loop {
+        let error = match result {
+            Err(e) if e.is_transient() && attempt < policy.attempts => e,
+            result => return result,
+        };
+
+        if is_cancelled().await {
+            return Err(error);
+        }
+
+        attempt += 1;
+        let delay_ms = policy
+            .base_delay_ms
+            .saturating_mul(1u64 << (attempt - 1).min(16))
+            .min(MAX_RETRY_DELAY_MS);
+        tracing::warn!(
+            task_id = %task.id,
+            attempt,
+            delay_ms,
+            error = %error,
+            "发送失败，稍后重试"
+        );
+        let _ = app.emit(
+            "transfer-retrying",
+            TransferRetryEvent {
+                task_id: task.id.clone(),
+                attempt,
+                max_attempts: policy.attempts,
+                delay_ms,
+                error: error.to_string(),
+            },
+        );
+
+        // 分段等待，取消后立即结束而不是醒来后重新发送
+        let poll = std::time::Duration::from_millis(RETRY_CANCEL_POLL_MS);
+        let mut remaining = std::time::Duration::from_millis(delay_ms);
+        while !remaining.is_zero() {
+            let wait = remaining.min(poll);
+            tokio::time::sleep(wait).await;
+            remaining -= wait;
+            if is_cancelled().await {
+                return Err(error);
+            }
+        }
+        result = transport.send(task).await;
+    }
+}
+
 /// 初始化传输服务
 #[tauri::command]
-pub async fn init_transfer(state: State<'_, TransferState>) -> Result<(), String> {
+pub async fn init_transfer(app: AppHandle, state: State<'_, TransferState>) -> Result<(), String> {
     let transport = LocalTransport::new();
     transport.initialize().await.map_err(|e| e.to_string())?;
+    transport.set_progress_sender(progress_event_sender(app.clone())).await;
+    transport
+        .set_verification_sender(verification_event_sender(app.clone()))
+        .await;
 
     let mut local_transport = state.local_transport.lock().await;
     *local_transport = Some(transport);
 
+    // 恢复已保存的云盘配置
+    let mut cloud_transport = state.cloud_transport.lock().await;
+    if cloud_transport.is_none() {
+        if let Some(config) = crate::transfer::load_cloud_config() {
+            let transport = CloudTransport::new(config);
+            transport.set_progress_sender(progress_event_sender(app)).await;
+            *cloud_transport = Some(transport);
+        }
+    }
+
+    Ok(())
+}
+
+/// 获取云盘配置（不含秘密密钥），未配置时返回 None
+#[tauri::command]
+pub async fn get_cloud_config(
+    state: State<'_, TransferState>,
+) -> Result<Option<CloudConfigSummary>, String> {
+    if let Some(transport) = state.cloud_transport.lock().await.as_ref() {
+        return Ok(Some(CloudConfigSummary::from(transport.config())));
+    }
+    Ok(crate::transfer::load_cloud_config()
+        .as_ref()
+        .map(CloudConfigSummary::from))
+}
+
+/// 设置云盘配置
+///
+/// 校验通过后保存到应用数据目录（仅当前用户可读写）并立即生效；
+/// `secretKey` 为空时沿用已保存的秘密密钥，前端无需回显密钥
+#[tauri::command]
+pub async fn set_cloud_config(
+    app: AppHandle,
+    state: State<'_, TransferState>,
+    mut config: CloudTransportConfig,
+) -> Result<(), String> {
+    if config.secret_key.is_empty() {
+        if let Some(saved) = crate::transfer::load_cloud_config() {
+            config.secret_key = saved.secret_key;
+        }
+    }
+
+    let transport = CloudTransport::connect(config.clone()).map_err(|e| e.to_string())?;
+    crate::transfer::save_cloud_config(&config).map_err(|e| e.to_string())?;
+    transport.set_progress_sender(progress_event_sender(app)).await;
+
+    *state.cloud_transport.lock().await = Some(transport);
     Ok(())
 }
 
@@ -83,10 +372,27 @@ pub async fn get_transfer_port(state: State<'_, TransferState>) -> Result<u16, S
     }
 }
 
+/// 准备进度事件的发送间隔（毫秒）
+const PREPARE_PROGRESS_INTERVAL_MS: u64 = 500;
+
+/// 准备进度事件载荷
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PrepareProgressEvent {
+    /// 调用时传入的文件路径
+    file_path: String,
+    /// 已完成哈希计算的字节数
+    processed_bytes: u64,
+    /// 文件总字节数
+    total_bytes: u64,
+}
+
 /// 准备文件传输（计算元数据和哈希）
+///
+/// 计算期间约每 500 毫秒发送一次 `prepare-progress` 事件
 #[tauri::command]
 pub async fn prepare_file_transfer(
-    state: State<'_, TransferState>,
+    app: AppHandle,
     file_path: String,
 ) -> Result<FileMetadata, String> {
     let path = PathBuf::from(&file_path);
@@ -111,16 +417,189 @@ pub async fn prepare_file_transfer(
     let metadata = tokio::fs::metadata(&path).await.map_err(|e| e.to_string())?;
     let mime_type = FileMetadata::infer_mime_type(&file_name);
 
-    let file_metadata = FileMetadata::new(file_name, metadata.len(), mime_type);
+    let mut file_metadata = FileMetadata::new(file_name, metadata.len(), mime_type);
+    file_metadata.modified_at = crate::transfer::timestamps::modified_millis(&metadata);
+    file_metadata.hash_algorithm = crate::transfer::transfer_hash_algorithm()
+        .as_str()
+        .to_string();
 
-    // 计算文件哈希和分块信息
-    state
-        .chunker
-        .compute_metadata_with_hashes(file_metadata, &path)
+    // 计算文件哈希和分块信息（按当前设置的分块大小和哈希算法，之后发送时沿用）
+    let total_bytes = file_metadata.size;
+    let processed = Arc::new(AtomicU64::new(0));
+    let mut job = tokio::task::spawn_blocking({
+        let processed = processed.clone();
+        move || {
+            FileChunker::from_config().compute_metadata_with_progress(
+                file_metadata,
+                &path,
+                &processed,
+            )
+        }
+    });
+
+    let emit_progress = |processed_bytes: u64| {
+        let _ = app.emit(
+            "prepare-progress",
+            PrepareProgressEvent {
+                file_path: file_path.clone(),
+                processed_bytes,
+                total_bytes,
+            },
+        );
+    };
+    let mut ticker = tokio::time::interval(std::time::Duration::from_millis(
+        PREPARE_PROGRESS_INTERVAL_MS,
+    ));
+    ticker.tick().await;
+    let result = loop {
+        tokio::select! {
+            result = &mut job => break result,
+            _ = ticker.tick() => {
+                // 文件读取两遍，按平均进度换算为文件字节数
+                emit_progress(processed.load(Ordering::Relaxed) / 2);
+            }
+        }
+    };
+
+    let metadata = result
+        .map_err(|e| format!("计算文件哈希失败：{}", e))?
+        .map_err(|e| e.to_string())?;
+    emit_progress(total_bytes);
+    Ok(metadata)
+}
+
+/// 独立哈希计算进度事件的最小发送间隔（毫秒）
+const HASH_PROGRESS_INTERVAL_MS: u64 = 200;
+
+/// 独立哈希计算进度事件载荷
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FileHashProgressEvent {
+    /// 计算 ID
+    hash_id: String,
+    /// 已处理字节数
+    processed_bytes: u64,
+    /// 文件总字节数
+    total_bytes: u64,
+}
+
+/// 计算任意文件的哈希，用于与他人提供的校验值比对
+///
+/// 与传输流程无关，不生成分块信息。计算期间发送 `file-hash-progress` 事件，
+/// 可通过 `cancel_file_hash` 按 `hash_id` 取消
+#[tauri::command]
+pub async fn compute_file_hash(
+    app: AppHandle,
+    state: State<'_, TransferState>,
+    path: String,
+    algorithm: Option<HashAlgorithm>,
+    hash_id: String,
+) -> Result<String, String> {
+    let file_path = PathBuf::from(&path);
+    let metadata = tokio::fs::metadata(&file_path)
+        .await
+        .map_err(|_| format!("文件不存在：{}", path))?;
+    if !metadata.is_file() {
+        return Err(format!("不是文件：{}", path));
+    }
+    let total_bytes = metadata.len();
+
+    let cancelled = Arc::new(AtomicBool::new(false));
+    {
+        let mut hash_jobs = state.hash_jobs.lock().await;
+        if hash_jobs.contains_key(&hash_id) {
+            return Err("该哈希计算已在进行中".to_string());
+        }
+        hash_jobs.insert(hash_id.clone(), cancelled.clone());
+    }
+
+    let checker = state.checker.clone();
+    let job_id = hash_id.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let interval = std::time::Duration::from_millis(HASH_PROGRESS_INTERVAL_MS);
+        let mut last_emit = std::time::Instant::now();
+        checker.compute_file_hash(
+            &file_path,
+            algorithm.unwrap_or_default(),
+            &cancelled,
+            |processed_bytes| {
+                if processed_bytes < total_bytes && last_emit.elapsed() < interval {
+                    return;
+                }
+                last_emit = std::time::Instant::now();
+                let _ = app.emit(
+                    "file-hash-progress",
+                    FileHashProgressEvent {
+                        hash_id: job_id.clone(),
+                        processed_bytes,
+                        total_bytes,
+                    },
+                );
+            },
+        )
+    })
+    .await;
+
+    state.hash_jobs.lock().await.remove(&hash_id);
+    result
+        .map_err(|e| format!("哈希计算失败：{}", e))?
         .map_err(|e| e.to_string())
 }
 
+/// 取消独立哈希计算，返回是否找到对应的计算
+#[tauri::command]
+pub async fn cancel_file_hash(
+    state: State<'_, TransferState>,
+    hash_id: String,
+) -> Result<bool, String> {
+    match state.hash_jobs.lock().await.get(&hash_id) {
+        Some(cancelled) => {
+            cancelled.store(true, Ordering::Relaxed);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// 测试与对端传输端口的连通性
+///
+/// `handshake` 默认为 true，会额外完成一次握手以确认对方为 PureSend 并获取其启用的特性
+#[tauri::command]
+pub async fn test_peer_connection(
+    ip: String,
+    port: u16,
+    handshake: Option<bool>,
+) -> Result<crate::transfer::ConnectionTestResult, String> {
+    let ip: std::net::IpAddr = ip
+        .trim()
+        .parse()
+        .map_err(|e| format!("无效的 IP 地址 '{}': {}", ip, e))?;
+    let addr = std::net::SocketAddr::new(ip, port);
+    Ok(LocalTransport::test_connection(addr, handshake.unwrap_or(true)).await)
+}
+
+/// 校验与对端的加密会话（调试用）
+///
+/// 仅完成握手和密钥交换，报告双方派生的会话密钥是否一致以及协商的加密算法、压缩，
+/// 用于区分问题出在密钥协商还是数据处理
+#[tauri::command]
+pub async fn verify_peer_session(
+    ip: String,
+    port: u16,
+) -> Result<crate::transfer::SessionVerificationResult, String> {
+    let ip: std::net::IpAddr = ip
+        .trim()
+        .parse()
+        .map_err(|e| format!("无效的 IP 地址 '{}': {}", ip, e))?;
+    let addr = std::net::SocketAddr::new(ip, port);
+    Ok(LocalTransport::verify_session(addr).await)
+}
+
 /// 发送文件（同步执行，阻塞直到完成或失败）
+///
+/// `encryption`、`compression` 为本次传输的覆盖设置，为空时沿用全局设置；
+/// `note` 为随文件发送的附言；`mode` 为传输模式，默认本地直连。
+/// 云盘模式下文件 ID 即接收方的下载密钥
 #[tauri::command]
 pub async fn send_file(
     app: AppHandle,
@@ -129,17 +608,25 @@ pub async fn send_file(
     peer_id: String,
     peer_ip: String,
     peer_port: u16,
+    encryption: Option<bool>,
+    compression: Option<bool>,
+    note: Option<String>,
+    mode: Option<TransferMode>,
 ) -> Result<String, String> {
     // 创建传输任务
     let mut task = TransferTask::new(
         file_metadata.clone(),
-        TransferMode::Local,
+        mode.unwrap_or_default(),
         TransferDirection::Send,
     );
 
-    // 设置目标设备
-    let peer = crate::models::PeerInfo::new(peer_id.clone(), peer_ip, peer_port);
-    task = task.with_peer(peer);
+    // 设置目标设备（沿用前端的设备 ID，传输历史按其归档）
+    let mut peer = crate::models::PeerInfo::new(peer_id.clone(), peer_ip, peer_port);
+    peer.id = peer_id;
+    task = task
+        .with_peer(peer)
+        .with_overrides(encryption, compression)
+        .with_note(note);
 
     let task_id = task.id.clone();
 
@@ -152,22 +639,21 @@ pub async fn send_file(
         active_tasks.insert(task_id.clone(), task.clone());
     }
 
-    // 获取传输实例
-    let transport_result = {
-        let local_transport = state.local_transport.lock().await;
-        if let Some(transport) = local_transport.as_ref() {
-            // 执行传输
-            transport.send(&task).await
-        } else {
-            Err(crate::error::TransferError::Internal(
-                "传输服务未初始化".to_string(),
-            ))
+    // 获取传输实例，克隆后释放锁，传输期间仍可取消
+    let transport_result = match state.transports().get(task.mode).await {
+        Ok(transport) => {
+            send_with_retry(&app, transport.as_ref(), &task, &state.active_tasks).await
         }
+        Err(e) => Err(e),
     };
 
-    // 更新任务状态并发送事件
+    // 更新任务状态并发送事件（已取消的任务保持取消状态）
     let mut active_tasks = state.active_tasks.lock().await;
-    if let Some(t) = active_tasks.get_mut(&task_id) {
+    if let Some(t) = active_tasks
+        .get_mut(&task_id)
+        .filter(|t| t.status != crate::models::TaskStatus::Cancelled)
+    {
+        emit_send_verification(&app, &task_id, &transport_result);
         match transport_result {
             Ok(progress) => {
                 t.progress = progress.progress;
@@ -197,12 +683,18 @@ pub async fn send_file(
                 let _ = app.emit("transfer-error", &error_progress);
             }
         }
+
+        crate::transfer::record_peer_transfer(t);
     }
 
     Ok(task_id)
 }
 
 /// 发送文件（后台执行，立即返回任务 ID）
+///
+/// `encryption`、`compression` 为本次传输的覆盖设置，为空时沿用全局设置；
+/// `note` 为随文件发送的附言；`mode` 为传输模式，默认本地直连。
+/// 云盘模式下文件 ID 即接收方的下载密钥
 #[tauri::command]
 pub async fn send_file_async(
     app: AppHandle,
@@ -211,17 +703,25 @@ pub async fn send_file_async(
     peer_id: String,
     peer_ip: String,
     peer_port: u16,
+    encryption: Option<bool>,
+    compression: Option<bool>,
+    note: Option<String>,
+    mode: Option<TransferMode>,
 ) -> Result<String, String> {
     // 创建传输任务
     let mut task = TransferTask::new(
         file_metadata.clone(),
-        TransferMode::Local,
+        mode.unwrap_or_default(),
         TransferDirection::Send,
     );
 
-    // 设置目标设备
-    let peer = crate::models::PeerInfo::new(peer_id.clone(), peer_ip, peer_port);
-    task = task.with_peer(peer);
+    // 设置目标设备（沿用前端的设备 ID，传输历史按其归档）
+    let mut peer = crate::models::PeerInfo::new(peer_id.clone(), peer_ip, peer_port);
+    peer.id = peer_id;
+    task = task
+        .with_peer(peer)
+        .with_overrides(encryption, compression)
+        .with_note(note);
 
     let task_id = task.id.clone();
 
@@ -235,124 +735,671 @@ pub async fn send_file_async(
     }
 
     // 克隆需要的资源用于后台任务
-    let local_transport = state.local_transport.clone();
+    let transports = state.transports();
     let active_tasks = state.active_tasks.clone();
     let task_id_clone = task_id.clone();
-    let app_handle = app.clone();
 
     // 在后台执行传输
     tokio::spawn(async move {
-        let transport_result = {
-            let local_transport = local_transport.lock().await;
-            if let Some(transport) = local_transport.as_ref() {
-                // 获取任务并发送
-                let tasks = active_tasks.lock().await;
-                let result = match tasks.get(&task_id_clone) {
-                    Some(task) => {
-                        let task_clone = task.clone();
-                        drop(tasks);
-                        transport.send(&task_clone).await
-                    }
-                    None => Err(crate::error::TransferError::Internal(
-                        "任务不存在".to_string(),
-                    )),
-                };
-                result
-            } else {
-                Err(crate::error::TransferError::Internal(
-                    "传输服务未初始化".to_string(),
-                ))
-            }
-        };
+        run_send_task(&app, &transports, &active_tasks, &task_id_clone).await;
+    });
 
-        // 更新任务状态并发送事件
-        let mut tasks = active_tasks.lock().await;
-        if let Some(t) = tasks.get_mut(&task_id_clone) {
-            match transport_result {
-                Ok(progress) => {
-                    t.progress = progress.progress;
-                    t.transferred_bytes = progress.transferred_bytes;
-                    t.speed = progress.speed;
-                    t.status = progress.status;
+    Ok(task_id)
+}
 
-                    // 发送进度事件
-                    let _ = app_handle.emit("transfer-progress", &progress);
-                }
-                Err(e) => {
-                    t.fail(e.to_string());
+/// 执行已登记的发送任务，结束后更新任务状态并通知前端（已取消的任务保持取消状态）
+async fn run_send_task(
+    app: &AppHandle,
+    transports: &TransportRegistry,
+    active_tasks: &Mutex<HashMap<String, TransferTask>>,
+    task_id: &str,
+) {
+    let Some(task) = active_tasks.lock().await.get(task_id).cloned() else {
+        return;
+    };
 
-                    // 发送错误事件
-                    let error_progress = TransferProgress::from(&*t);
-                    let _ = app_handle.emit("transfer-error", &error_progress);
-                }
-            }
-        }
-    });
+    // 克隆传输实例后释放锁，传输期间仍可取消
+    let transport_result = match transports.get(task.mode).await {
+        Ok(transport) => send_with_retry(app, transport.as_ref(), &task, active_tasks).await,
+        Err(e) => Err(e),
+    };
 
-    Ok(task_id)
+    settle_send_task(app, active_tasks, task_id, transport_result).await;
 }
 
-/// 取消传输
-#[tauri::command]
-pub async fn cancel_transfer(
-    state: State<'_, TransferState>,
-    task_id: String,
-) -> Result<(), String> {
-    // 取消本地传输
+/// 以传输结果更新发送任务的状态并通知前端（已取消的任务保持取消状态）
+async fn settle_send_task(
+    app: &AppHandle,
+    active_tasks: &Mutex<HashMap<String, TransferTask>>,
+    task_id: &str,
+    transport_result: crate::error::TransferResult<TransferProgress>,
+) {
+    let mut tasks = active_tasks.lock().await;
+    if let Some(t) = tasks
+        .get_mut(task_id)
+        .filter(|t| t.status != crate::models::TaskStatus::Cancelled)
     {
-        let local_transport = state.local_transport.lock().await;
-        if let Some(transport) = local_transport.as_ref() {
-            transport
-                .cancel(&task_id)
-                .await
-                .map_err(|e| e.to_string())?;
+        emit_send_verification(app, task_id, &transport_result);
+        match transport_result {
+            Ok(progress) => {
+                t.progress = progress.progress;
+                t.transferred_bytes = progress.transferred_bytes;
+                t.speed = progress.speed;
+                t.status = progress.status;
+
+                // 发送进度事件
+                let _ = app.emit("transfer-progress", &progress);
+            }
+            // 随整批取消而跳过的文件
+            Err(crate::error::TransferError::Cancelled) => {
+                t.cancel();
+                let _ = app.emit("transfer-progress", TransferProgress::from(&*t));
+            }
+            Err(e) => {
+                t.fail(e.to_string());
+
+                // 发送错误事件
+                let error_progress = TransferProgress::from(&*t);
+                let _ = app.emit("transfer-error", &error_progress);
+            }
         }
-    }
 
-    // 更新任务状态
-    let mut active_tasks = state.active_tasks.lock().await;
-    if let Some(task) = active_tasks.get_mut(&task_id) {
-        task.cancel();
+        crate::transfer::record_peer_transfer(t);
     }
-
-    Ok(())
 }
 
-/// 获取传输进度
-#[tauri::command]
-pub async fn get_transfer_progress(
-    state: State<'_, TransferState>,
-    task_id: String,
-) -> Result<TransferProgress, String> {
-    let active_tasks = state.active_tasks.lock().await;
-    active_tasks
-        .get(&task_id)
-        .map(|t| TransferProgress::from(t))
-        .ok_or_else(|| format!("任务不存在：{}", task_id))
+/// 以任务的最终状态结算其所属批次的进度
+async fn emit_settled_batch_progress(
+    app: &AppHandle,
+    active_tasks: &Mutex<HashMap<String, TransferTask>>,
+    task_id: &str,
+) {
+    let settled = active_tasks
+        .lock()
+        .await
+        .get(task_id)
+        .map(TransferProgress::from);
+    if let Some(progress) = settled {
+        crate::transfer::batch::emit_batch_progress(app, &progress);
+    }
 }
 
-/// 获取所有活跃任务
-#[tauri::command]
-pub async fn get_active_tasks(
-    state: State<'_, TransferState>,
-) -> Result<Vec<TransferTask>, String> {
-    let active_tasks = state.active_tasks.lock().await;
-    Ok(active_tasks.values().cloned().collect())
+/// 所有文件结束后结算批量发送的父任务
+///
+/// 全部完成时父任务完成；否则除父任务已被取消外标记为中断。已传输字节数为已完成
+/// 文件的大小之和；断点信息由各文件按自己的任务 ID 保存，父任务本身不可续传
+async fn settle_send_batch(
+    app: &AppHandle,
+    active_tasks: &Mutex<HashMap<String, TransferTask>>,
+    group_id: &str,
+    task_ids: &[String],
+) {
+    let parent = {
+        let mut tasks = active_tasks.lock().await;
+        let (completed_files, completed_bytes) = task_ids
+            .iter()
+            .filter_map(|id| tasks.get(id))
+            .filter(|t| t.status == crate::models::TaskStatus::Completed)
+            .fold((0, 0), |(files, bytes), t| (files + 1, bytes + t.file.size));
+        let Some(parent) = tasks.get_mut(group_id) else {
+            return;
+        };
+
+        parent.transferred_bytes = completed_bytes;
+        parent.progress = if parent.file.size > 0 {
+            completed_bytes as f64 / parent.file.size as f64 * 100.0
+        } else {
+            100.0
+        };
+        if completed_files == task_ids.len() {
+            parent.status = crate::models::TaskStatus::Completed;
+            parent.progress = 100.0;
+        } else if parent.status != crate::models::TaskStatus::Cancelled {
+            parent.status = crate::models::TaskStatus::Interrupted;
+            parent.error = Some(format!("{} 个文件未完成", task_ids.len() - completed_files));
+        }
+        parent.clone()
+    };
+
+    let _ = app.emit("transfer-progress", TransferProgress::from(&parent));
 }
 
-/// 验证文件完整性
+/// 批量发送多个文件到同一设备（后台执行，立即返回任务分组）
+///
+/// 整批对应一个父任务，其 ID 即分组 ID；各文件依次发送，进度事件携带该 ID，
+/// 并汇总为 `batch-progress` 事件。本地传输时各文件复用同一连接。
+/// 单个文件失败或被取消时继续发送其余文件；可通过任务 ID 单独取消某个文件，
+/// 取消父任务则跳过所有尚未开始的文件。各文件遇到临时错误时按重试策略单独重发，
+/// 有文件未完成时父任务标记为中断
 #[tauri::command]
-pub async fn verify_file_integrity(
+pub async fn send_files_async(
+    app: AppHandle,
     state: State<'_, TransferState>,
-    file_path: String,
-    expected_hash: String,
-) -> Result<bool, String> {
-    let path = PathBuf::from(&file_path);
-    state
-        .checker
-        .verify_file(&path, &expected_hash)
-        .map_err(|e| e.to_string())
-}
+    files: Vec<FileMetadata>,
+    peer_id: String,
+    peer_ip: String,
+    peer_port: u16,
+    encryption: Option<bool>,
+    compression: Option<bool>,
+    note: Option<String>,
+    mode: Option<TransferMode>,
+) -> Result<SendGroup, String> {
+    if files.is_empty() {
+        return Err("未选择要发送的文件".to_string());
+    }
+
+    let mode = mode.unwrap_or_default();
+    let transports = state.transports();
+    // 本地传输由同一连接依次发送，先取出传输实例
+    let local = match mode {
+        TransferMode::Local => Some(transports.local().await.map_err(|e| e.to_string())?),
+        TransferMode::Cloud => None,
+    };
+
+    let mut peer = crate::models::PeerInfo::new(peer_id.clone(), peer_ip, peer_port);
+    peer.id = peer_id;
+    let total_size = files.iter().map(|file| file.size).sum();
+    let batch_file =
+        FileMetadata::new(format!("{} 个文件", files.len()), total_size, String::new());
+    let mut parent = TransferTask::new(batch_file, mode, TransferDirection::Send)
+        .with_peer(peer.clone())
+        .with_overrides(encryption, compression);
+    parent.start();
+    let group_id = parent.id.clone();
+
+    let tasks: Vec<TransferTask> = files
+        .into_iter()
+        .map(|file| {
+            TransferTask::new(file, mode, TransferDirection::Send)
+                .with_peer(peer.clone())
+                .with_overrides(encryption, compression)
+                .with_note(note.clone())
+                .with_group(group_id.clone())
+        })
+        .collect();
+
+    crate::transfer::batch::register_batch(&group_id, &tasks);
+    {
+        let mut active_tasks = state.active_tasks.lock().await;
+        active_tasks.insert(group_id.clone(), parent.clone());
+        for task in &tasks {
+            active_tasks.insert(task.id.clone(), task.clone());
+        }
+    }
+    let _ = app.emit("transfer-progress", TransferProgress::from(&parent));
+
+    let group = SendGroup {
+        group_id: group_id.clone(),
+        task_ids: tasks.iter().map(|task| task.id.clone()).collect(),
+    };
+    let task_ids = group.task_ids.clone();
+    let active_tasks = state.active_tasks.clone();
+
+    tokio::spawn(async move {
+        match local {
+            Some(transport) => {
+                {
+                    let mut active_tasks = active_tasks.lock().await;
+                    for task_id in &task_ids {
+                        if let Some(t) = active_tasks.get_mut(task_id) {
+                            t.start();
+                        }
+                    }
+                }
+                let mut results = transport.send_batch(group_id.clone(), tasks.clone());
+                while let Some((task_id, result)) = results.recv().await {
+                    let result = match tasks.iter().find(|task| task.id == task_id) {
+                        Some(task) => {
+                            retry_send(&app, &transport, task, &active_tasks, result).await
+                        }
+                        None => result,
+                    };
+                    settle_send_task(&app, &active_tasks, &task_id, result).await;
+                    emit_settled_batch_progress(&app, &active_tasks, &task_id).await;
+                }
+            }
+            None => {
+                for task_id in &task_ids {
+                    // 轮到该文件前该文件或整批已被取消时跳过发送
+                    let started = {
+                        let mut active_tasks = active_tasks.lock().await;
+                        let batch_cancelled = active_tasks
+                            .get(&group_id)
+                            .is_some_and(|t| t.status == crate::models::TaskStatus::Cancelled);
+                        match active_tasks.get_mut(task_id) {
+                            Some(t) if t.status == crate::models::TaskStatus::Pending => {
+                                if batch_cancelled {
+                                    t.cancel();
+                                } else {
+                                    t.start();
+                                }
+                                !batch_cancelled
+                            }
+                            _ => false,
+                        }
+                    };
+                    if started {
+                        run_send_task(&app, &transports, &active_tasks, task_id).await;
+                    }
+                    emit_settled_batch_progress(&app, &active_tasks, task_id).await;
+                }
+            }
+        }
+
+        settle_send_batch(&app, &active_tasks, &group_id, &task_ids).await;
+    });
+
+    Ok(group)
+}
+
+/// 多设备发送的目标设备
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendTarget {
+    /// 设备 ID
+    pub peer_id: String,
+    /// 设备 IP
+    pub peer_ip: String,
+    /// 设备端口
+    pub peer_port: u16,
+}
+
+/// 多设备或批量发送的任务分组
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendGroup {
+    /// 分组 ID，各任务的进度事件均携带该 ID（批量发送时同时为父任务 ID）
+    pub group_id: String,
+    /// 各任务的 ID（与请求中的设备或文件顺序一致）
+    pub task_ids: Vec<String>,
+}
+
+/// 将同一文件同时发送到多个设备（后台执行，立即返回任务分组）
+///
+/// 文件只读取、压缩一次并分发给各设备，各设备的进度与失败独立跟踪，
+/// 进度事件携带共享的 `groupId`。可通过任务 ID 单独取消某个设备的发送
+#[tauri::command]
+pub async fn send_file_to_peers(
+    app: AppHandle,
+    state: State<'_, TransferState>,
+    file_metadata: FileMetadata,
+    peers: Vec<SendTarget>,
+    encryption: Option<bool>,
+    compression: Option<bool>,
+    note: Option<String>,
+) -> Result<SendGroup, String> {
+    if peers.is_empty() {
+        return Err("未指定目标设备".to_string());
+    }
+    let transport = state.transports().local().await.map_err(|e| e.to_string())?;
+
+    let group_id = uuid::Uuid::new_v4().to_string();
+    let tasks: Vec<TransferTask> = peers
+        .into_iter()
+        .map(|target| {
+            let mut peer = crate::models::PeerInfo::new(
+                target.peer_id.clone(),
+                target.peer_ip,
+                target.peer_port,
+            );
+            peer.id = target.peer_id;
+            let mut task = TransferTask::new(
+                file_metadata.clone(),
+                TransferMode::Local,
+                TransferDirection::Send,
+            )
+            .with_peer(peer)
+            .with_overrides(encryption, compression)
+            .with_note(note.clone())
+            .with_group(group_id.clone());
+            task.start();
+            task
+        })
+        .collect();
+
+    {
+        let mut active_tasks = state.active_tasks.lock().await;
+        for task in &tasks {
+            active_tasks.insert(task.id.clone(), task.clone());
+        }
+    }
+
+    let group = SendGroup {
+        group_id,
+        task_ids: tasks.iter().map(|task| task.id.clone()).collect(),
+    };
+    let active_tasks = state.active_tasks.clone();
+
+    tokio::spawn(async move {
+        let results = transport.send_file_to_peers(&tasks).await;
+
+        // 更新各任务状态并发送事件（已取消的任务保持取消状态）
+        let mut active_tasks = active_tasks.lock().await;
+        for (task, result) in tasks.iter().zip(results) {
+            let Some(t) = active_tasks
+                .get_mut(&task.id)
+                .filter(|t| t.status != crate::models::TaskStatus::Cancelled)
+            else {
+                continue;
+            };
+            emit_send_verification(&app, &task.id, &result);
+            match result {
+                Ok(progress) => {
+                    t.progress = progress.progress;
+                    t.transferred_bytes = progress.transferred_bytes;
+                    t.speed = progress.speed;
+                    t.status = progress.status;
+
+                    let _ = app.emit("transfer-progress", &progress);
+                    if progress.status == crate::models::TaskStatus::Completed {
+                        let _ = app.emit("transfer-complete", &progress);
+                    }
+                }
+                Err(e) => {
+                    t.fail(e.to_string());
+
+                    let error_progress = TransferProgress::from(&*t);
+                    let _ = app.emit("transfer-error", &error_progress);
+                }
+            }
+
+            crate::transfer::record_peer_transfer(t);
+        }
+    });
+
+    Ok(group)
+}
+
+/// 列出云盘中已上传完成的文件，接收方据此选择要下载的文件
+#[tauri::command]
+pub async fn list_cloud_files(
+    state: State<'_, TransferState>,
+) -> Result<Vec<crate::transfer::CloudFileSummary>, String> {
+    let transport = state
+        .transports()
+        .cloud()
+        .await
+        .map_err(|e| e.to_string())?;
+    transport.list_files().await.map_err(|e| e.to_string())
+}
+
+/// 从云盘下载文件（阻塞直到完成或失败）
+///
+/// `key` 为发送方的文件 ID 或发送完成后的分享链接，文件保存到 `save_directory`，返回最终保存路径
+#[tauri::command]
+pub async fn receive_cloud_file(
+    app: AppHandle,
+    state: State<'_, TransferState>,
+    key: String,
+    save_directory: String,
+) -> Result<String, String> {
+    let transport = state.transports().cloud().await.map_err(|e| e.to_string())?;
+
+    let mut request = FileMetadata::new(String::new(), 0, String::new());
+    request.id = crate::transfer::download_key_from_input(&key);
+    request.path = Some(save_directory);
+    let mut task = TransferTask::new(request, TransferMode::Cloud, TransferDirection::Receive);
+    let task_id = task.id.clone();
+    task.start();
+
+    {
+        let mut active_tasks = state.active_tasks.lock().await;
+        active_tasks.insert(task_id.clone(), task.clone());
+    }
+
+    let result = transport
+        .download_file(&task, file_overwrite_on_receive(), verify_on_receive_mode())
+        .await;
+
+    // 更新任务状态并发送事件（已取消的任务保持取消状态）
+    let mut active_tasks = state.active_tasks.lock().await;
+    if let Some(t) = active_tasks
+        .get_mut(&task_id)
+        .filter(|t| t.status != crate::models::TaskStatus::Cancelled)
+    {
+        match &result {
+            Ok((progress, path)) => {
+                t.file.size = progress.total_bytes;
+                t.file.name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                t.file.path = Some(path.to_string_lossy().to_string());
+                t.progress = progress.progress;
+                t.transferred_bytes = progress.transferred_bytes;
+                t.status = progress.status;
+
+                let _ = app.emit("transfer-progress", progress);
+                let _ = app.emit("transfer-complete", progress);
+            }
+            Err(e) => {
+                t.fail(e.to_string());
+
+                let error_progress = TransferProgress::from(&*t);
+                let _ = app.emit("transfer-error", &error_progress);
+            }
+        }
+    }
+
+    result
+        .map(|(_, path)| path.to_string_lossy().to_string())
+        .map_err(|e| e.to_string())
+}
+
+/// 获取与某设备的传输历史（最新在前）
+///
+/// `peer_id` 为设备 ID，对端提供了指纹时为设备指纹
+#[tauri::command]
+pub async fn get_peer_history(
+    peer_id: String,
+) -> Result<Vec<crate::transfer::PeerHistoryEntry>, String> {
+    Ok(crate::transfer::peer_history(&peer_id))
+}
+
+/// 清除传输历史，未指定设备时清除全部，返回清除的记录数
+#[tauri::command]
+pub async fn clear_peer_history(peer_id: Option<String>) -> Result<usize, String> {
+    crate::transfer::peer_history::clear_peer_history(peer_id.as_deref())
+}
+
+/// 取消传输
+#[tauri::command]
+pub async fn cancel_transfer(
+    state: State<'_, TransferState>,
+    task_id: String,
+) -> Result<(), String> {
+    // 取消本地传输
+    {
+        let local_transport = state.local_transport.lock().await;
+        if let Some(transport) = local_transport.as_ref() {
+            transport
+                .cancel(&task_id)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+    }
+
+    // 取消云盘传输
+    {
+        let cloud_transport = state.cloud_transport.lock().await;
+        if let Some(transport) = cloud_transport.as_ref() {
+            transport
+                .cancel(&task_id)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+    }
+
+    // 更新任务状态
+    let mut active_tasks = state.active_tasks.lock().await;
+    if let Some(task) = active_tasks.get_mut(&task_id) {
+        task.cancel();
+    }
+
+    Ok(())
+}
+
+/// 用户比对握手短认证串（`handshake-verification` 事件）后确认或拒绝
+///
+/// 确认时不做任何改变；拒绝时视为遭到中间人攻击：本地传输取消对应任务，
+/// HTTP 分享撤销对应的加密会话，浏览器后续的加密下载随之失败
+#[tauri::command]
+pub async fn confirm_handshake(
+    state: State<'_, TransferState>,
+    share_state: State<'_, crate::share::ShareManagerState>,
+    task_id: String,
+    confirmed: bool,
+) -> Result<(), String> {
+    if confirmed {
+        tracing::info!(task_id = %task_id, "用户已确认握手短认证串");
+        return Ok(());
+    }
+    tracing::warn!(task_id = %task_id, "用户拒绝握手短认证串，中止传输");
+
+    if crate::share::revoke_crypto_session(&share_state, &task_id).await {
+        return Ok(());
+    }
+    cancel_transfer(state, task_id).await
+}
+
+/// 暂停进行中的本地发送任务
+///
+/// 当前分块确认后停止发送并保持连接，暂停超过 60 秒未恢复时转为中断并保存断点信息
+#[tauri::command]
+pub async fn pause_transfer(
+    state: State<'_, TransferState>,
+    task_id: String,
+) -> Result<(), String> {
+    let transport = state
+        .local_transport
+        .lock()
+        .await
+        .clone()
+        .ok_or_else(|| "本地传输未初始化".to_string())?;
+    transport.pause(&task_id).await.map_err(|e| e.to_string())?;
+
+    let mut active_tasks = state.active_tasks.lock().await;
+    if let Some(task) = active_tasks.get_mut(&task_id) {
+        task.status = crate::models::TaskStatus::Paused;
+    }
+    Ok(())
+}
+
+/// 恢复已暂停的本地发送任务，在原连接上继续发送
+#[tauri::command]
+pub async fn resume_transfer_active(
+    state: State<'_, TransferState>,
+    task_id: String,
+) -> Result<(), String> {
+    let transport = state
+        .local_transport
+        .lock()
+        .await
+        .clone()
+        .ok_or_else(|| "本地传输未初始化".to_string())?;
+    transport
+        .resume(&task_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut active_tasks = state.active_tasks.lock().await;
+    if let Some(task) = active_tasks
+        .get_mut(&task_id)
+        .filter(|t| t.status == crate::models::TaskStatus::Paused)
+    {
+        task.start();
+    }
+    Ok(())
+}
+
+/// 取消所有进行中的传输
+///
+/// 向每个发送任务发出取消信号，并在同一次加锁内将所有未结束的任务标记为已取消，
+/// 返回被取消的任务数。并发结束的任务保持其最终状态
+#[tauri::command]
+pub async fn cancel_all_transfers(state: State<'_, TransferState>) -> Result<usize, String> {
+    let transport = state.local_transport.lock().await.clone();
+    if let Some(transport) = transport {
+        transport.cancel_all().await;
+    }
+    let cloud_transport = state.cloud_transport.lock().await.clone();
+    if let Some(transport) = cloud_transport {
+        transport.cancel_all().await;
+    }
+
+    let mut active_tasks = state.active_tasks.lock().await;
+    let mut cancelled = 0;
+    for task in active_tasks.values_mut() {
+        if matches!(
+            task.status,
+            crate::models::TaskStatus::Pending
+                | crate::models::TaskStatus::Transferring
+                | crate::models::TaskStatus::Paused
+        ) {
+            task.cancel();
+            cancelled += 1;
+        }
+    }
+
+    Ok(cancelled)
+}
+
+/// 获取传输进度
+#[tauri::command]
+pub async fn get_transfer_progress(
+    state: State<'_, TransferState>,
+    task_id: String,
+) -> Result<TransferProgress, String> {
+    let active_tasks = state.active_tasks.lock().await;
+    active_tasks
+        .get(&task_id)
+        .map(|t| TransferProgress::from(t))
+        .ok_or_else(|| format!("任务不存在：{}", task_id))
+}
+
+/// 获取所有活跃任务
+#[tauri::command]
+pub async fn get_active_tasks(
+    state: State<'_, TransferState>,
+) -> Result<Vec<TransferTask>, String> {
+    let active_tasks = state.active_tasks.lock().await;
+    Ok(active_tasks.values().cloned().collect())
+}
+
+/// 验证文件完整性
+///
+/// `algorithm` 为期望哈希使用的算法，缺省为 SHA-256
+#[tauri::command]
+pub async fn verify_file_integrity(
+    state: State<'_, TransferState>,
+    file_path: String,
+    expected_hash: String,
+    algorithm: Option<HashAlgorithm>,
+) -> Result<bool, String> {
+    let path = PathBuf::from(&file_path);
+    state
+        .checker
+        .verify_file(&path, &expected_hash, algorithm.unwrap_or_default())
+        .map_err(|e| e.to_string())
+}
+
+/// 验证部分文件完整性
+///
+/// 仅校验前 `up_to_chunk` 个分块，返回最后一个连续校验通过的分块索引，
+/// 供续传前展示已有数据的有效部分
+#[tauri::command]
+pub async fn verify_partial_file(
+    state: State<'_, TransferState>,
+    file_path: String,
+    metadata: FileMetadata,
+    up_to_chunk: u32,
+) -> Result<Option<u32>, String> {
+    let path = PathBuf::from(&file_path);
+    state
+        .checker
+        .verify_partial_file(&path, &metadata, up_to_chunk)
+        .map_err(|e| e.to_string())
+}
 
 /// 清理已完成的任务
 #[tauri::command]
@@ -368,19 +1415,264 @@ pub async fn cleanup_completed_tasks(state: State<'_, TransferState>) -> Result<
     Ok(before_count - active_tasks.len())
 }
 
-/// 启动接收监听服务器
-#[tauri::command]
-pub async fn start_receiving(
-    state: State<'_, TransferState>,
-    port: Option<u16>,
-) -> Result<ReceivingState, String> {
-    // 读取当前接收设置
-    let current_settings = {
-        let settings = get_receive_settings_lock()
-            .read()
-            .map_err(|e| e.to_string())?;
-        settings.clone()
-    };
+/// 接收监听器健康检查间隔（秒）
+const LISTENER_CHECK_INTERVAL_SECS: u64 = 5;
+
+/// 接收监听器失效后的最大重新绑定次数
+const MAX_REBIND_ATTEMPTS: u32 = 3;
+
+/// 重新绑定的首次等待时间（毫秒），之后每次翻倍
+const REBIND_BASE_DELAY_MS: u64 = 1_000;
+
+/// 接收端口或地址变化的原因
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum ReceivingChangeReason {
+    /// 请求的端口已被占用，改用了系统分配的端口
+    PortInUse,
+    /// 监听器失效后重新绑定
+    Rebound,
+    /// 本机网络地址变化（如网卡重置）
+    NetworkChanged,
+}
+
+/// `receiving-port-changed` 事件载荷
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReceivingPortChangedEvent {
+    /// 变化前的端口
+    previous_port: u16,
+    /// 当前监听端口
+    port: u16,
+    /// 当前网络地址列表
+    network_addresses: Vec<String>,
+    /// 当前分享码
+    share_code: String,
+    /// 变化原因
+    reason: ReceivingChangeReason,
+}
+
+/// `receiving-failed` 事件载荷
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReceivingFailedEvent {
+    /// 失效的监听端口
+    port: u16,
+    /// 已尝试的重新绑定次数
+    attempts: u32,
+    /// 最后一次失败的原因
+    error: String,
+}
+
+/// 生成分享码（6 位数字，基于端口和时间戳）
+fn generate_share_code(port: u16) -> String {
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u32;
+    format!("{:06}", (port as u32).wrapping_add(timestamp) % 1000000)
+}
+
+/// 接收服务是否已空闲超时，存在未结束的传输任务时视为活跃
+async fn receive_idle_expired(
+    active_tasks: &Mutex<HashMap<String, TransferTask>>,
+    receiving_state: &Mutex<ReceivingState>,
+    minutes: u32,
+) -> bool {
+    let busy = active_tasks.lock().await.values().any(|task| {
+        matches!(
+            task.status,
+            crate::models::TaskStatus::Pending
+                | crate::models::TaskStatus::Transferring
+                | crate::models::TaskStatus::Paused
+        )
+    });
+    let now = current_timestamp_millis();
+    let mut state = receiving_state.lock().await;
+    if busy {
+        state.last_activity = now;
+        return false;
+    }
+    crate::idle::is_idle(state.last_activity, now, minutes)
+}
+
+/// 通知接收服务已启动或停止（系统托盘据此更新提示）
+fn emit_receiving_state(app: &AppHandle, is_receiving: bool) {
+    let _ = app.emit("receiving-state-changed", is_receiving);
+}
+
+/// 清空接收状态
+fn reset_receiving_state(receiving_state: &mut ReceivingState) {
+    receiving_state.is_receiving = false;
+    receiving_state.port = 0;
+    receiving_state.network_addresses.clear();
+    receiving_state.network_interfaces.clear();
+    receiving_state.share_code.clear();
+}
+
+/// 更新接收状态中的端口与网络地址并通知前端
+///
+/// 端口变化时重新生成分享码，使界面上的二维码/分享码随之更新
+async fn publish_receiving_change(
+    app: &AppHandle,
+    receiving_state: &Mutex<ReceivingState>,
+    previous_port: u16,
+    port: u16,
+    reason: ReceivingChangeReason,
+) {
+    let event = {
+        let mut state = receiving_state.lock().await;
+        if port != previous_port {
+            state.share_code = generate_share_code(port);
+        }
+        state.port = port;
+        state.network_addresses = crate::network::get_local_ips();
+        state.network_interfaces = crate::network::get_local_addresses();
+        ReceivingPortChangedEvent {
+            previous_port,
+            port,
+            network_addresses: state.network_addresses.clone(),
+            share_code: state.share_code.clone(),
+            reason,
+        }
+    };
+    tracing::info!(previous_port, port, ?reason, "接收端口或地址已变化");
+    let _ = app.emit("receiving-port-changed", event);
+}
+
+/// 按指数退避重新绑定监听器，返回新端口；全部失败时返回最后一次的错误
+async fn rebind_receive_listener(transport: &LocalTransport, port: u16) -> Result<u16, String> {
+    let mut last_error = String::new();
+    for attempt in 1..=MAX_REBIND_ATTEMPTS {
+        match transport.rebind(port).await {
+            Ok(port) => return Ok(port),
+            Err(e) => {
+                tracing::warn!(port, attempt, error = %e, "接收监听器重新绑定失败");
+                last_error = e.to_string();
+            }
+        }
+        if attempt < MAX_REBIND_ATTEMPTS {
+            let delay = REBIND_BASE_DELAY_MS << (attempt - 1);
+            tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
+        }
+    }
+    Err(last_error)
+}
+
+/// 接收监听器守护任务
+///
+/// 定期从本机连接监听端口，检查监听器是否仍能接受连接，失效时先尝试重新绑定（优先沿用原端口），
+/// 端口或本机地址变化时发出 `receiving-port-changed`，重新绑定全部失败后
+/// 发出 `receiving-failed` 并重置接收状态；空闲超时后停止接收并发出
+/// `auto-stopped-idle`。由 `stop_receiving` 终止
+async fn watch_receive_listener(
+    app: AppHandle,
+    local_transport: Arc<Mutex<Option<LocalTransport>>>,
+    receiving_state: Arc<Mutex<ReceivingState>>,
+    active_tasks: Arc<Mutex<HashMap<String, TransferTask>>>,
+) {
+    let mut interval =
+        tokio::time::interval(std::time::Duration::from_secs(LISTENER_CHECK_INTERVAL_SECS));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    // 第一次 tick 立即完成，跳过
+    interval.tick().await;
+
+    loop {
+        interval.tick().await;
+
+        let Some(transport) = local_transport.lock().await.clone() else {
+            return;
+        };
+        let (previous_port, previous_addresses) = {
+            let state = receiving_state.lock().await;
+            if !state.is_receiving {
+                return;
+            }
+            (state.port, state.network_addresses.clone())
+        };
+
+        let error = match transport.probe_listener().await {
+            Ok(_) => {
+                if crate::network::get_local_ips() != previous_addresses {
+                    publish_receiving_change(
+                        &app,
+                        &receiving_state,
+                        previous_port,
+                        previous_port,
+                        ReceivingChangeReason::NetworkChanged,
+                    )
+                    .await;
+                }
+
+                let minutes = crate::idle::idle_timeout_minutes();
+                if minutes > 0
+                    && receive_idle_expired(&active_tasks, &receiving_state, minutes).await
+                {
+                    if let Some(transport) = local_transport.lock().await.take() {
+                        let _ = transport.shutdown().await;
+                    }
+                    reset_receiving_state(&mut receiving_state.lock().await);
+                    emit_receiving_state(&app, false);
+                    crate::idle::emit_auto_stopped_idle(
+                        &app,
+                        crate::idle::IdleService::Receive,
+                        None,
+                        minutes,
+                    );
+                    return;
+                }
+                continue;
+            }
+            Err(e) => e,
+        };
+
+        tracing::warn!(port = previous_port, error = %error, "接收监听器已失效，尝试重新绑定");
+        match rebind_receive_listener(&transport, previous_port).await {
+            Ok(port) => {
+                publish_receiving_change(
+                    &app,
+                    &receiving_state,
+                    previous_port,
+                    port,
+                    ReceivingChangeReason::Rebound,
+                )
+                .await;
+            }
+            Err(error) => {
+                tracing::error!(port = previous_port, error = %error, "接收监听器无法恢复");
+                if let Some(transport) = local_transport.lock().await.take() {
+                    let _ = transport.shutdown().await;
+                }
+                reset_receiving_state(&mut receiving_state.lock().await);
+                emit_receiving_state(&app, false);
+                let _ = app.emit(
+                    "receiving-failed",
+                    ReceivingFailedEvent {
+                        port: previous_port,
+                        attempts: MAX_REBIND_ATTEMPTS,
+                        error,
+                    },
+                );
+                return;
+            }
+        }
+    }
+}
+
+/// 启动接收监听服务器
+#[tauri::command]
+pub async fn start_receiving(
+    app: AppHandle,
+    state: State<'_, TransferState>,
+    port: Option<u16>,
+) -> Result<ReceivingState, String> {
+    // 读取当前接收设置
+    let current_settings = {
+        let settings = get_receive_settings_lock()
+            .read()
+            .map_err(|e| e.to_string())?;
+        settings.clone()
+    };
 
     // 检查是否已经在接收
     {
@@ -390,9 +1682,11 @@ pub async fn start_receiving(
                 is_receiving: true,
                 port: receiving_state.port,
                 network_addresses: receiving_state.network_addresses.clone(),
+                network_interfaces: receiving_state.network_interfaces.clone(),
                 share_code: receiving_state.share_code.clone(),
                 auto_receive: current_settings.auto_receive,
                 file_overwrite: current_settings.file_overwrite,
+                last_activity: receiving_state.last_activity,
             });
         }
     }
@@ -404,499 +1698,2030 @@ pub async fn start_receiving(
         LocalTransport::new()
     };
 
-    // 初始化传输服务
-    transport.initialize().await.map_err(|e| e.to_string())?;
+    // 初始化传输服务
+    transport.initialize().await.map_err(|e| e.to_string())?;
+    transport.set_progress_sender(progress_event_sender(app.clone())).await;
+    transport
+        .set_verification_sender(verification_event_sender(app.clone()))
+        .await;
+
+    // 设置接收配置
+    use crate::transfer::local::ReceiveConfig;
+    let receive_config = ReceiveConfig {
+        auto_receive: current_settings.auto_receive,
+        file_overwrite: current_settings.file_overwrite,
+        receive_directory: std::env::temp_dir(), // 使用临时目录作为默认接收目录
+        max_auto_accept_size: current_settings.max_auto_accept_size,
+    };
+    transport.set_receive_config(receive_config).await;
+
+    // 获取监听端口（指定端口被占用时为系统分配的端口）
+    let listen_port = transport
+        .get_listen_port()
+        .await
+        .map_err(|e| e.to_string())?;
+    let requested_port = port.filter(|&p| p != 0 && p != listen_port);
+
+    // 获取本地所有 IP 地址
+    let network_addresses = crate::network::get_local_ips();
+    let network_interfaces = crate::network::get_local_addresses();
+
+    let share_code = generate_share_code(listen_port);
+
+    // 保存传输实例
+    {
+        let mut local_transport = state.local_transport.lock().await;
+        *local_transport = Some(transport);
+    }
+
+    // 更新接收状态并返回结果
+    let result = {
+        let mut receiving_state = state.receiving_state.lock().await;
+        receiving_state.is_receiving = true;
+        receiving_state.port = listen_port;
+        receiving_state.network_addresses = network_addresses.clone();
+        receiving_state.network_interfaces = network_interfaces.clone();
+        receiving_state.share_code = share_code.clone();
+        receiving_state.last_activity = current_timestamp_millis();
+
+        ReceivingState {
+            is_receiving: true,
+            port: listen_port,
+            network_addresses,
+            network_interfaces,
+            share_code,
+            auto_receive: current_settings.auto_receive,
+            file_overwrite: current_settings.file_overwrite,
+            last_activity: receiving_state.last_activity,
+        }
+    };
+
+    if let Some(requested_port) = requested_port {
+        tracing::warn!(requested_port, port = listen_port, "接收端口已被占用，已改用其他端口");
+        let _ = app.emit(
+            "receiving-port-changed",
+            ReceivingPortChangedEvent {
+                previous_port: requested_port,
+                port: listen_port,
+                network_addresses: result.network_addresses.clone(),
+                share_code: result.share_code.clone(),
+                reason: ReceivingChangeReason::PortInUse,
+            },
+        );
+    }
+
+    emit_receiving_state(&app, true);
+
+    // 启动监听器守护任务
+    let watchdog = tokio::spawn(watch_receive_listener(
+        app,
+        state.local_transport.clone(),
+        state.receiving_state.clone(),
+        state.active_tasks.clone(),
+    ));
+    if let Some(previous) = state.receive_watchdog.lock().await.replace(watchdog) {
+        previous.abort();
+    }
+
+    Ok(result)
+}
+
+/// 停止接收监听服务器
+#[tauri::command]
+pub async fn stop_receiving(app: AppHandle, state: State<'_, TransferState>) -> Result<(), String> {
+    // 检查是否有活跃任务
+    {
+        let active_tasks = state.active_tasks.lock().await;
+        let has_active_tasks = active_tasks.values().any(|t| {
+            t.status == crate::models::TaskStatus::Pending
+                || t.status == crate::models::TaskStatus::Transferring
+                || t.status == crate::models::TaskStatus::Paused
+        });
+
+        if has_active_tasks {
+            return Err("有活跃的传输任务，无法停止接收服务".to_string());
+        }
+    }
+
+    // 先停止守护任务，避免把关闭的监听器当作失效而重新绑定
+    if let Some(watchdog) = state.receive_watchdog.lock().await.take() {
+        watchdog.abort();
+    }
+
+    // 关闭传输服务
+    {
+        let mut local_transport = state.local_transport.lock().await;
+        if let Some(transport) = local_transport.take() {
+            transport.shutdown().await.map_err(|e| e.to_string())?;
+        }
+    }
+
+    // 重置接收状态
+    reset_receiving_state(&mut state.receiving_state.lock().await);
+    emit_receiving_state(&app, false);
+
+    Ok(())
+}
+
+/// 获取网络信息（只返回真实服务器状态）
+#[tauri::command]
+pub async fn get_network_info(state: State<'_, TransferState>) -> Result<ReceivingState, String> {
+    let receiving_state = state.receiving_state.lock().await;
+
+    // 读取当前设置
+    let settings = get_receive_settings_lock()
+        .read()
+        .map_err(|e| e.to_string())?;
+
+    // 只返回真实的接收状态，不生成临时信息
+    Ok(ReceivingState {
+        is_receiving: receiving_state.is_receiving,
+        port: receiving_state.port,
+        network_addresses: receiving_state.network_addresses.clone(),
+        network_interfaces: receiving_state.network_interfaces.clone(),
+        share_code: receiving_state.share_code.clone(),
+        auto_receive: settings.auto_receive,
+        file_overwrite: settings.file_overwrite,
+        last_activity: receiving_state.last_activity,
+    })
+}
+
+/// 获取文件元数据（不计算哈希，仅获取基本信息）
+#[tauri::command]
+pub async fn get_file_metadata(file_path: String) -> Result<FileMetadata, String> {
+    let path = PathBuf::from(&file_path);
+
+    if !path.exists() {
+        return Err(format!("文件不存在：{}", file_path));
+    }
+
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown")
+        .to_string();
+
+    let metadata = std::fs::metadata(&path).map_err(|e| e.to_string())?;
+    let mime_type = FileMetadata::infer_mime_type(&file_name);
+
+    let mut file_metadata = FileMetadata::new(file_name, metadata.len(), mime_type);
+    file_metadata.modified_at = crate::transfer::timestamps::modified_millis(&metadata);
+    Ok(file_metadata)
+}
+
+/// 文件信息
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FileInfo {
+    /// 文件路径
+    pub path: String,
+    /// 文件名
+    pub name: String,
+    /// 文件大小
+    pub size: u64,
+    /// 相对路径
+    pub relative_path: String,
+}
+
+/// 递归获取文件夹下的所有文件
+///
+/// `patterns` 为可选的忽略规则（支持 `*`、`?` 通配符），匹配文件名、
+/// 相对路径或任一目录名的文件会被跳过
+#[tauri::command]
+pub async fn get_files_in_folder(
+    folder_path: String,
+    patterns: Option<Vec<String>>,
+) -> Result<Vec<FileInfo>, String> {
+    let folder = PathBuf::from(&folder_path);
+
+    if !folder.exists() {
+        return Err(format!("文件夹不存在：{}", folder_path));
+    }
+
+    if !folder.is_dir() {
+        return Err(format!("路径不是文件夹：{}", folder_path));
+    }
+
+    // 验证路径合法性（防止路径遍历攻击）
+    let canonical_folder = folder
+        .canonicalize()
+        .map_err(|e| format!("路径验证失败：{}", e))?;
+
+    let mut files = Vec::new();
+    collect_files_recursive(&canonical_folder, &canonical_folder, &mut files)
+        .map_err(|e| e.to_string())?;
+
+    let patterns = patterns.unwrap_or_default();
+    files.retain(|f| !is_ignored(&f.relative_path, &patterns));
+
+    Ok(files)
+}
+
+/// 文件夹发送预览
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderSendPreview {
+    /// 将要发送的文件数
+    pub file_count: usize,
+    /// 将要发送的总大小（字节）
+    pub total_size: u64,
+    /// 被忽略规则跳过的文件数
+    pub skipped_count: usize,
+    /// 被跳过的总大小（字节）
+    pub skipped_size: u64,
+    /// 按给定速度估算的传输时间（秒）
+    pub estimated_seconds: Option<u64>,
+    /// 最大的若干文件
+    pub largest_files: Vec<FileInfo>,
+}
+
+/// 预览中展示的最大文件数量
+const PREVIEW_LARGEST_FILES: usize = 10;
+
+/// 文件夹发送预览（不计算哈希）
+///
+/// 汇总将要发送的文件数与总大小、被忽略规则跳过的文件，
+/// 并按 `speed`（字节/秒）估算传输时间，便于发送前提示用户
+#[tauri::command]
+pub async fn preview_folder_send(
+    folder_path: String,
+    patterns: Option<Vec<String>>,
+    speed: Option<u64>,
+) -> Result<FolderSendPreview, String> {
+    let folder = PathBuf::from(&folder_path);
+
+    if !folder.is_dir() {
+        return Err(format!("路径不是文件夹：{}", folder_path));
+    }
+
+    let canonical_folder = folder
+        .canonicalize()
+        .map_err(|e| format!("路径验证失败：{}", e))?;
+
+    let patterns = patterns.unwrap_or_default();
+    let (included, skipped) = tokio::task::spawn_blocking(move || {
+        let mut files = Vec::new();
+        collect_files_recursive(&canonical_folder, &canonical_folder, &mut files)?;
+        Ok::<_, std::io::Error>(
+            files
+                .into_iter()
+                .partition::<Vec<FileInfo>, _>(|f| !is_ignored(&f.relative_path, &patterns)),
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())?;
+
+    let total_size: u64 = included.iter().map(|f| f.size).sum();
+    let skipped_size: u64 = skipped.iter().map(|f| f.size).sum();
+
+    let mut largest_files = included.clone();
+    largest_files.sort_by(|a, b| b.size.cmp(&a.size));
+    largest_files.truncate(PREVIEW_LARGEST_FILES);
+
+    Ok(FolderSendPreview {
+        file_count: included.len(),
+        total_size,
+        skipped_count: skipped.len(),
+        skipped_size,
+        estimated_seconds: speed.filter(|s| *s > 0).map(|s| total_size.div_ceil(s)),
+        largest_files,
+    })
+}
+
+/// 判断相对路径是否被忽略规则排除
+///
+/// 规则可匹配完整相对路径、文件名或任一目录名（如 `node_modules`、`*.tmp`）；
+/// 以 `!` 开头的规则重新包含此前被排除的文件，按顺序后面的规则优先
+fn is_ignored(relative_path: &str, patterns: &[String]) -> bool {
+    let normalized = relative_path.replace('\\', "/");
+    let mut ignored = false;
+    for pattern in patterns {
+        let pattern = pattern.trim();
+        let (negated, pattern) = match pattern.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, pattern),
+        };
+        let pattern = pattern.trim_end_matches('/');
+        // 只有能改变当前结果的规则才需要匹配
+        if pattern.is_empty() || ignored != negated {
+            continue;
+        }
+        if wildcard_match(pattern, &normalized)
+            || normalized
+                .split('/')
+                .any(|component| wildcard_match(pattern, component))
+        {
+            ignored = !negated;
+        }
+    }
+    ignored
+}
+
+/// 简单通配符匹配（`*` 匹配任意长度字符，`?` 匹配单个字符）
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// 递归收集文件
+fn collect_files_recursive(
+    current_dir: &PathBuf,
+    base_dir: &PathBuf,
+    files: &mut Vec<FileInfo>,
+) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(current_dir)? {
+        let entry = entry?;
+        let path = entry.path();
+
+        // 计算相对路径
+        let relative_path = path
+            .strip_prefix(base_dir)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .to_string();
+
+        if path.is_dir() {
+            // 递归处理子目录
+            collect_files_recursive(&path, base_dir, files)?;
+        } else if path.is_file() {
+            // 添加文件信息
+            let name = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("unknown")
+                .to_string();
+
+            let metadata = std::fs::metadata(&path)?;
+            files.push(FileInfo {
+                path: path.to_string_lossy().to_string(),
+                name,
+                size: metadata.len(),
+                relative_path,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+// ============ 远程资源相关命令 ============
+
+/// 远程资源下载默认大小上限（2GB）
+const FETCH_DEFAULT_MAX_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+/// 远程资源连接超时（秒）
+const FETCH_CONNECT_TIMEOUT_SECS: u64 = 10;
+
+/// 远程资源下载总超时（秒）
+const FETCH_TOTAL_TIMEOUT_SECS: u64 = 600;
+
+/// 最多跟随的重定向次数
+const FETCH_MAX_REDIRECTS: usize = 5;
+
+/// 下载远程资源到临时目录
+///
+/// 仅支持 http/https，默认拒绝指向内网、回环、链路本地地址的请求（防止 SSRF），
+/// 重定向时对每一跳重新校验，并把连接固定到校验过的地址。返回下载后的本地路径，供常规发送流程使用
+#[tauri::command]
+pub async fn fetch_url_to_temp(
+    url: String,
+    max_bytes: Option<u64>,
+    allow_private: Option<bool>,
+) -> Result<String, String> {
+    use futures::StreamExt;
+    use tokio::io::AsyncWriteExt;
+
+    let max_bytes = max_bytes.unwrap_or(FETCH_DEFAULT_MAX_BYTES);
+    let allow_private = allow_private.unwrap_or(false);
+
+    let mut current_url =
+        reqwest::Url::parse(&url).map_err(|e| format!("无效的 URL：{}", e))?;
+    let mut redirects = 0;
+
+    let response = loop {
+        let pinned = validate_fetch_target(&current_url, allow_private).await?;
+
+        let response = fetch_client(&current_url, &pinned)?
+            .get(current_url.clone())
+            .send()
+            .await
+            .map_err(|e| format!("请求失败：{}", e))?;
+
+        if !response.status().is_redirection() {
+            break response;
+        }
+
+        redirects += 1;
+        if redirects > FETCH_MAX_REDIRECTS {
+            return Err("重定向次数过多".to_string());
+        }
+
+        let location = response
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|v| v.to_str().ok())
+            .ok_or_else(|| "重定向响应缺少 Location".to_string())?;
+        current_url = current_url
+            .join(location)
+            .map_err(|e| format!("无效的重定向地址：{}", e))?;
+    };
+
+    if !response.status().is_success() {
+        return Err(format!("下载失败，状态码：{}", response.status().as_u16()));
+    }
+
+    if let Some(length) = response.content_length() {
+        if length > max_bytes {
+            return Err(format!(
+                "远程文件过大：{} 字节，上限 {} 字节",
+                length, max_bytes
+            ));
+        }
+    }
+
+    let file_name = response
+        .headers()
+        .get(reqwest::header::CONTENT_DISPOSITION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse_content_disposition_filename)
+        .or_else(|| filename_from_url(&current_url))
+        .unwrap_or_else(|| "download".to_string());
+
+    let temp_dir = app_temp_directory()
+        .join("fetched")
+        .join(uuid::Uuid::new_v4().to_string());
+    tokio::fs::create_dir_all(&temp_dir)
+        .await
+        .map_err(|e| format!("创建临时目录失败：{}", e))?;
+    let temp_path = temp_dir.join(&file_name);
+
+    let mut file = tokio::fs::File::create(&temp_path)
+        .await
+        .map_err(|e| format!("创建临时文件失败：{}", e))?;
+
+    let mut downloaded: u64 = 0;
+    let mut stream = response.bytes_stream();
+    let result: Result<(), String> = async {
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|e| format!("读取响应数据失败：{}", e))?;
+            downloaded += chunk.len() as u64;
+            if downloaded > max_bytes {
+                return Err(format!("远程文件超过大小上限 {} 字节", max_bytes));
+            }
+            file.write_all(&chunk)
+                .await
+                .map_err(|e| format!("写入临时文件失败：{}", e))?;
+        }
+        file.flush()
+            .await
+            .map_err(|e| format!("写入临时文件失败：{}", e))
+    }
+    .await;
+
+    if let Err(e) = result {
+        drop(file);
+        let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+        return Err(e);
+    }
+
+    Ok(temp_path.to_string_lossy().to_string())
+}
+
+/// 创建远程资源下载客户端
+///
+/// 传入校验过的地址时连接固定到这些地址，避免连接时重新解析得到另一个地址（DNS 重绑定）；
+/// 此时也不走系统代理，确保实际连接的就是校验过的地址
+fn fetch_client(
+    url: &reqwest::Url,
+    pinned: &[std::net::SocketAddr],
+) -> Result<reqwest::Client, String> {
+    let mut builder = reqwest::Client::builder()
+        .connect_timeout(std::time::Duration::from_secs(FETCH_CONNECT_TIMEOUT_SECS))
+        .timeout(std::time::Duration::from_secs(FETCH_TOTAL_TIMEOUT_SECS))
+        .redirect(reqwest::redirect::Policy::none());
+    if !pinned.is_empty() {
+        // IP 字面量不经过解析，只需固定域名
+        if let Some(domain) = url.domain() {
+            builder = builder.resolve_to_addrs(domain, pinned);
+        }
+        builder = builder.no_proxy();
+    }
+    builder
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败：{}", e))
+}
+
+/// 校验远程资源地址（协议与目标 IP），返回校验通过的解析地址
+///
+/// 允许内网地址时不解析主机，返回空列表
+async fn validate_fetch_target(
+    url: &reqwest::Url,
+    allow_private: bool,
+) -> Result<Vec<std::net::SocketAddr>, String> {
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return Err(format!("不支持的协议：{}，仅支持 http/https", url.scheme()));
+    }
+
+    let host = url
+        .host_str()
+        .ok_or_else(|| "URL 缺少主机名".to_string())?
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_string();
+    let port = url.port_or_known_default().unwrap_or(80);
+
+    if allow_private {
+        return Ok(Vec::new());
+    }
+
+    let addrs: Vec<std::net::SocketAddr> = tokio::net::lookup_host((host.as_str(), port))
+        .await
+        .map_err(|e| format!("无法解析主机 {}：{}", host, e))?
+        .collect();
+    if addrs.is_empty() {
+        return Err(format!("无法解析主机 {}", host));
+    }
+
+    for addr in &addrs {
+        if is_restricted_fetch_ip(&addr.ip()) {
+            return Err(format!("拒绝访问内网或本机地址：{}", addr.ip()));
+        }
+    }
+
+    Ok(addrs)
+}
+
+/// 判断是否为禁止访问的地址（内网、回环、链路本地等）
+fn is_restricted_fetch_ip(ip: &std::net::IpAddr) -> bool {
+    match ip {
+        std::net::IpAddr::V4(v4) => {
+            let octets = v4.octets();
+            v4.is_private()
+                || v4.is_loopback()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                || v4.is_multicast()
+                // 100.64.0.0/10 运营商级 NAT
+                || (octets[0] == 100 && (octets[1] & 0xc0) == 64)
+        }
+        std::net::IpAddr::V6(v6) => {
+            let first = v6.segments()[0];
+            v6.is_loopback()
+                || v6.is_unspecified()
+                || v6.is_multicast()
+                // fc00::/7 唯一本地地址
+                || (first & 0xfe00) == 0xfc00
+                // fe80::/10 链路本地地址
+                || (first & 0xffc0) == 0xfe80
+                || v6
+                    .to_ipv4_mapped()
+                    .map(|v4| is_restricted_fetch_ip(&std::net::IpAddr::V4(v4)))
+                    .unwrap_or(false)
+        }
+    }
+}
+
+/// 从 Content-Disposition 中解析文件名
+///
+/// 优先使用 RFC 5987 编码的 `filename*`，其次使用 `filename`
+fn parse_content_disposition_filename(value: &str) -> Option<String> {
+    let mut plain_name = None;
+
+    for part in value.split(';').map(str::trim) {
+        if let Some(encoded) = part.strip_prefix("filename*=") {
+            let encoded = encoded.trim_matches('"');
+            let raw = encoded.splitn(3, '\'').nth(2).unwrap_or(encoded);
+            if let Ok(decoded) = urlencoding::decode(raw) {
+                if let Some(name) = sanitize_fetched_filename(&decoded) {
+                    return Some(name);
+                }
+            }
+        } else if let Some(name) = part.strip_prefix("filename=") {
+            plain_name = sanitize_fetched_filename(name.trim_matches('"'));
+        }
+    }
+
+    plain_name
+}
+
+/// 从 URL 路径的最后一段推断文件名
+fn filename_from_url(url: &reqwest::Url) -> Option<String> {
+    let segment = url.path_segments()?.rev().find(|s| !s.is_empty())?;
+    let decoded = urlencoding::decode(segment).ok()?;
+    sanitize_fetched_filename(&decoded)
+}
+
+/// 清理文件名，去除路径成分
+fn sanitize_fetched_filename(name: &str) -> Option<String> {
+    let name = name.rsplit(['/', '\\']).next().unwrap_or("").trim();
+    if name.is_empty() || name == "." || name == ".." {
+        None
+    } else {
+        Some(name.to_string())
+    }
+}
+
+// ============ 文件名检查命令 ============
+
+/// 发送前检查文件名
+///
+/// 对端平台未知，按最严格的 Windows 规则检查，返回问题列表与对端可能使用的安全文件名
+#[tauri::command]
+pub async fn validate_filename(
+    file_name: String,
+) -> Result<crate::transfer::FilenameValidation, String> {
+    Ok(crate::transfer::inspect_filename(
+        &file_name,
+        crate::transfer::FilenamePlatform::Windows,
+    ))
+}
+
+// ============ 接收设置相关命令 ============
+
+/// 接收设置
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceiveSettings {
+    /// 是否自动接收
+    pub auto_receive: bool,
+    /// 是否覆盖同名文件
+    pub file_overwrite: bool,
+    /// 接收端完整性校验模式
+    #[serde(default)]
+    pub verify_mode: crate::transfer::VerifyMode,
+    /// 自动接收的文件大小上限（字节），为空时仅由 `auto_receive` 决定
+    ///
+    /// 尚未生效：接收处理落地前不读取该值，界面也不提供该设置
+    #[serde(default)]
+    pub max_auto_accept_size: Option<u64>,
+}
+
+impl Default for ReceiveSettings {
+    fn default() -> Self {
+        Self {
+            auto_receive: false,
+            file_overwrite: false,
+            verify_mode: crate::transfer::VerifyMode::default(),
+            max_auto_accept_size: None,
+        }
+    }
+}
+
+/// 接收设置状态（随后端设置一起持久化）
+static RECEIVE_SETTINGS: std::sync::OnceLock<std::sync::RwLock<ReceiveSettings>> =
+    std::sync::OnceLock::new();
+
+fn get_receive_settings_lock() -> &'static std::sync::RwLock<ReceiveSettings> {
+    RECEIVE_SETTINGS.get_or_init(|| std::sync::RwLock::new(ReceiveSettings::default()))
+}
+
+/// 获取接收设置
+#[tauri::command]
+pub async fn get_receive_settings() -> Result<ReceiveSettings, String> {
+    let settings = get_receive_settings_lock()
+        .read()
+        .map_err(|e| e.to_string())?;
+    Ok(settings.clone())
+}
+
+/// 设置自动接收
+#[tauri::command]
+pub async fn set_auto_receive(app: AppHandle, enabled: bool) -> Result<(), String> {
+    update_settings(&app, |bundle| bundle.receive.auto_receive = enabled)?;
+    Ok(())
+}
+
+/// 设置文件覆盖
+#[tauri::command]
+pub async fn set_file_overwrite(app: AppHandle, enabled: bool) -> Result<(), String> {
+    update_settings(&app, |bundle| bundle.receive.file_overwrite = enabled)?;
+    Ok(())
+}
+
+/// 设置自动接收的文件大小上限
+///
+/// 未开启自动接收时，不超过该大小的文件无需确认即接收，更大的文件仍需确认；为空时关闭。
+/// 尚未生效，仅保存设置，前端不调用
+#[tauri::command]
+pub async fn set_max_auto_accept_size(app: AppHandle, size: Option<u64>) -> Result<(), String> {
+    update_settings(&app, |bundle| bundle.receive.max_auto_accept_size = size)?;
+    Ok(())
+}
+
+/// 设置接收端校验模式
+///
+/// 支持 off、per_chunk、full_file、both；关闭校验可减少大文件的二次读盘
+#[tauri::command]
+pub async fn set_verify_on_receive(
+    app: AppHandle,
+    mode: crate::transfer::VerifyMode,
+) -> Result<(), String> {
+    update_settings(&app, |bundle| bundle.receive.verify_mode = mode)?;
+    Ok(())
+}
+
+/// 当前接收端校验模式
+pub fn verify_on_receive_mode() -> crate::transfer::VerifyMode {
+    get_receive_settings_lock()
+        .read()
+        .map(|s| s.verify_mode)
+        .unwrap_or_default()
+}
+
+/// 当前是否覆盖同名文件
+pub fn file_overwrite_on_receive() -> bool {
+    get_receive_settings_lock()
+        .read()
+        .map(|s| s.file_overwrite)
+        .unwrap_or_default()
+}
+
+/// 默认接收目录
+fn get_default_receive_directory() -> String {
+    // Windows 系统
+    if let Ok(userprofile) = std::env::var("USERPROFILE") {
+        return PathBuf::from(userprofile)
+            .join("Downloads")
+            .join("PureSend")
+            .to_string_lossy()
+            .to_string();
+    }
+    // 尝试获取用户下载目录
+    if let Some(home) = std::env::var("HOME").ok() {
+        return PathBuf::from(home)
+            .join("Downloads")
+            .join("PureSend")
+            .to_string_lossy()
+            .to_string();
+    }
+    // 降级到当前目录
+    "./downloads".to_string()
+}
+
+/// 已设置的接收目录（未设置时使用默认目录）
+static RECEIVE_DIRECTORY: std::sync::OnceLock<std::sync::RwLock<Option<String>>> =
+    std::sync::OnceLock::new();
+
+fn get_receive_directory_lock() -> &'static std::sync::RwLock<Option<String>> {
+    RECEIVE_DIRECTORY.get_or_init(|| std::sync::RwLock::new(None))
+}
+
+/// 当前接收目录
+fn current_receive_directory() -> String {
+    get_receive_directory_lock()
+        .read()
+        .ok()
+        .and_then(|directory| directory.clone())
+        .unwrap_or_else(get_default_receive_directory)
+}
+
+/// 获取接收目录
+#[tauri::command]
+pub async fn get_receive_directory() -> Result<String, String> {
+    Ok(current_receive_directory())
+}
+
+/// 设置接收目录
+#[tauri::command]
+pub async fn set_receive_directory(app: AppHandle, directory: String) -> Result<(), String> {
+    update_settings(&app, |bundle| bundle.receive_directory = directory)?;
+    Ok(())
+}
+
+/// 确保接收目录存在且可写
+fn ensure_receive_directory(directory: &str) -> Result<(), String> {
+    // 验证目录是否存在，不存在则创建
+    let path = PathBuf::from(directory);
+    if !path.exists() {
+        std::fs::create_dir_all(&path)
+            .map_err(|e| format!("无法创建接收目录 '{}': {}", directory, e))?;
+    }
+
+    // 验证目录是否可写
+    let test_file = path.join(".write_test");
+    if std::fs::File::create(&test_file).is_err() {
+        return Err(format!("接收目录 '{}' 不可写", directory));
+    }
+    // 删除测试文件
+    let _ = std::fs::remove_file(&test_file);
+
+    Ok(())
+}
+
+/// 校验接收目录路径，不创建目录
+fn validate_receive_directory(directory: &str) -> Result<(), String> {
+    if directory.trim().is_empty() {
+        return Err("接收目录不能为空".to_string());
+    }
+    let path = std::path::Path::new(directory);
+    if path.exists() && !path.is_dir() {
+        return Err(format!("接收目录 '{}' 不是文件夹", directory));
+    }
+    Ok(())
+}
+
+/// 获取接收目录的存储能力（可用空间与可写性）
+#[tauri::command]
+pub async fn get_receive_capacity(
+    directory: Option<String>,
+) -> Result<crate::transfer::local::ReceiveCapacity, String> {
+    let directory = PathBuf::from(directory.unwrap_or_else(current_receive_directory));
+    tokio::task::spawn_blocking(move || crate::transfer::local::ReceiveCapacity::probe(&directory))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// ============ 临时目录相关命令 ============
+
+/// 自定义临时目录（为空时沿用默认位置）
+static TEMP_DIRECTORY: std::sync::OnceLock<std::sync::RwLock<Option<PathBuf>>> =
+    std::sync::OnceLock::new();
+
+fn get_temp_directory_lock() -> &'static std::sync::RwLock<Option<PathBuf>> {
+    TEMP_DIRECTORY.get_or_init(|| std::sync::RwLock::new(None))
+}
+
+/// 获取自定义临时目录
+///
+/// 网页上传分块目录在未设置时位于接收目录下
+pub fn custom_temp_directory() -> Option<PathBuf> {
+    get_temp_directory_lock()
+        .read()
+        .ok()
+        .and_then(|dir| dir.clone())
+}
+
+/// 应用临时文件根目录：自定义目录，否则为系统临时目录下的 puresend
+pub fn app_temp_directory() -> PathBuf {
+    custom_temp_directory().unwrap_or_else(|| std::env::temp_dir().join("puresend"))
+}
+
+/// 临时目录设置结果
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TempDirectoryInfo {
+    /// 自定义临时目录（为空表示使用默认位置）
+    pub directory: Option<String>,
+    /// 是否与接收目录位于同一卷（无法判断时为空）
+    pub same_volume: Option<bool>,
+    /// 提示信息（如跨卷移动需要复制）
+    pub warning: Option<String>,
+}
+
+/// 判断两个路径是否位于同一卷，无法判断时返回 None
+#[cfg(unix)]
+fn is_same_volume(a: &std::path::Path, b: &std::path::Path) -> Option<bool> {
+    use std::os::unix::fs::MetadataExt;
+    let a = std::fs::metadata(a).ok()?;
+    let b = std::fs::metadata(b).ok()?;
+    Some(a.dev() == b.dev())
+}
+
+/// 判断两个路径是否位于同一卷（比较盘符），无法判断时返回 None
+#[cfg(windows)]
+fn is_same_volume(a: &std::path::Path, b: &std::path::Path) -> Option<bool> {
+    let a = a.canonicalize().ok()?;
+    let b = b.canonicalize().ok()?;
+    Some(a.components().next() == b.components().next())
+}
+
+#[cfg(not(any(unix, windows)))]
+fn is_same_volume(_a: &std::path::Path, _b: &std::path::Path) -> Option<bool> {
+    None
+}
+
+/// 获取自定义临时目录
+#[tauri::command]
+pub async fn get_temp_directory() -> Result<Option<String>, String> {
+    Ok(custom_temp_directory().map(|dir| dir.to_string_lossy().to_string()))
+}
+
+/// 设置临时目录
+///
+/// 用于网页上传分块和远程资源下载等临时文件，传空恢复默认位置。
+/// 与接收目录不在同一卷时返回提示：完成后的移动将退化为复制
+#[tauri::command]
+pub async fn set_temp_directory(
+    directory: Option<String>,
+    receive_directory: Option<String>,
+) -> Result<TempDirectoryInfo, String> {
+    let directory = directory.filter(|d| !d.trim().is_empty());
+
+    let Some(directory) = directory else {
+        *get_temp_directory_lock()
+            .write()
+            .map_err(|e| e.to_string())? = None;
+        return Ok(TempDirectoryInfo {
+            directory: None,
+            same_volume: None,
+            warning: None,
+        });
+    };
+
+    let path = PathBuf::from(&directory);
+    if !path.exists() {
+        std::fs::create_dir_all(&path)
+            .map_err(|e| format!("无法创建临时目录 '{}': {}", directory, e))?;
+    }
+
+    // 验证目录是否可写
+    let test_file = path.join(".write_test");
+    if std::fs::File::create(&test_file).is_err() {
+        return Err(format!("临时目录 '{}' 不可写", directory));
+    }
+    let _ = std::fs::remove_file(&test_file);
+
+    let receive_dir = PathBuf::from(receive_directory.unwrap_or_else(current_receive_directory));
+    let same_volume = is_same_volume(&path, &receive_dir);
+    let warning = (same_volume == Some(false))
+        .then(|| "临时目录与接收目录不在同一磁盘，文件完成后需要复制，可能较慢".to_string());
+
+    *get_temp_directory_lock()
+        .write()
+        .map_err(|e| e.to_string())? = Some(path);
+
+    Ok(TempDirectoryInfo {
+        directory: Some(directory),
+        same_volume,
+        warning,
+    })
+}
+
+// ============ 已接收文件管理命令 ============
+
+/// 已接收文件信息
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceivedFileInfo {
+    /// 文件路径
+    pub path: String,
+    /// 文件名
+    pub name: String,
+    /// 相对接收目录的路径
+    pub relative_path: String,
+    /// 文件大小
+    pub size: u64,
+    /// 修改时间戳（毫秒）
+    pub modified_at: u64,
+    /// MIME 类型
+    pub mime_type: String,
+}
+
+/// 已接收文件列表
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceivedFileList {
+    /// 接收目录
+    pub directory: String,
+    /// 文件列表（按修改时间倒序）
+    pub files: Vec<ReceivedFileInfo>,
+    /// 文件总大小（字节）
+    pub total_size: u64,
+}
+
+/// 递归收集已接收文件，跳过隐藏文件和目录（如上传临时目录）
+fn collect_received_files(
+    current_dir: &std::path::Path,
+    base_dir: &std::path::Path,
+    files: &mut Vec<ReceivedFileInfo>,
+) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(current_dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') {
+            continue;
+        }
+
+        let path = entry.path();
+        let metadata = entry.metadata()?;
+        if metadata.is_dir() {
+            collect_received_files(&path, base_dir, files)?;
+        } else if metadata.is_file() {
+            let modified_at = metadata
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0);
+            files.push(ReceivedFileInfo {
+                path: path.to_string_lossy().to_string(),
+                relative_path: path
+                    .strip_prefix(base_dir)
+                    .unwrap_or(&path)
+                    .to_string_lossy()
+                    .to_string(),
+                mime_type: FileMetadata::infer_mime_type(&name),
+                name,
+                size: metadata.len(),
+                modified_at,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// 列出接收目录中的文件
+///
+/// 未指定目录时使用默认接收目录，目录不存在时返回空列表
+#[tauri::command]
+pub async fn list_received_files(directory: Option<String>) -> Result<ReceivedFileList, String> {
+    let directory = directory.unwrap_or_else(current_receive_directory);
+    let dir = PathBuf::from(&directory);
+
+    let mut files = if dir.is_dir() {
+        tokio::task::spawn_blocking(move || {
+            let mut files = Vec::new();
+            collect_received_files(&dir, &dir, &mut files).map(|_| files)
+        })
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("读取接收目录失败：{}", e))?
+    } else {
+        Vec::new()
+    };
+
+    files.sort_by(|a, b| b.modified_at.cmp(&a.modified_at));
+    let total_size = files.iter().map(|f| f.size).sum();
+
+    Ok(ReceivedFileList {
+        directory,
+        files,
+        total_size,
+    })
+}
+
+/// 删除接收目录中的文件
+///
+/// 仅允许删除位于当前接收目录内的普通文件（防止路径遍历删除任意文件）
+#[tauri::command]
+pub async fn delete_received_file(path: String) -> Result<(), String> {
+    let directory = current_receive_directory();
+    let canonical_path = resolve_received_file(Path::new(&directory), Path::new(&path))?;
+
+    tokio::fs::remove_file(&canonical_path)
+        .await
+        .map_err(|e| format!("删除文件失败：{}", e))
+}
+
+/// 规范化路径并校验其为接收目录内的普通文件
+///
+/// 两侧都先解析 `..` 和符号链接，指向目录外的链接与遍历路径一并拒绝
+fn resolve_received_file(directory: &Path, path: &Path) -> Result<PathBuf, String> {
+    let canonical_dir = directory
+        .canonicalize()
+        .map_err(|e| format!("路径验证失败：{}", e))?;
+    let canonical_path = path
+        .canonicalize()
+        .map_err(|e| format!("文件不存在：{}", e))?;
+
+    if canonical_path == canonical_dir || !canonical_path.starts_with(&canonical_dir) {
+        return Err("只能删除接收目录中的文件".to_string());
+    }
+    if !canonical_path.is_file() {
+        return Err(format!("路径不是文件：{}", path.display()));
+    }
+    Ok(canonical_path)
+}
+
+// ============ 文件重命名工具函数 ============
+
+/// 生成不冲突的文件名
+///
+/// 如果目标文件已存在，自动生成新文件名（如 `file.txt` -> `file (1).txt`）
+/// 支持边界情况：
+/// - 多次重命名（file (1).txt -> file (2).txt）
+/// - 无扩展名文件
+/// - 多个扩展名（如 .tar.gz）
+/// - 隐藏文件（如 .gitignore）
+pub fn generate_unique_filename(
+    directory: &PathBuf,
+    original_name: &str,
+) -> Result<String, String> {
+    let path = directory.join(original_name);
+
+    // 如果文件不存在，直接使用原文件名
+    if !path.exists() {
+        return Ok(original_name.to_string());
+    }
+
+    // 解析文件名和扩展名
+    let (stem, extension) = parse_filename(original_name);
+
+    // 尝试找到可用的文件名
+    let mut counter = 1u32;
+    loop {
+        let new_name = if extension.is_empty() {
+            format!("{} ({})", stem, counter)
+        } else {
+            format!("{} ({}).{}", stem, counter, extension)
+        };
+
+        let new_path = directory.join(&new_name);
+        if !new_path.exists() {
+            return Ok(new_name);
+        }
+
+        counter += 1;
+
+        // 防止无限循环（最多尝试 10000 次）
+        if counter > 10000 {
+            return Err(format!("无法生成唯一文件名：{}", original_name));
+        }
+    }
+}
+
+/// 解析文件名为（主文件名，扩展名）
+///
+/// 特殊处理：
+/// - `.gitignore` -> (".gitignore", "")
+/// - `file.tar.gz` -> ("file", "tar.gz")
+/// - `file` -> ("file", "")
+pub fn parse_filename(filename: &str) -> (String, String) {
+    // 特殊情况：以点开头的隐藏文件
+    if filename.starts_with('.') && filename.matches('.').count() == 1 {
+        return (filename.to_string(), String::new());
+    }
+
+    // 查找最后一个点
+    if let Some(dot_pos) = filename.rfind('.') {
+        let stem = &filename[..dot_pos];
+        let ext = &filename[dot_pos + 1..];
+
+        // 检查是否为复合扩展名（如 .tar.gz）
+        if let Some(inner_dot) = stem.rfind('.') {
+            let inner_ext = &stem[inner_dot + 1..];
+            // 常见复合扩展名
+            const COMPOUND_EXTENSIONS: &[&str] = &["tar", "zip"];
+            if COMPOUND_EXTENSIONS.contains(&inner_ext) {
+                return (
+                    stem[..inner_dot].to_string(),
+                    format!("{}.{}", inner_ext, ext),
+                );
+            }
+        }
+
+        (stem.to_string(), ext.to_string())
+    } else {
+        (filename.to_string(), String::new())
+    }
+}
+
+/// 获取接收文件的实际保存路径
+///
+/// 根据 file_overwrite 设置决定：
+/// - true: 直接覆盖同名文件
+/// - false: 自动重命名避免冲突
+pub fn get_receive_file_path(
+    directory: &PathBuf,
+    filename: &str,
+    file_overwrite: bool,
+) -> Result<PathBuf, String> {
+    // 如果允许覆盖，直接返回原路径
+    if file_overwrite {
+        return Ok(directory.join(filename));
+    }
+
+    // 不允许覆盖，生成唯一文件名
+    let unique_name = generate_unique_filename(directory, filename)?;
+    Ok(directory.join(unique_name))
+}
+
+/// 目录自身或最近的已存在上级目录（目录可能尚未创建）
+pub fn nearest_existing_dir(dir: &Path) -> &Path {
+    dir.ancestors().find(|dir| dir.exists()).unwrap_or(dir)
+}
+
+/// 目录所在磁盘的可用空间（字节），无法读取时返回 None
+///
+/// 目录尚未创建时检测最近的已存在上级目录，不会创建任何目录
+pub fn available_space(dir: &Path) -> Option<u64> {
+    fs2::available_space(nearest_existing_dir(dir)).ok()
+}
+
+/// 用已写完并校验通过的临时文件替换目标文件
+///
+/// 优先原子重命名；跨卷时先复制到目标目录下的临时文件再重命名，
+/// 保证目标文件在任何时刻要么是旧内容、要么是完整的新内容。失败时清理临时文件
+pub fn commit_received_file(temp_path: &Path, target: &Path) -> std::io::Result<()> {
+    if std::fs::rename(temp_path, target).is_ok() {
+        return Ok(());
+    }
+
+    let file_name = target
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let staging_name = format!(".{}.{}.tmp", file_name, uuid::Uuid::new_v4());
+    let staging_path = target.with_file_name(staging_name);
+    let result = std::fs::copy(temp_path, &staging_path)
+        .and_then(|_| std::fs::rename(&staging_path, target));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&staging_path);
+    }
+    let _ = std::fs::remove_file(temp_path);
+    result
+}
+
+/// 获取接收文件的实际保存路径（Tauri 命令版本）
+#[tauri::command]
+pub async fn get_unique_file_path(
+    directory: String,
+    filename: String,
+    file_overwrite: bool,
+) -> Result<String, String> {
+    let dir_path = PathBuf::from(&directory);
+
+    // 确保目录存在
+    if !dir_path.exists() {
+        std::fs::create_dir_all(&dir_path)
+            .map_err(|e| format!("无法创建目录 '{}': {}", directory, e))?;
+    }
+
+    let result_path = get_receive_file_path(&dir_path, &filename, file_overwrite)?;
+    Ok(result_path.to_string_lossy().to_string())
+}
+
+// ============ 加密设置相关命令 ============
+
+/// 获取加密是否启用
+#[tauri::command]
+pub async fn get_encryption_enabled() -> Result<bool, String> {
+    Ok(crate::transfer::crypto::is_encryption_enabled())
+}
+
+/// 设置加密启用状态
+#[tauri::command]
+pub async fn set_encryption_enabled(app: AppHandle, enabled: bool) -> Result<(), String> {
+    update_settings(&app, |bundle| bundle.encryption_enabled = enabled)?;
+    Ok(())
+}
+
+/// 获取首选加密算法（"auto" 表示按平台自动选择）
+#[tauri::command]
+pub async fn get_preferred_cipher() -> Result<String, String> {
+    Ok(crate::transfer::crypto::get_preferred_cipher_setting()
+        .map(|c| c.as_str().to_string())
+        .unwrap_or_else(|| "auto".to_string()))
+}
+
+/// 设置首选加密算法
+///
+/// 支持 auto、aes-256-gcm、chacha20-poly1305；实际算法在握手时与对方协商
+#[tauri::command]
+pub async fn set_preferred_cipher(app: AppHandle, cipher: String) -> Result<(), String> {
+    update_settings(&app, |bundle| bundle.preferred_cipher = cipher)?;
+    Ok(())
+}
+
+/// 解析首选加密算法，"auto" 为 None
+fn parse_preferred_cipher(
+    cipher: &str,
+) -> Result<Option<crate::transfer::crypto::CipherSuite>, String> {
+    if cipher == "auto" {
+        return Ok(None);
+    }
+    crate::transfer::crypto::CipherSuite::from_name(cipher)
+        .map(Some)
+        .ok_or_else(|| {
+            format!(
+                "无效的加密算法: {}，支持 auto、aes-256-gcm 或 chacha20-poly1305",
+                cipher
+            )
+        })
+}
 
-    // 设置接收配置
-    use crate::transfer::local::ReceiveConfig;
-    let receive_config = ReceiveConfig {
-        auto_receive: current_settings.auto_receive,
-        file_overwrite: current_settings.file_overwrite,
-        receive_directory: std::env::temp_dir(), // 使用临时目录作为默认接收目录
-    };
-    transport.set_receive_config(receive_config).await;
+// ============ 发送重试相关命令 ============
 
-    // 获取监听端口
-    let listen_port = transport
-        .get_listen_port()
-        .await
-        .map_err(|e| e.to_string())?;
+/// 默认最大重试次数
+const DEFAULT_TRANSFER_MAX_RETRIES: u32 = 3;
 
-    // 获取本地所有 IP 地址
-    let network_addresses = crate::network::get_local_ips();
+/// 最大重试次数上限
+const MAX_TRANSFER_RETRIES: u32 = 10;
 
-    // 生成分享码（6 位数字，基于端口和时间戳）
-    let share_code = {
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_millis() as u32;
-        format!("{:06}", (listen_port as u32 + timestamp) % 1000000)
-    };
+/// 默认首次重试前的等待时间（毫秒），依次等待 1s、2s、4s
+const DEFAULT_RETRY_BASE_DELAY_MS: u64 = 1000;
 
-    // 保存传输实例
-    {
-        let mut local_transport = state.local_transport.lock().await;
-        *local_transport = Some(transport);
+/// 整体发送重试策略
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferRetryPolicy {
+    /// 最大重试次数，0 表示失败后立即结束
+    pub attempts: u32,
+    /// 首次重试前的等待时间（毫秒），之后每次翻倍
+    pub base_delay_ms: u64,
+}
+
+impl Default for TransferRetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: DEFAULT_TRANSFER_MAX_RETRIES,
+            base_delay_ms: DEFAULT_RETRY_BASE_DELAY_MS,
+        }
     }
+}
 
-    // 更新接收状态并返回结果
-    let result = {
-        let mut receiving_state = state.receiving_state.lock().await;
-        receiving_state.is_receiving = true;
-        receiving_state.port = listen_port;
-        receiving_state.network_addresses = network_addresses.clone();
-        receiving_state.share_code = share_code.clone();
+/// 发送重试策略（由前端同步到后端）
+static TRANSFER_RETRY_POLICY: std::sync::OnceLock<std::sync::RwLock<TransferRetryPolicy>> =
+    std::sync::OnceLock::new();
 
-        ReceivingState {
-            is_receiving: true,
-            port: listen_port,
-            network_addresses,
-            share_code,
-            auto_receive: current_settings.auto_receive,
-            file_overwrite: current_settings.file_overwrite,
-        }
-    };
+fn get_transfer_retry_policy_lock() -> &'static std::sync::RwLock<TransferRetryPolicy> {
+    TRANSFER_RETRY_POLICY.get_or_init(|| std::sync::RwLock::new(TransferRetryPolicy::default()))
+}
 
-    Ok(result)
+/// 当前发送重试策略
+fn get_transfer_retry_policy() -> TransferRetryPolicy {
+    get_transfer_retry_policy_lock()
+        .read()
+        .map(|policy| *policy)
+        .unwrap_or_default()
 }
 
-/// 停止接收监听服务器
+/// 设置发送重试策略
+///
+/// `attempts` 为最大重试次数（0 表示不重试），`base_delay` 为首次重试前的等待时间（毫秒）
 #[tauri::command]
-pub async fn stop_receiving(state: State<'_, TransferState>) -> Result<(), String> {
-    // 检查是否有活跃任务
-    {
-        let active_tasks = state.active_tasks.lock().await;
-        let has_active_tasks = active_tasks.values().any(|t| {
-            t.status == crate::models::TaskStatus::Pending
-                || t.status == crate::models::TaskStatus::Transferring
-        });
-
-        if has_active_tasks {
-            return Err("有活跃的传输任务，无法停止接收服务".to_string());
+pub async fn set_transfer_retry_policy(
+    app: AppHandle,
+    attempts: u32,
+    base_delay: u64,
+) -> Result<(), String> {
+    update_settings(&app, |bundle| {
+        bundle.retry_policy = TransferRetryPolicy {
+            attempts,
+            base_delay_ms: base_delay,
         }
-    }
+    })?;
+    Ok(())
+}
 
-    // 关闭传输服务
-    {
-        let mut local_transport = state.local_transport.lock().await;
-        if let Some(transport) = local_transport.take() {
-            transport.shutdown().await.map_err(|e| e.to_string())?;
+/// 设置最大重试次数，沿用当前的重试间隔
+///
+/// 只有网络类的临时错误会重试，取消和校验失败不会重试；0 表示不重试，最多 10 次
+#[tauri::command]
+pub async fn set_transfer_max_retries(app: AppHandle, n: u32) -> Result<(), String> {
+    update_settings(&app, |bundle| {
+        let policy = &mut bundle.retry_policy;
+        policy.attempts = n;
+        if policy.base_delay_ms == 0 {
+            policy.base_delay_ms = DEFAULT_RETRY_BASE_DELAY_MS;
         }
-    }
+    })?;
+    Ok(())
+}
 
-    // 重置接收状态
-    {
-        let mut receiving_state = state.receiving_state.lock().await;
-        receiving_state.is_receiving = false;
-        receiving_state.port = 0;
-        receiving_state.network_addresses.clear();
-        receiving_state.share_code.clear();
+/// 校验发送重试策略并限制重试间隔上限
+fn normalize_retry_policy(policy: TransferRetryPolicy) -> Result<TransferRetryPolicy, String> {
+    if policy.attempts > MAX_TRANSFER_RETRIES {
+        return Err(format!("重试次数不能超过 {} 次", MAX_TRANSFER_RETRIES));
     }
-
-    Ok(())
+    if policy.attempts > 0 && policy.base_delay_ms == 0 {
+        return Err("重试间隔必须大于 0".to_string());
+    }
+    Ok(TransferRetryPolicy {
+        attempts: policy.attempts,
+        base_delay_ms: policy.base_delay_ms.min(MAX_RETRY_DELAY_MS),
+    })
 }
 
-/// 获取网络信息（只返回真实服务器状态）
+// ============ 压缩设置相关命令 ============
+
+/// 获取压缩是否启用
 #[tauri::command]
-pub async fn get_network_info(state: State<'_, TransferState>) -> Result<ReceivingState, String> {
-    let receiving_state = state.receiving_state.lock().await;
+pub async fn get_compression_enabled() -> Result<bool, String> {
+    let config = crate::transfer::compression::get_compression_config();
+    Ok(config.enabled)
+}
 
-    // 读取当前设置
-    let settings = get_receive_settings_lock()
-        .read()
-        .map_err(|e| e.to_string())?;
+/// 设置压缩启用状态
+#[tauri::command]
+pub async fn set_compression_enabled(app: AppHandle, enabled: bool) -> Result<(), String> {
+    update_settings(&app, |bundle| bundle.compression_enabled = enabled)?;
+    Ok(())
+}
 
-    // 只返回真实的接收状态，不生成临时信息
-    Ok(ReceivingState {
-        is_receiving: receiving_state.is_receiving,
-        port: receiving_state.port,
-        network_addresses: receiving_state.network_addresses.clone(),
-        share_code: receiving_state.share_code.clone(),
-        auto_receive: settings.auto_receive,
-        file_overwrite: settings.file_overwrite,
-    })
+/// 设置压缩模式
+#[tauri::command]
+pub async fn set_compression_mode(app: AppHandle, mode: String) -> Result<(), String> {
+    update_settings(&app, |bundle| bundle.compression_mode = mode)?;
+    Ok(())
 }
 
-/// 获取文件元数据（不计算哈希，仅获取基本信息）
+/// 设置压缩级别
 #[tauri::command]
-pub async fn get_file_metadata(file_path: String) -> Result<FileMetadata, String> {
-    let path = PathBuf::from(&file_path);
+pub async fn set_compression_level(app: AppHandle, level: i32) -> Result<(), String> {
+    update_settings(&app, |bundle| bundle.compression_level = level)?;
+    Ok(())
+}
 
-    if !path.exists() {
-        return Err(format!("文件不存在：{}", file_path));
-    }
+/// 获取压缩算法
+#[tauri::command]
+pub async fn get_compression_algorithm() -> Result<String, String> {
+    let config = crate::transfer::compression::get_compression_config();
+    Ok(config.algorithm.as_str().to_string())
+}
 
-    let file_name = path
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("unknown")
-        .to_string();
+/// 设置压缩算法（zstd、lz4 或 gzip）
+#[tauri::command]
+pub async fn set_compression_algorithm(app: AppHandle, algorithm: String) -> Result<(), String> {
+    let algorithm = parse_compression_algorithm(&algorithm)?;
+    update_settings(&app, |bundle| bundle.compression_algorithm = algorithm)?;
+    Ok(())
+}
 
-    let metadata = std::fs::metadata(&path).map_err(|e| e.to_string())?;
-    let mime_type = FileMetadata::infer_mime_type(&file_name);
+// ============ 哈希算法设置相关命令 ============
 
-    Ok(FileMetadata::new(file_name, metadata.len(), mime_type))
+/// 获取准备发送文件时使用的哈希算法
+#[tauri::command]
+pub async fn get_transfer_hash_algorithm() -> Result<String, String> {
+    Ok(crate::transfer::transfer_hash_algorithm()
+        .as_str()
+        .to_string())
 }
 
-/// 文件信息
-#[derive(Debug, Clone, serde::Serialize)]
-pub struct FileInfo {
-    /// 文件路径
-    pub path: String,
-    /// 文件名
-    pub name: String,
-    /// 文件大小
-    pub size: u64,
-    /// 相对路径
-    pub relative_path: String,
+/// 设置准备发送文件时使用的哈希算法（sha256、sha512 或 blake3）
+///
+/// 对端不支持所选算法时，发送前按 SHA-256 重新计算哈希
+#[tauri::command]
+pub async fn set_transfer_hash_algorithm(algorithm: String) -> Result<(), String> {
+    let algorithm = HashAlgorithm::from_name(&algorithm).ok_or_else(|| {
+        format!(
+            "无效的哈希算法: {}，支持 sha256、sha512 或 blake3",
+            algorithm
+        )
+    })?;
+    crate::transfer::set_transfer_hash_algorithm_internal(algorithm);
+    Ok(())
 }
 
-/// 递归获取文件夹下的所有文件
+// ============ 分块大小设置相关命令 ============
+
+/// 获取本地传输的分块大小（字节）
 #[tauri::command]
-pub async fn get_files_in_folder(folder_path: String) -> Result<Vec<FileInfo>, String> {
-    let folder = PathBuf::from(&folder_path);
+pub async fn get_transfer_chunk_size() -> Result<u64, String> {
+    Ok(crate::transfer::transfer_chunk_size())
+}
 
-    if !folder.exists() {
-        return Err(format!("文件夹不存在：{}", folder_path));
+/// 设置本地传输的分块大小（64KB–16MB）
+///
+/// 只影响之后准备的任务，进行中的任务沿用开始时的分块布局
+#[tauri::command]
+pub async fn set_transfer_chunk_size(
+    state: State<'_, TransferState>,
+    bytes: usize,
+) -> Result<(), String> {
+    let bytes = bytes as u64;
+    validate_chunk_size(bytes)?;
+    crate::transfer::set_transfer_chunk_size_internal(bytes);
+    if let Some(transport) = state.local_transport.lock().await.as_mut() {
+        transport.set_chunk_size(bytes);
     }
+    Ok(())
+}
 
-    if !folder.is_dir() {
-        return Err(format!("路径不是文件夹：{}", folder_path));
+fn validate_chunk_size(bytes: u64) -> Result<(), String> {
+    if !(crate::transfer::MIN_CHUNK_SIZE..=crate::transfer::MAX_CHUNK_SIZE).contains(&bytes) {
+        return Err(format!(
+            "无效的分块大小: {}，支持 {} 到 {} 字节",
+            bytes,
+            crate::transfer::MIN_CHUNK_SIZE,
+            crate::transfer::MAX_CHUNK_SIZE
+        ));
     }
+    Ok(())
+}
 
-    // 验证路径合法性（防止路径遍历攻击）
-    let canonical_folder = folder
-        .canonicalize()
-        .map_err(|e| format!("路径验证失败：{}", e))?;
-
-    let mut files = Vec::new();
-    collect_files_recursive(&canonical_folder, &canonical_folder, &mut files)
-        .map_err(|e| e.to_string())?;
+// ============ 发送限速相关命令 ============
 
-    Ok(files)
+/// 获取局域网发送限速（字节/秒），None 表示不限速
+#[tauri::command]
+pub async fn get_transfer_speed_limit() -> Result<Option<u64>, String> {
+    Ok(crate::transfer::transfer_speed_limit())
 }
 
-/// 递归收集文件
-fn collect_files_recursive(
-    current_dir: &PathBuf,
-    base_dir: &PathBuf,
-    files: &mut Vec<FileInfo>,
-) -> std::io::Result<()> {
-    for entry in std::fs::read_dir(current_dir)? {
-        let entry = entry?;
-        let path = entry.path();
+/// 设置局域网发送限速（字节/秒）
+///
+/// 限速由所有发送任务共享，同时向多个设备发送时分摊总速度；None 或 0 表示不限速，
+/// 修改对进行中的任务立即生效
+#[tauri::command]
+pub async fn set_transfer_speed_limit(bytes_per_sec: Option<u64>) -> Result<(), String> {
+    crate::transfer::set_transfer_speed_limit_internal(bytes_per_sec);
+    Ok(())
+}
 
-        // 计算相对路径
-        let relative_path = path
-            .strip_prefix(base_dir)
-            .unwrap_or(&path)
-            .to_string_lossy()
-            .to_string();
+fn validate_compression_mode(mode: &str) -> Result<(), String> {
+    if mode != "smart" && mode != "manual" {
+        return Err(format!("无效的压缩模式: {}，支持 smart 或 manual", mode));
+    }
+    Ok(())
+}
 
-        if path.is_dir() {
-            // 递归处理子目录
-            collect_files_recursive(&path, base_dir, files)?;
-        } else if path.is_file() {
-            // 添加文件信息
-            let name = path
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("unknown")
-                .to_string();
+fn parse_compression_algorithm(
+    name: &str,
+) -> Result<crate::transfer::compression::CompressionAlgorithm, String> {
+    crate::transfer::compression::CompressionAlgorithm::from_name(name)
+        .ok_or_else(|| format!("无效的压缩算法: {}，支持 zstd、lz4 或 gzip", name))
+}
 
-            let metadata = std::fs::metadata(&path)?;
-            files.push(FileInfo {
-                path: path.to_string_lossy().to_string(),
-                name,
-                size: metadata.len(),
-                relative_path,
-            });
-        }
+fn validate_compression_level(level: i32) -> Result<(), String> {
+    if !(1..=19).contains(&level) {
+        return Err(format!("无效的压缩级别: {}，范围为 1-19", level));
     }
+    Ok(())
+}
+
+/// 获取各 MIME 大类的压缩统计
+#[tauri::command]
+pub async fn get_compression_stats(
+) -> Result<Vec<crate::transfer::compression::CompressionCategoryStats>, String> {
+    Ok(crate::transfer::compression::compression_stats())
+}
 
+/// 重置压缩统计
+#[tauri::command]
+pub async fn reset_compression_stats() -> Result<(), String> {
+    crate::transfer::compression::reset_compression_stats();
     Ok(())
 }
 
-// ============ 接收设置相关命令 ============
+// ============ 反向代理相关命令 ============
 
-/// 接收设置
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct ReceiveSettings {
-    /// 是否自动接收
-    pub auto_receive: bool,
-    /// 是否覆盖同名文件
-    pub file_overwrite: bool,
+/// 获取是否信任反向代理转发的客户端 IP
+#[tauri::command]
+pub async fn get_trusted_proxy_enabled() -> Result<bool, String> {
+    Ok(crate::http_common::is_trusted_proxy_enabled())
 }
 
-impl Default for ReceiveSettings {
-    fn default() -> Self {
-        Self {
-            auto_receive: false,
-            file_overwrite: false,
-        }
-    }
+/// 设置是否信任 X-Forwarded-For / X-Real-IP
+///
+/// 仅在分享/上传服务部署于反向代理之后时开启，否则客户端可伪造来源 IP
+#[tauri::command]
+pub async fn set_trusted_proxy_enabled(app: AppHandle, enabled: bool) -> Result<(), String> {
+    update_settings(&app, |bundle| bundle.trusted_proxy_enabled = enabled)?;
+    Ok(())
 }
 
-/// 接收设置状态（由前端 Tauri Store 管理，后端仅读取）
-static RECEIVE_SETTINGS: std::sync::OnceLock<std::sync::RwLock<ReceiveSettings>> =
-    std::sync::OnceLock::new();
+// ============ 修改时间保留相关命令 ============
 
-fn get_receive_settings_lock() -> &'static std::sync::RwLock<ReceiveSettings> {
-    RECEIVE_SETTINGS.get_or_init(|| std::sync::RwLock::new(ReceiveSettings::default()))
+/// 获取接收文件时是否保留来源的修改时间
+#[tauri::command]
+pub async fn get_preserve_timestamps() -> Result<bool, String> {
+    Ok(crate::transfer::timestamps::is_preserve_timestamps_enabled())
 }
 
-/// 获取接收设置
+/// 设置接收文件时是否保留来源的修改时间
+///
+/// 对云盘接收和网页上传生效（浏览器提供了文件修改时间时）
 #[tauri::command]
-pub async fn get_receive_settings() -> Result<ReceiveSettings, String> {
-    let settings = get_receive_settings_lock()
-        .read()
-        .map_err(|e| e.to_string())?;
-    Ok(settings.clone())
+pub async fn set_preserve_timestamps(app: AppHandle, enabled: bool) -> Result<(), String> {
+    update_settings(&app, |bundle| bundle.preserve_timestamps = enabled)?;
+    Ok(())
 }
 
-/// 设置自动接收
+// ============ 网页服务监听地址相关命令 ============
+
+/// 获取分享/上传服务监听的网卡地址，None 表示监听所有网卡
 #[tauri::command]
-pub async fn set_auto_receive(enabled: bool) -> Result<(), String> {
-    let mut settings = get_receive_settings_lock()
-        .write()
-        .map_err(|e| e.to_string())?;
-    settings.auto_receive = enabled;
-    Ok(())
+pub async fn get_http_bind_address() -> Result<Option<String>, String> {
+    Ok(crate::http_common::http_bind_address().map(|ip| ip.to_string()))
 }
 
-/// 设置文件覆盖
+/// 设置分享/上传服务监听的网卡地址
+///
+/// 为空或未指定地址（0.0.0.0 / ::）时监听所有网卡；指定的地址必须属于本机。
+/// 修改在下次启动分享或上传服务时生效，返回规范化后的地址
 #[tauri::command]
-pub async fn set_file_overwrite(enabled: bool) -> Result<(), String> {
-    let mut settings = get_receive_settings_lock()
-        .write()
-        .map_err(|e| e.to_string())?;
-    settings.file_overwrite = enabled;
-    Ok(())
+pub async fn set_http_bind_address(
+    app: AppHandle,
+    address: Option<String>,
+) -> Result<Option<String>, String> {
+    let applied = update_settings(&app, |bundle| bundle.http_bind_address = address)?;
+    Ok(applied.http_bind_address)
 }
 
-/// 默认接收目录
-fn get_default_receive_directory() -> String {
-    // Windows 系统
-    if let Ok(userprofile) = std::env::var("USERPROFILE") {
-        return PathBuf::from(userprofile)
-            .join("Downloads")
-            .join("PureSend")
-            .to_string_lossy()
-            .to_string();
-    }
-    // 尝试获取用户下载目录
-    if let Some(home) = std::env::var("HOME").ok() {
-        return PathBuf::from(home)
-            .join("Downloads")
-            .join("PureSend")
-            .to_string_lossy()
-            .to_string();
+/// 解析监听地址，为空或未指定地址时为 None，不是本机网卡地址时返回错误
+pub(crate) fn parse_http_bind_address(
+    address: Option<&str>,
+) -> Result<Option<std::net::IpAddr>, String> {
+    let Some(text) = address.map(str::trim).filter(|a| !a.is_empty()) else {
+        return Ok(None);
+    };
+    let ip: std::net::IpAddr = text
+        .parse()
+        .map_err(|_| format!("无效的 IP 地址：{}", text))?;
+    if ip.is_unspecified() {
+        Ok(None)
+    } else if crate::network::is_local_address(ip) {
+        Ok(Some(ip))
+    } else {
+        Err(format!("{} 不是本机网卡的地址", ip))
     }
-    // 降级到当前目录
-    "./downloads".to_string()
 }
 
-/// 获取接收目录
+// ============ 空闲自动停止相关命令 ============
+
+/// 获取空闲超时（分钟），0 表示关闭
 #[tauri::command]
-pub async fn get_receive_directory() -> Result<String, String> {
-    // 返回默认接收目录
-    Ok(get_default_receive_directory())
+pub async fn get_idle_timeout() -> Result<u32, String> {
+    Ok(crate::idle::idle_timeout_minutes())
 }
 
-/// 设置接收目录
+/// 设置空闲超时（分钟）
+///
+/// 分享、网页上传或接收服务在超时时间内没有任何客户端交互时自动停止，0 表示关闭
 #[tauri::command]
-pub async fn set_receive_directory(directory: String) -> Result<(), String> {
-    // 验证目录是否存在，不存在则创建
-    let path = PathBuf::from(&directory);
-    if !path.exists() {
-        std::fs::create_dir_all(&path)
-            .map_err(|e| format!("无法创建接收目录 '{}': {}", directory, e))?;
-    }
+pub async fn set_idle_timeout(app: AppHandle, minutes: u32) -> Result<(), String> {
+    update_settings(&app, |bundle| bundle.idle_timeout_minutes = minutes)?;
+    Ok(())
+}
 
-    // 验证目录是否可写
-    let test_file = path.join(".write_test");
-    if std::fs::File::create(&test_file).is_err() {
-        return Err(format!("接收目录 '{}' 不可写", directory));
+fn validate_idle_timeout(minutes: u32) -> Result<(), String> {
+    if minutes > crate::idle::MAX_IDLE_TIMEOUT_MINUTES {
+        return Err(format!(
+            "空闲超时不能超过 {} 分钟",
+            crate::idle::MAX_IDLE_TIMEOUT_MINUTES
+        ));
     }
-    // 删除测试文件
-    let _ = std::fs::remove_file(&test_file);
-
     Ok(())
 }
 
-// ============ 文件重命名工具函数 ============
+// ============ 页面品牌相关命令 ============
 
-/// 生成不冲突的文件名
-///
-/// 如果目标文件已存在，自动生成新文件名（如 `file.txt` -> `file (1).txt`）
-/// 支持边界情况：
-/// - 多次重命名（file (1).txt -> file (2).txt）
-/// - 无扩展名文件
-/// - 多个扩展名（如 .tar.gz）
-/// - 隐藏文件（如 .gitignore）
-pub fn generate_unique_filename(
-    directory: &PathBuf,
-    original_name: &str,
-) -> Result<String, String> {
-    let path = directory.join(original_name);
+/// 获取分享/上传网页的品牌设置
+#[tauri::command]
+pub async fn get_page_branding() -> Result<crate::http_common::PageBranding, String> {
+    Ok(crate::http_common::page_branding())
+}
 
-    // 如果文件不存在，直接使用原文件名
-    if !path.exists() {
-        return Ok(original_name.to_string());
-    }
+/// 设置分享/上传网页的标题、副标题和强调色
+///
+/// 空字段恢复默认外观；强调色必须是合法的 CSS 颜色
+#[tauri::command]
+pub async fn set_page_branding(
+    app: AppHandle,
+    branding: crate::http_common::PageBranding,
+) -> Result<crate::http_common::PageBranding, String> {
+    let applied = update_settings(&app, |bundle| bundle.page_branding = branding)?;
+    Ok(applied.page_branding)
+}
 
-    // 解析文件名和扩展名
-    let (stem, extension) = parse_filename(original_name);
+// ============ 统一设置相关命令 ============
 
-    // 尝试找到可用的文件名
-    let mut counter = 1u32;
-    loop {
-        let new_name = if extension.is_empty() {
-            format!("{} ({})", stem, counter)
-        } else {
-            format!("{} ({}).{}", stem, counter, extension)
-        };
+/// 后端设置持久化文件
+const SETTINGS_STORE_FILE: &str = "backend-settings.json";
 
-        let new_path = directory.join(&new_name);
-        if !new_path.exists() {
-            return Ok(new_name);
-        }
+/// 后端设置在存储中的键
+const SETTINGS_STORE_KEY: &str = "settings";
 
-        counter += 1;
+/// 后端设置集合，供前端一次读取或整体应用
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SettingsBundle {
+    /// 接收设置
+    pub receive: ReceiveSettings,
+    /// 接收目录
+    pub receive_directory: String,
+    /// 是否启用加密
+    pub encryption_enabled: bool,
+    /// 首选加密算法（"auto" 表示按平台自动选择）
+    pub preferred_cipher: String,
+    /// 是否启用压缩
+    pub compression_enabled: bool,
+    /// 压缩模式（smart 或 manual）
+    pub compression_mode: String,
+    /// 手动压缩级别（1-19）
+    pub compression_level: i32,
+    /// 压缩算法
+    pub compression_algorithm: crate::transfer::compression::CompressionAlgorithm,
+    /// 发送重试策略
+    pub retry_policy: TransferRetryPolicy,
+    /// 是否信任反向代理转发的客户端 IP
+    pub trusted_proxy_enabled: bool,
+    /// 接收文件时是否保留来源的修改时间
+    pub preserve_timestamps: bool,
+    /// 分享/上传服务监听地址，None 表示监听所有网卡
+    pub http_bind_address: Option<String>,
+    /// 空闲超时（分钟），0 表示关闭
+    pub idle_timeout_minutes: u32,
+    /// 分享/上传网页的品牌设置
+    pub page_branding: crate::http_common::PageBranding,
+}
 
-        // 防止无限循环（最多尝试 10000 次）
-        if counter > 10000 {
-            return Err(format!("无法生成唯一文件名：{}", original_name));
+impl Default for SettingsBundle {
+    fn default() -> Self {
+        let compression = crate::transfer::compression::CompressionConfig::default();
+        Self {
+            receive: ReceiveSettings::default(),
+            receive_directory: get_default_receive_directory(),
+            encryption_enabled: true,
+            preferred_cipher: "auto".to_string(),
+            compression_enabled: compression.enabled,
+            compression_mode: compression.mode,
+            compression_level: compression.level,
+            compression_algorithm: compression.algorithm,
+            retry_policy: TransferRetryPolicy::default(),
+            trusted_proxy_enabled: false,
+            preserve_timestamps: true,
+            http_bind_address: None,
+            idle_timeout_minutes: 0,
+            page_branding: crate::http_common::PageBranding::default(),
         }
     }
 }
 
-/// 解析文件名为（主文件名，扩展名）
-///
-/// 特殊处理：
-/// - `.gitignore` -> (".gitignore", "")
-/// - `file.tar.gz` -> ("file", "tar.gz")
-/// - `file` -> ("file", "")
-fn parse_filename(filename: &str) -> (String, String) {
-    // 特殊情况：以点开头的隐藏文件
-    if filename.starts_with('.') && filename.matches('.').count() == 1 {
-        return (filename.to_string(), String::new());
-    }
+/// 校验通过、可直接应用的设置
+struct ValidatedSettings {
+    /// 规范化后的设置集合
+    bundle: SettingsBundle,
+    preferred_cipher: Option<crate::transfer::crypto::CipherSuite>,
+    http_bind_address: Option<std::net::IpAddr>,
+}
 
-    // 查找最后一个点
-    if let Some(dot_pos) = filename.rfind('.') {
-        let stem = &filename[..dot_pos];
-        let ext = &filename[dot_pos + 1..];
+impl SettingsBundle {
+    /// 读取当前生效的设置
+    fn current() -> Self {
+        let compression = crate::transfer::compression::get_compression_config();
+        Self {
+            receive: get_receive_settings_lock()
+                .read()
+                .map(|s| s.clone())
+                .unwrap_or_default(),
+            receive_directory: current_receive_directory(),
+            encryption_enabled: crate::transfer::crypto::is_encryption_enabled(),
+            preferred_cipher: crate::transfer::crypto::get_preferred_cipher_setting()
+                .map(|c| c.as_str().to_string())
+                .unwrap_or_else(|| "auto".to_string()),
+            compression_enabled: compression.enabled,
+            compression_mode: compression.mode,
+            compression_level: compression.level,
+            compression_algorithm: compression.algorithm,
+            retry_policy: get_transfer_retry_policy(),
+            trusted_proxy_enabled: crate::http_common::is_trusted_proxy_enabled(),
+            preserve_timestamps: crate::transfer::timestamps::is_preserve_timestamps_enabled(),
+            http_bind_address: crate::http_common::http_bind_address().map(|ip| ip.to_string()),
+            idle_timeout_minutes: crate::idle::idle_timeout_minutes(),
+            page_branding: crate::http_common::page_branding(),
+        }
+    }
 
-        // 检查是否为复合扩展名（如 .tar.gz）
-        if let Some(inner_dot) = stem.rfind('.') {
-            let inner_ext = &stem[inner_dot + 1..];
-            // 常见复合扩展名
-            const COMPOUND_EXTENSIONS: &[&str] = &["tar", "zip"];
-            if COMPOUND_EXTENSIONS.contains(&inner_ext) {
+    /// 从存储的 JSON 中逐项恢复设置
+    ///
+    /// 无法解析或校验失败的字段保留 `fallback` 中的值，并连同原因一起返回；
+    /// `fallback` 本身须能通过校验
+    fn restore_fields(
+        stored: &serde_json::Value,
+        fallback: SettingsBundle,
+    ) -> (Self, Vec<(String, String)>) {
+        let Some(fields) = stored.as_object() else {
+            return (
+                fallback,
+                vec![("*".to_string(), "不是 JSON 对象".to_string())],
+            );
+        };
+        let mut restored = match serde_json::to_value(&fallback) {
+            Ok(serde_json::Value::Object(map)) => map,
+            _ => {
                 return (
-                    stem[..inner_dot].to_string(),
-                    format!("{}.{}", inner_ext, ext),
-                );
+                    fallback,
+                    vec![("*".to_string(), "序列化当前设置失败".to_string())],
+                )
+            }
+        };
+
+        let mut invalid = Vec::new();
+        for (field, value) in fields {
+            let mut candidate = restored.clone();
+            candidate.insert(field.clone(), value.clone());
+            let checked = serde_json::from_value::<SettingsBundle>(candidate.into())
+                .map_err(|e| e.to_string())
+                .and_then(|bundle| bundle.validate().map(drop));
+            match checked {
+                Ok(()) => {
+                    restored.insert(field.clone(), value.clone());
+                }
+                Err(e) => invalid.push((field.clone(), e)),
             }
         }
 
-        (stem.to_string(), ext.to_string())
-    } else {
-        (filename.to_string(), String::new())
+        match serde_json::from_value(restored.into()) {
+            Ok(bundle) => (bundle, invalid),
+            Err(e) => {
+                invalid.push(("*".to_string(), e.to_string()));
+                (fallback, invalid)
+            }
+        }
+    }
+
+    /// 校验全部字段，任一字段无效时整体拒绝；不修改文件系统
+    fn validate(self) -> Result<ValidatedSettings, String> {
+        validate_receive_directory(&self.receive_directory)?;
+        let preferred_cipher = parse_preferred_cipher(&self.preferred_cipher)?;
+        validate_compression_mode(&self.compression_mode)?;
+        validate_compression_level(self.compression_level)?;
+        let retry_policy = normalize_retry_policy(self.retry_policy)?;
+        let http_bind_address = parse_http_bind_address(self.http_bind_address.as_deref())?;
+        validate_idle_timeout(self.idle_timeout_minutes)?;
+        let page_branding = self.page_branding.normalized()?;
+
+        Ok(ValidatedSettings {
+            bundle: SettingsBundle {
+                preferred_cipher: preferred_cipher
+                    .map(|c| c.as_str().to_string())
+                    .unwrap_or_else(|| "auto".to_string()),
+                retry_policy,
+                http_bind_address: http_bind_address.map(|ip| ip.to_string()),
+                page_branding,
+                ..self
+            },
+            preferred_cipher,
+            http_bind_address,
+        })
     }
 }
 
-/// 获取接收文件的实际保存路径
-///
-/// 根据 file_overwrite 设置决定：
-/// - true: 直接覆盖同名文件
-/// - false: 自动重命名避免冲突
-pub fn get_receive_file_path(
-    directory: &PathBuf,
-    filename: &str,
-    file_overwrite: bool,
-) -> Result<PathBuf, String> {
-    // 如果允许覆盖，直接返回原路径
-    if file_overwrite {
-        return Ok(directory.join(filename));
+impl ValidatedSettings {
+    /// 应用到传输层和网页服务
+    ///
+    /// 先创建接收目录，失败时不修改任何设置
+    fn apply(self) -> Result<SettingsBundle, String> {
+        let bundle = self.bundle;
+        ensure_receive_directory(&bundle.receive_directory)?;
+        *get_receive_settings_lock()
+            .write()
+            .map_err(|e| e.to_string())? = bundle.receive.clone();
+        *get_receive_directory_lock()
+            .write()
+            .map_err(|e| e.to_string())? = Some(bundle.receive_directory.clone());
+        *get_transfer_retry_policy_lock()
+            .write()
+            .map_err(|e| e.to_string())? = bundle.retry_policy;
+
+        crate::transfer::crypto::set_encryption_enabled_internal(bundle.encryption_enabled);
+        crate::transfer::crypto::set_preferred_cipher_internal(self.preferred_cipher);
+        crate::transfer::compression::set_compression_enabled_internal(bundle.compression_enabled);
+        crate::transfer::compression::set_compression_mode_internal(
+            bundle.compression_mode.clone(),
+        );
+        crate::transfer::compression::set_compression_level_internal(bundle.compression_level);
+        crate::transfer::compression::set_compression_algorithm_internal(
+            bundle.compression_algorithm,
+        );
+        crate::http_common::set_trusted_proxy_enabled_internal(bundle.trusted_proxy_enabled);
+        crate::transfer::timestamps::set_preserve_timestamps_internal(bundle.preserve_timestamps);
+        crate::http_common::set_http_bind_address_internal(self.http_bind_address);
+        crate::idle::set_idle_timeout_minutes_internal(bundle.idle_timeout_minutes);
+        crate::http_common::set_page_branding_internal(bundle.page_branding.clone());
+        Ok(bundle)
     }
+}
 
-    // 不允许覆盖，生成唯一文件名
-    let unique_name = generate_unique_filename(directory, filename)?;
-    Ok(directory.join(unique_name))
+/// 将设置写入 Tauri Store
+fn save_settings_to_store(app: &AppHandle, bundle: &SettingsBundle) -> Result<(), String> {
+    let store = app
+        .store(SETTINGS_STORE_FILE)
+        .map_err(|e| format!("打开存储失败：{}", e))?;
+    let value = serde_json::to_value(bundle).map_err(|e| format!("序列化设置失败：{}", e))?;
+    store.set(SETTINGS_STORE_KEY, value);
+    store.save().map_err(|e| format!("保存设置失败：{}", e))
 }
 
-/// 获取接收文件的实际保存路径（Tauri 命令版本）
-#[tauri::command]
-pub async fn get_unique_file_path(
-    directory: String,
-    filename: String,
-    file_overwrite: bool,
-) -> Result<String, String> {
-    let dir_path = PathBuf::from(&directory);
+/// 串行化设置的读取、修改和写回
+static SETTINGS_UPDATE_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
 
-    // 确保目录存在
-    if !dir_path.exists() {
-        std::fs::create_dir_all(&dir_path)
-            .map_err(|e| format!("无法创建目录 '{}': {}", directory, e))?;
-    }
+/// 校验并应用整组设置，成功后持久化并向所有窗口发送 `settings-changed` 事件
+fn commit_settings(app: &AppHandle, bundle: SettingsBundle) -> Result<SettingsBundle, String> {
+    let applied = bundle.validate()?.apply()?;
+    save_settings_to_store(app, &applied)?;
+    let _ = app.emit("settings-changed", applied.clone());
+    Ok(applied)
+}
 
-    let result_path = get_receive_file_path(&dir_path, &filename, file_overwrite)?;
-    Ok(result_path.to_string_lossy().to_string())
+/// 在当前设置上修改部分字段后提交，单项设置命令均经由此处
+fn update_settings(
+    app: &AppHandle,
+    change: impl FnOnce(&mut SettingsBundle),
+) -> Result<SettingsBundle, String> {
+    let _guard = SETTINGS_UPDATE_LOCK
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    let mut bundle = SettingsBundle::current();
+    change(&mut bundle);
+    commit_settings(app, bundle)
 }
 
-// ============ 加密设置相关命令 ============
+/// 启动时恢复已持久化的设置
+///
+/// 逐项恢复：无效的字段（如监听地址已不属于本机）记录日志并保持默认值，
+/// 其余字段照常生效
+pub fn restore_persisted_settings(app: &AppHandle) {
+    let stored = match app.store(SETTINGS_STORE_FILE) {
+        Ok(store) => store.get(SETTINGS_STORE_KEY),
+        Err(e) => {
+            tracing::warn!(error = %e, "打开设置存储失败");
+            return;
+        }
+    };
+    let Some(value) = stored else {
+        return;
+    };
+    let (mut bundle, invalid) = SettingsBundle::restore_fields(&value, SettingsBundle::current());
+    for (field, error) in &invalid {
+        tracing::warn!(field = %field, error = %error, "已保存的设置无效，保持默认值");
+    }
 
-/// 获取加密是否启用
-#[tauri::command]
-pub async fn get_encryption_enabled() -> Result<bool, String> {
-    Ok(crate::transfer::crypto::is_encryption_enabled())
+    // 目录无法创建时只放弃接收目录，不影响其他设置
+    if let Err(e) = ensure_receive_directory(&bundle.receive_directory) {
+        tracing::warn!(error = %e, "无法使用已保存的接收目录，保持默认值");
+        bundle.receive_directory = current_receive_directory();
+    }
+    if let Err(e) = bundle.validate().and_then(ValidatedSettings::apply) {
+        tracing::warn!(error = %e, "恢复已保存的设置失败，使用默认设置");
+    }
 }
 
-/// 设置加密启用状态
+/// 获取全部后端设置
 #[tauri::command]
-pub async fn set_encryption_enabled(enabled: bool) -> Result<(), String> {
-    crate::transfer::crypto::set_encryption_enabled_internal(enabled);
-    Ok(())
+pub async fn get_all_settings() -> Result<SettingsBundle, String> {
+    Ok(SettingsBundle::current())
 }
 
-// ============ 压缩设置相关命令 ============
-
-/// 获取压缩是否启用
+/// 整体应用一组后端设置
+///
+/// 先校验全部字段，任一字段无效时不做任何修改；校验通过后生效、持久化，
+/// 并向所有窗口发送 `settings-changed` 事件。返回规范化后的设置
 #[tauri::command]
-pub async fn get_compression_enabled() -> Result<bool, String> {
-    let config = crate::transfer::compression::get_compression_config();
-    Ok(config.enabled)
+pub async fn apply_settings(
+    app: AppHandle,
+    bundle: SettingsBundle,
+) -> Result<SettingsBundle, String> {
+    let _guard = SETTINGS_UPDATE_LOCK
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    commit_settings(&app, bundle)
 }
 
-/// 设置压缩启用状态
-#[tauri::command]
-pub async fn set_compression_enabled(enabled: bool) -> Result<(), String> {
-    crate::transfer::compression::set_compression_enabled_internal(enabled);
-    Ok(())
+// ============ 设备身份相关命令 ============
+
+/// 设备身份变更事件载荷
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DeviceIdentityChangedPayload {
+    /// 旧指纹
+    old_fingerprint: String,
+    /// 新指纹
+    fingerprint: String,
 }
 
-/// 设置压缩模式
+/// 获取本机设备指纹
 #[tauri::command]
-pub async fn set_compression_mode(mode: String) -> Result<(), String> {
-    if mode != "smart" && mode != "manual" {
-        return Err(format!("无效的压缩模式: {}，支持 smart 或 manual", mode));
-    }
-    crate::transfer::compression::set_compression_mode_internal(mode);
-    Ok(())
+pub async fn get_device_fingerprint() -> Result<String, String> {
+    Ok(crate::transfer::current_device_identity().fingerprint())
 }
 
-/// 设置压缩级别
+/// 重新生成设备密钥对
+///
+/// 进行中的会话继续使用旧密钥，之后的握手使用新密钥；对端将看到指纹变化
 #[tauri::command]
-pub async fn set_compression_level(level: i32) -> Result<(), String> {
-    if !(1..=19).contains(&level) {
-        return Err(format!("无效的压缩级别: {}，范围为 1-19", level));
-    }
-    crate::transfer::compression::set_compression_level_internal(level);
-    Ok(())
+pub async fn regenerate_device_keypair(app: AppHandle) -> Result<String, String> {
+    let old_fingerprint = crate::transfer::current_device_identity().fingerprint();
+    let identity = tokio::task::spawn_blocking(crate::transfer::regenerate_device_identity)
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("重新生成设备密钥失败: {}", e))?;
+    let fingerprint = identity.fingerprint();
+
+    tracing::info!(old = %old_fingerprint, new = %fingerprint, "设备密钥已轮换");
+    let _ = app.emit(
+        "device-identity-changed",
+        DeviceIdentityChangedPayload {
+            old_fingerprint,
+            fingerprint: fingerprint.clone(),
+        },
+    );
+
+    Ok(fingerprint)
 }
 
 // ============ 断点续传相关命令 ============
@@ -940,3 +3765,291 @@ pub async fn cleanup_resume_info(task_id: Option<String>) -> Result<(), String>
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_restricted_fetch_ip() {
+        let restricted = [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "255.255.255.255",
+            "224.0.0.251",
+            "::1",
+            "::",
+            "fd00::1",
+            "fe80::1",
+            "ff02::1",
+            "::ffff:127.0.0.1",
+            "::ffff:169.254.169.254",
+        ];
+        for ip in restricted {
+            assert!(is_restricted_fetch_ip(&ip.parse().unwrap()), "{}", ip);
+        }
+
+        let public = [
+            "8.8.8.8",
+            "100.128.0.1",
+            "172.32.0.1",
+            "2606:4700::1111",
+            "::ffff:1.1.1.1",
+        ];
+        for ip in public {
+            assert!(!is_restricted_fetch_ip(&ip.parse().unwrap()), "{}", ip);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_validate_fetch_target() {
+        let url = |s: &str| reqwest::Url::parse(s).unwrap();
+
+        let rejected = [
+            "ftp://1.1.1.1/a",
+            "http://127.0.0.1/a",
+            "http://169.254.169.254/latest",
+            "http://[::1]:8080/",
+        ];
+        for target in rejected {
+            let result = validate_fetch_target(&url(target), false).await;
+            assert!(result.is_err(), "{}", target);
+        }
+
+        // 通过校验的地址即连接时使用的地址
+        let pinned = validate_fetch_target(&url("https://1.1.1.1/a"), false)
+            .await
+            .unwrap();
+        assert_eq!(pinned, vec!["1.1.1.1:443".parse().unwrap()]);
+        let pinned = validate_fetch_target(&url("http://127.0.0.1/a"), true)
+            .await
+            .unwrap();
+        assert!(pinned.is_empty());
+    }
+
+    #[test]
+    fn test_parse_content_disposition_filename() {
+        assert_eq!(
+            parse_content_disposition_filename("attachment; filename=\"report.pdf\""),
+            Some("report.pdf".to_string())
+        );
+        // filename* 优先于 filename
+        assert_eq!(
+            parse_content_disposition_filename(
+                "attachment; filename=\"fallback.txt\"; filename*=UTF-8''%E6%8A%A5%E5%91%8A.txt"
+            ),
+            Some("报告.txt".to_string())
+        );
+        // 去除路径成分
+        assert_eq!(
+            parse_content_disposition_filename("attachment; filename=\"../../etc/passwd\""),
+            Some("passwd".to_string())
+        );
+        assert_eq!(
+            parse_content_disposition_filename("attachment; filename*=UTF-8''..%2F..%2Fa.sh"),
+            Some("a.sh".to_string())
+        );
+        assert_eq!(
+            parse_content_disposition_filename("attachment; filename=\"C:\\dir\\b.exe\""),
+            Some("b.exe".to_string())
+        );
+        assert_eq!(
+            parse_content_disposition_filename("attachment; filename=\"..\""),
+            None
+        );
+        assert_eq!(parse_content_disposition_filename("inline"), None);
+    }
+
+    #[test]
+    fn test_filename_from_url() {
+        let url = |s: &str| reqwest::Url::parse(s).unwrap();
+        assert_eq!(
+            filename_from_url(&url("https://example.com/files/a%20b.zip?x=1")),
+            Some("a b.zip".to_string())
+        );
+        assert_eq!(
+            filename_from_url(&url("https://example.com/dir/")),
+            Some("dir".to_string())
+        );
+        assert_eq!(
+            filename_from_url(&url("https://example.com/x/%2E%2E%2Fsecret")),
+            Some("secret".to_string())
+        );
+        assert_eq!(filename_from_url(&url("https://example.com/")), None);
+        assert_eq!(filename_from_url(&url("https://example.com/%2E%2E")), None);
+    }
+
+    #[test]
+    fn test_resolve_received_file_inside_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("sub").join("a.txt");
+        std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+        std::fs::write(&file, b"data").unwrap();
+
+        let resolved = resolve_received_file(dir.path(), &file).unwrap();
+        assert_eq!(resolved, file.canonicalize().unwrap());
+    }
+
+    #[test]
+    fn test_resolve_received_file_rejects_outside_and_traversal() {
+        let root = tempfile::tempdir().unwrap();
+        let receive_dir = root.path().join("receive");
+        std::fs::create_dir(&receive_dir).unwrap();
+        let outside = root.path().join("outside.txt");
+        std::fs::write(&outside, b"secret").unwrap();
+
+        assert!(resolve_received_file(&receive_dir, &outside).is_err());
+        let traversal = receive_dir.join("..").join("outside.txt");
+        assert!(resolve_received_file(&receive_dir, &traversal).is_err());
+        // 接收目录本身与其中的子目录都不能删除
+        assert!(resolve_received_file(&receive_dir, &receive_dir).is_err());
+        std::fs::create_dir(receive_dir.join("sub")).unwrap();
+        assert!(resolve_received_file(&receive_dir, &receive_dir.join("sub")).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_received_file_rejects_symlink_outside() {
+        let root = tempfile::tempdir().unwrap();
+        let receive_dir = root.path().join("receive");
+        std::fs::create_dir(&receive_dir).unwrap();
+        let outside = root.path().join("outside.txt");
+        std::fs::write(&outside, b"secret").unwrap();
+
+        let link = receive_dir.join("link.txt");
+        std::os::unix::fs::symlink(&outside, &link).unwrap();
+        assert!(resolve_received_file(&receive_dir, &link).is_err());
+        assert!(outside.exists());
+    }
+
+    #[test]
+    fn test_restore_fields_keeps_valid_fields() {
+        let stored = serde_json::json!({
+            "compressionMode": "manual",
+            "compressionLevel": 99,
+            "idleTimeoutMinutes": "ten",
+            "encryptionEnabled": false,
+            "unknownField": 1,
+        });
+        let (bundle, invalid) = SettingsBundle::restore_fields(&stored, SettingsBundle::default());
+
+        assert_eq!(bundle.compression_mode, "manual");
+        assert!(!bundle.encryption_enabled);
+        assert_eq!(
+            bundle.compression_level,
+            SettingsBundle::default().compression_level
+        );
+        assert_eq!(bundle.idle_timeout_minutes, 0);
+
+        let mut fields: Vec<&str> = invalid.iter().map(|(field, _)| field.as_str()).collect();
+        fields.sort_unstable();
+        assert_eq!(fields, ["compressionLevel", "idleTimeoutMinutes"]);
+    }
+
+    #[test]
+    fn test_validate_does_not_create_receive_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing");
+        let bundle = SettingsBundle {
+            receive_directory: missing.to_string_lossy().to_string(),
+            ..SettingsBundle::default()
+        };
+        assert!(bundle.validate().is_ok());
+        assert!(!missing.exists());
+
+        let file = dir.path().join("file");
+        std::fs::write(&file, b"x").unwrap();
+        assert!(validate_receive_directory(&file.to_string_lossy()).is_err());
+        assert!(validate_receive_directory("  ").is_err());
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("*.tmp", "cache.tmp"));
+        assert!(wildcard_match("*.tmp", ".tmp"));
+        assert!(!wildcard_match("*.tmp", "cache.tmp.bak"));
+        assert!(wildcard_match("file?.txt", "file1.txt"));
+        assert!(!wildcard_match("file?.txt", "file10.txt"));
+        assert!(wildcard_match("a*b*c", "aXXbYYc"));
+        assert!(wildcard_match("*", ""));
+        assert!(!wildcard_match("?", ""));
+        assert!(wildcard_match("node_modules", "node_modules"));
+        assert!(!wildcard_match("node_modules", "node_modules2"));
+    }
+
+    #[test]
+    fn test_is_ignored() {
+        let patterns = |list: &[&str]| list.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+
+        // 目录规则匹配任一层级的目录名，末尾的 `/` 可省略
+        let dirs = patterns(&["build/", "node_modules"]);
+        assert!(is_ignored("build/out.js", &dirs));
+        assert!(is_ignored("src/build/out.js", &dirs));
+        assert!(is_ignored("web\\node_modules\\lib\\index.js", &dirs));
+        assert!(!is_ignored("builder/out.js", &dirs));
+
+        // 通配符匹配文件名或完整相对路径
+        let wildcards = patterns(&["*.log", "docs/*.md"]);
+        assert!(is_ignored("logs/app.log", &wildcards));
+        assert!(is_ignored("docs/readme.md", &wildcards));
+        assert!(!is_ignored("readme.md", &wildcards));
+
+        // 隐藏文件默认发送，可用 `.*` 排除
+        assert!(!is_ignored(".env", &[]));
+        let hidden = patterns(&[".*"]);
+        assert!(is_ignored(".env", &hidden));
+        assert!(is_ignored(".git/config", &hidden));
+        assert!(!is_ignored("src/main.rs", &hidden));
+
+        // 否定规则重新包含，后面的规则优先
+        let negated = patterns(&["*.log", "!keep.log"]);
+        assert!(is_ignored("debug.log", &negated));
+        assert!(!is_ignored("logs/keep.log", &negated));
+        let reignored = patterns(&["*.log", "!keep.log", "logs"]);
+        assert!(is_ignored("logs/keep.log", &reignored));
+        assert!(!is_ignored("keep.log", &reignored));
+        assert!(!is_ignored("keep.log", &patterns(&["!keep.log"])));
+    }
+
+    #[tokio::test]
+    async fn test_preview_folder_send() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join("a.txt"), vec![0u8; 10]).unwrap();
+        std::fs::write(root.join("b.tmp"), vec![0u8; 5]).unwrap();
+        std::fs::write(root.join(".env"), vec![0u8; 1]).unwrap();
+        std::fs::create_dir(root.join("node_modules")).unwrap();
+        std::fs::write(root.join("node_modules").join("x.js"), vec![0u8; 3]).unwrap();
+
+        let preview = preview_folder_send(
+            root.to_string_lossy().to_string(),
+            Some(vec!["*.tmp".to_string(), "node_modules/".to_string()]),
+            Some(4),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(preview.file_count, 2);
+        assert_eq!(preview.total_size, 11);
+        assert_eq!(preview.skipped_count, 2);
+        assert_eq!(preview.skipped_size, 8);
+        assert_eq!(preview.estimated_seconds, Some(3));
+        assert_eq!(preview.largest_files[0].name, "a.txt");
+        assert_eq!(preview.largest_files[1].name, ".env");
+
+        let unfiltered = preview_folder_send(root.to_string_lossy().to_string(), None, None)
+            .await
+            .unwrap();
+        assert_eq!(unfiltered.file_count, 4);
+        assert_eq!(unfiltered.skipped_count, 0);
+        assert_eq!(unfiltered.estimated_seconds, None);
+
+        let file = root.join("a.txt").to_string_lossy().to_string();
+        assert!(preview_folder_send(file, None, None).await.is_err());
+    }
+}
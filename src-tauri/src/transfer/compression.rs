@@ -1,8 +1,100 @@
 //! 传输压缩模块
 //!
-//! 提供 zstd 流式压缩/解压功能，支持智能压缩策略（根据文件 MIME 类型自动选择压缩级别）。
+//! 提供 zstd / lz4 / gzip 压缩/解压功能，支持智能压缩策略（根据文件 MIME 类型自动选择压缩级别）。
 
 use crate::error::{TransferError, TransferResult};
+use std::io::{Read, Write};
+
+/// 压缩算法
+///
+/// zstd 压缩率最高；lz4 压缩率较低但速度最快，适合高速局域网；
+/// gzip 可由浏览器 `DecompressionStream` 原生解压。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompressionAlgorithm {
+    /// zstd（默认）
+    #[default]
+    Zstd,
+    /// lz4 帧格式
+    Lz4,
+    /// gzip
+    Gzip,
+}
+
+impl CompressionAlgorithm {
+    /// 算法名称（用于 `x-compression` 头等）
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Zstd => "zstd",
+            Self::Lz4 => "lz4",
+            Self::Gzip => "gzip",
+        }
+    }
+
+    /// 从算法名称解析
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "zstd" => Some(Self::Zstd),
+            "lz4" => Some(Self::Lz4),
+            "gzip" => Some(Self::Gzip),
+            _ => None,
+        }
+    }
+
+    /// 压缩数据块
+    ///
+    /// `level` 为 zstd 级别（1-19），gzip 截取到 1-9，lz4 不区分级别
+    pub fn compress(self, data: &[u8], level: i32) -> TransferResult<Vec<u8>> {
+        let result = match self {
+            Self::Zstd => zstd::encode_all(std::io::Cursor::new(data), level),
+            Self::Lz4 => {
+                let mut encoder = lz4_flex::frame::FrameEncoder::new(Vec::new());
+                encoder
+                    .write_all(data)
+                    .and_then(|_| encoder.finish().map_err(std::io::Error::other))
+            }
+            Self::Gzip => {
+                let level = flate2::Compression::new(level.clamp(1, 9) as u32);
+                let mut encoder = flate2::write::GzEncoder::new(Vec::new(), level);
+                encoder.write_all(data).and_then(|_| encoder.finish())
+            }
+        };
+        result.map_err(|e| TransferError::Compression(format!("{} 压缩失败: {}", self.as_str(), e)))
+    }
+
+    /// 解压数据块
+    pub fn decompress(self, compressed_data: &[u8]) -> TransferResult<Vec<u8>> {
+        fn read_all(mut reader: impl Read) -> std::io::Result<Vec<u8>> {
+            let mut data = Vec::new();
+            reader.read_to_end(&mut data)?;
+            Ok(data)
+        }
+
+        let result = match self {
+            Self::Zstd => zstd::decode_all(std::io::Cursor::new(compressed_data)),
+            Self::Lz4 => read_all(lz4_flex::frame::FrameDecoder::new(compressed_data)),
+            Self::Gzip => read_all(flate2::read::GzDecoder::new(compressed_data)),
+        };
+        result
+            .map_err(|e| TransferError::Decompression(format!("{} 解压失败: {}", self.as_str(), e)))
+    }
+}
+
+/// 本端支持的压缩算法（按偏好排序，当前设置的算法在前）
+pub fn supported_compression_algorithms() -> Vec<CompressionAlgorithm> {
+    let preferred = get_compression_config().algorithm;
+    let mut algorithms = vec![preferred];
+    algorithms.extend(
+        [
+            CompressionAlgorithm::Zstd,
+            CompressionAlgorithm::Lz4,
+            CompressionAlgorithm::Gzip,
+        ]
+        .into_iter()
+        .filter(|algorithm| *algorithm != preferred),
+    );
+    algorithms
+}
 
 /// 压缩模式
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -15,10 +107,12 @@ pub enum CompressionMode {
 
 /// 压缩器
 ///
-/// 封装 zstd 压缩/解压操作，支持智能压缩策略。
+/// 按所选算法压缩数据块，支持智能压缩策略。
 pub struct Compressor {
     /// 压缩模式
     mode: CompressionMode,
+    /// 压缩算法
+    algorithm: CompressionAlgorithm,
 }
 
 impl Compressor {
@@ -26,6 +120,7 @@ impl Compressor {
     pub fn smart() -> Self {
         Self {
             mode: CompressionMode::Smart,
+            algorithm: CompressionAlgorithm::default(),
         }
     }
 
@@ -35,9 +130,21 @@ impl Compressor {
         let clamped_level = level.clamp(1, 19);
         Self {
             mode: CompressionMode::Manual(clamped_level),
+            algorithm: CompressionAlgorithm::default(),
         }
     }
 
+    /// 指定压缩算法
+    pub fn with_algorithm(mut self, algorithm: CompressionAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// 压缩算法
+    pub fn algorithm(&self) -> CompressionAlgorithm {
+        self.algorithm
+    }
+
     /// 根据 MIME 类型判断是否应该跳过压缩
     ///
     /// 已压缩的文件格式（如 zip、mp4、jpg）再次压缩效果极差，应跳过。
@@ -136,21 +243,8 @@ impl Compressor {
     ///
     /// # Returns
     /// 压缩后的数据
-    pub fn compress(data: &[u8], level: i32) -> TransferResult<Vec<u8>> {
-        zstd::encode_all(std::io::Cursor::new(data), level)
-            .map_err(|e| TransferError::Compression(format!("zstd 压缩失败: {}", e)))
-    }
-
-    /// 解压数据块
-    ///
-    /// # Arguments
-    /// * `compressed_data` - 压缩后的数据
-    ///
-    /// # Returns
-    /// 解压后的原始数据
-    pub fn decompress(compressed_data: &[u8]) -> TransferResult<Vec<u8>> {
-        zstd::decode_all(std::io::Cursor::new(compressed_data))
-            .map_err(|e| TransferError::Decompression(format!("zstd 解压失败: {}", e)))
+    pub fn compress(&self, data: &[u8], level: i32) -> TransferResult<Vec<u8>> {
+        self.algorithm.compress(data, level)
     }
 }
 
@@ -167,6 +261,8 @@ pub struct CompressionConfig {
     pub mode: String,
     /// 手动压缩级别（1-19）
     pub level: i32,
+    /// 压缩算法
+    pub algorithm: CompressionAlgorithm,
 }
 
 impl Default for CompressionConfig {
@@ -175,6 +271,7 @@ impl Default for CompressionConfig {
             enabled: true,
             mode: "smart".to_string(),
             level: 3,
+            algorithm: CompressionAlgorithm::default(),
         }
     }
 }
@@ -213,6 +310,13 @@ pub fn set_compression_level_internal(level: i32) {
     }
 }
 
+/// 设置压缩算法
+pub fn set_compression_algorithm_internal(algorithm: CompressionAlgorithm) {
+    if let Ok(mut lock) = get_compression_lock().write() {
+        lock.algorithm = algorithm;
+    }
+}
+
 /// 根据当前配置创建压缩器
 pub fn create_compressor_from_config() -> Option<Compressor> {
     let config = get_compression_config();
@@ -220,10 +324,100 @@ pub fn create_compressor_from_config() -> Option<Compressor> {
         return None;
     }
 
-    match config.mode.as_str() {
-        "smart" => Some(Compressor::smart()),
-        "manual" => Some(Compressor::manual(config.level)),
-        _ => Some(Compressor::smart()),
+    let compressor = match config.mode.as_str() {
+        "smart" => Compressor::smart(),
+        "manual" => Compressor::manual(config.level),
+        _ => Compressor::smart(),
+    };
+    Some(compressor.with_algorithm(config.algorithm))
+}
+
+/// 单个 MIME 大类的压缩统计
+#[derive(Debug, Clone, Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompressionCategoryStats {
+    /// MIME 大类（如 text、image、application）
+    pub category: String,
+    /// 以压缩形式发送的分块数
+    pub compressed_chunks: u64,
+    /// 未压缩发送的分块数（策略跳过或压缩后未变小）
+    pub skipped_chunks: u64,
+    /// 其中尝试压缩但未变小的分块数
+    pub ineffective_chunks: u64,
+    /// 压缩分块的原始字节数
+    pub original_bytes: u64,
+    /// 压缩分块压缩后的字节数
+    pub compressed_bytes: u64,
+    /// 平均压缩比（压缩后 / 原始），无压缩分块时为 None
+    pub average_ratio: Option<f64>,
+}
+
+/// MIME 大类 -> 压缩统计（自启动或上次重置以来）
+static COMPRESSION_STATS: std::sync::OnceLock<
+    std::sync::RwLock<std::collections::BTreeMap<String, CompressionCategoryStats>>,
+> = std::sync::OnceLock::new();
+
+fn get_compression_stats_lock(
+) -> &'static std::sync::RwLock<std::collections::BTreeMap<String, CompressionCategoryStats>> {
+    COMPRESSION_STATS.get_or_init(|| std::sync::RwLock::new(std::collections::BTreeMap::new()))
+}
+
+/// 取 MIME 类型的大类（斜杠前部分），无法识别时归为 other
+fn mime_category(mime_type: &str) -> String {
+    match mime_type.split('/').next().map(str::trim) {
+        Some(category) if !category.is_empty() => category.to_ascii_lowercase(),
+        _ => "other".to_string(),
+    }
+}
+
+/// 记录一个分块的压缩结果
+///
+/// `attempted_size` 为尝试压缩后的大小，策略跳过时为 None；`compressed` 表示最终是否以压缩形式发送
+pub fn record_chunk_compression(
+    mime_type: &str,
+    original_size: usize,
+    attempted_size: Option<usize>,
+    compressed: bool,
+) {
+    let Ok(mut stats) = get_compression_stats_lock().write() else {
+        return;
+    };
+    let category = mime_category(mime_type);
+    let entry = stats
+        .entry(category.clone())
+        .or_insert_with(|| CompressionCategoryStats {
+            category,
+            ..Default::default()
+        });
+
+    match attempted_size {
+        Some(size) if compressed => {
+            entry.compressed_chunks += 1;
+            entry.original_bytes += original_size as u64;
+            entry.compressed_bytes += size as u64;
+            entry.average_ratio = (entry.original_bytes > 0)
+                .then(|| entry.compressed_bytes as f64 / entry.original_bytes as f64);
+        }
+        Some(_) => {
+            entry.skipped_chunks += 1;
+            entry.ineffective_chunks += 1;
+        }
+        None => entry.skipped_chunks += 1,
+    }
+}
+
+/// 获取各 MIME 大类的压缩统计（按大类名排序）
+pub fn compression_stats() -> Vec<CompressionCategoryStats> {
+    get_compression_stats_lock()
+        .read()
+        .map(|stats| stats.values().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// 清空压缩统计
+pub fn reset_compression_stats() {
+    if let Ok(mut stats) = get_compression_stats_lock().write() {
+        stats.clear();
     }
 }
 
@@ -234,12 +428,31 @@ mod tests {
     #[test]
     fn test_compress_decompress() {
         let data = b"Hello, PureSend! This is a test for zstd compression.".repeat(100);
-        let compressed = Compressor::compress(&data, 3).unwrap();
-        let decompressed = Compressor::decompress(&compressed).unwrap();
+        let compressed = Compressor::smart().compress(&data, 3).unwrap();
+        let decompressed = CompressionAlgorithm::Zstd.decompress(&compressed).unwrap();
         assert_eq!(data, decompressed);
         assert!(compressed.len() < data.len());
     }
 
+    #[test]
+    fn test_algorithms_round_trip() {
+        let data = b"Hello, PureSend! Compression algorithms round trip.".repeat(100);
+        for algorithm in supported_compression_algorithms() {
+            let compressor = Compressor::manual(19).with_algorithm(algorithm);
+            let compressed = compressor.compress(&data, 19).unwrap();
+            assert!(compressed.len() < data.len());
+            assert_eq!(algorithm.decompress(&compressed).unwrap(), data);
+            assert_eq!(
+                CompressionAlgorithm::from_name(algorithm.as_str()),
+                Some(algorithm)
+            );
+        }
+        // 算法不匹配时解压失败而不是返回错误数据
+        let compressed = CompressionAlgorithm::Gzip.compress(&data, 3).unwrap();
+        assert!(CompressionAlgorithm::Lz4.decompress(&compressed).is_err());
+        assert_eq!(CompressionAlgorithm::from_name("brotli"), None);
+    }
+
     #[test]
     fn test_smart_compression_level() {
         // 文档类文件应使用高压缩级别
@@ -275,4 +488,29 @@ mod tests {
         assert!(!Compressor::should_skip_compression("text/plain"));
         assert!(!Compressor::should_skip_compression("image/png"));
     }
+
+    #[test]
+    fn test_mime_category() {
+        assert_eq!(mime_category("text/plain"), "text");
+        assert_eq!(mime_category("Image/PNG"), "image");
+        assert_eq!(mime_category(""), "other");
+    }
+
+    #[test]
+    fn test_record_chunk_compression() {
+        let mime = "x-stats-test/plain";
+        record_chunk_compression(mime, 100, Some(25), true);
+        record_chunk_compression(mime, 300, Some(75), true);
+        record_chunk_compression(mime, 100, Some(120), false);
+        record_chunk_compression(mime, 100, None, false);
+
+        let stats = compression_stats()
+            .into_iter()
+            .find(|stats| stats.category == "x-stats-test")
+            .unwrap();
+        assert_eq!(stats.compressed_chunks, 2);
+        assert_eq!(stats.skipped_chunks, 2);
+        assert_eq!(stats.ineffective_chunks, 1);
+        assert_eq!(stats.average_ratio, Some(0.25));
+    }
 }
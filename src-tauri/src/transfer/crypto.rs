@@ -1,27 +1,136 @@
 //! 传输加密模块
 //!
-//! 提供 X25519 ECDH 密钥交换和 AES-256-GCM 加密/解密功能，
+//! 提供 X25519 ECDH 密钥交换和 AES-256-GCM / ChaCha20-Poly1305 加密/解密功能，
 //! 用于保护 P2P 直连模式下的文件传输数据。
 
 use aes_gcm::aead::{Aead, KeyInit};
-use aes_gcm::{Aes256Gcm, Nonce};
+use aes_gcm::Aes256Gcm;
+use chacha20poly1305::ChaCha20Poly1305;
 use rand::rngs::OsRng;
 use rand::RngCore;
+use hkdf::Hkdf;
+use sha2::{Digest, Sha256};
 use x25519_dalek::{EphemeralSecret, PublicKey, SharedSecret};
 
 use crate::error::{TransferError, TransferResult};
 
-/// AES-256-GCM nonce 大小（12 字节）
+/// AEAD nonce 大小（12 字节，AES-256-GCM 与 ChaCha20-Poly1305 相同）
 const NONCE_SIZE: usize = 12;
 
+/// 密钥确认值的派生上下文，与加密密钥的用途隔离
+const KEY_CONFIRMATION_CONTEXT: &[u8] = b"puresend-key-confirmation-v1";
+
+/// 短认证串的派生上下文（HTTP 分享页的浏览器端使用相同的值）
+const SAS_CONTEXT: &[u8] = b"puresend-sas";
+
+/// 对称加密算法
+///
+/// 有硬件 AES 指令的平台上 AES-256-GCM 更快，
+/// 没有 AES 指令的旧 ARM/移动设备上 ChaCha20-Poly1305 更快。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum CipherSuite {
+    /// AES-256-GCM（默认，浏览器 Web Crypto 仅支持此算法）
+    #[default]
+    #[serde(rename = "aes-256-gcm")]
+    Aes256Gcm,
+    /// ChaCha20-Poly1305
+    #[serde(rename = "chacha20-poly1305")]
+    ChaCha20Poly1305,
+}
+
+impl CipherSuite {
+    /// 算法名称（用于 `x-encryption` 响应头等）
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Aes256Gcm => "aes-256-gcm",
+            Self::ChaCha20Poly1305 => "chacha20-poly1305",
+        }
+    }
+
+    /// 从算法名称解析
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "aes-256-gcm" => Some(Self::Aes256Gcm),
+            "chacha20-poly1305" => Some(Self::ChaCha20Poly1305),
+            _ => None,
+        }
+    }
+}
+
+/// 反序列化加密算法列表，丢弃未识别的算法
+///
+/// 对端可能支持本端还不认识的新算法，不应因此导致整个握手请求解析失败
+pub fn deserialize_cipher_suites<'de, D>(deserializer: D) -> Result<Vec<CipherSuite>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let names = <Vec<String> as serde::Deserialize>::deserialize(deserializer)?;
+    Ok(names
+        .iter()
+        .filter_map(|name| CipherSuite::from_name(name))
+        .collect())
+}
+
+/// AEAD 密码实例
+pub(crate) enum AeadCipher {
+    Aes(Box<Aes256Gcm>),
+    ChaCha(Box<ChaCha20Poly1305>),
+}
+
+impl AeadCipher {
+    /// 使用 32 字节密钥创建密码实例
+    pub(crate) fn new(suite: CipherSuite, key: &[u8]) -> Result<Self, String> {
+        match suite {
+            CipherSuite::Aes256Gcm => Aes256Gcm::new_from_slice(key)
+                .map(|c| Self::Aes(Box::new(c)))
+                .map_err(|e| format!("创建 AES-256-GCM 实例失败: {}", e)),
+            CipherSuite::ChaCha20Poly1305 => ChaCha20Poly1305::new_from_slice(key)
+                .map(|c| Self::ChaCha(Box::new(c)))
+                .map_err(|e| format!("创建 ChaCha20-Poly1305 实例失败: {}", e)),
+        }
+    }
+
+    pub(crate) fn encrypt(
+        &self,
+        nonce: &[u8; NONCE_SIZE],
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        match self {
+            Self::Aes(cipher) => cipher.encrypt(&aes_gcm::Nonce::from(*nonce), plaintext),
+            Self::ChaCha(cipher) => {
+                cipher.encrypt(&chacha20poly1305::Nonce::from(*nonce), plaintext)
+            }
+        }
+        .map_err(|e| e.to_string())
+    }
+
+    pub(crate) fn decrypt(
+        &self,
+        nonce: &[u8; NONCE_SIZE],
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        match self {
+            Self::Aes(cipher) => cipher.decrypt(&aes_gcm::Nonce::from(*nonce), ciphertext),
+            Self::ChaCha(cipher) => {
+                cipher.decrypt(&chacha20poly1305::Nonce::from(*nonce), ciphertext)
+            }
+        }
+        .map_err(|e| e.to_string())
+    }
+}
+
 /// 加密会话
 ///
-/// 封装一次传输会话中的加密状态，包括共享密钥和 AES-256-GCM 密码实例。
+/// 封装一次传输会话中的加密状态，包括共享密钥和协商出的 AEAD 密码实例。
 pub struct CryptoSession {
-    /// AES-256-GCM 密码实例
-    cipher: Aes256Gcm,
+    /// AEAD 密码实例
+    cipher: AeadCipher,
+    /// 协商使用的加密算法
+    suite: CipherSuite,
     /// nonce 计数器（每次加密递增，防止 nonce 重用）
     nonce_counter: u64,
+    /// 密钥确认值（共享密钥与算法的单向哈希，可公开比对）
+    key_confirmation: [u8; 32],
 }
 
 /// 密钥交换发起方
@@ -45,8 +154,17 @@ impl KeyExchangeInitiator {
         self.public_key.as_bytes().to_vec()
     }
 
-    /// 使用对方公钥完成密钥交换，生成加密会话
+    /// 使用对方公钥完成密钥交换，生成加密会话（AES-256-GCM）
     pub fn complete(self, peer_public_key: &[u8]) -> TransferResult<CryptoSession> {
+        self.complete_with_cipher(peer_public_key, CipherSuite::Aes256Gcm)
+    }
+
+    /// 使用对方公钥完成密钥交换，生成指定算法的加密会话
+    pub fn complete_with_cipher(
+        self,
+        peer_public_key: &[u8],
+        suite: CipherSuite,
+    ) -> TransferResult<CryptoSession> {
         let peer_key_bytes: [u8; 32] = peer_public_key.try_into().map_err(|_| {
             TransferError::KeyExchange("对方公钥长度无效，期望 32 字节".to_string())
         })?;
@@ -54,7 +172,7 @@ impl KeyExchangeInitiator {
         let peer_public = PublicKey::from(peer_key_bytes);
         let shared_secret: SharedSecret = self.secret.diffie_hellman(&peer_public);
 
-        CryptoSession::from_shared_secret(shared_secret.as_bytes())
+        CryptoSession::from_shared_secret(shared_secret.as_bytes(), suite)
     }
 }
 
@@ -81,8 +199,17 @@ impl KeyExchangeResponder {
         self.public_key.as_bytes().to_vec()
     }
 
-    /// 使用对方公钥完成密钥交换，生成加密会话
+    /// 使用对方公钥完成密钥交换，生成加密会话（AES-256-GCM）
     pub fn complete(self, peer_public_key: &[u8]) -> TransferResult<CryptoSession> {
+        self.complete_with_cipher(peer_public_key, CipherSuite::Aes256Gcm)
+    }
+
+    /// 使用对方公钥完成密钥交换，生成指定算法的加密会话
+    pub fn complete_with_cipher(
+        self,
+        peer_public_key: &[u8],
+        suite: CipherSuite,
+    ) -> TransferResult<CryptoSession> {
         let peer_key_bytes: [u8; 32] = peer_public_key.try_into().map_err(|_| {
             TransferError::KeyExchange("对方公钥长度无效，期望 32 字节".to_string())
         })?;
@@ -90,33 +217,51 @@ impl KeyExchangeResponder {
         let peer_public = PublicKey::from(peer_key_bytes);
         let shared_secret: SharedSecret = self.secret.diffie_hellman(&peer_public);
 
-        CryptoSession::from_shared_secret(shared_secret.as_bytes())
+        CryptoSession::from_shared_secret(shared_secret.as_bytes(), suite)
     }
 }
 
 impl CryptoSession {
     /// 从共享密钥创建加密会话
-    fn from_shared_secret(shared_secret: &[u8; 32]) -> TransferResult<Self> {
-        let cipher = Aes256Gcm::new_from_slice(shared_secret)
-            .map_err(|e| TransferError::Encryption(format!("创建 AES-256-GCM 实例失败: {}", e)))?;
+    fn from_shared_secret(shared_secret: &[u8; 32], suite: CipherSuite) -> TransferResult<Self> {
+        let cipher = AeadCipher::new(suite, shared_secret).map_err(TransferError::Encryption)?;
+
+        let mut hasher = Sha256::new();
+        hasher.update(KEY_CONFIRMATION_CONTEXT);
+        hasher.update(suite.as_str().as_bytes());
+        hasher.update(shared_secret);
 
         Ok(Self {
             cipher,
+            suite,
             nonce_counter: 0,
+            key_confirmation: hasher.finalize().into(),
         })
     }
 
+    /// 密钥确认值（十六进制）
+    ///
+    /// 双方派生出相同的共享密钥和算法时该值一致，可在握手中交换以确认密钥协商成功，
+    /// 不泄露密钥本身
+    pub fn key_confirmation(&self) -> String {
+        hex::encode(self.key_confirmation)
+    }
+
+    /// 获取会话使用的加密算法
+    pub fn cipher_suite(&self) -> CipherSuite {
+        self.suite
+    }
+
     /// 加密数据
     ///
     /// 使用递增 nonce 加密数据，返回 nonce + 密文。
     /// 输出格式：[12 字节 nonce][密文 + 16 字节 tag]
     pub fn encrypt(&mut self, plaintext: &[u8]) -> TransferResult<Vec<u8>> {
         let nonce_bytes = self.next_nonce();
-        let nonce = Nonce::from(nonce_bytes);
 
         let ciphertext = self
             .cipher
-            .encrypt(&nonce, plaintext)
+            .encrypt(&nonce_bytes, plaintext)
             .map_err(|e| TransferError::Encryption(format!("加密失败: {}", e)))?;
 
         // 输出格式：nonce + ciphertext
@@ -142,10 +287,8 @@ impl CryptoSession {
         let nonce_array: [u8; NONCE_SIZE] = nonce_bytes.try_into().map_err(|_| {
             TransferError::Decryption("nonce 长度不正确".to_string())
         })?;
-        let nonce = Nonce::from(nonce_array);
-
         self.cipher
-            .decrypt(&nonce, ciphertext)
+            .decrypt(&nonce_array, ciphertext)
             .map_err(|e| TransferError::Decryption(format!("解密失败: {}", e)))
     }
 
@@ -162,6 +305,33 @@ impl CryptoSession {
     }
 }
 
+/// 由双方公钥派生 6 位数字短认证串（SAS）
+///
+/// 公钥按发起方在前、响应方在后拼接后经 HKDF-SHA256 派生。双方界面显示同一个数字时
+/// 说明公钥未被中间人替换，可由用户口头或目视比对。HTTP 分享中浏览器为发起方
+pub fn short_auth_string(initiator_public_key: &[u8], responder_public_key: &[u8]) -> String {
+    let mut ikm = Vec::with_capacity(initiator_public_key.len() + responder_public_key.len());
+    ikm.extend_from_slice(initiator_public_key);
+    ikm.extend_from_slice(responder_public_key);
+
+    let mut okm = [0u8; 4];
+    // 输出长度远小于上限，expand 不会失败
+    let _ = Hkdf::<Sha256>::new(None, &ikm).expand(SAS_CONTEXT, &mut okm);
+    format!("{:06}", u32::from_be_bytes(okm) % 1_000_000)
+}
+
+/// 握手完成后推送给前端的短认证串（`handshake-verification` 事件）
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HandshakeVerification {
+    /// 传输任务 ID（HTTP 分享中为加密会话 ID）
+    pub task_id: String,
+    /// 对端地址
+    pub peer: String,
+    /// 6 位短认证串
+    pub sas: String,
+}
+
 /// 加密设置状态（由前端同步到后端）
 static ENCRYPTION_ENABLED: std::sync::OnceLock<std::sync::RwLock<bool>> =
     std::sync::OnceLock::new();
@@ -182,6 +352,78 @@ pub fn set_encryption_enabled_internal(enabled: bool) {
     }
 }
 
+/// 首选加密算法设置（`None` 表示按平台自动选择）
+static PREFERRED_CIPHER: std::sync::OnceLock<std::sync::RwLock<Option<CipherSuite>>> =
+    std::sync::OnceLock::new();
+
+fn get_preferred_cipher_lock() -> &'static std::sync::RwLock<Option<CipherSuite>> {
+    PREFERRED_CIPHER.get_or_init(|| std::sync::RwLock::new(None))
+}
+
+/// 获取首选加密算法设置（`None` 表示自动）
+pub fn get_preferred_cipher_setting() -> Option<CipherSuite> {
+    get_preferred_cipher_lock().read().ok().and_then(|v| *v)
+}
+
+/// 设置首选加密算法（`None` 表示自动）
+pub fn set_preferred_cipher_internal(cipher: Option<CipherSuite>) {
+    if let Ok(mut lock) = get_preferred_cipher_lock().write() {
+        *lock = cipher;
+    }
+}
+
+/// 获取实际使用的首选加密算法
+///
+/// 自动模式下，有硬件 AES 指令时使用 AES-256-GCM，否则使用 ChaCha20-Poly1305
+pub fn preferred_cipher() -> CipherSuite {
+    get_preferred_cipher_setting().unwrap_or_else(|| {
+        if has_hardware_aes() {
+            CipherSuite::Aes256Gcm
+        } else {
+            CipherSuite::ChaCha20Poly1305
+        }
+    })
+}
+
+/// 本端支持的加密算法（按偏好排序，用于握手时告知对方）
+pub fn supported_ciphers() -> Vec<CipherSuite> {
+    match preferred_cipher() {
+        CipherSuite::Aes256Gcm => vec![CipherSuite::Aes256Gcm, CipherSuite::ChaCha20Poly1305],
+        CipherSuite::ChaCha20Poly1305 => {
+            vec![CipherSuite::ChaCha20Poly1305, CipherSuite::Aes256Gcm]
+        }
+    }
+}
+
+/// 根据对方提供的算法列表协商加密算法
+///
+/// 对方支持本端首选算法时使用首选算法，否则使用对方列表中的第一个；
+/// 对方未提供列表（旧版本客户端或浏览器）时回退到 AES-256-GCM
+pub fn negotiate_cipher(offered: &[CipherSuite]) -> CipherSuite {
+    let preferred = preferred_cipher();
+    if offered.contains(&preferred) {
+        preferred
+    } else {
+        offered.first().copied().unwrap_or_default()
+    }
+}
+
+/// 检测当前平台是否有硬件 AES 指令
+fn has_hardware_aes() -> bool {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        std::arch::is_x86_feature_detected!("aes")
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        std::arch::is_aarch64_feature_detected!("aes")
+    }
+    #[cfg(not(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64")))]
+    {
+        false
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -206,6 +448,47 @@ mod tests {
         assert_eq!(plaintext.to_vec(), decrypted);
     }
 
+    #[test]
+    fn test_key_confirmation() {
+        let initiator = KeyExchangeInitiator::new();
+        let responder = KeyExchangeResponder::new();
+        let initiator_pub = initiator.public_key_bytes();
+        let responder_pub = responder.public_key_bytes();
+
+        let session_a = initiator.complete(&responder_pub).unwrap();
+        let session_b = responder.complete(&initiator_pub).unwrap();
+        assert_eq!(session_a.key_confirmation(), session_b.key_confirmation());
+
+        // 公钥被替换（如中间人）时确认值不一致
+        let other = KeyExchangeInitiator::new();
+        let session_c = other.complete(&responder_pub).unwrap();
+        assert_ne!(session_a.key_confirmation(), session_c.key_confirmation());
+
+        // 算法不一致时确认值也不一致
+        let initiator = KeyExchangeInitiator::new();
+        let responder = KeyExchangeResponder::new();
+        let initiator_pub = initiator.public_key_bytes();
+        let session_d = initiator
+            .complete_with_cipher(&responder.public_key_bytes(), CipherSuite::ChaCha20Poly1305)
+            .unwrap();
+        let session_e = responder.complete(&initiator_pub).unwrap();
+        assert_ne!(session_d.key_confirmation(), session_e.key_confirmation());
+    }
+
+    #[test]
+    fn test_short_auth_string() {
+        // 固定向量，浏览器端（Web Crypto HKDF）须得到相同结果
+        assert_eq!(short_auth_string(&[1; 32], &[2; 32]), "774548");
+        // 公钥顺序有意义
+        assert_eq!(short_auth_string(&[2; 32], &[1; 32]), "473110");
+
+        let initiator = KeyExchangeInitiator::new();
+        let responder = KeyExchangeResponder::new();
+        let sas = short_auth_string(&initiator.public_key_bytes(), &responder.public_key_bytes());
+        assert_eq!(sas.len(), 6);
+        assert!(sas.bytes().all(|b| b.is_ascii_digit()));
+    }
+
     #[test]
     fn test_encrypt_decrypt_large_data() {
         let initiator = KeyExchangeInitiator::new();
@@ -225,10 +508,70 @@ mod tests {
         assert_eq!(plaintext, decrypted);
     }
 
+    #[test]
+    fn test_chacha20_encrypt_decrypt() {
+        let initiator = KeyExchangeInitiator::new();
+        let responder = KeyExchangeResponder::new();
+
+        let initiator_pub = initiator.public_key_bytes();
+        let responder_pub = responder.public_key_bytes();
+
+        let mut session_a = initiator
+            .complete_with_cipher(&responder_pub, CipherSuite::ChaCha20Poly1305)
+            .unwrap();
+        let session_b = responder
+            .complete_with_cipher(&initiator_pub, CipherSuite::ChaCha20Poly1305)
+            .unwrap();
+        assert_eq!(session_a.cipher_suite(), CipherSuite::ChaCha20Poly1305);
+
+        let plaintext = b"Hello, ChaCha!";
+        let encrypted = session_a.encrypt(plaintext).unwrap();
+        let decrypted = session_b.decrypt(&encrypted).unwrap();
+
+        assert_eq!(plaintext.to_vec(), decrypted);
+    }
+
+    #[test]
+    fn test_negotiate_cipher() {
+        // 旧版本对端 / 浏览器未提供列表时回退 AES
+        assert_eq!(negotiate_cipher(&[]), CipherSuite::Aes256Gcm);
+        assert_eq!(
+            negotiate_cipher(&[CipherSuite::Aes256Gcm]),
+            CipherSuite::Aes256Gcm
+        );
+
+        let preferred = preferred_cipher();
+        assert_eq!(
+            negotiate_cipher(&[CipherSuite::Aes256Gcm, CipherSuite::ChaCha20Poly1305]),
+            preferred
+        );
+    }
+
     #[test]
     fn test_invalid_key_length() {
         let initiator = KeyExchangeInitiator::new();
         let result = initiator.complete(&[0u8; 16]);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_unknown_cipher_suites_ignored() {
+        #[derive(serde::Deserialize)]
+        struct Payload {
+            #[serde(default, deserialize_with = "deserialize_cipher_suites")]
+            ciphers: Vec<CipherSuite>,
+        }
+
+        let payload: Payload = serde_json::from_str(
+            r#"{"ciphers":["xchacha20-poly1305","chacha20-poly1305","aes-256-gcm"]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            payload.ciphers,
+            vec![CipherSuite::ChaCha20Poly1305, CipherSuite::Aes256Gcm]
+        );
+
+        let legacy: Payload = serde_json::from_str("{}").unwrap();
+        assert!(legacy.ciphers.is_empty());
+    }
 }
@@ -0,0 +1,161 @@
+//! 协议特性协商模块
+//!
+//! 握手时双方以命名特性列表声明各自启用的可选行为，最终只使用双方都列出的特性。
+//! 未识别的特性名在解析时被忽略，旧版本对端不携带列表时回退到布尔字段协商
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// 协议可选特性
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProtocolFeature {
+    /// 端到端加密
+    Encryption,
+    /// 分块压缩
+    Compression,
+    /// 断点续传
+    Resume,
+    /// 加密算法协商
+    CipherNegotiation,
+    /// 握手响应携带接收端存储能力
+    ReceiveCapacity,
+    /// 传输结束后接收端回传文件哈希供发送端比对
+    VerifyAfterSend,
+    /// 同一连接上依次发送多个文件，握手后先发送批量清单
+    BatchTransfer,
+    /// 对端声明了本端无法识别的特性
+    #[serde(other)]
+    Unknown,
+}
+
+/// 反序列化特性列表，丢弃未识别的特性
+pub fn deserialize_features<'de, D>(
+    deserializer: D,
+) -> Result<Option<Vec<ProtocolFeature>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let features = Option::<Vec<ProtocolFeature>>::deserialize(deserializer)?;
+    Ok(features.map(|list| {
+        list.into_iter()
+            .filter(|feature| *feature != ProtocolFeature::Unknown)
+            .collect()
+    }))
+}
+
+/// 特性协商结果
+#[derive(Debug, Clone)]
+pub struct FeatureNegotiation {
+    /// 双方都启用的特性，对端未携带特性列表时为 None
+    agreed: Option<HashSet<ProtocolFeature>>,
+}
+
+impl FeatureNegotiation {
+    /// 根据本端提供的特性和对端响应的特性列表协商
+    pub fn new(offered: &[ProtocolFeature], peer: Option<&[ProtocolFeature]>) -> Self {
+        let agreed = peer.map(|peer| {
+            offered
+                .iter()
+                .filter(|feature| peer.contains(feature))
+                .copied()
+                .collect()
+        });
+        Self { agreed }
+    }
+
+    /// 是否允许使用某特性
+    ///
+    /// 对端未携带特性列表时不做限制，由原有的布尔字段决定
+    pub fn allows(&self, feature: ProtocolFeature) -> bool {
+        self.agreed
+            .as_ref()
+            .is_none_or(|agreed| agreed.contains(&feature))
+    }
+
+    /// 对端是否明确同意了某特性，对端未携带特性列表时为 false
+    ///
+    /// 用于需要对端发送新消息的特性，旧版本对端不会发送这些消息
+    pub fn peer_agreed(&self, feature: ProtocolFeature) -> bool {
+        self.agreed
+            .as_ref()
+            .is_some_and(|agreed| agreed.contains(&feature))
+    }
+
+    /// 双方都启用的特性（按固定顺序）
+    pub fn agreed(&self) -> Option<Vec<ProtocolFeature>> {
+        let agreed = self.agreed.as_ref()?;
+        Some(
+            offered_features(true, true)
+                .into_iter()
+                .filter(|feature| agreed.contains(feature))
+                .collect(),
+        )
+    }
+}
+
+/// 本端提供的特性列表
+pub fn offered_features(encryption: bool, compression: bool) -> Vec<ProtocolFeature> {
+    let mut features = Vec::new();
+    if encryption {
+        features.push(ProtocolFeature::Encryption);
+        features.push(ProtocolFeature::CipherNegotiation);
+    }
+    if compression {
+        features.push(ProtocolFeature::Compression);
+    }
+    features.push(ProtocolFeature::Resume);
+    features.push(ProtocolFeature::ReceiveCapacity);
+    features.push(ProtocolFeature::VerifyAfterSend);
+    features.push(ProtocolFeature::BatchTransfer);
+    features
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Payload {
+        #[serde(default, deserialize_with = "deserialize_features")]
+        features: Option<Vec<ProtocolFeature>>,
+    }
+
+    #[test]
+    fn test_unknown_features_ignored() {
+        let payload: Payload =
+            serde_json::from_str(r#"{"features":["resume","binary-frames","encryption"]}"#)
+                .unwrap();
+        assert_eq!(
+            payload.features,
+            Some(vec![ProtocolFeature::Resume, ProtocolFeature::Encryption])
+        );
+
+        let legacy: Payload = serde_json::from_str("{}").unwrap();
+        assert_eq!(legacy.features, None);
+    }
+
+    #[test]
+    fn test_negotiation() {
+        let offered = offered_features(true, false);
+        let peer = [ProtocolFeature::Encryption, ProtocolFeature::Compression];
+        let negotiation = FeatureNegotiation::new(&offered, Some(&peer[..]));
+        assert!(negotiation.allows(ProtocolFeature::Encryption));
+        // 本端未提供的特性即使对端支持也不使用
+        assert!(!negotiation.allows(ProtocolFeature::Compression));
+        assert!(!negotiation.allows(ProtocolFeature::Resume));
+        assert!(negotiation.peer_agreed(ProtocolFeature::Encryption));
+        assert!(!negotiation.peer_agreed(ProtocolFeature::VerifyAfterSend));
+        assert_eq!(
+            negotiation.agreed(),
+            Some(vec![ProtocolFeature::Encryption])
+        );
+
+        // 旧版本对端：不限制，交由布尔字段协商
+        let legacy = FeatureNegotiation::new(&offered, None);
+        assert_eq!(legacy.agreed(), None);
+        assert!(legacy.allows(ProtocolFeature::Compression));
+        // 需要对端发送新消息的特性不能假定旧版本对端支持
+        assert!(!legacy.peer_agreed(ProtocolFeature::VerifyAfterSend));
+    }
+}
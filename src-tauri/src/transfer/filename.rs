@@ -0,0 +1,273 @@
+//! 文件名安全处理模块
+//!
+//! 按目标平台规则将文件名中的非法字符、保留名称等映射为安全形式，
+//! 并在发送前检查文件名在对端可能遇到的问题
+
+use crate::error::{TransferError, TransferResult};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Windows 文件名非法字符
+const WINDOWS_ILLEGAL_CHARS: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+/// Windows 保留设备名（不区分大小写，带扩展名同样保留）
+const WINDOWS_RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// 非法字符的替换字符
+const REPLACEMENT_CHAR: char = '_';
+
+/// 文件名规则所属平台
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilenamePlatform {
+    /// Windows（规则最严格）
+    Windows,
+    /// Linux / macOS 等类 Unix 系统
+    Unix,
+}
+
+impl FilenamePlatform {
+    /// 当前运行平台
+    pub fn current() -> Self {
+        if cfg!(windows) {
+            Self::Windows
+        } else {
+            Self::Unix
+        }
+    }
+}
+
+/// 文件名问题
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilenameIssue {
+    /// 为空或仅由点组成
+    Empty,
+    /// 包含目标平台不允许的字符
+    IllegalCharacter,
+    /// 包含控制字符
+    ControlCharacter,
+    /// Windows 保留设备名
+    ReservedName,
+    /// 以点或空格结尾（Windows 会静默去除）
+    TrailingDotOrSpace,
+}
+
+/// 文件名检查结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilenameValidation {
+    /// 原始文件名
+    pub original_name: String,
+    /// 处理后的安全文件名
+    pub sanitized_name: String,
+    /// 检测到的问题
+    pub issues: Vec<FilenameIssue>,
+}
+
+fn is_illegal_char(c: char, platform: FilenamePlatform) -> bool {
+    match platform {
+        FilenamePlatform::Windows => WINDOWS_ILLEGAL_CHARS.contains(&c),
+        FilenamePlatform::Unix => c == '/',
+    }
+}
+
+fn is_reserved_name(name: &str) -> bool {
+    let stem = name.split('.').next().unwrap_or(name).trim_end();
+    WINDOWS_RESERVED_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(stem))
+}
+
+/// 检查文件名并给出目标平台下的安全文件名
+pub fn inspect_filename(name: &str, platform: FilenamePlatform) -> FilenameValidation {
+    let mut issues = Vec::new();
+    let mut push_issue = |issue: FilenameIssue| {
+        if !issues.contains(&issue) {
+            issues.push(issue);
+        }
+    };
+
+    let mut sanitized: String = name
+        .chars()
+        .map(|c| {
+            if c.is_control() {
+                push_issue(FilenameIssue::ControlCharacter);
+                REPLACEMENT_CHAR
+            } else if is_illegal_char(c, platform) {
+                push_issue(FilenameIssue::IllegalCharacter);
+                REPLACEMENT_CHAR
+            } else {
+                c
+            }
+        })
+        .collect();
+
+    if platform == FilenamePlatform::Windows {
+        let trimmed_len = sanitized.trim_end_matches(['.', ' ']).len();
+        if trimmed_len < sanitized.len() {
+            push_issue(FilenameIssue::TrailingDotOrSpace);
+            sanitized.truncate(trimmed_len);
+        }
+        if is_reserved_name(&sanitized) {
+            push_issue(FilenameIssue::ReservedName);
+            sanitized.insert(0, REPLACEMENT_CHAR);
+        }
+    }
+
+    if sanitized.trim_matches('.').trim().is_empty() {
+        push_issue(FilenameIssue::Empty);
+        sanitized = REPLACEMENT_CHAR.to_string();
+    }
+
+    FilenameValidation {
+        original_name: name.to_string(),
+        sanitized_name: sanitized,
+        issues,
+    }
+}
+
+/// 将文件名映射为目标平台下的安全文件名
+pub fn sanitize_filename(name: &str, platform: FilenamePlatform) -> String {
+    inspect_filename(name, platform).sanitized_name
+}
+
+/// 是否为 Windows 盘符（如 `C:`）
+fn is_drive_prefix(component: &str) -> bool {
+    let mut chars = component.chars();
+    matches!(
+        (chars.next(), chars.next()),
+        (Some(letter), Some(':')) if letter.is_ascii_alphabetic()
+    )
+}
+
+/// 根据对端提供的相对路径得到接收目录下的保存目录
+///
+/// 相对路径包含文件名本身，只取其中的目录部分并逐级转换为安全名称；为空时直接保存到接收目录。
+/// 包含 `..`、以根目录或盘符开头的路径会逃出接收目录，视为非法元数据
+pub fn resolve_relative_dir(
+    receive_dir: &Path,
+    relative_path: Option<&str>,
+    platform: FilenamePlatform,
+) -> TransferResult<PathBuf> {
+    let Some(relative_path) = relative_path.filter(|p| !p.is_empty()) else {
+        return Ok(receive_dir.to_path_buf());
+    };
+
+    let invalid = || TransferError::InvalidMetadata(format!("非法的相对路径：{}", relative_path));
+    if relative_path.starts_with(['/', '\\']) {
+        return Err(invalid());
+    }
+    let components: Vec<&str> = relative_path
+        .split(['/', '\\'])
+        .filter(|c| !c.is_empty() && *c != ".")
+        .collect();
+    if components.iter().any(|c| *c == "..")
+        || components.first().is_some_and(|c| is_drive_prefix(c))
+    {
+        return Err(invalid());
+    }
+
+    let mut dir = receive_dir.to_path_buf();
+    if let Some((_, parents)) = components.split_last() {
+        for component in parents {
+            dir.push(sanitize_filename(component, platform));
+        }
+    }
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn windows(name: &str) -> FilenameValidation {
+        inspect_filename(name, FilenamePlatform::Windows)
+    }
+
+    #[test]
+    fn test_reserved_windows_names() {
+        assert_eq!(windows("CON").sanitized_name, "_CON");
+        assert_eq!(windows("nul.txt").sanitized_name, "_nul.txt");
+        assert_eq!(windows("Com1.tar.gz").sanitized_name, "_Com1.tar.gz");
+        assert_eq!(windows("NUL").issues, vec![FilenameIssue::ReservedName]);
+        // 仅完全匹配的设备名才保留
+        assert!(windows("CONSOLE.txt").issues.is_empty());
+        assert!(windows("COM10").issues.is_empty());
+    }
+
+    #[test]
+    fn test_trailing_dots_and_spaces() {
+        let result = windows("report. . ");
+        assert_eq!(result.sanitized_name, "report");
+        assert_eq!(result.issues, vec![FilenameIssue::TrailingDotOrSpace]);
+
+        // 去除结尾后才暴露出的保留名
+        let result = windows("NUL.");
+        assert_eq!(result.sanitized_name, "_NUL");
+        assert_eq!(
+            result.issues,
+            vec![
+                FilenameIssue::TrailingDotOrSpace,
+                FilenameIssue::ReservedName
+            ]
+        );
+
+        assert_eq!(windows("...").sanitized_name, "_");
+        // 类 Unix 系统允许结尾的点和空格
+        assert!(inspect_filename("notes. ", FilenamePlatform::Unix)
+            .issues
+            .is_empty());
+    }
+
+    #[test]
+    fn test_illegal_characters() {
+        let result = windows("a:b\\c?.txt");
+        assert_eq!(result.sanitized_name, "a_b_c_.txt");
+        assert_eq!(result.original_name, "a:b\\c?.txt");
+        assert_eq!(result.issues, vec![FilenameIssue::IllegalCharacter]);
+
+        assert_eq!(
+            sanitize_filename("a:b\\c/d", FilenamePlatform::Unix),
+            "a:b\\c_d"
+        );
+        assert_eq!(
+            sanitize_filename("tab\tname", FilenamePlatform::Unix),
+            "tab_name"
+        );
+    }
+
+    #[test]
+    fn test_resolve_relative_dir() {
+        let base = Path::new("/recv");
+        let resolve =
+            |path: Option<&str>| resolve_relative_dir(base, path, FilenamePlatform::Windows);
+        assert_eq!(resolve(None).unwrap(), base);
+        assert_eq!(resolve(Some("")).unwrap(), base);
+        assert_eq!(resolve(Some("a.txt")).unwrap(), base);
+        assert_eq!(
+            resolve(Some("docs\\2024/./a.txt")).unwrap(),
+            base.join("docs").join("2024")
+        );
+        // 目录名同样按平台规则清理
+        assert_eq!(
+            resolve(Some("CON/a?b/x.txt")).unwrap(),
+            base.join("_CON").join("a_b")
+        );
+
+        for malicious in [
+            "../../etc",
+            "docs/../../a.txt",
+            "/etc/passwd",
+            "\\share\\a",
+            "C:/a",
+        ] {
+            assert!(matches!(
+                resolve(Some(malicious)),
+                Err(TransferError::InvalidMetadata(_))
+            ));
+        }
+    }
+}
@@ -1,13 +1,12 @@
 //! HTTP 传输加密模块
 //!
-//! 提供 P-256 ECDH 密钥交换和 AES-256-GCM 加密/解密功能，
+//! 提供 P-256 ECDH 密钥交换和 AES-256-GCM / ChaCha20-Poly1305 加密/解密功能，
 //! 用于保护 HTTP 传输模式下的文件数据。
 //!
 //! 与 P2P 模式使用 X25519 不同，HTTP 模式使用 P-256 ECDH
-//! 以兼容浏览器 Web Crypto API。
+//! 以兼容浏览器 Web Crypto API。浏览器不支持 ChaCha20-Poly1305，
+//! 未声明支持列表的客户端一律协商为 AES-256-GCM。
 
-use aes_gcm::aead::{Aead, KeyInit};
-use aes_gcm::{Aes256Gcm, Nonce};
 use base64::Engine;
 use hkdf::Hkdf;
 use p256::ecdh::EphemeralSecret;
@@ -19,28 +18,33 @@ use sha2::Sha256;
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
 
+use crate::transfer::crypto::{
+    deserialize_cipher_suites, negotiate_cipher, short_auth_string, AeadCipher, CipherSuite,
+};
+
 const NONCE_SIZE: usize = 12;
 const SESSION_EXPIRY: Duration = Duration::from_secs(3600);
 const HKDF_INFO: &[u8] = b"puresend-http-encryption";
 
 pub struct HttpCryptoSession {
-    cipher: Aes256Gcm,
+    cipher: AeadCipher,
+    suite: CipherSuite,
     nonce_counter: u64,
     created_at: Instant,
 }
 
 impl HttpCryptoSession {
-    fn new(shared_secret: &[u8]) -> Result<Self, String> {
+    fn new(shared_secret: &[u8], suite: CipherSuite) -> Result<Self, String> {
         let hk = Hkdf::<Sha256>::new(None, shared_secret);
         let mut key = [0u8; 32];
         hk.expand(HKDF_INFO, &mut key)
             .map_err(|e| format!("HKDF 密钥派生失败: {}", e))?;
 
-        let cipher = Aes256Gcm::new_from_slice(&key)
-            .map_err(|e| format!("创建 AES-256-GCM 实例失败: {}", e))?;
+        let cipher = AeadCipher::new(suite, &key)?;
 
         Ok(Self {
             cipher,
+            suite,
             nonce_counter: 0,
             created_at: Instant::now(),
         })
@@ -50,12 +54,15 @@ impl HttpCryptoSession {
         self.created_at.elapsed() > SESSION_EXPIRY
     }
 
+    pub fn cipher_suite(&self) -> CipherSuite {
+        self.suite
+    }
+
     pub fn encrypt(&mut self, plaintext: &[u8]) -> Result<Vec<u8>, String> {
         let nonce_bytes = self.next_nonce();
-        let nonce = Nonce::from(nonce_bytes);
 
         self.cipher
-            .encrypt(&nonce, plaintext)
+            .encrypt(&nonce_bytes, plaintext)
             .map_err(|e| format!("加密失败: {}", e))
             .map(|ciphertext| {
                 let mut output = Vec::with_capacity(NONCE_SIZE + ciphertext.len());
@@ -72,10 +79,9 @@ impl HttpCryptoSession {
 
         let (nonce_bytes, ciphertext) = encrypted_data.split_at(NONCE_SIZE);
         let nonce_array: [u8; NONCE_SIZE] = nonce_bytes.try_into().map_err(|_| "nonce 长度不正确".to_string())?;
-        let nonce = Nonce::from(nonce_array);
 
         self.cipher
-            .decrypt(&nonce, ciphertext)
+            .decrypt(&nonce_array, ciphertext)
             .map_err(|e| format!("解密失败: {}", e))
     }
 
@@ -93,6 +99,10 @@ impl HttpCryptoSession {
 #[derive(Debug, Deserialize)]
 pub struct HandshakeRequest {
     pub client_public_key: String,
+    /// Ciphers the client can use, in preference order. Empty for browsers (AES-GCM only).
+    /// Names this build does not know are dropped.
+    #[serde(default, deserialize_with = "deserialize_cipher_suites")]
+    pub supported_ciphers: Vec<CipherSuite>,
 }
 
 #[derive(Debug, Serialize)]
@@ -102,6 +112,8 @@ pub struct HandshakeResponse {
     pub server_public_key: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub session_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cipher: Option<CipherSuite>,
 }
 
 pub struct HttpCryptoSessionManager {
@@ -123,10 +135,12 @@ impl HttpCryptoSessionManager {
         }
     }
 
+    /// Returns the session ID, the server public key (Base64), the cipher and the SAS
     pub fn handshake(
         &mut self,
         client_public_key_b64: &str,
-    ) -> Result<(String, String), String> {
+        client_ciphers: &[CipherSuite],
+    ) -> Result<(String, String, CipherSuite, String), String> {
         let b64 = base64::engine::general_purpose::STANDARD;
 
         let client_pub_bytes = b64
@@ -141,15 +155,18 @@ impl HttpCryptoSessionManager {
 
         let shared_secret = server_secret.diffie_hellman(&client_public);
 
+        let suite = negotiate_cipher(client_ciphers);
         let session =
-            HttpCryptoSession::new(shared_secret.raw_secret_bytes().as_ref())?;
+            HttpCryptoSession::new(shared_secret.raw_secret_bytes().as_ref(), suite)?;
 
         let session_id = uuid::Uuid::new_v4().to_string();
-        let server_pub_b64 = b64.encode(server_public.to_sec1_bytes());
+        let server_pub_bytes = server_public.to_sec1_bytes();
+        let server_pub_b64 = b64.encode(&server_pub_bytes);
+        let sas = short_auth_string(&client_pub_bytes, &server_pub_bytes);
 
         self.sessions.insert(session_id.clone(), session);
 
-        Ok((session_id, server_pub_b64))
+        Ok((session_id, server_pub_b64, suite, sas))
     }
 
     pub fn get_session(&self, session_id: &str) -> Option<&HttpCryptoSession> {
@@ -163,6 +180,11 @@ impl HttpCryptoSessionManager {
         }
     }
 
+    /// Drop a session, e.g. after the user rejected its SAS. Returns false if it did not exist.
+    pub fn remove_session(&mut self, session_id: &str) -> bool {
+        self.sessions.remove(session_id).is_some()
+    }
+
     pub fn cleanup_expired(&mut self) {
         self.sessions.retain(|_, s| !s.is_expired());
     }
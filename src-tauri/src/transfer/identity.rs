@@ -0,0 +1,172 @@
+//! 设备身份模块
+//!
+//! 管理设备长期 X25519 密钥对，用于生成对端首次信任（TOFU）比对的设备指纹
+
+use crate::error::{TransferError, TransferResult};
+use rand::rngs::OsRng;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock, RwLock};
+use x25519_dalek::{PublicKey, StaticSecret};
+
+/// 密钥文件名
+const IDENTITY_KEY_FILENAME: &str = "device_identity.key";
+
+/// 指纹使用的摘要字节数
+const FINGERPRINT_BYTES: usize = 16;
+
+/// 设备长期身份密钥对
+pub struct DeviceIdentity {
+    secret: StaticSecret,
+    public_key: PublicKey,
+}
+
+impl DeviceIdentity {
+    /// 生成新的随机密钥对
+    pub fn generate() -> Self {
+        Self::from_secret(StaticSecret::random_from_rng(OsRng))
+    }
+
+    fn from_secret(secret: StaticSecret) -> Self {
+        let public_key = PublicKey::from(&secret);
+        Self { secret, public_key }
+    }
+
+    /// 设备指纹：公钥 SHA-256 前 16 字节，冒号分隔的大写十六进制
+    pub fn fingerprint(&self) -> String {
+        let digest = Sha256::digest(self.public_key.as_bytes());
+        digest[..FINGERPRINT_BYTES]
+            .iter()
+            .map(|b| format!("{:02X}", b))
+            .collect::<Vec<_>>()
+            .join(":")
+    }
+
+    /// 从密钥文件加载，文件不存在时返回 None
+    fn load(path: &Path) -> TransferResult<Option<Self>> {
+        let bytes = match std::fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let secret: [u8; 32] = bytes
+            .as_slice()
+            .try_into()
+            .map_err(|_| TransferError::Internal("设备密钥文件已损坏".to_string()))?;
+        Ok(Some(Self::from_secret(StaticSecret::from(secret))))
+    }
+
+    /// 写入密钥文件
+    ///
+    /// 先写临时文件再重命名，避免中途失败留下损坏的密钥
+    fn persist(&self, path: &Path) -> TransferResult<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp_path = path.with_extension("key.tmp");
+        std::fs::write(&tmp_path, self.secret.to_bytes())?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&tmp_path, std::fs::Permissions::from_mode(0o600))?;
+        }
+        std::fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    /// 加载已有密钥，不存在或损坏时生成并保存新密钥
+    fn load_or_create(path: &Path) -> Self {
+        match Self::load(path) {
+            Ok(Some(identity)) => return identity,
+            Ok(None) => {}
+            Err(e) => tracing::warn!(error = %e, "设备密钥无法读取，将重新生成"),
+        }
+        let identity = Self::generate();
+        if let Err(e) = identity.persist(path) {
+            tracing::warn!(error = %e, "设备密钥保存失败");
+        }
+        identity
+    }
+}
+
+/// 当前设备身份
+static DEVICE_IDENTITY: OnceLock<RwLock<Arc<DeviceIdentity>>> = OnceLock::new();
+
+fn get_identity_lock() -> &'static RwLock<Arc<DeviceIdentity>> {
+    DEVICE_IDENTITY.get_or_init(|| {
+        let path = identity_path();
+        RwLock::new(Arc::new(DeviceIdentity::load_or_create(&path)))
+    })
+}
+
+fn identity_path() -> PathBuf {
+    crate::app_data_dir().join(IDENTITY_KEY_FILENAME)
+}
+
+/// 获取当前设备身份
+///
+/// 返回快照，会话应在整个生命周期内持有同一个快照，
+/// 这样密钥轮换不会影响进行中的会话，之后的握手则使用新密钥
+pub fn current_device_identity() -> Arc<DeviceIdentity> {
+    get_identity_lock()
+        .read()
+        .map(|identity| identity.clone())
+        .unwrap_or_else(|e| e.into_inner().clone())
+}
+
+/// 生成并保存新的设备密钥对，返回新身份
+///
+/// 保存失败时保留旧密钥
+pub fn regenerate_device_identity() -> TransferResult<Arc<DeviceIdentity>> {
+    let identity = Arc::new(DeviceIdentity::generate());
+    identity.persist(&identity_path())?;
+    let mut current = get_identity_lock()
+        .write()
+        .unwrap_or_else(|e| e.into_inner());
+    *current = identity.clone();
+    Ok(identity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identity_persist_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(IDENTITY_KEY_FILENAME);
+
+        assert!(DeviceIdentity::load(&path).unwrap().is_none());
+
+        let created = DeviceIdentity::load_or_create(&path);
+        let loaded = DeviceIdentity::load(&path).unwrap().unwrap();
+        assert_eq!(created.fingerprint(), loaded.fingerprint());
+
+        let rotated = DeviceIdentity::generate();
+        rotated.persist(&path).unwrap();
+        let reloaded = DeviceIdentity::load_or_create(&path);
+        assert_eq!(rotated.fingerprint(), reloaded.fingerprint());
+        assert_ne!(created.fingerprint(), reloaded.fingerprint());
+    }
+
+    #[test]
+    fn test_corrupted_key_is_replaced() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(IDENTITY_KEY_FILENAME);
+        std::fs::write(&path, b"broken").unwrap();
+
+        assert!(DeviceIdentity::load(&path).is_err());
+        let identity = DeviceIdentity::load_or_create(&path);
+        let loaded = DeviceIdentity::load(&path).unwrap().unwrap();
+        assert_eq!(identity.fingerprint(), loaded.fingerprint());
+    }
+
+    #[test]
+    fn test_fingerprint_format() {
+        let fingerprint = DeviceIdentity::generate().fingerprint();
+        assert_eq!(fingerprint.split(':').count(), FINGERPRINT_BYTES);
+        assert!(fingerprint
+            .chars()
+            .all(|c| c == ':' || (c.is_ascii_hexdigit() && !c.is_ascii_lowercase())));
+    }
+}
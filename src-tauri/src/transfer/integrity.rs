@@ -2,11 +2,176 @@
 //!
 //! 提供文件传输前后的数据完整性验证
 
-use crate::error::TransferResult;
+use crate::error::{TransferError, TransferResult};
+use crate::models::FileMetadata;
 use crate::transfer::FileChunker;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha512};
+use std::io::Read;
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{OnceLock, RwLock};
+
+/// 流式计算文件哈希时每次读取的字节数
+const HASH_READ_BUFFER_SIZE: usize = 256 * 1024;
+
+/// 文件哈希算法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HashAlgorithm {
+    /// SHA-256（传输校验的默认算法，所有版本都支持）
+    #[default]
+    Sha256,
+    /// SHA-512
+    Sha512,
+    /// BLAKE3，计算大文件哈希时比 SHA-256 快得多
+    Blake3,
+}
+
+impl HashAlgorithm {
+    /// 算法名称（与 `FileMetadata::hash_algorithm` 一致）
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Sha256 => "sha256",
+            Self::Sha512 => "sha512",
+            Self::Blake3 => "blake3",
+        }
+    }
+
+    /// 从算法名称解析
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "sha256" => Some(Self::Sha256),
+            "sha512" => Some(Self::Sha512),
+            "blake3" => Some(Self::Blake3),
+            _ => None,
+        }
+    }
+
+    /// 解析元数据中的哈希算法，无法识别时返回错误
+    pub fn from_metadata(metadata: &FileMetadata) -> TransferResult<Self> {
+        Self::from_name(&metadata.hash_algorithm).ok_or_else(|| {
+            TransferError::InvalidMetadata(format!("不支持的哈希算法: {}", metadata.hash_algorithm))
+        })
+    }
+
+    /// 计算数据的哈希值，返回十六进制字符串
+    pub fn hash_data(self, data: &[u8]) -> String {
+        let mut hasher = StreamHasher::new(self);
+        hasher.update(data);
+        hasher.finalize_hex()
+    }
+
+    /// 计算整个文件的哈希值，返回十六进制字符串
+    pub fn hash_file(self, file_path: &Path) -> TransferResult<String> {
+        self.hash_file_with_progress(file_path, |_| {})
+    }
+
+    /// 计算整个文件的哈希值，每读取一段后以已处理的字节数调用 `on_progress`
+    pub fn hash_file_with_progress(
+        self,
+        file_path: &Path,
+        on_progress: impl FnMut(u64),
+    ) -> TransferResult<String> {
+        let file = std::fs::File::open(file_path)?;
+        stream_digest(file, self, &AtomicBool::new(false), on_progress)
+    }
+}
+
+/// 本端支持的哈希算法，握手时告知对端
+pub fn supported_hash_algorithms() -> Vec<HashAlgorithm> {
+    vec![
+        HashAlgorithm::Sha256,
+        HashAlgorithm::Sha512,
+        HashAlgorithm::Blake3,
+    ]
+}
+
+static HASH_ALGORITHM_SETTING: OnceLock<RwLock<HashAlgorithm>> = OnceLock::new();
+
+fn get_hash_algorithm_lock() -> &'static RwLock<HashAlgorithm> {
+    HASH_ALGORITHM_SETTING.get_or_init(|| RwLock::new(HashAlgorithm::default()))
+}
+
+/// 获取准备发送文件时使用的哈希算法
+pub fn transfer_hash_algorithm() -> HashAlgorithm {
+    get_hash_algorithm_lock()
+        .read()
+        .map(|v| *v)
+        .unwrap_or_default()
+}
+
+/// 设置准备发送文件时使用的哈希算法
+pub fn set_transfer_hash_algorithm_internal(algorithm: HashAlgorithm) {
+    if let Ok(mut value) = get_hash_algorithm_lock().write() {
+        *value = algorithm;
+    }
+}
+
+/// 按算法分派的增量哈希计算
+enum StreamHasher {
+    Sha256(Sha256),
+    Sha512(Sha512),
+    Blake3(Box<blake3::Hasher>),
+}
+
+impl StreamHasher {
+    fn new(algorithm: HashAlgorithm) -> Self {
+        match algorithm {
+            HashAlgorithm::Sha256 => Self::Sha256(Sha256::new()),
+            HashAlgorithm::Sha512 => Self::Sha512(Sha512::new()),
+            HashAlgorithm::Blake3 => Self::Blake3(Box::new(blake3::Hasher::new())),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Self::Sha256(hasher) => hasher.update(data),
+            Self::Sha512(hasher) => hasher.update(data),
+            Self::Blake3(hasher) => {
+                hasher.update(data);
+            }
+        }
+    }
+
+    fn finalize_hex(self) -> String {
+        match self {
+            Self::Sha256(hasher) => hex::encode(hasher.finalize()),
+            Self::Sha512(hasher) => hex::encode(hasher.finalize()),
+            Self::Blake3(hasher) => hasher.finalize().to_hex().to_string(),
+        }
+    }
+}
+
+/// 接收端校验模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerifyMode {
+    /// 不校验
+    Off,
+    /// 逐块校验分块哈希
+    PerChunk,
+    /// 接收完成后校验整个文件哈希
+    #[default]
+    FullFile,
+    /// 逐块校验并在完成后校验整个文件
+    Both,
+}
+
+impl VerifyMode {
+    /// 是否在接收每个分块时校验
+    pub fn verifies_chunks(self) -> bool {
+        matches!(self, Self::PerChunk | Self::Both)
+    }
+
+    /// 是否在接收完成后校验整个文件
+    pub fn verifies_file(self) -> bool {
+        matches!(self, Self::FullFile | Self::Both)
+    }
+}
 
 /// 完整性校验器
+#[derive(Clone)]
 pub struct IntegrityChecker {
     chunker: FileChunker,
 }
@@ -21,18 +186,137 @@ impl IntegrityChecker {
 
     /// 验证文件完整性
     ///
-    /// 比较文件的实际哈希与期望哈希
+    /// 按 `algorithm` 计算文件的实际哈希并与期望哈希比较
     ///
     /// # Arguments
     /// * `file_path` - 文件路径
-    /// * `expected_hash` - 期望的 SHA256 哈希值
+    /// * `expected_hash` - 期望的哈希值
+    /// * `algorithm` - 期望哈希使用的算法
     ///
     /// # Returns
     /// * `TransferResult<bool>` - 校验结果
-    pub fn verify_file(&self, file_path: &Path, expected_hash: &str) -> TransferResult<bool> {
-        let actual_hash = self.chunker.compute_file_hash(file_path)?;
+    pub fn verify_file(
+        &self,
+        file_path: &Path,
+        expected_hash: &str,
+        algorithm: HashAlgorithm,
+    ) -> TransferResult<bool> {
+        let actual_hash = algorithm.hash_file(file_path)?;
         Ok(actual_hash == expected_hash)
     }
+
+    /// 按校验模式验证单个接收到的分块
+    ///
+    /// 模式不要求逐块校验或对方未提供分块哈希时视为通过
+    pub fn verify_received_chunk(
+        &self,
+        data: &[u8],
+        expected_hash: &str,
+        algorithm: HashAlgorithm,
+        mode: VerifyMode,
+    ) -> bool {
+        if !mode.verifies_chunks() || expected_hash.is_empty() {
+            return true;
+        }
+        algorithm.hash_data(data) == expected_hash
+    }
+
+    /// 按校验模式验证接收完成的文件
+    ///
+    /// # Returns
+    /// * `TransferResult<Option<bool>>` - 校验结果，模式不要求整体校验时返回 `None`
+    pub fn verify_received_file(
+        &self,
+        file_path: &Path,
+        expected_hash: &str,
+        algorithm: HashAlgorithm,
+        mode: VerifyMode,
+    ) -> TransferResult<Option<bool>> {
+        if !mode.verifies_file() {
+            return Ok(None);
+        }
+        self.verify_file(file_path, expected_hash, algorithm)
+            .map(Some)
+    }
+
+    /// 验证部分文件的完整性
+    ///
+    /// 按 `metadata.chunks` 中的偏移量依次读取前 `up_to_chunk` 个分块，
+    /// 与期望的分块哈希比较，遇到第一个不匹配（或缺少哈希）的分块即停止
+    ///
+    /// # Arguments
+    /// * `file_path` - 部分文件路径
+    /// * `metadata` - 包含分块哈希的文件元数据
+    /// * `up_to_chunk` - 最多校验的分块数量
+    ///
+    /// # Returns
+    /// * `TransferResult<Option<u32>>` - 最后一个连续校验通过的分块索引，
+    ///   第一个分块即校验失败时返回 `None`
+    pub fn verify_partial_file(
+        &self,
+        file_path: &Path,
+        metadata: &FileMetadata,
+        up_to_chunk: u32,
+    ) -> TransferResult<Option<u32>> {
+        let algorithm = HashAlgorithm::from_metadata(metadata)?;
+        let file_size = std::fs::metadata(file_path)?.len();
+        let mut last_verified = None;
+
+        for chunk in metadata.chunks.iter().take(up_to_chunk as usize) {
+            if chunk.hash.is_empty() || chunk.offset + chunk.size > file_size {
+                break;
+            }
+
+            let data = self.chunker.read_chunk(file_path, chunk)?;
+            if algorithm.hash_data(&data) != chunk.hash {
+                break;
+            }
+
+            last_verified = Some(chunk.index);
+        }
+
+        Ok(last_verified)
+    }
+
+    /// 流式计算文件哈希，返回十六进制字符串
+    ///
+    /// 每读取一段后以已处理的字节数调用 `on_progress`；
+    /// `cancelled` 被置位时中止并返回 `TransferError::Cancelled`
+    pub fn compute_file_hash(
+        &self,
+        file_path: &Path,
+        algorithm: HashAlgorithm,
+        cancelled: &AtomicBool,
+        on_progress: impl FnMut(u64),
+    ) -> TransferResult<String> {
+        let file = std::fs::File::open(file_path)?;
+        stream_digest(file, algorithm, cancelled, on_progress)
+    }
+}
+
+/// 将读取器的全部内容送入摘要计算
+fn stream_digest(
+    mut reader: impl Read,
+    algorithm: HashAlgorithm,
+    cancelled: &AtomicBool,
+    mut on_progress: impl FnMut(u64),
+) -> TransferResult<String> {
+    let mut hasher = StreamHasher::new(algorithm);
+    let mut buffer = vec![0u8; HASH_READ_BUFFER_SIZE];
+    let mut processed = 0u64;
+    loop {
+        if cancelled.load(Ordering::Relaxed) {
+            return Err(TransferError::Cancelled);
+        }
+        let bytes_read = reader.read(&mut buffer)?;
+        if bytes_read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..bytes_read]);
+        processed += bytes_read as u64;
+        on_progress(processed);
+    }
+    Ok(hasher.finalize_hex())
 }
 
 impl Default for IntegrityChecker {
@@ -55,9 +339,153 @@ mod tests {
         temp_file.flush().unwrap();
 
         let hash = checker.chunker.compute_file_hash(temp_file.path()).unwrap();
-        assert!(checker.verify_file(temp_file.path(), &hash).unwrap());
+        assert!(checker
+            .verify_file(temp_file.path(), &hash, HashAlgorithm::Sha256)
+            .unwrap());
         assert!(!checker
-            .verify_file(temp_file.path(), "invalid_hash")
+            .verify_file(temp_file.path(), "invalid_hash", HashAlgorithm::Sha256)
             .unwrap());
     }
+
+    #[test]
+    fn test_verify_modes() {
+        let checker = IntegrityChecker::new();
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(b"test content").unwrap();
+        temp_file.flush().unwrap();
+
+        let hash = checker.chunker.compute_file_hash(temp_file.path()).unwrap();
+        let path = temp_file.path();
+        let sha256 = HashAlgorithm::Sha256;
+
+        assert_eq!(
+            checker
+                .verify_received_file(path, "bad", sha256, VerifyMode::Off)
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            checker
+                .verify_received_file(path, "bad", sha256, VerifyMode::PerChunk)
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            checker
+                .verify_received_file(path, &hash, sha256, VerifyMode::FullFile)
+                .unwrap(),
+            Some(true)
+        );
+        assert_eq!(
+            checker
+                .verify_received_file(path, "bad", sha256, VerifyMode::Both)
+                .unwrap(),
+            Some(false)
+        );
+
+        let chunk_hash = FileChunker::compute_hash(b"chunk");
+        assert!(checker.verify_received_chunk(b"other", &chunk_hash, sha256, VerifyMode::FullFile));
+        assert!(checker.verify_received_chunk(b"chunk", &chunk_hash, sha256, VerifyMode::PerChunk));
+        assert!(!checker.verify_received_chunk(b"other", &chunk_hash, sha256, VerifyMode::Both));
+    }
+
+    #[test]
+    fn test_compute_file_hash() {
+        let checker = IntegrityChecker::new();
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(b"test content").unwrap();
+        temp_file.flush().unwrap();
+        let path = temp_file.path();
+
+        let mut progress = 0;
+        let cancelled = AtomicBool::new(false);
+        let sha256 = checker
+            .compute_file_hash(path, HashAlgorithm::Sha256, &cancelled, |n| progress = n)
+            .unwrap();
+        assert_eq!(sha256, checker.chunker.compute_file_hash(path).unwrap());
+        assert_eq!(progress, 12);
+
+        let sha512 = checker
+            .compute_file_hash(path, HashAlgorithm::Sha512, &cancelled, |_| {})
+            .unwrap();
+        assert_eq!(sha512.len(), 128);
+
+        cancelled.store(true, Ordering::Relaxed);
+        assert!(matches!(
+            checker.compute_file_hash(path, HashAlgorithm::Sha256, &cancelled, |_| {}),
+            Err(TransferError::Cancelled)
+        ));
+    }
+
+    #[test]
+    fn test_hash_algorithm_dispatch_on_file() {
+        let checker = IntegrityChecker::new();
+        let mut temp_file = NamedTempFile::new().unwrap();
+        let block: Vec<u8> = (0..4096).map(|i| (i % 251) as u8).collect();
+        temp_file.write_all(&block).unwrap();
+        temp_file.flush().unwrap();
+        let path = temp_file.path();
+
+        let sha256 = HashAlgorithm::Sha256.hash_file(path).unwrap();
+        let blake3 = HashAlgorithm::Blake3.hash_file(path).unwrap();
+
+        assert_eq!(sha256, checker.chunker.compute_file_hash(path).unwrap());
+        assert_eq!(blake3.len(), 64);
+        assert_ne!(sha256, blake3);
+        assert!(checker
+            .verify_file(path, &blake3, HashAlgorithm::Blake3)
+            .unwrap());
+        assert!(!checker
+            .verify_file(path, &blake3, HashAlgorithm::Sha256)
+            .unwrap());
+        assert_eq!(blake3, blake3::hash(&block).to_hex().to_string());
+        assert_eq!(HashAlgorithm::Blake3.hash_data(&block), blake3);
+    }
+
+    #[test]
+    fn test_verify_partial_file() {
+        let checker = IntegrityChecker::new();
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(b"aaaabbbbccccdd").unwrap();
+        temp_file.flush().unwrap();
+
+        let metadata = FileMetadata::new("partial.bin".to_string(), 14, String::new());
+        let metadata = FileChunker::new(4)
+            .compute_metadata_with_hashes(metadata, temp_file.path())
+            .unwrap();
+
+        assert_eq!(
+            checker
+                .verify_partial_file(temp_file.path(), &metadata, 4)
+                .unwrap(),
+            Some(3)
+        );
+        assert_eq!(
+            checker
+                .verify_partial_file(temp_file.path(), &metadata, 2)
+                .unwrap(),
+            Some(1)
+        );
+
+        // 破坏第 3 个分块后，只有前两个分块可信
+        let mut partial = NamedTempFile::new().unwrap();
+        partial.write_all(b"aaaabbbbcc").unwrap();
+        partial.flush().unwrap();
+        assert_eq!(
+            checker
+                .verify_partial_file(partial.path(), &metadata, 4)
+                .unwrap(),
+            Some(1)
+        );
+
+        let mut corrupted = NamedTempFile::new().unwrap();
+        corrupted.write_all(b"xaaabbbb").unwrap();
+        corrupted.flush().unwrap();
+        assert_eq!(
+            checker
+                .verify_partial_file(corrupted.path(), &metadata, 4)
+                .unwrap(),
+            None
+        );
+    }
 }
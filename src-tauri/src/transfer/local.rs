@@ -3,9 +3,11 @@
 //! 基于 TCP 的本地网络文件传输
 
 use async_trait::async_trait;
-use std::collections::HashMap;
+use futures::StreamExt;
+use std::collections::{HashMap, VecDeque};
 use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::{TcpListener, TcpStream};
@@ -13,7 +15,17 @@ use tokio::sync::{mpsc, Mutex, RwLock};
 
 use crate::error::{TransferError, TransferResult};
 use crate::models::{TransferMode, TransferProgress, TransferTask};
-use crate::transfer::{FileChunker, IntegrityChecker, Transport};
+use crate::models::ChunkInfo;
+use crate::transfer::compression::{
+    supported_compression_algorithms, CompressionAlgorithm, Compressor,
+};
+use crate::transfer::crypto::{deserialize_cipher_suites, CipherSuite, HandshakeVerification};
+use crate::transfer::features::{
+    deserialize_features, offered_features, FeatureNegotiation, ProtocolFeature,
+};
+use crate::transfer::{
+    supported_hash_algorithms, FileChunker, HashAlgorithm, IntegrityChecker, Transport,
+};
 
 /// 接收配置
 #[allow(dead_code)]
@@ -25,8 +37,149 @@ pub struct ReceiveConfig {
     pub file_overwrite: bool,
     /// 接收目录
     pub receive_directory: PathBuf,
+    /// 自动接收的文件大小上限（字节），未开启自动接收时不超过该大小的文件仍自动接收
+    ///
+    /// 尚未生效：接收处理落地前不读取该值，界面也不提供该设置
+    pub max_auto_accept_size: Option<u64>,
+}
+
+/// 接收端存储能力
+///
+/// 握手响应中携带，发送方据此在传输开始前判断对方能否存下文件
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceiveCapacity {
+    /// 接收目录
+    pub receive_directory: String,
+    /// 可用空间（字节），无法读取时为空
+    #[serde(default)]
+    pub available_bytes: Option<u64>,
+    /// 接收目录是否可写
+    pub writable: bool,
+}
+
+impl ReceiveCapacity {
+    /// 检测指定接收目录的可用空间与可写性
+    ///
+    /// 不创建目录：目录尚未创建时检测最近的已存在上级目录，接收时会在其下创建接收目录
+    pub fn probe(directory: &std::path::Path) -> Self {
+        let probe_dir = crate::transfer::nearest_existing_dir(directory);
+        let test_file = probe_dir.join(format!(".puresend_write_test_{}", uuid::Uuid::new_v4()));
+        let writable = std::fs::File::create(&test_file).is_ok();
+        let _ = std::fs::remove_file(&test_file);
+
+        Self {
+            receive_directory: directory.to_string_lossy().to_string(),
+            available_bytes: crate::transfer::available_space(directory),
+            writable,
+        }
+    }
+
+    /// 检查是否能存下指定大小的文件
+    pub fn check(&self, required_bytes: u64) -> TransferResult<()> {
+        if !self.writable {
+            return Err(TransferError::PermissionDenied(format!(
+                "对方接收目录不可写: {}",
+                self.receive_directory
+            )));
+        }
+        // 可用空间未知时不拦截，由写入时的错误处理
+        if let Some(available) = self.available_bytes.filter(|&a| a < required_bytes) {
+            return Err(TransferError::DiskFull(format!(
+                "对方可用空间不足：需要 {} 字节，剩余 {} 字节",
+                required_bytes, available
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// 对端连接测试的超时时间（秒）
+const CONNECTION_TEST_TIMEOUT_SECS: u64 = 3;
+
+/// 监听器存活检查的超时时间（秒）
+const LISTENER_PROBE_TIMEOUT_SECS: u64 = 2;
+
+/// 对端连接测试结果
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionTestResult {
+    /// 端口是否可连接
+    pub reachable: bool,
+    /// 是否为 PureSend 传输端点（完成了握手）
+    pub is_puresend: bool,
+    /// 建立 TCP 连接耗时（毫秒）
+    pub latency_ms: Option<u64>,
+    /// 对方协议版本
+    pub protocol_version: Option<u8>,
+    /// 对方是否启用加密
+    pub encryption: Option<bool>,
+    /// 对方是否启用压缩
+    pub compression: Option<bool>,
+    /// 对方是否支持断点续传
+    pub resume: Option<bool>,
+    /// 对方选定的加密算法
+    pub cipher: Option<CipherSuite>,
+    /// 对方接收目录的存储能力
+    pub receive_capacity: Option<ReceiveCapacity>,
+    /// 对方启用的可选协议特性（旧版本对端不提供）
+    pub features: Option<Vec<ProtocolFeature>>,
+    /// 失败原因
+    pub error: Option<String>,
+}
+
+/// 加密会话校验结果（调试用）
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionVerificationResult {
+    /// 是否完成握手
+    pub handshake_completed: bool,
+    /// 是否协商使用加密
+    pub encryption: bool,
+    /// 是否协商使用压缩
+    pub compression: bool,
+    /// 协商的加密算法
+    pub cipher: Option<CipherSuite>,
+    /// 双方都启用的可选协议特性（旧版本对端不提供）
+    pub features: Option<Vec<ProtocolFeature>>,
+    /// 双方派生的会话密钥是否一致，对端不支持密钥确认时为空
+    pub key_match: Option<bool>,
+    /// 本端会话指纹（密钥确认值前 16 位），可与对端显示的值人工比对
+    pub session_fingerprint: Option<String>,
+    /// 失败原因
+    pub error: Option<String>,
 }
 
+/// 超过该时长未收到分块确认即视为停滞（秒）
+const STALL_THRESHOLD_SECS: u64 = 10;
+
+/// 超过该时长未收到分块确认即视为连接已断开，任务转为中断（秒）
+const STALL_TIMEOUT_SECS: u64 = 60;
+
+/// 停滞检测间隔（秒）
+const STALL_CHECK_INTERVAL_SECS: u64 = 2;
+
+/// 对端分块校验失败时同一分块的最大重发次数
+const MAX_CHUNK_RESENDS: u32 = 3;
+
+/// 暂停超过该时长后放弃保持连接，任务转为中断（秒）
+const PAUSE_TIMEOUT_SECS: u64 = 60;
+
+/// 暂停期间发送心跳的间隔（秒）
+const PAUSE_HEARTBEAT_INTERVAL_SECS: u64 = 5;
+
+/// 暂停期间检查是否已恢复的间隔（毫秒）
+const PAUSE_POLL_INTERVAL_MS: u64 = 200;
+
+/// 等待接收端回传文件哈希的超时时间（秒），接收端需要先读完整个文件计算哈希
+const TRANSFER_COMPLETE_TIMEOUT_SECS: u64 = 120;
+
+/// 分块预处理流水线的最大并行度
+const MAX_PIPELINE_DEPTH: usize = 8;
+
+/// 多设备发送时每个设备的分块缓冲上限，慢设备最多落后这么多分块后才会拖慢其他设备
+const FANOUT_BUFFER_CHUNKS: usize = 8;
+
 /// 传输协议魔数
 const PROTOCOL_MAGIC: &[u8; 4] = b"PSEN";
 
@@ -55,6 +208,10 @@ enum MessageType {
     Handshake = 0x08,
     /// 握手响应（v2）
     HandshakeAck = 0x09,
+    /// 传输完成确认，携带接收端计算的文件哈希
+    TransferComplete = 0x0A,
+    /// 批量清单，握手后发送，说明该连接上将依次发送的文件数与总大小
+    BatchManifest = 0x0B,
 }
 
 /// 消息头
@@ -102,6 +259,8 @@ impl MessageHeader {
             0x07 => MessageType::Error,
             0x08 => MessageType::Handshake,
             0x09 => MessageType::HandshakeAck,
+            0x0A => MessageType::TransferComplete,
+            0x0B => MessageType::BatchManifest,
             _ => return Err(TransferError::Network("未知的消息类型".to_string())),
         };
 
@@ -126,6 +285,9 @@ impl MessageHeader {
 }
 
 /// 本地传输实现
+///
+/// 内部状态均为共享引用，克隆后的实例操作同一组任务
+#[derive(Clone)]
 pub struct LocalTransport {
     /// 监听端口
     listen_port: u16,
@@ -142,8 +304,72 @@ pub struct LocalTransport {
     initialized: Arc<Mutex<bool>>,
     /// 取消信号发送器
     cancel_senders: Arc<RwLock<HashMap<String, mpsc::Sender<()>>>>,
+    /// 发送中任务的暂停标志
+    pause_flags: Arc<RwLock<HashMap<String, Arc<AtomicBool>>>>,
     /// 接收配置
     receive_config: Arc<RwLock<Option<ReceiveConfig>>>,
+    /// 进度事件发送器（由命令层转发给前端）
+    progress_sender: Arc<RwLock<Option<mpsc::UnboundedSender<TransferProgress>>>>,
+    /// 握手短认证串发送器（由命令层转发给前端）
+    verification_sender: Arc<RwLock<Option<mpsc::UnboundedSender<HandshakeVerification>>>>,
+}
+
+/// 计算瞬时速度的滚动窗口时长（秒）
+const SPEED_WINDOW_SECS: u64 = 5;
+
+/// 最近一段时间内确认的字节数，用于计算瞬时速度
+///
+/// 按累计平均计算的速度在起步较慢时长期偏低，预估剩余时间随之失真；
+/// 窗口内没有任何确认（停滞）时速度为 0
+#[derive(Debug, Clone)]
+struct SpeedWindow {
+    /// 开始计时的时间，窗口未满时以此计算时长
+    started: std::time::Instant,
+    /// 窗口内的（确认时间, 字节数）
+    samples: VecDeque<(std::time::Instant, u64)>,
+}
+
+impl SpeedWindow {
+    fn new(now: std::time::Instant) -> Self {
+        Self {
+            started: now,
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// 重新开始计时（暂停恢复后调用，暂停时长不计入速度）
+    fn restart(&mut self, now: std::time::Instant) {
+        *self = Self::new(now);
+    }
+
+    /// 记录 `now` 时确认的字节数
+    fn record(&mut self, now: std::time::Instant, bytes: u64) {
+        self.samples.push_back((now, bytes));
+        let window = std::time::Duration::from_secs(SPEED_WINDOW_SECS);
+        while self
+            .samples
+            .front()
+            .is_some_and(|(at, _)| now.saturating_duration_since(*at) >= window)
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    /// 窗口内的平均速度（字节/秒）
+    fn speed(&self, now: std::time::Instant) -> u64 {
+        let window = std::time::Duration::from_secs(SPEED_WINDOW_SECS);
+        let span = now.saturating_duration_since(self.started).min(window);
+        if span.is_zero() {
+            return 0;
+        }
+        let bytes: u64 = self
+            .samples
+            .iter()
+            .filter(|(at, _)| now.saturating_duration_since(*at) < window)
+            .map(|(_, bytes)| bytes)
+            .sum();
+        (bytes as f64 / span.as_secs_f64()) as u64
+    }
 }
 
 /// 传输任务状态
@@ -153,20 +379,72 @@ struct TransferTaskState {
     progress: TransferProgress,
     /// 是否已取消
     cancelled: bool,
+    /// 速度滚动窗口
+    speed_window: SpeedWindow,
+}
+
+impl TransferTaskState {
+    fn new(progress: TransferProgress) -> Self {
+        Self {
+            progress,
+            cancelled: false,
+            speed_window: SpeedWindow::new(std::time::Instant::now()),
+        }
+    }
+
+    /// 按滚动窗口刷新速度和预估剩余时间，速度为 0 时剩余时间未知
+    fn refresh_speed(&mut self, now: std::time::Instant) {
+        let speed = self.speed_window.speed(now);
+        let remaining = self
+            .progress
+            .total_bytes
+            .saturating_sub(self.progress.transferred_bytes);
+        self.progress.speed = speed;
+        self.progress.estimated_time_remaining = (speed > 0).then(|| remaining / speed);
+    }
 }
 
 impl LocalTransport {
+    /// 取消所有进行中的发送任务，返回被取消的任务 ID
+    ///
+    /// 一次取出全部取消信号发送器；接收端已关闭说明任务已结束，不计入结果
+    pub async fn cancel_all(&self) -> Vec<String> {
+        let senders: Vec<(String, mpsc::Sender<()>)> =
+            self.cancel_senders.write().await.drain().collect();
+
+        let mut cancelled = Vec::new();
+        let mut tasks = self.active_tasks.write().await;
+        for (task_id, sender) in senders {
+            match sender.try_send(()) {
+                // 通道已满表示已收到过取消信号，同样视为取消中
+                Ok(()) | Err(mpsc::error::TrySendError::Full(())) => {
+                    if let Some(task_state) = tasks.get_mut(&task_id) {
+                        task_state.cancelled = true;
+                        task_state.progress.status = crate::models::TaskStatus::Cancelled;
+                    }
+                    cancelled.push(task_id);
+                }
+                Err(mpsc::error::TrySendError::Closed(())) => {}
+            }
+        }
+
+        cancelled
+    }
+
     /// 创建新的本地传输实例
     pub fn new() -> Self {
         Self {
             listen_port: 0, // 自动分配端口
-            chunker: FileChunker::default_chunker(),
+            chunker: FileChunker::from_config(),
             checker: IntegrityChecker::new(),
             active_tasks: Arc::new(RwLock::new(HashMap::new())),
             listener: Arc::new(Mutex::new(None)),
             initialized: Arc::new(Mutex::new(false)),
             cancel_senders: Arc::new(RwLock::new(HashMap::new())),
+            pause_flags: Arc::new(RwLock::new(HashMap::new())),
             receive_config: Arc::new(RwLock::new(None)),
+            progress_sender: Arc::new(RwLock::new(None)),
+            verification_sender: Arc::new(RwLock::new(None)),
         }
     }
 
@@ -175,13 +453,16 @@ impl LocalTransport {
     pub fn with_port(port: u16) -> Self {
         Self {
             listen_port: port,
-            chunker: FileChunker::default_chunker(),
+            chunker: FileChunker::from_config(),
             checker: IntegrityChecker::new(),
             active_tasks: Arc::new(RwLock::new(HashMap::new())),
             listener: Arc::new(Mutex::new(None)),
             initialized: Arc::new(Mutex::new(false)),
             cancel_senders: Arc::new(RwLock::new(HashMap::new())),
+            pause_flags: Arc::new(RwLock::new(HashMap::new())),
             receive_config: Arc::new(RwLock::new(None)),
+            progress_sender: Arc::new(RwLock::new(None)),
+            verification_sender: Arc::new(RwLock::new(None)),
         }
     }
 
@@ -197,6 +478,33 @@ impl LocalTransport {
         self.receive_config.read().await.clone()
     }
 
+    /// 设置进度事件发送器
+    pub async fn set_progress_sender(&self, sender: mpsc::UnboundedSender<TransferProgress>) {
+        *self.progress_sender.write().await = Some(sender);
+    }
+
+    /// 推送进度事件
+    async fn emit_progress(&self, progress: &TransferProgress) {
+        if let Some(sender) = self.progress_sender.read().await.as_ref() {
+            let _ = sender.send(progress.clone());
+        }
+    }
+
+    /// 设置握手短认证串发送器
+    pub async fn set_verification_sender(
+        &self,
+        sender: mpsc::UnboundedSender<HandshakeVerification>,
+    ) {
+        *self.verification_sender.write().await = Some(sender);
+    }
+
+    /// 推送握手短认证串，供用户与对端界面比对
+    async fn emit_verification(&self, verification: HandshakeVerification) {
+        if let Some(sender) = self.verification_sender.read().await.as_ref() {
+            let _ = sender.send(verification);
+        }
+    }
+
     /// 获取监听端口
     pub async fn get_listen_port(&self) -> TransferResult<u16> {
         let listener = self.listener.lock().await;
@@ -207,42 +515,473 @@ impl LocalTransport {
         }
     }
 
+    /// 检查监听器是否仍能接受连接，返回监听端口
+    ///
+    /// 从本机回环地址连接监听端口并接受这条连接；监听套接字失效（如休眠唤醒、
+    /// 网卡重置后）时连接或接受会失败或超时
+    pub async fn probe_listener(&self) -> TransferResult<u16> {
+        let listener = self.listener.lock().await;
+        let Some(listener) = listener.as_ref() else {
+            return Err(TransferError::Internal("传输未初始化".to_string()));
+        };
+        let port = listener.local_addr()?.port();
+
+        let timeout = std::time::Duration::from_secs(LISTENER_PROBE_TIMEOUT_SECS);
+        let (probe, accepted) = tokio::time::timeout(timeout, async {
+            tokio::join!(
+                TcpStream::connect((std::net::Ipv4Addr::LOCALHOST, port)),
+                listener.accept()
+            )
+        })
+        .await
+        .map_err(|_| TransferError::Timeout)?;
+        probe?;
+        accepted?;
+        Ok(port)
+    }
+
+    /// 重新绑定监听器，优先沿用 `port`，被占用时改用系统分配的端口，返回新的监听端口
+    pub async fn rebind(&self, port: u16) -> TransferResult<u16> {
+        let mut listener = self.listener.lock().await;
+        // 先释放旧监听器，否则原端口仍被自身占用
+        *listener = None;
+        let rebound = bind_receive_listener(port).await?;
+        let port = rebound.local_addr()?.port();
+        *listener = Some(rebound);
+        Ok(port)
+    }
+
+    /// 测试对端传输端口是否可达
+    ///
+    /// 先尝试 TCP 连接；`handshake` 为 true 时继续发送一次完整握手（提供全部特性），
+    /// 根据握手响应判断对方是否为 PureSend 端点以及启用了哪些特性，随后直接断开
+    pub async fn test_connection(addr: SocketAddr, handshake: bool) -> ConnectionTestResult {
+        let timeout = std::time::Duration::from_secs(CONNECTION_TEST_TIMEOUT_SECS);
+        Self::test_connection_with_timeout(addr, handshake, timeout).await
+    }
+
+    /// 按指定超时测试对端传输端口，连接与握手分别计时（子网扫描使用较短的超时）
+    pub async fn test_connection_with_timeout(
+        addr: SocketAddr,
+        handshake: bool,
+        timeout: std::time::Duration,
+    ) -> ConnectionTestResult {
+        let mut result = ConnectionTestResult::default();
+
+        let started = std::time::Instant::now();
+        let mut stream = match tokio::time::timeout(timeout, TcpStream::connect(addr)).await {
+            Ok(Ok(stream)) => stream,
+            Ok(Err(e)) => {
+                result.error = Some(format!("连接失败: {}", e));
+                return result;
+            }
+            Err(_) => {
+                result.error = Some("连接超时，端口可能被防火墙拦截".to_string());
+                return result;
+            }
+        };
+        result.reachable = true;
+        result.latency_ms = Some(started.elapsed().as_millis() as u64);
+
+        if !handshake {
+            return result;
+        }
+
+        let initiator = crate::transfer::crypto::KeyExchangeInitiator::new();
+        let payload = HandshakePayload {
+            protocol_version: PROTOCOL_VERSION,
+            supports_encryption: true,
+            supports_compression: true,
+            supports_resume: true,
+            public_key: Some(initiator.public_key_bytes()),
+            supported_ciphers: crate::transfer::crypto::supported_ciphers(),
+            compression_algorithms: supported_compression_algorithms(),
+            hash_algorithms: supported_hash_algorithms(),
+            features: Some(offered_features(true, true)),
+            chunk_size: None,
+        };
+
+        match tokio::time::timeout(timeout, exchange_handshake(&mut stream, &payload)).await {
+            Ok(Ok(ack)) => {
+                result.is_puresend = true;
+                result.protocol_version = Some(ack.protocol_version);
+                result.encryption = Some(ack.use_encryption);
+                result.compression = Some(ack.use_compression);
+                result.resume = Some(ack.use_resume);
+                result.cipher = ack.use_encryption.then(|| ack.cipher.unwrap_or_default());
+                result.receive_capacity = ack.receive_capacity;
+                result.features = ack.features;
+            }
+            Ok(Err(e)) => result.error = Some(format!("端口可连接，但握手失败: {}", e)),
+            Err(_) => result.error = Some("端口可连接，但对方未响应握手".to_string()),
+        }
+
+        result
+    }
+
+    /// 与对端完成一次握手和密钥交换，校验双方派生的会话密钥是否一致（调试用）
+    ///
+    /// 始终提供加密以完成密钥交换，压缩按当前全局设置提供。双方只交换密钥确认值
+    /// （共享密钥的单向哈希），不传输密钥本身，随后直接断开
+    pub async fn verify_session(addr: SocketAddr) -> SessionVerificationResult {
+        let timeout = std::time::Duration::from_secs(CONNECTION_TEST_TIMEOUT_SECS);
+        let mut result = SessionVerificationResult::default();
+
+        let mut stream = match tokio::time::timeout(timeout, TcpStream::connect(addr)).await {
+            Ok(Ok(stream)) => stream,
+            Ok(Err(e)) => {
+                result.error = Some(format!("连接失败: {}", e));
+                return result;
+            }
+            Err(_) => {
+                result.error = Some("连接超时，端口可能被防火墙拦截".to_string());
+                return result;
+            }
+        };
+
+        let compression_enabled = crate::transfer::compression::get_compression_config().enabled;
+        let initiator = crate::transfer::crypto::KeyExchangeInitiator::new();
+        let payload = HandshakePayload {
+            protocol_version: PROTOCOL_VERSION,
+            supports_encryption: true,
+            supports_compression: compression_enabled,
+            supports_resume: true,
+            public_key: Some(initiator.public_key_bytes()),
+            supported_ciphers: crate::transfer::crypto::supported_ciphers(),
+            compression_algorithms: supported_compression_algorithms(),
+            hash_algorithms: supported_hash_algorithms(),
+            features: Some(offered_features(true, compression_enabled)),
+            chunk_size: None,
+        };
+
+        let ack = match tokio::time::timeout(timeout, exchange_handshake(&mut stream, &payload))
+            .await
+        {
+            Ok(Ok(ack)) => ack,
+            Ok(Err(e)) => {
+                result.error = Some(format!("握手失败: {}", e));
+                return result;
+            }
+            Err(_) => {
+                result.error = Some("对方未响应握手".to_string());
+                return result;
+            }
+        };
+        result.handshake_completed = true;
+
+        let features = FeatureNegotiation::new(
+            payload.features.as_deref().unwrap_or_default(),
+            ack.features.as_deref(),
+        );
+        result.features = features.agreed();
+        result.compression = payload.supports_compression
+            && ack.use_compression
+            && features.allows(ProtocolFeature::Compression);
+        result.encryption = ack.use_encryption && features.allows(ProtocolFeature::Encryption);
+        if !result.encryption {
+            result.error = Some("对方未同意加密，无法校验会话密钥".to_string());
+            return result;
+        }
+
+        let cipher = if features.allows(ProtocolFeature::CipherNegotiation) {
+            ack.cipher.unwrap_or_default()
+        } else {
+            CipherSuite::default()
+        };
+        result.cipher = Some(cipher);
+
+        let session = match ack.public_key.as_deref() {
+            Some(peer_public_key) => initiator.complete_with_cipher(peer_public_key, cipher),
+            None => Err(TransferError::KeyExchange("对方未提供加密公钥".to_string())),
+        };
+        match session {
+            Ok(session) => {
+                let confirmation = session.key_confirmation();
+                result.session_fingerprint = Some(confirmation[..16].to_string());
+                result.key_match = ack.key_confirmation.map(|peer| peer == confirmation);
+                if result.key_match.is_none() {
+                    result.error =
+                        Some("对方版本不支持密钥确认，请比对双方的会话指纹".to_string());
+                }
+            }
+            Err(e) => result.error = Some(format!("密钥交换失败: {}", e)),
+        }
+
+        result
+    }
+
+    /// 将同一文件同时发送到多个设备
+    ///
+    /// 每个任务对应一个目标设备，文件只读取、压缩一次，分块分发给各设备的连接。
+    /// 各设备独立握手、加密和续传，失败互不影响；每个设备最多缓冲
+    /// `FANOUT_BUFFER_CHUNKS` 个分块，慢设备超出后才会拖慢其他设备。
+    /// 返回结果与 `tasks` 一一对应
+    pub async fn send_file_to_peers(
+        &self,
+        tasks: &[TransferTask],
+    ) -> Vec<TransferResult<TransferProgress>> {
+        let Some(first) = tasks.first() else {
+            return Vec::new();
+        };
+
+        let prepared = async {
+            let file_path = first
+                .file
+                .path
+                .as_ref()
+                .map(PathBuf::from)
+                .ok_or_else(|| TransferError::InvalidMetadata("文件路径未设置".to_string()))?;
+            if !file_path.exists() {
+                return Err(TransferError::FileNotFound(file_path.display().to_string()));
+            }
+            let chunks = self.chunker_for(first).compute_chunks(&file_path)?;
+            Ok::<_, TransferError>((file_path, chunks))
+        }
+        .await;
+        let (file_path, chunks) = match prepared {
+            Ok(prepared) => prepared,
+            Err(e) => {
+                let message = e.to_string();
+                return tasks
+                    .iter()
+                    .map(|_| Err(TransferError::Internal(message.clone())))
+                    .collect();
+            }
+        };
+
+        let compressor = if first.compression_override.unwrap_or(true) {
+            crate::transfer::compression::create_compressor_from_config()
+        } else {
+            None
+        };
+
+        let mut senders = Vec::with_capacity(tasks.len());
+        let mut sends = Vec::with_capacity(tasks.len());
+        for task in tasks {
+            let (sender, receiver) = mpsc::channel(FANOUT_BUFFER_CHUNKS);
+            senders.push(sender);
+            sends.push(async move {
+                let peer = task
+                    .peer
+                    .as_ref()
+                    .ok_or_else(|| TransferError::PeerUnreachable("未指定目标设备".to_string()))?;
+                let addr: SocketAddr = format!("{}:{}", peer.ip, peer.port).parse().map_err(
+                    |e| TransferError::PeerUnreachable(format!("无效的地址: {}", e)),
+                )?;
+                self.send_file_to(task, addr, Some(receiver)).await
+            });
+        }
+
+        let source = spawn_chunk_pipeline(
+            file_path,
+            self.chunker.clone(),
+            chunks,
+            compressor,
+            first.file.mime_type.clone(),
+            prepare_shared_chunk,
+        );
+        tokio::spawn(fan_out_chunks(source, senders));
+
+        futures::future::join_all(sends).await
+    }
 
     /// 发送文件到指定地址
     ///
     /// 传输流程：连接 → 握手协商（v2） → 文件请求/响应 → 分块传输（可选加密+压缩） → 完成
     /// 支持断点续传：传输中断时保存断点信息，恢复时跳过已传输的分块
+    ///
+    /// `shared_chunks` 为多设备发送时共享的分块来源，为空时自行读取、压缩分块
+    #[tracing::instrument(skip_all, err, fields(task_id = %task.id, peer = %addr, file = %task.file.name))]
     async fn send_file_to(
         &self,
         task: &TransferTask,
         addr: SocketAddr,
+        shared_chunks: Option<mpsc::Receiver<SharedChunkResult>>,
+    ) -> TransferResult<TransferProgress> {
+        source_path(task)?;
+        let cancel_rx = self.register_cancel(&task.id).await;
+        let mut session = self.open_session(task, addr, task.file.size).await?;
+        let paused = self.register_pause(&task.id).await;
+        let result = self
+            .transfer_file(&mut session, task, addr, shared_chunks, cancel_rx, &paused)
+            .await;
+        self.pause_flags.write().await.remove(&task.id);
+        result
+    }
+
+    /// 在同一连接上依次发送多个文件到同一设备（后台执行）
+    ///
+    /// 对端支持批量传输时，握手后先发送批量清单，各文件复用该连接和加密会话；
+    /// 不支持时每个文件单独建立连接。文件被拒收后连接继续使用，中断或取消后
+    /// 为下一个文件重新建立连接。通过 `batch_id` 取消时跳过所有尚未开始的文件。
+    /// 每个文件结束时经返回的通道推送其结果，全部结束后通道关闭
+    pub fn send_batch(
+        &self,
+        batch_id: String,
+        tasks: Vec<TransferTask>,
+    ) -> mpsc::UnboundedReceiver<(String, TransferResult<TransferProgress>)> {
+        let (result_tx, result_rx) = mpsc::unbounded_channel();
+        let transport = self.clone();
+
+        tokio::spawn(async move {
+            // 提前登记各文件的取消信号，轮到某文件前已取消时直接跳过
+            let mut cancel_rxs = Vec::with_capacity(tasks.len());
+            for task in &tasks {
+                cancel_rxs.push(transport.register_cancel(&task.id).await);
+            }
+            let mut batch_cancel_rx = transport.register_cancel(&batch_id).await;
+            let mut batch_cancelled = false;
+            let mut session = None;
+
+            for (index, mut cancel_rx) in cancel_rxs.into_iter().enumerate() {
+                let task = &tasks[index];
+                batch_cancelled |= batch_cancel_rx.try_recv().is_ok();
+                let result = if batch_cancelled || cancel_rx.try_recv().is_ok() {
+                    Err(TransferError::Cancelled)
+                } else {
+                    transport
+                        .send_batch_file(&mut session, &batch_id, &tasks[index..], cancel_rx)
+                        .await
+                };
+                if result_tx.send((task.id.clone(), result)).is_err() {
+                    break;
+                }
+            }
+
+            transport.cancel_senders.write().await.remove(&batch_id);
+        });
+
+        result_rx
+    }
+
+    /// 发送批量中的一个文件（`remaining` 的第一个），必要时建立连接并发送批量清单
+    #[tracing::instrument(skip_all, err, fields(task_id = %remaining[0].id, batch_id = %batch_id))]
+    async fn send_batch_file(
+        &self,
+        session: &mut Option<SendSession>,
+        batch_id: &str,
+        remaining: &[TransferTask],
+        cancel_rx: mpsc::Receiver<()>,
     ) -> TransferResult<TransferProgress> {
-        let file_path = task
-            .file
-            .path
-            .as_ref()
-            .ok_or_else(|| TransferError::InvalidMetadata("文件路径未设置".to_string()))?;
+        let task = &remaining[0];
+        source_path(task)?;
+        let addr = peer_addr(task)?;
+
+        let mut current = match session.take() {
+            Some(current) => current,
+            None => {
+                let manifest = BatchManifestPayload {
+                    batch_id: batch_id.to_string(),
+                    file_count: remaining.len(),
+                    total_size: remaining.iter().map(|t| t.file.size).sum(),
+                };
+                let mut opened = self.open_session(task, addr, manifest.total_size).await?;
+                if opened.batch {
+                    let manifest_json = serde_json::to_vec(&manifest)?;
+                    let header =
+                        MessageHeader::new(MessageType::BatchManifest, manifest_json.len() as u32);
+                    opened.stream.write_all(&header.to_bytes()).await?;
+                    opened.stream.write_all(&manifest_json).await?;
+                }
+                opened
+            }
+        };
 
-        let file_path = std::path::Path::new(file_path);
-        if !file_path.exists() {
-            return Err(TransferError::FileNotFound(file_path.display().to_string()));
+        let paused = self.register_pause(&task.id).await;
+        let result = self
+            .transfer_file(&mut current, task, addr, None, cancel_rx, &paused)
+            .await;
+        self.pause_flags.write().await.remove(&task.id);
+        // 成功或被拒收时双方停在消息边界，连接可以继续发送下一个文件
+        let reusable =
+            current.batch && matches!(&result, Ok(_) | Err(TransferError::PermissionDenied(_)));
+        if reusable {
+            *session = Some(current);
         }
+        result
+    }
 
-        // 创建取消通道
-        let (cancel_tx, mut cancel_rx) = mpsc::channel::<()>(1);
+    /// 修改分块大小，只影响之后开始的任务
+    pub fn set_chunk_size(&mut self, bytes: u64) {
+        self.chunker = FileChunker::new(bytes);
+    }
+
+    /// 任务使用的分块器：沿用准备传输时元数据中的分块布局，元数据未携带分块信息时
+    /// 使用当前的分块大小
+    fn chunker_for(&self, task: &TransferTask) -> FileChunker {
+        FileChunker::from_metadata(&task.file).unwrap_or_else(|| self.chunker.clone())
+    }
+
+    /// 登记任务的取消信号，返回对应的接收端
+    async fn register_cancel(&self, task_id: &str) -> mpsc::Receiver<()> {
+        let (cancel_tx, cancel_rx) = mpsc::channel::<()>(1);
         self.cancel_senders
             .write()
             .await
-            .insert(task.id.clone(), cancel_tx);
+            .insert(task_id.to_string(), cancel_tx);
+        cancel_rx
+    }
+
+    /// 登记任务的暂停标志，文件发送结束后移除
+    async fn register_pause(&self, task_id: &str) -> Arc<AtomicBool> {
+        let paused = Arc::new(AtomicBool::new(false));
+        self.pause_flags
+            .write()
+            .await
+            .insert(task_id.to_string(), paused.clone());
+        paused
+    }
+
+    /// 暂停发送中的任务，当前分块确认后停止发送，连接保持不断开
+    pub async fn pause(&self, task_id: &str) -> TransferResult<()> {
+        match self.pause_flags.read().await.get(task_id) {
+            Some(paused) => {
+                paused.store(true, Ordering::SeqCst);
+                Ok(())
+            }
+            None => Err(TransferError::UnsupportedOperation(format!(
+                "任务未在发送中: {}",
+                task_id
+            ))),
+        }
+    }
+
+    /// 恢复已暂停的任务，在原连接上继续发送
+    pub async fn resume(&self, task_id: &str) -> TransferResult<()> {
+        match self.pause_flags.read().await.get(task_id) {
+            Some(paused) => {
+                paused.store(false, Ordering::SeqCst);
+                Ok(())
+            }
+            None => Err(TransferError::UnsupportedOperation(format!(
+                "任务未在发送中，暂停已超时或传输已结束: {}",
+                task_id
+            ))),
+        }
+    }
 
+    /// 连接目标并完成握手协商与密钥交换
+    ///
+    /// `required_bytes` 为本次连接将要发送的总字节数，对方上报了存储能力时据此提前检查
+    async fn open_session(
+        &self,
+        task: &TransferTask,
+        addr: SocketAddr,
+        required_bytes: u64,
+    ) -> TransferResult<SendSession> {
         // 连接目标
         let mut stream = TcpStream::connect(&addr)
             .await
             .map_err(|e| TransferError::Network(format!("连接失败: {}", e)))?;
 
         // === 阶段 1：握手协商（v2 特性协商） ===
-        let encryption_enabled = crate::transfer::crypto::is_encryption_enabled();
-        let compression_config = crate::transfer::compression::get_compression_config();
+        // 任务级覆盖优先于全局设置，只影响本端提供的能力，最终仍由双方协商
+        let encryption_enabled = task
+            .encryption_override
+            .unwrap_or_else(crate::transfer::crypto::is_encryption_enabled);
+        let compression_enabled = task
+            .compression_override
+            .unwrap_or_else(|| crate::transfer::compression::get_compression_config().enabled);
 
         // 创建密钥交换发起方（如果启用加密）
         let key_exchange_initiator = if encryption_enabled {
@@ -254,11 +993,16 @@ impl LocalTransport {
         let handshake = HandshakePayload {
             protocol_version: PROTOCOL_VERSION,
             supports_encryption: encryption_enabled,
-            supports_compression: compression_config.enabled,
+            supports_compression: compression_enabled,
             supports_resume: true,
             public_key: key_exchange_initiator
                 .as_ref()
                 .map(|k| k.public_key_bytes()),
+            supported_ciphers: crate::transfer::crypto::supported_ciphers(),
+            compression_algorithms: supported_compression_algorithms(),
+            hash_algorithms: supported_hash_algorithms(),
+            features: Some(offered_features(encryption_enabled, compression_enabled)),
+            chunk_size: Some(self.chunker_for(task).chunk_size()),
         };
 
         let handshake_json = serde_json::to_vec(&handshake)?;
@@ -277,12 +1021,54 @@ impl LocalTransport {
         stream.read_exact(&mut ack_buf).await?;
         let handshake_ack: HandshakeAckPayload = serde_json::from_slice(&ack_buf)?;
 
-        // 协商最终特性
+        // 对方上报了存储能力时提前失败，避免传输中途才发现写入失败
+        if let Some(capacity) = &handshake_ack.receive_capacity {
+            capacity.check(required_bytes)?;
+        }
+
+        // 协商最终特性：布尔字段与特性列表须同时同意，旧版本对端只看布尔字段
+        let features = FeatureNegotiation::new(
+            handshake.features.as_deref().unwrap_or_default(),
+            handshake_ack.features.as_deref(),
+        );
+        // 对端选定的压缩算法须在本端提供的列表中，否则不压缩
+        let compression_algorithm = handshake_ack.compression_algorithm.unwrap_or_default();
         let negotiated = NegotiatedFeatures {
-            encryption: handshake.supports_encryption && handshake_ack.use_encryption,
-            compression: handshake.supports_compression && handshake_ack.use_compression,
-            resume: handshake_ack.use_resume,
+            encryption: handshake.supports_encryption
+                && handshake_ack.use_encryption
+                && features.allows(ProtocolFeature::Encryption),
+            compression: handshake.supports_compression
+                && handshake_ack.use_compression
+                && features.allows(ProtocolFeature::Compression)
+                && handshake
+                    .compression_algorithms
+                    .contains(&compression_algorithm),
+            compression_algorithm,
+            hash_algorithms: if handshake_ack.hash_algorithms.is_empty() {
+                vec![HashAlgorithm::Sha256]
+            } else {
+                handshake_ack.hash_algorithms.clone()
+            },
+            resume: handshake_ack.use_resume && features.allows(ProtocolFeature::Resume),
+            cipher: if features.allows(ProtocolFeature::CipherNegotiation) {
+                handshake_ack.cipher.unwrap_or_default()
+            } else {
+                CipherSuite::default()
+            },
+            verify_after_send: features.peer_agreed(ProtocolFeature::VerifyAfterSend),
         };
+        tracing::info!(
+            features = ?features.agreed(),
+            encryption = negotiated.encryption,
+            compression = negotiated.compression,
+            compression_algorithm = negotiated.compression_algorithm.as_str(),
+            hash_algorithms = ?negotiated.hash_algorithms,
+            resume = negotiated.resume,
+            cipher = negotiated.cipher.as_str(),
+            verify_after_send = negotiated.verify_after_send,
+            batch = features.peer_agreed(ProtocolFeature::BatchTransfer),
+            "握手完成"
+        );
 
         // 完成密钥交换（如果双方都同意加密）
         let mut crypto_session = if negotiated.encryption {
@@ -292,18 +1078,75 @@ impl LocalTransport {
             let peer_public_key = handshake_ack.public_key.ok_or_else(|| {
                 TransferError::KeyExchange("对方未提供加密公钥".to_string())
             })?;
-            Some(initiator.complete(&peer_public_key)?)
+            let sas = crate::transfer::crypto::short_auth_string(
+                &initiator.public_key_bytes(),
+                &peer_public_key,
+            );
+            let session = initiator.complete_with_cipher(&peer_public_key, negotiated.cipher)?;
+            // 对端提供了密钥确认值时先比对，避免密钥不一致导致接收端数据全部解密失败
+            if let Some(peer_confirmation) = &handshake_ack.key_confirmation {
+                if *peer_confirmation != session.key_confirmation() {
+                    return Err(TransferError::KeyExchange(
+                        "双方派生的会话密钥不一致".to_string(),
+                    ));
+                }
+            }
+            tracing::info!(task_id = %task.id, cipher = ?session.cipher_suite(), "加密会话已建立");
+            // 不等待用户比对（机会加密），用户拒绝时经 `confirm_handshake` 取消任务
+            self.emit_verification(HandshakeVerification {
+                task_id: task.id.clone(),
+                peer: addr.to_string(),
+                sas,
+            })
+            .await;
+            Some(session)
         } else {
             None
         };
 
-        // 创建压缩器（如果双方都同意压缩）
-        let compressor = if negotiated.compression {
+        Ok(SendSession {
+            stream,
+            negotiated,
+            crypto_session,
+            batch: features.peer_agreed(ProtocolFeature::BatchTransfer),
+        })
+    }
+
+    /// 在已建立的连接上发送单个文件
+    ///
+    /// 传输流程：文件请求/响应 → 分块传输（可选加密+压缩） → 端到端校验 → 完成。
+    /// `paused` 置位后在分块之间暂停发送，超过 [`PAUSE_TIMEOUT_SECS`] 未恢复时按中断处理
+    async fn transfer_file(
+        &self,
+        session: &mut SendSession,
+        task: &TransferTask,
+        addr: SocketAddr,
+        shared_chunks: Option<mpsc::Receiver<SharedChunkResult>>,
+        mut cancel_rx: mpsc::Receiver<()>,
+        paused: &AtomicBool,
+    ) -> TransferResult<TransferProgress> {
+        let file_path = source_path(task)?;
+        let SendSession {
+            stream,
+            negotiated,
+            crypto_session,
+            ..
+        } = session;
+
+        // 创建压缩器（如果双方都同意压缩；共享分块来源已按全局设置压缩）
+        let compressor = if negotiated.compression && shared_chunks.is_none() {
             crate::transfer::compression::create_compressor_from_config()
+                .map(|c| c.with_algorithm(negotiated.compression_algorithm))
         } else {
             None
         };
 
+        // 对端无法校验元数据使用的哈希算法时改用 SHA-256
+        let rehashed = self
+            .rehash_for_peer(task, file_path, &negotiated.hash_algorithms)
+            .await?;
+        let task = rehashed.as_ref().unwrap_or(task);
+
         // === 阶段 2：文件请求/响应 ===
         let metadata_json = serde_json::to_string(&task.file)?;
         let header = MessageHeader::new(MessageType::FileRequest, metadata_json.len() as u32);
@@ -311,7 +1154,7 @@ impl LocalTransport {
         stream.write_all(metadata_json.as_bytes()).await?;
 
         // 等待响应
-        let response_header = MessageHeader::read_from_stream(&mut stream).await?;
+        let response_header = MessageHeader::read_from_stream(stream).await?;
 
         if response_header.message_type != MessageType::FileResponse {
             return Err(TransferError::Network("未收到正确的文件响应".to_string()));
@@ -322,7 +1165,7 @@ impl LocalTransport {
         let response: FileResponse = serde_json::from_slice(&response_buf)?;
 
         if !response.accepted {
-            return Err(TransferError::Network(format!(
+            return Err(TransferError::PermissionDenied(format!(
                 "对方拒绝接收: {}",
                 response.reason.unwrap_or_default()
             )));
@@ -334,31 +1177,33 @@ impl LocalTransport {
         );
         let _ = resume_manager.load().await;
 
-        let resume_from_chunk: u32 = if negotiated.resume {
-            if let Some(resume_info) = resume_manager.get_resume_info(&task.id).await {
-                if resume_info.file_name == task.file.name
-                    && resume_info.file_size == task.file.size
-                {
-                    resume_info.last_chunk_index + 1
-                } else {
-                    0
-                }
-            } else {
-                0
-            }
+        // 按任务 ID 查找，找不到时按内容（哈希 + 大小 + 对端）沿用已有断点
+        let existing_resume = if negotiated.resume {
+            resume_manager
+                .find_resume_info(
+                    &task.id,
+                    &task.file.hash,
+                    task.file.size,
+                    &addr.ip().to_string(),
+                    "send",
+                )
+                .await
         } else {
-            0
+            None
         };
 
-        // === 阶段 4：分块传输 ===
-        let chunks = self.chunker.compute_chunks(file_path)?;
-        let mut task_state = TransferTaskState {
-            progress: TransferProgress::from(task),
-            cancelled: false,
+        let resume_from_chunk: u32 = match &existing_resume {
+            Some(resume_info) if resume_info.file_size == task.file.size => {
+                resume_info.last_chunk_index + 1
+            }
+            _ => 0,
         };
+
+        // === 阶段 4：分块传输 ===
+        let chunks = self.chunker_for(task).compute_chunks(file_path)?;
+        let mut task_state = TransferTaskState::new(TransferProgress::from(task));
         task_state.progress.status = crate::models::TaskStatus::Transferring;
 
-        let start_time = std::time::Instant::now();
         // 断点续传时，已传输的字节数从断点处开始计算
         let mut total_transferred: u64 = chunks
             .iter()
@@ -371,7 +1216,32 @@ impl LocalTransport {
             0
         };
 
-        let mime_type = &task.file.mime_type;
+        // 在后台并行读取、压缩待发送的分块，与网络发送重叠
+        let mut chunk_feed = match shared_chunks {
+            Some(receiver) => ChunkFeed::Shared {
+                receiver,
+                compression: negotiated
+                    .compression
+                    .then_some(negotiated.compression_algorithm),
+            },
+            None => ChunkFeed::Pipeline(spawn_chunk_pipeline(
+                file_path.to_path_buf(),
+                self.chunker.clone(),
+                chunks
+                    .iter()
+                    .filter(|c| c.index >= resume_from_chunk)
+                    .cloned()
+                    .collect(),
+                compressor,
+                task.file.mime_type.clone(),
+                prepare_chunk,
+            )),
+        };
+
+        // 停滞检测：记录最后一次收到确认的时间
+        let mut last_progress_at = std::time::Instant::now();
+        let mut stall_ticker =
+            tokio::time::interval(std::time::Duration::from_secs(STALL_CHECK_INTERVAL_SECS));
 
         for chunk in &chunks {
             // 跳过已传输的分块（断点续传）
@@ -379,8 +1249,16 @@ impl LocalTransport {
                 continue;
             }
 
+            // 按全局发送限速等待，多个任务同时发送时分摊额度；等待计入耗时，速度即为限速后的速度。
+            // 等待期间可随时取消，且不计入停滞检测
+            let cancelled = tokio::select! {
+                Some(()) = cancel_rx.recv() => true,
+                _ = super::throttle::send_throttle().acquire(chunk.size) => false,
+            };
+            last_progress_at = std::time::Instant::now();
+
             // 检查取消信号
-            if cancel_rx.try_recv().is_ok() {
+            if cancelled || cancel_rx.try_recv().is_ok() {
                 // 保存断点信息
                 self.save_resume_info_on_interrupt(
                     &resume_manager,
@@ -400,79 +1278,81 @@ impl LocalTransport {
                 return Err(TransferError::Cancelled);
             }
 
-            // 读取分块数据
-            let raw_data = self.chunker.read_chunk(file_path, chunk)?;
-
-            // 可选压缩
-            let (chunk_data, is_compressed) = match &compressor {
-                Some(comp) => {
-                    match comp.get_level(mime_type) {
-                        Some(level) => {
-                            let compressed = crate::transfer::compression::Compressor::compress(
-                                &raw_data, level,
-                            )?;
-                            // 仅当压缩后更小时才使用压缩数据
-                            if compressed.len() < raw_data.len() {
-                                (compressed, true)
-                            } else {
-                                (raw_data, false)
-                            }
-                        }
-                        None => (raw_data, false),
-                    }
+            // 暂停时停在分块之间，期间以心跳保持连接
+            if paused.load(Ordering::SeqCst) {
+                let outcome = self
+                    .hold_paused(stream, paused, &mut cancel_rx, &mut task_state)
+                    .await;
+                if let Err(e) = outcome {
+                    self.save_resume_info_on_interrupt(
+                        &resume_manager,
+                        task,
+                        last_successful_chunk_index,
+                        total_transferred,
+                        &addr,
+                        "send",
+                    )
+                    .await;
+
+                    task_state.progress.status = if matches!(e, TransferError::Cancelled) {
+                        crate::models::TaskStatus::Cancelled
+                    } else {
+                        crate::models::TaskStatus::Interrupted
+                    };
+                    self.active_tasks
+                        .write()
+                        .await
+                        .insert(task.id.clone(), task_state);
+                    return Err(e);
                 }
-                None => (raw_data, false),
-            };
+                // 暂停前的停滞计时不再有效
+                last_progress_at = std::time::Instant::now();
+            }
 
-            // 可选加密
-            let final_data = match &mut crypto_session {
-                Some(session) => session.encrypt(&chunk_data)?,
-                None => chunk_data,
+            // 取出预处理好的分块（流水线按原始顺序产出）
+            let prepared = match chunk_feed.next(chunk.index).await {
+                Some(result) => result?,
+                None => {
+                    return Err(TransferError::Internal(
+                        "分块预处理流水线意外结束".to_string(),
+                    ))
+                }
             };
-
-            // 发送分块
-            let chunk_message = ChunkMessage {
-                index: chunk.index,
-                data: final_data,
-                compressed: is_compressed,
-            };
-            let chunk_json = serde_json::to_vec(&chunk_message)?;
-            let header = MessageHeader::new(MessageType::ChunkData, chunk_json.len() as u32);
-
-            let send_result = async {
-                stream.write_all(&header.to_bytes()).await?;
-                stream.write_all(&chunk_json).await?;
-                Ok::<(), std::io::Error>(())
-            }
-            .await;
-
-            if let Err(send_err) = send_result {
-                // 网络错误，保存断点信息
-                self.save_resume_info_on_interrupt(
-                    &resume_manager,
-                    task,
-                    last_successful_chunk_index,
-                    total_transferred,
-                    &addr,
-                    "send",
-                )
+            debug_assert_eq!(prepared.index, chunk.index);
+
+            let mut first_attempt = Some(prepared);
+            let mut resends = 0;
+            loop {
+                // 首次发送使用预处理好的分块，重发时从磁盘重新读取且不再压缩
+                let (data, compressed) = match first_attempt.take() {
+                    Some(prepared) => (prepared.data, prepared.compressed),
+                    None => (self.chunker_for(task).read_chunk(file_path, chunk)?, false),
+                };
+
+                // 可选加密（依赖递增的 nonce，必须按顺序进行）
+                let final_data = match crypto_session.as_mut() {
+                    Some(session) => session.encrypt(&data)?,
+                    None => data,
+                };
+
+                // 发送分块
+                let chunk_message = ChunkMessage {
+                    index: chunk.index,
+                    data: final_data,
+                    compressed,
+                };
+                let chunk_json = serde_json::to_vec(&chunk_message)?;
+                let header = MessageHeader::new(MessageType::ChunkData, chunk_json.len() as u32);
+
+                let send_result = async {
+                    stream.write_all(&header.to_bytes()).await?;
+                    stream.write_all(&chunk_json).await?;
+                    Ok::<(), std::io::Error>(())
+                }
                 .await;
 
-                task_state.progress.status = crate::models::TaskStatus::Interrupted;
-                self.active_tasks
-                    .write()
-                    .await
-                    .insert(task.id.clone(), task_state);
-                return Err(TransferError::Network(format!("发送数据失败: {}", send_err)));
-            }
-
-            // 等待确认
-            let ack_result = tokio::select! {
-                result = MessageHeader::read_from_stream(&mut stream) => {
-                    result
-                }
-                _ = cancel_rx.recv() => {
-                    // 取消时保存断点信息
+                if let Err(send_err) = send_result {
+                    // 网络错误，保存断点信息
                     self.save_resume_info_on_interrupt(
                         &resume_manager,
                         task,
@@ -480,60 +1360,169 @@ impl LocalTransport {
                         total_transferred,
                         &addr,
                         "send",
-                    ).await;
-
-                    task_state.progress.status = crate::models::TaskStatus::Cancelled;
-                    self.active_tasks.write().await.insert(task.id.clone(), task_state);
-                    return Err(TransferError::Cancelled);
+                    )
+                    .await;
+
+                    task_state.progress.status = crate::models::TaskStatus::Interrupted;
+                    self.active_tasks
+                        .write()
+                        .await
+                        .insert(task.id.clone(), task_state);
+                    return Err(TransferError::Network(format!(
+                        "发送数据失败: {}",
+                        send_err
+                    )));
                 }
-            };
 
-            if let Err(ack_err) = ack_result {
-                // 等待确认时网络错误，保存断点信息
-                self.save_resume_info_on_interrupt(
-                    &resume_manager,
-                    task,
-                    last_successful_chunk_index,
-                    total_transferred,
-                    &addr,
-                    "send",
-                )
-                .await;
+                // 等待确认（读取 future 固定在循环外，避免停滞检测打断半读的消息头）
+                let ack_result = {
+                    let ack_read = MessageHeader::read_from_stream(stream);
+                    tokio::pin!(ack_read);
 
-                task_state.progress.status = crate::models::TaskStatus::Interrupted;
-                self.active_tasks
-                    .write()
-                    .await
-                    .insert(task.id.clone(), task_state);
-                return Err(ack_err);
+                    loop {
+                        tokio::select! {
+                            result = &mut ack_read => {
+                                break result;
+                            }
+                            _ = cancel_rx.recv() => {
+                                // 取消时保存断点信息
+                                self.save_resume_info_on_interrupt(
+                                    &resume_manager,
+                                    task,
+                                    last_successful_chunk_index,
+                                    total_transferred,
+                                    &addr,
+                                    "send",
+                                ).await;
+
+                                task_state.progress.status = crate::models::TaskStatus::Cancelled;
+                                self.active_tasks.write().await.insert(task.id.clone(), task_state);
+                                return Err(TransferError::Cancelled);
+                            }
+                            _ = stall_ticker.tick() => {
+                                let stalled_secs = last_progress_at.elapsed().as_secs();
+                                if stalled_secs >= STALL_TIMEOUT_SECS {
+                                    // 长时间无响应，视为链路已断开
+                                    break Err(TransferError::Timeout);
+                                }
+                                if stalled_secs >= STALL_THRESHOLD_SECS {
+                                    task_state.progress.is_stalled = true;
+                                    task_state.progress.seconds_since_progress = stalled_secs;
+                                    task_state.refresh_speed(std::time::Instant::now());
+                                    self.active_tasks
+                                        .write()
+                                        .await
+                                        .insert(task.id.clone(), task_state.clone());
+                                    self.emit_progress(&task_state.progress).await;
+                                }
+                            }
+                        }
+                    }
+                };
+
+                // 对端校验分块失败时重发同一分块，超过上限后按校验失败结束
+                let ack_result = match ack_result {
+                    Ok(header) => read_chunk_ack(stream, &header, chunk.index).await,
+                    Err(e) => Err(e),
+                };
+                match ack_result {
+                    Ok(true) => break,
+                    Ok(false) if resends < MAX_CHUNK_RESENDS => {
+                        resends += 1;
+                        tracing::warn!(
+                            task_id = %task.id,
+                            chunk = chunk.index,
+                            resends,
+                            "对端分块校验失败，重新发送"
+                        );
+                    }
+                    result => {
+                        // 等待确认时网络错误或分块多次校验失败，保存断点信息
+                        self.save_resume_info_on_interrupt(
+                            &resume_manager,
+                            task,
+                            last_successful_chunk_index,
+                            total_transferred,
+                            &addr,
+                            "send",
+                        )
+                        .await;
+
+                        let error = result.err().unwrap_or_else(|| {
+                            TransferError::ChunkVerificationFailed(format!(
+                                "分块 {} 重发 {} 次后仍校验失败",
+                                chunk.index, MAX_CHUNK_RESENDS
+                            ))
+                        });
+                        task_state.progress.status = if error.is_transient() {
+                            crate::models::TaskStatus::Interrupted
+                        } else {
+                            crate::models::TaskStatus::Failed
+                        };
+                        self.active_tasks
+                            .write()
+                            .await
+                            .insert(task.id.clone(), task_state);
+                        return Err(error);
+                    }
+                }
             }
 
             last_successful_chunk_index = chunk.index;
             total_transferred += chunk.size;
-            let elapsed = start_time.elapsed().as_secs_f64();
-            let speed = if elapsed > 0.0 {
-                (total_transferred as f64 / elapsed) as u64
-            } else {
-                0
-            };
+            let now = std::time::Instant::now();
+            task_state.speed_window.record(now, chunk.size);
 
             task_state.progress.transferred_bytes = total_transferred;
-            task_state.progress.speed = speed;
+            task_state.refresh_speed(now);
             task_state.progress.progress =
                 (total_transferred as f64 / task.file.size as f64) * 100.0;
 
+            // 收到确认，解除停滞状态
+            last_progress_at = std::time::Instant::now();
+            let was_stalled = task_state.progress.is_stalled;
+            task_state.progress.is_stalled = false;
+            task_state.progress.seconds_since_progress = 0;
+
             // 更新活跃任务状态
             self.active_tasks
                 .write()
                 .await
                 .insert(task.id.clone(), task_state.clone());
+
+            if was_stalled {
+                self.emit_progress(&task_state.progress).await;
+            }
         }
 
-        // 传输完成，清理断点信息
+        // === 阶段 5：端到端校验 ===
+        // 所有分块都已确认，对端支持时再比对接收端落盘文件的哈希
+        if negotiated.verify_after_send {
+            if let Err(e) = self.confirm_received_hash(stream, task, file_path).await {
+                tracing::warn!(task_id = %task.id, error = %e, "端到端校验失败");
+                task_state.progress.status = crate::models::TaskStatus::Failed;
+                task_state.progress.verified = Some(false);
+                task_state.progress.error = Some(e.to_string());
+                self.active_tasks
+                    .write()
+                    .await
+                    .insert(task.id.clone(), task_state);
+                return Err(e);
+            }
+            task_state.progress.verified = Some(true);
+        }
+
+        // 传输完成，清理断点信息（包括沿用的其他任务 ID 的断点）
         let _ = resume_manager.remove_resume_info(&task.id).await;
+        if let Some(resume_info) = existing_resume {
+            if resume_info.task_id != task.id {
+                let _ = resume_manager.remove_resume_info(&resume_info.task_id).await;
+            }
+        }
 
         task_state.progress.status = crate::models::TaskStatus::Completed;
         task_state.progress.progress = 100.0;
+        task_state.progress.estimated_time_remaining = Some(0);
         self.active_tasks
             .write()
             .await
@@ -542,6 +1531,157 @@ impl LocalTransport {
         Ok(task_state.progress)
     }
 
+    /// 暂停期间保持连接：定期发送心跳，直到恢复、取消或暂停超时
+    ///
+    /// 恢复时返回 Ok，取消返回 [`TransferError::Cancelled`]，超时或心跳发送失败时返回
+    /// [`TransferError::Interrupted`]，由调用方保存断点信息；两者都不会被自动重试
+    async fn hold_paused(
+        &self,
+        stream: &mut TcpStream,
+        paused: &AtomicBool,
+        cancel_rx: &mut mpsc::Receiver<()>,
+        task_state: &mut TransferTaskState,
+    ) -> TransferResult<()> {
+        let task_id = task_state.progress.task_id.clone();
+        tracing::info!(task_id = %task_id, "发送已暂停");
+        task_state.progress.status = crate::models::TaskStatus::Paused;
+        task_state.progress.speed = 0;
+        task_state.progress.estimated_time_remaining = None;
+        self.active_tasks
+            .write()
+            .await
+            .insert(task_id.clone(), task_state.clone());
+        self.emit_progress(&task_state.progress).await;
+
+        let started = std::time::Instant::now();
+        let heartbeat_interval = std::time::Duration::from_secs(PAUSE_HEARTBEAT_INTERVAL_SECS);
+        let mut heartbeat = tokio::time::interval_at(
+            tokio::time::Instant::now() + heartbeat_interval,
+            heartbeat_interval,
+        );
+        let mut poll =
+            tokio::time::interval(std::time::Duration::from_millis(PAUSE_POLL_INTERVAL_MS));
+
+        while paused.load(Ordering::SeqCst) {
+            tokio::select! {
+                _ = cancel_rx.recv() => return Err(TransferError::Cancelled),
+                _ = heartbeat.tick() => {
+                    let header = MessageHeader::new(MessageType::Heartbeat, 0);
+                    stream.write_all(&header.to_bytes()).await.map_err(|e| {
+                        TransferError::Interrupted(format!("暂停期间连接已断开: {}", e))
+                    })?;
+                }
+                _ = poll.tick() => {
+                    if started.elapsed().as_secs() >= PAUSE_TIMEOUT_SECS {
+                        tracing::warn!(task_id = %task_id, "暂停超时，转为中断");
+                        return Err(TransferError::Interrupted("暂停超时".to_string()));
+                    }
+                }
+            }
+        }
+
+        tracing::info!(task_id = %task_id, "发送已恢复");
+        task_state.progress.status = crate::models::TaskStatus::Transferring;
+        task_state.speed_window.restart(std::time::Instant::now());
+        self.active_tasks
+            .write()
+            .await
+            .insert(task_id, task_state.clone());
+        self.emit_progress(&task_state.progress).await;
+        Ok(())
+    }
+
+    /// 等待接收端的传输完成确认，并将其回传的文件哈希与本端比对
+    ///
+    /// 元数据未携带哈希时在本端重新计算。接收端回传错误、超时未回传或哈希不一致
+    /// 均视为校验失败
+    async fn confirm_received_hash(
+        &self,
+        stream: &mut TcpStream,
+        task: &TransferTask,
+        file_path: &std::path::Path,
+    ) -> TransferResult<()> {
+        let reply = tokio::time::timeout(
+            std::time::Duration::from_secs(TRANSFER_COMPLETE_TIMEOUT_SECS),
+            async {
+                let header = MessageHeader::read_from_stream(stream).await?;
+                let mut payload = vec![0u8; header.payload_length as usize];
+                stream.read_exact(&mut payload).await?;
+                Ok::<_, TransferError>((header.message_type, payload))
+            },
+        )
+        .await
+        .map_err(|_| TransferError::IntegrityCheckFailed("等待接收端校验结果超时".to_string()))?;
+        let (message_type, payload) = reply?;
+
+        let received_hash = match message_type {
+            MessageType::TransferComplete => {
+                serde_json::from_slice::<TransferCompletePayload>(&payload)?.file_hash
+            }
+            MessageType::Error => {
+                return Err(TransferError::IntegrityCheckFailed(format!(
+                    "接收端校验失败: {}",
+                    String::from_utf8_lossy(&payload)
+                )))
+            }
+            _ => return Err(TransferError::Network("未收到传输完成确认".to_string())),
+        };
+
+        let expected_hash = if task.file.hash.is_empty() {
+            let algorithm = HashAlgorithm::from_metadata(&task.file)?;
+            let path = file_path.to_path_buf();
+            tokio::task::spawn_blocking(move || algorithm.hash_file(&path))
+                .await
+                .map_err(|e| TransferError::Internal(format!("计算文件哈希失败: {}", e)))??
+        } else {
+            task.file.hash.clone()
+        };
+
+        if received_hash.eq_ignore_ascii_case(&expected_hash) {
+            Ok(())
+        } else {
+            Err(TransferError::IntegrityCheckFailed(format!(
+                "接收端文件哈希不一致（期望 {}，实际 {}）",
+                expected_hash, received_hash
+            )))
+        }
+    }
+
+    /// 对端不支持元数据使用的哈希算法时，按 SHA-256 重新计算文件及分块哈希
+    ///
+    /// 分块布局保持不变；对端支持该算法时返回 None，沿用原元数据
+    async fn rehash_for_peer(
+        &self,
+        task: &TransferTask,
+        file_path: &std::path::Path,
+        peer_algorithms: &[HashAlgorithm],
+    ) -> TransferResult<Option<TransferTask>> {
+        let supported = HashAlgorithm::from_name(&task.file.hash_algorithm)
+            .is_some_and(|algorithm| peer_algorithms.contains(&algorithm));
+        if supported {
+            return Ok(None);
+        }
+
+        tracing::info!(
+            task_id = %task.id,
+            hash_algorithm = %task.file.hash_algorithm,
+            "对端不支持该哈希算法，改用 sha256 重新计算"
+        );
+        let mut metadata = task.file.clone();
+        metadata.hash_algorithm = HashAlgorithm::Sha256.as_str().to_string();
+        let chunker = self.chunker_for(task);
+        let path = file_path.to_path_buf();
+        let metadata = tokio::task::spawn_blocking(move || {
+            chunker.compute_metadata_with_hashes(metadata, &path)
+        })
+        .await
+        .map_err(|e| TransferError::Internal(format!("计算文件哈希失败: {}", e)))??;
+
+        let mut rehashed = task.clone();
+        rehashed.file = metadata;
+        Ok(Some(rehashed))
+    }
+
     /// 传输中断时保存断点信息
     async fn save_resume_info_on_interrupt(
         &self,
@@ -665,6 +1805,26 @@ struct HandshakePayload {
     supports_resume: bool,
     /// 加密公钥（X25519，仅在支持加密时有值）
     public_key: Option<Vec<u8>>,
+    /// 支持的加密算法（按偏好排序，旧版本对端缺省时视为仅支持 AES-256-GCM），未识别的算法忽略
+    #[serde(default, deserialize_with = "deserialize_cipher_suites")]
+    supported_ciphers: Vec<CipherSuite>,
+    /// 支持的压缩算法（按偏好排序，旧版本对端缺省时视为仅支持 zstd）
+    #[serde(default)]
+    compression_algorithms: Vec<CompressionAlgorithm>,
+    /// 支持的文件哈希算法（旧版本对端缺省时视为仅支持 SHA-256）
+    #[serde(default)]
+    hash_algorithms: Vec<HashAlgorithm>,
+    /// 提供的可选特性（旧版本对端不携带，未识别的特性被忽略）
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_features"
+    )]
+    features: Option<Vec<ProtocolFeature>>,
+    /// 发送端的分块大小（字节），各文件的实际分块以文件请求中的分块信息为准；
+    /// 连接测试及旧版本对端不携带
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    chunk_size: Option<u64>,
 }
 
 /// 握手响应载荷
@@ -682,6 +1842,47 @@ struct HandshakeAckPayload {
     use_resume: bool,
     /// 加密公钥（X25519，仅在同意加密时有值）
     public_key: Option<Vec<u8>>,
+    /// 选定的加密算法（旧版本对端缺省时为 AES-256-GCM）
+    #[serde(default)]
+    cipher: Option<CipherSuite>,
+    /// 选定的压缩算法（旧版本对端缺省时为 zstd）
+    #[serde(default)]
+    compression_algorithm: Option<CompressionAlgorithm>,
+    /// 接收端能校验的文件哈希算法（旧版本对端缺省时视为仅支持 SHA-256）
+    #[serde(default)]
+    hash_algorithms: Vec<HashAlgorithm>,
+    /// 接收端存储能力（旧版本对端不提供）
+    #[serde(default)]
+    receive_capacity: Option<ReceiveCapacity>,
+    /// 同意使用的可选特性，须为请求中特性的子集（旧版本对端不携带）
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_features"
+    )]
+    features: Option<Vec<ProtocolFeature>>,
+    /// 响应方派生会话后的密钥确认值（旧版本对端不携带）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    key_confirmation: Option<String>,
+}
+
+/// 发送握手请求并读取握手响应
+async fn exchange_handshake(
+    stream: &mut TcpStream,
+    payload: &HandshakePayload,
+) -> TransferResult<HandshakeAckPayload> {
+    let payload_json = serde_json::to_vec(payload)?;
+    let header = MessageHeader::new(MessageType::Handshake, payload_json.len() as u32);
+    stream.write_all(&header.to_bytes()).await?;
+    stream.write_all(&payload_json).await?;
+
+    let ack_header = MessageHeader::read_from_stream(stream).await?;
+    if ack_header.message_type != MessageType::HandshakeAck {
+        return Err(TransferError::Network("未收到握手响应".to_string()));
+    }
+    let mut ack_buf = vec![0u8; ack_header.payload_length as usize];
+    stream.read_exact(&mut ack_buf).await?;
+    Ok(serde_json::from_slice(&ack_buf)?)
 }
 
 /// 协商后的传输特性
@@ -692,8 +1893,73 @@ struct NegotiatedFeatures {
     encryption: bool,
     /// 是否使用压缩
     compression: bool,
+    /// 使用的压缩算法
+    compression_algorithm: CompressionAlgorithm,
+    /// 对端能校验的文件哈希算法
+    hash_algorithms: Vec<HashAlgorithm>,
     /// 是否使用断点续传
     resume: bool,
+    /// 使用的加密算法
+    cipher: CipherSuite,
+    /// 是否等待接收端回传文件哈希
+    verify_after_send: bool,
+}
+
+/// 已完成握手的发送连接，批量发送时多个文件复用同一连接和加密会话
+struct SendSession {
+    /// TCP 连接
+    stream: TcpStream,
+    /// 协商后的传输特性
+    negotiated: NegotiatedFeatures,
+    /// 加密会话（协商使用加密时存在）
+    crypto_session: Option<crate::transfer::crypto::CryptoSession>,
+    /// 对端是否支持在同一连接上连续发送多个文件
+    batch: bool,
+}
+
+/// 批量清单载荷
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BatchManifestPayload {
+    /// 批次 ID
+    batch_id: String,
+    /// 该连接上将要发送的文件数
+    file_count: usize,
+    /// 这些文件的总大小（字节）
+    total_size: u64,
+}
+
+/// 任务的源文件路径，文件不存在时返回错误
+fn source_path(task: &TransferTask) -> TransferResult<&std::path::Path> {
+    let file_path = task
+        .file
+        .path
+        .as_deref()
+        .map(std::path::Path::new)
+        .ok_or_else(|| TransferError::InvalidMetadata("文件路径未设置".to_string()))?;
+    if !file_path.exists() {
+        return Err(TransferError::FileNotFound(file_path.display().to_string()));
+    }
+    Ok(file_path)
+}
+
+/// 任务目标设备的地址
+fn peer_addr(task: &TransferTask) -> TransferResult<SocketAddr> {
+    let peer = task
+        .peer
+        .as_ref()
+        .ok_or_else(|| TransferError::PeerUnreachable("未指定目标设备".to_string()))?;
+    format!("{}:{}", peer.ip, peer.port)
+        .parse()
+        .map_err(|e| TransferError::PeerUnreachable(format!("无效的地址: {}", e)))
+}
+
+/// 传输完成确认载荷
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TransferCompletePayload {
+    /// 接收端对落盘文件计算的哈希，算法与文件元数据中的 `hash_algorithm` 一致
+    file_hash: String,
 }
 
 /// 文件传输请求响应
@@ -727,6 +1993,287 @@ struct ChunkAck {
     success: bool,
 }
 
+/// 读取分块确认的内容，返回对端是否接受了分块 `index`
+///
+/// 不带内容的确认视为接受（旧版本接收端只回复消息头）；
+/// 对端校验分块哈希失败时回复 `success: false`，由发送端重发该分块。
+/// 收到其他类型的消息属于协议错误，返回错误后连接不再复用，其内容无需读取
+async fn read_chunk_ack(
+    stream: &mut TcpStream,
+    header: &MessageHeader,
+    index: u32,
+) -> TransferResult<bool> {
+    if header.message_type != MessageType::ChunkAck {
+        return Err(TransferError::Network(format!(
+            "等待分块 {} 的确认时收到意外消息: {:?}",
+            index, header.message_type
+        )));
+    }
+    if header.payload_length == 0 {
+        return Ok(true);
+    }
+    let mut payload = vec![0u8; header.payload_length as usize];
+    stream.read_exact(&mut payload).await?;
+    let ack: ChunkAck = serde_json::from_slice(&payload)?;
+    if ack.index != index {
+        return Err(TransferError::Network(format!(
+            "分块确认不匹配：期望 {}，收到 {}",
+            index, ack.index
+        )));
+    }
+    Ok(ack.success)
+}
+
+/// 预处理完成的分块（已读取并按需压缩，尚未加密）
+struct PreparedChunk {
+    /// 分块索引
+    index: u32,
+    /// 分块数据
+    data: Vec<u8>,
+    /// 数据是否经过压缩
+    compressed: bool,
+}
+
+/// 读取并按需压缩单个分块
+fn prepare_chunk(
+    chunker: &FileChunker,
+    file_path: &std::path::Path,
+    chunk: &ChunkInfo,
+    compressor: Option<&Compressor>,
+    mime_type: &str,
+) -> TransferResult<PreparedChunk> {
+    let raw_data = chunker.read_chunk(file_path, chunk)?;
+    Ok(match compress_chunk(&raw_data, compressor, mime_type)? {
+        Some(compressed) => PreparedChunk {
+            index: chunk.index,
+            data: compressed,
+            compressed: true,
+        },
+        None => PreparedChunk {
+            index: chunk.index,
+            data: raw_data,
+            compressed: false,
+        },
+    })
+}
+
+/// 读取并按需压缩单个分块，同时保留原始数据（多设备发送）
+fn prepare_shared_chunk(
+    chunker: &FileChunker,
+    file_path: &std::path::Path,
+    chunk: &ChunkInfo,
+    compressor: Option<&Compressor>,
+    mime_type: &str,
+) -> TransferResult<SharedChunk> {
+    let raw = chunker.read_chunk(file_path, chunk)?;
+    let compressed = compress_chunk(&raw, compressor, mime_type)?;
+    Ok(SharedChunk {
+        index: chunk.index,
+        raw,
+        compressed,
+        algorithm: compressor.map(Compressor::algorithm).unwrap_or_default(),
+    })
+}
+
+/// 按压缩策略压缩分块，仅当压缩后更小时返回压缩数据
+fn compress_chunk(
+    raw_data: &[u8],
+    compressor: Option<&Compressor>,
+    mime_type: &str,
+) -> TransferResult<Option<Vec<u8>>> {
+    let Some(compressor) = compressor else {
+        return Ok(None);
+    };
+
+    let original_size = raw_data.len();
+    let (compressed, attempted_size) = match compressor.get_level(mime_type) {
+        Some(level) => {
+            let compressed = compressor.compress(raw_data, level)?;
+            let attempted_size = compressed.len();
+            // 仅当压缩后更小时才使用压缩数据
+            ((attempted_size < original_size).then_some(compressed), Some(attempted_size))
+        }
+        None => (None, None),
+    };
+    crate::transfer::compression::record_chunk_compression(
+        mime_type,
+        original_size,
+        attempted_size,
+        compressed.is_some(),
+    );
+    Ok(compressed)
+}
+
+/// 多设备共享的预处理分块
+///
+/// 压缩只进行一次，同时保留原始数据，供未协商压缩的设备使用
+struct SharedChunk {
+    /// 分块索引
+    index: u32,
+    /// 原始数据
+    raw: Vec<u8>,
+    /// 压缩后的数据（压缩未启用或压缩后未变小时为空）
+    compressed: Option<Vec<u8>>,
+    /// 压缩使用的算法，协商了其他算法的设备改发原始数据
+    algorithm: CompressionAlgorithm,
+}
+
+/// 共享分块来源产出的结果
+type SharedChunkResult = Result<Arc<SharedChunk>, Arc<str>>;
+
+/// 单个连接的分块来源
+enum ChunkFeed {
+    /// 本连接独占的预处理流水线（只含待发送的分块）
+    Pipeline(mpsc::Receiver<TransferResult<PreparedChunk>>),
+    /// 多设备共享的分块（包含全部分块，续传时跳过已发送的部分）
+    Shared {
+        receiver: mpsc::Receiver<SharedChunkResult>,
+        /// 本连接协商的压缩算法，未协商压缩时为 None
+        compression: Option<CompressionAlgorithm>,
+    },
+}
+
+impl ChunkFeed {
+    /// 取出指定索引的分块，来源结束时返回 None
+    async fn next(&mut self, index: u32) -> Option<TransferResult<PreparedChunk>> {
+        match self {
+            ChunkFeed::Pipeline(receiver) => receiver.recv().await,
+            ChunkFeed::Shared {
+                receiver,
+                compression,
+            } => loop {
+                let chunk = match receiver.recv().await? {
+                    Ok(chunk) => chunk,
+                    Err(message) => {
+                        return Some(Err(TransferError::Internal(message.to_string())))
+                    }
+                };
+                if chunk.index < index {
+                    continue;
+                }
+                let prepared = match (&chunk.compressed, *compression) {
+                    (Some(data), Some(algorithm)) if algorithm == chunk.algorithm => {
+                        PreparedChunk {
+                            index: chunk.index,
+                            data: data.clone(),
+                            compressed: true,
+                        }
+                    }
+                    _ => PreparedChunk {
+                        index: chunk.index,
+                        data: chunk.raw.clone(),
+                        compressed: false,
+                    },
+                };
+                return Some(Ok(prepared));
+            },
+        }
+    }
+}
+
+/// 将预处理流水线的分块分发给多个设备的连接
+///
+/// 连接结束（接收端被丢弃）的设备不再分发，所有设备都结束后停止
+async fn fan_out_chunks(
+    mut source: mpsc::Receiver<TransferResult<SharedChunk>>,
+    mut senders: Vec<mpsc::Sender<SharedChunkResult>>,
+) {
+    while let Some(result) = source.recv().await {
+        let shared: SharedChunkResult = match result {
+            Ok(chunk) => Ok(Arc::new(chunk)),
+            Err(e) => Err(e.to_string().into()),
+        };
+
+        let mut alive = Vec::with_capacity(senders.len());
+        for sender in senders {
+            if sender.send(shared.clone()).await.is_ok() {
+                alive.push(sender);
+            }
+        }
+        senders = alive;
+        if senders.is_empty() || shared.is_err() {
+            break;
+        }
+    }
+}
+
+/// 启动分块预处理流水线
+///
+/// 在阻塞线程池中并行读取、压缩分块（由 `prepare` 处理单个分块），并按原始顺序写入有界通道，
+/// 让 CPU 密集的压缩提前于网络发送进行。接收端被丢弃时流水线随之停止，
+/// 遇到错误时发送该错误后结束
+fn spawn_chunk_pipeline<T: Send + 'static>(
+    file_path: PathBuf,
+    chunker: FileChunker,
+    chunks: Vec<ChunkInfo>,
+    compressor: Option<Compressor>,
+    mime_type: String,
+    prepare: fn(
+        &FileChunker,
+        &std::path::Path,
+        &ChunkInfo,
+        Option<&Compressor>,
+        &str,
+    ) -> TransferResult<T>,
+) -> mpsc::Receiver<TransferResult<T>> {
+    let depth = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(2)
+        .clamp(2, MAX_PIPELINE_DEPTH);
+    let (tx, rx) = mpsc::channel(depth);
+
+    let file_path = Arc::new(file_path);
+    let chunker = Arc::new(chunker);
+    let compressor = Arc::new(compressor);
+    let mime_type: Arc<str> = mime_type.into();
+
+    tokio::spawn(async move {
+        let mut prepared = futures::stream::iter(chunks)
+            .map(|chunk| {
+                let file_path = file_path.clone();
+                let chunker = chunker.clone();
+                let compressor = compressor.clone();
+                let mime_type = mime_type.clone();
+                tokio::task::spawn_blocking(move || {
+                    prepare(
+                        &chunker,
+                        &file_path,
+                        &chunk,
+                        (*compressor).as_ref(),
+                        &mime_type,
+                    )
+                })
+            })
+            .buffered(depth);
+
+        while let Some(joined) = prepared.next().await {
+            let result = joined.unwrap_or_else(|e| {
+                Err(TransferError::Internal(format!("分块预处理任务失败: {}", e)))
+            });
+            let failed = result.is_err();
+            if tx.send(result).await.is_err() || failed {
+                break;
+            }
+        }
+    });
+
+    rx
+}
+
+/// 绑定接收监听器，指定端口被占用时回退到系统分配的端口
+///
+/// 调用方通过比较实际端口与请求端口判断是否发生了回退
+async fn bind_receive_listener(port: u16) -> TransferResult<TcpListener> {
+    match TcpListener::bind(("0.0.0.0", port)).await {
+        Ok(listener) => Ok(listener),
+        Err(e) if port != 0 && e.kind() == std::io::ErrorKind::AddrInUse => {
+            tracing::warn!(port, "接收端口已被占用，改用系统分配的端口");
+            Ok(TcpListener::bind(("0.0.0.0", 0)).await?)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
 #[async_trait]
 impl Transport for LocalTransport {
     async fn initialize(&self) -> TransferResult<()> {
@@ -736,7 +2283,7 @@ impl Transport for LocalTransport {
         }
 
         // 创建 TCP 监听器
-        let listener = TcpListener::bind(format!("0.0.0.0:{}", self.listen_port)).await?;
+        let listener = bind_receive_listener(self.listen_port).await?;
 
         let mut listener_guard = self.listener.lock().await;
         *listener_guard = Some(listener);
@@ -752,16 +2299,8 @@ impl Transport for LocalTransport {
             ));
         }
 
-        let peer = task
-            .peer
-            .as_ref()
-            .ok_or_else(|| TransferError::PeerUnreachable("未指定目标设备".to_string()))?;
-
-        let addr: SocketAddr = format!("{}:{}", peer.ip, peer.port)
-            .parse()
-            .map_err(|e| TransferError::PeerUnreachable(format!("无效的地址: {}", e)))?;
-
-        self.send_file_to(task, addr).await
+        let addr = peer_addr(task)?;
+        self.send_file_to(task, addr, None).await
     }
 
     async fn receive(&self, _task: &TransferTask) -> TransferResult<TransferProgress> {
@@ -775,6 +2314,7 @@ impl Transport for LocalTransport {
         if let Some(sender) = self.cancel_senders.write().await.remove(task_id) {
             let _ = sender.send(()).await;
         }
+        self.pause_flags.write().await.remove(task_id);
         if let Some(task_state) = self.active_tasks.write().await.get_mut(task_id) {
             task_state.cancelled = true;
             task_state.progress.status = crate::models::TaskStatus::Cancelled;
@@ -794,6 +2334,7 @@ impl Transport for LocalTransport {
         // 清理资源
         self.active_tasks.write().await.clear();
         self.cancel_senders.write().await.clear();
+        self.pause_flags.write().await.clear();
         *self.listener.lock().await = None;
         *self.initialized.lock().await = false;
         Ok(())
@@ -820,4 +2361,347 @@ mod tests {
         let bytes = header.to_bytes();
         assert_eq!(bytes.len(), 10);
     }
+
+    #[tokio::test]
+    async fn test_batch_manifest_header_round_trip() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let mut sender = TcpStream::connect(addr).await.unwrap();
+        let (mut receiver, _) = listener.accept().await.unwrap();
+
+        let manifest = BatchManifestPayload {
+            batch_id: "batch".to_string(),
+            file_count: 3,
+            total_size: 4096,
+        };
+        let manifest_json = serde_json::to_vec(&manifest).unwrap();
+        let header = MessageHeader::new(MessageType::BatchManifest, manifest_json.len() as u32);
+        sender.write_all(&header.to_bytes()).await.unwrap();
+        sender.write_all(&manifest_json).await.unwrap();
+
+        let header = MessageHeader::read_from_stream(&mut receiver)
+            .await
+            .unwrap();
+        assert_eq!(header.message_type, MessageType::BatchManifest);
+        let mut payload = vec![0u8; header.payload_length as usize];
+        receiver.read_exact(&mut payload).await.unwrap();
+        let received: BatchManifestPayload = serde_json::from_slice(&payload).unwrap();
+        assert_eq!((received.file_count, received.total_size), (3, 4096));
+    }
+
+    #[tokio::test]
+    async fn test_failed_chunk_ack_requests_resend() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let mut sender = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (mut receiver, _) = listener.accept().await.unwrap();
+
+        for success in [false, true] {
+            let ack = serde_json::to_vec(&ChunkAck { index: 4, success }).unwrap();
+            let header = MessageHeader::new(MessageType::ChunkAck, ack.len() as u32);
+            receiver.write_all(&header.to_bytes()).await.unwrap();
+            receiver.write_all(&ack).await.unwrap();
+
+            let header = MessageHeader::read_from_stream(&mut sender).await.unwrap();
+            assert_eq!(
+                read_chunk_ack(&mut sender, &header, 4).await.unwrap(),
+                success
+            );
+        }
+
+        // 不带内容的确认视为接受
+        let header = MessageHeader::new(MessageType::ChunkAck, 0);
+        assert!(read_chunk_ack(&mut sender, &header, 5).await.unwrap());
+
+        // 其他类型的消息不能当作确认
+        let header = MessageHeader::new(MessageType::Heartbeat, 0);
+        assert!(read_chunk_ack(&mut sender, &header, 5).await.is_err());
+    }
+
+    #[test]
+    fn test_receive_capacity_probe_creates_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("not").join("created");
+        let capacity = ReceiveCapacity::probe(&missing);
+        assert!(!missing.exists());
+        assert!(capacity.writable);
+        assert!(capacity.available_bytes.is_some());
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+
+        // 可用空间未知时不视为空间不足
+        let unknown = ReceiveCapacity {
+            available_bytes: None,
+            ..capacity
+        };
+        assert!(unknown.check(u64::MAX).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_probe_listener() {
+        let transport = LocalTransport::new();
+        assert!(transport.probe_listener().await.is_err());
+
+        transport.initialize().await.unwrap();
+        let port = transport.get_listen_port().await.unwrap();
+        assert_eq!(transport.probe_listener().await.unwrap(), port);
+        // 探测连接已被接受，不会积压在监听队列中
+        assert_eq!(transport.probe_listener().await.unwrap(), port);
+    }
+
+    #[tokio::test]
+    async fn test_pause_holds_until_resumed() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let mut sender = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let _receiver = listener.accept().await.unwrap();
+
+        let transport = LocalTransport::new();
+        assert!(transport.pause("task").await.is_err());
+        let paused = transport.register_pause("task").await;
+        transport.pause("task").await.unwrap();
+
+        let file = crate::models::FileMetadata::new("a.bin".to_string(), 10, String::new());
+        let task = TransferTask::new(
+            file,
+            TransferMode::Local,
+            crate::models::TransferDirection::Send,
+        );
+        let mut task_state = TransferTaskState::new(TransferProgress::from(&task));
+        let (cancel_tx, mut cancel_rx) = mpsc::channel::<()>(1);
+
+        let resumer = transport.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+            resumer.resume("task").await.unwrap();
+        });
+        transport
+            .hold_paused(&mut sender, &paused, &mut cancel_rx, &mut task_state)
+            .await
+            .unwrap();
+        assert_eq!(
+            task_state.progress.status,
+            crate::models::TaskStatus::Transferring
+        );
+
+        // 暂停期间取消
+        transport.pause("task").await.unwrap();
+        cancel_tx.send(()).await.unwrap();
+        let result = transport
+            .hold_paused(&mut sender, &paused, &mut cancel_rx, &mut task_state)
+            .await;
+        assert!(matches!(result, Err(TransferError::Cancelled)));
+        assert_eq!(
+            task_state.progress.status,
+            crate::models::TaskStatus::Paused
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cancel_all_skips_finished_tasks() {
+        let transport = LocalTransport::new();
+        let (running_tx, mut running_rx) = mpsc::channel::<()>(1);
+        let (finished_tx, finished_rx) = mpsc::channel::<()>(1);
+        drop(finished_rx);
+        {
+            let mut senders = transport.cancel_senders.write().await;
+            senders.insert("running".to_string(), running_tx);
+            senders.insert("finished".to_string(), finished_tx);
+        }
+
+        let cancelled = transport.clone().cancel_all().await;
+        assert_eq!(cancelled, vec!["running".to_string()]);
+        assert!(running_rx.try_recv().is_ok());
+        assert!(transport.cancel_senders.read().await.is_empty());
+        assert!(transport.cancel_all().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_listener_falls_back_when_port_in_use() {
+        let occupied = TcpListener::bind(("0.0.0.0", 0)).await.unwrap();
+        let occupied_port = occupied.local_addr().unwrap().port();
+
+        let transport = LocalTransport::with_port(occupied_port);
+        transport.initialize().await.unwrap();
+        let port = transport.get_listen_port().await.unwrap();
+        assert_ne!(port, occupied_port);
+
+        // 重新绑定时沿用自身端口
+        assert_eq!(transport.rebind(port).await.unwrap(), port);
+        assert_ne!(transport.rebind(occupied_port).await.unwrap(), occupied_port);
+    }
+
+    #[tokio::test]
+    async fn test_chunk_pipeline_preserves_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("data.txt");
+        let content: Vec<u8> = (0..10_000u32)
+            .flat_map(|i| format!("line {}\n", i % 97).into_bytes())
+            .collect();
+        std::fs::write(&file_path, &content).unwrap();
+
+        let chunker = FileChunker::new(4096);
+        let chunks = chunker.compute_chunks(&file_path).unwrap();
+        let mut rx = spawn_chunk_pipeline(
+            file_path,
+            chunker,
+            chunks.clone(),
+            Some(Compressor::smart()),
+            "text/plain".to_string(),
+            prepare_chunk,
+        );
+
+        let mut restored = Vec::new();
+        for chunk in &chunks {
+            let prepared = rx.recv().await.unwrap().unwrap();
+            assert_eq!(prepared.index, chunk.index);
+            if prepared.compressed {
+                let data = CompressionAlgorithm::Zstd
+                    .decompress(&prepared.data)
+                    .unwrap();
+                restored.extend(data);
+            } else {
+                restored.extend(prepared.data);
+            }
+        }
+        assert!(rx.recv().await.is_none());
+        assert_eq!(restored, content);
+    }
+
+    #[tokio::test]
+    async fn test_fan_out_serves_each_peer_independently() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("data.txt");
+        let content: Vec<u8> = (0..10_000u32)
+            .flat_map(|i| format!("line {}\n", i % 97).into_bytes())
+            .collect();
+        std::fs::write(&file_path, &content).unwrap();
+
+        let chunker = FileChunker::new(4096);
+        let chunks = chunker.compute_chunks(&file_path).unwrap();
+        let source = spawn_chunk_pipeline(
+            file_path,
+            chunker,
+            chunks.clone(),
+            Some(Compressor::smart()),
+            "text/plain".to_string(),
+            prepare_shared_chunk,
+        );
+        let (compressed_tx, compressed_rx) = mpsc::channel(FANOUT_BUFFER_CHUNKS);
+        let (raw_tx, raw_rx) = mpsc::channel(FANOUT_BUFFER_CHUNKS);
+        let (dropped_tx, dropped_rx) = mpsc::channel(FANOUT_BUFFER_CHUNKS);
+        // 提前结束的设备不影响其他设备
+        drop(dropped_rx);
+        tokio::spawn(fan_out_chunks(source, vec![compressed_tx, raw_tx, dropped_tx]));
+
+        let mut compressed_feed = ChunkFeed::Shared {
+            receiver: compressed_rx,
+            compression: Some(CompressionAlgorithm::Zstd),
+        };
+        // 续传的设备从第 2 个分块开始
+        let mut raw_feed = ChunkFeed::Shared {
+            receiver: raw_rx,
+            compression: None,
+        };
+
+        let mut restored = Vec::new();
+        let mut resumed = Vec::new();
+        for chunk in &chunks {
+            let prepared = compressed_feed.next(chunk.index).await.unwrap().unwrap();
+            assert_eq!(prepared.index, chunk.index);
+            if prepared.compressed {
+                let data = CompressionAlgorithm::Zstd
+                    .decompress(&prepared.data)
+                    .unwrap();
+                restored.extend(data);
+            } else {
+                restored.extend(prepared.data);
+            }
+
+            if chunk.index >= 2 {
+                let prepared = raw_feed.next(chunk.index).await.unwrap().unwrap();
+                assert_eq!(prepared.index, chunk.index);
+                assert!(!prepared.compressed);
+                resumed.extend(prepared.data);
+            }
+        }
+        assert_eq!(restored, content);
+        assert_eq!(resumed, content[2 * 4096..]);
+    }
+
+    #[test]
+    fn test_speed_window_tracks_recent_rate() {
+        let start = std::time::Instant::now();
+        let at = |secs: u64| start + std::time::Duration::from_secs(secs);
+        let file = crate::models::FileMetadata::new("a.bin".to_string(), 10_000, String::new());
+        let task = TransferTask::new(
+            file,
+            TransferMode::Local,
+            crate::models::TransferDirection::Send,
+        );
+        let mut state = TransferTaskState::new(TransferProgress::from(&task));
+        state.speed_window.restart(start);
+
+        // 起步较慢：前 10 秒每秒 10 字节
+        for secs in 1..=10 {
+            state.speed_window.record(at(secs), 10);
+        }
+        // 之后提速到每秒 1000 字节，窗口内只反映最近的速度
+        for secs in 11..=15 {
+            state.speed_window.record(at(secs), 1000);
+        }
+        state.progress.transferred_bytes = 5100;
+        state.refresh_speed(at(15));
+        assert_eq!(state.progress.speed, 1000);
+        assert_eq!(state.progress.estimated_time_remaining, Some(4));
+
+        // 窗口内没有确认时速度为 0，剩余时间未知
+        state.refresh_speed(at(20));
+        assert_eq!(state.progress.speed, 0);
+        assert_eq!(state.progress.estimated_time_remaining, None);
+    }
+
+    #[tokio::test]
+    async fn test_rehash_for_peer_falls_back_to_sha256() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("data.bin");
+        std::fs::write(&file_path, [7u8; 300]).unwrap();
+
+        let mut file = crate::models::FileMetadata::new("data.bin".to_string(), 300, String::new());
+        file.hash_algorithm = HashAlgorithm::Blake3.as_str().to_string();
+        let file = FileChunker::new(100)
+            .compute_metadata_with_hashes(file, &file_path)
+            .unwrap();
+        let task = TransferTask::new(
+            file,
+            TransferMode::Local,
+            crate::models::TransferDirection::Send,
+        );
+
+        let transport = LocalTransport::new();
+        let unchanged = transport
+            .rehash_for_peer(&task, &file_path, &supported_hash_algorithms())
+            .await
+            .unwrap();
+        assert!(unchanged.is_none());
+
+        // 旧版本对端只认 SHA-256，按原分块布局重新计算
+        let rehashed = transport
+            .rehash_for_peer(&task, &file_path, &[HashAlgorithm::Sha256])
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(rehashed.id, task.id);
+        assert_eq!(rehashed.file.hash_algorithm, "sha256");
+        assert_eq!(
+            rehashed.file.hash,
+            HashAlgorithm::Sha256.hash_file(&file_path).unwrap()
+        );
+        assert_eq!(rehashed.file.chunks.len(), 3);
+        assert_eq!(
+            rehashed.file.chunks[2].hash,
+            FileChunker::compute_hash(&[7u8; 100])
+        );
+    }
 }
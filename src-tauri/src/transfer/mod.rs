@@ -1,18 +1,30 @@
 //! 传输核心模块
 
+pub mod batch;
 mod chunker;
 mod cloud;
 mod commands;
 pub mod compression;
 pub mod crypto;
+pub mod features;
 pub mod http_crypto;
+pub mod timestamps;
+mod filename;
+mod identity;
 mod integrity;
 mod local;
+mod peer_history;
 mod resume;
+mod throttle;
 mod transport;
 
 pub use chunker::*;
+pub use cloud::*;
 pub use commands::*;
+pub use filename::*;
+pub use identity::*;
 pub use integrity::*;
 pub use local::*;
+pub use peer_history::{peer_history, record_peer_transfer, PeerHistoryEntry};
+pub use throttle::{set_transfer_speed_limit_internal, transfer_speed_limit};
 pub use transport::*;
@@ -0,0 +1,202 @@
+//! 设备传输历史
+//!
+//! 按对端设备（有指纹时用指纹，否则用设备 ID）记录已结束的传输，持久化到应用数据目录
+
+use crate::models::{TaskStatus, TransferDirection, TransferTask};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+
+/// 持久化文件名
+const PEER_HISTORY_FILENAME: &str = "peer_history.json";
+
+/// 每个设备保留的记录上限
+const MAX_ENTRIES_PER_PEER: usize = 200;
+
+/// 所有设备合计保留的记录上限
+const MAX_TOTAL_ENTRIES: usize = 2000;
+
+/// 设备 -> 传输记录（最新在前）
+static PEER_HISTORY: OnceLock<RwLock<HashMap<String, Vec<PeerHistoryEntry>>>> = OnceLock::new();
+
+/// 设备传输记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerHistoryEntry {
+    /// 任务 ID
+    pub task_id: String,
+    /// 文件名
+    pub file_name: String,
+    /// 文件大小（字节）
+    pub file_size: u64,
+    /// 传输方向
+    pub direction: TransferDirection,
+    /// 最终状态
+    pub status: TaskStatus,
+    /// 结束时间（毫秒）
+    pub timestamp: u64,
+}
+
+fn peer_history_path() -> PathBuf {
+    crate::app_data_dir().join(PEER_HISTORY_FILENAME)
+}
+
+fn get_peer_history_lock() -> &'static RwLock<HashMap<String, Vec<PeerHistoryEntry>>> {
+    PEER_HISTORY.get_or_init(|| RwLock::new(load(&peer_history_path())))
+}
+
+fn load(path: &Path) -> HashMap<String, Vec<PeerHistoryEntry>> {
+    std::fs::read(path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+fn save(path: &Path, history: &HashMap<String, Vec<PeerHistoryEntry>>) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("无法创建目录: {}", e))?;
+    }
+    let json = serde_json::to_vec(history).map_err(|e| e.to_string())?;
+    std::fs::write(path, json).map_err(|e| format!("保存传输历史失败: {}", e))
+}
+
+/// 插入记录并按上限淘汰最旧的记录
+fn insert_entry(
+    history: &mut HashMap<String, Vec<PeerHistoryEntry>>,
+    peer_key: String,
+    entry: PeerHistoryEntry,
+) {
+    let entries = history.entry(peer_key).or_default();
+    entries.insert(0, entry);
+    entries.truncate(MAX_ENTRIES_PER_PEER);
+
+    let mut total: usize = history.values().map(Vec::len).sum();
+    while total > MAX_TOTAL_ENTRIES {
+        let oldest_peer = history
+            .iter()
+            .filter_map(|(key, entries)| entries.last().map(|e| (key, e.timestamp)))
+            .min_by_key(|(_, timestamp)| *timestamp)
+            .map(|(key, _)| key.clone());
+        let Some(oldest_peer) = oldest_peer else {
+            break;
+        };
+        if let Some(entries) = history.get_mut(&oldest_peer) {
+            entries.pop();
+            if entries.is_empty() {
+                history.remove(&oldest_peer);
+            }
+        }
+        total -= 1;
+    }
+}
+
+/// 记录已结束的传输，未指定对端或任务未结束时忽略
+pub fn record_peer_transfer(task: &TransferTask) {
+    let Some(peer) = task.peer.as_ref() else {
+        return;
+    };
+    if !matches!(
+        task.status,
+        TaskStatus::Completed | TaskStatus::Failed | TaskStatus::Cancelled
+    ) {
+        return;
+    }
+
+    let entry = PeerHistoryEntry {
+        task_id: task.id.clone(),
+        file_name: task.file.name.clone(),
+        file_size: task.file.size,
+        direction: task.direction,
+        status: task.status,
+        timestamp: task.completed_at.unwrap_or_else(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64
+        }),
+    };
+    let peer_key = peer.fingerprint.clone().unwrap_or_else(|| peer.id.clone());
+
+    let Ok(mut history) = get_peer_history_lock().write() else {
+        return;
+    };
+    insert_entry(&mut history, peer_key, entry);
+    if let Err(e) = save(&peer_history_path(), &history) {
+        tracing::warn!(error = %e, "传输历史保存失败");
+    }
+}
+
+/// 获取设备的传输记录（最新在前），`peer_key` 为设备指纹或设备 ID
+pub fn peer_history(peer_key: &str) -> Vec<PeerHistoryEntry> {
+    get_peer_history_lock()
+        .read()
+        .ok()
+        .and_then(|history| history.get(peer_key).cloned())
+        .unwrap_or_default()
+}
+
+/// 清除传输记录，未指定设备时清除全部，返回清除的记录数
+pub fn clear_peer_history(peer_key: Option<&str>) -> Result<usize, String> {
+    let mut history = get_peer_history_lock().write().map_err(|e| e.to_string())?;
+    let cleared = match peer_key {
+        Some(key) => history.remove(key).map_or(0, |entries| entries.len()),
+        None => history.drain().map(|(_, entries)| entries.len()).sum(),
+    };
+    if cleared > 0 {
+        save(&peer_history_path(), &history)?;
+    }
+    Ok(cleared)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(timestamp: u64) -> PeerHistoryEntry {
+        PeerHistoryEntry {
+            task_id: timestamp.to_string(),
+            file_name: "a.txt".to_string(),
+            file_size: 1,
+            direction: TransferDirection::Send,
+            status: TaskStatus::Completed,
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn test_entry_caps() {
+        let mut history = HashMap::new();
+        for i in 0..MAX_ENTRIES_PER_PEER as u64 + 10 {
+            insert_entry(&mut history, "alice".to_string(), entry(i));
+        }
+        let alice = &history["alice"];
+        assert_eq!(alice.len(), MAX_ENTRIES_PER_PEER);
+        assert_eq!(alice[0].timestamp, MAX_ENTRIES_PER_PEER as u64 + 9);
+
+        // 超出总上限时淘汰所有设备中最旧的记录
+        let peers = MAX_TOTAL_ENTRIES / MAX_ENTRIES_PER_PEER;
+        for peer in 1..peers {
+            for i in 0..MAX_ENTRIES_PER_PEER as u64 {
+                insert_entry(&mut history, format!("peer-{}", peer), entry(1_000 + i));
+            }
+        }
+        insert_entry(&mut history, "bob".to_string(), entry(5_000));
+        let total: usize = history.values().map(Vec::len).sum();
+        assert_eq!(total, MAX_TOTAL_ENTRIES);
+        assert_eq!(history["alice"].len(), MAX_ENTRIES_PER_PEER - 1);
+        assert_eq!(history["alice"].last().unwrap().timestamp, 11);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(PEER_HISTORY_FILENAME);
+        let mut history = HashMap::new();
+        insert_entry(&mut history, "alice".to_string(), entry(42));
+        save(&path, &history).unwrap();
+
+        let loaded = load(&path);
+        assert_eq!(loaded["alice"][0].task_id, "42");
+    }
+}
@@ -81,6 +81,21 @@ impl ResumeInfo {
         }
     }
 
+    /// 检查是否与给定内容指向同一份部分文件（文件哈希 + 大小 + 对端 + 方向）
+    pub fn matches_content(
+        &self,
+        file_hash: &str,
+        file_size: u64,
+        peer_ip: &str,
+        direction: &str,
+    ) -> bool {
+        !file_hash.is_empty()
+            && self.file_hash == file_hash
+            && self.file_size == file_size
+            && self.peer_ip == peer_ip
+            && self.direction == direction
+    }
+
     /// 检查断点信息是否已过期
     pub fn is_expired(&self) -> bool {
         let now = SystemTime::now()
@@ -196,9 +211,21 @@ impl ResumeManager {
     }
 
     /// 保存断点信息
+    ///
+    /// 同一份内容（哈希、大小、对端、方向相同）只保留一条断点记录，
+    /// 避免新旧任务 ID 同时声明同一个部分文件
     pub async fn save_resume_info(&self, info: ResumeInfo) -> TransferResult<()> {
         {
             let mut cache = self.resume_infos.write().await;
+            cache.retain(|task_id, existing| {
+                task_id == &info.task_id
+                    || !existing.matches_content(
+                        &info.file_hash,
+                        info.file_size,
+                        &info.peer_ip,
+                        &info.direction,
+                    )
+            });
             cache.insert(info.task_id.clone(), info);
         }
         self.save().await
@@ -216,6 +243,32 @@ impl ResumeManager {
         })
     }
 
+    /// 查找断点信息
+    ///
+    /// 优先按任务 ID 查找；找不到时按内容（文件哈希、大小、对端、方向）查找，
+    /// 使重新发起的同内容任务（新任务 ID）也能沿用已有断点
+    pub async fn find_resume_info(
+        &self,
+        task_id: &str,
+        file_hash: &str,
+        file_size: u64,
+        peer_ip: &str,
+        direction: &str,
+    ) -> Option<ResumeInfo> {
+        if let Some(info) = self.get_resume_info(task_id).await {
+            return Some(info);
+        }
+
+        let cache = self.resume_infos.read().await;
+        cache
+            .values()
+            .filter(|info| {
+                !info.is_expired() && info.matches_content(file_hash, file_size, peer_ip, direction)
+            })
+            .max_by_key(|info| info.interrupted_at)
+            .cloned()
+    }
+
     /// 获取所有可恢复的任务列表
     pub async fn get_resumable_tasks(&self) -> Vec<ResumableTaskInfo> {
         let cache = self.resume_infos.read().await;
@@ -333,4 +386,47 @@ mod tests {
         // 清理
         let _ = std::fs::remove_dir_all(&temp_dir);
     }
+
+    #[tokio::test]
+    async fn test_find_resume_info_by_content() {
+        let temp_dir = std::env::temp_dir().join("puresend_test_resume_content");
+        let _ = std::fs::remove_dir_all(&temp_dir);
+
+        let manager = ResumeManager::new(temp_dir.clone());
+        let new_info = |task_id: &str, chunk: u32| {
+            ResumeInfo::new(
+                task_id.to_string(),
+                "file.txt".to_string(),
+                2000,
+                "hash123".to_string(),
+                chunk as u64 * 100,
+                chunk,
+                "10.0.0.1".to_string(),
+                9090,
+                "send".to_string(),
+            )
+        };
+
+        manager.save_resume_info(new_info("old-task", 3)).await.unwrap();
+
+        // 新任务 ID，相同内容与对端，可沿用旧断点
+        let found = manager
+            .find_resume_info("new-task", "hash123", 2000, "10.0.0.1", "send")
+            .await;
+        assert_eq!(found.unwrap().task_id, "old-task");
+
+        // 不同对端不应匹配
+        assert!(manager
+            .find_resume_info("new-task", "hash123", 2000, "10.0.0.2", "send")
+            .await
+            .is_none());
+
+        // 新任务保存断点后，旧任务的断点被去重移除
+        manager.save_resume_info(new_info("new-task", 5)).await.unwrap();
+        assert!(manager.get_resume_info("old-task").await.is_none());
+        assert_eq!(manager.get_resumable_tasks().await.len(), 1);
+
+        // 清理
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
 }
@@ -0,0 +1,92 @@
+//! 局域网发送限速
+//!
+//! 所有发送任务共用一个令牌桶，同时向多个设备发送时分摊设定的总速度。
+//! 发送每个分块前先预留对应的令牌，令牌不足时等待后再发送
+
+use crate::token_bucket::TokenBucket;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// 发送限速器
+#[derive(Debug)]
+pub(crate) struct SendThrottle {
+    bucket: Mutex<TokenBucket>,
+}
+
+impl SendThrottle {
+    fn new(rate: Option<u64>) -> Self {
+        Self {
+            bucket: Mutex::new(TokenBucket::new(rate, Instant::now())),
+        }
+    }
+
+    fn rate(&self) -> Option<u64> {
+        self.bucket.lock().ok().and_then(|bucket| bucket.rate())
+    }
+
+    fn set_rate(&self, rate: Option<u64>) {
+        if let Ok(mut bucket) = self.bucket.lock() {
+            bucket.set_rate(rate, Instant::now());
+        }
+    }
+
+    /// 等待到可以发出 `bytes` 字节为止，不限速时立即返回
+    pub async fn acquire(&self, bytes: u64) {
+        let wait = self
+            .bucket
+            .lock()
+            .map(|mut bucket| bucket.reserve(bytes, Instant::now()))
+            .unwrap_or(Duration::ZERO);
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+static SEND_THROTTLE: OnceLock<SendThrottle> = OnceLock::new();
+
+/// 全局发送限速器
+pub(crate) fn send_throttle() -> &'static SendThrottle {
+    SEND_THROTTLE.get_or_init(|| SendThrottle::new(None))
+}
+
+/// 获取局域网发送限速（字节/秒），None 表示不限速
+pub fn transfer_speed_limit() -> Option<u64> {
+    send_throttle().rate()
+}
+
+/// 设置局域网发送限速，None 或 0 表示不限速
+pub fn set_transfer_speed_limit_internal(bytes_per_sec: Option<u64>) {
+    send_throttle().set_rate(bytes_per_sec);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_throttled_send_duration() {
+        // 1 MB/s 发送 400 KiB：首个 100 毫秒的容量立即可用，其余按限速等待
+        let rate = 1_000_000u64;
+        let total = 400 * 1024u64;
+        let throttle = SendThrottle::new(Some(rate));
+        let started = Instant::now();
+        for _ in 0..total / (16 * 1024) {
+            throttle.acquire(16 * 1024).await;
+        }
+        let elapsed = started.elapsed().as_secs_f64();
+        let expected = (total - rate / 10) as f64 / rate as f64;
+        assert!(
+            (elapsed - expected).abs() < 0.1,
+            "elapsed {:.3}s, expected {:.3}s",
+            elapsed,
+            expected
+        );
+
+        // 不限速时不等待
+        throttle.set_rate(None);
+        let started = Instant::now();
+        throttle.acquire(u64::MAX).await;
+        assert!(started.elapsed() < Duration::from_millis(10));
+    }
+}
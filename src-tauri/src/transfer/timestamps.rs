@@ -0,0 +1,90 @@
+//! 文件修改时间保留
+//!
+//! 发送端在文件元数据中携带源文件的修改时间，接收端校验通过后将其设置到保存的文件上，
+//! 避免照片库、构建缓存等依赖修改时间的场景在传输后失去原有时间
+
+use std::path::Path;
+use std::sync::{OnceLock, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// 是否保留修改时间（默认开启）
+static PRESERVE_TIMESTAMPS: OnceLock<RwLock<bool>> = OnceLock::new();
+
+fn get_preserve_timestamps_lock() -> &'static RwLock<bool> {
+    PRESERVE_TIMESTAMPS.get_or_init(|| RwLock::new(true))
+}
+
+/// 获取是否保留修改时间
+pub fn is_preserve_timestamps_enabled() -> bool {
+    get_preserve_timestamps_lock()
+        .read()
+        .map(|v| *v)
+        .unwrap_or(true)
+}
+
+/// 设置是否保留修改时间
+pub fn set_preserve_timestamps_internal(enabled: bool) {
+    if let Ok(mut value) = get_preserve_timestamps_lock().write() {
+        *value = enabled;
+    }
+}
+
+/// 文件的修改时间（毫秒），平台不支持或早于 1970 年时为 None
+pub fn modified_millis(metadata: &std::fs::Metadata) -> Option<u64> {
+    let modified = metadata.modified().ok()?;
+    let since_epoch = modified.duration_since(UNIX_EPOCH).ok()?;
+    Some(since_epoch.as_millis() as u64)
+}
+
+/// 将文件的修改时间设置为 `modified_at`（毫秒）
+fn set_modified_millis(path: &Path, modified_at: u64) -> std::io::Result<()> {
+    let file = std::fs::OpenOptions::new().write(true).open(path)?;
+    file.set_modified(UNIX_EPOCH + Duration::from_millis(modified_at))
+}
+
+/// 按设置为已保存的文件恢复来源的修改时间
+///
+/// 未携带修改时间或已关闭该设置时不做处理；设置失败只记录日志，不影响传输结果
+pub fn restore_modified_time(path: &Path, modified_at: Option<u64>) {
+    let Some(modified_at) = modified_at else {
+        return;
+    };
+    if !is_preserve_timestamps_enabled() {
+        return;
+    }
+    // 拒绝明显无效的未来时间，避免对端传来的异常值
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    if modified_at > now.saturating_add(24 * 60 * 60 * 1000) {
+        tracing::debug!(path = %path.display(), modified_at, "修改时间晚于当前时间，忽略");
+        return;
+    }
+    if let Err(e) = set_modified_millis(path, modified_at) {
+        tracing::warn!(path = %path.display(), error = %e, "恢复文件修改时间失败");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_modified_time_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("photo.jpg");
+        std::fs::write(&path, b"jpeg").unwrap();
+
+        let original = 1_600_000_000_123;
+        restore_modified_time(&path, Some(original));
+        let restored = modified_millis(&std::fs::metadata(&path).unwrap()).unwrap();
+        // 部分文件系统的时间精度为秒
+        assert!(restored.abs_diff(original) < 1000);
+
+        // 未携带修改时间时保持不变
+        restore_modified_time(&path, None);
+        let unchanged = modified_millis(&std::fs::metadata(&path).unwrap()).unwrap();
+        assert_eq!(unchanged, restored);
+    }
+}
@@ -0,0 +1,180 @@
+//! Windows / Linux 系统托盘
+//!
+//! 托盘菜单提供发送文件、开始接收、切换分享和退出，前三项与 macOS 菜单栏一样向前端发出
+//! `menu-event`，由前端统一处理。托盘提示随接收服务的启停（`receiving-state-changed`）变化
+
+use std::sync::{Mutex, OnceLock};
+use tauri::menu::{Menu, MenuBuilder, MenuItemBuilder};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Emitter, Listener, Manager, Wry};
+
+/// 托盘图标 ID
+const TRAY_ID: &str = "main";
+
+/// 托盘文本的中英文映射
+struct TrayTexts {
+    send_file: &'static str,
+    start_receiving: &'static str,
+    toggle_share: &'static str,
+    quit: &'static str,
+    // 托盘提示
+    idle_tooltip: &'static str,
+    receiving_tooltip: &'static str,
+}
+
+const TRAY_TEXTS_ZH: TrayTexts = TrayTexts {
+    send_file: "发送文件",
+    start_receiving: "开始接收",
+    toggle_share: "开启/关闭分享",
+    quit: "退出",
+    idle_tooltip: "PureSend",
+    receiving_tooltip: "PureSend - 正在接收",
+};
+
+const TRAY_TEXTS_EN: TrayTexts = TrayTexts {
+    send_file: "Send File",
+    start_receiving: "Start Receiving",
+    toggle_share: "Toggle Share",
+    quit: "Quit",
+    idle_tooltip: "PureSend",
+    receiving_tooltip: "PureSend - Receiving",
+};
+
+/// 根据语言获取托盘文本
+fn get_tray_texts(lang: &str) -> &'static TrayTexts {
+    if lang.starts_with("zh") {
+        &TRAY_TEXTS_ZH
+    } else {
+        &TRAY_TEXTS_EN
+    }
+}
+
+/// 托盘当前的语言与接收状态
+#[derive(Debug)]
+struct TrayStatus {
+    lang: String,
+    receiving: bool,
+}
+
+static TRAY_STATUS: OnceLock<Mutex<TrayStatus>> = OnceLock::new();
+
+fn get_tray_status() -> &'static Mutex<TrayStatus> {
+    TRAY_STATUS.get_or_init(|| {
+        Mutex::new(TrayStatus {
+            lang: "zh-CN".to_string(),
+            receiving: false,
+        })
+    })
+}
+
+/// 当前状态对应的托盘文本与提示
+fn current_texts() -> (&'static TrayTexts, &'static str) {
+    let (lang, receiving) = get_tray_status()
+        .lock()
+        .map(|status| (status.lang.clone(), status.receiving))
+        .unwrap_or_else(|_| ("zh-CN".to_string(), false));
+    let texts = get_tray_texts(&lang);
+    let tooltip = if receiving {
+        texts.receiving_tooltip
+    } else {
+        texts.idle_tooltip
+    };
+    (texts, tooltip)
+}
+
+/// 构建托盘菜单
+fn build_tray_menu(app: &AppHandle, texts: &TrayTexts) -> Result<Menu<Wry>, tauri::Error> {
+    let send_file_item = MenuItemBuilder::with_id("send_file", texts.send_file).build(app)?;
+    let start_receiving_item =
+        MenuItemBuilder::with_id("start_receiving", texts.start_receiving).build(app)?;
+    let toggle_share_item =
+        MenuItemBuilder::with_id("toggle_share", texts.toggle_share).build(app)?;
+    let quit_item = MenuItemBuilder::with_id("quit", texts.quit).build(app)?;
+
+    MenuBuilder::new(app)
+        .item(&send_file_item)
+        .item(&start_receiving_item)
+        .item(&toggle_share_item)
+        .separator()
+        .item(&quit_item)
+        .build()
+}
+
+/// 显示并聚焦主窗口
+fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+/// 处理托盘菜单事件
+fn handle_tray_menu_event(app: &AppHandle, id: &str) {
+    match id {
+        "send_file" | "start_receiving" | "toggle_share" => {
+            show_main_window(app);
+            let _ = app.emit("menu-event", id);
+        }
+        "quit" => app.exit(0),
+        _ => {}
+    }
+}
+
+/// 接收服务启停时更新托盘提示
+fn set_receiving(app: &AppHandle, receiving: bool) {
+    if let Ok(mut status) = get_tray_status().lock() {
+        status.receiving = receiving;
+    }
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let (_, tooltip) = current_texts();
+        let _ = tray.set_tooltip(Some(tooltip));
+    }
+}
+
+/// 创建系统托盘并订阅接收状态变化
+pub fn init(app: &tauri::App) -> Result<(), tauri::Error> {
+    let handle = app.handle().clone();
+    let (texts, tooltip) = current_texts();
+    let menu = build_tray_menu(&handle, texts)?;
+
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip(tooltip)
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(|app, event| handle_tray_menu_event(app, event.id().as_ref()))
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                show_main_window(tray.app_handle());
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+
+    let listener_handle = handle.clone();
+    handle.listen("receiving-state-changed", move |event| {
+        let receiving = serde_json::from_str::<bool>(event.payload()).unwrap_or(false);
+        set_receiving(&listener_handle, receiving);
+    });
+    Ok(())
+}
+
+/// 切换托盘菜单与提示的语言
+pub fn update_language(app: &AppHandle, lang: &str) -> Result<(), tauri::Error> {
+    if let Ok(mut status) = get_tray_status().lock() {
+        status.lang = lang.to_string();
+    }
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return Ok(());
+    };
+    let (texts, tooltip) = current_texts();
+    tray.set_menu(Some(build_tray_menu(app, texts)?))?;
+    tray.set_tooltip(Some(tooltip))
+}
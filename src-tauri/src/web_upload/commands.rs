@@ -4,8 +4,15 @@ use std::sync::Arc;
 use tauri::{AppHandle, Emitter, State};
 use tokio::sync::Mutex;
 
-use super::models::{UploadRequest, UploadRequestStatus, WebUploadState};
-use super::server::WebUploadServer;
+use super::models::current_timestamp_millis;
+use super::models::{
+    UploadFilters, UploadRequest, UploadRequestStatus, UploadTempCleanup, WebUploadState,
+};
+use super::server::{sweep_orphan_chunk_dirs, WebUploadServer, UPLOAD_SESSION_EXPIRY_SECS};
+use crate::transfer::available_space;
+
+/// 服务器运行时手动清理的宽限时间（秒），避免误删刚创建、尚未登记的分块目录
+const MANUAL_CLEANUP_GRACE_SECS: u64 = 60;
 
 /// Web 上传管理器状态
 pub struct WebUploadManagerState {
@@ -13,6 +20,10 @@ pub struct WebUploadManagerState {
     pub upload_state: Arc<Mutex<WebUploadState>>,
     /// HTTP 服务器
     pub server: Arc<Mutex<Option<WebUploadServer>>>,
+    /// 上次使用的端口，重启时优先复用以保持链接不变
+    pub last_port: Arc<Mutex<Option<u16>>>,
+    /// 空闲自动停止任务
+    pub idle_watch: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
 }
 
 impl WebUploadManagerState {
@@ -20,6 +31,8 @@ impl WebUploadManagerState {
         Self {
             upload_state: Arc::new(Mutex::new(WebUploadState::new())),
             server: Arc::new(Mutex::new(None)),
+            last_port: Arc::new(Mutex::new(None)),
+            idle_watch: Arc::new(Mutex::new(None)),
         }
     }
 }
@@ -40,6 +53,64 @@ pub struct WebUploadInfo {
     pub port: u16,
     /// 上传链接列表
     pub urls: Vec<String>,
+    /// 是否沿用了请求的端口（为 false 时之前分享的链接已失效）
+    pub port_reused: bool,
+}
+
+/// 清空本次上传服务期间的请求与授权
+async fn reset_upload_state(upload_state: &Mutex<WebUploadState>) {
+    let mut upload_state = upload_state.lock().await;
+    upload_state.requests.clear();
+    upload_state.allowed_ips.clear();
+}
+
+/// Web 上传服务空闲超时后自动停止
+///
+/// 仍有请求在处理（如单次大文件上传）时视为活跃。服务器被停止或重启时由调用方终止
+async fn watch_upload_idle(
+    app: AppHandle,
+    server: Arc<Mutex<Option<WebUploadServer>>>,
+    upload_state: Arc<Mutex<WebUploadState>>,
+) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+        crate::idle::IDLE_CHECK_INTERVAL_SECS,
+    ));
+    loop {
+        interval.tick().await;
+
+        let minutes = crate::idle::idle_timeout_minutes();
+        if minutes == 0 {
+            continue;
+        }
+        let mut server_guard = server.lock().await;
+        let Some(active_server) = server_guard.as_ref() else {
+            return;
+        };
+        {
+            let mut upload_state = upload_state.lock().await;
+            if active_server.active_requests() > 0 {
+                upload_state.touch();
+                continue;
+            }
+            let now = current_timestamp_millis();
+            if !crate::idle::is_idle(upload_state.last_activity, now, minutes) {
+                continue;
+            }
+        }
+
+        if let Some(mut stopped) = server_guard.take() {
+            stopped.stop();
+        }
+        drop(server_guard);
+        reset_upload_state(&upload_state).await;
+        crate::idle::emit_auto_stopped_idle(
+            &app,
+            crate::idle::IdleService::WebUpload,
+            None,
+            minutes,
+        );
+        return;
+    }
 }
 
 /// 启动 Web 上传服务器
@@ -53,6 +124,9 @@ pub async fn start_web_upload(
     preferred_port: Option<u16>,
 ) -> Result<WebUploadInfo, String> {
     // 如果已经启动，先停止
+    if let Some(watch) = state.idle_watch.lock().await.take() {
+        watch.abort();
+    }
     {
         let mut server_guard = state.server.lock().await;
         if let Some(mut server) = server_guard.take() {
@@ -67,40 +141,54 @@ pub async fn start_web_upload(
         upload_state.file_overwrite = file_overwrite;
         upload_state.receive_directory = receive_directory;
         upload_state.requests.clear();
+        upload_state.touch();
     }
 
-    // 创建并启动服务器（优先使用首选端口，失败则自动分配）
-    let port = preferred_port.unwrap_or(0);
+    // 创建并启动服务器（优先使用首选端口，其次上次使用的端口，失败则自动分配）
+    let port = match preferred_port {
+        Some(p) => p,
+        None => state.last_port.lock().await.unwrap_or(0),
+    };
     let mut server = WebUploadServer::new(state.upload_state.clone(), app.clone(), port);
     let actual_port = match server.start().await {
         Ok(p) => p,
         Err(_) if port != 0 => {
-            server = WebUploadServer::new(state.upload_state.clone(), app, 0);
+            server = WebUploadServer::new(state.upload_state.clone(), app.clone(), 0);
             server.start().await?
         }
         Err(e) => return Err(e),
     };
 
-    // 获取本机 IP 地址
-    let local_ips = crate::network::get_local_ips();
-    let urls: Vec<String> = local_ips.iter().map(|ip| format!("http://{}:{}", ip, actual_port)).collect();
+    // 生成访问地址（限定监听网卡时只包含该地址）
+    let urls = crate::http_common::server_links(actual_port);
 
     // 保存服务器实例
     {
         let mut server_guard = state.server.lock().await;
         *server_guard = Some(server);
     }
+    *state.last_port.lock().await = Some(actual_port);
+    *state.idle_watch.lock().await = Some(tokio::spawn(watch_upload_idle(
+        app,
+        state.server.clone(),
+        state.upload_state.clone(),
+    )));
 
     Ok(WebUploadInfo {
         enabled: true,
         port: actual_port,
         urls,
+        port_reused: port != 0 && port == actual_port,
     })
 }
 
 /// 停止 Web 上传服务器
 #[tauri::command]
 pub async fn stop_web_upload(state: State<'_, WebUploadManagerState>) -> Result<(), String> {
+    if let Some(watch) = state.idle_watch.lock().await.take() {
+        watch.abort();
+    }
+
     // 停止服务器
     {
         let mut server_guard = state.server.lock().await;
@@ -110,22 +198,41 @@ pub async fn stop_web_upload(state: State<'_, WebUploadManagerState>) -> Result<
     }
 
     // 清理状态
-    {
-        let mut upload_state = state.upload_state.lock().await;
-        upload_state.requests.clear();
-        upload_state.allowed_ips.clear();
-    }
+    reset_upload_state(&state.upload_state).await;
 
     Ok(())
 }
 
 /// 获取 Web 上传请求列表
+///
+/// 结果按请求时间从新到旧排列，可通过 `offset` / `limit` 分页
 #[tauri::command]
 pub async fn get_web_upload_requests(
     state: State<'_, WebUploadManagerState>,
+    offset: Option<usize>,
+    limit: Option<usize>,
 ) -> Result<Vec<UploadRequest>, String> {
     let upload_state = state.upload_state.lock().await;
-    Ok(upload_state.requests.values().cloned().collect())
+    let mut requests: Vec<UploadRequest> = upload_state.requests.values().cloned().collect();
+    requests.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Ok(requests
+        .into_iter()
+        .skip(offset.unwrap_or(0))
+        .take(limit.unwrap_or(usize::MAX))
+        .collect())
+}
+
+/// 清除已结束的上传文件记录，进行中的记录保留，返回清除的记录数
+#[tauri::command]
+pub async fn clear_completed_upload_records(
+    state: State<'_, WebUploadManagerState>,
+) -> Result<usize, String> {
+    let mut upload_state = state.upload_state.lock().await;
+    Ok(upload_state
+        .requests
+        .values_mut()
+        .map(|request| request.clear_finished_records())
+        .sum())
 }
 
 /// 同意 Web 上传请求（将该 IP 添加到 allowed_ips）
@@ -174,3 +281,135 @@ pub async fn reject_web_upload(
     Ok(())
 }
 
+/// 取消 Web 上传
+///
+/// 取消该请求下进行中的上传，指定 `record_id` 时只取消对应文件。被取消的记录标记为失败，
+/// 浏览器后续发送的分块会收到错误，分块临时文件随即删除。返回取消的上传数量。
+#[tauri::command]
+pub async fn cancel_web_upload(
+    state: State<'_, WebUploadManagerState>,
+    request_id: String,
+    record_id: Option<String>,
+) -> Result<usize, String> {
+    if !state.upload_state.lock().await.requests.contains_key(&request_id) {
+        return Err("请求不存在".to_string());
+    }
+
+    let server_guard = state.server.lock().await;
+    let server = server_guard
+        .as_ref()
+        .ok_or_else(|| "上传服务未启动".to_string())?;
+    let cancelled = server
+        .cancel_uploads(&request_id, record_id.as_deref())
+        .await;
+    if record_id.is_some() && cancelled == 0 {
+        return Err("上传不存在或已结束".to_string());
+    }
+    Ok(cancelled)
+}
+
+/// 获取接收目录所在磁盘的可用空间（字节）
+///
+/// 未指定目录时使用当前 Web 上传的接收目录，供界面在空间不足时提前提醒
+#[tauri::command]
+pub async fn get_receive_directory_free_space(
+    state: State<'_, WebUploadManagerState>,
+    directory: Option<String>,
+) -> Result<u64, String> {
+    let receive_directory = match directory {
+        Some(dir) => dir,
+        None => state.upload_state.lock().await.receive_directory.clone(),
+    };
+    if receive_directory.is_empty() {
+        return Err("未设置接收目录".to_string());
+    }
+
+    let path = std::path::PathBuf::from(&receive_directory);
+    tokio::task::spawn_blocking(move || available_space(&path))
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("无法读取磁盘可用空间: {}", receive_directory))
+}
+
+/// 获取上传文件过滤规则
+#[tauri::command]
+pub async fn get_web_upload_filters(
+    state: State<'_, WebUploadManagerState>,
+) -> Result<UploadFilters, String> {
+    Ok(state.upload_state.lock().await.filters.clone())
+}
+
+/// 设置上传文件过滤规则
+///
+/// 扩展名不区分大小写，可带前导点，支持 `tar.gz` 这类复合扩展名；允许列表为空表示不限制，
+/// 禁止列表优先。`max_file_size` 为单个文件大小上限（字节），None 表示不限制。
+/// 对之后开始的上传立即生效
+#[tauri::command]
+pub async fn set_web_upload_filters(
+    state: State<'_, WebUploadManagerState>,
+    allowed_ext: Vec<String>,
+    blocked_ext: Vec<String>,
+    max_file_size: Option<u64>,
+) -> Result<UploadFilters, String> {
+    if max_file_size == Some(0) {
+        return Err("文件大小上限必须大于 0".to_string());
+    }
+    let filters = UploadFilters {
+        allowed_extensions: normalize_extensions(allowed_ext)?,
+        blocked_extensions: normalize_extensions(blocked_ext)?,
+        max_file_size,
+    };
+    state.upload_state.lock().await.filters = filters.clone();
+    Ok(filters)
+}
+
+/// 统一扩展名格式：去掉空白和前导点并转为小写，去除重复项
+fn normalize_extensions(extensions: Vec<String>) -> Result<Vec<String>, String> {
+    let mut normalized: Vec<String> = Vec::with_capacity(extensions.len());
+    for ext in extensions {
+        let ext = ext.trim().trim_start_matches('.').to_lowercase();
+        if ext.is_empty() || ext.ends_with('.') || ext.contains(['/', '\\']) {
+            return Err(format!("无效的扩展名: {}", ext));
+        }
+        if !normalized.contains(&ext) {
+            normalized.push(ext);
+        }
+    }
+    Ok(normalized)
+}
+
+
+/// 清理上传临时目录
+///
+/// 删除接收目录下 `.puresend_chunks` 中没有对应活跃会话的分块目录，以及中断上传残留的文件。
+/// 服务未运行时，未过期的断点续传会话予以保留，下次启动仍可续传。
+/// 未指定目录时使用当前 Web 上传的接收目录。
+#[tauri::command]
+pub async fn cleanup_web_upload_temp(
+    state: State<'_, WebUploadManagerState>,
+    directory: Option<String>,
+) -> Result<UploadTempCleanup, String> {
+    let receive_directory = match directory {
+        Some(dir) => dir,
+        None => state.upload_state.lock().await.receive_directory.clone(),
+    };
+    if receive_directory.is_empty() {
+        return Err("未设置接收目录".to_string());
+    }
+
+    let (live_ids, min_age) = {
+        let server_guard = state.server.lock().await;
+        match server_guard.as_ref() {
+            Some(server) => (
+                server.live_upload_ids().await,
+                std::time::Duration::from_secs(MANUAL_CLEANUP_GRACE_SECS),
+            ),
+            None => (
+                Default::default(),
+                std::time::Duration::from_secs(UPLOAD_SESSION_EXPIRY_SECS),
+            ),
+        }
+    };
+
+    Ok(sweep_orphan_chunk_dirs(std::path::Path::new(&receive_directory), &live_ids, min_age).await)
+}
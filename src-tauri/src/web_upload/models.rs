@@ -4,8 +4,11 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use uuid::Uuid;
 
+/// 每个上传请求保留的文件记录上限，超出部分合并为汇总
+pub const MAX_UPLOAD_RECORDS_PER_REQUEST: usize = 100;
+
 /// 获取当前时间戳（毫秒），如果系统时钟异常则返回 0
-fn current_timestamp_millis() -> u64 {
+pub(super) fn current_timestamp_millis() -> u64 {
     std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap_or_default()
@@ -41,8 +44,11 @@ impl Default for UploadRequestStatus {
 pub struct WebUploadRecord {
     /// 记录唯一 ID
     pub id: String,
-    /// 文件名
+    /// 文件名（已按本机规则处理）
     pub file_name: String,
+    /// 客户端提供的原始文件名，仅在与处理后的文件名不同时存在
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_file_name: Option<String>,
     /// 已上传字节数
     pub uploaded_bytes: u64,
     /// 总字节数
@@ -60,6 +66,34 @@ pub struct WebUploadRecord {
     pub completed_at: Option<u64>,
 }
 
+impl WebUploadRecord {
+    /// 是否已结束（完成、取消或失败）
+    pub fn is_finished(&self) -> bool {
+        matches!(self.status.as_str(), "completed" | "cancelled" | "failed")
+    }
+}
+
+/// 已移出列表的文件记录汇总
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchivedRecordSummary {
+    /// 记录数
+    pub count: u64,
+    /// 其中已完成的记录数
+    pub completed: u64,
+    /// 已接收字节数合计
+    pub transferred_bytes: u64,
+}
+
+impl ArchivedRecordSummary {
+    fn absorb(&mut self, record: &WebUploadRecord) {
+        self.count += 1;
+        if record.status == "completed" {
+            self.completed += 1;
+        }
+        self.transferred_bytes += record.uploaded_bytes;
+    }
+}
 
 /// Web 上传请求（按 IP 审批的接收任务）
 ///
@@ -80,8 +114,11 @@ pub struct UploadRequest {
     /// 上传方 User-Agent
     #[serde(skip_serializing_if = "Option::is_none")]
     pub user_agent: Option<String>,
-    /// 该 IP 下的所有上传文件记录
+    /// 该 IP 下的上传文件记录（最早在前）
     pub upload_records: Vec<WebUploadRecord>,
+    /// 超出保留上限后移出列表的记录汇总
+    #[serde(default)]
+    pub archived_records: ArchivedRecordSummary,
 }
 
 impl UploadRequest {
@@ -96,8 +133,90 @@ impl UploadRequest {
             created_at: now,
             user_agent: None,
             upload_records: Vec::new(),
+            archived_records: ArchivedRecordSummary::default(),
         }
     }
+
+    /// 添加文件记录
+    ///
+    /// 超出保留上限时从最早的已结束记录开始移出并计入汇总，进行中的记录不会被移出
+    pub fn push_upload_record(&mut self, record: WebUploadRecord) {
+        self.upload_records.push(record);
+
+        let mut index = 0;
+        while self.upload_records.len() > MAX_UPLOAD_RECORDS_PER_REQUEST
+            && index < self.upload_records.len()
+        {
+            if self.upload_records[index].is_finished() {
+                let removed = self.upload_records.remove(index);
+                self.archived_records.absorb(&removed);
+            } else {
+                index += 1;
+            }
+        }
+    }
+
+    /// 清除已结束的文件记录及汇总，返回清除的记录数
+    pub fn clear_finished_records(&mut self) -> usize {
+        let before = self.upload_records.len();
+        self.upload_records.retain(|record| !record.is_finished());
+        self.archived_records = ArchivedRecordSummary::default();
+        before - self.upload_records.len()
+    }
+}
+
+/// 上传文件过滤规则
+///
+/// 扩展名均为小写且不含前导点，可以是 `tar.gz` 这类复合扩展名，按文件名结尾匹配，
+/// 不区分大小写
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadFilters {
+    /// 允许的扩展名，为空表示不限制
+    pub allowed_extensions: Vec<String>,
+    /// 禁止的扩展名，优先于允许列表
+    pub blocked_extensions: Vec<String>,
+    /// 单个文件大小上限（字节），None 表示不限制
+    pub max_file_size: Option<u64>,
+}
+
+impl UploadFilters {
+    /// 文件名（小写）是否以 `.扩展名` 结尾，且点号前还有内容
+    fn has_extension(file_name: &str, extension: &str) -> bool {
+        file_name
+            .strip_suffix(extension)
+            .and_then(|stem| stem.strip_suffix('.'))
+            .is_some_and(|stem| !stem.is_empty())
+    }
+
+    /// 检查文件名的扩展名是否允许上传
+    pub fn check_name(&self, file_name: &str) -> Result<(), String> {
+        let name = file_name.to_lowercase();
+        let matches = |list: &[String]| list.iter().any(|ext| Self::has_extension(&name, ext));
+        if matches(&self.blocked_extensions)
+            || (!self.allowed_extensions.is_empty() && !matches(&self.allowed_extensions))
+        {
+            return Err(format!("File type not allowed: {}", file_name));
+        }
+        Ok(())
+    }
+
+    /// 检查文件大小是否超过上限
+    pub fn check_size(&self, size: u64) -> Result<(), String> {
+        match self.max_file_size {
+            Some(max) if size > max => Err(format!(
+                "File exceeds the maximum upload size of {} bytes",
+                max
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// 检查文件名和大小是否符合过滤规则
+    pub fn check(&self, file_name: &str, size: u64) -> Result<(), String> {
+        self.check_name(file_name)?;
+        self.check_size(size)
+    }
 }
 
 /// Web 上传服务器状态
@@ -113,6 +232,10 @@ pub struct WebUploadState {
     pub file_overwrite: bool,
     /// 接收目录
     pub receive_directory: String,
+    /// 最近一次客户端交互时间（毫秒），用于空闲自动停止
+    pub last_activity: u64,
+    /// 上传文件过滤规则（重启上传服务后保留）
+    pub filters: UploadFilters,
 }
 
 impl WebUploadState {
@@ -124,9 +247,16 @@ impl WebUploadState {
             auto_receive: false,
             file_overwrite: false,
             receive_directory: String::new(),
+            last_activity: current_timestamp_millis(),
+            filters: UploadFilters::default(),
         }
     }
 
+    /// 记录一次客户端交互
+    pub fn touch(&mut self) {
+        self.last_activity = current_timestamp_millis();
+    }
+
     /// 检查 IP 是否已被拒绝
     pub fn is_ip_rejected(&self, ip: &str) -> bool {
         self.requests
@@ -145,3 +275,44 @@ impl Default for WebUploadState {
         Self::new()
     }
 }
+
+/// 上传临时目录清理结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadTempCleanup {
+    /// 删除的分块目录和残留文件数量
+    pub removed_entries: usize,
+    /// 释放的字节数
+    pub freed_bytes: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upload_filters_match_extensions() {
+        let filters = UploadFilters {
+            allowed_extensions: vec!["jpg".to_string(), "tar.gz".to_string()],
+            blocked_extensions: vec![],
+            max_file_size: Some(1024),
+        };
+        // 不区分大小写，复合扩展名按完整后缀匹配
+        assert!(filters.check("Photo.JPG", 1024).is_ok());
+        assert!(filters.check("backup.tar.gz", 10).is_ok());
+        assert!(filters.check("backup.gz", 10).is_err());
+        assert!(filters.check(".jpg", 10).is_err());
+        assert!(filters.check("photo.jpg", 1025).is_err());
+
+        // 禁止列表优先，且能匹配复合扩展名的最后一段
+        let filters = UploadFilters {
+            allowed_extensions: vec![],
+            blocked_extensions: vec!["exe".to_string(), "gz".to_string()],
+            max_file_size: None,
+        };
+        assert!(filters.check("setup.EXE", 10).is_err());
+        assert!(filters.check("backup.tar.gz", 10).is_err());
+        assert!(filters.check("notes.txt", u64::MAX).is_ok());
+        assert!(filters.check("exe", 10).is_ok());
+    }
+}
@@ -4,7 +4,9 @@
 
 use axum::extract::DefaultBodyLimit;
 use axum::{
-    extract::{connect_info::ConnectInfo, Multipart, Path, State as AxumState},
+    extract::{
+        connect_info::ConnectInfo, multipart::Field, Multipart, Path, State as AxumState,
+    },
     http::{header, HeaderMap},
     response::{Html, IntoResponse, Json, Response},
     routing::{get, post},
@@ -16,19 +18,52 @@ use sha2::{Digest, Sha256};
 use std::collections::{HashMap, HashSet};
 use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Instant;
 use tauri::{AppHandle, Emitter};
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
 use tokio::sync::Mutex;
 
-use super::models::{UploadRequest, UploadRequestStatus, WebUploadRecord, WebUploadState};
+use super::models::{
+    UploadFilters, UploadRequest, UploadRequestStatus, UploadTempCleanup, WebUploadRecord,
+    WebUploadState,
+};
 use crate::http_common::{
-    self, HasCryptoSessions, ServerCapabilities, HTTP_CHUNK_SIZE,
+    self, page_shell_response, resolve_client_ip, HasCryptoSessions, ServerCapabilities,
+    ServerInfo, HTTP_CHUNK_SIZE,
 };
-use crate::transfer::compression::Compressor;
+use crate::transfer::compression::CompressionAlgorithm;
 use crate::transfer::http_crypto::HttpCryptoSessionManager;
-const UPLOAD_SESSION_EXPIRY_SECS: u64 = 24 * 3600; // 24h
+use crate::transfer::{available_space, sanitize_filename, FilenamePlatform};
+pub(super) const UPLOAD_SESSION_EXPIRY_SECS: u64 = 24 * 3600; // 24h
+
+/// Temp directory (under the receive or configured temp directory) holding chunked upload sessions
+const CHUNK_TEMP_DIR: &str = ".puresend_chunks";
+/// Subdirectory of `CHUNK_TEMP_DIR` holding interrupted legacy uploads
+const PARTIAL_UPLOAD_DIR: &str = "partial";
+/// File inside an upload session directory that contiguous chunks are appended to
+const ASSEMBLY_FILE_NAME: &str = "assembled";
+/// File inside an upload session directory describing the session, see `UploadSessionManifest`
+const SESSION_MANIFEST_FILE_NAME: &str = "manifest.json";
+/// Error returned to the browser for uploads cancelled from the desktop
+const UPLOAD_CANCELLED_MESSAGE: &str = "Upload cancelled by receiver";
+/// Free space kept on the receive volume so an upload never fills the disk completely
+const DISK_SPACE_MARGIN: u64 = 64 * 1024 * 1024;
+
+/// Endpoints advertised by `/info`
+const UPLOAD_ENDPOINTS: &[&str] = &[
+    "GET /",
+    "GET /info",
+    "GET /request-status",
+    "GET /capabilities",
+    "POST /crypto/handshake",
+    "POST /upload/init",
+    "POST /upload/chunk",
+    "GET /upload/status/{upload_id}",
+    "POST /upload/adopt",
+    "POST /upload",
+];
 
 /// Chunked upload session
 #[derive(Debug)]
@@ -43,9 +78,147 @@ pub struct ChunkedUploadSession {
     client_ip: String,
     request_id: String,
     created_at: Instant,
+    /// Running hash over the contiguous prefix already written to the assembly file
+    hasher: Sha256,
+    /// Number of leading chunks hashed and written to the assembly file
+    assembled_chunks: usize,
+    /// Length of the assembly file's valid prefix
+    assembled_bytes: u64,
+    /// Modification time reported by the browser (ms since the Unix epoch)
+    last_modified: Option<u64>,
+    /// Total size of the distinct chunks received so far
+    received_bytes: u64,
+    /// When this run of the app started receiving the session (creation or restore)
+    resumed_at: Instant,
+    /// Bytes already received before `resumed_at`
+    resumed_bytes: u64,
+    /// Partial legacy upload offered for adoption until the client verifies or skips it
+    pending_partial: Option<PathBuf>,
+}
+
+/// On-disk description of a chunked upload session
+///
+/// Stored next to the chunk files so the session can be rebuilt after the app restarts.
+#[derive(Debug, Serialize, Deserialize)]
+struct UploadSessionManifest {
+    upload_id: String,
+    file_name: String,
+    file_size: u64,
+    chunk_size: usize,
+    chunk_count: usize,
+    received_chunks: Vec<usize>,
+    client_ip: String,
+    /// Unix time (seconds) the session was created
+    created_at: u64,
+    #[serde(default)]
+    last_modified: Option<u64>,
+}
+
+impl UploadSessionManifest {
+    async fn load(temp_dir: &std::path::Path) -> Option<Self> {
+        let bytes = tokio::fs::read(temp_dir.join(SESSION_MANIFEST_FILE_NAME))
+            .await
+            .ok()?;
+        serde_json::from_slice(&bytes).ok()
+    }
+
+    fn age_secs(&self) -> u64 {
+        unix_now_secs().saturating_sub(self.created_at)
+    }
+
+    fn is_expired(&self) -> bool {
+        self.age_secs() > UPLOAD_SESSION_EXPIRY_SECS
+    }
+}
+
+fn unix_now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
 }
 
 impl ChunkedUploadSession {
+    /// Rebuild a session from its manifest and the files left in `temp_dir`
+    ///
+    /// The assembly file is re-hashed up to the last chunk it fully contains, and parked
+    /// chunks are kept only if their file is still there. The session is not attached to
+    /// any upload request until the client sends its next chunk.
+    async fn restore(temp_dir: PathBuf, manifest: UploadSessionManifest) -> Option<Self> {
+        if manifest.chunk_size == 0 {
+            return None;
+        }
+        let created_at = Instant::now()
+            .checked_sub(std::time::Duration::from_secs(manifest.age_secs()))
+            .unwrap_or_else(Instant::now);
+        let mut session = Self {
+            id: manifest.upload_id,
+            file_name: manifest.file_name,
+            file_size: manifest.file_size,
+            chunk_size: manifest.chunk_size,
+            chunk_count: manifest.chunk_count,
+            received_chunks: HashSet::new(),
+            temp_dir,
+            client_ip: manifest.client_ip,
+            request_id: String::new(),
+            created_at,
+            hasher: Sha256::new(),
+            assembled_chunks: 0,
+            assembled_bytes: 0,
+            last_modified: manifest.last_modified,
+            received_bytes: 0,
+            resumed_at: Instant::now(),
+            resumed_bytes: 0,
+            pending_partial: None,
+        };
+        let listed: HashSet<usize> = manifest
+            .received_chunks
+            .into_iter()
+            .filter(|index| *index < session.chunk_count)
+            .collect();
+
+        let assembly_len = tokio::fs::metadata(session.assembly_path())
+            .await
+            .map(|meta| meta.len())
+            .unwrap_or(0);
+        while listed.contains(&session.assembled_chunks) {
+            let end = ((session.assembled_chunks as u64 + 1) * session.chunk_size as u64)
+                .min(session.file_size);
+            if end > assembly_len {
+                break;
+            }
+            session.assembled_chunks += 1;
+            session.assembled_bytes = end;
+        }
+
+        if session.assembled_bytes > 0 {
+            let file = tokio::fs::File::open(session.assembly_path()).await.ok()?;
+            let mut prefix = file.take(session.assembled_bytes);
+            let mut buf = vec![0u8; HTTP_CHUNK_SIZE];
+            loop {
+                let n = prefix.read(&mut buf).await.ok()?;
+                if n == 0 {
+                    break;
+                }
+                session.hasher.update(&buf[..n]);
+            }
+        }
+
+        session.received_chunks = (0..session.assembled_chunks).collect();
+        session.received_bytes = session.assembled_bytes;
+        for index in listed {
+            if index <= session.assembled_chunks {
+                continue;
+            }
+            if let Ok(meta) = tokio::fs::metadata(session.chunk_path(index)).await {
+                session.received_chunks.insert(index);
+                session.received_bytes += meta.len();
+            }
+        }
+        session.resumed_bytes = session.received_bytes;
+        Some(session)
+    }
+
     fn is_expired(&self) -> bool {
         self.created_at.elapsed().as_secs() > UPLOAD_SESSION_EXPIRY_SECS
     }
@@ -53,6 +226,149 @@ impl ChunkedUploadSession {
     fn is_complete(&self) -> bool {
         self.received_chunks.len() == self.chunk_count
     }
+
+    /// Average receive speed (bytes/s) since the session was created or restored
+    fn speed(&self) -> u64 {
+        let elapsed = self.resumed_at.elapsed().as_secs_f64();
+        if elapsed <= 0.0 {
+            return 0;
+        }
+        (self.received_bytes.saturating_sub(self.resumed_bytes) as f64 / elapsed) as u64
+    }
+
+    /// Upload progress percentage based on the bytes received so far
+    fn progress(&self) -> f64 {
+        (self.received_bytes as f64 / self.file_size.max(1) as f64 * 100.0).min(100.0)
+    }
+
+    /// Resume status reported to the client
+    fn status(&self) -> UploadSessionStatusResponse {
+        let mut received_chunks: Vec<usize> = self.received_chunks.iter().copied().collect();
+        received_chunks.sort_unstable();
+        UploadSessionStatusResponse {
+            found: true,
+            upload_id: self.id.clone(),
+            file_name: Some(self.file_name.clone()),
+            received_chunks,
+            total_chunks: self.chunk_count,
+            chunk_size: self.chunk_size,
+            complete: self.is_complete(),
+        }
+    }
+
+    fn chunk_path(&self, index: usize) -> PathBuf {
+        self.temp_dir.join(format!("chunk_{}", index))
+    }
+
+    fn assembly_path(&self) -> PathBuf {
+        self.temp_dir.join(ASSEMBLY_FILE_NAME)
+    }
+
+    /// Write the session manifest so the upload can be resumed after a restart
+    ///
+    /// Called after chunks are on disk; the manifest is replaced atomically so a crash
+    /// never leaves a half-written one behind.
+    async fn save_manifest(&self) -> std::io::Result<()> {
+        let mut received_chunks: Vec<usize> = self.received_chunks.iter().copied().collect();
+        received_chunks.sort_unstable();
+        let manifest = UploadSessionManifest {
+            upload_id: self.id.clone(),
+            file_name: self.file_name.clone(),
+            file_size: self.file_size,
+            chunk_size: self.chunk_size,
+            chunk_count: self.chunk_count,
+            received_chunks,
+            client_ip: self.client_ip.clone(),
+            created_at: unix_now_secs().saturating_sub(self.created_at.elapsed().as_secs()),
+            last_modified: self.last_modified,
+        };
+        let json = serde_json::to_vec(&manifest).map_err(std::io::Error::other)?;
+        let temp_path = self
+            .temp_dir
+            .join(format!("{}.tmp", SESSION_MANIFEST_FILE_NAME));
+        tokio::fs::write(&temp_path, json).await?;
+        tokio::fs::rename(&temp_path, self.temp_dir.join(SESSION_MANIFEST_FILE_NAME)).await
+    }
+
+    /// Length of the chunk at `chunk_index`: `chunk_size`, or the remainder for the last chunk
+    fn expected_chunk_len(&self, chunk_index: usize) -> u64 {
+        let start = chunk_index as u64 * self.chunk_size as u64;
+        self.file_size
+            .saturating_sub(start)
+            .min(self.chunk_size as u64)
+    }
+
+    /// Store a received chunk and fold every newly contiguous chunk into the assembly file
+    ///
+    /// The next expected chunk is hashed and written straight from memory. Chunks that
+    /// arrive ahead of a gap are parked as `chunk_{index}` and only read back once the
+    /// gap is filled. Counters advance only after a successful write, so a failed
+    /// chunk can simply be retried. A chunk whose length differs from its slot in the
+    /// file is rejected, so the size checked against free space at init bounds what
+    /// reaches the disk.
+    async fn absorb_chunk(&mut self, chunk_index: usize, data: &[u8]) -> std::io::Result<()> {
+        let expected_len = self.expected_chunk_len(chunk_index);
+        if data.len() as u64 != expected_len {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "chunk {} is {} bytes, expected {}",
+                    chunk_index,
+                    data.len(),
+                    expected_len
+                ),
+            ));
+        }
+        if !self.received_chunks.contains(&chunk_index)
+            && self.received_bytes + expected_len > self.file_size
+        {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "chunk exceeds the declared file size",
+            ));
+        }
+
+        if chunk_index < self.assembled_chunks {
+            // Retry of a chunk that is already assembled
+            return Ok(());
+        }
+
+        let mut output = tokio::fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(self.assembly_path())
+            .await?;
+        output
+            .seek(std::io::SeekFrom::Start(self.assembled_bytes))
+            .await?;
+
+        if chunk_index == self.assembled_chunks {
+            output.write_all(data).await?;
+            self.hasher.update(data);
+            self.assembled_chunks += 1;
+            self.assembled_bytes += data.len() as u64;
+        } else {
+            tokio::fs::write(self.chunk_path(chunk_index), data).await?;
+        }
+        if self.received_chunks.insert(chunk_index) {
+            // Retried chunks overwrite the parked copy and are counted only once
+            self.received_bytes += data.len() as u64;
+        }
+
+        while self.received_chunks.contains(&self.assembled_chunks) {
+            let parked_path = self.chunk_path(self.assembled_chunks);
+            let parked = tokio::fs::read(&parked_path).await?;
+            output.write_all(&parked).await?;
+            self.hasher.update(&parked);
+            self.assembled_chunks += 1;
+            self.assembled_bytes += parked.len() as u64;
+            let _ = tokio::fs::remove_file(&parked_path).await;
+        }
+
+        output.set_len(self.assembled_bytes).await?;
+        output.flush().await
+    }
 }
 
 #[derive(Debug)]
@@ -61,6 +377,10 @@ pub struct UploadServerState {
     pub app_handle: AppHandle,
     pub crypto_sessions: Arc<Mutex<HttpCryptoSessionManager>>,
     pub upload_sessions: Arc<Mutex<HashMap<String, ChunkedUploadSession>>>,
+    /// Upload record IDs cancelled from the desktop; later data for them is refused
+    cancelled_uploads: Arc<Mutex<HashSet<String>>>,
+    /// Client requests currently being handled
+    active_requests: Arc<AtomicUsize>,
 }
 
 impl HasCryptoSessions for UploadServerState {
@@ -73,11 +393,12 @@ pub struct WebUploadServer {
     pub addr: SocketAddr,
     pub state: Arc<UploadServerState>,
     shutdown_tx: Option<tokio::sync::oneshot::Sender<()>>,
+    cleanup_task: Option<tokio::task::JoinHandle<()>>,
 }
 
 impl WebUploadServer {
     pub fn new(upload_state: Arc<Mutex<WebUploadState>>, app_handle: AppHandle, port: u16) -> Self {
-        let addr = SocketAddr::from(([0, 0, 0, 0], port));
+        let addr = http_common::http_listen_addr(port);
 
         Self {
             addr,
@@ -86,8 +407,11 @@ impl WebUploadServer {
                 app_handle,
                 crypto_sessions: Arc::new(Mutex::new(HttpCryptoSessionManager::new())),
                 upload_sessions: Arc::new(Mutex::new(HashMap::new())),
+                cancelled_uploads: Arc::new(Mutex::new(HashSet::new())),
+                active_requests: Arc::new(AtomicUsize::new(0)),
             }),
             shutdown_tx: None,
+            cleanup_task: None,
         }
     }
 
@@ -98,6 +422,8 @@ impl WebUploadServer {
             .route("/apple-touch-icon.png", get(http_common::favicon_handler))
             .route("/apple-touch-icon-precomposed.png", get(http_common::favicon_handler))
             .route("/request-status", get(request_status_handler))
+            .route("/admin", get(admin_handler))
+            .route("/info", get(upload_info_handler))
             .route("/capabilities", get(upload_capabilities_handler))
             .route("/crypto/handshake", post(http_common::crypto_handshake_handler::<UploadServerState>))
             .route("/upload/init", post(upload_init_handler))
@@ -106,16 +432,18 @@ impl WebUploadServer {
                 post(upload_chunk_handler).layer(DefaultBodyLimit::max(10 * 1024 * 1024)),
             )
             .route("/upload/status/{upload_id}", get(upload_session_status_handler))
+            .route("/upload/adopt", post(upload_adopt_handler))
             .route(
                 "/upload",
                 post(upload_handler).layer(DefaultBodyLimit::max(10 * 1024 * 1024 * 1024)),
             )
             .fallback(http_common::fallback_handler)
+            .layer(axum::middleware::from_fn_with_state(self.state.clone(), track_activity))
             .layer(http_common::web_upload_cors_layer())
+            .layer(http_common::http_trace_layer())
             .with_state(self.state.clone());
 
-        let listener = tokio::net::TcpListener::bind(self.addr)
-            .await
+        let listener = http_common::bind_reusable_listener(self.addr)
             .map_err(|e| format!("Failed to bind port: {}", e))?;
 
         let actual_port = listener
@@ -126,18 +454,58 @@ impl WebUploadServer {
         let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
         self.shutdown_tx = Some(shutdown_tx);
 
+        // Pick up chunked uploads left on disk by a previous run so browsers can resume them
+        let receive_directory = self
+            .state
+            .upload_state
+            .lock()
+            .await
+            .receive_directory
+            .clone();
+        if !receive_directory.is_empty() {
+            let restored = restore_upload_sessions(std::path::Path::new(&receive_directory)).await;
+            self.state.upload_sessions.lock().await.extend(restored);
+        }
+
         let crypto_sessions = self.state.crypto_sessions.clone();
         let upload_sessions = self.state.upload_sessions.clone();
-        tokio::spawn(async move {
+        let upload_state = self.state.upload_state.clone();
+        let orphan_age = std::time::Duration::from_secs(UPLOAD_SESSION_EXPIRY_SECS);
+        self.cleanup_task = Some(tokio::spawn(async move {
             let mut interval = tokio::time::interval(std::time::Duration::from_secs(
                 http_common::SESSION_CLEANUP_INTERVAL_SECS,
             ));
+            // The first tick fires immediately, so this also sweeps leftovers from previous runs
             loop {
                 interval.tick().await;
                 crypto_sessions.lock().await.cleanup_expired();
-                upload_sessions.lock().await.retain(|_, s| !s.is_expired());
+                let (live_ids, expired_dirs) = {
+                    let mut sessions = upload_sessions.lock().await;
+                    let expired_dirs: Vec<PathBuf> = sessions
+                        .values()
+                        .filter(|s| s.is_expired())
+                        .map(|s| s.temp_dir.clone())
+                        .collect();
+                    sessions.retain(|_, s| !s.is_expired());
+                    let live_ids: HashSet<String> = sessions.keys().cloned().collect();
+                    (live_ids, expired_dirs)
+                };
+                // Expired sessions may live under an earlier receive directory the sweep
+                // below no longer looks at, so their chunks are removed directly
+                for dir in expired_dirs {
+                    let _ = tokio::fs::remove_dir_all(&dir).await;
+                }
+                let receive_directory = upload_state.lock().await.receive_directory.clone();
+                if !receive_directory.is_empty() {
+                    sweep_orphan_chunk_dirs(
+                        std::path::Path::new(&receive_directory),
+                        &live_ids,
+                        orphan_age,
+                    )
+                    .await;
+                }
             }
-        });
+        }));
 
         tokio::spawn(async move {
             axum::serve(
@@ -158,24 +526,375 @@ impl WebUploadServer {
         if let Some(tx) = self.shutdown_tx.take() {
             let _ = tx.send(());
         }
+        if let Some(task) = self.cleanup_task.take() {
+            task.abort();
+        }
+    }
+
+    /// Number of client requests currently being handled
+    pub fn active_requests(&self) -> usize {
+        self.state.active_requests.load(Ordering::SeqCst)
+    }
+
+    /// IDs of chunked upload sessions that are still alive
+    pub async fn live_upload_ids(&self) -> HashSet<String> {
+        self.state
+            .upload_sessions
+            .lock()
+            .await
+            .iter()
+            .filter(|(_, s)| !s.is_expired())
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    /// Cancel the in-progress uploads of a request, or only `record_id` when given
+    ///
+    /// Records are marked failed and `web-upload-file-complete` is emitted for each.
+    /// Chunked sessions are dropped together with their temp files, and streaming
+    /// uploads stop at their next progress check. Returns the number of uploads cancelled.
+    pub async fn cancel_uploads(&self, request_id: &str, record_id: Option<&str>) -> usize {
+        let cancelled: Vec<(String, String, u64)> = {
+            let mut upload_state = self.state.upload_state.lock().await;
+            let Some(request) = upload_state.requests.get_mut(request_id) else {
+                return 0;
+            };
+            let now = unix_now_secs();
+            request
+                .upload_records
+                .iter_mut()
+                .filter(|r| !r.is_finished() && record_id.is_none_or(|id| id == r.id))
+                .map(|record| {
+                    record.status = "failed".to_string();
+                    record.speed = 0;
+                    record.completed_at = Some(now);
+                    (
+                        record.id.clone(),
+                        record.file_name.clone(),
+                        record.uploaded_bytes,
+                    )
+                })
+                .collect()
+        };
+        if cancelled.is_empty() {
+            return 0;
+        }
+
+        self.state
+            .cancelled_uploads
+            .lock()
+            .await
+            .extend(cancelled.iter().map(|(id, _, _)| id.clone()));
+        let sessions: Vec<ChunkedUploadSession> = {
+            let mut upload_sessions = self.state.upload_sessions.lock().await;
+            cancelled
+                .iter()
+                .filter_map(|(id, _, _)| upload_sessions.remove(id))
+                .collect()
+        };
+        for session in &sessions {
+            let _ = tokio::fs::remove_dir_all(&session.temp_dir).await;
+        }
+
+        for (record_id, file_name, uploaded_bytes) in &cancelled {
+            let _ = self.state.app_handle.emit(
+                "web-upload-file-complete",
+                FileCompleteEvent {
+                    request_id: request_id.to_string(),
+                    record_id: record_id.clone(),
+                    file_name: file_name.clone(),
+                    total_bytes: *uploaded_bytes,
+                    status: "failed".to_string(),
+                },
+            );
+        }
+        cancelled.len()
+    }
+}
+
+/// Rebuild the chunked upload sessions persisted under the chunk temp root
+///
+/// Directories whose manifest has expired are removed right away; directories without
+/// a usable manifest are left to `sweep_orphan_chunk_dirs`.
+async fn restore_upload_sessions(
+    receive_dir: &std::path::Path,
+) -> HashMap<String, ChunkedUploadSession> {
+    let mut sessions = HashMap::new();
+    let Ok(mut entries) = tokio::fs::read_dir(chunks_root(receive_dir)).await else {
+        return sessions;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let temp_dir = entry.path();
+        let Some(manifest) = UploadSessionManifest::load(&temp_dir).await else {
+            continue;
+        };
+        if manifest.is_expired() {
+            let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+            continue;
+        }
+        if let Some(session) = ChunkedUploadSession::restore(temp_dir, manifest).await {
+            sessions.insert(session.id.clone(), session);
+        }
+    }
+    sessions
+}
+
+/// Remove chunk temp directories that no live session owns
+///
+/// Entries under the chunk temp root (see `chunks_root`) are removed when their upload id
+/// is not in `live_ids` and they were last modified, or for sessions with a manifest
+/// created, more than `min_age` ago. Partial legacy uploads are aged out the same way.
+pub async fn sweep_orphan_chunk_dirs(
+    receive_dir: &std::path::Path,
+    live_ids: &HashSet<String>,
+    min_age: std::time::Duration,
+) -> UploadTempCleanup {
+    let mut result = UploadTempCleanup::default();
+    let chunks_root = chunks_root(receive_dir);
+
+    let mut candidates = Vec::new();
+    if let Ok(mut entries) = tokio::fs::read_dir(&chunks_root).await {
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name == PARTIAL_UPLOAD_DIR {
+                if let Ok(mut partials) = tokio::fs::read_dir(entry.path()).await {
+                    while let Ok(Some(partial)) = partials.next_entry().await {
+                        candidates.push(partial.path());
+                    }
+                }
+            } else if !live_ids.contains(&name) {
+                candidates.push(entry.path());
+            }
+        }
+    }
+
+    for path in candidates {
+        let Ok(meta) = tokio::fs::metadata(&path).await else {
+            continue;
+        };
+        let mut age = meta
+            .modified()
+            .ok()
+            .and_then(|t| t.elapsed().ok())
+            .unwrap_or_default();
+        if meta.is_dir() {
+            if let Some(manifest) = UploadSessionManifest::load(&path).await {
+                age = age.max(std::time::Duration::from_secs(manifest.age_secs()));
+            }
+        }
+        if age < min_age {
+            continue;
+        }
+
+        let (size, removed) = if meta.is_dir() {
+            let size = dir_size(&path).await;
+            (size, tokio::fs::remove_dir_all(&path).await.is_ok())
+        } else {
+            (meta.len(), tokio::fs::remove_file(&path).await.is_ok())
+        };
+        if removed {
+            result.removed_entries += 1;
+            result.freed_bytes += size;
+        }
+    }
+
+    // Drop the temp root itself once nothing is left in it
+    let _ = tokio::fs::remove_dir(chunks_root.join(PARTIAL_UPLOAD_DIR)).await;
+    let _ = tokio::fs::remove_dir(&chunks_root).await;
+
+    result
+}
+
+/// Total size of the files directly inside a chunk directory
+async fn dir_size(path: &std::path::Path) -> u64 {
+    let mut total = 0;
+    if let Ok(mut entries) = tokio::fs::read_dir(path).await {
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            if let Ok(meta) = entry.metadata().await {
+                total += meta.len();
+            }
+        }
     }
+    total
 }
 
 // ─── Handlers ───────────────────────────────────────────────────────────────
 
-async fn upload_capabilities_handler() -> Json<ServerCapabilities> {
-    Json(ServerCapabilities::for_web_upload())
+/// Decrements the in-flight request count when a request finishes or is dropped
+struct ActiveRequestGuard(Arc<AtomicUsize>);
+
+impl ActiveRequestGuard {
+    fn new(counter: Arc<AtomicUsize>) -> Self {
+        counter.fetch_add(1, Ordering::SeqCst);
+        Self(counter)
+    }
+}
+
+impl Drop for ActiveRequestGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Record client activity for the idle timeout; the operator `/admin` page does not count.
+///
+/// Requests in flight are counted so a long single-request upload is not taken for
+/// idleness, and activity is recorded again once the request completes.
+async fn track_activity(
+    AxumState(state): AxumState<Arc<UploadServerState>>,
+    request: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> Response {
+    if request.uri().path() == "/admin" {
+        return next.run(request).await;
+    }
+    state.upload_state.lock().await.touch();
+    let _guard = ActiveRequestGuard::new(state.active_requests.clone());
+    let response = next.run(request).await;
+    state.upload_state.lock().await.touch();
+    response
+}
+
+async fn upload_info_handler() -> Json<ServerInfo> {
+    Json(ServerInfo::new(
+        "upload",
+        ServerCapabilities::for_web_upload(),
+        UPLOAD_ENDPOINTS,
+    ))
+}
+
+/// `/capabilities` response: shared capabilities plus the upload filters, so the page
+/// can reject files before sending them
+#[derive(Debug, Serialize)]
+struct UploadCapabilities {
+    #[serde(flatten)]
+    capabilities: ServerCapabilities,
+    allowed_extensions: Vec<String>,
+    blocked_extensions: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_file_size: Option<u64>,
+}
+
+async fn upload_capabilities_handler(
+    AxumState(state): AxumState<Arc<UploadServerState>>,
+) -> Json<UploadCapabilities> {
+    let filters = state.upload_state.lock().await.filters.clone();
+    Json(UploadCapabilities {
+        capabilities: ServerCapabilities::for_web_upload(),
+        allowed_extensions: filters.allowed_extensions,
+        blocked_extensions: filters.blocked_extensions,
+        max_file_size: filters.max_file_size,
+    })
+}
+
+/// Operator view of upload requests and records, local clients only
+async fn admin_handler(
+    ConnectInfo(client_addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    AxumState(state): AxumState<Arc<UploadServerState>>,
+) -> Response {
+    if !http_common::is_admin_request_allowed(&client_addr, &headers) {
+        return http_common::admin_forbidden_response(&client_addr);
+    }
+
+    let mut requests: Vec<_> = {
+        let upload_state = state.upload_state.lock().await;
+        upload_state.requests.values().cloned().collect()
+    };
+    requests.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+
+    let request_rows = requests
+        .iter()
+        .map(|request| {
+            vec![
+                http_common::format_admin_timestamp(request.created_at),
+                request.client_ip.clone(),
+                request.user_agent.clone().unwrap_or_default(),
+                format!("{:?}", request.status).to_lowercase(),
+                (request.upload_records.len() as u64 + request.archived_records.count)
+                    .to_string(),
+            ]
+        })
+        .collect();
+    let record_rows = requests
+        .iter()
+        .flat_map(|request| {
+            request.upload_records.iter().rev().map(move |record| {
+                vec![
+                    http_common::format_admin_timestamp(record.started_at),
+                    request.client_ip.clone(),
+                    record.file_name.clone(),
+                    format!("{:.1}%", record.progress),
+                    format!("{} / {}", record.uploaded_bytes, record.total_bytes),
+                    record.status.clone(),
+                ]
+            })
+        })
+        .collect();
+
+    http_common::admin_page_response(
+        "PureSend Upload Activity",
+        &[
+            http_common::AdminTable {
+                title: "Upload requests",
+                columns: &["Requested", "IP", "Client", "Status", "Uploads"],
+                rows: request_rows,
+            },
+            http_common::AdminTable {
+                title: "Uploads",
+                columns: &["Started", "IP", "File", "Progress", "Bytes", "Status"],
+                rows: record_rows,
+            },
+        ],
+    )
+}
+
+/// Whether the browser asked for English; pages and messages default to Chinese
+fn prefers_english(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("zh-CN")
+        .starts_with("en")
+}
+
+/// Reject an upload that would not fit on the receive directory's volume
+///
+/// When the free space cannot be read the upload is allowed and fails on write instead.
+fn check_free_space(
+    receive_dir: &std::path::Path,
+    required_bytes: u64,
+    is_english: bool,
+) -> Result<(), String> {
+    let Some(available) = available_space(receive_dir) else {
+        return Ok(());
+    };
+    if required_bytes.saturating_add(DISK_SPACE_MARGIN) <= available {
+        return Ok(());
+    }
+    Err(if is_english {
+        format!(
+            "Not enough disk space on the receiver: {} bytes needed, {} bytes available",
+            required_bytes, available
+        )
+    } else {
+        format!(
+            "接收方磁盘空间不足：需要 {} 字节，剩余 {} 字节",
+            required_bytes, available
+        )
+    })
 }
 
 /// Initialize chunked upload session
 async fn upload_init_handler(
     ConnectInfo(client_addr): ConnectInfo<SocketAddr>,
     AxumState(state): AxumState<Arc<UploadServerState>>,
+    headers: HeaderMap,
     Json(payload): Json<UploadInitRequest>,
 ) -> Json<UploadInitResponse> {
-    let client_ip = client_addr.ip().to_string();
+    let client_ip = resolve_client_ip(&client_addr, &headers);
 
-    let (is_allowed, receive_directory, request_id) = {
+    let (is_allowed, receive_directory, request_id, filters) = {
         let upload_state = state.upload_state.lock().await;
         let allowed = upload_state.is_ip_allowed(&client_ip);
         let req_id = upload_state
@@ -184,7 +903,12 @@ async fn upload_init_handler(
             .find(|r| r.client_ip == client_ip)
             .map(|r| r.id.clone())
             .unwrap_or_default();
-        (allowed, upload_state.receive_directory.clone(), req_id)
+        (
+            allowed,
+            upload_state.receive_directory.clone(),
+            req_id,
+            upload_state.filters.clone(),
+        )
     };
 
     if !is_allowed || request_id.is_empty() {
@@ -193,10 +917,27 @@ async fn upload_init_handler(
             upload_id: String::new(),
             chunk_size: 0,
             chunk_count: 0,
+            adoptable_chunks: 0,
             message: Some("Unauthorized upload".to_string()),
         });
     }
 
+    let receive_dir = std::path::Path::new(&receive_directory);
+    let is_english = prefers_english(&headers);
+    if let Err(message) = filters
+        .check(&payload.file_name, payload.file_size)
+        .and_then(|()| check_free_space(receive_dir, payload.file_size, is_english))
+    {
+        return Json(UploadInitResponse {
+            success: false,
+            upload_id: String::new(),
+            chunk_size: 0,
+            chunk_count: 0,
+            adoptable_chunks: 0,
+            message: Some(message),
+        });
+    }
+
     let chunk_size = if payload.chunk_size > 0 {
         payload.chunk_size
     } else {
@@ -206,19 +947,32 @@ async fn upload_init_handler(
     let upload_id = uuid::Uuid::new_v4().to_string();
 
     // Create temp directory for chunks
-    let temp_dir = PathBuf::from(&receive_directory)
-        .join(".puresend_chunks")
-        .join(&upload_id);
+    let temp_dir = chunks_root(receive_dir).join(&upload_id);
     if let Err(e) = tokio::fs::create_dir_all(&temp_dir).await {
         return Json(UploadInitResponse {
             success: false,
             upload_id: String::new(),
             chunk_size: 0,
             chunk_count: 0,
+            adoptable_chunks: 0,
             message: Some(format!("Failed to create temp directory: {}", e)),
         });
     }
 
+    let (file_name, original_file_name) = safe_upload_name(&payload.file_name);
+
+    // Offer data left behind by this client's interrupted legacy multipart upload; it is
+    // only adopted once the client proves it matches through `/upload/adopt`
+    let partial_path = partial_upload_path(receive_dir, &client_ip, &file_name);
+    let adoptable_chunks =
+        adoptable_chunk_count(&partial_path, payload.file_size, chunk_size, chunk_count).await;
+    let pending_partial = if adoptable_chunks > 0 {
+        Some(partial_path)
+    } else {
+        let _ = tokio::fs::remove_file(&partial_path).await;
+        None
+    };
+
     let record_id = upload_id.clone();
     let now = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
@@ -227,7 +981,8 @@ async fn upload_init_handler(
 
     let record = WebUploadRecord {
         id: record_id.clone(),
-        file_name: payload.file_name.clone(),
+        file_name: file_name.clone(),
+        original_file_name,
         uploaded_bytes: 0,
         total_bytes: payload.file_size,
         progress: 0.0,
@@ -244,7 +999,7 @@ async fn upload_init_handler(
             .values_mut()
             .find(|r| r.client_ip == client_ip)
         {
-            req.upload_records.push(record);
+            req.push_upload_record(record);
         }
     }
 
@@ -253,7 +1008,7 @@ async fn upload_init_handler(
         FileStartEvent {
             request_id: request_id.clone(),
             record_id,
-            file_name: payload.file_name.clone(),
+            file_name: file_name.clone(),
             total_bytes: payload.file_size,
             client_ip: client_ip.clone(),
         },
@@ -261,7 +1016,7 @@ async fn upload_init_handler(
 
     let session = ChunkedUploadSession {
         id: upload_id.clone(),
-        file_name: payload.file_name.clone(),
+        file_name,
         file_size: payload.file_size,
         chunk_size,
         chunk_count,
@@ -270,7 +1025,16 @@ async fn upload_init_handler(
         client_ip,
         request_id,
         created_at: Instant::now(),
+        hasher: Sha256::new(),
+        assembled_chunks: 0,
+        assembled_bytes: 0,
+        last_modified: payload.last_modified,
+        received_bytes: 0,
+        resumed_at: Instant::now(),
+        resumed_bytes: 0,
+        pending_partial,
     };
+    let _ = session.save_manifest().await;
 
     state
         .upload_sessions
@@ -283,6 +1047,7 @@ async fn upload_init_handler(
         upload_id,
         chunk_size,
         chunk_count,
+        adoptable_chunks,
         message: None,
     })
 }
@@ -294,7 +1059,7 @@ async fn upload_chunk_handler(
     headers: HeaderMap,
     body: Bytes,
 ) -> Json<UploadChunkResponse> {
-    let client_ip = client_addr.ip().to_string();
+    let client_ip = resolve_client_ip(&client_addr, &headers);
 
     let upload_id = headers
         .get("x-upload-id")
@@ -346,8 +1111,12 @@ async fn upload_chunk_handler(
         .get("x-compression")
         .and_then(|v| v.to_str().ok())
         .unwrap_or("");
-    if compression == "zstd" {
-        match Compressor::decompress(&data) {
+    if !compression.is_empty() {
+        let result = match CompressionAlgorithm::from_name(compression) {
+            Some(algorithm) => algorithm.decompress(&data).map_err(|e| e.to_string()),
+            None => Err(format!("unsupported algorithm {}", compression)),
+        };
+        match result {
             Ok(decompressed) => data = decompressed,
             Err(e) => {
                 return Json(UploadChunkResponse {
@@ -360,6 +1129,15 @@ async fn upload_chunk_handler(
         }
     }
 
+    if state.cancelled_uploads.lock().await.contains(&upload_id) {
+        return Json(UploadChunkResponse {
+            success: false,
+            message: UPLOAD_CANCELLED_MESSAGE.to_string(),
+            complete: false,
+            file_hash: None,
+        });
+    }
+
     // Save chunk to temp file and check completion
     let mut upload_sessions = state.upload_sessions.lock().await;
     let session = match upload_sessions.get_mut(&upload_id) {
@@ -374,41 +1152,67 @@ async fn upload_chunk_handler(
         }
     };
 
-    let chunk_path = session.temp_dir.join(format!("chunk_{}", chunk_index));
-    if let Err(e) = tokio::fs::write(&chunk_path, &data).await {
+    if chunk_index >= session.chunk_count {
         return Json(UploadChunkResponse {
             success: false,
-            message: format!("Failed to write chunk: {}", e),
+            message: format!("Invalid chunk index {}", chunk_index),
             complete: false,
             file_hash: None,
         });
     }
 
-    session.received_chunks.insert(chunk_index);
+    if let Some(partial_path) = session.pending_partial.take() {
+        // The client skipped adoption, so the partial data is no longer wanted
+        let _ = tokio::fs::remove_file(partial_path).await;
+    }
+
+    if session.request_id.is_empty() {
+        if let Err(message) = attach_restored_session(&state, session).await {
+            return Json(UploadChunkResponse {
+                success: false,
+                message,
+                complete: false,
+                file_hash: None,
+            });
+        }
+    }
+
+    if let Err(e) = session.absorb_chunk(chunk_index, &data).await {
+        return Json(UploadChunkResponse {
+            success: false,
+            message: format!("Failed to write chunk: {}", e),
+            complete: false,
+            file_hash: None,
+        });
+    }
+    if !session.is_complete() {
+        let _ = session.save_manifest().await;
+    }
 
     // Emit progress event
-    let progress = (session.received_chunks.len() as f64 / session.chunk_count as f64) * 100.0;
     let _ = state.app_handle.emit(
         "web-upload-file-progress",
         FileProgressEvent {
             request_id: session.request_id.clone(),
             record_id: session.id.clone(),
             file_name: session.file_name.clone(),
-            uploaded_bytes: session.received_chunks.len() as u64 * session.chunk_size as u64,
+            uploaded_bytes: session.received_bytes,
             total_bytes: session.file_size,
-            progress,
-            speed: 0,
+            progress: session.progress(),
+            speed: session.speed(),
         },
     );
 
     if session.is_complete() {
-        // Merge chunks into final file
+        // Every chunk is already hashed and written to the assembly file in order
         let file_name = session.file_name.clone();
         let file_size = session.file_size;
-        let chunk_count = session.chunk_count;
         let temp_dir = session.temp_dir.clone();
+        let assembly_path = session.assembly_path();
         let request_id = session.request_id.clone();
         let record_id = session.id.clone();
+        let file_hash = hex::encode(session.hasher.clone().finalize());
+        let last_modified = session.last_modified;
 
         let (receive_directory, file_overwrite) = {
             let upload_state = state.upload_state.lock().await;
@@ -424,46 +1228,15 @@ async fn upload_chunk_handler(
             final_path = get_unique_path(&final_path);
         }
 
-        // Merge all chunks
-        let mut hasher = Sha256::new();
-        match tokio::fs::File::create(&final_path).await {
-            Ok(mut output) => {
-                for i in 0..chunk_count {
-                    let chunk_path = temp_dir.join(format!("chunk_{}", i));
-                    match tokio::fs::read(&chunk_path).await {
-                        Ok(chunk_data) => {
-                            hasher.update(&chunk_data);
-                            if let Err(e) = output.write_all(&chunk_data).await {
-                                return Json(UploadChunkResponse {
-                                    success: false,
-                                    message: format!("Failed to merge chunks: {}", e),
-                                    complete: false,
-                                    file_hash: None,
-                                });
-                            }
-                        }
-                        Err(e) => {
-                            return Json(UploadChunkResponse {
-                                success: false,
-                                message: format!("Failed to read chunk: {}", e),
-                                complete: false,
-                                file_hash: None,
-                            });
-                        }
-                    }
-                }
-            }
-            Err(e) => {
-                return Json(UploadChunkResponse {
-                    success: false,
-                    message: format!("Failed to create target file: {}", e),
-                    complete: false,
-                    file_hash: None,
-                });
-            }
+        if let Err(e) = move_file(&assembly_path, &final_path).await {
+            return Json(UploadChunkResponse {
+                success: false,
+                message: format!("Failed to move merged file: {}", e),
+                complete: false,
+                file_hash: None,
+            });
         }
-
-        let file_hash = hex::encode(hasher.finalize());
+        crate::transfer::timestamps::restore_modified_time(&final_path, last_modified);
 
         // Cleanup temp directory
         let _ = tokio::fs::remove_dir_all(&temp_dir).await;
@@ -522,37 +1295,112 @@ async fn upload_chunk_handler(
     })
 }
 
+/// Attach a session restored from disk to the client's current upload request
+///
+/// Restored sessions belong to a request from before the restart, so the client must
+/// be approved again before it can continue.
+async fn attach_restored_session(
+    state: &UploadServerState,
+    session: &mut ChunkedUploadSession,
+) -> Result<(), String> {
+    let uploaded_bytes = session.received_bytes;
+    let request_id = {
+        let mut upload_state = state.upload_state.lock().await;
+        if !upload_state.is_ip_allowed(&session.client_ip) {
+            return Err("Unauthorized upload".to_string());
+        }
+        let Some(req) = upload_state
+            .requests
+            .values_mut()
+            .find(|r| r.client_ip == session.client_ip)
+        else {
+            return Err("Unauthorized upload".to_string());
+        };
+        req.push_upload_record(WebUploadRecord {
+            id: session.id.clone(),
+            file_name: session.file_name.clone(),
+            original_file_name: None,
+            uploaded_bytes,
+            total_bytes: session.file_size,
+            progress: session.progress(),
+            speed: 0,
+            status: "transferring".to_string(),
+            started_at: unix_now_secs(),
+            completed_at: None,
+        });
+        req.id.clone()
+    };
+
+    let _ = state.app_handle.emit(
+        "web-upload-file-start",
+        FileStartEvent {
+            request_id: request_id.clone(),
+            record_id: session.id.clone(),
+            file_name: session.file_name.clone(),
+            total_bytes: session.file_size,
+            client_ip: session.client_ip.clone(),
+        },
+    );
+    session.request_id = request_id;
+    Ok(())
+}
+
 /// Query upload session status (for resume)
 async fn upload_session_status_handler(
     ConnectInfo(client_addr): ConnectInfo<SocketAddr>,
     AxumState(state): AxumState<Arc<UploadServerState>>,
+    headers: HeaderMap,
     Path(upload_id): Path<String>,
 ) -> Json<UploadSessionStatusResponse> {
-    let client_ip = client_addr.ip().to_string();
+    let client_ip = resolve_client_ip(&client_addr, &headers);
     let upload_sessions = state.upload_sessions.lock().await;
 
     match upload_sessions.get(&upload_id) {
         Some(session) if session.client_ip == client_ip && !session.is_expired() => {
-            let mut received: Vec<usize> = session.received_chunks.iter().copied().collect();
-            received.sort();
-            Json(UploadSessionStatusResponse {
-                found: true,
-                upload_id: session.id.clone(),
-                file_name: Some(session.file_name.clone()),
-                received_chunks: received,
-                total_chunks: session.chunk_count,
-                complete: session.is_complete(),
-            })
+            Json(session.status())
         }
-        _ => Json(UploadSessionStatusResponse {
-            found: false,
-            upload_id,
-            file_name: None,
-            received_chunks: vec![],
-            total_chunks: 0,
-            complete: false,
-        }),
+        _ => Json(UploadSessionStatusResponse::not_found(upload_id)),
+    }
+}
+
+/// Adopt the verified leading chunks of a partial legacy upload
+///
+/// The client sends the SHA-256 of each of its first `adoptable_chunks` chunks; only the
+/// leading chunks whose partial data hashes the same are kept. The partial file is removed
+/// either way.
+async fn upload_adopt_handler(
+    ConnectInfo(client_addr): ConnectInfo<SocketAddr>,
+    AxumState(state): AxumState<Arc<UploadServerState>>,
+    headers: HeaderMap,
+    Json(payload): Json<UploadAdoptRequest>,
+) -> Json<UploadSessionStatusResponse> {
+    let client_ip = resolve_client_ip(&client_addr, &headers);
+    let mut upload_sessions = state.upload_sessions.lock().await;
+
+    let session = match upload_sessions.get_mut(&payload.upload_id) {
+        Some(session) if session.client_ip == client_ip && !session.is_expired() => session,
+        _ => return Json(UploadSessionStatusResponse::not_found(payload.upload_id)),
+    };
+
+    if let Some(partial_path) = session.pending_partial.take() {
+        let adopted = adopt_partial_upload(
+            &partial_path,
+            &session.temp_dir,
+            session.file_size,
+            session.chunk_size,
+            session.chunk_count,
+            &payload.chunk_hashes,
+        )
+        .await;
+        // Adopted chunks are always whole chunks
+        let adopted_bytes = adopted.len() as u64 * session.chunk_size as u64;
+        session.received_chunks.extend(adopted);
+        session.received_bytes += adopted_bytes;
+        session.resumed_bytes += adopted_bytes;
+        let _ = session.save_manifest().await;
     }
+
+    Json(session.status())
 }
 
 /// Index handler
@@ -561,22 +1409,18 @@ async fn index_handler(
     headers: HeaderMap,
     AxumState(state): AxumState<Arc<UploadServerState>>,
 ) -> Response {
-    let client_ip = client_addr.ip().to_string();
+    let client_ip = resolve_client_ip(&client_addr, &headers);
     let user_agent = headers
         .get(header::USER_AGENT)
         .and_then(|v| v.to_str().ok())
         .map(|s| http_common::parse_user_agent(s).to_string());
 
-    let accept_language = headers
-        .get(header::ACCEPT_LANGUAGE)
-        .and_then(|v| v.to_str().ok())
-        .unwrap_or("zh-CN");
-    let is_english = accept_language.starts_with("en");
+    let is_english = prefers_english(&headers);
 
     let mut upload_state = state.upload_state.lock().await;
 
     if upload_state.is_ip_rejected(&client_ip) {
-        return Html(generate_rejected_page(is_english)).into_response();
+        return page_shell_response(&headers, "rejected", is_english, generate_rejected_page);
     }
 
     let has_request = upload_state
@@ -609,9 +1453,9 @@ async fn index_handler(
     let is_allowed = upload_state.is_ip_allowed(&client_ip);
 
     if is_allowed {
-        Html(generate_upload_page(is_english)).into_response()
+        page_shell_response(&headers, "upload", is_english, generate_upload_page)
     } else {
-        Html(generate_waiting_page(is_english)).into_response()
+        page_shell_response(&headers, "waiting", is_english, generate_waiting_page)
     }
 }
 
@@ -627,8 +1471,9 @@ struct RequestStatusResponse {
 async fn request_status_handler(
     ConnectInfo(client_addr): ConnectInfo<SocketAddr>,
     AxumState(state): AxumState<Arc<UploadServerState>>,
+    headers: HeaderMap,
 ) -> Json<RequestStatusResponse> {
-    let client_ip = client_addr.ip().to_string();
+    let client_ip = resolve_client_ip(&client_addr, &headers);
     let upload_state = state.upload_state.lock().await;
 
     let request = upload_state
@@ -680,6 +1525,15 @@ async fn mark_upload_record_failed(
     }
 }
 
+/// Map a client-supplied file name to one that is valid on this host
+///
+/// Returns the safe name, plus the original name when the two differ.
+fn safe_upload_name(file_name: &str) -> (String, Option<String>) {
+    let sanitized = sanitize_filename(file_name, FilenamePlatform::current());
+    let original = (sanitized != file_name).then(|| file_name.to_string());
+    (sanitized, original)
+}
+
 /// Create an upload record for tracking
 fn create_upload_record(file_name: &str, content_length: u64) -> WebUploadRecord {
     let record_id = uuid::Uuid::new_v4().to_string();
@@ -691,6 +1545,7 @@ fn create_upload_record(file_name: &str, content_length: u64) -> WebUploadRecord
     WebUploadRecord {
         id: record_id.clone(),
         file_name: file_name.to_string(),
+        original_file_name: None,
         uploaded_bytes: 0,
         total_bytes: content_length,
         progress: 0.0,
@@ -701,87 +1556,254 @@ fn create_upload_record(file_name: &str, content_length: u64) -> WebUploadRecord
     }
 }
 
-/// Process a single file upload with progress tracking
+/// Stream a single multipart field to disk with progress tracking
+///
+/// Data is written to a `.part` file under the chunk temp directory and renamed
+/// into place once the field ends. If the connection drops midway the partial
+/// file is kept so the client can resume it through `/upload/init`.
+#[allow(clippy::too_many_arguments)]
 async fn process_single_file_upload(
     state: &Arc<UploadServerState>,
+    field: &mut Field<'_>,
+    partial_path: &std::path::Path,
     file_path: &std::path::Path,
-    data: &[u8],
     request_id: &str,
     record_id: &str,
     file_name: &str,
     client_ip: &str,
     content_length: u64,
     start_time: std::time::Instant,
+    filters: &UploadFilters,
 ) -> Result<u64, String> {
-    match tokio::fs::File::create(file_path).await {
-        Ok(mut output_file) => {
-            if let Err(err) = output_file.write_all(data).await {
-                let _ = state.app_handle.emit(
-                    "web-upload-file-complete",
-                    FileCompleteEvent {
-                        request_id: request_id.to_string(),
-                        record_id: record_id.to_string(),
-                        file_name: file_name.to_string(),
-                        total_bytes: data.len() as u64,
-                        status: "failed".to_string(),
-                    },
-                );
+    if let Some(parent) = partial_path.parent() {
+        if let Err(err) = tokio::fs::create_dir_all(parent).await {
+            mark_upload_record_failed(state, client_ip, record_id).await;
+            return Err(format!("Failed to create temp directory: {}", err));
+        }
+    }
 
-                mark_upload_record_failed(state, client_ip, record_id).await;
+    let mut output_file = match tokio::fs::File::create(partial_path).await {
+        Ok(f) => f,
+        Err(err) => {
+            mark_upload_record_failed(state, client_ip, record_id).await;
+            return Err(format!("Failed to create file: {}", err));
+        }
+    };
 
-                return Err(format!("Failed to write file: {}", err));
-            }
+    let emit_progress = |total_written: u64| {
+        let elapsed = start_time.elapsed().as_secs_f64();
+        let speed = if elapsed > 0.0 {
+            (total_written as f64 / elapsed) as u64
+        } else {
+            0
+        };
+        let actual_total = if content_length > 0 {
+            content_length
+        } else {
+            total_written
+        };
+        let progress = if actual_total > 0 {
+            (total_written as f64 / actual_total as f64 * 100.0).min(100.0)
+        } else {
+            100.0
+        };
 
-            let total_written = data.len() as u64;
+        let _ = state.app_handle.emit(
+            "web-upload-file-progress",
+            FileProgressEvent {
+                request_id: request_id.to_string(),
+                record_id: record_id.to_string(),
+                file_name: file_name.to_string(),
+                uploaded_bytes: total_written,
+                total_bytes: actual_total,
+                progress,
+                speed,
+            },
+        );
+    };
 
-            let elapsed = start_time.elapsed().as_secs_f64();
-            let speed = if elapsed > 0.0 {
-                (total_written as f64 / elapsed) as u64
-            } else {
-                0
-            };
-            let actual_total = if content_length > 0 {
-                content_length
-            } else {
-                total_written
-            };
-            let progress = if actual_total > 0 {
-                (total_written as f64 / actual_total as f64) * 100.0
-            } else {
-                100.0
-            };
+    let mut total_written: u64 = 0;
+    let mut last_emit = std::time::Instant::now();
+    // An oversized file is rejected outright, so its partial data is not kept for resume
+    let mut oversized = false;
 
-            let _ = state.app_handle.emit(
-                "web-upload-file-progress",
-                FileProgressEvent {
-                    request_id: request_id.to_string(),
-                    record_id: record_id.to_string(),
-                    file_name: file_name.to_string(),
-                    uploaded_bytes: total_written,
-                    total_bytes: actual_total,
-                    progress,
-                    speed,
+    loop {
+        let failure = match field.chunk().await {
+            Ok(Some(data)) => match filters.check_size(total_written + data.len() as u64) {
+                Err(err) => {
+                    oversized = true;
+                    err
+                }
+                Ok(()) => match output_file.write_all(&data).await {
+                    Ok(()) => {
+                        total_written += data.len() as u64;
+                        if last_emit.elapsed() >= std::time::Duration::from_millis(500) {
+                            // Cancelled from the desktop: the record is already final
+                            if state.cancelled_uploads.lock().await.contains(record_id) {
+                                drop(output_file);
+                                let _ = tokio::fs::remove_file(partial_path).await;
+                                return Err(UPLOAD_CANCELLED_MESSAGE.to_string());
+                            }
+                            emit_progress(total_written);
+                            last_emit = std::time::Instant::now();
+                        }
+                        continue;
+                    }
+                    Err(err) => format!("Failed to write file: {}", err),
                 },
-            );
+            },
+            Ok(None) => break,
+            Err(err) => format!(
+                "Upload interrupted after {} bytes, partial data kept for resume: {}",
+                total_written, err
+            ),
+        };
 
-            Ok(total_written)
+        let _ = output_file.flush().await;
+        if oversized {
+            drop(output_file);
+            let _ = tokio::fs::remove_file(partial_path).await;
         }
-        Err(err) => {
-            mark_upload_record_failed(state, client_ip, record_id).await;
-            Err(format!("Failed to create file: {}", err))
+
+        let _ = state.app_handle.emit(
+            "web-upload-file-complete",
+            FileCompleteEvent {
+                request_id: request_id.to_string(),
+                record_id: record_id.to_string(),
+                file_name: file_name.to_string(),
+                total_bytes: total_written,
+                status: "failed".to_string(),
+            },
+        );
+
+        mark_upload_record_failed(state, client_ip, record_id).await;
+
+        return Err(failure);
+    }
+
+    if let Err(err) = output_file.flush().await {
+        mark_upload_record_failed(state, client_ip, record_id).await;
+        return Err(format!("Failed to write file: {}", err));
+    }
+    drop(output_file);
+
+    if let Err(err) = move_file(partial_path, file_path).await {
+        mark_upload_record_failed(state, client_ip, record_id).await;
+        return Err(format!("Failed to move file into place: {}", err));
+    }
+
+    emit_progress(total_written);
+
+    Ok(total_written)
+}
+
+/// Location of the partial file kept for an interrupted legacy upload
+///
+/// Keyed by client IP and file name so only the same client's later chunked upload of
+/// that file is offered the data.
+fn partial_upload_path(receive_dir: &std::path::Path, client_ip: &str, file_name: &str) -> PathBuf {
+    let base_name = std::path::Path::new(file_name)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown");
+    let client_key: String = client_ip
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    chunks_root(receive_dir)
+        .join(PARTIAL_UPLOAD_DIR)
+        .join(format!("{}-{}.part", client_key, base_name))
+}
+
+/// Root of the chunk temp directories for a receive directory
+///
+/// Uses the configured temp directory when set, otherwise `<receive_dir>/.puresend_chunks`.
+fn chunks_root(receive_dir: &std::path::Path) -> PathBuf {
+    match crate::transfer::custom_temp_directory() {
+        Some(temp_dir) => temp_dir.join(CHUNK_TEMP_DIR),
+        None => receive_dir.join(CHUNK_TEMP_DIR),
+    }
+}
+
+/// Move a file, falling back to copy + delete when the temp directory is on another volume
+async fn move_file(from: &std::path::Path, to: &std::path::Path) -> std::io::Result<()> {
+    if tokio::fs::rename(from, to).await.is_ok() {
+        return Ok(());
+    }
+    tokio::fs::copy(from, to).await?;
+    tokio::fs::remove_file(from).await
+}
+
+/// Number of whole chunks a partial legacy upload could supply to a new chunked session
+///
+/// Never the last chunk, so the client always sends at least one chunk and the normal
+/// merge path runs. Partials larger than the announced file are unusable.
+async fn adoptable_chunk_count(
+    partial_path: &std::path::Path,
+    file_size: u64,
+    chunk_size: usize,
+    chunk_count: usize,
+) -> usize {
+    match tokio::fs::metadata(partial_path).await {
+        Ok(meta) if meta.len() <= file_size && chunk_size > 0 => {
+            ((meta.len() / chunk_size as u64) as usize).min(chunk_count.saturating_sub(1))
         }
+        _ => 0,
     }
 }
 
+/// Split a partial legacy upload into chunk files of a new chunked session
+///
+/// Chunks are adopted in order while their SHA-256 matches the hash the client computed
+/// over the same range of its file; the first mismatch stops adoption. The partial file is
+/// removed afterwards. Returns the chunk indices that were restored.
+async fn adopt_partial_upload(
+    partial_path: &std::path::Path,
+    temp_dir: &std::path::Path,
+    file_size: u64,
+    chunk_size: usize,
+    chunk_count: usize,
+    chunk_hashes: &[String],
+) -> HashSet<usize> {
+    let mut adopted = HashSet::new();
+    let whole_chunks = adoptable_chunk_count(partial_path, file_size, chunk_size, chunk_count)
+        .await
+        .min(chunk_hashes.len());
+
+    if whole_chunks > 0 {
+        if let Ok(mut input) = tokio::fs::File::open(partial_path).await {
+            let mut buffer = vec![0u8; chunk_size];
+            for (i, expected) in chunk_hashes.iter().enumerate().take(whole_chunks) {
+                if input.read_exact(&mut buffer).await.is_err()
+                    || !hex::encode(Sha256::digest(&buffer)).eq_ignore_ascii_case(expected)
+                {
+                    break;
+                }
+                if tokio::fs::write(temp_dir.join(format!("chunk_{}", i)), &buffer)
+                    .await
+                    .is_err()
+                {
+                    break;
+                }
+                adopted.insert(i);
+            }
+        }
+    }
+
+    let _ = tokio::fs::remove_file(partial_path).await;
+    adopted
+}
+
 /// Legacy multipart upload handler (backward compatible)
 async fn upload_handler(
     ConnectInfo(client_addr): ConnectInfo<SocketAddr>,
     AxumState(state): AxumState<Arc<UploadServerState>>,
+    headers: HeaderMap,
     mut multipart: Multipart,
 ) -> Json<UploadResponse> {
-    let client_ip = client_addr.ip().to_string();
+    let client_ip = resolve_client_ip(&client_addr, &headers);
 
-    let (is_allowed, file_overwrite, receive_directory, request_id) = {
+    let (is_allowed, file_overwrite, receive_directory, request_id, filters) = {
         let upload_state = state.upload_state.lock().await;
         let allowed = upload_state.is_ip_allowed(&client_ip);
         let req_id = upload_state
@@ -795,6 +1817,7 @@ async fn upload_handler(
             upload_state.file_overwrite,
             upload_state.receive_directory.clone(),
             req_id,
+            upload_state.filters.clone(),
         )
     };
 
@@ -822,10 +1845,26 @@ async fn upload_handler(
         }
     }
 
+    // The request body size covers all files plus multipart overhead, close enough
+    // to refuse an upload that cannot fit before any file is created
+    let request_length = headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or(0);
+    if let Err(message) = check_free_space(&receive_dir, request_length, prefers_english(&headers))
+    {
+        return Json(UploadResponse {
+            success: false,
+            message,
+        });
+    }
+
     let mut uploaded_count: u32 = 0;
 
-    while let Ok(Some(field)) = multipart.next_field().await {
-        let file_name = field.file_name().unwrap_or("unknown").to_string();
+    while let Ok(Some(mut field)) = multipart.next_field().await {
+        let raw_file_name = field.file_name().unwrap_or("unknown").to_string();
+        let (file_name, original_file_name) = safe_upload_name(&raw_file_name);
         let content_length = field
             .headers()
             .get(header::CONTENT_LENGTH)
@@ -833,7 +1872,17 @@ async fn upload_handler(
             .and_then(|s| s.parse::<u64>().ok())
             .unwrap_or(0);
 
-        let record = create_upload_record(&file_name, content_length);
+        // Reject before anything is written; the size is checked again while streaming
+        // because multipart fields rarely carry a Content-Length
+        if let Err(message) = filters.check(&raw_file_name, content_length) {
+            return Json(UploadResponse {
+                success: false,
+                message,
+            });
+        }
+
+        let mut record = create_upload_record(&file_name, content_length);
+        record.original_file_name = original_file_name;
         let record_id = record.id.clone();
 
         {
@@ -843,7 +1892,7 @@ async fn upload_handler(
                 .values_mut()
                 .find(|r| r.client_ip == client_ip)
             {
-                req.upload_records.push(record);
+                req.push_upload_record(record);
             }
         }
 
@@ -864,52 +1913,30 @@ async fn upload_handler(
         }
 
         let start_time = std::time::Instant::now();
-        let total_written: u64;
-
-        match field.bytes().await {
-            Ok(data) => {
-                match process_single_file_upload(
-                    &state,
-                    &file_path,
-                    &data,
-                    &request_id,
-                    &record_id,
-                    &file_name,
-                    &client_ip,
-                    content_length,
-                    start_time,
-                )
-                .await
-                {
-                    Ok(written) => total_written = written,
-                    Err(err) => {
-                        return Json(UploadResponse {
-                            success: false,
-                            message: err,
-                        });
-                    }
-                }
-            }
+        let partial_path = partial_upload_path(&receive_dir, &client_ip, &file_name);
+        let total_written = match process_single_file_upload(
+            &state,
+            &mut field,
+            &partial_path,
+            &file_path,
+            &request_id,
+            &record_id,
+            &file_name,
+            &client_ip,
+            content_length,
+            start_time,
+            &filters,
+        )
+        .await
+        {
+            Ok(written) => written,
             Err(err) => {
-                mark_upload_record_failed(&state, &client_ip, &record_id).await;
-
-                let _ = state.app_handle.emit(
-                    "web-upload-file-complete",
-                    FileCompleteEvent {
-                        request_id: request_id.clone(),
-                        record_id: record_id.clone(),
-                        file_name: file_name.clone(),
-                        total_bytes: 0,
-                        status: "failed".to_string(),
-                    },
-                );
-
                 return Json(UploadResponse {
                     success: false,
-                    message: format!("Failed to read file data: {}", err),
+                    message: err,
                 });
             }
-        }
+        };
 
         let completed_at = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
@@ -967,23 +1994,16 @@ async fn upload_handler(
     })
 }
 
-fn get_unique_path(path: &PathBuf) -> PathBuf {
-    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("file");
-    let extension = path.extension().and_then(|s| s.to_str()).unwrap_or("");
-    let parent = path.parent().unwrap_or(path);
+/// Pick a free name next to `path`, e.g. `backup.tar.gz` -> `backup (1).tar.gz`
+///
+/// Shares the naming rules of local receives (compound extensions, dotfiles).
+fn get_unique_path(path: &std::path::Path) -> PathBuf {
+    let parent = path.parent().unwrap_or(std::path::Path::new("."));
+    let file_name = path.file_name().and_then(|s| s.to_str()).unwrap_or("file");
 
-    let mut counter = 1;
-    loop {
-        let new_name = if extension.is_empty() {
-            format!("{}_{}", stem, counter)
-        } else {
-            format!("{}_{}.{}", stem, counter, extension)
-        };
-        let new_path = parent.join(new_name);
-        if !new_path.exists() {
-            return new_path;
-        }
-        counter += 1;
+    match crate::transfer::generate_unique_filename(&parent.to_path_buf(), file_name) {
+        Ok(name) => parent.join(name),
+        Err(_) => parent.join(format!("{}_{}", uuid::Uuid::new_v4(), file_name)),
     }
 }
 
@@ -996,6 +2016,9 @@ struct UploadInitRequest {
     file_size: u64,
     #[serde(default)]
     chunk_size: usize,
+    /// `File.lastModified` from the browser, in milliseconds
+    #[serde(default)]
+    last_modified: Option<u64>,
 }
 
 #[derive(Debug, Serialize)]
@@ -1004,6 +2027,8 @@ struct UploadInitResponse {
     upload_id: String,
     chunk_size: usize,
     chunk_count: usize,
+    /// Leading chunks a partial legacy upload could supply, see `/upload/adopt`
+    adoptable_chunks: usize,
     #[serde(skip_serializing_if = "Option::is_none")]
     message: Option<String>,
 }
@@ -1025,9 +2050,32 @@ struct UploadSessionStatusResponse {
     file_name: Option<String>,
     received_chunks: Vec<usize>,
     total_chunks: usize,
+    chunk_size: usize,
     complete: bool,
 }
 
+impl UploadSessionStatusResponse {
+    fn not_found(upload_id: String) -> Self {
+        Self {
+            found: false,
+            upload_id,
+            file_name: None,
+            received_chunks: vec![],
+            total_chunks: 0,
+            chunk_size: 0,
+            complete: false,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct UploadAdoptRequest {
+    upload_id: String,
+    /// Hex SHA-256 of the client's leading chunks, in order
+    #[serde(default)]
+    chunk_hashes: Vec<String>,
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 struct UploadResponse {
@@ -1081,6 +2129,8 @@ struct UploadPageLabels {
     total_size_label: &'static str,
     remove_label: &'static str,
     encrypted_label: &'static str,
+    type_not_allowed: &'static str,
+    too_large: &'static str,
     lang: &'static str,
 }
 
@@ -1262,13 +2312,39 @@ fn upload_page_javascript(labels: &UploadPageLabels) -> String {
 
         function removeFile(index) {{ selectedFiles.splice(index, 1); updateUI(); }}
 
+        // Mirrors the server-side filters; the server still enforces them
+        function checkUploadFilters(file) {{
+            if (!caps) return null;
+            const name = file.name.toLowerCase();
+            const hasExt = ext => name.length > ext.length + 1 && name.endsWith("." + ext);
+            const allowed = caps.allowed_extensions || [];
+            const blocked = caps.blocked_extensions || [];
+            if (blocked.some(hasExt) || (allowed.length > 0 && !allowed.some(hasExt))) {{
+                return "{type_not_allowed}";
+            }}
+            if (caps.max_file_size != null && file.size > caps.max_file_size) {{
+                return "{too_large} (" + formatSize(caps.max_file_size) + ")";
+            }}
+            return null;
+        }}
+
         function addFiles(files) {{
+            const rejected = [];
             for (const file of files) {{
+                const reason = checkUploadFilters(file);
+                if (reason) {{
+                    rejected.push(file.name + ": " + reason);
+                    continue;
+                }}
                 if (!selectedFiles.some(f => f.name === file.name && f.size === file.size)) {{
                     selectedFiles.push(file);
                 }}
             }}
-            statusEl.className = "status"; statusEl.textContent = "";
+            if (rejected.length > 0) {{
+                statusEl.className = "status error"; statusEl.textContent = rejected.join("; ");
+            }} else {{
+                statusEl.className = "status"; statusEl.textContent = "";
+            }}
             updateUI();
         }}
 
@@ -1279,30 +2355,73 @@ fn upload_page_javascript(labels: &UploadPageLabels) -> String {
         }}
         fileInput.addEventListener("change", () => {{ addFiles(fileInput.files); fileInput.value = ""; }});
 
-        async function uploadChunked(file, baseBytes, totalBytes) {{
-            const chunkSize = (caps && caps.chunk_size) || 1048576;
-            const initResp = await fetch("/upload/init", {{
-                method: "POST",
-                headers: {{ "Content-Type": "application/json" }},
-                body: JSON.stringify({{ file_name: file.name, file_size: file.size, chunk_size: chunkSize }})
-            }});
-            const initResult = await initResp.json();
-            if (!initResult.success) throw new Error(initResult.message);
-
-            const uploadId = initResult.upload_id;
-            sessionStorage.setItem("puresend_upload_id_" + file.name, uploadId);
-
-            let startChunk = 0;
+        async function fetchUploadStatus(uploadId) {{
             try {{
-                const statusResp = await fetch("/upload/status/" + uploadId);
+                const statusResp = await fetch("/upload/status/" + encodeURIComponent(uploadId));
                 const statusResult = await statusResp.json();
-                if (statusResult.found && statusResult.received_chunks.length > 0) {{
-                    startChunk = statusResult.received_chunks.length;
+                return statusResult.found && !statusResult.complete ? statusResult : null;
+            }} catch(e) {{ return null; }}
+        }}
+
+        async function adoptPartialUpload(file, uploadId, chunkSize, count) {{
+            const chunkHashes = [];
+            try {{
+                for (let i = 0; i < count; i++) {{
+                    const slice = await file.slice(i * chunkSize, (i + 1) * chunkSize).arrayBuffer();
+                    const digest = new Uint8Array(await crypto.subtle.digest("SHA-256", slice));
+                    chunkHashes.push(Array.from(digest, b => b.toString(16).padStart(2, "0")).join(""));
                 }}
-            }} catch(e) {{}}
+            }} catch(e) {{
+                // Hashing needs a secure context; send nothing so the partial data is dropped
+                chunkHashes.length = 0;
+            }}
+            try {{
+                const resp = await fetch("/upload/adopt", {{
+                    method: "POST",
+                    headers: {{ "Content-Type": "application/json" }},
+                    body: JSON.stringify({{ upload_id: uploadId, chunk_hashes: chunkHashes }})
+                }});
+                const result = await resp.json();
+                return result.found ? result : null;
+            }} catch(e) {{ return null; }}
+        }}
 
-            const totalChunks = initResult.chunk_count;
-            for (let i = startChunk; i < totalChunks; i++) {{
+        async function uploadChunked(file, baseBytes, totalBytes) {{
+            // Upload ids outlive the tab so a later visit can resume the same session
+            const resumeKey = "puresend_upload_id_" + file.name + "_" + file.size;
+            let uploadId = localStorage.getItem(resumeKey);
+            let status = uploadId ? await fetchUploadStatus(uploadId) : null;
+            let chunkSize = (caps && caps.chunk_size) || 1048576;
+            let totalChunks = 0;
+            if (status) {{
+                chunkSize = status.chunk_size;
+                totalChunks = status.total_chunks;
+            }} else {{
+                const initResp = await fetch("/upload/init", {{
+                    method: "POST",
+                    headers: {{ "Content-Type": "application/json" }},
+                    body: JSON.stringify({{
+                        file_name: file.name,
+                        file_size: file.size,
+                        chunk_size: chunkSize,
+                        last_modified: file.lastModified || null
+                    }})
+                }});
+                const initResult = await initResp.json();
+                if (!initResult.success) throw new Error(initResult.message);
+
+                uploadId = initResult.upload_id;
+                totalChunks = initResult.chunk_count;
+                localStorage.setItem(resumeKey, uploadId);
+                // A new session may adopt data left by an interrupted upload once it matches
+                if (initResult.adoptable_chunks > 0) {{
+                    status = await adoptPartialUpload(file, uploadId, chunkSize, initResult.adoptable_chunks);
+                }}
+            }}
+            const received = new Set(status ? status.received_chunks : []);
+
+            for (let i = 0; i < totalChunks; i++) {{
+                if (received.has(i)) continue;
                 const start = i * chunkSize;
                 const end = Math.min(start + chunkSize, file.size);
                 let chunk = new Uint8Array(await file.slice(start, end).arrayBuffer());
@@ -1323,7 +2442,7 @@ fn upload_page_javascript(labels: &UploadPageLabels) -> String {
                 progressText.textContent = pct + "% (" + formatSize(overallDone) + " / " + formatSize(totalBytes) + ")";
 
                 if (result.complete) {{
-                    sessionStorage.removeItem("puresend_upload_id_" + file.name);
+                    localStorage.removeItem(resumeKey);
                     return result.file_hash;
                 }}
             }}
@@ -1402,6 +2521,8 @@ fn upload_page_javascript(labels: &UploadPageLabels) -> String {
         initEnhanced();
     "##,
         encrypted_label = labels.encrypted_label,
+        type_not_allowed = labels.type_not_allowed,
+        too_large = labels.too_large,
         remove_label = labels.remove_label,
         file_label = labels.file_label,
         total_size_label = labels.total_size_label,
@@ -1426,6 +2547,8 @@ fn generate_upload_page(is_english: bool) -> String {
             total_size_label: "Total size",
             remove_label: "Remove",
             encrypted_label: "Encrypted",
+            type_not_allowed: "File type not allowed",
+            too_large: "File too large",
             lang: "en",
         }
     } else {
@@ -1441,11 +2564,26 @@ fn generate_upload_page(is_english: bool) -> String {
             total_size_label: "总大小",
             remove_label: "移除",
             encrypted_label: "已加密",
+            type_not_allowed: "不允许的文件类型",
+            too_large: "文件过大",
             lang: "zh-CN",
         }
     };
 
-    let css = upload_page_css();
+    let branding = http_common::page_branding();
+    let title = branding.title_html(labels.title);
+    let subtitle = branding
+        .subtitle_html()
+        .unwrap_or_else(|| "PureSend".to_string());
+    let mut css = upload_page_css().to_string();
+    if let Some(accent) = branding.accent_color() {
+        css.push_str(&format!(
+            "\n        .drop-zone:hover, .drop-zone.dragover \
+             {{ border-color: {accent}; }}\n        \
+             .drop-zone-btn, .drop-zone-btn:hover, .resume-btn, .progress-fill \
+             {{ background: {accent}; }}"
+        ));
+    }
     let javascript = upload_page_javascript(&labels);
 
     format!(
@@ -1462,7 +2600,7 @@ fn generate_upload_page(is_english: bool) -> String {
     <div class="container">
         <div class="card">
             <h1>📤 {title}</h1>
-            <p class="subtitle">PureSend</p>
+            <p class="subtitle">{subtitle}</p>
             <div class="badges" id="capBadges"></div>
 
             <div class="drop-zone" id="dropZone">
@@ -1488,7 +2626,8 @@ fn generate_upload_page(is_english: bool) -> String {
 </body>
 </html>"##,
         lang = labels.lang,
-        title = labels.title,
+        title = title,
+        subtitle = subtitle,
         css = css,
         select_files = labels.select_files,
         drag_hint = labels.drag_hint,
@@ -1617,3 +2756,215 @@ fn generate_rejected_page(is_english: bool) -> String {
         rejected_desc = rejected_desc,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unique_name(dir: &std::path::Path, existing: &[&str], name: &str) -> String {
+        for file in existing {
+            std::fs::write(dir.join(file), b"x").unwrap();
+        }
+        get_unique_path(&dir.join(name))
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .to_string()
+    }
+
+    #[test]
+    fn test_unique_path_compound_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(
+            unique_name(dir.path(), &["backup.tar.gz"], "backup.tar.gz"),
+            "backup (1).tar.gz"
+        );
+        assert_eq!(
+            unique_name(dir.path(), &["backup (1).tar.gz"], "backup.tar.gz"),
+            "backup (2).tar.gz"
+        );
+    }
+
+    #[test]
+    fn test_unique_path_dotfile() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(
+            unique_name(dir.path(), &[".gitignore"], ".gitignore"),
+            ".gitignore (1)"
+        );
+    }
+
+    #[test]
+    fn test_free_space_check_probes_existing_ancestor() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("not").join("created");
+        assert!(available_space(&missing).is_some());
+        assert!(check_free_space(&missing, 0, false).is_ok());
+
+        let err = check_free_space(&missing, u64::MAX, false).unwrap_err();
+        assert!(err.starts_with("接收方磁盘空间不足"));
+        let err = check_free_space(&missing, u64::MAX, true).unwrap_err();
+        assert!(err.starts_with("Not enough disk space"));
+    }
+
+    #[test]
+    fn test_unique_path_without_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(unique_name(dir.path(), &["README"], "README"), "README (1)");
+        assert_eq!(
+            unique_name(dir.path(), &["photo.jpg"], "photo.jpg"),
+            "photo (1).jpg"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_absorb_out_of_order_chunks() {
+        let dir = tempfile::tempdir().unwrap();
+        let chunks: [&[u8]; 3] = [b"alpha-", b"bravo-", b"gamma"];
+        let mut session = ChunkedUploadSession {
+            id: "upload".to_string(),
+            file_name: "file.txt".to_string(),
+            file_size: 17,
+            chunk_size: 6,
+            chunk_count: chunks.len(),
+            received_chunks: HashSet::new(),
+            temp_dir: dir.path().to_path_buf(),
+            client_ip: "127.0.0.1".to_string(),
+            request_id: "request".to_string(),
+            created_at: Instant::now(),
+            hasher: Sha256::new(),
+            assembled_chunks: 0,
+            assembled_bytes: 0,
+            last_modified: None,
+            received_bytes: 0,
+            resumed_at: Instant::now(),
+            resumed_bytes: 0,
+            pending_partial: None,
+        };
+
+        // Chunks must fill exactly their slot of the declared file size
+        assert!(session.absorb_chunk(0, b"alpha").await.is_err());
+        assert!(session.absorb_chunk(2, b"gamma-").await.is_err());
+        assert!(session.received_chunks.is_empty());
+
+        session.absorb_chunk(2, chunks[2]).await.unwrap();
+        assert_eq!(session.assembled_chunks, 0);
+        assert!(session.chunk_path(2).exists());
+
+        session.absorb_chunk(0, chunks[0]).await.unwrap();
+        session.absorb_chunk(0, chunks[0]).await.unwrap();
+        assert_eq!(session.assembled_chunks, 1);
+
+        session.absorb_chunk(1, chunks[1]).await.unwrap();
+        assert!(session.is_complete());
+        assert_eq!(session.assembled_chunks, 3);
+        assert!(!session.chunk_path(2).exists());
+        // The short last chunk is counted by its real size, duplicates only once
+        assert_eq!(session.received_bytes, 17);
+        assert_eq!(session.progress(), 100.0);
+
+        let expected = chunks.concat();
+        assert_eq!(std::fs::read(session.assembly_path()).unwrap(), expected);
+        assert_eq!(
+            hex::encode(session.hasher.finalize()),
+            hex::encode(Sha256::digest(&expected))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_restore_session_from_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let chunks: [&[u8]; 3] = [b"alpha-", b"bravo-", b"gamma"];
+        let mut session = ChunkedUploadSession {
+            id: "upload".to_string(),
+            file_name: "file.txt".to_string(),
+            file_size: 17,
+            chunk_size: 6,
+            chunk_count: chunks.len(),
+            received_chunks: HashSet::new(),
+            temp_dir: dir.path().to_path_buf(),
+            client_ip: "127.0.0.1".to_string(),
+            request_id: "request".to_string(),
+            created_at: Instant::now(),
+            hasher: Sha256::new(),
+            assembled_chunks: 0,
+            assembled_bytes: 0,
+            last_modified: Some(1_600_000_000_000),
+            received_bytes: 0,
+            resumed_at: Instant::now(),
+            resumed_bytes: 0,
+            pending_partial: None,
+        };
+        session.absorb_chunk(0, chunks[0]).await.unwrap();
+        session.absorb_chunk(2, chunks[2]).await.unwrap();
+        session.save_manifest().await.unwrap();
+
+        let manifest = UploadSessionManifest::load(dir.path()).await.unwrap();
+        assert!(!manifest.is_expired());
+        let mut restored = ChunkedUploadSession::restore(dir.path().to_path_buf(), manifest)
+            .await
+            .unwrap();
+        assert_eq!(restored.id, "upload");
+        assert_eq!(restored.client_ip, "127.0.0.1");
+        assert_eq!(restored.last_modified, Some(1_600_000_000_000));
+        // Restored sessions wait to be attached to a current request
+        assert!(restored.request_id.is_empty());
+        assert_eq!(restored.received_chunks, HashSet::from([0, 2]));
+        assert_eq!(restored.assembled_chunks, 1);
+        assert_eq!(restored.assembled_bytes, 6);
+        assert_eq!(restored.received_bytes, 11);
+        assert_eq!(restored.resumed_bytes, 11);
+
+        restored.absorb_chunk(1, chunks[1]).await.unwrap();
+        assert!(restored.is_complete());
+        let expected = chunks.concat();
+        assert_eq!(
+            hex::encode(restored.hasher.finalize()),
+            hex::encode(Sha256::digest(&expected))
+        );
+    }
+
+    #[test]
+    fn test_partial_upload_path_is_keyed_by_client() {
+        let dir = std::path::Path::new("/tmp/receive");
+        let a = partial_upload_path(dir, "192.168.1.2", "file.bin");
+        let b = partial_upload_path(dir, "192.168.1.3", "file.bin");
+        assert_ne!(a, b);
+        let v6 = partial_upload_path(dir, "fe80::1", "../file.bin");
+        assert_eq!(v6.file_name().unwrap(), "fe80__1-file.bin.part");
+    }
+
+    #[tokio::test]
+    async fn test_adopt_partial_upload_requires_matching_hashes() {
+        let dir = tempfile::tempdir().unwrap();
+        let partial = dir.path().join("partial.part");
+        let chunks: [&[u8]; 3] = [b"alpha-", b"beta--", b"gamma-"];
+        std::fs::write(&partial, chunks.concat()).unwrap();
+        let hash = |data: &[u8]| hex::encode(Sha256::digest(data));
+
+        // The partial holds three whole chunks of a four-chunk file
+        assert_eq!(adoptable_chunk_count(&partial, 20, 6, 4).await, 3);
+        assert_eq!(adoptable_chunk_count(&partial, 10, 6, 2).await, 0);
+
+        let hashes = vec![hash(chunks[0]), hash(b"other-"), hash(chunks[2])];
+        let adopted = adopt_partial_upload(&partial, dir.path(), 20, 6, 4, &hashes).await;
+        assert_eq!(adopted, HashSet::from([0]));
+        assert_eq!(
+            std::fs::read(dir.path().join("chunk_0")).unwrap(),
+            chunks[0]
+        );
+        assert!(!dir.path().join("chunk_1").exists());
+        assert!(!partial.exists());
+    }
+
+    #[tokio::test]
+    async fn test_adopt_partial_upload_without_hashes_discards_partial() {
+        let dir = tempfile::tempdir().unwrap();
+        let partial = dir.path().join("partial.part");
+        std::fs::write(&partial, b"alpha-beta--").unwrap();
+
+        let adopted = adopt_partial_upload(&partial, dir.path(), 20, 6, 4, &[]).await;
+        assert!(adopted.is_empty());
+        assert!(!partial.exists());
+    }
+}
@@ -13,6 +13,7 @@ export { default as ProgressDisplay } from './ProgressDisplay.vue'
 export { default as PeerList } from './PeerList.vue'
 export { default as SelectedFileList } from './SelectedFileList.vue'
 export { default as PinConfigDialog } from './PinConfigDialog.vue'
+export { default as HandshakeVerificationDialog } from './HandshakeVerificationDialog.vue'
 export { default as SendSettingsCard } from './SendSettingsCard.vue'
 export { default as ReceiveSettingsCard } from './ReceiveSettingsCard.vue'
 export { default as ReceiveModeSelector } from './ReceiveModeSelector.vue'
@@ -52,6 +52,41 @@ export async function addPeerManual(
     return invoke('add_peer_manual', { ip, port })
 }
 
+/**
+ * 扫描本机所在 /24 网段中的设备（mDNS 被屏蔽时的后备方式），结果会合并到设备列表
+ * @param port 扫描的传输端口，默认为本机通告的端口
+ * @returns 扫描发现的设备
+ */
+export async function scanSubnetForPeers(port?: number): Promise<PeerInfo[]> {
+    return invoke('scan_subnet_for_peers', { port })
+}
+
+/**
+ * 取消进行中的子网扫描，已发现的设备仍会返回
+ * @returns 是否有扫描在进行
+ */
+export async function cancelSubnetScan(): Promise<boolean> {
+    return invoke('cancel_subnet_scan')
+}
+
+/**
+ * 收藏设备，收藏的设备排在列表前面，重启后保留且不会因过期被移除
+ * @param peerId 设备ID
+ * @returns 更新后的设备信息
+ */
+export async function pinPeer(peerId: string): Promise<PeerInfo> {
+    return invoke('pin_peer', { peerId })
+}
+
+/**
+ * 取消收藏设备
+ * @param peerId 设备ID
+ * @returns 更新后的设备信息
+ */
+export async function unpinPeer(peerId: string): Promise<PeerInfo> {
+    return invoke('unpin_peer', { peerId })
+}
+
 /**
  * 检查设备是否在线
  * @param peerId 设备ID
@@ -67,6 +102,39 @@ export async function getOnlineCount(): Promise<number> {
     return invoke('get_online_count')
 }
 
+/**
+ * 获取设备过期时间
+ * @returns 过期时间（秒）
+ */
+export async function getPeerTtl(): Promise<number> {
+    return invoke('get_peer_ttl')
+}
+
+/**
+ * 设置设备过期时间，超过该时间未再通告的设备会被移除（手动添加的设备除外）
+ * @param seconds 过期时间（秒，5-3600）
+ */
+export async function setPeerTtl(seconds: number): Promise<void> {
+    return invoke('set_peer_ttl', { seconds })
+}
+
+/**
+ * 获取是否启用 UDP 广播发现
+ */
+export async function getBroadcastDiscoveryEnabled(): Promise<boolean> {
+    return invoke('get_broadcast_discovery_enabled')
+}
+
+/**
+ * 设置是否启用 UDP 广播发现（mDNS 被屏蔽时的后备）
+ * @param enabled 是否启用
+ */
+export async function setBroadcastDiscoveryEnabled(
+    enabled: boolean
+): Promise<void> {
+    return invoke('set_broadcast_discovery_enabled', { enabled })
+}
+
 // ============ 事件监听 ============
 
 /** 设备发现事件监听器类型 */
@@ -84,3 +152,16 @@ export function onPeerDiscovery(
         listener(event.payload)
     })
 }
+
+/**
+ * 监听设备因超过过期时间未通告而被移除
+ * @param listener 监听器函数
+ * @returns 取消监听函数
+ */
+export function onPeerRemoved(
+    listener: (peer: PeerInfo) => void
+): Promise<UnlistenFn> {
+    return listen<PeerInfo>('peer-removed', (event) => {
+        listener(event.payload)
+    })
+}
@@ -5,6 +5,7 @@
  */
 
 import type { UnlistenFn } from '@tauri-apps/api/event'
+import type { VerifyMode } from '../types'
 
 // ============ 环境检测 ============
 
@@ -195,6 +196,64 @@ export async function setFileOverwrite(enabled: boolean): Promise<void> {
     }
 }
 
+/**
+ * 设置接收端校验模式
+ */
+export async function setVerifyOnReceive(mode: VerifyMode): Promise<void> {
+    try {
+        if (await isTauriEnvironmentAvailable()) {
+            const { invoke } = await import('@tauri-apps/api/core')
+            await invoke('set_verify_on_receive', { mode })
+        }
+    } catch (error) {
+        console.warn('[SettingsService] 设置接收校验模式失败:', error)
+    }
+}
+
+// ============ 临时目录设置 ============
+
+/** 临时目录设置结果 */
+export interface TempDirectoryInfo {
+    /** 自定义临时目录（为空表示使用默认位置） */
+    directory?: string
+    /** 是否与接收目录位于同一磁盘（无法判断时为空） */
+    sameVolume?: boolean
+    /** 提示信息（如跨磁盘移动需要复制） */
+    warning?: string
+}
+
+/**
+ * 获取自定义临时目录
+ */
+export async function getTempDirectory(): Promise<string | null> {
+    try {
+        if (await isTauriEnvironmentAvailable()) {
+            const { invoke } = await import('@tauri-apps/api/core')
+            return await invoke<string | null>('get_temp_directory')
+        }
+    } catch (error) {
+        console.warn('[SettingsService] 获取临时目录失败:', error)
+    }
+    return null
+}
+
+/**
+ * 设置临时目录（网页上传分块、远程资源下载等），传空恢复默认位置
+ * 目录不可写时抛出错误
+ * @param directory 临时目录
+ * @param receiveDirectory 接收目录，用于判断是否跨磁盘
+ */
+export async function setTempDirectory(
+    directory: string | null,
+    receiveDirectory?: string
+): Promise<TempDirectoryInfo> {
+    if (!(await isTauriEnvironmentAvailable())) {
+        return {}
+    }
+    const { invoke } = await import('@tauri-apps/api/core')
+    return invoke<TempDirectoryInfo>('set_temp_directory', { directory, receiveDirectory })
+}
+
 // ============ 传输加密设置 ============
 
 /**
@@ -269,3 +328,386 @@ export async function setCompressionLevel(level: number): Promise<void> {
         console.warn('[SettingsService] 设置压缩级别失败:', error)
     }
 }
+
+/** 压缩算法 */
+export type CompressionAlgorithm = 'zstd' | 'lz4' | 'gzip'
+
+/**
+ * 设置压缩算法
+ * @param algorithm 压缩算法，局域网对端不支持时退回不压缩
+ */
+export async function setCompressionAlgorithm(algorithm: CompressionAlgorithm): Promise<void> {
+    try {
+        if (await isTauriEnvironmentAvailable()) {
+            const { invoke } = await import('@tauri-apps/api/core')
+            await invoke('set_compression_algorithm', { algorithm })
+        }
+    } catch (error) {
+        console.warn('[SettingsService] 设置压缩算法失败:', error)
+    }
+}
+
+/** 单个 MIME 大类的压缩统计 */
+export interface CompressionCategoryStats {
+    /** MIME 大类（如 text、image、application） */
+    category: string
+    /** 以压缩形式发送的分块数 */
+    compressedChunks: number
+    /** 未压缩发送的分块数（策略跳过或压缩后未变小） */
+    skippedChunks: number
+    /** 其中尝试压缩但未变小的分块数 */
+    ineffectiveChunks: number
+    /** 压缩分块的原始字节数 */
+    originalBytes: number
+    /** 压缩分块压缩后的字节数 */
+    compressedBytes: number
+    /** 平均压缩比（压缩后 / 原始） */
+    averageRatio: number | null
+}
+
+/**
+ * 获取各 MIME 大类的压缩统计
+ */
+export async function getCompressionStats(): Promise<CompressionCategoryStats[]> {
+    try {
+        if (await isTauriEnvironmentAvailable()) {
+            const { invoke } = await import('@tauri-apps/api/core')
+            return await invoke<CompressionCategoryStats[]>('get_compression_stats')
+        }
+    } catch (error) {
+        console.warn('[SettingsService] 获取压缩统计失败:', error)
+    }
+    return []
+}
+
+/**
+ * 重置压缩统计
+ */
+export async function resetCompressionStats(): Promise<void> {
+    try {
+        if (await isTauriEnvironmentAvailable()) {
+            const { invoke } = await import('@tauri-apps/api/core')
+            await invoke('reset_compression_stats')
+        }
+    } catch (error) {
+        console.warn('[SettingsService] 重置压缩统计失败:', error)
+    }
+}
+
+// ============ 空闲自动停止 ============
+
+/** 空闲自动停止事件载荷 */
+export interface AutoStoppedIdleEvent {
+    /** 被停止的服务 */
+    service: 'share' | 'web-upload' | 'receive'
+    /** 分享 ID（仅文件分享） */
+    shareId: string | null
+    /** 生效的空闲超时（分钟） */
+    idleMinutes: number
+}
+
+/**
+ * 获取空闲超时（分钟），0 表示关闭
+ */
+export async function getIdleTimeout(): Promise<number> {
+    try {
+        if (await isTauriEnvironmentAvailable()) {
+            const { invoke } = await import('@tauri-apps/api/core')
+            return await invoke<number>('get_idle_timeout')
+        }
+    } catch (error) {
+        console.warn('[SettingsService] 获取空闲超时失败:', error)
+    }
+    return 0
+}
+
+/**
+ * 设置空闲超时：分享、网页上传或接收服务在该时间内没有客户端交互时自动停止
+ * @param minutes 超时时间（分钟），0 表示关闭
+ */
+export async function setIdleTimeout(minutes: number): Promise<void> {
+    if (!(await isTauriEnvironmentAvailable())) {
+        return
+    }
+    const { invoke } = await import('@tauri-apps/api/core')
+    await invoke('set_idle_timeout', { minutes })
+}
+
+/**
+ * 监听服务因空闲被自动停止的事件
+ * @param callback 回调函数
+ * @returns 取消监听函数
+ */
+export async function onAutoStoppedIdle(
+    callback: (event: AutoStoppedIdleEvent) => void
+): Promise<UnlistenFn | null> {
+    try {
+        if (!(await isTauriEnvironmentAvailable())) {
+            return null
+        }
+        const { listen } = await import('@tauri-apps/api/event')
+        return listen<AutoStoppedIdleEvent>('auto-stopped-idle', (event) => {
+            callback(event.payload)
+        })
+    } catch (error) {
+        console.warn('[SettingsService] 事件监听注册失败:', error)
+        return null
+    }
+}
+
+// ============ 修改时间保留 ============
+
+/**
+ * 获取接收文件时是否保留来源的修改时间
+ * @returns 是否保留，非 Tauri 环境返回 true
+ */
+export async function getPreserveTimestamps(): Promise<boolean> {
+    try {
+        if (await isTauriEnvironmentAvailable()) {
+            const { invoke } = await import('@tauri-apps/api/core')
+            return await invoke<boolean>('get_preserve_timestamps')
+        }
+    } catch (error) {
+        console.warn('[SettingsService] 获取修改时间保留设置失败:', error)
+    }
+    return true
+}
+
+/**
+ * 设置接收文件时是否保留来源的修改时间（云盘接收与网页上传）
+ * @param enabled 是否保留
+ */
+export async function setPreserveTimestamps(enabled: boolean): Promise<void> {
+    try {
+        if (await isTauriEnvironmentAvailable()) {
+            const { invoke } = await import('@tauri-apps/api/core')
+            await invoke('set_preserve_timestamps', { enabled })
+        }
+    } catch (error) {
+        console.warn('[SettingsService] 设置修改时间保留失败:', error)
+    }
+}
+
+// ============ 网页服务监听地址 ============
+
+/**
+ * 获取分享/上传服务监听的网卡地址，null 表示监听所有网卡
+ */
+export async function getHttpBindAddress(): Promise<string | null> {
+    try {
+        if (await isTauriEnvironmentAvailable()) {
+            const { invoke } = await import('@tauri-apps/api/core')
+            return await invoke<string | null>('get_http_bind_address')
+        }
+    } catch (error) {
+        console.warn('[SettingsService] 获取监听地址失败:', error)
+    }
+    return null
+}
+
+/**
+ * 设置分享/上传服务监听的网卡地址，下次启动服务时生效；地址不属于本机时抛出错误
+ * @param address 本机网卡地址（如 192.168.1.42），null 表示监听所有网卡
+ * @returns 规范化后的地址
+ */
+export async function setHttpBindAddress(address: string | null): Promise<string | null> {
+    if (!(await isTauriEnvironmentAvailable())) {
+        return address
+    }
+    const { invoke } = await import('@tauri-apps/api/core')
+    return invoke<string | null>('set_http_bind_address', { address })
+}
+
+// ============ 网页品牌设置 ============
+
+/**
+ * 分享/上传网页的品牌设置，未设置的字段使用默认外观
+ */
+export interface PageBranding {
+    /** 页面标题 */
+    title?: string | null
+    /** 副标题 */
+    subtitle?: string | null
+    /** 强调色（CSS 颜色，如 #1976d2） */
+    accentColor?: string | null
+}
+
+/**
+ * 获取网页品牌设置
+ */
+export async function getPageBranding(): Promise<PageBranding> {
+    try {
+        if (await isTauriEnvironmentAvailable()) {
+            const { invoke } = await import('@tauri-apps/api/core')
+            return await invoke<PageBranding>('get_page_branding')
+        }
+    } catch (error) {
+        console.warn('[SettingsService] 获取网页品牌设置失败:', error)
+    }
+    return {}
+}
+
+/**
+ * 设置网页品牌，返回规范化后的设置；强调色不合法时抛出错误
+ */
+export async function setPageBranding(branding: PageBranding): Promise<PageBranding> {
+    if (!(await isTauriEnvironmentAvailable())) {
+        return branding
+    }
+    const { invoke } = await import('@tauri-apps/api/core')
+    return invoke<PageBranding>('set_page_branding', { branding })
+}
+
+// ============ 统一设置 ============
+
+/** 后端设置集合 */
+export interface SettingsBundle {
+    /** 接收设置 */
+    receive: ReceiveSettings & {
+        verifyMode: VerifyMode
+    }
+    /** 接收目录 */
+    receiveDirectory: string
+    /** 是否启用加密 */
+    encryptionEnabled: boolean
+    /** 首选加密算法（auto 表示按平台自动选择） */
+    preferredCipher: string
+    /** 是否启用压缩 */
+    compressionEnabled: boolean
+    /** 压缩模式（smart 或 manual） */
+    compressionMode: string
+    /** 手动压缩级别（1-19） */
+    compressionLevel: number
+    /** 压缩算法 */
+    compressionAlgorithm: CompressionAlgorithm
+    /** 发送重试策略 */
+    retryPolicy: {
+        attempts: number
+        baseDelayMs: number
+    }
+    /** 是否信任反向代理转发的客户端 IP */
+    trustedProxyEnabled: boolean
+    /** 接收文件时是否保留来源的修改时间 */
+    preserveTimestamps: boolean
+    /** 分享/上传服务监听地址，null 表示监听所有网卡 */
+    httpBindAddress: string | null
+    /** 空闲超时（分钟），0 表示关闭 */
+    idleTimeoutMinutes: number
+    /** 分享/上传网页的品牌设置 */
+    pageBranding: PageBranding
+}
+
+/**
+ * 一次获取全部后端设置
+ * @returns 后端设置，非 Tauri 环境返回 null
+ */
+export async function getAllSettings(): Promise<SettingsBundle | null> {
+    try {
+        if (await isTauriEnvironmentAvailable()) {
+            const { invoke } = await import('@tauri-apps/api/core')
+            return await invoke<SettingsBundle>('get_all_settings')
+        }
+    } catch (error) {
+        console.warn('[SettingsService] 获取后端设置失败:', error)
+    }
+    return null
+}
+
+/**
+ * 整体应用后端设置，任一字段无效时不做任何修改并抛出错误
+ * @param bundle 后端设置
+ * @returns 规范化后的设置
+ */
+export async function applySettings(
+    bundle: SettingsBundle
+): Promise<SettingsBundle> {
+    if (!(await isTauriEnvironmentAvailable())) {
+        return bundle
+    }
+    const { invoke } = await import('@tauri-apps/api/core')
+    return invoke<SettingsBundle>('apply_settings', { bundle })
+}
+
+/**
+ * 监听后端设置整体变更事件（任一窗口应用设置后触发）
+ * @param callback 回调函数
+ * @returns 取消监听函数
+ */
+export async function onSettingsBundleChanged(
+    callback: (bundle: SettingsBundle) => void
+): Promise<UnlistenFn | null> {
+    try {
+        if (!(await isTauriEnvironmentAvailable())) {
+            return null
+        }
+        const { listen } = await import('@tauri-apps/api/event')
+        return listen<SettingsBundle>('settings-changed', (event) => {
+            callback(event.payload)
+        })
+    } catch (error) {
+        console.warn('[SettingsService] 事件监听注册失败:', error)
+        return null
+    }
+}
+
+// ============ 日志设置 ============
+
+/**
+ * 设置日志级别（trace / debug / info / warn / error / off）
+ */
+export async function setLogLevel(level: string): Promise<void> {
+    try {
+        if (await isTauriEnvironmentAvailable()) {
+            const { invoke } = await import('@tauri-apps/api/core')
+            await invoke('set_log_level', { level })
+        }
+    } catch (error) {
+        console.warn('[SettingsService] 设置日志级别失败:', error)
+    }
+}
+
+/**
+ * 获取最新的日志文件路径，便于反馈问题时附带日志
+ */
+export async function getLogFilePath(): Promise<string | null> {
+    try {
+        if (await isTauriEnvironmentAvailable()) {
+            const { invoke } = await import('@tauri-apps/api/core')
+            return await invoke<string>('get_log_file_path')
+        }
+    } catch (error) {
+        console.warn('[SettingsService] 获取日志路径失败:', error)
+    }
+    return null
+}
+
+/**
+ * 获取最近的日志，用于诊断面板
+ * @param limit 最多返回的条数
+ * @returns 按时间从早到晚排列的日志行
+ */
+export async function getRecentLogs(limit: number): Promise<string[]> {
+    try {
+        if (await isTauriEnvironmentAvailable()) {
+            const { invoke } = await import('@tauri-apps/api/core')
+            return await invoke<string[]>('get_recent_logs', { limit })
+        }
+    } catch (error) {
+        console.warn('[SettingsService] 获取最近日志失败:', error)
+    }
+    return []
+}
+
+/**
+ * 在文件管理器中打开日志目录
+ */
+export async function openLogs(): Promise<void> {
+    try {
+        if (await isTauriEnvironmentAvailable()) {
+            const { invoke } = await import('@tauri-apps/api/core')
+            await invoke('open_logs')
+        }
+    } catch (error) {
+        console.warn('[SettingsService] 打开日志目录失败:', error)
+    }
+}
@@ -13,6 +13,7 @@ import type {
     ShareSettings,
     FileMetadata,
     UploadProgress,
+    PageOptions,
 } from '../types'
 
 // 重新导出类型供外部使用
@@ -25,16 +26,23 @@ export type { UploadProgress }
  * 开始分享
  * @param files 要分享的文件列表
  * @param settings 分享设置
+ * @param preferredPort 首选端口
+ * @param expirySeconds 自动到期时长（秒），为空或 0 表示不自动到期
+ * @param bindAddress 只在该本机网卡地址上监听，为空时沿用全局监听地址设置
  */
 export async function startShareService(
     files: FileMetadata[],
     settings: ShareSettings,
-    preferredPort?: number
+    preferredPort?: number,
+    expirySeconds?: number,
+    bindAddress?: string
 ): Promise<ShareLinkInfo> {
     return invoke<ShareLinkInfo>('start_share', {
         files,
         settings,
         preferredPort: preferredPort ?? null,
+        expirySeconds: expirySeconds ?? null,
+        bindAddress: bindAddress ?? null,
     })
 }
 
@@ -53,18 +61,74 @@ export async function getShareInfo(): Promise<ShareLinkInfo | null> {
 }
 
 /**
- * 获取访问请求列表
+ * 获取分享链接的二维码（由后端生成）
+ * @param size 图片边长（像素），限制在 128–1024 之间
+ * @param shareId 分享 ID，只有一个分享时可不指定
+ * @param link 要编码的链接，须为该分享的链接之一，默认使用第一个链接
+ * @returns PNG 图片的 data URI
  */
-export async function getAccessRequests(): Promise<AccessRequest[]> {
-    return invoke<AccessRequest[]>('get_access_requests')
+export async function getShareQRCode(size: number, shareId?: string, link?: string): Promise<string> {
+    return invoke<string>('get_share_qr_code', {
+        size,
+        shareId: shareId ?? null,
+        link: link ?? null,
+    })
+}
+
+/**
+ * 获取访问请求列表（按请求时间从新到旧）
+ * @param shareId 分享 ID，不指定时返回所有分享的请求
+ * @param page 分页参数
+ */
+export async function getAccessRequests(
+    shareId?: string,
+    page?: PageOptions
+): Promise<AccessRequest[]> {
+    return invoke<AccessRequest[]>('get_access_requests', {
+        shareId: shareId ?? null,
+        offset: page?.offset ?? null,
+        limit: page?.limit ?? null,
+    })
 }
 
 /**
  * 接受访问请求
  * @param requestId 请求 ID
+ * @param options remember 记住该 IP（本次分享），persist 永久记住
  */
-export async function acceptAccessRequest(requestId: string): Promise<void> {
-    return invoke('accept_access_request', { requestId })
+export async function acceptAccessRequest(
+    requestId: string,
+    options?: { remember?: boolean; persist?: boolean }
+): Promise<void> {
+    return invoke('accept_access_request', {
+        requestId,
+        remember: options?.remember,
+        persist: options?.persist,
+    })
+}
+
+/**
+ * 接受所有待处理的访问请求
+ * @param shareId 分享 ID，不指定时处理所有分享
+ * @returns 接受的请求数
+ */
+export async function acceptAllPendingAccessRequests(shareId?: string): Promise<number> {
+    return invoke<number>('accept_all_pending_access_requests', { shareId: shareId ?? null })
+}
+
+/**
+ * 获取永久记住的受信任 IP 列表
+ */
+export async function listTrustedIps(): Promise<string[]> {
+    return invoke('list_trusted_ips')
+}
+
+/**
+ * 取消信任 IP
+ * @param ip IP 地址
+ */
+export async function forgetTrustedIp(ip: string): Promise<void> {
+    return invoke('forget_trusted_ip', { ip })
 }
 
 /**
@@ -75,6 +139,15 @@ export async function rejectAccessRequest(requestId: string): Promise<void> {
     return invoke('reject_access_request', { requestId })
 }
 
+/**
+ * 拒绝所有待处理的访问请求
+ * @param shareId 分享 ID，不指定时处理所有分享
+ * @returns 拒绝的请求数
+ */
+export async function rejectAllPendingAccessRequests(shareId?: string): Promise<number> {
+    return invoke<number>('reject_all_pending_access_requests', { shareId: shareId ?? null })
+}
+
 /**
  * 移除单个访问请求
  * @param requestId 请求 ID
@@ -90,6 +163,15 @@ export async function clearAccessRequests(): Promise<void> {
     return invoke('clear_access_requests')
 }
 
+/**
+ * 清除已结束的上传记录（进行中的记录保留）
+ * @param shareId 分享 ID，不指定时清理所有分享
+ * @returns 清除的记录数
+ */
+export async function clearCompletedRecords(shareId?: string): Promise<number> {
+    return invoke<number>('clear_completed_records', { shareId: shareId ?? null })
+}
+
 /**
  * 更新分享文件列表
  * @param files 新的文件列表
@@ -103,11 +185,48 @@ export async function updateShareFilesService(
 /**
  * 更新分享设置
  * @param settings 分享设置
+ * @param autoAcceptDurationSecs 限时自动接受的时长（秒），到期后恢复手动确认
  */
 export async function updateShareSettingsService(
-    settings: ShareSettings
+    settings: ShareSettings,
+    autoAcceptDurationSecs?: number
 ): Promise<void> {
-    return invoke('update_share_settings', { settings })
+    return invoke('update_share_settings', {
+        settings,
+        autoAcceptDurationSecs: autoAcceptDurationSecs ?? null,
+    })
+}
+
+/**
+ * 获取分享下载限速
+ * @returns 每秒字节数，null 表示不限速
+ */
+export async function getShareBandwidthLimit(): Promise<number | null> {
+    return invoke<number | null>('get_share_bandwidth_limit')
+}
+
+/**
+ * 设置分享下载限速，由所有下载连接共享
+ * @param bytesPerSec 每秒字节数，null 或 0 表示不限速
+ */
+export async function setShareBandwidthLimit(bytesPerSec: number | null): Promise<void> {
+    return invoke('set_share_bandwidth_limit', { bytesPerSec })
+}
+
+/**
+ * 获取每分钟每个 IP 允许新建的访问请求数
+ * @returns 请求数上限，0 表示不限制
+ */
+export async function getShareRequestRateLimit(): Promise<number> {
+    return invoke<number>('get_share_request_rate_limit')
+}
+
+/**
+ * 设置每分钟每个 IP 允许新建的访问请求数，超过后返回 429（默认 20）
+ * @param perMinute 请求数上限，0 表示不限制
+ */
+export async function setShareRequestRateLimit(perMinute: number): Promise<void> {
+    return invoke('set_share_request_rate_limit', { perMinute })
 }
 
 // ============ 事件监听 ============
@@ -148,6 +267,50 @@ export async function onAccessRequestRejected(
     })
 }
 
+/** IP 被自动拒绝事件载荷 */
+export interface AccessIpAutoRejectedEvent {
+    /** 被自动拒绝的 IP */
+    ip: string
+    /** 累计被拒绝次数 */
+    rejections: number
+}
+
+/**
+ * 监听 IP 被拒绝次数达到上限事件，之后该 IP 的请求在本次分享内自动拒绝
+ * @param callback 回调函数
+ */
+export async function onAccessIpAutoRejected(
+    callback: (event: AccessIpAutoRejectedEvent) => void
+): Promise<UnlistenFn> {
+    return listen<AccessIpAutoRejectedEvent>('access-ip-auto-rejected', (event) => {
+        callback(event.payload)
+    })
+}
+
+/**
+ * 监听限时自动接受到期事件
+ * @param callback 回调函数，参数为分享 ID
+ */
+export async function onAutoAcceptExpired(
+    callback: (shareId: string) => void
+): Promise<UnlistenFn> {
+    return listen<{ shareId: string }>('share-auto-accept-expired', (event) => {
+        callback(event.payload.shareId)
+    })
+}
+
+/**
+ * 监听分享到期事件，到期后分享已自动停止
+ * @param callback 回调函数，参数为分享 ID
+ */
+export async function onShareExpired(
+    callback: (shareId: string) => void
+): Promise<UnlistenFn> {
+    return listen<{ shareId: string }>('share-expired', (event) => {
+        callback(event.payload.shareId)
+    })
+}
+
 /**
  * 监听上传进度事件（分享者向接收者传输文件的进度）
  * @param callback 回调函数
@@ -184,6 +347,22 @@ export interface UploadCompletePayload {
     client_ip: string
 }
 
+/** 上传中断事件载荷（接收者断开连接或读取文件失败） */
+export interface UploadCancelledPayload {
+    /** 上传记录 ID */
+    upload_id: string
+    /** 文件名 */
+    file_name: string
+    /** 文件大小 */
+    file_size: number
+    /** 中断前已传输的字节数 */
+    transferred_bytes: number
+    /** 接收者 IP */
+    client_ip: string
+    /** 接收者断开为 cancelled，读取文件失败为 failed */
+    status: 'cancelled' | 'failed'
+}
+
 /**
  * 监听上传开始事件（分享者开始向接收者传输文件）
  * @param callback 回调函数
@@ -208,6 +387,42 @@ export async function onUploadComplete(
     })
 }
 
+/**
+ * 监听上传中断事件（接收者中途断开或分块下载长时间无活动）
+ * @param callback 回调函数
+ */
+export async function onUploadCancelled(
+    callback: (payload: UploadCancelledPayload) => void
+): Promise<UnlistenFn> {
+    return listen<UploadCancelledPayload>('upload-cancelled', (event) => {
+        callback(event.payload)
+    })
+}
+
+/** 文件下载次数达到上限事件载荷 */
+export interface DownloadLimitReachedPayload {
+    /** 文件 ID */
+    file_id: string
+    /** 文件名 */
+    file_name: string
+    /** 已完成的下载次数 */
+    downloads: number
+    /** 下载次数上限 */
+    limit: number
+}
+
+/**
+ * 监听文件下载次数达到上限事件，之后该文件的下载请求会被拒绝
+ * @param callback 回调函数
+ */
+export async function onDownloadLimitReached(
+    callback: (payload: DownloadLimitReachedPayload) => void
+): Promise<UnlistenFn> {
+    return listen<DownloadLimitReachedPayload>('download-limit-reached', (event) => {
+        callback(event.payload)
+    })
+}
+
 /**
  * 监听访问请求被移除事件
  * @param callback 回调函数
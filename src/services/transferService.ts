@@ -4,7 +4,25 @@
 
 import { invoke } from '@tauri-apps/api/core'
 import { listen, type UnlistenFn } from '@tauri-apps/api/event'
-import type { FileMetadata, TransferTask, TransferProgress } from '../types'
+import type {
+    FileMetadata,
+    TransferTask,
+    TransferProgress,
+    TransferOverrides,
+    TransferMode,
+    SendTarget,
+    SendGroup,
+    BatchProgress,
+    PeerHistoryEntry,
+    CloudTransportConfig,
+    CloudConfigSummary,
+    CloudFileSummary,
+    ConnectionTestResult,
+    SessionVerificationResult,
+    LocalAddress,
+    ReceivedFileList,
+    FilenameValidation,
+} from '../types'
 
 /**
  * 初始化传输服务
@@ -13,6 +31,39 @@ export async function initTransfer(): Promise<void> {
     return invoke('init_transfer')
 }
 
+/**
+ * 获取云盘配置（不含秘密密钥），未配置时返回 null
+ */
+export async function getCloudConfig(): Promise<CloudConfigSummary | null> {
+    return invoke('get_cloud_config')
+}
+
+/**
+ * 设置云盘配置，校验通过后保存并立即生效
+ * @param config 云盘配置，secretKey 为空时沿用已保存的密钥
+ */
+export async function setCloudConfig(config: CloudTransportConfig): Promise<void> {
+    return invoke('set_cloud_config', { config })
+}
+
+/**
+ * 从云盘下载文件
+ * @param key 下载密钥（发送方的文件 ID）或分享链接
+ * @param saveDirectory 保存目录
+ * @returns 保存后的文件路径
+ */
+export async function receiveCloudFile(key: string, saveDirectory: string): Promise<string> {
+    return invoke('receive_cloud_file', { key, saveDirectory })
+}
+
+/**
+ * 列出云盘中已上传完成的文件（按上传时间从新到旧）
+ * @returns 文件列表，key 即下载密钥
+ */
+export async function listCloudFiles(): Promise<CloudFileSummary[]> {
+    return invoke('list_cloud_files')
+}
+
 /**
  * 获取本机监听端口
  */
@@ -21,7 +72,7 @@ export async function getTransferPort(): Promise<number> {
 }
 
 /**
- * 准备文件传输（计算元数据和哈希）
+ * 准备文件传输（计算元数据和哈希），计算期间发送 `prepare-progress` 事件
  * @param filePath 文件路径
  */
 export async function prepareFileTransfer(
@@ -30,6 +81,85 @@ export async function prepareFileTransfer(
     return invoke('prepare_file_transfer', { filePath })
 }
 
+/**
+ * 准备文件传输的哈希计算进度
+ */
+export interface PrepareProgressEvent {
+    /** 调用时传入的文件路径 */
+    filePath: string
+    /** 已完成哈希计算的字节数 */
+    processedBytes: number
+    /** 文件总字节数 */
+    totalBytes: number
+}
+
+/**
+ * 监听准备文件传输的哈希计算进度（约每 500 毫秒一次）
+ * @param listener 监听器函数
+ * @returns 取消监听函数
+ */
+export function onPrepareProgress(
+    listener: (event: PrepareProgressEvent) => void
+): Promise<UnlistenFn> {
+    return listen<PrepareProgressEvent>('prepare-progress', (event) => {
+        listener(event.payload)
+    })
+}
+
+/**
+ * 文件哈希算法
+ */
+export type HashAlgorithm = 'sha256' | 'sha512' | 'blake3'
+
+/**
+ * 独立哈希计算进度事件
+ */
+export interface FileHashProgressEvent {
+    /** 计算 ID */
+    hashId: string
+    /** 已处理字节数 */
+    processedBytes: number
+    /** 文件总字节数 */
+    totalBytes: number
+}
+
+/**
+ * 计算任意文件的哈希（用于校验他人提供的校验值，不生成传输元数据）
+ * @param path 文件路径
+ * @param hashId 计算 ID，用于进度事件和取消
+ * @param algorithm 哈希算法，默认 sha256
+ * @returns 十六进制哈希值
+ */
+export async function computeFileHash(
+    path: string,
+    hashId: string,
+    algorithm?: HashAlgorithm
+): Promise<string> {
+    return invoke<string>('compute_file_hash', { path, hashId, algorithm: algorithm ?? null })
+}
+
+/**
+ * 取消独立哈希计算
+ * @param hashId 计算 ID
+ * @returns 是否找到对应的计算
+ */
+export async function cancelFileHash(hashId: string): Promise<boolean> {
+    return invoke<boolean>('cancel_file_hash', { hashId })
+}
+
+/**
+ * 监听独立哈希计算进度
+ * @param listener 监听器函数
+ * @returns 取消监听函数
+ */
+export function onFileHashProgress(
+    listener: (event: FileHashProgressEvent) => void
+): Promise<UnlistenFn> {
+    return listen<FileHashProgressEvent>('file-hash-progress', (event) => {
+        listener(event.payload)
+    })
+}
+
 /**
  * 获取文件元数据（不计算哈希，仅获取基本信息）
  * @param filePath 文件路径
@@ -38,24 +168,68 @@ export async function getFileMetadata(filePath: string): Promise<FileMetadata> {
     return invoke('get_file_metadata', { filePath })
 }
 
+/**
+ * 发送前检查文件名在对端是否合法（按最严格的 Windows 规则）
+ * @param fileName 文件名
+ */
+export async function validateFilename(fileName: string): Promise<FilenameValidation> {
+    return invoke('validate_filename', { fileName })
+}
+
+/**
+ * 测试与对端传输端口的连通性
+ * @param ip 目标设备IP
+ * @param port 目标设备端口
+ * @param handshake 是否完成握手以确认对方为 PureSend 并获取其特性，默认 true
+ */
+export async function testPeerConnection(
+    ip: string,
+    port: number,
+    handshake?: boolean
+): Promise<ConnectionTestResult> {
+    return invoke('test_peer_connection', { ip, port, handshake: handshake ?? null })
+}
+
+/**
+ * 校验与对端的加密会话（调试用），确认双方派生的会话密钥一致
+ * @param ip 目标设备IP
+ * @param port 目标设备端口
+ */
+export async function verifyPeerSession(
+    ip: string,
+    port: number
+): Promise<SessionVerificationResult> {
+    return invoke('verify_peer_session', { ip, port })
+}
+
 /**
  * 发送文件（同步执行，阻塞直到完成或失败）
  * @param fileMetadata 文件元数据
  * @param peerId 目标设备ID
  * @param peerIp 目标设备IP
  * @param peerPort 目标设备端口
+ * @param overrides 本次传输的加密/压缩设置，未指定时沿用全局设置
+ * @param note 随文件发送的附言（最长 4KB）
+ * @param mode 传输模式，默认本地直连；云盘模式下文件 ID 即接收方的下载密钥
  */
 export async function sendFile(
     fileMetadata: FileMetadata,
     peerId: string,
     peerIp: string,
-    peerPort: number
+    peerPort: number,
+    overrides?: TransferOverrides,
+    note?: string,
+    mode?: TransferMode
 ): Promise<string> {
     return invoke('send_file', {
         fileMetadata,
         peerId,
         peerIp,
         peerPort,
+        encryption: overrides?.encryption ?? null,
+        compression: overrides?.compression ?? null,
+        note: note ?? null,
+        mode: mode ?? null,
     })
 }
 
@@ -65,18 +239,84 @@ export async function sendFile(
  * @param peerId 目标设备ID
  * @param peerIp 目标设备IP
  * @param peerPort 目标设备端口
+ * @param overrides 本次传输的加密/压缩设置，未指定时沿用全局设置
+ * @param note 随文件发送的附言（最长 4KB）
+ * @param mode 传输模式，默认本地直连；云盘模式下文件 ID 即接收方的下载密钥
  */
 export async function sendFileAsync(
     fileMetadata: FileMetadata,
     peerId: string,
     peerIp: string,
-    peerPort: number
+    peerPort: number,
+    overrides?: TransferOverrides,
+    note?: string,
+    mode?: TransferMode
 ): Promise<string> {
     return invoke('send_file_async', {
         fileMetadata,
         peerId,
         peerIp,
         peerPort,
+        encryption: overrides?.encryption ?? null,
+        compression: overrides?.compression ?? null,
+        note: note ?? null,
+        mode: mode ?? null,
+    })
+}
+
+/**
+ * 批量发送多个文件到同一设备（后台执行，立即返回任务分组）
+ *
+ * 整批对应一个父任务（ID 即 groupId），取消父任务会跳过尚未开始的文件；
+ * 有文件未完成时父任务标记为中断
+ * @param files 文件元数据列表，按顺序依次发送
+ * @param peerId 目标设备ID
+ * @param peerIp 目标设备IP
+ * @param peerPort 目标设备端口
+ * @param overrides 本次传输的加密/压缩设置，未指定时沿用全局设置
+ * @param note 随文件发送的附言（最长 4KB）
+ * @param mode 传输模式，默认本地直连
+ */
+export async function sendFilesAsync(
+    files: FileMetadata[],
+    peerId: string,
+    peerIp: string,
+    peerPort: number,
+    overrides?: TransferOverrides,
+    note?: string,
+    mode?: TransferMode
+): Promise<SendGroup> {
+    return invoke('send_files_async', {
+        files,
+        peerId,
+        peerIp,
+        peerPort,
+        encryption: overrides?.encryption ?? null,
+        compression: overrides?.compression ?? null,
+        note: note ?? null,
+        mode: mode ?? null,
+    })
+}
+
+/**
+ * 将同一文件同时发送到多个设备（后台执行，立即返回任务分组）
+ * @param fileMetadata 文件元数据
+ * @param peers 目标设备列表
+ * @param overrides 本次传输的加密/压缩设置，未指定时沿用全局设置
+ * @param note 随文件发送的附言（最长 4KB）
+ */
+export async function sendFileToPeers(
+    fileMetadata: FileMetadata,
+    peers: SendTarget[],
+    overrides?: TransferOverrides,
+    note?: string
+): Promise<SendGroup> {
+    return invoke('send_file_to_peers', {
+        fileMetadata,
+        peers,
+        encryption: overrides?.encryption ?? null,
+        compression: overrides?.compression ?? null,
+        note: note ?? null,
     })
 }
 
@@ -88,6 +328,127 @@ export async function cancelTransfer(taskId: string): Promise<void> {
     return invoke('cancel_transfer', { taskId })
 }
 
+/** 握手短认证串事件载荷 */
+export interface HandshakeVerificationEvent {
+    /** 传输任务 ID（HTTP 分享中为加密会话 ID） */
+    taskId: string
+    /** 对端地址 */
+    peer: string
+    /** 6 位短认证串，与对端界面显示的值一致时说明未被中间人替换公钥 */
+    sas: string
+}
+
+/**
+ * 监听握手短认证串事件（本地传输与 HTTP 分享完成密钥交换后触发）
+ * @param listener 监听器函数
+ * @returns 取消监听函数
+ */
+export function onHandshakeVerification(
+    listener: (event: HandshakeVerificationEvent) => void
+): Promise<UnlistenFn> {
+    return listen<HandshakeVerificationEvent>('handshake-verification', (event) => {
+        listener(event.payload)
+    })
+}
+
+/**
+ * 确认或拒绝握手短认证串，拒绝时中止对应的传输
+ * @param taskId 任务ID（HTTP 分享中为加密会话 ID）
+ * @param confirmed 双方短认证串是否一致
+ */
+export async function confirmHandshake(taskId: string, confirmed: boolean): Promise<void> {
+    return invoke('confirm_handshake', { taskId, confirmed })
+}
+
+/**
+ * 获取本地传输的分块大小
+ * @returns 分块大小（字节）
+ */
+export async function getTransferChunkSize(): Promise<number> {
+    return invoke('get_transfer_chunk_size')
+}
+
+/**
+ * 设置本地传输的分块大小（64KB–16MB），只影响之后开始的任务
+ * @param bytes 分块大小（字节）
+ */
+export async function setTransferChunkSize(bytes: number): Promise<void> {
+    return invoke('set_transfer_chunk_size', { bytes })
+}
+
+/**
+ * 获取局域网发送限速
+ * @returns 每秒字节数，null 表示不限速
+ */
+export async function getTransferSpeedLimit(): Promise<number | null> {
+    return invoke<number | null>('get_transfer_speed_limit')
+}
+
+/**
+ * 设置局域网发送限速，由所有发送任务共享，对进行中的任务立即生效
+ * @param bytesPerSec 每秒字节数，null 或 0 表示不限速
+ */
+export async function setTransferSpeedLimit(bytesPerSec: number | null): Promise<void> {
+    return invoke('set_transfer_speed_limit', { bytesPerSec })
+}
+
+/**
+ * 获取准备发送文件时使用的哈希算法
+ * @returns 哈希算法
+ */
+export async function getTransferHashAlgorithm(): Promise<HashAlgorithm> {
+    return invoke('get_transfer_hash_algorithm')
+}
+
+/**
+ * 设置准备发送文件时使用的哈希算法，对端不支持时发送前改用 sha256
+ * @param algorithm 哈希算法
+ */
+export async function setTransferHashAlgorithm(algorithm: HashAlgorithm): Promise<void> {
+    return invoke('set_transfer_hash_algorithm', { algorithm })
+}
+
+/**
+ * 暂停进行中的本地发送，连接保持不断开，超过 60 秒未恢复时转为中断
+ * @param taskId 任务ID
+ */
+export async function pauseTransfer(taskId: string): Promise<void> {
+    return invoke('pause_transfer', { taskId })
+}
+
+/**
+ * 恢复已暂停的本地发送，在原连接上继续
+ * @param taskId 任务ID
+ */
+export async function resumeActiveTransfer(taskId: string): Promise<void> {
+    return invoke('resume_transfer_active', { taskId })
+}
+
+/**
+ * 获取与某设备的传输历史（最新在前）
+ * @param peerId 设备 ID（对端提供指纹时为设备指纹）
+ */
+export async function getPeerHistory(peerId: string): Promise<PeerHistoryEntry[]> {
+    return invoke('get_peer_history', { peerId })
+}
+
+/**
+ * 清除传输历史
+ * @param peerId 设备 ID，不指定时清除全部
+ * @returns 清除的记录数
+ */
+export async function clearPeerHistory(peerId?: string): Promise<number> {
+    return invoke('clear_peer_history', { peerId: peerId ?? null })
+}
+
+/**
+ * 取消所有进行中的传输
+ * @returns 被取消的任务数
+ */
+export async function cancelAllTransfers(): Promise<number> {
+    return invoke('cancel_all_transfers')
+}
+
 /**
  * 获取传输进度
  * @param taskId 任务ID
@@ -109,12 +470,14 @@ export async function getActiveTasks(): Promise<TransferTask[]> {
  * 验证文件完整性
  * @param filePath 文件路径
  * @param expectedHash 期望的哈希值
+ * @param algorithm 期望哈希使用的算法，默认 sha256
  */
 export async function verifyFileIntegrity(
     filePath: string,
-    expectedHash: string
+    expectedHash: string,
+    algorithm?: HashAlgorithm
 ): Promise<boolean> {
-    return invoke('verify_file_integrity', { filePath, expectedHash })
+    return invoke('verify_file_integrity', { filePath, expectedHash, algorithm: algorithm ?? null })
 }
 
 /**
@@ -132,6 +495,7 @@ export async function getNetworkInfo(): Promise<{
     isReceiving: boolean
     port: number
     networkAddresses: string[]
+    networkInterfaces: LocalAddress[]
     shareCode: string
 }> {
     return invoke('get_network_info')
@@ -145,6 +509,7 @@ export async function startReceiving(port?: number): Promise<{
     isReceiving: boolean
     port: number
     networkAddresses: string[]
+    networkInterfaces: LocalAddress[]
     shareCode: string
     autoReceive: boolean
     fileOverwrite: boolean
@@ -159,6 +524,56 @@ export async function stopReceiving(): Promise<void> {
     return invoke('stop_receiving')
 }
 
+/** 接收端口或地址变化事件载荷 */
+export interface ReceivingPortChangedEvent {
+    /** 变化前的端口 */
+    previousPort: number
+    /** 当前监听端口 */
+    port: number
+    /** 当前网络地址列表 */
+    networkAddresses: string[]
+    /** 当前分享码 */
+    shareCode: string
+    /** 变化原因：端口被占用 / 监听器失效后重新绑定 / 本机网络地址变化 */
+    reason: 'port-in-use' | 'rebound' | 'network-changed'
+}
+
+/** 接收监听器失效事件载荷 */
+export interface ReceivingFailedEvent {
+    /** 失效的监听端口 */
+    port: number
+    /** 已尝试的重新绑定次数 */
+    attempts: number
+    /** 最后一次失败的原因 */
+    error: string
+}
+
+/**
+ * 监听接收端口或地址变化（端口回退、重新绑定、网卡变化），用于刷新二维码与分享码
+ * @param listener 监听器函数
+ * @returns 取消监听函数
+ */
+export function onReceivingPortChanged(
+    listener: (event: ReceivingPortChangedEvent) => void
+): Promise<UnlistenFn> {
+    return listen<ReceivingPortChangedEvent>('receiving-port-changed', (event) => {
+        listener(event.payload)
+    })
+}
+
+/**
+ * 监听接收监听器失效事件（重新绑定全部失败后接收服务已停止）
+ * @param listener 监听器函数
+ * @returns 取消监听函数
+ */
+export function onReceivingFailed(
+    listener: (event: ReceivingFailedEvent) => void
+): Promise<UnlistenFn> {
+    return listen<ReceivingFailedEvent>('receiving-failed', (event) => {
+        listener(event.payload)
+    })
+}
+
 /**
  * 获取接收目录
  */
@@ -174,6 +589,22 @@ export async function setReceiveDirectory(directory: string): Promise<void> {
     return invoke('set_receive_directory', { directory })
 }
 
+/**
+ * 列出接收目录中的文件（按修改时间倒序）
+ * @param directory 接收目录，不传则使用默认接收目录
+ */
+export async function listReceivedFiles(directory?: string): Promise<ReceivedFileList> {
+    return invoke('list_received_files', { directory })
+}
+
+/**
+ * 删除接收目录中的文件
+ * @param path 文件路径（必须位于当前接收目录内）
+ */
+export async function deleteReceivedFile(path: string): Promise<void> {
+    return invoke('delete_received_file', { path })
+}
+
 // ============ 事件监听 ============
 
 /** 传输进度事件监听器类型 */
@@ -224,6 +655,99 @@ export function onTransferComplete(
     })
 }
 
+/**
+ * 监听批量发送的整体进度事件
+ * @param listener 监听器函数
+ * @returns 取消监听函数
+ */
+export function onBatchProgress(
+    listener: (progress: BatchProgress) => void
+): Promise<UnlistenFn> {
+    return listen<BatchProgress>('batch-progress', (event) => {
+        listener(event.payload)
+    })
+}
+
+/** 整体发送重试事件载荷 */
+export interface TransferRetryEvent {
+    /** 任务 ID */
+    taskId: string
+    /** 即将进行的重试次数（从 1 开始） */
+    attempt: number
+    /** 最大重试次数 */
+    maxAttempts: number
+    /** 本次重试前的等待时间（毫秒） */
+    delayMs: number
+    /** 上次失败的原因 */
+    error: string
+}
+
+/**
+ * 监听发送重试事件
+ * @param listener 监听器函数
+ * @returns 取消监听函数
+ */
+export function onTransferRetrying(
+    listener: (event: TransferRetryEvent) => void
+): Promise<UnlistenFn> {
+    return listen<TransferRetryEvent>('transfer-retrying', (event) => {
+        listener(event.payload)
+    })
+}
+
+/**
+ * 端到端校验结果事件
+ */
+export interface TransferVerifyEvent {
+    /** 任务 ID */
+    taskId: string
+    /** 校验失败原因（仅校验失败时） */
+    error?: string
+}
+
+/**
+ * 监听发送校验通过事件（接收端回传的文件哈希与本端一致）
+ * @param listener 监听器函数
+ * @returns 取消监听函数
+ */
+export function onTransferVerified(
+    listener: (event: TransferVerifyEvent) => void
+): Promise<UnlistenFn> {
+    return listen<TransferVerifyEvent>('transfer-verified', (event) => {
+        listener(event.payload)
+    })
+}
+
+/**
+ * 监听发送校验失败事件（接收端回传错误、超时或哈希不一致）
+ * @param listener 监听器函数
+ * @returns 取消监听函数
+ */
+export function onTransferVerifyFailed(
+    listener: (event: TransferVerifyEvent) => void
+): Promise<UnlistenFn> {
+    return listen<TransferVerifyEvent>('transfer-verify-failed', (event) => {
+        listener(event.payload)
+    })
+}
+
+/**
+ * 设置发送重试策略
+ * @param attempts 最大重试次数（0-10），0 表示失败后立即结束
+ * @param baseDelay 首次重试前的等待时间（毫秒），之后每次翻倍
+ */
+export async function setTransferRetryPolicy(attempts: number, baseDelay: number): Promise<void> {
+    return invoke('set_transfer_retry_policy', { attempts, baseDelay })
+}
+
+/**
+ * 设置最大重试次数（默认 3 次），沿用当前的重试间隔
+ * @param n 最大重试次数（0-10），0 表示失败后立即结束
+ */
+export async function setTransferMaxRetries(n: number): Promise<void> {
+    return invoke('set_transfer_max_retries', { n })
+}
+
 // ============ 断点续传相关 ============
 
 /** 可恢复任务信息 */
@@ -280,3 +804,42 @@ export function onTransferInterrupted(
         listener(event.payload)
     })
 }
+
+// ============ 设备身份相关 ============
+
+/** 设备身份变更事件载荷 */
+export interface DeviceIdentityChangedPayload {
+    /** 旧指纹 */
+    oldFingerprint: string
+    /** 新指纹 */
+    fingerprint: string
+}
+
+/**
+ * 获取本机设备指纹
+ */
+export async function getDeviceFingerprint(): Promise<string> {
+    return invoke('get_device_fingerprint')
+}
+
+/**
+ * 重新生成设备密钥对
+ * 对端将看到指纹变化，需要重新确认信任
+ * @returns 新的设备指纹
+ */
+export async function regenerateDeviceKeypair(): Promise<string> {
+    return invoke('regenerate_device_keypair')
+}
+
+/**
+ * 监听设备身份变更事件
+ * @param listener 监听器函数
+ * @returns 取消监听函数
+ */
+export function onDeviceIdentityChanged(
+    listener: (payload: DeviceIdentityChangedPayload) => void
+): Promise<UnlistenFn> {
+    return listen<DeviceIdentityChangedPayload>('device-identity-changed', (event) => {
+        listener(event.payload)
+    })
+}
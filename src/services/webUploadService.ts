@@ -10,6 +10,9 @@ import type {
     WebUploadFileStartEvent,
     WebUploadFileProgressEvent,
     WebUploadFileCompleteEvent,
+    UploadTempCleanup,
+    UploadFilters,
+    PageOptions,
 } from '../types'
 
 /**
@@ -40,10 +43,22 @@ export async function stopWebUpload(): Promise<void> {
 }
 
 /**
- * 获取 Web 上传请求列表
+ * 获取 Web 上传请求列表（按请求时间从新到旧）
+ * @param page 分页参数
  */
-export async function getWebUploadRequests(): Promise<WebUploadRequest[]> {
-    return invoke('get_web_upload_requests')
+export async function getWebUploadRequests(page?: PageOptions): Promise<WebUploadRequest[]> {
+    return invoke('get_web_upload_requests', {
+        offset: page?.offset ?? null,
+        limit: page?.limit ?? null,
+    })
+}
+
+/**
+ * 清除已结束的上传文件记录（进行中的记录保留）
+ * @returns 清除的记录数
+ */
+export async function clearCompletedUploadRecords(): Promise<number> {
+    return invoke('clear_completed_upload_records')
 }
 
 /**
@@ -62,6 +77,56 @@ export async function rejectWebUpload(requestId: string): Promise<void> {
     return invoke('reject_web_upload', { requestId })
 }
 
+/**
+ * 取消进行中的 Web 上传
+ * @param requestId 请求 ID
+ * @param recordId 文件记录 ID，不指定时取消该请求下所有进行中的上传
+ * @returns 取消的上传数量
+ */
+export async function cancelWebUpload(requestId: string, recordId?: string): Promise<number> {
+    return invoke('cancel_web_upload', { requestId, recordId: recordId ?? null })
+}
+
+/**
+ * 获取接收目录所在磁盘的可用空间，用于空间不足时提前提醒
+ * @param directory 接收目录，默认为当前 Web 上传的接收目录
+ * @returns 可用空间（字节）
+ */
+export async function getReceiveDirectoryFreeSpace(directory?: string): Promise<number> {
+    return invoke('get_receive_directory_free_space', { directory: directory ?? null })
+}
+
+/**
+ * 获取 Web 上传文件过滤规则
+ * @returns 当前过滤规则
+ */
+export async function getWebUploadFilters(): Promise<UploadFilters> {
+    return invoke('get_web_upload_filters')
+}
+
+/**
+ * 设置 Web 上传文件过滤规则，扩展名不区分大小写，支持 tar.gz 这类复合扩展名
+ * @param allowedExt 允许的扩展名，为空表示不限制
+ * @param blockedExt 禁止的扩展名
+ * @param maxFileSize 单个文件大小上限（字节），null 表示不限制
+ * @returns 规范化后的过滤规则
+ */
+export async function setWebUploadFilters(
+    allowedExt: string[],
+    blockedExt: string[],
+    maxFileSize: number | null
+): Promise<UploadFilters> {
+    return invoke('set_web_upload_filters', { allowedExt, blockedExt, maxFileSize })
+}
+
+/**
+ * 清理上传临时目录中废弃的分块数据
+ * @param directory 接收目录，默认为当前 Web 上传的接收目录
+ */
+export async function cleanupWebUploadTemp(directory?: string): Promise<UploadTempCleanup> {
+    return invoke('cleanup_web_upload_temp', { directory: directory ?? null })
+}
+
 // ============ 事件监听 ============
 
 /**
@@ -483,9 +483,11 @@ export const useShareStore = defineStore('share', () => {
     /**
      * 开始分享
      * @param files 要分享的文件列表
+     * @param bindAddress 只在该本机网卡地址上监听
      */
     async function startShare(
-        files: FileMetadata[]
+        files: FileMetadata[],
+        bindAddress?: string
     ): Promise<ShareLinkInfo | null> {
         loading.value = true
         error.value = ''
@@ -497,7 +499,9 @@ export const useShareStore = defineStore('share', () => {
             const result = await startShareService(
                 files,
                 settings.value,
-                preferredPort
+                preferredPort,
+                undefined,
+                bindAddress
             )
             shareInfo.value = result
 
@@ -26,10 +26,18 @@ export interface FileMetadata {
     mimeType: string
     /** 文件哈希（用于校验） */
     hash: string
+    /** 文件及分块哈希使用的算法，缺省为 sha256 */
+    hashAlgorithm?: string
     /** 分块信息 */
     chunks: ChunkInfo[]
     /** 文件路径（发送时为源路径，接收时为目标路径） */
     path?: string
+    /** 随文件发送的附言 */
+    note?: string
+    /** 源文件修改时间（毫秒），接收端据此恢复保存文件的修改时间 */
+    modifiedAt?: number
+    /** 文件在所选文件夹内的相对路径（含文件名），接收端据此重建子目录 */
+    relativePath?: string
 }
 
 /** 根据文件扩展名推断 MIME 类型 */
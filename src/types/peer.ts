@@ -8,6 +8,9 @@ export type DeviceType = 'desktop' | 'mobile' | 'web' | 'unknown'
 /** 设备状态 */
 export type PeerStatus = 'available' | 'busy' | 'offline'
 
+/** 设备发现来源 */
+export type DiscoverySource = 'mdns' | 'broadcast' | 'manual' | 'scan'
+
 /** 设备事件类型 */
 export type PeerEventType = 'discovered' | 'updated' | 'offline'
 
@@ -29,6 +32,12 @@ export interface PeerInfo {
     lastSeen: number
     /** 设备状态 */
     status: PeerStatus
+    /** 发现来源 */
+    source: DiscoverySource
+    /** 设备指纹（广播信标携带） */
+    fingerprint?: string | null
+    /** 是否已收藏 */
+    pinned: boolean
 }
 
 /** 设备发现事件 */
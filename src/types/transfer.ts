@@ -15,6 +15,7 @@ export type TaskStatus =
     | 'failed'
     | 'cancelled'
     | 'interrupted'
+    | 'paused'
 
 /** 传输方向 */
 export type TransferDirection = 'send' | 'receive'
@@ -91,6 +92,195 @@ export interface TransferTask {
     encrypted?: boolean
     /** 压缩率（0-100，仅在启用压缩时有效） */
     compressionRatio?: number
+    /** 本次传输的加密设置（为空时使用全局设置） */
+    encryptionOverride?: boolean
+    /** 本次传输的压缩设置（为空时使用全局设置） */
+    compressionOverride?: boolean
+    /** 多设备发送的分组 ID（同一文件发往多个设备的任务共享） */
+    groupId?: string
+}
+
+/** 已接收文件信息 */
+export interface ReceivedFileInfo {
+    /** 文件路径 */
+    path: string
+    /** 文件名 */
+    name: string
+    /** 相对接收目录的路径 */
+    relativePath: string
+    /** 文件大小 */
+    size: number
+    /** 修改时间戳（毫秒） */
+    modifiedAt: number
+    /** MIME 类型 */
+    mimeType: string
+}
+
+/** 已接收文件列表 */
+export interface ReceivedFileList {
+    /** 接收目录 */
+    directory: string
+    /** 文件列表（按修改时间倒序） */
+    files: ReceivedFileInfo[]
+    /** 文件总大小（字节） */
+    totalSize: number
+}
+
+/** 文件名问题 */
+export type FilenameIssue =
+    | 'empty'
+    | 'illegal_character'
+    | 'control_character'
+    | 'reserved_name'
+    | 'trailing_dot_or_space'
+
+/** 文件名检查结果 */
+export interface FilenameValidation {
+    /** 原始文件名 */
+    originalName: string
+    /** 处理后的安全文件名 */
+    sanitizedName: string
+    /** 检测到的问题（为空表示无需处理） */
+    issues: FilenameIssue[]
+}
+
+/** 本机网卡地址 */
+export interface LocalAddress {
+    /** IP 地址 */
+    address: string
+    /** 网卡名称 */
+    interface: string
+    /** 地址族 */
+    family: 'v4' | 'v6'
+    /** 地址作用域 */
+    scope: 'loopback' | 'link_local' | 'private' | 'public'
+    /** 是否为虚拟网卡（容器、虚拟机、VPN 隧道等） */
+    isVirtual: boolean
+    /** 其他设备能否通过该地址访问本机的推测 */
+    reachability: 'likely' | 'possible' | 'unlikely'
+}
+
+/** 设备传输历史记录 */
+export interface PeerHistoryEntry {
+    /** 任务 ID */
+    taskId: string
+    /** 文件名 */
+    fileName: string
+    /** 文件大小（字节） */
+    fileSize: number
+    /** 传输方向 */
+    direction: TransferDirection
+    /** 最终状态 */
+    status: TaskStatus
+    /** 结束时间（毫秒） */
+    timestamp: number
+}
+
+/** 可选协议特性 */
+export type ProtocolFeature =
+    | 'encryption'
+    | 'compression'
+    | 'resume'
+    | 'cipher-negotiation'
+    | 'receive-capacity'
+
+/** 对端连接测试结果 */
+export interface ConnectionTestResult {
+    /** 端口是否可连接 */
+    reachable: boolean
+    /** 是否为 PureSend 传输端点（完成了握手） */
+    isPuresend: boolean
+    /** 建立 TCP 连接耗时（毫秒） */
+    latencyMs?: number
+    /** 对方协议版本 */
+    protocolVersion?: number
+    /** 对方是否启用加密 */
+    encryption?: boolean
+    /** 对方是否启用压缩 */
+    compression?: boolean
+    /** 对方是否支持断点续传 */
+    resume?: boolean
+    /** 对方选定的加密算法 */
+    cipher?: string
+    /** 对方启用的可选协议特性（旧版本对端不提供） */
+    features?: ProtocolFeature[]
+    /** 失败原因 */
+    error?: string
+}
+
+/** 加密会话校验结果（调试用） */
+export interface SessionVerificationResult {
+    /** 是否完成握手 */
+    handshakeCompleted: boolean
+    /** 是否协商使用加密 */
+    encryption: boolean
+    /** 是否协商使用压缩 */
+    compression: boolean
+    /** 协商的加密算法 */
+    cipher?: string
+    /** 双方都启用的可选协议特性（旧版本对端不提供） */
+    features?: ProtocolFeature[]
+    /** 双方派生的会话密钥是否一致，对端不支持密钥确认时为空 */
+    keyMatch?: boolean
+    /** 本端会话指纹（密钥确认值前 16 位），可与对端显示的值人工比对 */
+    sessionFingerprint?: string
+    /** 失败原因 */
+    error?: string
+}
+
+/** 云服务提供商 */
+export type CloudProvider = 'aliyun_oss' | 'tencent_cos' | 'qiniu' | 'aws_s3' | 'unknown'
+
+/** 云盘传输配置 */
+export interface CloudTransportConfig {
+    /** 云服务提供商 */
+    provider: CloudProvider
+    /** 访问密钥 */
+    accessKey: string
+    /** 秘密密钥，为空时沿用已保存的密钥 */
+    secretKey: string
+    /** 存储桶名称 */
+    bucket: string
+    /** 区域 */
+    region: string
+    /** 自定义服务地址（如自建 MinIO），为空时按提供商和区域推导 */
+    endpoint?: string
+}
+
+/** 云盘配置摘要（不含秘密密钥） */
+export interface CloudConfigSummary {
+    /** 云服务提供商 */
+    provider: CloudProvider
+    /** 访问密钥 */
+    accessKey: string
+    /** 存储桶名称 */
+    bucket: string
+    /** 区域 */
+    region: string
+    /** 自定义服务地址 */
+    endpoint?: string
+    /** 是否已保存秘密密钥 */
+    hasSecretKey: boolean
+}
+
+/** 云盘中已上传完成的文件 */
+export interface CloudFileSummary {
+    /** 下载密钥 */
+    key: string
+    /** 文件名 */
+    fileName: string
+    /** 文件大小 */
+    size: number
+    /** 上传完成时间（毫秒） */
+    uploadedAt: number
+}
+
+/** 单次传输的加密/压缩覆盖设置 */
+export interface TransferOverrides {
+    /** 是否加密 */
+    encryption?: boolean
+    /** 是否压缩 */
+    compression?: boolean
 }
 
 /** 传输进度事件 */
@@ -111,8 +301,59 @@ export interface TransferProgress {
     estimatedTimeRemaining?: number
     /** 错误信息 */
     error?: string
+    /** 是否停滞（超过阈值未收到对方确认） */
+    isStalled?: boolean
+    /** 距上次进度更新的秒数 */
+    secondsSinceProgress?: number
+    /** 接收完成后文件是否经过校验（接收端校验关闭时为 false） */
+    verified?: boolean
+    /** 多设备发送的分组 ID */
+    groupId?: string
+    /** 云盘发送完成后的分享链接 */
+    shareUrl?: string
 }
 
+/** 多设备发送的目标设备 */
+export interface SendTarget {
+    /** 设备 ID */
+    peerId: string
+    /** 设备 IP */
+    peerIp: string
+    /** 设备端口 */
+    peerPort: number
+}
+
+/** 多设备或批量发送的任务分组 */
+export interface SendGroup {
+    /** 分组 ID，各任务的进度事件均携带该 ID（批量发送时同时为父任务 ID） */
+    groupId: string
+    /** 各任务的 ID（与请求中的设备或文件顺序一致） */
+    taskIds: string[]
+}
+
+/** 批量发送的进度汇总 */
+export interface BatchProgress {
+    /** 分组 ID */
+    groupId: string
+    /** 文件总数 */
+    totalFiles: number
+    /** 已完成的文件数 */
+    completedFiles: number
+    /** 失败或取消的文件数 */
+    failedFiles: number
+    /** 已传输字节数 */
+    transferredBytes: number
+    /** 总字节数（失败或取消的文件只计已传输部分） */
+    totalBytes: number
+    /** 进度百分比（0-100） */
+    progress: number
+    /** 所有文件是否都已结束 */
+    finished: boolean
+}
+
+/** 接收端完整性校验模式 */
+export type VerifyMode = 'off' | 'per_chunk' | 'full_file' | 'both'
+
 /** 获取状态显示文本 */
 export function getStatusText(status: TaskStatus): string {
     const statusTexts: Record<TaskStatus, string> = {
@@ -122,6 +363,7 @@ export function getStatusText(status: TaskStatus): string {
         failed: '失败',
         cancelled: '已取消',
         interrupted: '已中断',
+        paused: '已暂停',
     }
     return statusTexts[status]
 }
@@ -164,6 +406,8 @@ export function calculateProgress(transferred: number, total: number): number {
 
 /** 分享链接信息 */
 export interface ShareLinkInfo {
+    /** 分享 ID（同时运行多个分享时用于区分） */
+    shareId: string
     /** 分享链接列表 */
     links: string[]
     /** HTTP 服务器端口 */
@@ -180,6 +424,12 @@ export interface ShareLinkInfo {
     autoAccept: boolean
     /** 分享状态 */
     status: ShareStatus
+    /** 当前进行中的下载数 */
+    activeDownloads: number
+    /** 自动到期时间戳（毫秒），未设置时不会自动到期 */
+    expiresAt?: number
+    /** 距离到期的剩余秒数 */
+    remainingSeconds?: number
 }
 
 /** 分享状态 */
@@ -390,12 +640,32 @@ export type WebUploadRequestStatus =
     | 'rejected'
     | 'expired'
 
+/** 超出保留上限后移出列表的记录汇总 */
+export interface ArchivedRecordSummary {
+    /** 记录数 */
+    count: number
+    /** 其中已完成的记录数 */
+    completed: number
+    /** 已传输字节数合计 */
+    transferredBytes: number
+}
+
+/** 分页参数 */
+export interface PageOptions {
+    /** 跳过的条数 */
+    offset?: number
+    /** 最多返回的条数 */
+    limit?: number
+}
+
 /** Web 上传文件记录（对应后端 UploadRecord） */
 export interface WebUploadRecord {
     /** 记录唯一 ID */
     id: string
-    /** 文件名 */
+    /** 文件名（已按接收端规则处理） */
     fileName: string
+    /** 客户端提供的原始文件名，仅在与处理后的文件名不同时存在 */
+    originalFileName?: string
     /** 已上传字节数 */
     uploadedBytes: number
     /** 总字节数 */
@@ -424,8 +694,10 @@ export interface WebUploadRequest {
     createdAt: number
     /** 上传方 User-Agent */
     userAgent?: string
-    /** 该 IP 下的所有上传文件记录 */
+    /** 该 IP 下的上传文件记录（最早在前） */
     uploadRecords: WebUploadRecord[]
+    /** 超出保留上限后移出列表的记录汇总 */
+    archivedRecords: ArchivedRecordSummary
 }
 
 /** Web 上传文件开始事件 */
@@ -482,6 +754,26 @@ export interface WebUploadInfo {
     port: number
     /** 上传链接列表 */
     urls: string[]
+    /** 是否沿用了请求的端口（为 false 时之前分享的链接已失效） */
+    portReused: boolean
+}
+
+/** 上传临时目录清理结果 */
+export interface UploadTempCleanup {
+    /** 删除的分块目录和残留文件数量 */
+    removedEntries: number
+    /** 释放的字节数 */
+    freedBytes: number
+}
+
+/** Web 上传文件过滤规则 */
+export interface UploadFilters {
+    /** 允许的扩展名（小写、不含前导点），为空表示不限制 */
+    allowedExtensions: string[]
+    /** 禁止的扩展名，优先于允许列表 */
+    blockedExtensions: string[]
+    /** 单个文件大小上限（字节），null 表示不限制 */
+    maxFileSize: number | null
 }
 
 /** 访问请求 */
@@ -502,8 +794,10 @@ export interface AccessRequest {
     lockedUntil?: number
     /** 用户代理（浏览器/平台信息，如 "Chrome(Android)"） */
     userAgent?: string
-    /** 上传记录列表 */
+    /** 上传记录列表（最新在前） */
     uploadRecords: UploadRecord[]
+    /** 超出保留上限后移出列表的记录汇总 */
+    archivedRecords: ArchivedRecordSummary
 }
 
 /** 分享设置 */
@@ -514,6 +808,18 @@ export interface ShareSettings {
     pin?: string
     /** 是否自动接受所有访问请求 */
     autoAccept: boolean
+    /** 自动接受截止时间戳（毫秒），为空表示一直有效 */
+    autoAcceptUntil?: number
+    /** 同时进行的最大下载数，为空或 0 表示不限制 */
+    maxConcurrentDownloads?: number
+    /** 允许在浏览器内预览的扩展名（不含点），为空时使用默认列表（图片、纯文本、PDF） */
+    previewExtensions?: string[]
+    /** 访问获准（请求被接受或 PIN 验证通过）前隐藏服务能力等细节 */
+    hideDetailsBeforeAccess?: boolean
+    /** 同一 IP 的请求被拒绝达到该次数后，本次分享内自动拒绝其后续请求；为空或 0 表示不限制 */
+    maxRejections?: number
+    /** 每个文件允许完整下载的次数，用尽后拒绝该文件的下载；为空或 0 表示不限制 */
+    maxDownloadsPerFile?: number
 }
 
 /** PIN 验证结果 */
@@ -38,6 +38,7 @@ export function getStatusColor(status: string): string {
         failed: 'error',
         cancelled: 'warning',
         interrupted: 'warning',
+        paused: 'info',
     }
     return colorMap[status] || 'grey'
 }